@@ -31,7 +31,6 @@
 //! });
 //! ```
 
-
 extern crate alloc;
 
 use alloc::vec::Vec;
@@ -60,6 +59,8 @@ pub enum ActionRequest {
     WithFirstPBlock(ActionRequestWithFirstPBlock),
     /// ACTION-Request-With-List-And-First-PBlock: Multiple methods with block transfer (choice 0x05)
     WithListAndFirstPBlock(ActionRequestWithListAndFirstPBlock),
+    /// ACTION-Request-With-PBlock: Continue sending method parameters in blocks (choice 0x06)
+    WithPBlock(ActionRequestWithPBlock),
 }
 
 /// ACTION-Request-Normal: Invoke a single COSEM method
@@ -142,6 +143,19 @@ pub struct ActionRequestWithListAndFirstPBlock {
     pub pblock: DataBlockSa,
 }
 
+/// ACTION-Request-With-PBlock: Next block of method parameters
+///
+/// Sent after the server acknowledged the previous block with
+/// ACTION-Response-Next-PBlock.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ActionRequestWithPBlock {
+    /// Invoke ID and priority (1 byte)
+    pub invoke_id: u8,
+    /// Next block of method parameters
+    pub pblock: DataBlockSa,
+}
+
 /// Method descriptor for ACTION-Request-With-List
 ///
 /// Identifies a method to invoke and its parameters.
@@ -392,6 +406,13 @@ impl ActionRequest {
                 buffer.push_u32(req.pblock.block_number);
                 buffer.extend(&req.pblock.raw_data);
             }
+            ActionRequest::WithPBlock(req) => {
+                buffer.push(0x06); // WithPBlock choice
+                buffer.push(req.invoke_id);
+                buffer.push(req.pblock.last_block as u8);
+                buffer.push_u32(req.pblock.block_number);
+                buffer.extend(&req.pblock.raw_data);
+            }
         }
 
         buffer
@@ -712,6 +733,22 @@ impl ActionRequest {
                     }),
                 ))
             }
+            0x06 => {
+                // WithPBlock
+                let (input, invoke_id) = nom_u8(input)?;
+                let (input, last_block) = nom_u8(input)?;
+                let (input, block_number) = be_u32(input)?;
+
+                let raw_data = input.to_vec();
+
+                Ok((
+                    &[],
+                    ActionRequest::WithPBlock(ActionRequestWithPBlock {
+                        invoke_id,
+                        pblock: DataBlockSa { last_block: last_block != 0, block_number, raw_data },
+                    }),
+                ))
+            }
             _ => Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Switch))),
         }
     }
@@ -1232,6 +1269,22 @@ mod tests {
         assert_eq!(parsed, request);
     }
 
+    #[test]
+    fn test_action_request_with_pblock() {
+        let request = ActionRequest::WithPBlock(ActionRequestWithPBlock {
+            invoke_id: 0x13,
+            pblock: DataBlockSa { last_block: true, block_number: 2, raw_data: vec![0x0F, 0x01] },
+        });
+
+        let encoded = request.encode();
+        assert_eq!(encoded, vec![0xC3, 0x06, 0x13, 0x01, 0x00, 0x00, 0x00, 0x02, 0x0F, 0x01]);
+
+        // Round-trip test
+        let (remaining, parsed) = ActionRequest::parse(&encoded).unwrap();
+        assert!(remaining.is_empty());
+        assert_eq!(parsed, request);
+    }
+
     #[test]
     fn test_action_response_with_pblock() {
         let response = ActionResponse::WithPBlock(ActionResponseWithPBlock {
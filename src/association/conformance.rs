@@ -187,6 +187,12 @@ impl Conformance {
     }
 }
 
+impl Default for Conformance {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
 impl core::ops::BitOr for Conformance {
     type Output = Self;
 
@@ -0,0 +1,133 @@
+//! DLMS/COSEM meter simulator
+//!
+//! Serves a simulated meter described in JSON over TCP:
+//!
+//! ```text
+//! dlms-sim meter.json --listen 0.0.0.0:4059 --framing hdlc --speed 60
+//! ```
+//!
+//! See [`dlms_cosem::server::sim`] for the description format.
+
+use std::net::TcpListener;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use dlms_cosem::server::sim::{Framing, Simulator, serve};
+
+const USAGE: &str = "\
+Usage: dlms-sim <description.json> [options]
+
+Options:
+  --listen <address>    Address to listen on (default 0.0.0.0:4059)
+  --framing <framing>   wrapper, hdlc or raw (default wrapper)
+  --speed <factor>      Simulated seconds per real second (default 1)
+  --seed <seed>         Seed of the value generators
+  -h, --help            Print this help";
+
+/// Interval between simulation ticks, in real time.
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Options {
+    description: String,
+    listen: String,
+    framing: Framing,
+    speed: u32,
+    seed: Option<u64>,
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut description = None;
+    let mut options = Options {
+        description: String::new(),
+        listen: "0.0.0.0:4059".into(),
+        framing: Framing::Wrapper,
+        speed: 1,
+        seed: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "--listen" => options.listen = value("--listen")?,
+            "--framing" => options.framing = value("--framing")?.parse()?,
+            "--speed" => {
+                options.speed = value("--speed")?.parse().map_err(|_| "invalid --speed")?;
+            }
+            "--seed" => {
+                options.seed = Some(value("--seed")?.parse().map_err(|_| "invalid --seed")?);
+            }
+            "-h" | "--help" => return Err(USAGE.into()),
+            _ if arg.starts_with('-') => {
+                return Err(format!("unknown option {}\n\n{}", arg, USAGE));
+            }
+            _ if description.is_none() => description = Some(arg),
+            _ => return Err(format!("unexpected argument {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    options.description = description.ok_or(USAGE)?;
+    Ok(options)
+}
+
+fn unix_time() -> u32 {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_secs());
+    u32::try_from(seconds).unwrap_or(u32::MAX)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut simulator = match Simulator::load(&options.description) {
+        Ok(simulator) => simulator,
+        Err(error) => {
+            eprintln!("{}: {}", options.description, error);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(seed) = options.seed {
+        simulator = simulator.with_seed(seed);
+    }
+
+    let start = unix_time();
+    simulator.tick(start);
+    let simulator = Arc::new(Mutex::new(simulator));
+
+    let ticking = Arc::clone(&simulator);
+    let speed = options.speed;
+    thread::spawn(move || {
+        loop {
+            thread::sleep(TICK_INTERVAL);
+            let elapsed = unix_time().saturating_sub(start);
+            let now = start.saturating_add(elapsed.saturating_mul(speed));
+            ticking.lock().unwrap_or_else(PoisonError::into_inner).tick(now);
+        }
+    });
+
+    let listener = match TcpListener::bind(&options.listen) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("cannot listen on {}: {}", options.listen, error);
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!(
+        "serving {} on {} ({:?} framing)",
+        options.description, options.listen, options.framing
+    );
+
+    match serve(listener, simulator, options.framing) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,455 @@
+//! Time-of-use calendars of the Activity Calendar (class 20)
+//!
+//! A calendar is made of:
+//! - **Season profiles**: from `season_start` on, the season uses a week profile
+//! - **Week profiles**: the day profile of each day of the week, Monday first
+//! - **Day profiles**: switch times, each executing a script of a Script Table
+//!
+//! These types are shared by the client, which programs the passive calendar
+//! of a meter, and the [`ActivityCalendar`](crate::cosem::activity_calendar)
+//! object of the server. [`Calendar::validate`] checks the references between
+//! the profiles before a calendar is sent or activated.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use dlms_cosem::calendar::{Calendar, CalendarError, DayProfile};
+//! use dlms_cosem::{Date, DateTime, ObisCode, Time};
+//!
+//! let tariff_script = ObisCode::new(0, 0, 10, 0, 100, 255);
+//! let winter = DateTime::new(
+//!     Date::new(0xFFFF, 10, 1, 0xFF),
+//!     Time::new(Some(0), Some(0), Some(0), Some(0)),
+//!     None,
+//!     None,
+//! );
+//! let at = |hour| Time::new(Some(hour), Some(0), Some(0), Some(0));
+//!
+//! let calendar = Calendar::new(b"TOU-2025".to_vec())
+//!     .with_season(b"WINTER".to_vec(), winter, b"WEEK".to_vec())
+//!     .with_week(b"WEEK".to_vec(), [1, 1, 1, 1, 1, 2, 2])
+//!     .with_day(
+//!         DayProfile::new(1)
+//!             .with_action(at(0), tariff_script, 1)
+//!             .with_action(at(7), tariff_script, 2),
+//!     )
+//!     .with_day(DayProfile::new(2).with_action(at(0), tariff_script, 1));
+//! assert_eq!(calendar.validate(), Ok(()));
+//!
+//! let dangling = calendar.clone().with_week(b"HOLIDAYS".to_vec(), [3; 7]);
+//! assert_eq!(dangling.validate(), Err(CalendarError::UnknownDayProfile(3)));
+//! ```
+
+use core::fmt;
+
+use crate::get::DataAccessResult;
+use crate::{Data, DateTime, ObisCode, Time};
+
+#[cfg(all(not(feature = "std"), feature = "encode"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// One season of a calendar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SeasonProfile {
+    /// Name of the season
+    pub season_profile_name: Vec<u8>,
+    /// Start of the season; a wildcard year repeats it every year
+    pub season_start: DateTime,
+    /// Week profile used during the season
+    pub week_name: Vec<u8>,
+}
+
+/// Day profiles of the days of a week.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct WeekProfile {
+    /// Name of the week profile
+    pub week_profile_name: Vec<u8>,
+    /// Day profile ids from Monday to Sunday
+    pub day_ids: [u8; 7],
+}
+
+/// One switch time of a day profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DayProfileAction {
+    /// Time of day the script is executed
+    pub start_time: Time,
+    /// Logical name of the Script Table
+    pub script_logical_name: ObisCode,
+    /// Script of the Script Table to execute
+    pub script_selector: u16,
+}
+
+/// Switch times of a day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DayProfile {
+    /// Id of the day profile, referenced by week profiles
+    pub day_id: u8,
+    /// Switch times, in ascending order of start time
+    pub day_schedule: Vec<DayProfileAction>,
+}
+
+impl DayProfile {
+    /// Creates a day profile without switch times.
+    pub fn new(day_id: u8) -> Self {
+        Self { day_id, day_schedule: Vec::new() }
+    }
+
+    /// Adds a switch time (builder style).
+    pub fn with_action(
+        mut self,
+        start_time: Time,
+        script_logical_name: ObisCode,
+        script_selector: u16,
+    ) -> Self {
+        self.day_schedule.push(DayProfileAction {
+            start_time,
+            script_logical_name,
+            script_selector,
+        });
+        self
+    }
+}
+
+/// Inconsistencies found by [`Calendar::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CalendarError {
+    /// A season references a week profile that does not exist.
+    UnknownWeekProfile(Vec<u8>),
+    /// A week profile references a day profile that does not exist.
+    UnknownDayProfile(u8),
+    /// Two week profiles have the same name.
+    DuplicateWeekProfile(Vec<u8>),
+    /// Two day profiles have the same id.
+    DuplicateDayProfile(u8),
+    /// The switch times of a day profile are not in ascending order.
+    UnsortedSwitchTimes(u8),
+}
+
+impl fmt::Display for CalendarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalendarError::UnknownWeekProfile(name) => write!(f, "Unknown week profile {:?}", name),
+            CalendarError::UnknownDayProfile(day_id) => write!(f, "Unknown day profile {}", day_id),
+            CalendarError::DuplicateWeekProfile(name) => {
+                write!(f, "Duplicate week profile {:?}", name)
+            }
+            CalendarError::DuplicateDayProfile(day_id) => {
+                write!(f, "Duplicate day profile {}", day_id)
+            }
+            CalendarError::UnsortedSwitchTimes(day_id) => {
+                write!(f, "Switch times of day profile {} are not in ascending order", day_id)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CalendarError {}
+
+/// A complete calendar: name, season profiles, week and day profile tables.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Calendar {
+    /// Name of the calendar
+    pub calendar_name: Vec<u8>,
+    /// Season profiles
+    pub season_profile: Vec<SeasonProfile>,
+    /// Week profile table
+    pub week_profile_table: Vec<WeekProfile>,
+    /// Day profile table
+    pub day_profile_table: Vec<DayProfile>,
+}
+
+impl Calendar {
+    /// Creates an empty calendar.
+    pub fn new(calendar_name: Vec<u8>) -> Self {
+        Self { calendar_name, ..Default::default() }
+    }
+
+    /// Adds a season profile (builder style).
+    pub fn with_season(
+        mut self,
+        season_profile_name: Vec<u8>,
+        season_start: DateTime,
+        week_name: Vec<u8>,
+    ) -> Self {
+        self.season_profile.push(SeasonProfile { season_profile_name, season_start, week_name });
+        self
+    }
+
+    /// Adds a week profile, day profile ids from Monday to Sunday (builder style).
+    pub fn with_week(mut self, week_profile_name: Vec<u8>, day_ids: [u8; 7]) -> Self {
+        self.week_profile_table.push(WeekProfile { week_profile_name, day_ids });
+        self
+    }
+
+    /// Adds a day profile (builder style).
+    pub fn with_day(mut self, day_profile: DayProfile) -> Self {
+        self.day_profile_table.push(day_profile);
+        self
+    }
+
+    /// Returns the week profile with a name.
+    pub fn week_profile(&self, week_profile_name: &[u8]) -> Option<&WeekProfile> {
+        self.week_profile_table.iter().find(|week| week.week_profile_name == week_profile_name)
+    }
+
+    /// Returns the day profile with an id.
+    pub fn day_profile(&self, day_id: u8) -> Option<&DayProfile> {
+        self.day_profile_table.iter().find(|day| day.day_id == day_id)
+    }
+
+    /// Checks that every referenced week and day profile exists, that names
+    /// and ids are unique and that switch times are in ascending order.
+    pub fn validate(&self) -> Result<(), CalendarError> {
+        for (index, week) in self.week_profile_table.iter().enumerate() {
+            if self.week_profile_table[..index]
+                .iter()
+                .any(|other| other.week_profile_name == week.week_profile_name)
+            {
+                return Err(CalendarError::DuplicateWeekProfile(week.week_profile_name.clone()));
+            }
+            if let Some(day_id) =
+                week.day_ids.iter().find(|day_id| self.day_profile(**day_id).is_none())
+            {
+                return Err(CalendarError::UnknownDayProfile(*day_id));
+            }
+        }
+        for (index, day) in self.day_profile_table.iter().enumerate() {
+            if self.day_profile_table[..index].iter().any(|other| other.day_id == day.day_id) {
+                return Err(CalendarError::DuplicateDayProfile(day.day_id));
+            }
+            let sorted = day.day_schedule.windows(2).all(|actions| {
+                seconds_of_day(&actions[0].start_time) < seconds_of_day(&actions[1].start_time)
+            });
+            if !sorted {
+                return Err(CalendarError::UnsortedSwitchTimes(day.day_id));
+            }
+        }
+        match self
+            .season_profile
+            .iter()
+            .find(|season| self.week_profile(&season.week_name).is_none())
+        {
+            Some(season) => Err(CalendarError::UnknownWeekProfile(season.week_name.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Encodes the season profiles (attributes 3 and 7).
+    #[cfg(feature = "encode")]
+    pub fn season_profile_data(&self) -> Data {
+        Data::Structure(
+            self.season_profile
+                .iter()
+                .map(|season| {
+                    Data::Structure(vec![
+                        Data::OctetString(season.season_profile_name.clone()),
+                        Data::DateTime(season.season_start.clone()),
+                        Data::OctetString(season.week_name.clone()),
+                    ])
+                })
+                .collect(),
+        )
+    }
+
+    /// Encodes the week profile table (attributes 4 and 8).
+    #[cfg(feature = "encode")]
+    pub fn week_profile_table_data(&self) -> Data {
+        Data::Structure(
+            self.week_profile_table
+                .iter()
+                .map(|week| {
+                    let mut elements = vec![Data::OctetString(week.week_profile_name.clone())];
+                    elements.extend(week.day_ids.iter().map(|day_id| Data::Unsigned(*day_id)));
+                    Data::Structure(elements)
+                })
+                .collect(),
+        )
+    }
+
+    /// Encodes the day profile table (attributes 5 and 9).
+    #[cfg(feature = "encode")]
+    pub fn day_profile_table_data(&self) -> Data {
+        Data::Structure(
+            self.day_profile_table
+                .iter()
+                .map(|day| {
+                    let actions = day
+                        .day_schedule
+                        .iter()
+                        .map(|action| {
+                            Data::Structure(vec![
+                                Data::Time(action.start_time.clone()),
+                                Data::OctetString(action.script_logical_name.encode().to_vec()),
+                                Data::LongUnsigned(action.script_selector),
+                            ])
+                        })
+                        .collect();
+                    Data::Structure(vec![Data::Unsigned(day.day_id), Data::Structure(actions)])
+                })
+                .collect(),
+        )
+    }
+
+    /// Parses season profiles (attributes 3 and 7).
+    pub fn parse_season_profile(data: &Data) -> Result<Vec<SeasonProfile>, DataAccessResult> {
+        elements(data)?
+            .iter()
+            .map(|season| match elements(season)? {
+                [
+                    Data::OctetString(season_profile_name),
+                    Data::DateTime(season_start),
+                    Data::OctetString(week_name),
+                ] => Ok(SeasonProfile {
+                    season_profile_name: season_profile_name.clone(),
+                    season_start: season_start.clone(),
+                    week_name: week_name.clone(),
+                }),
+                _ => Err(DataAccessResult::TypeUnmatched),
+            })
+            .collect()
+    }
+
+    /// Parses a week profile table (attributes 4 and 8).
+    pub fn parse_week_profile_table(data: &Data) -> Result<Vec<WeekProfile>, DataAccessResult> {
+        elements(data)?
+            .iter()
+            .map(|week| match elements(week)? {
+                [Data::OctetString(week_profile_name), days @ ..] if days.len() == 7 => {
+                    let mut day_ids = [0; 7];
+                    for (day_id, day) in day_ids.iter_mut().zip(days) {
+                        match day {
+                            Data::Unsigned(id) => *day_id = *id,
+                            _ => return Err(DataAccessResult::TypeUnmatched),
+                        }
+                    }
+                    Ok(WeekProfile { week_profile_name: week_profile_name.clone(), day_ids })
+                }
+                _ => Err(DataAccessResult::TypeUnmatched),
+            })
+            .collect()
+    }
+
+    /// Parses a day profile table (attributes 5 and 9).
+    pub fn parse_day_profile_table(data: &Data) -> Result<Vec<DayProfile>, DataAccessResult> {
+        elements(data)?
+            .iter()
+            .map(|day| match elements(day)? {
+                [Data::Unsigned(day_id), actions] => {
+                    let day_schedule = elements(actions)?
+                        .iter()
+                        .map(|action| match elements(action)? {
+                            [
+                                Data::Time(start_time),
+                                Data::OctetString(script),
+                                Data::LongUnsigned(script_selector),
+                            ] => Ok(DayProfileAction {
+                                start_time: start_time.clone(),
+                                script_logical_name: ObisCode::try_from(script.as_slice())
+                                    .map_err(|_| DataAccessResult::TypeUnmatched)?,
+                                script_selector: *script_selector,
+                            }),
+                            _ => Err(DataAccessResult::TypeUnmatched),
+                        })
+                        .collect::<Result<_, _>>()?;
+                    Ok(DayProfile { day_id: *day_id, day_schedule })
+                }
+                _ => Err(DataAccessResult::TypeUnmatched),
+            })
+            .collect()
+    }
+}
+
+/// Seconds since midnight of a time; wildcard fields count as 0.
+pub(crate) fn seconds_of_day(time: &Time) -> u32 {
+    u32::from(time.hour.unwrap_or(0)) * 3_600
+        + u32::from(time.minute.unwrap_or(0)) * 60
+        + u32::from(time.second.unwrap_or(0))
+}
+
+fn elements(data: &Data) -> Result<&[Data], DataAccessResult> {
+    match data {
+        Data::Structure(elements) => Ok(elements),
+        _ => Err(DataAccessResult::TypeUnmatched),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Date;
+
+    fn at(hour: u8, minute: u8) -> Time {
+        Time::new(Some(hour), Some(minute), Some(0), Some(0))
+    }
+
+    fn calendar() -> Calendar {
+        let script = ObisCode::new(0, 0, 10, 0, 100, 255);
+        let start = DateTime::new(Date::new(0xFFFF, 1, 1, 0xFF), at(0, 0), None, None);
+        Calendar::new(b"TOU".to_vec())
+            .with_season(b"ALL".to_vec(), start, b"WEEK".to_vec())
+            .with_week(b"WEEK".to_vec(), [1, 1, 1, 1, 1, 2, 2])
+            .with_day(DayProfile::new(1).with_action(at(6, 0), script, 1).with_action(
+                at(22, 0),
+                script,
+                2,
+            ))
+            .with_day(DayProfile::new(2).with_action(at(0, 0), script, 2))
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(calendar().validate(), Ok(()));
+
+        let mut unsorted = calendar();
+        unsorted.day_profile_table[0].day_schedule.reverse();
+        assert_eq!(unsorted.validate(), Err(CalendarError::UnsortedSwitchTimes(1)));
+
+        let mut dangling_week = calendar();
+        dangling_week.season_profile[0].week_name = b"OTHER".to_vec();
+        assert_eq!(
+            dangling_week.validate(),
+            Err(CalendarError::UnknownWeekProfile(b"OTHER".to_vec()))
+        );
+
+        let duplicate_day = calendar().with_day(DayProfile::new(2));
+        assert_eq!(duplicate_day.validate(), Err(CalendarError::DuplicateDayProfile(2)));
+        let duplicate_week = calendar().with_week(b"WEEK".to_vec(), [1; 7]);
+        assert_eq!(
+            duplicate_week.validate(),
+            Err(CalendarError::DuplicateWeekProfile(b"WEEK".to_vec()))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "encode")]
+    fn test_data_round_trip() {
+        let calendar = calendar();
+        assert_eq!(
+            Calendar::parse_season_profile(&calendar.season_profile_data()),
+            Ok(calendar.season_profile.clone())
+        );
+        assert_eq!(
+            Calendar::parse_week_profile_table(&calendar.week_profile_table_data()),
+            Ok(calendar.week_profile_table.clone())
+        );
+        assert_eq!(
+            Calendar::parse_day_profile_table(&calendar.day_profile_table_data()),
+            Ok(calendar.day_profile_table.clone())
+        );
+        assert_eq!(
+            Calendar::parse_week_profile_table(&Data::Structure(vec![Data::Structure(vec![
+                Data::OctetString(b"WEEK".to_vec()),
+                Data::Unsigned(1),
+            ])])),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+    }
+}
@@ -22,6 +22,57 @@ pub const PROFILE_GENERIC_CLASS_ID: u16 = 7;
 /// Attribute ID for ProfileGeneric.buffer (attribute 2)
 pub const PROFILE_GENERIC_BUFFER_ATTRIBUTE_ID: i8 = 2;
 
+/// Class ID for ActivityCalendar object (COSEM interface class 20)
+pub const ACTIVITY_CALENDAR_CLASS_ID: u16 = 20;
+
+/// Class ID for ImageTransfer object (COSEM interface class 18)
+pub const IMAGE_TRANSFER_CLASS_ID: u16 = 18;
+
+/// Maximum number of times missing image blocks are retransmitted
+pub const IMAGE_TRANSFER_MAX_RETRANSMISSIONS: usize = 3;
+
+/// Maximum number of image_transfer_status reads while verifying or activating
+pub const IMAGE_TRANSFER_MAX_STATUS_POLLS: usize = 60;
+
+/// Delay between image_transfer_status reads, in milliseconds (std only)
+pub const IMAGE_TRANSFER_STATUS_POLL_INTERVAL_MS: u64 = 1000;
+
+/// Class ID for DisconnectControl object (COSEM interface class 70)
+pub const DISCONNECT_CONTROL_CLASS_ID: u16 = 70;
+
+/// Attribute ID for DisconnectControl.control_state (attribute 3)
+pub const DISCONNECT_CONTROL_STATE_ATTRIBUTE_ID: i8 = 3;
+
+/// Class ID for SecuritySetup object (COSEM interface class 64)
+pub const SECURITY_SETUP_CLASS_ID: u16 = 64;
+
+/// Method ID for SecuritySetup.key_transfer (method 2)
+pub const SECURITY_SETUP_KEY_TRANSFER_METHOD_ID: i8 = 2;
+
+/// Class ID for TokenGateway object (COSEM interface class 115)
+pub const TOKEN_GATEWAY_CLASS_ID: u16 = 115;
+
+/// Method ID for TokenGateway.enter (method 1)
+pub const TOKEN_GATEWAY_ENTER_METHOD_ID: i8 = 1;
+
+/// Attribute ID for TokenGateway.token_status (attribute 6)
+pub const TOKEN_GATEWAY_STATUS_ATTRIBUTE_ID: i8 = 6;
+
+/// Class ID for MBusClient object (COSEM interface class 72)
+pub const MBUS_CLIENT_CLASS_ID: u16 = 72;
+
+/// Attribute ID for MBusClient.capture_definition (attribute 3)
+pub const MBUS_CLIENT_CAPTURE_DEFINITION_ATTRIBUTE_ID: i8 = 3;
+
+/// Class ID for ExtendedRegister object (COSEM interface class 4)
+pub const EXTENDED_REGISTER_CLASS_ID: u16 = 4;
+
+/// Attribute ID for ExtendedRegister.value (attribute 2)
+pub const EXTENDED_REGISTER_VALUE_ATTRIBUTE_ID: i8 = 2;
+
+/// Attribute ID for ExtendedRegister.capture_time (attribute 5)
+pub const EXTENDED_REGISTER_CAPTURE_TIME_ATTRIBUTE_ID: i8 = 5;
+
 /// Default maximum attributes per request (Gurux compatibility)
 ///
 /// This default value matches Gurux DLMS.c behavior for maximum
@@ -34,6 +85,46 @@ pub const DEFAULT_MAX_ATTRIBUTES_PER_REQUEST: usize = 10;
 /// keeping memory usage reasonable.
 pub const RECV_BUFFER_SIZE: usize = 4096;
 
+// ============================================================================
+// Shared Types
+// ============================================================================
+
+/// State of the supply disconnector, as reported by the Disconnect Control
+/// object (control_state, attribute 3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelayState {
+    /// Supply disconnected
+    Disconnected,
+    /// Supply connected
+    Connected,
+    /// Supply disconnected, the customer may reconnect it manually
+    ReadyForReconnection,
+}
+
+impl RelayState {
+    /// Converts a control_state enum value, or returns `None` if unknown.
+    pub fn from_control_state(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(RelayState::Disconnected),
+            1 => Some(RelayState::Connected),
+            2 => Some(RelayState::ReadyForReconnection),
+            _ => None,
+        }
+    }
+}
+
+/// A value of an M-Bus sub-meter, read from its value register
+/// 0-x:24.2.y.255 (Extended Register).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MBusValue {
+    /// Logical name of the value register
+    pub register: crate::obis_code::ObisCode,
+    /// Value captured from the sub-meter
+    pub value: crate::data::Data,
+    /// Time of the capture
+    pub capture_time: crate::data::DateTime,
+}
+
 // ============================================================================
 // Submodules
 // ============================================================================
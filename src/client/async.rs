@@ -2,11 +2,19 @@
 extern crate alloc;
 
 use crate::association::{AareApdu, AssociationResult, ReleaseResponseApdu, ReleaseResponseReason};
+use crate::calendar::CalendarError;
 use crate::client::sync::{Buffer, ClientSettings, DlmsSession, SessionState};
 use crate::client::{
-    CLOCK_CLASS_ID, CLOCK_TIME_ATTRIBUTE_ID, PROFILE_GENERIC_BUFFER_ATTRIBUTE_ID,
-    PROFILE_GENERIC_CLASS_ID,
+    ACTIVITY_CALENDAR_CLASS_ID, CLOCK_CLASS_ID, CLOCK_TIME_ATTRIBUTE_ID,
+    DISCONNECT_CONTROL_CLASS_ID, DISCONNECT_CONTROL_STATE_ATTRIBUTE_ID,
+    EXTENDED_REGISTER_CAPTURE_TIME_ATTRIBUTE_ID, EXTENDED_REGISTER_CLASS_ID,
+    EXTENDED_REGISTER_VALUE_ATTRIBUTE_ID, MBUS_CLIENT_CAPTURE_DEFINITION_ATTRIBUTE_ID,
+    MBUS_CLIENT_CLASS_ID, MBusValue, PROFILE_GENERIC_BUFFER_ATTRIBUTE_ID, PROFILE_GENERIC_CLASS_ID,
+    RelayState, SECURITY_SETUP_CLASS_ID, SECURITY_SETUP_KEY_TRANSFER_METHOD_ID,
+    TOKEN_GATEWAY_CLASS_ID, TOKEN_GATEWAY_ENTER_METHOD_ID, TOKEN_GATEWAY_STATUS_ATTRIBUTE_ID,
 };
+use crate::key_transfer::{KeyId, KeyWrapError, WrappedKey};
+use crate::token::TokenStatus;
 use crate::transport::r#async::AsyncTransport;
 use alloc::vec;
 use alloc::vec::Vec;
@@ -68,6 +76,12 @@ pub enum AsyncClientError<E> {
     /// Invalid response data format.
     #[cfg(feature = "parse")]
     InvalidResponseData,
+    /// Calendar rejected before being sent.
+    InvalidCalendar(CalendarError),
+    /// Key or KEK rejected before being wrapped.
+    InvalidKey(KeyWrapError),
+    /// The new key could not be verified; the previous key was restored.
+    KeyVerificationFailed,
 }
 
 impl<E> From<E> for AsyncClientError<E> {
@@ -98,6 +112,11 @@ impl<E: fmt::Display> fmt::Display for AsyncClientError<E> {
             AsyncClientError::InvokeIdMismatch => write!(f, "Invoke ID mismatch"),
             #[cfg(feature = "parse")]
             AsyncClientError::InvalidResponseData => write!(f, "Invalid response data"),
+            AsyncClientError::InvalidCalendar(err) => write!(f, "Invalid calendar: {}", err),
+            AsyncClientError::InvalidKey(err) => write!(f, "Invalid key: {}", err),
+            AsyncClientError::KeyVerificationFailed => {
+                write!(f, "New key verification failed, previous key restored")
+            }
         }
     }
 }
@@ -1234,6 +1253,457 @@ impl<T: AsyncTransport, B: Buffer> AsyncDlmsClient<T, B> {
         let obis = ObisCode::new(0, 0, 1, 0, 0, 255);
         self.write(CLOCK_CLASS_ID, obis, CLOCK_TIME_ATTRIBUTE_ID, Data::DateTime(time)).await
     }
+
+    /// Programs the passive calendar of the Activity Calendar object.
+    ///
+    /// Validates the calendar, then writes attributes 6-9 of the Activity
+    /// Calendar object (0.0.13.0.0.255) and, if given, the activation time
+    /// (attribute 10). An inconsistent calendar is rejected with
+    /// `InvalidCalendar` before anything is sent.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use dlms_cosem::async_client::{AsyncClientBuilder, AsyncClientError};
+    /// # use dlms_cosem::transport::r#async::AsyncTransport;
+    /// # use dlms_cosem::client::ClientSettings;
+    /// # use dlms_cosem::calendar::{Calendar, DayProfile};
+    /// # use dlms_cosem::{Date, DateTime, ObisCode, Time};
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl AsyncTransport for MyTransport {
+    /// #     type Error = std::io::Error;
+    /// #     async fn send(&mut self, _data: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     async fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, Self::Error> { Ok(0) }
+    /// #     #[cfg(feature = "std")]
+    /// #     async fn recv_timeout(&mut self, buffer: &mut [u8], _timeout: std::time::Duration) -> Result<usize, Self::Error> { self.recv(buffer).await }
+    /// # }
+    /// # async fn example() -> Result<(), AsyncClientError<std::io::Error>> {
+    /// # let mut client = AsyncClientBuilder::new(MyTransport, ClientSettings::default())
+    /// #     .build_with_heap(2048);
+    /// let script = ObisCode::new(0, 0, 10, 0, 100, 255);
+    /// let midnight = Time::new(Some(0), Some(0), Some(0), Some(0));
+    /// let every_year = DateTime::new(Date::new(0xFFFF, 1, 1, 0xFF), midnight.clone(), None, None);
+    /// let calendar = Calendar::new(b"FLAT".to_vec())
+    ///     .with_season(b"ALL".to_vec(), every_year, b"WEEK".to_vec())
+    ///     .with_week(b"WEEK".to_vec(), [1; 7])
+    ///     .with_day(DayProfile::new(1).with_action(midnight, script, 1));
+    /// client.write_passive_calendar(&calendar, None).await?;
+    /// client.activate_passive_calendar().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub async fn write_passive_calendar(
+        &mut self,
+        calendar: &crate::calendar::Calendar,
+        activation_time: Option<DateTime>,
+    ) -> Result<(), AsyncClientError<T::Error>> {
+        calendar.validate().map_err(AsyncClientError::InvalidCalendar)?;
+
+        let obis = ObisCode::new(0, 0, 13, 0, 0, 255);
+        let mut attributes = vec![
+            (6, Data::OctetString(calendar.calendar_name.clone())),
+            (7, calendar.season_profile_data()),
+            (8, calendar.week_profile_table_data()),
+            (9, calendar.day_profile_table_data()),
+        ];
+        if let Some(time) = activation_time {
+            attributes.push((10, Data::DateTime(time)));
+        }
+        for (attribute_id, value) in attributes {
+            self.write(ACTIVITY_CALENDAR_CLASS_ID, obis, attribute_id, value).await?;
+        }
+        Ok(())
+    }
+
+    /// Activates the passive calendar of the Activity Calendar object now.
+    ///
+    /// Invokes activate_passive_calendar (method 1) on the Activity Calendar
+    /// object (0.0.13.0.0.255).
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub async fn activate_passive_calendar(&mut self) -> Result<(), AsyncClientError<T::Error>> {
+        let obis = ObisCode::new(0, 0, 13, 0, 0, 255);
+        self.method(ACTIVITY_CALENDAR_CLASS_ID, obis, 1, Some(Data::Integer(0))).await?;
+        Ok(())
+    }
+
+    /// Disconnects the supply with the Disconnect Control object.
+    ///
+    /// Invokes remote_disconnect (method 1) on the Disconnect Control object
+    /// (0.0.96.3.10.255).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use dlms_cosem::async_client::{AsyncClientBuilder, AsyncClientError};
+    /// # use dlms_cosem::transport::r#async::AsyncTransport;
+    /// # use dlms_cosem::client::ClientSettings;
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl AsyncTransport for MyTransport {
+    /// #     type Error = std::io::Error;
+    /// #     async fn send(&mut self, _data: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     async fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, Self::Error> { Ok(0) }
+    /// #     #[cfg(feature = "std")]
+    /// #     async fn recv_timeout(&mut self, buffer: &mut [u8], _timeout: std::time::Duration) -> Result<usize, Self::Error> { self.recv(buffer).await }
+    /// # }
+    /// # async fn example() -> Result<(), AsyncClientError<std::io::Error>> {
+    /// # let mut client = AsyncClientBuilder::new(MyTransport, ClientSettings::default())
+    /// #     .build_with_heap(2048);
+    /// client.remote_disconnect().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub async fn remote_disconnect(&mut self) -> Result<(), AsyncClientError<T::Error>> {
+        let obis = ObisCode::new(0, 0, 96, 3, 10, 255);
+        self.method(DISCONNECT_CONTROL_CLASS_ID, obis, 1, Some(Data::Integer(0))).await?;
+        Ok(())
+    }
+
+    /// Reconnects the supply with the Disconnect Control object.
+    ///
+    /// Invokes remote_reconnect (method 2) on the Disconnect Control object
+    /// (0.0.96.3.10.255). Depending on the control mode the meter connects the
+    /// supply or only arms it for a manual reconnection.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use dlms_cosem::async_client::{AsyncClientBuilder, AsyncClientError};
+    /// # use dlms_cosem::transport::r#async::AsyncTransport;
+    /// # use dlms_cosem::client::ClientSettings;
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl AsyncTransport for MyTransport {
+    /// #     type Error = std::io::Error;
+    /// #     async fn send(&mut self, _data: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     async fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, Self::Error> { Ok(0) }
+    /// #     #[cfg(feature = "std")]
+    /// #     async fn recv_timeout(&mut self, buffer: &mut [u8], _timeout: std::time::Duration) -> Result<usize, Self::Error> { self.recv(buffer).await }
+    /// # }
+    /// # async fn example() -> Result<(), AsyncClientError<std::io::Error>> {
+    /// # let mut client = AsyncClientBuilder::new(MyTransport, ClientSettings::default())
+    /// #     .build_with_heap(2048);
+    /// client.remote_reconnect().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub async fn remote_reconnect(&mut self) -> Result<(), AsyncClientError<T::Error>> {
+        let obis = ObisCode::new(0, 0, 96, 3, 10, 255);
+        self.method(DISCONNECT_CONTROL_CLASS_ID, obis, 2, Some(Data::Integer(0))).await?;
+        Ok(())
+    }
+
+    /// Reads the state of the supply disconnector (control_state of the
+    /// Disconnect Control object, 0.0.96.3.10.255).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use dlms_cosem::async_client::{AsyncClientBuilder, AsyncClientError};
+    /// # use dlms_cosem::transport::r#async::AsyncTransport;
+    /// # use dlms_cosem::client::ClientSettings;
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl AsyncTransport for MyTransport {
+    /// #     type Error = std::io::Error;
+    /// #     async fn send(&mut self, _data: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     async fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, Self::Error> { Ok(0) }
+    /// #     #[cfg(feature = "std")]
+    /// #     async fn recv_timeout(&mut self, buffer: &mut [u8], _timeout: std::time::Duration) -> Result<usize, Self::Error> { self.recv(buffer).await }
+    /// # }
+    /// # async fn example() -> Result<(), AsyncClientError<std::io::Error>> {
+    /// # let mut client = AsyncClientBuilder::new(MyTransport, ClientSettings::default())
+    /// #     .build_with_heap(2048);
+    /// let state = client.relay_state().await?;
+    /// println!("Relay: {:?}", state);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub async fn relay_state(&mut self) -> Result<RelayState, AsyncClientError<T::Error>> {
+        let obis = ObisCode::new(0, 0, 96, 3, 10, 255);
+        let data = self
+            .read(DISCONNECT_CONTROL_CLASS_ID, obis, DISCONNECT_CONTROL_STATE_ATTRIBUTE_ID, None)
+            .await?;
+
+        match data {
+            Data::Enum(state) => {
+                RelayState::from_control_state(state).ok_or(AsyncClientError::InvalidResponseData)
+            }
+            _ => Err(AsyncClientError::InvalidResponseData),
+        }
+    }
+
+    /// Transfers keys to a Security Setup object.
+    ///
+    /// Wraps each key under the master key `kek` (RFC 3394) and invokes
+    /// key_transfer (method 2) on the Security Setup object `security_setup`
+    /// (0.0.43.0.e.255). Invalid key lengths are rejected with `InvalidKey`
+    /// before anything is sent.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use dlms_cosem::async_client::{AsyncClientBuilder, AsyncClientError};
+    /// # use dlms_cosem::transport::r#async::AsyncTransport;
+    /// # use dlms_cosem::client::ClientSettings;
+    /// # use dlms_cosem::key_transfer::KeyId;
+    /// # use dlms_cosem::ObisCode;
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl AsyncTransport for MyTransport {
+    /// #     type Error = std::io::Error;
+    /// #     async fn send(&mut self, _data: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     async fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, Self::Error> { Ok(0) }
+    /// #     #[cfg(feature = "std")]
+    /// #     async fn recv_timeout(&mut self, buffer: &mut [u8], _timeout: std::time::Duration) -> Result<usize, Self::Error> { self.recv(buffer).await }
+    /// # }
+    /// # async fn example() -> Result<(), AsyncClientError<std::io::Error>> {
+    /// # let mut client = AsyncClientBuilder::new(MyTransport, ClientSettings::default())
+    /// #     .build_with_heap(2048);
+    /// let security_setup = ObisCode::new(0, 0, 43, 0, 1, 255);
+    /// client
+    ///     .transfer_keys(security_setup, &[0x0F; 16], &[(KeyId::AuthenticationKey, &[0x22; 16])])
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub async fn transfer_keys(
+        &mut self,
+        security_setup: ObisCode,
+        kek: &[u8],
+        keys: &[(KeyId, &[u8])],
+    ) -> Result<(), AsyncClientError<T::Error>> {
+        let keys = keys
+            .iter()
+            .map(|(key_id, key)| WrappedKey::new(*key_id, kek, key))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AsyncClientError::InvalidKey)?;
+        self.method(
+            SECURITY_SETUP_CLASS_ID,
+            security_setup,
+            SECURITY_SETUP_KEY_TRANSFER_METHOD_ID,
+            Some(WrappedKey::key_transfer_data(&keys)),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Rotates a key of a Security Setup object and verifies the new key.
+    ///
+    /// Transfers the new key, then awaits `verify`, which proves that the new
+    /// key works (e.g. by opening an association ciphered with it). If the
+    /// verification fails, the current key is transferred back (a current
+    /// master key wrapped under the new one) and `KeyVerificationFailed` is
+    /// returned. Both keys are wrapped before anything is sent.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use dlms_cosem::async_client::{AsyncClientBuilder, AsyncClientError};
+    /// # use dlms_cosem::transport::r#async::AsyncTransport;
+    /// # use dlms_cosem::client::ClientSettings;
+    /// # use dlms_cosem::key_transfer::KeyId;
+    /// # use dlms_cosem::ObisCode;
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl AsyncTransport for MyTransport {
+    /// #     type Error = std::io::Error;
+    /// #     async fn send(&mut self, _data: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     async fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, Self::Error> { Ok(0) }
+    /// #     #[cfg(feature = "std")]
+    /// #     async fn recv_timeout(&mut self, buffer: &mut [u8], _timeout: std::time::Duration) -> Result<usize, Self::Error> { self.recv(buffer).await }
+    /// # }
+    /// # async fn associate_with(_key: &[u8; 16]) -> bool { true }
+    /// # async fn example() -> Result<(), AsyncClientError<std::io::Error>> {
+    /// # let mut client = AsyncClientBuilder::new(MyTransport, ClientSettings::default())
+    /// #     .build_with_heap(2048);
+    /// let (kek, current_key, new_key) = ([0x0F; 16], [0x11; 16], [0x12; 16]);
+    /// let security_setup = ObisCode::new(0, 0, 43, 0, 1, 255);
+    /// client
+    ///     .rotate_key(
+    ///         security_setup,
+    ///         &kek,
+    ///         KeyId::GlobalUnicastEncryptionKey,
+    ///         &current_key,
+    ///         &new_key,
+    ///         async |_client| match associate_with(&new_key).await {
+    ///             true => Ok(()),
+    ///             false => Err(AsyncClientError::KeyVerificationFailed),
+    ///         },
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub async fn rotate_key(
+        &mut self,
+        security_setup: ObisCode,
+        kek: &[u8],
+        key_id: KeyId,
+        current_key: &[u8],
+        new_key: &[u8],
+        verify: impl AsyncFnOnce(&mut Self) -> Result<(), AsyncClientError<T::Error>>,
+    ) -> Result<(), AsyncClientError<T::Error>> {
+        let restore_kek = if key_id == KeyId::MasterKey { new_key } else { kek };
+        let forward =
+            WrappedKey::new(key_id, kek, new_key).map_err(AsyncClientError::InvalidKey)?;
+        let restore = WrappedKey::new(key_id, restore_kek, current_key)
+            .map_err(AsyncClientError::InvalidKey)?;
+
+        self.method(
+            SECURITY_SETUP_CLASS_ID,
+            security_setup,
+            SECURITY_SETUP_KEY_TRANSFER_METHOD_ID,
+            Some(WrappedKey::key_transfer_data(&[forward])),
+        )
+        .await?;
+        if verify(self).await.is_ok() {
+            return Ok(());
+        }
+
+        self.method(
+            SECURITY_SETUP_CLASS_ID,
+            security_setup,
+            SECURITY_SETUP_KEY_TRANSFER_METHOD_ID,
+            Some(WrappedKey::key_transfer_data(&[restore])),
+        )
+        .await?;
+        Err(AsyncClientError::KeyVerificationFailed)
+    }
+
+    /// Enters a token, e.g. an STS token, into a Token Gateway object and
+    /// reads back its token_status. A rejected token is not an error: check
+    /// the returned status code.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use dlms_cosem::async_client::{AsyncClientBuilder, AsyncClientError};
+    /// # use dlms_cosem::transport::r#async::AsyncTransport;
+    /// # use dlms_cosem::client::ClientSettings;
+    /// # use dlms_cosem::ObisCode;
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl AsyncTransport for MyTransport {
+    /// #     type Error = std::io::Error;
+    /// #     async fn send(&mut self, _data: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     async fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, Self::Error> { Ok(0) }
+    /// #     #[cfg(feature = "std")]
+    /// #     async fn recv_timeout(&mut self, buffer: &mut [u8], _timeout: std::time::Duration) -> Result<usize, Self::Error> { self.recv(buffer).await }
+    /// # }
+    /// # async fn example() -> Result<(), AsyncClientError<std::io::Error>> {
+    /// # let mut client = AsyncClientBuilder::new(MyTransport, ClientSettings::default())
+    /// #     .build_with_heap(2048);
+    /// let token_gateway = ObisCode::new(0, 0, 19, 40, 0, 255);
+    /// let status = client.enter_token(token_gateway, b"56781234987612340987").await?;
+    /// println!("{}", status.code);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub async fn enter_token(
+        &mut self,
+        token_gateway: ObisCode,
+        token: &[u8],
+    ) -> Result<TokenStatus, AsyncClientError<T::Error>> {
+        self.method(
+            TOKEN_GATEWAY_CLASS_ID,
+            token_gateway,
+            TOKEN_GATEWAY_ENTER_METHOD_ID,
+            Some(Data::OctetString(token.to_vec())),
+        )
+        .await?;
+        let status = self
+            .read(TOKEN_GATEWAY_CLASS_ID, token_gateway, TOKEN_GATEWAY_STATUS_ATTRIBUTE_ID, None)
+            .await?;
+        TokenStatus::from_data(&status).map_err(|_| AsyncClientError::InvalidResponseData)
+    }
+
+    /// Read the values of an M-Bus sub-meter with their capture times.
+    ///
+    /// Reads capture_definition (attribute 3) of the M-Bus Client
+    /// 0-`channel`:24.1.0.255 (class 72), then the value (attribute 2) and
+    /// capture_time (attribute 5) of one value register 0-`channel`:24.2.y.255
+    /// (class 4) per captured record, with GET-Request-With-List.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use dlms_cosem::async_client::{AsyncClientBuilder, AsyncClientError};
+    /// # use dlms_cosem::transport::r#async::AsyncTransport;
+    /// # use dlms_cosem::client::ClientSettings;
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl AsyncTransport for MyTransport {
+    /// #     type Error = std::io::Error;
+    /// #     async fn send(&mut self, _data: &[u8]) -> Result<(), Self::Error> { Ok(()) }
+    /// #     async fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, Self::Error> { Ok(0) }
+    /// #     #[cfg(feature = "std")]
+    /// #     async fn recv_timeout(&mut self, buffer: &mut [u8], _timeout: std::time::Duration) -> Result<usize, Self::Error> { self.recv(buffer).await }
+    /// # }
+    /// # async fn example() -> Result<(), AsyncClientError<std::io::Error>> {
+    /// # let mut client = AsyncClientBuilder::new(MyTransport, ClientSettings::default())
+    /// #     .build_with_heap(2048);
+    /// // Gas meter on M-Bus channel 1
+    /// for value in client.read_mbus_values(1).await? {
+    ///     println!("{}: {:?} at {:?}", value.register, value.value, value.capture_time);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub async fn read_mbus_values(
+        &mut self,
+        channel: u8,
+    ) -> Result<Vec<MBusValue>, AsyncClientError<T::Error>> {
+        let capture_definition = self
+            .read(
+                MBUS_CLIENT_CLASS_ID,
+                ObisCode::new(0, channel, 24, 1, 0, 255),
+                MBUS_CLIENT_CAPTURE_DEFINITION_ATTRIBUTE_ID,
+                None,
+            )
+            .await?;
+        let Data::Structure(elements) = capture_definition else {
+            return Err(AsyncClientError::InvalidResponseData);
+        };
+        let registers: Vec<_> = (1..=u8::MAX)
+            .take(elements.len())
+            .map(|y| ObisCode::new(0, channel, 24, 2, y, 255))
+            .collect();
+        let descriptors: Vec<_> = registers
+            .iter()
+            .flat_map(|register| {
+                [EXTENDED_REGISTER_VALUE_ATTRIBUTE_ID, EXTENDED_REGISTER_CAPTURE_TIME_ATTRIBUTE_ID]
+                    .map(|attribute_id| AttributeDescriptor {
+                        class_id: EXTENDED_REGISTER_CLASS_ID,
+                        instance_id: *register,
+                        attribute_id,
+                    })
+            })
+            .collect();
+        let results = self.read_multiple_chunked(&descriptors).await?;
+        registers
+            .into_iter()
+            .zip(results.chunks(2))
+            .map(|(register, result)| match result {
+                [Ok(value), Ok(Data::DateTime(capture_time))] => Ok(MBusValue {
+                    register,
+                    value: value.clone(),
+                    capture_time: capture_time.clone(),
+                }),
+                [Err(error), _] | [_, Err(error)] => Err(AsyncClientError::DataAccessError(*error)),
+                _ => Err(AsyncClientError::InvalidResponseData),
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -6,10 +6,20 @@ use crate::association::{
     MechanismName, ReleaseRequestApdu, ReleaseRequestReason, ReleaseResponseApdu,
     ReleaseResponseReason,
 };
+use crate::calendar::{Calendar, CalendarError};
 use crate::client::{
-    CLOCK_CLASS_ID, CLOCK_TIME_ATTRIBUTE_ID, DEFAULT_MAX_ATTRIBUTES_PER_REQUEST,
-    PROFILE_GENERIC_BUFFER_ATTRIBUTE_ID, PROFILE_GENERIC_CLASS_ID,
+    ACTIVITY_CALENDAR_CLASS_ID, CLOCK_CLASS_ID, CLOCK_TIME_ATTRIBUTE_ID,
+    DEFAULT_MAX_ATTRIBUTES_PER_REQUEST, DISCONNECT_CONTROL_CLASS_ID,
+    DISCONNECT_CONTROL_STATE_ATTRIBUTE_ID, EXTENDED_REGISTER_CAPTURE_TIME_ATTRIBUTE_ID,
+    EXTENDED_REGISTER_CLASS_ID, EXTENDED_REGISTER_VALUE_ATTRIBUTE_ID, IMAGE_TRANSFER_CLASS_ID,
+    IMAGE_TRANSFER_MAX_RETRANSMISSIONS, IMAGE_TRANSFER_MAX_STATUS_POLLS,
+    MBUS_CLIENT_CAPTURE_DEFINITION_ATTRIBUTE_ID, MBUS_CLIENT_CLASS_ID, MBusValue,
+    PROFILE_GENERIC_BUFFER_ATTRIBUTE_ID, PROFILE_GENERIC_CLASS_ID, RelayState,
+    SECURITY_SETUP_CLASS_ID, SECURITY_SETUP_KEY_TRANSFER_METHOD_ID, TOKEN_GATEWAY_CLASS_ID,
+    TOKEN_GATEWAY_ENTER_METHOD_ID, TOKEN_GATEWAY_STATUS_ATTRIBUTE_ID,
 };
+use crate::key_transfer::{KeyId, KeyWrapError, WrappedKey};
+use crate::token::TokenStatus;
 use crate::transport::sync::Transport;
 use alloc::vec;
 use alloc::vec::Vec;
@@ -43,6 +53,18 @@ use crate::selective_access::{CaptureObjectDefinition, RangeDescriptor};
 #[cfg(feature = "heapless-buffer")]
 use heapless::Vec as HeaplessVec;
 
+/// Logical name of the Activity Calendar object used by the calendar helpers.
+#[cfg(all(feature = "encode", feature = "parse"))]
+const ACTIVITY_CALENDAR: ObisCode = ObisCode { a: 0, b: 0, c: 13, d: 0, e: 0, f: 255 };
+
+/// Logical name of the Disconnect Control object used by the relay helpers.
+#[cfg(all(feature = "encode", feature = "parse"))]
+const DISCONNECT_CONTROL: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 3, e: 10, f: 255 };
+
+/// Logical name of the Image Transfer object used by [`DlmsClient::upgrade_firmware`].
+#[cfg(all(feature = "encode", feature = "parse"))]
+const IMAGE_TRANSFER: ObisCode = ObisCode { a: 0, b: 0, c: 44, d: 0, e: 0, f: 255 };
+
 /// Errors that can occur during client operations.
 #[derive(Debug)]
 pub enum ClientError<E> {
@@ -73,6 +95,14 @@ pub enum ClientError<E> {
     /// Invalid response data format.
     #[cfg(feature = "parse")]
     InvalidResponseData,
+    /// Image transfer ended with the given image_transfer_status
+    ImageTransferFailed(u8),
+    /// Calendar rejected before being sent
+    InvalidCalendar(CalendarError),
+    /// Key or KEK rejected before being wrapped
+    InvalidKey(KeyWrapError),
+    /// The new key could not be verified; the previous key was restored
+    KeyVerificationFailed,
 }
 
 impl<E> From<E> for ClientError<E> {
@@ -99,6 +129,14 @@ impl<E: fmt::Display> fmt::Display for ClientError<E> {
             ClientError::InvokeIdMismatch => write!(f, "Invoke ID mismatch"),
             #[cfg(feature = "parse")]
             ClientError::InvalidResponseData => write!(f, "Invalid response data"),
+            ClientError::ImageTransferFailed(status) => {
+                write!(f, "Image transfer failed with status {}", status)
+            }
+            ClientError::InvalidCalendar(err) => write!(f, "Invalid calendar: {}", err),
+            ClientError::InvalidKey(err) => write!(f, "Invalid key: {}", err),
+            ClientError::KeyVerificationFailed => {
+                write!(f, "New key verification failed, previous key restored")
+            }
         }
     }
 }
@@ -1288,6 +1326,638 @@ impl<T: Transport, B: Buffer> DlmsClient<T, B> {
         Ok(())
     }
 
+    /// Program the passive calendar of the Activity Calendar object.
+    ///
+    /// Validates the calendar (see [`Calendar::validate`]), then writes the
+    /// passive calendar name, season profiles, week and day profile tables
+    /// (attributes 6-9) of the Activity Calendar object at OBIS code
+    /// 0.0.13.0.0.255, and the activation time (attribute 10) if given.
+    ///
+    /// # Arguments
+    ///
+    /// * `calendar` - Calendar to program
+    /// * `activation_time` - When the meter activates the calendar; `None`
+    ///   leaves the activation to [`activate_passive_calendar()`](Self::activate_passive_calendar)
+    ///
+    /// # Errors
+    ///
+    /// Returns `ClientError` if:
+    /// - The calendar is inconsistent (`InvalidCalendar`, nothing is sent)
+    /// - Not associated with the server
+    /// - Transport error occurs
+    /// - Data access error from server
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dlms_cosem::client::{ClientBuilder, ClientSettings};
+    /// # use dlms_cosem::calendar::{Calendar, DayProfile};
+    /// # use dlms_cosem::{Date, DateTime, ObisCode, Time};
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl dlms_cosem::transport::Transport for MyTransport {
+    /// #     type Error = ();
+    /// #     fn send(&mut self, _data: &[u8]) -> Result<(), ()> { Ok(()) }
+    /// #     fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+    /// # }
+    /// # let transport = MyTransport;
+    /// # let settings = ClientSettings::default();
+    /// # let mut client = ClientBuilder::new(transport, settings).build_with_heap(2048);
+    /// let script = ObisCode::new(0, 0, 10, 0, 100, 255);
+    /// let midnight = Time::new(Some(0), Some(0), Some(0), Some(0));
+    /// let every_year = DateTime::new(Date::new(0xFFFF, 1, 1, 0xFF), midnight.clone(), None, None);
+    /// let calendar = Calendar::new(b"FLAT".to_vec())
+    ///     .with_season(b"ALL".to_vec(), every_year, b"WEEK".to_vec())
+    ///     .with_week(b"WEEK".to_vec(), [1; 7])
+    ///     .with_day(DayProfile::new(1).with_action(midnight, script, 1));
+    /// client.write_passive_calendar(&calendar, None);
+    /// client.activate_passive_calendar();
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub fn write_passive_calendar(
+        &mut self,
+        calendar: &Calendar,
+        activation_time: Option<DateTime>,
+    ) -> Result<(), ClientError<T::Error>> {
+        calendar.validate().map_err(ClientError::InvalidCalendar)?;
+
+        let mut attributes = vec![
+            (6, Data::OctetString(calendar.calendar_name.clone())),
+            (7, calendar.season_profile_data()),
+            (8, calendar.week_profile_table_data()),
+            (9, calendar.day_profile_table_data()),
+        ];
+        if let Some(time) = activation_time {
+            attributes.push((10, Data::DateTime(time)));
+        }
+        for (attribute_id, value) in attributes {
+            self.write(ACTIVITY_CALENDAR_CLASS_ID, ACTIVITY_CALENDAR, attribute_id, value, None)?;
+        }
+        Ok(())
+    }
+
+    /// Activate the passive calendar of the Activity Calendar object now.
+    ///
+    /// Invokes activate_passive_calendar (method 1) on the Activity Calendar
+    /// object at OBIS code 0.0.13.0.0.255.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ClientError` if:
+    /// - Not associated with the server
+    /// - Transport error occurs
+    /// - Action error from server (e.g., inconsistent passive calendar)
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub fn activate_passive_calendar(&mut self) -> Result<(), ClientError<T::Error>> {
+        self.method(ACTIVITY_CALENDAR_CLASS_ID, ACTIVITY_CALENDAR, 1, Some(Data::Integer(0)))?;
+        Ok(())
+    }
+
+    /// Disconnect the supply with the Disconnect Control object.
+    ///
+    /// Invokes remote_disconnect (method 1) on the Disconnect Control object
+    /// at OBIS code 0.0.96.3.10.255.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ClientError` if:
+    /// - Not associated with the server
+    /// - Transport error occurs
+    /// - Action error from server (e.g., control mode 0)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dlms_cosem::client::{ClientBuilder, ClientSettings};
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl dlms_cosem::transport::Transport for MyTransport {
+    /// #     type Error = ();
+    /// #     fn send(&mut self, _data: &[u8]) -> Result<(), ()> { Ok(()) }
+    /// #     fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+    /// # }
+    /// # let transport = MyTransport;
+    /// # let settings = ClientSettings::default();
+    /// # let mut client = ClientBuilder::new(transport, settings).build_with_heap(2048);
+    /// client.remote_disconnect();
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub fn remote_disconnect(&mut self) -> Result<(), ClientError<T::Error>> {
+        self.method(DISCONNECT_CONTROL_CLASS_ID, DISCONNECT_CONTROL, 1, Some(Data::Integer(0)))?;
+        Ok(())
+    }
+
+    /// Reconnect the supply with the Disconnect Control object.
+    ///
+    /// Invokes remote_reconnect (method 2) on the Disconnect Control object
+    /// at OBIS code 0.0.96.3.10.255. Depending on the control mode the meter
+    /// connects the supply or only arms it for a manual reconnection; use
+    /// [`relay_state()`](Self::relay_state) to check.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ClientError` if:
+    /// - Not associated with the server
+    /// - Transport error occurs
+    /// - Action error from server (e.g., control mode 0)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dlms_cosem::client::{ClientBuilder, ClientSettings};
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl dlms_cosem::transport::Transport for MyTransport {
+    /// #     type Error = ();
+    /// #     fn send(&mut self, _data: &[u8]) -> Result<(), ()> { Ok(()) }
+    /// #     fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+    /// # }
+    /// # let transport = MyTransport;
+    /// # let settings = ClientSettings::default();
+    /// # let mut client = ClientBuilder::new(transport, settings).build_with_heap(2048);
+    /// client.remote_reconnect();
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub fn remote_reconnect(&mut self) -> Result<(), ClientError<T::Error>> {
+        self.method(DISCONNECT_CONTROL_CLASS_ID, DISCONNECT_CONTROL, 2, Some(Data::Integer(0)))?;
+        Ok(())
+    }
+
+    /// Read the state of the supply disconnector.
+    ///
+    /// Reads control_state (attribute 3) of the Disconnect Control object at
+    /// OBIS code 0.0.96.3.10.255.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ClientError` if:
+    /// - Not associated with the server
+    /// - Transport error occurs
+    /// - Response is not a known control state
+    /// - Data access error from server
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dlms_cosem::client::{ClientBuilder, ClientSettings, RelayState};
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl dlms_cosem::transport::Transport for MyTransport {
+    /// #     type Error = ();
+    /// #     fn send(&mut self, _data: &[u8]) -> Result<(), ()> { Ok(()) }
+    /// #     fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+    /// # }
+    /// # let transport = MyTransport;
+    /// # let settings = ClientSettings::default();
+    /// # let mut client = ClientBuilder::new(transport, settings).build_with_heap(2048);
+    /// if client.relay_state().ok() == Some(RelayState::ReadyForReconnection) {
+    ///     println!("Waiting for the customer to press the button");
+    /// }
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub fn relay_state(&mut self) -> Result<RelayState, ClientError<T::Error>> {
+        let data = self.read(
+            DISCONNECT_CONTROL_CLASS_ID,
+            DISCONNECT_CONTROL,
+            DISCONNECT_CONTROL_STATE_ATTRIBUTE_ID,
+            None,
+        )?;
+
+        match data {
+            Data::Enum(state) => {
+                RelayState::from_control_state(state).ok_or(ClientError::InvalidResponseData)
+            }
+            _ => Err(ClientError::InvalidResponseData),
+        }
+    }
+
+    /// Transfer keys to a Security Setup object.
+    ///
+    /// Wraps each key under the master key `kek` (RFC 3394) and invokes
+    /// key_transfer (method 2) on the Security Setup object `security_setup`
+    /// (0.0.43.0.e.255). The meter replaces either all keys or none. A new
+    /// master key is wrapped under the current one, like the other keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ClientError` if:
+    /// - A key or the KEK has an invalid length (`InvalidKey`), before
+    ///   anything is sent
+    /// - Not associated with the server
+    /// - Transport error occurs
+    /// - Action error from server (e.g., wrong KEK or key size)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dlms_cosem::client::{ClientBuilder, ClientSettings};
+    /// # use dlms_cosem::key_transfer::KeyId;
+    /// # use dlms_cosem::ObisCode;
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl dlms_cosem::transport::Transport for MyTransport {
+    /// #     type Error = ();
+    /// #     fn send(&mut self, _data: &[u8]) -> Result<(), ()> { Ok(()) }
+    /// #     fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+    /// # }
+    /// # let transport = MyTransport;
+    /// # let settings = ClientSettings::default();
+    /// # let mut client = ClientBuilder::new(transport, settings).build_with_heap(2048);
+    /// let kek = [0x0F; 16];
+    /// let security_setup = ObisCode::new(0, 0, 43, 0, 1, 255);
+    /// client.transfer_keys(
+    ///     security_setup,
+    ///     &kek,
+    ///     &[(KeyId::GlobalUnicastEncryptionKey, &[0x11; 16]), (KeyId::AuthenticationKey, &[0x22; 16])],
+    /// );
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub fn transfer_keys(
+        &mut self,
+        security_setup: ObisCode,
+        kek: &[u8],
+        keys: &[(KeyId, &[u8])],
+    ) -> Result<(), ClientError<T::Error>> {
+        let keys = keys
+            .iter()
+            .map(|(key_id, key)| WrappedKey::new(*key_id, kek, key))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ClientError::InvalidKey)?;
+        self.method(
+            SECURITY_SETUP_CLASS_ID,
+            security_setup,
+            SECURITY_SETUP_KEY_TRANSFER_METHOD_ID,
+            Some(WrappedKey::key_transfer_data(&keys)),
+        )?;
+        Ok(())
+    }
+
+    /// Rotate a key of a Security Setup object and verify the new key.
+    ///
+    /// 1. Wraps both the new key and the current key before anything is
+    ///    sent, so that the rollback cannot fail on the client side.
+    /// 2. Transfers the new key (see [`transfer_keys()`](Self::transfer_keys)).
+    /// 3. Calls `verify`, which proves that the new key works, typically by
+    ///    opening an association ciphered or authenticated with it.
+    /// 4. If the verification fails, transfers the current key back; a
+    ///    current master key is then wrapped under the new one.
+    ///
+    /// The key transfer and the rollback are sent in the association of
+    /// this client, which should be protected by the current keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ClientError` if:
+    /// - A key or the KEK has an invalid length (`InvalidKey`)
+    /// - The transfer of the new key fails; the current key is still in use
+    /// - The verification fails and the current key was restored
+    ///   (`KeyVerificationFailed`)
+    /// - The restoration fails: the error of the restoration is returned,
+    ///   and the meter may use either key
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dlms_cosem::client::{ClientBuilder, ClientSettings};
+    /// # use dlms_cosem::key_transfer::KeyId;
+    /// # use dlms_cosem::ObisCode;
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl dlms_cosem::transport::Transport for MyTransport {
+    /// #     type Error = ();
+    /// #     fn send(&mut self, _data: &[u8]) -> Result<(), ()> { Ok(()) }
+    /// #     fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+    /// # }
+    /// # fn associate_with(_key: &[u8; 16]) -> bool { true }
+    /// # let transport = MyTransport;
+    /// # let settings = ClientSettings::default();
+    /// # let mut client = ClientBuilder::new(transport, settings).build_with_heap(2048);
+    /// let (kek, current_key, new_key) = ([0x0F; 16], [0x11; 16], [0x12; 16]);
+    /// let security_setup = ObisCode::new(0, 0, 43, 0, 1, 255);
+    /// client.rotate_key(
+    ///     security_setup,
+    ///     &kek,
+    ///     KeyId::GlobalUnicastEncryptionKey,
+    ///     &current_key,
+    ///     &new_key,
+    ///     |_client| {
+    ///         if associate_with(&new_key) {
+    ///             Ok(())
+    ///         } else {
+    ///             Err(dlms_cosem::client::ClientError::KeyVerificationFailed)
+    ///         }
+    ///     },
+    /// );
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub fn rotate_key(
+        &mut self,
+        security_setup: ObisCode,
+        kek: &[u8],
+        key_id: KeyId,
+        current_key: &[u8],
+        new_key: &[u8],
+        verify: impl FnOnce(&mut Self) -> Result<(), ClientError<T::Error>>,
+    ) -> Result<(), ClientError<T::Error>> {
+        let restore_kek = if key_id == KeyId::MasterKey { new_key } else { kek };
+        let forward = WrappedKey::new(key_id, kek, new_key).map_err(ClientError::InvalidKey)?;
+        let restore =
+            WrappedKey::new(key_id, restore_kek, current_key).map_err(ClientError::InvalidKey)?;
+
+        self.method(
+            SECURITY_SETUP_CLASS_ID,
+            security_setup,
+            SECURITY_SETUP_KEY_TRANSFER_METHOD_ID,
+            Some(WrappedKey::key_transfer_data(&[forward])),
+        )?;
+        if verify(self).is_ok() {
+            return Ok(());
+        }
+
+        self.method(
+            SECURITY_SETUP_CLASS_ID,
+            security_setup,
+            SECURITY_SETUP_KEY_TRANSFER_METHOD_ID,
+            Some(WrappedKey::key_transfer_data(&[restore])),
+        )?;
+        Err(ClientError::KeyVerificationFailed)
+    }
+
+    /// Enter a token, e.g. an STS token, into a Token Gateway object.
+    ///
+    /// Invokes enter (method 1) on the Token Gateway object `token_gateway`
+    /// (0.0.19.40.e.255), then reads back token_status (attribute 6). A
+    /// rejected token is not an error: check the returned status code.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ClientError` if:
+    /// - Not associated with the server
+    /// - Transport error occurs
+    /// - Response is not a token status
+    /// - Action or data access error from server
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dlms_cosem::client::{ClientBuilder, ClientSettings};
+    /// # use dlms_cosem::ObisCode;
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl dlms_cosem::transport::Transport for MyTransport {
+    /// #     type Error = ();
+    /// #     fn send(&mut self, _data: &[u8]) -> Result<(), ()> { Ok(()) }
+    /// #     fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+    /// # }
+    /// # let transport = MyTransport;
+    /// # let settings = ClientSettings::default();
+    /// # let mut client = ClientBuilder::new(transport, settings).build_with_heap(2048);
+    /// let token_gateway = ObisCode::new(0, 0, 19, 40, 0, 255);
+    /// if let Ok(status) = client.enter_token(token_gateway, b"56781234987612340987") {
+    ///     println!("{}", status.code);
+    /// }
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub fn enter_token(
+        &mut self,
+        token_gateway: ObisCode,
+        token: &[u8],
+    ) -> Result<TokenStatus, ClientError<T::Error>> {
+        self.method(
+            TOKEN_GATEWAY_CLASS_ID,
+            token_gateway,
+            TOKEN_GATEWAY_ENTER_METHOD_ID,
+            Some(Data::OctetString(token.to_vec())),
+        )?;
+        let status = self.read(
+            TOKEN_GATEWAY_CLASS_ID,
+            token_gateway,
+            TOKEN_GATEWAY_STATUS_ATTRIBUTE_ID,
+            None,
+        )?;
+        TokenStatus::from_data(&status).map_err(|_| ClientError::InvalidResponseData)
+    }
+
+    /// Read the values of an M-Bus sub-meter with their capture times.
+    ///
+    /// Reads capture_definition (attribute 3) of the M-Bus Client
+    /// 0-`channel`:24.1.0.255 (class 72), then the value (attribute 2) and
+    /// capture_time (attribute 5) of one value register 0-`channel`:24.2.y.255
+    /// (class 4) per captured record, with GET-Request-With-List.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ClientError` if:
+    /// - Not associated with the server
+    /// - Transport error occurs
+    /// - Response is not a capture definition or a capture time
+    /// - Data access error from server, e.g. a missing value register
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dlms_cosem::client::{ClientBuilder, ClientSettings};
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl dlms_cosem::transport::Transport for MyTransport {
+    /// #     type Error = ();
+    /// #     fn send(&mut self, _data: &[u8]) -> Result<(), ()> { Ok(()) }
+    /// #     fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+    /// # }
+    /// # let transport = MyTransport;
+    /// # let settings = ClientSettings::default();
+    /// # let mut client = ClientBuilder::new(transport, settings).build_with_heap(2048);
+    /// // Gas meter on M-Bus channel 1
+    /// for value in client.read_mbus_values(1).unwrap_or_default() {
+    ///     println!("{}: {:?} at {:?}", value.register, value.value, value.capture_time);
+    /// }
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub fn read_mbus_values(
+        &mut self,
+        channel: u8,
+    ) -> Result<Vec<MBusValue>, ClientError<T::Error>> {
+        let capture_definition = self.read(
+            MBUS_CLIENT_CLASS_ID,
+            ObisCode::new(0, channel, 24, 1, 0, 255),
+            MBUS_CLIENT_CAPTURE_DEFINITION_ATTRIBUTE_ID,
+            None,
+        )?;
+        let Data::Structure(elements) = capture_definition else {
+            return Err(ClientError::InvalidResponseData);
+        };
+        let registers: Vec<_> = (1..=u8::MAX)
+            .take(elements.len())
+            .map(|y| ObisCode::new(0, channel, 24, 2, y, 255))
+            .collect();
+        let requests: Vec<_> = registers
+            .iter()
+            .flat_map(|register| {
+                [
+                    (EXTENDED_REGISTER_CLASS_ID, *register, EXTENDED_REGISTER_VALUE_ATTRIBUTE_ID),
+                    (
+                        EXTENDED_REGISTER_CLASS_ID,
+                        *register,
+                        EXTENDED_REGISTER_CAPTURE_TIME_ATTRIBUTE_ID,
+                    ),
+                ]
+            })
+            .collect();
+        let results = self.read_multiple_chunked(&requests, None)?;
+        registers
+            .into_iter()
+            .zip(results.chunks(2))
+            .map(|(register, result)| match result {
+                [Ok(value), Ok(Data::DateTime(capture_time))] => Ok(MBusValue {
+                    register,
+                    value: value.clone(),
+                    capture_time: capture_time.clone(),
+                }),
+                [Err(error), _] | [_, Err(error)] => Err(ClientError::DataAccessError(*error)),
+                _ => Err(ClientError::InvalidResponseData),
+            })
+            .collect()
+    }
+
+    /// Transfer a firmware image to the Image Transfer object and activate it.
+    ///
+    /// Uses the Image Transfer object at OBIS code 0.0.44.0.0.255 (class 18):
+    /// 1. Reads the block size (attribute 2) and initiates the transfer
+    ///    (method 1). A transfer of the same image interrupted earlier resumes
+    ///    from `image_first_not_transferred_block_number` (attribute 4).
+    /// 2. Transfers the remaining blocks (method 2), then retransmits the
+    ///    blocks missing from `image_transferred_blocks_status` (attribute 3),
+    ///    at most [`IMAGE_TRANSFER_MAX_RETRANSMISSIONS`] times.
+    /// 3. Verifies (method 3) and activates (method 4) the image, polling
+    ///    `image_transfer_status` (attribute 6) while the meter reports the
+    ///    step in progress.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image to transfer
+    /// * `identifier` - Image identifier sent with the initiate method
+    ///
+    /// # Errors
+    ///
+    /// Returns `ClientError` if:
+    /// - Not associated with the server
+    /// - Transport error occurs
+    /// - Data access or action error from server
+    /// - Blocks are still missing after the retransmissions, or verification
+    ///   or activation fails (`ImageTransferFailed` with the final status)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dlms_cosem::client::{ClientBuilder, ClientSettings};
+    /// # #[derive(Debug)]
+    /// # struct MyTransport;
+    /// # impl dlms_cosem::transport::Transport for MyTransport {
+    /// #     type Error = ();
+    /// #     fn send(&mut self, _data: &[u8]) -> Result<(), ()> { Ok(()) }
+    /// #     fn recv(&mut self, _buffer: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+    /// # }
+    /// # let transport = MyTransport;
+    /// # let settings = ClientSettings::default();
+    /// # let mut client = ClientBuilder::new(transport, settings).build_with_heap(2048);
+    /// let image = std::fs::read("firmware-2.0.bin").unwrap();
+    /// client.upgrade_firmware(&image, b"FW-2.0");
+    /// ```
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    pub fn upgrade_firmware(
+        &mut self,
+        image: &[u8],
+        identifier: &[u8],
+    ) -> Result<(), ClientError<T::Error>> {
+        let block_size = match self.read(IMAGE_TRANSFER_CLASS_ID, IMAGE_TRANSFER, 2, None)? {
+            Data::DoubleLongUnsigned(size) if size > 0 => size as usize,
+            _ => return Err(ClientError::InvalidResponseData),
+        };
+        let blocks: Vec<&[u8]> = image.chunks(block_size).collect();
+
+        let initiate = Data::Structure(vec![
+            Data::OctetString(identifier.to_vec()),
+            Data::DoubleLongUnsigned(image.len() as u32),
+        ]);
+        self.method(IMAGE_TRANSFER_CLASS_ID, IMAGE_TRANSFER, 1, Some(initiate))?;
+
+        let first_missing = match self.read(IMAGE_TRANSFER_CLASS_ID, IMAGE_TRANSFER, 4, None)? {
+            Data::DoubleLongUnsigned(number) => number as usize,
+            _ => return Err(ClientError::InvalidResponseData),
+        };
+        let mut missing: Vec<usize> = (first_missing..blocks.len()).collect();
+        for round in 0..=IMAGE_TRANSFER_MAX_RETRANSMISSIONS {
+            for &number in &missing {
+                let block = Data::Structure(vec![
+                    Data::DoubleLongUnsigned(number as u32),
+                    Data::OctetString(blocks[number].to_vec()),
+                ]);
+                self.method(IMAGE_TRANSFER_CLASS_ID, IMAGE_TRANSFER, 2, Some(block))?;
+            }
+
+            let status = match self.read(IMAGE_TRANSFER_CLASS_ID, IMAGE_TRANSFER, 3, None)? {
+                Data::BitString(status) => status,
+                _ => return Err(ClientError::InvalidResponseData),
+            };
+            missing = (0..blocks.len())
+                .filter(|number| {
+                    status.get(number / 8).is_none_or(|byte| byte & (0x80 >> (number % 8)) == 0)
+                })
+                .collect();
+            if missing.is_empty() {
+                break;
+            }
+            if round == IMAGE_TRANSFER_MAX_RETRANSMISSIONS {
+                return Err(ClientError::ImageTransferFailed(self.read_image_transfer_status()?));
+            }
+        }
+
+        // Verification and activation may run in the background: the meter
+        // answers temporary-failure and reports progress in attribute 6.
+        self.image_transfer_step(3, 2, 3)?;
+        self.image_transfer_step(4, 5, 6)
+    }
+
+    /// Invokes an Image Transfer method, then polls image_transfer_status
+    /// while it is `pending` and checks that it ends as `successful`.
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    fn image_transfer_step(
+        &mut self,
+        method_id: i8,
+        pending: u8,
+        successful: u8,
+    ) -> Result<(), ClientError<T::Error>> {
+        match self.method(
+            IMAGE_TRANSFER_CLASS_ID,
+            IMAGE_TRANSFER,
+            method_id,
+            Some(Data::Integer(0)),
+        ) {
+            Ok(_) | Err(ClientError::ActionError(_)) => {}
+            Err(err) => return Err(err),
+        }
+        let mut status = self.read_image_transfer_status()?;
+        for _ in 0..IMAGE_TRANSFER_MAX_STATUS_POLLS {
+            if status != pending {
+                break;
+            }
+            #[cfg(feature = "std")]
+            std::thread::sleep(std::time::Duration::from_millis(
+                crate::client::IMAGE_TRANSFER_STATUS_POLL_INTERVAL_MS,
+            ));
+            status = self.read_image_transfer_status()?;
+        }
+        if status == successful { Ok(()) } else { Err(ClientError::ImageTransferFailed(status)) }
+    }
+
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    fn read_image_transfer_status(&mut self) -> Result<u8, ClientError<T::Error>> {
+        match self.read(IMAGE_TRANSFER_CLASS_ID, IMAGE_TRANSFER, 6, None)? {
+            Data::Enum(status) => Ok(status),
+            _ => Err(ClientError::InvalidResponseData),
+        }
+    }
+
     /// Read multiple attributes with automatic chunking.
     ///
     /// This method splits large bulk read operations into smaller chunks based on
@@ -2892,4 +3562,417 @@ mod tests {
         let results = results.unwrap();
         assert_eq!(results.len(), 25);
     }
+
+    /// Transport answering requests from a [`DlmsServer`](crate::server::DlmsServer).
+    ///
+    /// Image blocks whose number is in `lost` are lost once: the client gets a
+    /// success response but the block never reaches the server.
+    #[cfg(feature = "server")]
+    #[derive(Debug)]
+    struct ServerTransport {
+        server: crate::server::DlmsServer,
+        session: crate::server::ServerSession,
+        response: Vec<u8>,
+        lost: Vec<u32>,
+        blocks_sent: Vec<u32>,
+    }
+
+    #[cfg(feature = "server")]
+    impl Transport for ServerTransport {
+        type Error = ();
+
+        fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+            if let Ok((_, ActionRequest::Normal(request))) = ActionRequest::parse(data)
+                && request.method_id == 2
+                && let Some(Data::Structure(block)) = &request.method_invocation_parameters
+                && let Some(Data::DoubleLongUnsigned(number)) = block.first()
+            {
+                self.blocks_sent.push(*number);
+                if let Some(index) = self.lost.iter().position(|lost| lost == number) {
+                    self.lost.remove(index);
+                    let response = ActionResponseNormal {
+                        invoke_id: request.invoke_id,
+                        result: crate::action::ActionResult::Success(None),
+                    };
+                    self.response = ActionResponse::Normal(response).encode();
+                    return Ok(());
+                }
+            }
+            self.response = self.server.handle(&mut self.session, data).map_err(|_| ())?;
+            Ok(())
+        }
+
+        fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+            buffer[..self.response.len()].copy_from_slice(&self.response);
+            Ok(self.response.len())
+        }
+    }
+
+    #[cfg(feature = "server")]
+    fn server_client(
+        object: impl crate::cosem::CosemObject + Send + 'static,
+        lost: Vec<u32>,
+    ) -> DlmsClient<ServerTransport, Vec<u8>> {
+        let transport = ServerTransport {
+            server: crate::server::DlmsServer::new(Default::default()).with_object(object),
+            session: crate::server::ServerSession::new(),
+            response: Vec::new(),
+            lost,
+            blocks_sent: Vec::new(),
+        };
+        let mut client =
+            ClientBuilder::new(transport, ClientSettings::default()).build_with_heap(2048);
+        client.connect().unwrap();
+        client
+    }
+
+    #[cfg(feature = "server")]
+    fn image_transfer_attribute(client: &DlmsClient<ServerTransport, Vec<u8>>, id: i8) -> Data {
+        let objects = client.transport().server.objects();
+        objects.get(IMAGE_TRANSFER_CLASS_ID, &IMAGE_TRANSFER).unwrap().get_attribute(id).unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn test_upgrade_firmware_retransmits_lost_blocks() {
+        use crate::cosem::image_transfer::ImageTransfer;
+
+        let image: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut client = server_client(ImageTransfer::new(200), vec![1, 3]);
+        client.upgrade_firmware(&image, b"FW-2.0").unwrap();
+
+        assert_eq!(client.transport().blocks_sent, vec![0, 1, 2, 3, 4, 1, 3]);
+        assert_eq!(image_transfer_attribute(&client, 6), Data::Enum(6));
+        let info = image_transfer_attribute(&client, 7);
+        let Data::Structure(info) = info else { panic!("unexpected info {:?}", info) };
+        assert_eq!(
+            info,
+            vec![Data::Structure(vec![
+                Data::DoubleLongUnsigned(1000),
+                Data::OctetString(b"FW-2.0".to_vec()),
+                Data::OctetString(vec![]),
+            ])]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn test_upgrade_firmware_resumes_transfer() {
+        use crate::cosem::CosemObject;
+        use crate::cosem::image_transfer::ImageTransfer;
+
+        let image = [0x5A; 50];
+        let mut image_transfer = ImageTransfer::new(10);
+        let initiate = Data::Structure(vec![
+            Data::OctetString(b"FW-2.0".to_vec()),
+            Data::DoubleLongUnsigned(50),
+        ]);
+        image_transfer.invoke_method(1, Some(initiate)).unwrap();
+        for number in [0, 1, 3] {
+            let block = Data::Structure(vec![
+                Data::DoubleLongUnsigned(number),
+                Data::OctetString(image[..10].to_vec()),
+            ]);
+            image_transfer.invoke_method(2, Some(block)).unwrap();
+        }
+
+        let mut client = server_client(image_transfer, vec![]);
+        client.upgrade_firmware(&image, b"FW-2.0").unwrap();
+
+        // Resumes from block 2; block 3 is already transferred but is resent
+        // with the others from the first block not transferred on.
+        assert_eq!(client.transport().blocks_sent, vec![2, 3, 4]);
+        assert_eq!(image_transfer_attribute(&client, 6), Data::Enum(6));
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn test_upgrade_firmware_verification_failed() {
+        use crate::cosem::image_transfer::ImageTransfer;
+
+        let image_transfer =
+            ImageTransfer::new(64).with_verifier(|identifier, _| identifier == b"FW");
+        let mut client = server_client(image_transfer, vec![]);
+        let result = client.upgrade_firmware(&[0; 100], b"FW-2.0");
+
+        assert!(matches!(result, Err(ClientError::ImageTransferFailed(4))));
+        assert_eq!(image_transfer_attribute(&client, 7), Data::Structure(vec![]));
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn test_upgrade_firmware_gives_up_on_missing_blocks() {
+        use crate::cosem::image_transfer::ImageTransfer;
+
+        let lost = vec![0; IMAGE_TRANSFER_MAX_RETRANSMISSIONS + 1];
+        let mut client = server_client(ImageTransfer::new(64), lost);
+        let result = client.upgrade_firmware(&[0; 100], b"FW-2.0");
+
+        assert!(matches!(result, Err(ClientError::ImageTransferFailed(1))));
+        assert_eq!(client.transport().blocks_sent, vec![0, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn test_remote_disconnect_and_reconnect() {
+        use crate::cosem::disconnect_control::DisconnectControl;
+
+        for (mode, reconnected) in
+            [(1, RelayState::Connected), (2, RelayState::ReadyForReconnection)]
+        {
+            let mut client = server_client(DisconnectControl::new(mode), vec![]);
+            assert_eq!(client.relay_state().unwrap(), RelayState::Connected);
+            client.remote_disconnect().unwrap();
+            assert_eq!(client.relay_state().unwrap(), RelayState::Disconnected);
+            client.remote_reconnect().unwrap();
+            assert_eq!(client.relay_state().unwrap(), reconnected);
+        }
+
+        let mut client = server_client(DisconnectControl::new(0), vec![]);
+        assert!(matches!(
+            client.remote_disconnect(),
+            Err(ClientError::ActionError(crate::action::ActionResult::ReadWriteDenied))
+        ));
+    }
+
+    #[test]
+    #[cfg(all(feature = "server", feature = "persistence"))]
+    fn test_rotate_key() {
+        use crate::SecuritySuite;
+        use crate::cosem::security_setup::SecuritySetup;
+
+        let logical_name = ObisCode::new(0, 0, 43, 0, 1, 255);
+        let security_setup = SecuritySetup::new(logical_name, SecuritySuite::V0, *b"MTR00001")
+            .with_key(KeyId::MasterKey, &[0x0F; 16])
+            .unwrap()
+            .with_key(KeyId::GlobalUnicastEncryptionKey, &[0x11; 16])
+            .unwrap();
+        let mut client = server_client(security_setup, vec![]);
+        let key = |client: &DlmsClient<ServerTransport, Vec<u8>>, key_id| {
+            let objects = client.transport().server.objects();
+            let object = objects.get(SECURITY_SETUP_CLASS_ID, &logical_name).unwrap();
+            let persisted = object.save_state().unwrap().attributes[3].clone();
+            let Data::Structure(keys) = persisted else {
+                panic!("unexpected keys {:?}", persisted)
+            };
+            keys.into_iter().find_map(|key| match key {
+                Data::Structure(fields) if fields[0] == Data::Enum(key_id as u8) => {
+                    Some(fields[1].clone())
+                }
+                _ => None,
+            })
+        };
+        let global_key = KeyId::GlobalUnicastEncryptionKey;
+
+        client
+            .rotate_key(logical_name, &[0x0F; 16], global_key, &[0x11; 16], &[0x12; 16], |_| Ok(()))
+            .unwrap();
+        assert_eq!(key(&client, global_key), Some(Data::OctetString(vec![0x12; 16])));
+
+        // The verification fails: the current key is restored.
+        let result = client.rotate_key(
+            logical_name,
+            &[0x0F; 16],
+            global_key,
+            &[0x12; 16],
+            &[0x13; 16],
+            |client| {
+                assert_eq!(key(client, global_key), Some(Data::OctetString(vec![0x13; 16])));
+                Err(ClientError::InvalidResponseData)
+            },
+        );
+        assert!(matches!(result, Err(ClientError::KeyVerificationFailed)));
+        assert_eq!(key(&client, global_key), Some(Data::OctetString(vec![0x12; 16])));
+
+        // A master key rotation is rolled back under the new master key.
+        let result = client.rotate_key(
+            logical_name,
+            &[0x0F; 16],
+            KeyId::MasterKey,
+            &[0x0F; 16],
+            &[0xF0; 16],
+            |_| Err(ClientError::InvalidResponseData),
+        );
+        assert!(matches!(result, Err(ClientError::KeyVerificationFailed)));
+        assert_eq!(key(&client, KeyId::MasterKey), Some(Data::OctetString(vec![0x0F; 16])));
+
+        // A wrong KEK is rejected by the meter, before any verification.
+        assert!(matches!(
+            client.transfer_keys(logical_name, &[0xAA; 16], &[(global_key, &[0x14; 16])]),
+            Err(ClientError::ActionError(crate::action::ActionResult::OtherReason))
+        ));
+        assert!(matches!(
+            client.transfer_keys(logical_name, &[0x0F; 16], &[(global_key, &[0x14; 12])]),
+            Err(ClientError::InvalidKey(KeyWrapError::InvalidKeyLength))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn test_enter_token() {
+        use crate::cosem::account::{Account, PaymentMode};
+        use crate::cosem::credit::{Credit, CreditType};
+        use crate::cosem::token_gateway::{DecodedToken, TokenAction, TokenDecoder, TokenGateway};
+        use crate::token::TokenStatusCode;
+
+        /// Accepts 20-digit tokens worth their last four digits.
+        #[derive(Debug)]
+        struct LastDigits;
+
+        impl TokenDecoder for LastDigits {
+            fn decode(&mut self, token: &[u8]) -> Result<DecodedToken, TokenStatus> {
+                core::str::from_utf8(token)
+                    .ok()
+                    .filter(|digits| digits.len() == 20)
+                    .and_then(|digits| digits[16..].parse().ok())
+                    .map(|amount| DecodedToken {
+                        action: TokenAction::AddCredit(amount),
+                        description: vec![],
+                    })
+                    .ok_or(TokenStatus::new(TokenStatusCode::FormatFailure))
+            }
+        }
+
+        let token_gateway = ObisCode::new(0, 0, 19, 40, 0, 255);
+        let credit = ObisCode::new(0, 0, 19, 10, 0, 255);
+        let mut client = server_client(
+            TokenGateway::new(token_gateway).with_decoder(Box::new(LastDigits)),
+            vec![],
+        );
+        let objects = client.transport_mut().server.objects_mut();
+        objects.insert(Box::new(
+            Account::new(ObisCode::new(0, 0, 19, 0, 0, 255), PaymentMode::Prepayment)
+                .with_credit(credit, 100),
+        ));
+        objects.insert(Box::new(Credit::new(credit, CreditType::Token, 1)));
+
+        let status = client.enter_token(token_gateway, b"56781234987612341500").unwrap();
+        assert_eq!(status, TokenStatus::new(TokenStatusCode::ExecutionOk));
+        let objects = client.transport().server.objects();
+        assert_eq!(objects.get(112, &credit).unwrap().get_attribute(2), Ok(Data::DoubleLong(1500)));
+
+        let status = client.enter_token(token_gateway, b"1500").unwrap();
+        assert_eq!(status.code, TokenStatusCode::FormatFailure);
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn test_read_mbus_values() {
+        use crate::cosem::activity_calendar::NOT_SCHEDULED;
+        use crate::cosem::extended_register::ExtendedRegister;
+        use crate::cosem::mbus_client::{
+            CaptureDefinitionElement, MBusClient, MBusCommand, MBusIdentification, MBusPort,
+            MBusRecord, MBusResponse,
+        };
+        use crate::unit::{ScalerUnit, Unit};
+
+        /// A gas meter reading 1234.567 m³.
+        #[derive(Debug)]
+        struct GasMeter;
+
+        impl MBusPort for GasMeter {
+            fn install(&mut self, _primary_address: u8) -> Option<MBusIdentification> {
+                Some(MBusIdentification {
+                    identification_number: 0x1234_5678,
+                    manufacturer_id: 0x2C2D,
+                    version: 1,
+                    device_type: 3,
+                })
+            }
+
+            fn read(&mut self, _primary_address: u8, _key: Option<&[u8]>) -> Option<MBusResponse> {
+                let volume = Data::DoubleLongUnsigned(1_234_567);
+                Some(MBusResponse {
+                    access_number: 1,
+                    status: 0,
+                    encrypted: false,
+                    records: vec![MBusRecord { dib: vec![0x0C], vib: vec![0x13], value: volume }],
+                })
+            }
+
+            fn send(&mut self, _primary_address: u8, _command: MBusCommand) -> bool {
+                true
+            }
+        }
+
+        let mbus_client = ObisCode::new(0, 1, 24, 1, 0, 255);
+        let volume = ObisCode::new(0, 1, 24, 2, 1, 255);
+        let mut client = server_client(
+            MBusClient::new(mbus_client).with_port(Box::new(GasMeter)).with_capture_definition(
+                vec![CaptureDefinitionElement {
+                    data_information_block: vec![0x0C],
+                    value_information_block: vec![0x13],
+                }],
+            ),
+            vec![],
+        );
+        let objects = client.transport_mut().server.objects_mut();
+        objects.insert(Box::new(ExtendedRegister::new(
+            volume,
+            Data::DoubleLongUnsigned(0),
+            ScalerUnit { scaler: -3, unit: Unit::CubicMeter },
+            Data::Null,
+            NOT_SCHEDULED,
+        )));
+        objects.tick(1_738_368_000, &mut |_| {});
+        objects.invoke_method(72, &mbus_client, 1, Some(Data::Unsigned(5))).unwrap();
+        objects.invoke_method(72, &mbus_client, 3, Some(Data::Integer(0))).unwrap();
+
+        let values = client.read_mbus_values(1).unwrap();
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].register, volume);
+        assert_eq!(values[0].value, Data::DoubleLongUnsigned(1_234_567));
+        assert_eq!(values[0].capture_time, crate::cosem::push_setup::date_time_at(1_738_368_000));
+
+        assert!(matches!(
+            client.read_mbus_values(2),
+            Err(ClientError::DataAccessError(DataAccessResult::ObjectUndefined))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "server")]
+    fn test_write_passive_calendar() {
+        use crate::calendar::DayProfile;
+        use crate::cosem::activity_calendar::ActivityCalendar;
+        use crate::data::{Date, Time};
+
+        let script = ObisCode::new(0, 0, 10, 0, 100, 255);
+        let at = |hour| Time::new(Some(hour), Some(0), Some(0), Some(0));
+        let every_year = DateTime::new(Date::new(0xFFFF, 1, 1, 0xFF), at(0), None, None);
+        let calendar = Calendar::new(b"TOU".to_vec())
+            .with_season(b"ALL".to_vec(), every_year, b"WEEK".to_vec())
+            .with_week(b"WEEK".to_vec(), [1, 1, 1, 1, 1, 2, 2])
+            .with_day(DayProfile::new(1).with_action(at(7), script, 1).with_action(
+                at(22),
+                script,
+                2,
+            ))
+            .with_day(DayProfile::new(2).with_action(at(0), script, 2));
+
+        let mut client = server_client(ActivityCalendar::new(), vec![]);
+        let activation = DateTime::new(Date::new(2030, 1, 1, 0xFF), at(0), None, None);
+        client.write_passive_calendar(&calendar, Some(activation.clone())).unwrap();
+        let passive = |client: &DlmsClient<ServerTransport, Vec<u8>>, id| {
+            let objects = client.transport().server.objects();
+            objects.get(ACTIVITY_CALENDAR_CLASS_ID, &ACTIVITY_CALENDAR).unwrap().get_attribute(id)
+        };
+        assert_eq!(passive(&client, 9), Ok(calendar.day_profile_table_data()));
+        assert_eq!(passive(&client, 10), Ok(Data::DateTime(activation)));
+
+        client.activate_passive_calendar().unwrap();
+        assert_eq!(passive(&client, 7), passive(&client, 3));
+        assert_eq!(passive(&client, 2), Ok(Data::OctetString(b"TOU".to_vec())));
+
+        // Unsorted switch times are rejected before anything is sent.
+        let mut unsorted = calendar.clone();
+        unsorted.calendar_name = b"BROKEN".to_vec();
+        unsorted.day_profile_table[0].day_schedule.reverse();
+        let name = passive(&client, 6);
+        assert!(matches!(
+            client.write_passive_calendar(&unsorted, None),
+            Err(ClientError::InvalidCalendar(CalendarError::UnsortedSwitchTimes(1)))
+        ));
+        assert_eq!(passive(&client, 6), name);
+    }
 }
@@ -74,16 +74,62 @@ use crate::action::ActionResult;
 use crate::get::DataAccessResult;
 use crate::{Data, ObisCode};
 
+pub mod account;
+pub mod activity_calendar;
+#[cfg(feature = "association")]
+pub mod association_ln;
+pub mod capture_scheduler;
+pub mod charge;
 pub mod clock;
+pub mod credit;
 pub mod data;
 pub mod demand_register;
+pub mod disconnect_control;
 pub mod extended_register;
+pub mod iec_hdlc_setup;
+pub mod image_transfer;
+pub mod ipv4_setup;
+pub mod ipv6_setup;
+pub mod limiter;
+pub mod mbus_client;
+#[cfg(feature = "persistence")]
+pub mod persistence;
 pub mod profile_generic;
+pub mod push_setup;
 pub mod register;
+pub mod register_monitor;
+pub mod registry;
+pub mod sap_assignment;
+pub mod schedule;
+pub mod script_scheduler;
+pub mod script_table;
+pub mod security_setup;
+pub mod single_action_schedule;
+pub mod tcp_udp_setup;
+pub mod token_gateway;
 
 // Re-export commonly used types
 pub use crate::selective_access::CaptureObjectDefinition;
+pub use account::Account;
+#[cfg(feature = "association")]
+pub use association_ln::{AccessRights, AssociationLn, AssociationStatus, ObjectListElement};
+pub use charge::Charge;
+pub use credit::Credit;
+pub use iec_hdlc_setup::IecHdlcSetup;
+pub use ipv4_setup::Ipv4Setup;
+pub use ipv6_setup::Ipv6Setup;
+pub use limiter::Limiter;
+pub use mbus_client::MBusClient;
 pub use profile_generic::{ProfileGeneric, SortMethod};
+pub use push_setup::PushSetup;
+pub use register_monitor::RegisterMonitor;
+pub use registry::{BoxedCosemObject, ObjectRegistry};
+pub use schedule::Schedule;
+pub use script_table::ScriptTable;
+pub use security_setup::SecuritySetup;
+pub use single_action_schedule::SingleActionSchedule;
+pub use tcp_udp_setup::TcpUdpSetup;
+pub use token_gateway::TokenGateway;
 
 /// Core trait for all COSEM interface class objects.
 ///
@@ -169,6 +215,25 @@ pub trait CosemObject {
     /// ```
     fn get_attribute(&self, attribute_id: i8) -> Result<Data, DataAccessResult>;
 
+    /// Reads an attribute with selective access.
+    ///
+    /// `selector` and `parameters` are the access selector and its parameters
+    /// as received in the GET request (e.g. selector 1 with an encoded
+    /// [`RangeDescriptor`](crate::selective_access::RangeDescriptor)).
+    ///
+    /// The default implementation rejects selective access with
+    /// `ScopeOfAccessViolated`; objects that support it (such as
+    /// [`ProfileGeneric`]) override this method.
+    fn get_attribute_selective(
+        &self,
+        attribute_id: i8,
+        selector: u8,
+        parameters: &Data,
+    ) -> Result<Data, DataAccessResult> {
+        let _ = (attribute_id, selector, parameters);
+        Err(DataAccessResult::ScopeOfAccessViolated)
+    }
+
     /// Writes an attribute value.
     ///
     /// # Arguments
@@ -252,6 +317,65 @@ pub trait CosemObject {
         method_id: i8,
         params: Option<Data>,
     ) -> Result<Option<Data>, ActionResult>;
+
+    /// Invokes a method that may access the other objects of the logical device.
+    ///
+    /// `objects` holds the other objects of the logical device (see
+    /// [`ObjectRegistry::invoke_method`]). Objects whose methods depend on
+    /// other objects, such as [`ProfileGeneric`] capturing its columns or a
+    /// script table executing its actions, override this method; the default
+    /// ignores `objects` and calls [`invoke_method`](Self::invoke_method).
+    fn invoke_method_with_objects(
+        &mut self,
+        objects: &mut ObjectRegistry,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        let _ = objects;
+        self.invoke_method(method_id, params)
+    }
+
+    /// Evaluates value or time driven behaviour against the other objects of
+    /// the logical device at `now` (local seconds since 1970-01-01).
+    ///
+    /// Scripts to execute are passed to `executor` (see
+    /// [`ObjectRegistry::tick`]). Threshold objects such as
+    /// [`RegisterMonitor`] and [`Limiter`] override this method; the default
+    /// does nothing.
+    fn tick(
+        &mut self,
+        now: u32,
+        objects: &ObjectRegistry,
+        executor: &mut dyn FnMut(&script_table::ScriptReference),
+    ) {
+        let _ = (now, objects, executor);
+    }
+
+    /// Returns the state to persist, including internal fields that are not
+    /// attributes (see [`persistence`]).
+    ///
+    /// The default returns `None`: the object is not persisted.
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<persistence::ObjectState> {
+        None
+    }
+
+    /// Restores a state returned by [`save_state`](Self::save_state).
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: persistence::ObjectState) -> Result<(), DataAccessResult> {
+        let _ = state;
+        Err(DataAccessResult::ReadWriteDenied)
+    }
+
+    /// Adds an entry to the buffer of a profile, as a capture would.
+    ///
+    /// Replays entries captured after a snapshot; the default fails with
+    /// `ObjectClassInconsistent` for classes without a buffer.
+    #[cfg(feature = "persistence")]
+    fn append_entry(&mut self, entry: alloc::vec::Vec<Data>) -> Result<(), DataAccessResult> {
+        let _ = entry;
+        Err(DataAccessResult::ObjectClassInconsistent)
+    }
 }
 
 /// Represents a COSEM attribute with its ID, access rights, and current value.
@@ -420,6 +544,44 @@ impl AttributeAccess {
     pub const fn is_no_access(&self) -> bool {
         self.0 == 0
     }
+
+    /// Converts to the `attribute_access_mode` enum used in Association object lists.
+    ///
+    /// Values: 0 no_access, 1 read_only, 2 write_only, 3 read_and_write,
+    /// 4-6 authenticated read_only / write_only / read_and_write.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use dlms_cosem::cosem::AttributeAccess;
+    ///
+    /// let access = AttributeAccess::READ_ONLY | AttributeAccess::AUTHENTICATED_READ;
+    /// assert_eq!(access.access_mode(), 4);
+    /// assert_eq!(AttributeAccess::from_access_mode(4), Some(access));
+    /// ```
+    pub const fn access_mode(&self) -> u8 {
+        let mode = self.0 & Self::READ_WRITE.0;
+        if mode != 0 && self.0 & (Self::AUTHENTICATED_READ.0 | Self::AUTHENTICATED_WRITE.0) != 0 {
+            mode + 3
+        } else {
+            mode
+        }
+    }
+
+    /// Creates access rights from an `attribute_access_mode` enum value.
+    ///
+    /// Returns `None` for values outside 0-6.
+    pub const fn from_access_mode(mode: u8) -> Option<Self> {
+        match mode {
+            0..=3 => Some(AttributeAccess(mode)),
+            4 => Some(AttributeAccess(Self::READ_ONLY.0 | Self::AUTHENTICATED_READ.0)),
+            5 => Some(AttributeAccess(Self::WRITE_ONLY.0 | Self::AUTHENTICATED_WRITE.0)),
+            6 => Some(AttributeAccess(
+                Self::READ_WRITE.0 | Self::AUTHENTICATED_READ.0 | Self::AUTHENTICATED_WRITE.0,
+            )),
+            _ => None,
+        }
+    }
 }
 
 impl core::ops::BitOr for AttributeAccess {
@@ -558,6 +720,27 @@ impl MethodAccess {
     pub const fn is_no_access(&self) -> bool {
         self.0 == 0
     }
+
+    /// Converts to the `method_access_mode` enum used in Association object lists.
+    ///
+    /// Values: 0 no_access, 1 access, 2 authenticated_access.
+    pub const fn access_mode(&self) -> u8 {
+        if self.requires_authentication() {
+            2
+        } else {
+            self.0 & Self::ACCESS.0
+        }
+    }
+
+    /// Creates access rights from a `method_access_mode` enum value.
+    ///
+    /// Returns `None` for values outside 0-2.
+    pub const fn from_access_mode(mode: u8) -> Option<Self> {
+        match mode {
+            0..=2 => Some(MethodAccess(mode)),
+            _ => None,
+        }
+    }
 }
 
 impl core::ops::BitOr for MethodAccess {
@@ -0,0 +1,838 @@
+//! COSEM Interface Class 111: Account
+//!
+//! The Account is the hub of payment metering: it references the Credit
+//! objects the customer has, the Charge objects collecting money from them,
+//! and tells how payments received as tokens are shared among the credits.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.19.0.0.255)
+//! - Attribute 2: `account_mode_and_status` - `{payment_mode,
+//!   account_status}` (read-only)
+//! - Attribute 3: `current_credit_in_use` - Index (1-based) in
+//!   `credit_reference_list`, 0 if none (read-only)
+//! - Attribute 4: `current_credit_status` - Bit string, see [`IN_CREDIT`],
+//!   [`LOW_CREDIT`], [`NEXT_CREDIT_SELECTABLE`] and [`OUT_OF_CREDIT`]
+//!   (read-only)
+//! - Attribute 5: `available_credit` - (read-only)
+//! - Attribute 6: `amount_to_clear` - (read-only)
+//! - Attribute 7: `clearance_threshold`
+//! - Attribute 8: `aggregated_debt` - (read-only)
+//! - Attribute 9: `credit_reference_list` - Array of logical names
+//! - Attribute 10: `charge_reference_list` - Array of logical names
+//! - Attribute 11: `credit_charge_configuration` - Array of
+//!   `{credit_reference, charge_reference, collection_configuration}`
+//! - Attribute 12: `token_gateway_configuration` - Array of
+//!   `{credit_reference, token_proportion}`
+//! - Attribute 13: `account_activation_time`
+//! - Attribute 14: `account_closure_time`
+//! - Attribute 15: `currency` - `{name, scale, unit}`
+//! - Attribute 16: `low_credit_threshold`
+//! - Attribute 17: `next_credit_available_threshold`
+//! - Attribute 18: `max_provision`
+//! - Attribute 19: `max_provision_period` - Seconds
+//!
+//! ## Methods
+//! - Method 1: `activate_account(integer)` - New (inactive) to active
+//! - Method 2: `close_account(integer)` - Active to closed
+//! - Method 3: `reset_account(integer)` - Back to new (inactive)
+//!
+//! Transitions from other states are denied with `ReadWriteDenied`.
+//!
+//! ## Credits
+//!
+//! The credit in use is the usable credit (enabled, selected or in use, and
+//! above its limit) of the highest priority, the first referenced one on a
+//! tie. [`ObjectRegistry::tick`] reads the referenced credits and charges to
+//! update the credit in use, the available credit (the sum of the usable
+//! credits), the credit status and the aggregated debt (the sum of the
+//! amounts remaining of the charges); it also stamps the activation and
+//! closure. Collections of the [`Charge`](crate::cosem::charge) objects are
+//! debited from the credit in use, or from the first credit when none is
+//! usable.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::account::{Account, AccountStatus, PaymentMode, OUT_OF_CREDIT};
+//! use dlms_cosem::cosem::credit::{Credit, CreditType};
+//! use dlms_cosem::cosem::{CosemObject, ObjectRegistry};
+//! use dlms_cosem::ObisCode;
+//!
+//! let token_credit = ObisCode::new(0, 0, 19, 10, 0, 255);
+//! let mut account = Account::new(ObisCode::new(0, 0, 19, 0, 0, 255), PaymentMode::Prepayment)
+//!     .with_credit(token_credit, 100);
+//! let objects = ObjectRegistry::new().with_object(Credit::new(token_credit, CreditType::Token, 1));
+//!
+//! account.activate_account().unwrap();
+//! account.tick(1_738_368_000, &objects, &mut |_| {});
+//! assert_eq!(account.account_status, AccountStatus::Active);
+//! assert_eq!(account.available_credit, 0);
+//! assert_eq!(account.current_credit_status & OUT_OF_CREDIT, OUT_OF_CREDIT);
+//! ```
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::action::ActionResult;
+use crate::cosem::activity_calendar::NOT_SCHEDULED;
+use crate::cosem::charge::CHARGE_CLASS_ID;
+use crate::cosem::credit::{CREDIT_CLASS_ID, CreditStatus};
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::cosem::push_setup::date_time_at;
+use crate::cosem::script_table::ScriptReference;
+use crate::cosem::{CosemObject, ObjectRegistry};
+use crate::data::{Data, DateTime};
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Account interface class id
+pub(crate) const ACCOUNT_CLASS_ID: u16 = 111;
+
+/// `current_credit_status` flag: some credit is available.
+pub const IN_CREDIT: u8 = 0x80;
+/// `current_credit_status` flag: the available credit is at or below the
+/// low credit threshold.
+pub const LOW_CREDIT: u8 = 0x80 >> 1;
+/// `current_credit_status` flag: a credit may be selected, e.g. emergency
+/// credit.
+pub const NEXT_CREDIT_SELECTABLE: u8 = 0x80 >> 3;
+/// `current_credit_status` flag: no credit is available.
+pub const OUT_OF_CREDIT: u8 = 0x80 >> 6;
+
+/// Payment mode (attribute 2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PaymentMode {
+    /// Billed afterwards
+    Credit = 1,
+    /// Paid in advance
+    Prepayment = 2,
+}
+
+impl PaymentMode {
+    /// Converts a payment_mode enum value.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            1 => Ok(Self::Credit),
+            2 => Ok(Self::Prepayment),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Account status (attribute 2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AccountStatus {
+    /// Not activated yet
+    NewInactive = 1,
+    /// In operation
+    Active = 2,
+    /// Closed
+    Closed = 3,
+}
+
+impl AccountStatus {
+    /// Converts an account_status enum value.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            1 => Ok(Self::NewInactive),
+            2 => Ok(Self::Active),
+            3 => Ok(Self::Closed),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Link between a credit and a charge (element of attribute 11).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CreditChargeConfiguration {
+    /// Logical name of the Credit
+    pub credit_reference: ObisCode,
+    /// Logical name of the Charge
+    pub charge_reference: ObisCode,
+    /// Bit string: collect when supply disconnected, in load limiting
+    /// periods, in friendly credit periods
+    pub collection_configuration: u8,
+}
+
+/// Share of the token payments for a credit (element of attribute 12).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TokenGatewayConfiguration {
+    /// Logical name of the Credit
+    pub credit_reference: ObisCode,
+    /// Percentage of the payments
+    pub token_proportion: u8,
+}
+
+/// Unit of the amounts of an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CurrencyUnit {
+    /// Time
+    Time = 0,
+    /// Consumption, e.g. kWh
+    Consumption = 1,
+    /// Money
+    Monetary = 2,
+}
+
+/// Currency of the amounts (attribute 15).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Currency {
+    /// Name, e.g. ISO 4217 "EUR"
+    pub name: String,
+    /// Power of ten of the amounts, e.g. -2 for cents
+    pub scale: i8,
+    /// Unit
+    pub unit: CurrencyUnit,
+}
+
+impl Currency {
+    fn to_data(&self) -> Data {
+        Data::Structure(vec![
+            Data::Utf8String(self.name.clone()),
+            Data::Integer(self.scale),
+            Data::Enum(self.unit as u8),
+        ])
+    }
+
+    fn from_data(data: &Data) -> Result<Self, DataAccessResult> {
+        match data {
+            Data::Structure(fields) => match fields.as_slice() {
+                [Data::Utf8String(name), Data::Integer(scale), Data::Enum(unit)] => Ok(Self {
+                    name: name.clone(),
+                    scale: *scale,
+                    unit: match unit {
+                        0 => CurrencyUnit::Time,
+                        1 => CurrencyUnit::Consumption,
+                        2 => CurrencyUnit::Monetary,
+                        _ => return Err(DataAccessResult::TypeUnmatched),
+                    },
+                }),
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Account object - COSEM Interface Class 111
+///
+/// Reference: Blue Book 4.6.2
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Account {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Payment mode
+    pub payment_mode: PaymentMode,
+    /// Attribute 2: Status
+    pub account_status: AccountStatus,
+    /// Attribute 3: Index (1-based) of the credit in use, 0 if none
+    pub current_credit_in_use: u8,
+    /// Attribute 4: Credit status flags
+    pub current_credit_status: u8,
+    /// Attribute 5: Sum of the usable credits
+    pub available_credit: i32,
+    /// Attribute 6: Amount to clear before the account may be closed
+    pub amount_to_clear: i32,
+    /// Attribute 7: Clearance threshold
+    pub clearance_threshold: i32,
+    /// Attribute 8: Sum of the amounts remaining of the charges
+    pub aggregated_debt: i32,
+    /// Attribute 9: Credits
+    pub credit_reference_list: Vec<ObisCode>,
+    /// Attribute 10: Charges
+    pub charge_reference_list: Vec<ObisCode>,
+    /// Attribute 11: Charges collected from each credit
+    pub credit_charge_configuration: Vec<CreditChargeConfiguration>,
+    /// Attribute 12: Shares of the token payments
+    pub token_gateway_configuration: Vec<TokenGatewayConfiguration>,
+    /// Attribute 13: Local time of the activation
+    pub account_activation_time: DateTime,
+    /// Attribute 14: Local time of the closure
+    pub account_closure_time: DateTime,
+    /// Attribute 15: Currency
+    pub currency: Currency,
+    /// Attribute 16: Available credit at or below which credit is low
+    pub low_credit_threshold: i32,
+    /// Attribute 17: Threshold from which the next credit becomes available
+    pub next_credit_available_threshold: i32,
+    /// Attribute 18: Maximum credit provided per period
+    pub max_provision: u16,
+    /// Attribute 19: Period of `max_provision` in seconds
+    pub max_provision_period: u32,
+    /// Time of the last tick
+    now: Option<u32>,
+}
+
+impl Account {
+    /// Creates a new (inactive) account without credits nor charges, in an
+    /// unnamed monetary currency.
+    pub fn new(logical_name: ObisCode, payment_mode: PaymentMode) -> Self {
+        Self {
+            logical_name,
+            payment_mode,
+            account_status: AccountStatus::NewInactive,
+            current_credit_in_use: 0,
+            current_credit_status: 0,
+            available_credit: 0,
+            amount_to_clear: 0,
+            clearance_threshold: 0,
+            aggregated_debt: 0,
+            credit_reference_list: Vec::new(),
+            charge_reference_list: Vec::new(),
+            credit_charge_configuration: Vec::new(),
+            token_gateway_configuration: Vec::new(),
+            account_activation_time: NOT_SCHEDULED,
+            account_closure_time: NOT_SCHEDULED,
+            currency: Currency { name: String::new(), scale: 0, unit: CurrencyUnit::Monetary },
+            low_credit_threshold: 0,
+            next_credit_available_threshold: 0,
+            max_provision: 0,
+            max_provision_period: 0,
+            now: None,
+        }
+    }
+
+    /// Adds a credit receiving `token_proportion` percent of the token
+    /// payments (builder style).
+    pub fn with_credit(mut self, credit: ObisCode, token_proportion: u8) -> Self {
+        self.credit_reference_list.push(credit);
+        if token_proportion > 0 {
+            self.token_gateway_configuration
+                .push(TokenGatewayConfiguration { credit_reference: credit, token_proportion });
+        }
+        self
+    }
+
+    /// Adds a charge (builder style).
+    pub fn with_charge(mut self, charge: ObisCode) -> Self {
+        self.charge_reference_list.push(charge);
+        self
+    }
+
+    /// Sets the currency (builder style).
+    pub fn with_currency(mut self, currency: Currency) -> Self {
+        self.currency = currency;
+        self
+    }
+
+    /// Sets the low credit threshold (builder style).
+    pub fn with_low_credit_threshold(mut self, threshold: i32) -> Self {
+        self.low_credit_threshold = threshold;
+        self
+    }
+
+    /// Activates a new account, as the `activate_account` method does.
+    pub fn activate_account(&mut self) -> Result<(), ActionResult> {
+        if self.account_status != AccountStatus::NewInactive {
+            return Err(ActionResult::ReadWriteDenied);
+        }
+        self.account_status = AccountStatus::Active;
+        if let Some(now) = self.now {
+            self.account_activation_time = date_time_at(now);
+        }
+        Ok(())
+    }
+
+    /// Closes an active account, as the `close_account` method does.
+    pub fn close_account(&mut self) -> Result<(), ActionResult> {
+        if self.account_status != AccountStatus::Active {
+            return Err(ActionResult::ReadWriteDenied);
+        }
+        self.account_status = AccountStatus::Closed;
+        if let Some(now) = self.now {
+            self.account_closure_time = date_time_at(now);
+        }
+        Ok(())
+    }
+
+    /// Returns the account to new (inactive), clearing the amounts and
+    /// times, as the `reset_account` method does.
+    pub fn reset_account(&mut self) {
+        self.account_status = AccountStatus::NewInactive;
+        self.current_credit_in_use = 0;
+        self.current_credit_status = 0;
+        self.available_credit = 0;
+        self.amount_to_clear = 0;
+        self.aggregated_debt = 0;
+        self.account_activation_time = NOT_SCHEDULED;
+        self.account_closure_time = NOT_SCHEDULED;
+    }
+
+    /// Updates the credit in use, the available credit, the credit status
+    /// and the aggregated debt from the referenced objects.
+    pub fn update(&mut self, now: u32, objects: &ObjectRegistry) {
+        self.now = Some(now);
+        let credits: Vec<_> = self
+            .credit_reference_list
+            .iter()
+            .map(|credit| objects.get(CREDIT_CLASS_ID, credit).and_then(credit_state))
+            .collect();
+        let available = credits
+            .iter()
+            .flatten()
+            .filter(|credit| credit.usable)
+            .map(|credit| i64::from(credit.amount.max(0)))
+            .sum::<i64>();
+        self.available_credit = available.min(i32::MAX.into()) as i32;
+        self.current_credit_in_use = credit_in_use(&credits).map_or(0, |index| index as u8 + 1);
+
+        let mut status = 0;
+        if self.available_credit > 0 {
+            status |= IN_CREDIT;
+        } else {
+            status |= OUT_OF_CREDIT;
+        }
+        if self.available_credit <= self.low_credit_threshold {
+            status |= LOW_CREDIT;
+        }
+        if credits.iter().flatten().any(|credit| credit.status == CreditStatus::Selectable) {
+            status |= NEXT_CREDIT_SELECTABLE;
+        }
+        self.current_credit_status = status;
+
+        let debt = self
+            .charge_reference_list
+            .iter()
+            .filter_map(|charge| objects.get(CHARGE_CLASS_ID, charge)?.get_attribute(12).ok())
+            .filter_map(|remaining| match remaining {
+                Data::DoubleLong(remaining) => Some(i64::from(remaining.max(0))),
+                _ => None,
+            })
+            .sum::<i64>();
+        self.aggregated_debt = debt.min(i32::MAX.into()) as i32;
+    }
+
+    fn parse_credit_charge_configuration(
+        data: &Data,
+    ) -> Result<Vec<CreditChargeConfiguration>, DataAccessResult> {
+        elements(data, |fields| match fields {
+            [
+                Data::OctetString(credit),
+                Data::OctetString(charge),
+                Data::BitString(configuration),
+            ] => Ok(CreditChargeConfiguration {
+                credit_reference: ObisCode::try_from(credit.as_slice())
+                    .map_err(|_| DataAccessResult::TypeUnmatched)?,
+                charge_reference: ObisCode::try_from(charge.as_slice())
+                    .map_err(|_| DataAccessResult::TypeUnmatched)?,
+                collection_configuration: configuration.first().copied().unwrap_or(0),
+            }),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        })
+    }
+
+    fn parse_token_gateway_configuration(
+        data: &Data,
+    ) -> Result<Vec<TokenGatewayConfiguration>, DataAccessResult> {
+        elements(data, |fields| match fields {
+            [Data::OctetString(credit), Data::Unsigned(proportion)] if *proportion <= 100 => {
+                Ok(TokenGatewayConfiguration {
+                    credit_reference: ObisCode::try_from(credit.as_slice())
+                        .map_err(|_| DataAccessResult::TypeUnmatched)?,
+                    token_proportion: *proportion,
+                })
+            }
+            _ => Err(DataAccessResult::TypeUnmatched),
+        })
+    }
+}
+
+impl CosemObject for Account {
+    fn class_id(&self) -> u16 {
+        ACCOUNT_CLASS_ID
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::Structure(vec![
+                Data::Enum(self.payment_mode as u8),
+                Data::Enum(self.account_status as u8),
+            ])),
+            3 => Ok(Data::Unsigned(self.current_credit_in_use)),
+            4 => Ok(Data::BitString(vec![self.current_credit_status])),
+            5 => Ok(Data::DoubleLong(self.available_credit)),
+            6 => Ok(Data::DoubleLong(self.amount_to_clear)),
+            7 => Ok(Data::DoubleLong(self.clearance_threshold)),
+            8 => Ok(Data::DoubleLong(self.aggregated_debt)),
+            9 => Ok(references_to(&self.credit_reference_list)),
+            10 => Ok(references_to(&self.charge_reference_list)),
+            11 => Ok(Data::Structure(
+                self.credit_charge_configuration
+                    .iter()
+                    .map(|configuration| {
+                        Data::Structure(vec![
+                            Data::OctetString(configuration.credit_reference.encode().to_vec()),
+                            Data::OctetString(configuration.charge_reference.encode().to_vec()),
+                            Data::BitString(vec![configuration.collection_configuration]),
+                        ])
+                    })
+                    .collect(),
+            )),
+            12 => Ok(Data::Structure(
+                self.token_gateway_configuration
+                    .iter()
+                    .map(|configuration| {
+                        Data::Structure(vec![
+                            Data::OctetString(configuration.credit_reference.encode().to_vec()),
+                            Data::Unsigned(configuration.token_proportion),
+                        ])
+                    })
+                    .collect(),
+            )),
+            13 => Ok(Data::DateTime(self.account_activation_time.clone())),
+            14 => Ok(Data::DateTime(self.account_closure_time.clone())),
+            15 => Ok(self.currency.to_data()),
+            16 => Ok(Data::DoubleLong(self.low_credit_threshold)),
+            17 => Ok(Data::DoubleLong(self.next_credit_available_threshold)),
+            18 => Ok(Data::LongUnsigned(self.max_provision)),
+            19 => Ok(Data::DoubleLongUnsigned(self.max_provision_period)),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (7, Data::DoubleLong(threshold)) => self.clearance_threshold = threshold,
+            (9, value) => self.credit_reference_list = references_from(&value)?,
+            (10, value) => self.charge_reference_list = references_from(&value)?,
+            (11, value) => {
+                self.credit_charge_configuration = Self::parse_credit_charge_configuration(&value)?
+            }
+            (12, value) => {
+                self.token_gateway_configuration = Self::parse_token_gateway_configuration(&value)?
+            }
+            (13, Data::DateTime(time)) => self.account_activation_time = time,
+            (14, Data::DateTime(time)) => self.account_closure_time = time,
+            (15, value) => self.currency = Currency::from_data(&value)?,
+            (16, Data::DoubleLong(threshold)) => self.low_credit_threshold = threshold,
+            (17, Data::DoubleLong(threshold)) => self.next_credit_available_threshold = threshold,
+            (18, Data::LongUnsigned(provision)) => self.max_provision = provision,
+            (19, Data::DoubleLongUnsigned(seconds)) => self.max_provision_period = seconds,
+            (7 | 13 | 14 | 16..=19, _) => return Err(DataAccessResult::TypeUnmatched),
+            (1..=6 | 8, _) => return Err(DataAccessResult::ReadWriteDenied),
+            _ => return Err(DataAccessResult::ObjectUndefined),
+        }
+        Ok(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        _params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match method_id {
+            1 => self.activate_account()?,
+            2 => self.close_account()?,
+            3 => self.reset_account(),
+            _ => return Err(ActionResult::ObjectUndefined),
+        }
+        Ok(None)
+    }
+
+    /// Updates the credit figures from the referenced credits and charges.
+    fn tick(
+        &mut self,
+        now: u32,
+        objects: &ObjectRegistry,
+        _executor: &mut dyn FnMut(&ScriptReference),
+    ) {
+        self.update(now, objects);
+    }
+
+    /// Persists the configuration attributes, then the mode, status and
+    /// amount to clear; the other figures are updated on the next tick.
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [7, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 2, 6])
+            .ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        match state
+            .restore_attributes(self, [7, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19])?
+            .as_slice()
+        {
+            [Data::Structure(mode_and_status), Data::DoubleLong(amount_to_clear)] => {
+                match mode_and_status.as_slice() {
+                    [Data::Enum(mode), Data::Enum(status)] => {
+                        self.payment_mode = PaymentMode::from_u8(*mode)?;
+                        self.account_status = AccountStatus::from_u8(*status)?;
+                    }
+                    _ => return Err(DataAccessResult::TypeUnmatched),
+                }
+                self.amount_to_clear = *amount_to_clear;
+                Ok(())
+            }
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// State of a referenced credit, as read from its attributes.
+#[derive(Debug, Clone, Copy)]
+struct CreditState {
+    priority: u8,
+    status: CreditStatus,
+    amount: i32,
+    usable: bool,
+}
+
+fn credit_state(credit: &(dyn CosemObject + Send)) -> Option<CreditState> {
+    let amount = match credit.get_attribute(2).ok()? {
+        Data::DoubleLong(amount) => amount,
+        _ => return None,
+    };
+    let (Data::Unsigned(priority), Data::DoubleLong(limit), Data::Enum(status)) = (
+        credit.get_attribute(4).ok()?,
+        credit.get_attribute(6).ok()?,
+        credit.get_attribute(8).ok()?,
+    ) else {
+        return None;
+    };
+    let status = CreditStatus::from_u8(status).ok()?;
+    Some(CreditState { priority, status, amount, usable: status.is_usable() && amount > limit })
+}
+
+/// Index of the usable credit of the highest priority, the first on a tie.
+fn credit_in_use(credits: &[Option<CreditState>]) -> Option<usize> {
+    credits
+        .iter()
+        .enumerate()
+        .filter_map(|(index, credit)| credit.filter(|credit| credit.usable).map(|c| (index, c)))
+        .min_by_key(|(index, credit)| (credit.priority, *index))
+        .map(|(index, _)| index)
+}
+
+/// Debits `amount` collected by `charge` from the credit in use of the
+/// account referencing the charge, or from its first credit when none is
+/// usable. Does nothing if no account references the charge.
+pub(crate) fn debit(objects: &mut ObjectRegistry, charge: &ObisCode, amount: i32) {
+    let credits = objects
+        .iter()
+        .filter(|object| object.class_id() == ACCOUNT_CLASS_ID)
+        .filter_map(|account| {
+            references_from(&account.get_attribute(10).ok()?)
+                .ok()
+                .zip(references_from(&account.get_attribute(9).ok()?).ok())
+        })
+        .find_map(|(charges, credits)| charges.contains(charge).then_some(credits));
+    let Some(credits) = credits else {
+        return;
+    };
+    let states: Vec<_> = credits
+        .iter()
+        .map(|credit| objects.get(CREDIT_CLASS_ID, credit).and_then(credit_state))
+        .collect();
+    let Some(credit) = credit_in_use(&states).or((!credits.is_empty()).then_some(0)) else {
+        return;
+    };
+    let _ = objects.invoke_method(
+        CREDIT_CLASS_ID,
+        &credits[credit],
+        1,
+        Some(Data::DoubleLong(amount.saturating_neg())),
+    );
+}
+
+/// The token gateway configuration of an account, or `None` if the account
+/// is missing or closed.
+pub(crate) fn token_gateway_configuration(
+    objects: &ObjectRegistry,
+    account: &ObisCode,
+) -> Option<Vec<TokenGatewayConfiguration>> {
+    let account = objects.get(ACCOUNT_CLASS_ID, account)?;
+    match account.get_attribute(2).ok()? {
+        Data::Structure(fields)
+            if fields.get(1) != Some(&Data::Enum(AccountStatus::Closed as u8)) => {}
+        _ => return None,
+    }
+    Account::parse_token_gateway_configuration(&account.get_attribute(12).ok()?).ok()
+}
+
+fn references_to(names: &[ObisCode]) -> Data {
+    Data::Structure(names.iter().map(|name| Data::OctetString(name.encode().to_vec())).collect())
+}
+
+fn references_from(data: &Data) -> Result<Vec<ObisCode>, DataAccessResult> {
+    match data {
+        Data::Structure(names) => names
+            .iter()
+            .map(|name| match name {
+                Data::OctetString(name) => {
+                    ObisCode::try_from(name.as_slice()).map_err(|_| DataAccessResult::TypeUnmatched)
+                }
+                _ => Err(DataAccessResult::TypeUnmatched),
+            })
+            .collect(),
+        _ => Err(DataAccessResult::TypeUnmatched),
+    }
+}
+
+/// Parses an array of structures with `parse`.
+fn elements<T>(
+    data: &Data,
+    parse: impl Fn(&[Data]) -> Result<T, DataAccessResult>,
+) -> Result<Vec<T>, DataAccessResult> {
+    match data {
+        Data::Structure(elements) => elements
+            .iter()
+            .map(|element| match element {
+                Data::Structure(fields) => parse(fields),
+                _ => Err(DataAccessResult::TypeUnmatched),
+            })
+            .collect(),
+        _ => Err(DataAccessResult::TypeUnmatched),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::charge::{Charge, ChargeType};
+    use crate::cosem::credit::{Credit, CreditType};
+
+    const ACCOUNT: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 0, e: 0, f: 255 };
+    const TOKEN_CREDIT: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 10, e: 0, f: 255 };
+    const EMERGENCY_CREDIT: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 10, e: 1, f: 255 };
+    const DEBT: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 20, e: 1, f: 255 };
+
+    /// 2025-02-01 00:00
+    const MIDNIGHT: u32 = 1_738_368_000;
+
+    fn account() -> Account {
+        Account::new(ACCOUNT, PaymentMode::Prepayment)
+            .with_credit(TOKEN_CREDIT, 100)
+            .with_credit(EMERGENCY_CREDIT, 0)
+            .with_charge(DEBT)
+            .with_low_credit_threshold(200)
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut account = account();
+        assert_eq!(
+            account.invoke_method(2, Some(Data::Integer(0))),
+            Err(ActionResult::ReadWriteDenied)
+        );
+
+        account.update(MIDNIGHT, &ObjectRegistry::new());
+        assert_eq!(account.invoke_method(1, Some(Data::Integer(0))), Ok(None));
+        assert_eq!(
+            account.get_attribute(2),
+            Ok(Data::Structure(vec![Data::Enum(2), Data::Enum(2)]))
+        );
+        assert_eq!(account.account_activation_time, date_time_at(MIDNIGHT));
+        assert_eq!(account.invoke_method(1, None), Err(ActionResult::ReadWriteDenied));
+
+        account.update(MIDNIGHT + 60, &ObjectRegistry::new());
+        assert_eq!(account.invoke_method(2, None), Ok(None));
+        assert_eq!(account.account_status, AccountStatus::Closed);
+        assert_eq!(account.account_closure_time, date_time_at(MIDNIGHT + 60));
+
+        assert_eq!(account.invoke_method(3, None), Ok(None));
+        assert_eq!(account.account_status, AccountStatus::NewInactive);
+        assert_eq!(account.account_closure_time, NOT_SCHEDULED);
+        assert_eq!(account.invoke_method(4, None), Err(ActionResult::ObjectUndefined));
+    }
+
+    #[test]
+    fn test_credit_figures() {
+        let mut objects = ObjectRegistry::new()
+            .with_object(Credit::new(TOKEN_CREDIT, CreditType::Token, 1))
+            .with_object(
+                Credit::new(EMERGENCY_CREDIT, CreditType::Emergency, 2)
+                    .with_preset_credit_amount(500),
+            )
+            .with_object(
+                Charge::new(DEBT, ChargeType::TimeBased, 2).with_total_amount_remaining(1_200),
+            );
+        let mut account = account();
+
+        account.update(MIDNIGHT, &objects);
+        assert_eq!(account.current_credit_in_use, 0);
+        assert_eq!(account.available_credit, 0);
+        assert_eq!(
+            account.current_credit_status,
+            OUT_OF_CREDIT | LOW_CREDIT | NEXT_CREDIT_SELECTABLE
+        );
+        assert_eq!(account.get_attribute(8), Ok(Data::DoubleLong(1_200)));
+
+        objects.invoke_method(112, &EMERGENCY_CREDIT, 3, None).unwrap();
+        account.update(MIDNIGHT, &objects);
+        assert_eq!(account.current_credit_in_use, 2);
+        assert_eq!(account.available_credit, 500);
+        assert_eq!(account.get_attribute(4), Ok(Data::BitString(vec![IN_CREDIT])));
+
+        objects.invoke_method(112, &TOKEN_CREDIT, 1, Some(Data::DoubleLong(150))).unwrap();
+        account.update(MIDNIGHT, &objects);
+        assert_eq!(account.get_attribute(3), Ok(Data::Unsigned(1)));
+        assert_eq!(account.get_attribute(5), Ok(Data::DoubleLong(650)));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let mut account = Account::new(ACCOUNT, PaymentMode::Credit);
+        let credits = Data::Structure(vec![Data::OctetString(TOKEN_CREDIT.encode().to_vec())]);
+        let token_gateway_configuration = Data::Structure(vec![Data::Structure(vec![
+            Data::OctetString(TOKEN_CREDIT.encode().to_vec()),
+            Data::Unsigned(100),
+        ])]);
+        let credit_charge_configuration = Data::Structure(vec![Data::Structure(vec![
+            Data::OctetString(TOKEN_CREDIT.encode().to_vec()),
+            Data::OctetString(DEBT.encode().to_vec()),
+            Data::BitString(vec![0x80]),
+        ])]);
+        let currency =
+            Data::Structure(vec![Data::Utf8String("EUR".into()), Data::Integer(-2), Data::Enum(2)]);
+        for (id, value) in [
+            (9, credits),
+            (11, credit_charge_configuration),
+            (12, token_gateway_configuration),
+            (15, currency),
+            (18, Data::LongUnsigned(10_000)),
+        ] {
+            assert_eq!(account.set_attribute(id, value.clone()), Ok(()));
+            assert_eq!(account.get_attribute(id), Ok(value));
+        }
+        assert_eq!(account.currency.scale, -2);
+        assert_eq!(account.token_gateway_configuration[0].credit_reference, TOKEN_CREDIT);
+
+        assert_eq!(
+            account.set_attribute(
+                12,
+                Data::Structure(vec![Data::Structure(vec![
+                    Data::OctetString(TOKEN_CREDIT.encode().to_vec()),
+                    Data::Unsigned(101),
+                ])])
+            ),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        assert_eq!(
+            account.set_attribute(9, Data::Structure(vec![Data::OctetString(vec![0, 0, 19])])),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        assert_eq!(
+            account.set_attribute(5, Data::DoubleLong(1)),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+        assert_eq!(account.set_attribute(20, Data::Null), Err(DataAccessResult::ObjectUndefined));
+    }
+}
@@ -0,0 +1,533 @@
+//! COSEM Interface Class 20: Activity Calendar
+//!
+//! The Activity Calendar switches tariffs: at each switch time of the day
+//! profile in force, it executes a script of a Script Table. The calendar in
+//! force (active) is replaced by the passive calendar on
+//! `activate_passive_calendar`, either invoked or at
+//! `activate_passive_calendar_time`.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (0.0.13.0.0.255)
+//! - Attributes 2-5: `calendar_name_active`, `season_profile_active`,
+//!   `week_profile_table_active`, `day_profile_table_active` (read-only)
+//! - Attributes 6-9: `calendar_name_passive`, `season_profile_passive`,
+//!   `week_profile_table_passive`, `day_profile_table_passive`
+//! - Attribute 10: `activate_passive_calendar_time` - DateTime, all
+//!   wildcards when no activation is scheduled
+//!
+//! ## Methods
+//! - Method 1: `activate_passive_calendar(data)` - Copies the passive
+//!   calendar to the active one; rejected if the passive calendar is
+//!   inconsistent (see [`Calendar::validate`])
+//!
+//! ## Evaluation
+//!
+//! Switch times are local times. [`ActivityCalendar::evaluate`] converts the
+//! time to the local time of a [`Clock`] (time zone and daylight saving
+//! settings) when it carries a UTC deviation, then finds the season in
+//! force, the day profile of the weekday and the last switch time passed.
+//! [`ActivityCalendar::tick`] also activates a scheduled passive calendar and
+//! returns the evaluation only when the script to execute changes.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::calendar::{Calendar, DayProfile};
+//! use dlms_cosem::cosem::activity_calendar::ActivityCalendar;
+//! use dlms_cosem::cosem::clock::Clock;
+//! use dlms_cosem::{Date, DateTime, ObisCode, Time};
+//!
+//! let script = ObisCode::new(0, 0, 10, 0, 100, 255);
+//! let at = |hour| Time::new(Some(hour), Some(0), Some(0), Some(0));
+//! let every_year = DateTime::new(Date::new(0xFFFF, 1, 1, 0xFF), at(0), None, None);
+//! let calendar = Calendar::new(b"TOU".to_vec())
+//!     .with_season(b"ALL".to_vec(), every_year, b"WEEK".to_vec())
+//!     .with_week(b"WEEK".to_vec(), [1; 7])
+//!     .with_day(DayProfile::new(1).with_action(at(7), script, 1).with_action(at(22), script, 2));
+//! let activity_calendar = ActivityCalendar::new().with_active(calendar);
+//!
+//! let clock = Clock::new(ObisCode::new(0, 0, 1, 0, 0, 255));
+//! let now = DateTime::new(Date::new(2025, 3, 4, 0xFF), at(23), None, None);
+//! let evaluation = activity_calendar.evaluate(&now, &clock).unwrap();
+//! assert_eq!(evaluation.action.script_selector, 2);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::action::ActionResult;
+use crate::calendar::{Calendar, DayProfileAction, seconds_of_day};
+use crate::cosem::CosemObject;
+use crate::cosem::capture_scheduler::days_from_civil;
+use crate::cosem::clock::Clock;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::data::{Data, Date, DateTime, Time};
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Logical name of the Activity Calendar object.
+pub const ACTIVITY_CALENDAR: ObisCode = ObisCode { a: 0, b: 0, c: 13, d: 0, e: 0, f: 255 };
+
+/// Days an evaluation looks back for the last switch time passed.
+const MAX_DAYS_BACK: u8 = 7;
+
+/// Activation time meaning "no activation scheduled".
+pub(crate) const NOT_SCHEDULED: DateTime = DateTime::new(
+    Date::new(0xFFFF, 0xFF, 0xFF, 0xFF),
+    Time::new(None, None, None, None),
+    None,
+    None,
+);
+
+/// The switch time in force at a given time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarEvaluation {
+    /// Season in force
+    pub season_profile_name: Vec<u8>,
+    /// Week profile of the season
+    pub week_profile_name: Vec<u8>,
+    /// Day profile of the weekday
+    pub day_id: u8,
+    /// Last switch time passed, possibly of a previous day; its script is
+    /// the one to execute
+    pub action: DayProfileAction,
+}
+
+/// Activity Calendar object - COSEM Interface Class 20
+///
+/// Reference: Blue Book 4.5.5
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityCalendar {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attributes 2-5: Calendar in force
+    pub active: Calendar,
+    /// Attributes 6-9: Calendar activated next
+    pub passive: Calendar,
+    /// Attribute 10: Activation time of the passive calendar
+    pub activate_passive_calendar_time: DateTime,
+    /// Evaluation last returned by [`tick`](Self::tick)
+    last_evaluation: Option<CalendarEvaluation>,
+}
+
+impl Default for ActivityCalendar {
+    fn default() -> Self {
+        Self {
+            logical_name: ACTIVITY_CALENDAR,
+            active: Calendar::default(),
+            passive: Calendar::default(),
+            activate_passive_calendar_time: NOT_SCHEDULED,
+            last_evaluation: None,
+        }
+    }
+}
+
+impl ActivityCalendar {
+    /// Creates an Activity Calendar object (0.0.13.0.0.255) with empty calendars.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the active calendar (builder style).
+    pub fn with_active(mut self, calendar: Calendar) -> Self {
+        self.active = calendar;
+        self
+    }
+
+    /// Sets the passive calendar (builder style).
+    pub fn with_passive(mut self, calendar: Calendar) -> Self {
+        self.passive = calendar;
+        self
+    }
+
+    /// Copies the passive calendar to the active one and clears the
+    /// scheduled activation.
+    pub fn activate_passive_calendar(&mut self) -> Result<(), crate::calendar::CalendarError> {
+        self.passive.validate()?;
+        self.active = self.passive.clone();
+        self.activate_passive_calendar_time = NOT_SCHEDULED;
+        Ok(())
+    }
+
+    /// Returns the switch time of the active calendar in force at `now`.
+    ///
+    /// Returns `None` if no season has started yet or the calendar references
+    /// a missing week or day profile.
+    pub fn evaluate(&self, now: &DateTime, clock: &Clock) -> Option<CalendarEvaluation> {
+        let local = local_seconds(now, clock);
+        let days = local.div_euclid(86_400);
+        let (year, _, _) = civil_from_days(days);
+
+        let season = self
+            .active
+            .season_profile
+            .iter()
+            .filter_map(|season| {
+                let start = &season.season_start;
+                let mut start_seconds = transition_seconds(start, year)?;
+                if start.date.year == 0xFFFF && start_seconds > local {
+                    start_seconds = transition_seconds(start, year - 1)?;
+                }
+                (start_seconds <= local).then_some((start_seconds, season))
+            })
+            .max_by_key(|(start_seconds, _)| *start_seconds)?
+            .1;
+        let week = self.active.week_profile(&season.week_name)?;
+        let day_id = week.day_ids[weekday(days) as usize - 1];
+
+        // Before the first switch time of the day, the last switch time of
+        // a previous day is still in force.
+        let mut seconds = local.rem_euclid(86_400) as u32;
+        let mut day = days;
+        let action = loop {
+            let day_profile = self.active.day_profile(week.day_ids[weekday(day) as usize - 1])?;
+            let passed = day_profile
+                .day_schedule
+                .iter()
+                .filter(|action| seconds_of_day(&action.start_time) <= seconds)
+                .max_by_key(|action| seconds_of_day(&action.start_time));
+            if let Some(action) = passed {
+                break action.clone();
+            }
+            if days - day >= i64::from(MAX_DAYS_BACK) {
+                return None;
+            }
+            day -= 1;
+            seconds = u32::MAX;
+        };
+
+        Some(CalendarEvaluation {
+            season_profile_name: season.season_profile_name.clone(),
+            week_profile_name: week.week_profile_name.clone(),
+            day_id,
+            action,
+        })
+    }
+
+    /// Activates the passive calendar once its activation time has passed,
+    /// then evaluates the active calendar.
+    ///
+    /// Returns the evaluation when its switch time differs from the one
+    /// returned by the previous call: the caller executes its script.
+    pub fn tick(&mut self, now: &DateTime, clock: &Clock) -> Option<CalendarEvaluation> {
+        let activation = &self.activate_passive_calendar_time;
+        if activation.date.year != 0xFFFF
+            && transition_seconds(activation, i64::from(activation.date.year))
+                .is_some_and(|activation| activation <= local_seconds(now, clock))
+        {
+            // An inconsistent passive calendar stays scheduled.
+            let _ = self.activate_passive_calendar();
+        }
+
+        let evaluation = self.evaluate(now, clock)?;
+        let changed = self.last_evaluation.as_ref().is_none_or(|last| {
+            last.action != evaluation.action || last.day_id != evaluation.day_id
+        });
+        self.last_evaluation = Some(evaluation.clone());
+        changed.then_some(evaluation)
+    }
+
+    fn set_calendar_attribute(
+        calendar: &mut Calendar,
+        id: i8,
+        value: Data,
+    ) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (2 | 6, Data::OctetString(name)) => calendar.calendar_name = name,
+            (3 | 7, value) => calendar.season_profile = Calendar::parse_season_profile(&value)?,
+            (4 | 8, value) => {
+                calendar.week_profile_table = Calendar::parse_week_profile_table(&value)?
+            }
+            (5 | 9, value) => {
+                calendar.day_profile_table = Calendar::parse_day_profile_table(&value)?
+            }
+            _ => return Err(DataAccessResult::TypeUnmatched),
+        }
+        Ok(())
+    }
+}
+
+impl CosemObject for ActivityCalendar {
+    fn class_id(&self) -> u16 {
+        20
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        let calendar = if (2..=5).contains(&id) { &self.active } else { &self.passive };
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 | 6 => Ok(Data::OctetString(calendar.calendar_name.clone())),
+            3 | 7 => Ok(calendar.season_profile_data()),
+            4 | 8 => Ok(calendar.week_profile_table_data()),
+            5 | 9 => Ok(calendar.day_profile_table_data()),
+            10 => Ok(Data::DateTime(self.activate_passive_calendar_time.clone())),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match id {
+            6..=9 => Self::set_calendar_attribute(&mut self.passive, id, value),
+            10 => match value {
+                Data::DateTime(time) => {
+                    self.activate_passive_calendar_time = time;
+                    Ok(())
+                }
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            1..=5 => Err(DataAccessResult::ReadWriteDenied),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        _parameters: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match method_id {
+            1 => {
+                self.activate_passive_calendar().map_err(|_| ActionResult::OtherReason)?;
+                Ok(None)
+            }
+            _ => Err(ActionResult::ObjectUndefined),
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [6, 7, 8, 9, 10, 2, 3, 4, 5]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        let active = state.restore_attributes(self, [6, 7, 8, 9, 10])?;
+        let mut calendar = Calendar::default();
+        for (id, value) in (2..=5).zip(active) {
+            Self::set_calendar_attribute(&mut calendar, id, value)?;
+        }
+        self.active = calendar;
+        Ok(())
+    }
+}
+
+/// Local time of `now`, in seconds since 1970-01-01.
+///
+/// A time with a UTC deviation is converted to the standard time of the
+/// clock's time zone, shifted by the daylight saving deviation while
+/// daylight saving is in force. A time without deviation is already local.
+fn local_seconds(now: &DateTime, clock: &Clock) -> i64 {
+    let naive = transition_seconds(now, i64::from(now.date.year)).unwrap_or(0);
+    let Some(offset) = now.offset_minutes else {
+        return naive;
+    };
+    let standard = naive + i64::from(clock.time_zone - offset) * 60;
+    let deviation = i64::from(clock.daylight_savings_deviation) * 60;
+    let year = civil_from_days(standard.div_euclid(86_400)).0;
+    let begin = transition_seconds(&clock.daylight_savings_begin, year);
+    let end = transition_seconds(&clock.daylight_savings_end, year).map(|end| end - deviation);
+    let daylight_saving = match (clock.daylight_savings_enabled != 0, begin, end) {
+        (true, Some(begin), Some(end)) if begin <= end => (begin..end).contains(&standard),
+        (true, Some(begin), Some(end)) => standard >= begin || standard < end,
+        _ => false,
+    };
+    if daylight_saving { standard + deviation } else { standard }
+}
+
+/// Seconds since 1970-01-01 of a date and time in `year` (used when the
+/// year is a wildcard).
+///
+/// A day of month of 0xFE is the last day of the month; with a day of week,
+/// the date moves to that weekday: backwards from the last day, forwards
+/// otherwise. Returns `None` if the month is not specified.
+pub(crate) fn transition_seconds(time: &DateTime, year: i64) -> Option<i64> {
+    let date = &time.date;
+    if !(1..=12).contains(&date.month) {
+        return None;
+    }
+    let year = if date.year == 0xFFFF { year } else { i64::from(date.year) };
+    let month = i64::from(date.month);
+    let first = days_from_civil(year, month, 1);
+    let length = days_from_civil(year + month / 12, month % 12 + 1, 1) - first;
+    let mut days = match date.day_of_month {
+        day @ 1..=31 => first + i64::from(day).min(length) - 1,
+        0xFE => first + length - 1,
+        _ => first,
+    };
+    if (1..=7).contains(&date.day_of_week) {
+        let day_of_week = i64::from(date.day_of_week);
+        if date.day_of_month == 0xFE {
+            days -= (weekday(days) - day_of_week).rem_euclid(7);
+        } else {
+            days += (day_of_week - weekday(days)).rem_euclid(7);
+        }
+    }
+    Some(days * 86_400 + i64::from(seconds_of_day(&time.time)))
+}
+
+/// Day of week (1 = Monday) of a day since 1970-01-01, a Thursday.
+pub(crate) fn weekday(days: i64) -> i64 {
+    (days + 3).rem_euclid(7) + 1
+}
+
+/// Proleptic Gregorian date of a day since 1970-01-01.
+pub(crate) fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::DayProfile;
+
+    const SCRIPT: ObisCode = ObisCode { a: 0, b: 0, c: 10, d: 0, e: 100, f: 255 };
+
+    fn at(hour: u8, minute: u8) -> Time {
+        Time::new(Some(hour), Some(minute), Some(0), Some(0))
+    }
+
+    fn date_time(year: u16, month: u8, day: u8, hour: u8, offset: Option<i16>) -> DateTime {
+        DateTime::new(Date::new(year, month, day, 0xFF), at(hour, 0), offset, None)
+    }
+
+    /// Summer from April 1st, winter from October 1st; weekends are off-peak.
+    fn calendar() -> Calendar {
+        Calendar::new(b"TOU".to_vec())
+            .with_season(b"SUMMER".to_vec(), date_time(0xFFFF, 4, 1, 0, None), b"SUMMER".to_vec())
+            .with_season(b"WINTER".to_vec(), date_time(0xFFFF, 10, 1, 0, None), b"WINTER".to_vec())
+            .with_week(b"SUMMER".to_vec(), [1, 1, 1, 1, 1, 3, 3])
+            .with_week(b"WINTER".to_vec(), [2, 2, 2, 2, 2, 3, 3])
+            .with_day(DayProfile::new(1).with_action(at(8, 0), SCRIPT, 1).with_action(
+                at(20, 0),
+                SCRIPT,
+                2,
+            ))
+            .with_day(DayProfile::new(2).with_action(at(7, 0), SCRIPT, 1).with_action(
+                at(21, 0),
+                SCRIPT,
+                2,
+            ))
+            .with_day(DayProfile::new(3).with_action(at(0, 0), SCRIPT, 2))
+    }
+
+    fn clock() -> Clock {
+        let mut clock = Clock::new(ObisCode::new(0, 0, 1, 0, 0, 255));
+        clock.time_zone = 60;
+        clock.daylight_savings_begin =
+            DateTime::new(Date::new(0xFFFF, 3, 0xFE, 7), at(2, 0), None, None);
+        clock.daylight_savings_end =
+            DateTime::new(Date::new(0xFFFF, 10, 0xFE, 7), at(3, 0), None, None);
+        clock.daylight_savings_deviation = 60;
+        clock.daylight_savings_enabled = 1;
+        clock
+    }
+
+    #[test]
+    fn test_seasons_and_weekdays() {
+        let activity_calendar = ActivityCalendar::new().with_active(calendar());
+        let clock = clock();
+        let selector = |now: DateTime| {
+            activity_calendar.evaluate(&now, &clock).map(|evaluation| {
+                (
+                    evaluation.season_profile_name,
+                    evaluation.day_id,
+                    evaluation.action.script_selector,
+                )
+            })
+        };
+
+        // Tuesday 2025-06-03, summer.
+        assert_eq!(selector(date_time(2025, 6, 3, 9, None)), Some((b"SUMMER".to_vec(), 1, 1)));
+        assert_eq!(selector(date_time(2025, 6, 3, 20, None)), Some((b"SUMMER".to_vec(), 1, 2)));
+        // Before the first switch time, the previous evening's switch applies.
+        assert_eq!(selector(date_time(2025, 6, 3, 7, None)), Some((b"SUMMER".to_vec(), 1, 2)));
+        // Saturday 2025-06-07.
+        assert_eq!(selector(date_time(2025, 6, 7, 9, None)), Some((b"SUMMER".to_vec(), 3, 2)));
+        // January is in the winter season started the previous year.
+        assert_eq!(selector(date_time(2025, 1, 14, 7, None)), Some((b"WINTER".to_vec(), 2, 1)));
+    }
+
+    #[test]
+    fn test_daylight_saving() {
+        let activity_calendar = ActivityCalendar::new().with_active(calendar());
+        let clock = clock();
+        let hour = |now: DateTime| local_seconds(&now, &clock).rem_euclid(86_400) / 3_600;
+
+        // 05:30 UTC is 07:30 local time in summer (UTC+1 and DST)...
+        let summer = DateTime::new(Date::new(2025, 6, 3, 0xFF), at(5, 30), Some(0), None);
+        assert_eq!(hour(summer.clone()), 7);
+        assert_eq!(activity_calendar.evaluate(&summer, &clock).unwrap().action.script_selector, 2);
+        // ...and 06:30 local time in winter.
+        let winter = DateTime::new(Date::new(2025, 1, 14, 0xFF), at(5, 30), Some(0), None);
+        assert_eq!(hour(winter), 6);
+
+        // DST starts on the last Sunday of March (2025-03-30) at 02:00.
+        assert_eq!(hour(date_time(2025, 3, 30, 0, Some(0))), 1);
+        assert_eq!(hour(date_time(2025, 3, 30, 1, Some(0))), 3);
+        // and ends on the last Sunday of October (2025-10-26) at 03:00 DST.
+        assert_eq!(hour(date_time(2025, 10, 26, 0, Some(0))), 2);
+        assert_eq!(hour(date_time(2025, 10, 26, 1, Some(0))), 2);
+    }
+
+    #[test]
+    fn test_tick_and_activation() {
+        let clock = clock();
+        let mut activity_calendar = ActivityCalendar::new().with_passive(calendar());
+        activity_calendar
+            .set_attribute(10, Data::DateTime(date_time(2025, 6, 1, 0, None)))
+            .unwrap();
+
+        assert_eq!(activity_calendar.tick(&date_time(2025, 5, 31, 12, None), &clock), None);
+        let evaluation = activity_calendar.tick(&date_time(2025, 6, 2, 9, None), &clock).unwrap();
+        assert_eq!(evaluation.action.script_selector, 1);
+        assert_eq!(activity_calendar.get_attribute(2), Ok(Data::OctetString(b"TOU".to_vec())));
+        assert_eq!(activity_calendar.activate_passive_calendar_time, NOT_SCHEDULED);
+
+        // Same switch time: nothing to execute.
+        assert_eq!(activity_calendar.tick(&date_time(2025, 6, 2, 10, None), &clock), None);
+        let evaluation = activity_calendar.tick(&date_time(2025, 6, 2, 21, None), &clock).unwrap();
+        assert_eq!(evaluation.action.script_selector, 2);
+    }
+
+    #[test]
+    fn test_attributes_and_method() {
+        let passive = calendar();
+        let mut activity_calendar = ActivityCalendar::new();
+        activity_calendar
+            .set_attribute(6, Data::OctetString(passive.calendar_name.clone()))
+            .unwrap();
+        activity_calendar.set_attribute(7, passive.season_profile_data()).unwrap();
+        activity_calendar.set_attribute(8, passive.week_profile_table_data()).unwrap();
+        assert_eq!(
+            activity_calendar.set_attribute(3, passive.season_profile_data()),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+
+        // The day profiles are still missing.
+        assert_eq!(
+            activity_calendar.invoke_method(1, Some(Data::Integer(0))),
+            Err(ActionResult::OtherReason)
+        );
+        activity_calendar.set_attribute(9, passive.day_profile_table_data()).unwrap();
+        activity_calendar.invoke_method(1, Some(Data::Integer(0))).unwrap();
+        assert_eq!(activity_calendar.active, passive);
+        assert_eq!(activity_calendar.get_attribute(5), Ok(passive.day_profile_table_data()));
+    }
+}
@@ -0,0 +1,916 @@
+//! COSEM Interface Class 15: Association LN
+//!
+//! The Association LN object describes an application association: which
+//! objects are visible through it (with their access rights), who the partners
+//! are, and what was negotiated. Clients read its `object_list` to discover the
+//! object model of a meter; servers use it to check access rights.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (current association: 0.0.40.0.0.255)
+//! - Attribute 2: `object_list` - Array of visible objects with access rights
+//! - Attribute 3: `associated_partners_id` - Client SAP and server SAP
+//! - Attribute 4: `application_context_name` - Application context OID
+//! - Attribute 5: `xDLMS_context_info` - Conformance, PDU sizes, DLMS version
+//! - Attribute 6: `authentication_mechanism_name` - Mechanism OID
+//! - Attribute 7: `secret` - LLS password or HLS secret (write-only)
+//! - Attribute 8: `association_status` - non-associated / pending / associated
+//! - Attribute 9: `security_setup_reference` - Logical name of the SecuritySetup object
+//!
+//! ## Methods
+//! - Method 1: `reply_to_HLS_authentication(data)` - Pass 3/4 of HLS authentication
+//! - Method 2: `change_HLS_secret(data)` - Replace the HLS secret
+//! - Method 3: `add_object(data)` - Add an object_list element
+//! - Method 4: `remove_object(data)` - Remove an object_list element
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::association_ln::{AccessRights, AssociationLn};
+//! use dlms_cosem::cosem::data::DataObject;
+//! use dlms_cosem::cosem::{AttributeAccess, CosemObject, ObjectRegistry};
+//! use dlms_cosem::{Data, ObisCode};
+//!
+//! let registry = ObjectRegistry::new()
+//!     .with_object(DataObject::new(ObisCode::new(0, 0, 96, 1, 0, 255), Data::Unsigned(1)));
+//!
+//! let association = AssociationLn::new().with_object_list_from(&registry, |_| {
+//!     AccessRights::new()
+//!         .with_attribute(1, AttributeAccess::READ_ONLY)
+//!         .with_attribute(2, AttributeAccess::READ_ONLY)
+//! });
+//!
+//! // A client parses attribute 2 back into elements
+//! let object_list = association.get_attribute(2).unwrap();
+//! let elements = AssociationLn::parse_object_list(&object_list).unwrap();
+//! assert_eq!(elements[0].logical_name, ObisCode::new(0, 0, 96, 1, 0, 255));
+//! assert_eq!(
+//!     elements[0].access_rights.attribute_access(2),
+//!     Some(AttributeAccess::READ_ONLY)
+//! );
+//! ```
+
+use alloc::vec::Vec;
+
+use aes::Aes128;
+use cipher::{BlockEncrypt, KeyInit, generic_array::GenericArray};
+
+use crate::action::ActionResult;
+use crate::association::{ApplicationContextName, Conformance, MechanismName};
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::cosem::{AttributeAccess, CosemObject, MethodAccess, ObjectRegistry};
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Computes the HLS response `f(challenge)` for a mechanism and secret.
+///
+/// Returns `None` if the mechanism is not supported.
+pub type HlsFunction = fn(MechanismName, &[u8], &[u8]) -> Option<Vec<u8>>;
+
+/// Default HLS function.
+///
+/// Supports mechanism 2 (manufacturer specific HLS) as implemented by most
+/// meters and client libraries: the challenge, zero-padded to a multiple of 16
+/// bytes, is encrypted with AES-128-ECB using the secret (zero-padded to 16
+/// bytes) as key. Secrets longer than 16 bytes and all other mechanisms are
+/// rejected; use [`AssociationLn::with_hls_function`] to plug in others.
+pub fn default_hls_function(
+    mechanism: MechanismName,
+    secret: &[u8],
+    challenge: &[u8],
+) -> Option<Vec<u8>> {
+    if mechanism != MechanismName::HighLevelSecurity || secret.len() > 16 {
+        return None;
+    }
+
+    let mut key = [0u8; 16];
+    key[..secret.len()].copy_from_slice(secret);
+    let cipher = Aes128::new(GenericArray::from_slice(&key));
+
+    let mut output = challenge.to_vec();
+    output.resize(challenge.len().div_ceil(16).max(1) * 16, 0);
+    for block in output.chunks_exact_mut(16) {
+        cipher.encrypt_block(GenericArray::from_mut_slice(block));
+    }
+    Some(output)
+}
+
+/// Access rights of one object as listed in an object_list element.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AccessRights {
+    /// Attribute access rights
+    pub attributes: Vec<AttributeAccessItem>,
+    /// Method access rights
+    pub methods: Vec<MethodAccessItem>,
+}
+
+impl AccessRights {
+    /// Creates empty access rights (everything not listed is inaccessible).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an attribute access right (builder style).
+    pub fn with_attribute(mut self, attribute_id: i8, access: AttributeAccess) -> Self {
+        self.attributes.push(AttributeAccessItem { attribute_id, access, access_selectors: None });
+        self
+    }
+
+    /// Adds an attribute access right with supported selective access selectors.
+    pub fn with_selective_attribute(
+        mut self,
+        attribute_id: i8,
+        access: AttributeAccess,
+        access_selectors: Vec<i8>,
+    ) -> Self {
+        self.attributes.push(AttributeAccessItem {
+            attribute_id,
+            access,
+            access_selectors: Some(access_selectors),
+        });
+        self
+    }
+
+    /// Adds a method access right (builder style).
+    pub fn with_method(mut self, method_id: i8, access: MethodAccess) -> Self {
+        self.methods.push(MethodAccessItem { method_id, access });
+        self
+    }
+
+    /// Returns the access right of an attribute, if listed.
+    pub fn attribute_access(&self, attribute_id: i8) -> Option<AttributeAccess> {
+        self.attributes
+            .iter()
+            .find(|item| item.attribute_id == attribute_id)
+            .map(|item| item.access)
+    }
+
+    /// Returns the access right of a method, if listed.
+    pub fn method_access(&self, method_id: i8) -> Option<MethodAccess> {
+        self.methods.iter().find(|item| item.method_id == method_id).map(|item| item.access)
+    }
+
+    fn to_data(&self) -> Data {
+        let attributes = self
+            .attributes
+            .iter()
+            .map(|item| {
+                Data::Structure(vec![
+                    Data::Integer(item.attribute_id),
+                    Data::Enum(item.access.access_mode()),
+                    match &item.access_selectors {
+                        Some(selectors) => Data::Structure(
+                            selectors.iter().map(|selector| Data::Integer(*selector)).collect(),
+                        ),
+                        None => Data::Null,
+                    },
+                ])
+            })
+            .collect();
+        let methods = self
+            .methods
+            .iter()
+            .map(|item| {
+                Data::Structure(vec![
+                    Data::Integer(item.method_id),
+                    Data::Enum(item.access.access_mode()),
+                ])
+            })
+            .collect();
+        Data::Structure(vec![Data::Structure(attributes), Data::Structure(methods)])
+    }
+
+    fn from_data(data: &Data) -> Result<Self, DataAccessResult> {
+        let [attributes, methods] = structure::<2>(data)?;
+
+        let attributes = elements(attributes)?
+            .iter()
+            .map(|item| {
+                let [id, mode, selectors] = structure::<3>(item)?;
+                let access_selectors = match selectors {
+                    Data::Null => None,
+                    Data::Structure(selectors) => {
+                        Some(selectors.iter().map(integer).collect::<Result<Vec<_>, _>>()?)
+                    }
+                    _ => return Err(DataAccessResult::TypeUnmatched),
+                };
+                Ok(AttributeAccessItem {
+                    attribute_id: integer(id)?,
+                    access: AttributeAccess::from_access_mode(enumeration(mode)?)
+                        .ok_or(DataAccessResult::TypeUnmatched)?,
+                    access_selectors,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let methods = elements(methods)?
+            .iter()
+            .map(|item| {
+                let [id, mode] = structure::<2>(item)?;
+                let access = MethodAccess::from_access_mode(enumeration(mode)?)
+                    .ok_or(DataAccessResult::TypeUnmatched)?;
+                Ok(MethodAccessItem { method_id: integer(id)?, access })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { attributes, methods })
+    }
+}
+
+/// Access right of one attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeAccessItem {
+    /// Attribute index
+    pub attribute_id: i8,
+    /// Access mode
+    pub access: AttributeAccess,
+    /// Supported selective access selectors (`None` if not supported)
+    pub access_selectors: Option<Vec<i8>>,
+}
+
+/// Access right of one method.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodAccessItem {
+    /// Method index
+    pub method_id: i8,
+    /// Access mode
+    pub access: MethodAccess,
+}
+
+/// One element of the object_list attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectListElement {
+    /// Interface class
+    pub class_id: u16,
+    /// Interface class version
+    pub version: u8,
+    /// Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Access rights granted through this association
+    pub access_rights: AccessRights,
+}
+
+impl ObjectListElement {
+    /// Creates an element describing a COSEM object.
+    pub fn from_object(object: &dyn CosemObject, access_rights: AccessRights) -> Self {
+        Self {
+            class_id: object.class_id(),
+            version: object.version(),
+            logical_name: *object.logical_name(),
+            access_rights,
+        }
+    }
+
+    /// Encodes the element as `object_list_element` structure.
+    pub fn to_data(&self) -> Data {
+        Data::Structure(vec![
+            Data::LongUnsigned(self.class_id),
+            Data::Unsigned(self.version),
+            Data::OctetString(self.logical_name.encode().to_vec()),
+            self.access_rights.to_data(),
+        ])
+    }
+
+    /// Decodes an `object_list_element` structure.
+    pub fn from_data(data: &Data) -> Result<Self, DataAccessResult> {
+        let [class_id, version, logical_name, access_rights] = structure::<4>(data)?;
+        let (
+            Data::LongUnsigned(class_id),
+            Data::Unsigned(version),
+            Data::OctetString(logical_name),
+        ) = (class_id, version, logical_name)
+        else {
+            return Err(DataAccessResult::TypeUnmatched);
+        };
+        Ok(Self {
+            class_id: *class_id,
+            version: *version,
+            logical_name: ObisCode::try_from(logical_name.as_slice())
+                .map_err(|_| DataAccessResult::TypeUnmatched)?,
+            access_rights: AccessRights::from_data(access_rights)?,
+        })
+    }
+}
+
+/// Associated partners (attribute 3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssociatedPartnersId {
+    /// Client SAP
+    pub client_sap: i8,
+    /// Server SAP (logical device address)
+    pub server_sap: u16,
+}
+
+impl Default for AssociatedPartnersId {
+    /// Public client (16) and management logical device (1).
+    fn default() -> Self {
+        Self { client_sap: 16, server_sap: 1 }
+    }
+}
+
+/// xDLMS context info (attribute 5).
+#[derive(Debug, Clone, PartialEq)]
+pub struct XdlmsContextInfo {
+    /// Negotiated conformance
+    pub conformance: Conformance,
+    /// Maximum PDU size the client can receive
+    pub max_receive_pdu_size: u16,
+    /// Maximum PDU size the server sends
+    pub max_send_pdu_size: u16,
+    /// DLMS version number
+    pub dlms_version_number: u8,
+    /// Quality of service
+    pub quality_of_service: i8,
+    /// Ciphering info (dedicated key or empty)
+    pub cyphering_info: Vec<u8>,
+}
+
+impl Default for XdlmsContextInfo {
+    fn default() -> Self {
+        Self {
+            conformance: Conformance::EMPTY,
+            max_receive_pdu_size: 0xFFFF,
+            max_send_pdu_size: 0xFFFF,
+            dlms_version_number: 6,
+            quality_of_service: 0,
+            cyphering_info: Vec::new(),
+        }
+    }
+}
+
+impl XdlmsContextInfo {
+    fn to_data(&self) -> Data {
+        Data::Structure(vec![
+            Data::BitString(self.conformance.to_bytes().to_vec()),
+            Data::LongUnsigned(self.max_receive_pdu_size),
+            Data::LongUnsigned(self.max_send_pdu_size),
+            Data::Unsigned(self.dlms_version_number),
+            Data::Integer(self.quality_of_service),
+            Data::OctetString(self.cyphering_info.clone()),
+        ])
+    }
+}
+
+/// Association status (attribute 8).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum AssociationStatus {
+    /// Not associated
+    #[default]
+    NonAssociated = 0,
+    /// HLS authentication pending (pass 3/4 outstanding)
+    AssociationPending = 1,
+    /// Associated
+    Associated = 2,
+}
+
+/// Association LN object - COSEM Interface Class 15
+///
+/// Reference: Blue Book 4.4.4
+#[derive(Debug, Clone)]
+pub struct AssociationLn {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Objects visible through this association
+    pub object_list: Vec<ObjectListElement>,
+    /// Attribute 3: Associated partners
+    pub associated_partners_id: AssociatedPartnersId,
+    /// Attribute 4: Application context name
+    pub application_context_name: ApplicationContextName,
+    /// Attribute 5: xDLMS context info
+    pub xdlms_context_info: XdlmsContextInfo,
+    /// Attribute 6: Authentication mechanism name
+    pub authentication_mechanism_name: MechanismName,
+    /// Attribute 7: LLS password or HLS secret
+    secret: Vec<u8>,
+    /// Attribute 8: Association status
+    pub association_status: AssociationStatus,
+    /// Attribute 9: Logical name of the SecuritySetup object
+    pub security_setup_reference: ObisCode,
+    /// Challenges (CtoS, StoC) of the HLS authentication in progress
+    hls_challenges: Option<(Vec<u8>, Vec<u8>)>,
+    hls_function: HlsFunction,
+}
+
+impl Default for AssociationLn {
+    fn default() -> Self {
+        Self {
+            logical_name: ObisCode::new(0, 0, 40, 0, 0, 255),
+            object_list: Vec::new(),
+            associated_partners_id: AssociatedPartnersId::default(),
+            application_context_name: ApplicationContextName::LogicalNameReferencing,
+            xdlms_context_info: XdlmsContextInfo::default(),
+            authentication_mechanism_name: MechanismName::LowestLevelSecurity,
+            secret: Vec::new(),
+            association_status: AssociationStatus::NonAssociated,
+            security_setup_reference: ObisCode::new(0, 0, 43, 0, 0, 255),
+            hls_challenges: None,
+            hls_function: default_hls_function,
+        }
+    }
+}
+
+impl AssociationLn {
+    /// Creates the "current association" object (0.0.40.0.0.255) without authentication.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the logical name (e.g. 0.0.40.0.1.255 for a specific association).
+    pub fn with_logical_name(mut self, logical_name: ObisCode) -> Self {
+        self.logical_name = logical_name;
+        self
+    }
+
+    /// Sets the associated partners.
+    pub fn with_partners(mut self, client_sap: i8, server_sap: u16) -> Self {
+        self.associated_partners_id = AssociatedPartnersId { client_sap, server_sap };
+        self
+    }
+
+    /// Sets the authentication mechanism and its secret (LLS password or HLS key).
+    pub fn with_authentication(mut self, mechanism: MechanismName, secret: Vec<u8>) -> Self {
+        self.authentication_mechanism_name = mechanism;
+        self.secret = secret;
+        self
+    }
+
+    /// Replaces the function computing HLS responses.
+    pub fn with_hls_function(mut self, hls_function: HlsFunction) -> Self {
+        self.hls_function = hls_function;
+        self
+    }
+
+    /// Adds an object_list element.
+    pub fn with_object(mut self, element: ObjectListElement) -> Self {
+        self.add_object(element);
+        self
+    }
+
+    /// Lists every object of a registry, with access rights chosen per object.
+    pub fn with_object_list_from(
+        mut self,
+        registry: &ObjectRegistry,
+        access_rights: impl Fn(&dyn CosemObject) -> AccessRights,
+    ) -> Self {
+        self.object_list = registry
+            .iter()
+            .map(|object| ObjectListElement::from_object(object, access_rights(object)))
+            .collect();
+        self
+    }
+
+    /// Returns the LLS password or HLS secret.
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    /// Adds an object_list element, replacing an element for the same object.
+    pub fn add_object(&mut self, element: ObjectListElement) {
+        self.remove_object(element.class_id, &element.logical_name);
+        self.object_list.push(element);
+    }
+
+    /// Removes the element for an object, returning it.
+    pub fn remove_object(
+        &mut self,
+        class_id: u16,
+        logical_name: &ObisCode,
+    ) -> Option<ObjectListElement> {
+        let index = self.object_list.iter().position(|element| {
+            element.class_id == class_id && element.logical_name == *logical_name
+        })?;
+        Some(self.object_list.remove(index))
+    }
+
+    /// Returns the element for an object, if listed.
+    pub fn find_object(
+        &self,
+        class_id: u16,
+        logical_name: &ObisCode,
+    ) -> Option<&ObjectListElement> {
+        self.object_list
+            .iter()
+            .find(|element| element.class_id == class_id && element.logical_name == *logical_name)
+    }
+
+    /// Returns the access right of an attribute as granted through this association.
+    ///
+    /// Returns `None` if the object is not listed.
+    pub fn attribute_access(
+        &self,
+        class_id: u16,
+        logical_name: &ObisCode,
+        attribute_id: i8,
+    ) -> Option<AttributeAccess> {
+        let element = self.find_object(class_id, logical_name)?;
+        Some(
+            element
+                .access_rights
+                .attribute_access(attribute_id)
+                .unwrap_or(AttributeAccess::NO_ACCESS),
+        )
+    }
+
+    /// Returns the access right of a method as granted through this association.
+    ///
+    /// Returns `None` if the object is not listed.
+    pub fn method_access(
+        &self,
+        class_id: u16,
+        logical_name: &ObisCode,
+        method_id: i8,
+    ) -> Option<MethodAccess> {
+        let element = self.find_object(class_id, logical_name)?;
+        Some(element.access_rights.method_access(method_id).unwrap_or(MethodAccess::NO_ACCESS))
+    }
+
+    /// Starts HLS authentication after the AARE was sent (passes 1 and 2).
+    ///
+    /// `ctos` is the client challenge from the AARQ, `stoc` the server
+    /// challenge sent in the AARE. The status becomes pending until the client
+    /// invokes `reply_to_HLS_authentication`.
+    pub fn begin_hls_authentication(&mut self, ctos: Vec<u8>, stoc: Vec<u8>) {
+        self.hls_challenges = Some((ctos, stoc));
+        self.association_status = AssociationStatus::AssociationPending;
+    }
+
+    /// Parses an object_list attribute value (attribute 2).
+    pub fn parse_object_list(data: &Data) -> Result<Vec<ObjectListElement>, DataAccessResult> {
+        elements(data)?.iter().map(ObjectListElement::from_data).collect()
+    }
+
+    /// Passes 3 and 4 of HLS: checks f(StoC) and answers with f(CtoS).
+    #[cfg(feature = "encode")]
+    fn reply_to_hls_authentication(
+        &mut self,
+        parameters: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        let Some(Data::OctetString(client_response)) = parameters else {
+            return Err(ActionResult::TypeUnmatched);
+        };
+        let Some((ctos, stoc)) = self.hls_challenges.take() else {
+            return Err(ActionResult::ReadWriteDenied);
+        };
+
+        let hls = self.hls_function;
+        let mechanism = self.authentication_mechanism_name;
+        let expected = hls(mechanism, &self.secret, &stoc).ok_or(ActionResult::OtherReason)?;
+        if expected != client_response {
+            self.association_status = AssociationStatus::NonAssociated;
+            return Err(ActionResult::ReadWriteDenied);
+        }
+
+        let server_response =
+            hls(mechanism, &self.secret, &ctos).ok_or(ActionResult::OtherReason)?;
+        self.association_status = AssociationStatus::Associated;
+        Ok(Some(Data::OctetString(server_response)))
+    }
+}
+
+impl CosemObject for AssociationLn {
+    fn class_id(&self) -> u16 {
+        15
+    }
+
+    fn version(&self) -> u8 {
+        1
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::Structure(
+                self.object_list.iter().map(ObjectListElement::to_data).collect(),
+            )),
+            3 => Ok(Data::Structure(vec![
+                Data::Integer(self.associated_partners_id.client_sap),
+                Data::LongUnsigned(self.associated_partners_id.server_sap),
+            ])),
+            4 => Ok(Data::OctetString(self.application_context_name.oid_bytes().to_vec())),
+            5 => Ok(self.xdlms_context_info.to_data()),
+            6 => Ok(Data::OctetString(self.authentication_mechanism_name.oid_bytes().to_vec())),
+            7 => Err(DataAccessResult::ReadWriteDenied), // secret is write-only
+            8 => Ok(Data::Enum(self.association_status as u8)),
+            9 => Ok(Data::OctetString(self.security_setup_reference.encode().to_vec())),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match id {
+            1..=6 | 8 => Err(DataAccessResult::ReadWriteDenied),
+            7 => match value {
+                Data::OctetString(secret) => {
+                    self.secret = secret;
+                    Ok(())
+                }
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            9 => match value {
+                Data::OctetString(name) => {
+                    self.security_setup_reference = ObisCode::try_from(name.as_slice())
+                        .map_err(|_| DataAccessResult::TypeUnmatched)?;
+                    Ok(())
+                }
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    #[cfg(feature = "encode")]
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        parameters: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match method_id {
+            1 => self.reply_to_hls_authentication(parameters),
+            2 => match parameters {
+                Some(Data::OctetString(secret)) => {
+                    self.secret = secret;
+                    Ok(None)
+                }
+                _ => Err(ActionResult::TypeUnmatched),
+            },
+            3 => {
+                let element = parameters
+                    .as_ref()
+                    .map(ObjectListElement::from_data)
+                    .ok_or(ActionResult::TypeUnmatched)?
+                    .map_err(|_| ActionResult::TypeUnmatched)?;
+                self.add_object(element);
+                Ok(None)
+            }
+            4 => {
+                let element = parameters
+                    .as_ref()
+                    .map(ObjectListElement::from_data)
+                    .ok_or(ActionResult::TypeUnmatched)?
+                    .map_err(|_| ActionResult::TypeUnmatched)?;
+                self.remove_object(element.class_id, &element.logical_name)
+                    .map(|_| None)
+                    .ok_or(ActionResult::ObjectUnavailable)
+            }
+            _ => Err(ActionResult::ObjectUndefined),
+        }
+    }
+
+    /// Persists the secret, which `change_HLS_secret` may have changed.
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        let attributes = vec![Data::OctetString(self.secret.clone())];
+        Some(ObjectState { attributes, entries: Vec::new() })
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, [7]).map(drop)
+    }
+}
+
+fn structure<const N: usize>(data: &Data) -> Result<&[Data; N], DataAccessResult> {
+    elements(data)?.try_into().map_err(|_| DataAccessResult::TypeUnmatched)
+}
+
+fn elements(data: &Data) -> Result<&[Data], DataAccessResult> {
+    match data {
+        Data::Structure(elements) => Ok(elements),
+        _ => Err(DataAccessResult::TypeUnmatched),
+    }
+}
+
+fn integer(data: &Data) -> Result<i8, DataAccessResult> {
+    match data {
+        Data::Integer(value) => Ok(*value),
+        _ => Err(DataAccessResult::TypeUnmatched),
+    }
+}
+
+fn enumeration(data: &Data) -> Result<u8, DataAccessResult> {
+    match data {
+        Data::Enum(value) => Ok(*value),
+        _ => Err(DataAccessResult::TypeUnmatched),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::data::DataObject;
+
+    fn element() -> ObjectListElement {
+        ObjectListElement {
+            class_id: 3,
+            version: 0,
+            logical_name: ObisCode::new(1, 0, 1, 8, 0, 255),
+            access_rights: AccessRights::new()
+                .with_attribute(1, AttributeAccess::READ_ONLY)
+                .with_attribute(2, AttributeAccess::from_access_mode(6).unwrap())
+                .with_selective_attribute(3, AttributeAccess::READ_ONLY, vec![1, 2])
+                .with_method(1, MethodAccess::AUTHENTICATED_ACCESS),
+        }
+    }
+
+    #[test]
+    fn test_access_mode_round_trip() {
+        for mode in 0..=6 {
+            assert_eq!(AttributeAccess::from_access_mode(mode).unwrap().access_mode(), mode);
+        }
+        for mode in 0..=2 {
+            assert_eq!(MethodAccess::from_access_mode(mode).unwrap().access_mode(), mode);
+        }
+        assert_eq!(AttributeAccess::from_access_mode(7), None);
+        assert_eq!(MethodAccess::from_access_mode(3), None);
+    }
+
+    #[test]
+    fn test_object_list_round_trip() {
+        let association = AssociationLn::new().with_object(element());
+        let data = association.get_attribute(2).unwrap();
+
+        let Data::Structure(ref elements) = data else { panic!("expected array") };
+        let Data::Structure(ref fields) = elements[0] else { panic!("expected structure") };
+        assert_eq!(fields[0], Data::LongUnsigned(3));
+        assert_eq!(fields[2], Data::OctetString(vec![1, 0, 1, 8, 0, 255]));
+
+        assert_eq!(AssociationLn::parse_object_list(&data).unwrap(), vec![element()]);
+    }
+
+    #[test]
+    fn test_parse_object_list() {
+        let data = Data::Structure(vec![Data::Structure(vec![
+            Data::LongUnsigned(8),
+            Data::Unsigned(0),
+            Data::OctetString(vec![0, 0, 1, 0, 0, 255]),
+            Data::Structure(vec![
+                Data::Structure(vec![]),
+                Data::Structure(vec![Data::Structure(vec![Data::Integer(1), Data::Enum(1)])]),
+            ]),
+        ])]);
+
+        let elements = AssociationLn::parse_object_list(&data).unwrap();
+        assert_eq!(elements[0].access_rights.method_access(1), Some(MethodAccess::ACCESS));
+        assert_eq!(
+            AssociationLn::parse_object_list(&Data::Unsigned(0)),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+    }
+
+    #[test]
+    fn test_object_list_from_registry() {
+        let registry = ObjectRegistry::new()
+            .with_object(DataObject::new(ObisCode::new(0, 0, 96, 1, 0, 255), Data::Unsigned(1)));
+        let association = AssociationLn::new().with_object_list_from(&registry, |_| {
+            AccessRights::new().with_attribute(2, AttributeAccess::READ_ONLY)
+        });
+
+        let name = ObisCode::new(0, 0, 96, 1, 0, 255);
+        assert_eq!(association.attribute_access(1, &name, 2), Some(AttributeAccess::READ_ONLY));
+        assert_eq!(association.attribute_access(1, &name, 1), Some(AttributeAccess::NO_ACCESS));
+        assert_eq!(association.attribute_access(3, &name, 2), None);
+        assert_eq!(association.method_access(1, &name, 1), Some(MethodAccess::NO_ACCESS));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let association = AssociationLn::new()
+            .with_partners(0x30, 1)
+            .with_authentication(MechanismName::LowLevelSecurity, b"12345678".to_vec());
+
+        assert_eq!(association.class_id(), 15);
+        assert_eq!(association.version(), 1);
+        assert_eq!(
+            association.get_attribute(3),
+            Ok(Data::Structure(vec![Data::Integer(0x30), Data::LongUnsigned(1)]))
+        );
+        assert_eq!(
+            association.get_attribute(4),
+            Ok(Data::OctetString(vec![0x60, 0x85, 0x74, 0x05, 0x08, 0x01, 0x01]))
+        );
+        assert_eq!(
+            association.get_attribute(6),
+            Ok(Data::OctetString(vec![0x60, 0x85, 0x74, 0x05, 0x08, 0x02, 0x01]))
+        );
+        assert_eq!(association.get_attribute(7), Err(DataAccessResult::ReadWriteDenied));
+        assert_eq!(association.get_attribute(8), Ok(Data::Enum(0)));
+        assert_eq!(association.get_attribute(10), Err(DataAccessResult::ObjectUndefined));
+
+        let Ok(Data::Structure(context)) = association.get_attribute(5) else {
+            panic!("expected structure")
+        };
+        assert_eq!(context.len(), 6);
+        assert_eq!(context[3], Data::Unsigned(6));
+    }
+
+    #[test]
+    fn test_set_attributes() {
+        let mut association = AssociationLn::new();
+
+        assert_eq!(
+            association.set_attribute(8, Data::Enum(2)),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+        association.set_attribute(7, Data::OctetString(b"secret".to_vec())).unwrap();
+        assert_eq!(association.secret(), b"secret");
+        association.set_attribute(9, Data::OctetString(vec![0, 0, 43, 0, 1, 255])).unwrap();
+        assert_eq!(association.security_setup_reference, ObisCode::new(0, 0, 43, 0, 1, 255));
+    }
+
+    #[test]
+    fn test_add_and_remove_object() {
+        let mut association = AssociationLn::new();
+
+        association.invoke_method(3, Some(element().to_data())).unwrap();
+        association.invoke_method(3, Some(element().to_data())).unwrap();
+        assert_eq!(association.object_list.len(), 1);
+
+        association.invoke_method(4, Some(element().to_data())).unwrap();
+        assert!(association.object_list.is_empty());
+        assert_eq!(
+            association.invoke_method(4, Some(element().to_data())),
+            Err(ActionResult::ObjectUnavailable)
+        );
+        assert_eq!(association.invoke_method(3, None), Err(ActionResult::TypeUnmatched));
+    }
+
+    #[test]
+    fn test_hls_authentication() {
+        let secret = b"0123456789ABCDEF".to_vec();
+        let mut association = AssociationLn::new()
+            .with_authentication(MechanismName::HighLevelSecurity, secret.clone());
+
+        // No authentication in progress
+        assert_eq!(
+            association.invoke_method(1, Some(Data::OctetString(vec![0; 16]))),
+            Err(ActionResult::ReadWriteDenied)
+        );
+
+        association
+            .begin_hls_authentication(b"client challenge".to_vec(), b"server challenge".to_vec());
+        assert_eq!(association.association_status, AssociationStatus::AssociationPending);
+
+        let client_response =
+            default_hls_function(MechanismName::HighLevelSecurity, &secret, b"server challenge")
+                .unwrap();
+        let server_response =
+            association.invoke_method(1, Some(Data::OctetString(client_response)));
+
+        let expected =
+            default_hls_function(MechanismName::HighLevelSecurity, &secret, b"client challenge")
+                .unwrap();
+        assert_eq!(server_response, Ok(Some(Data::OctetString(expected))));
+        assert_eq!(association.association_status, AssociationStatus::Associated);
+    }
+
+    #[test]
+    fn test_hls_authentication_failure() {
+        let mut association = AssociationLn::new()
+            .with_authentication(MechanismName::HighLevelSecurity, b"0123456789ABCDEF".to_vec());
+
+        association.begin_hls_authentication(vec![1; 8], vec![2; 8]);
+        assert_eq!(
+            association.invoke_method(1, Some(Data::OctetString(vec![0; 16]))),
+            Err(ActionResult::ReadWriteDenied)
+        );
+        assert_eq!(association.association_status, AssociationStatus::NonAssociated);
+
+        association.authentication_mechanism_name = MechanismName::HighLevelSecurityMd5;
+        association.begin_hls_authentication(vec![1; 8], vec![2; 8]);
+        assert_eq!(
+            association.invoke_method(1, Some(Data::OctetString(vec![0; 16]))),
+            Err(ActionResult::OtherReason)
+        );
+    }
+
+    #[test]
+    fn test_default_hls_function() {
+        // AES-128 FIPS-197 test vector
+        let key: Vec<u8> = (0u8..16).collect();
+        let plaintext = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ];
+        let ciphertext = [
+            0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4,
+            0xc5, 0x5a,
+        ];
+        assert_eq!(
+            default_hls_function(MechanismName::HighLevelSecurity, &key, &plaintext),
+            Some(ciphertext.to_vec())
+        );
+        assert_eq!(
+            default_hls_function(MechanismName::HighLevelSecurity, &[0; 17], &plaintext),
+            None
+        );
+        assert_eq!(
+            default_hls_function(MechanismName::HighLevelSecurityGmac, &key, &plaintext),
+            None
+        );
+    }
+}
@@ -0,0 +1,590 @@
+//! Periodic capture scheduler for ProfileGeneric
+//!
+//! Captures ProfileGeneric objects of an [`ObjectRegistry`] whenever their
+//! `capture_period` boundary is reached. Boundaries are aligned to multiples of
+//! the period (e.g. exactly on 15-minute marks), counted from midnight of the
+//! time base (UTC unless a local time offset is configured).
+//!
+//! Time is expressed as seconds since 1970-01-01 and comes from a
+//! [`ClockSource`]: the system clock, a closure, or a COSEM [`Clock`] object.
+//!
+//! ## Clock jumps
+//!
+//! Following the behaviour of load profiles in the Blue Book:
+//! - **Forward** (e.g. a time set or `shift_time` skipping several periods):
+//!   one entry is captured for the boundary that was crossed; missed periods
+//!   are skipped by default, or filled with one entry each with
+//!   [`ClockJumpPolicy::FillGaps`].
+//! - **Backward**: no entry is captured for the jump itself; the next capture
+//!   happens on the next boundary after the new time, even if that interval
+//!   was already captured before the jump.
+//!
+//! ## Driving the scheduler
+//!
+//! - Caller driven (firmware main loop, tests): call [`CaptureScheduler::tick`]
+//!   with the current time.
+//! - Background thread: [`CaptureScheduler::spawn`].
+//! - Async runtime: [`CaptureScheduler::run`] with the runtime's sleep function.
+//!
+//! The system clock and both runners need the `std` feature; `tick` and
+//! `tick_with` only use `core` and `alloc`. The `cosem-objects` feature itself
+//! still enables `std`, so the scheduler is not usable on `no_std` targets yet.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::capture_scheduler::CaptureScheduler;
+//! use dlms_cosem::cosem::data::DataObject;
+//! use dlms_cosem::cosem::{CaptureObjectDefinition, CosemObject, ObjectRegistry, ProfileGeneric};
+//! use dlms_cosem::{Data, ObisCode};
+//!
+//! let counter = ObisCode::new(0, 0, 96, 15, 0, 255);
+//! let load_profile = ObisCode::new(1, 0, 99, 1, 0, 255);
+//! let mut objects = ObjectRegistry::new()
+//!     .with_object(DataObject::new(counter, Data::LongUnsigned(1)))
+//!     .with_object(ProfileGeneric::with_fifo(
+//!         load_profile,
+//!         vec![CaptureObjectDefinition {
+//!             class_id: 1,
+//!             logical_name: counter,
+//!             attribute_index: 2,
+//!             data_index: 0,
+//!         }],
+//!         900, // 15 minutes
+//!         96,
+//!     ));
+//!
+//! let mut scheduler = CaptureScheduler::new().with_profile(load_profile);
+//!
+//! assert_eq!(scheduler.tick(1_000, &mut objects), 0); // first boundary is 1800
+//! assert_eq!(scheduler.next_capture(), Some(1_800));
+//! assert_eq!(scheduler.tick(1_800, &mut objects), 1);
+//! assert_eq!(objects.get(7, &load_profile).unwrap().get_attribute(7), Ok(Data::DoubleLongUnsigned(1)));
+//! ```
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::future::Future;
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "std")]
+use std::thread::JoinHandle;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cosem::ObjectRegistry;
+use crate::cosem::clock::Clock;
+use crate::data::Data;
+use crate::obis_code::ObisCode;
+
+/// ProfileGeneric interface class id
+const PROFILE_GENERIC_CLASS_ID: u16 = 7;
+
+/// ProfileGeneric attribute 4: capture_period
+const CAPTURE_PERIOD_ATTRIBUTE: i8 = 4;
+
+/// ProfileGeneric method 2: capture
+const CAPTURE_METHOD: i8 = 2;
+
+/// Longest sleep of the background runners, so clock jumps are noticed.
+#[cfg(feature = "std")]
+const MAX_SLEEP: Duration = Duration::from_secs(1);
+
+/// Source of the current time in seconds since 1970-01-01.
+pub trait ClockSource {
+    /// Returns the current time.
+    fn now(&self) -> u32;
+}
+
+/// The system clock (UTC).
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl ClockSource for SystemClock {
+    fn now(&self) -> u32 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs() as u32)
+    }
+}
+
+impl<F: Fn() -> u32> ClockSource for F {
+    fn now(&self) -> u32 {
+        self()
+    }
+}
+
+/// A COSEM clock object is read as its local date and time.
+///
+/// Wildcard fields count as 0 (January and the 1st for month and day).
+impl ClockSource for Clock {
+    fn now(&self) -> u32 {
+        let date = &self.time.date;
+        let time = &self.time.time;
+        let month = if (1..=12).contains(&date.month) { date.month } else { 1 };
+        let day = if (1..=31).contains(&date.day_of_month) { date.day_of_month } else { 1 };
+        let days = days_from_civil(i64::from(date.year), month.into(), day.into());
+        let seconds = days * 86_400
+            + i64::from(time.hour.unwrap_or(0)) * 3_600
+            + i64::from(time.minute.unwrap_or(0)) * 60
+            + i64::from(time.second.unwrap_or(0));
+        seconds.clamp(0, i64::from(u32::MAX)) as u32
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// What to do with the periods skipped by a forward clock jump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClockJumpPolicy {
+    /// Capture one entry for the latest boundary only.
+    #[default]
+    Skip,
+    /// Capture one entry per skipped boundary, at most `max_entries` in total.
+    ///
+    /// The missed entries hold the values at the time of the tick.
+    FillGaps {
+        /// Upper bound of entries captured in one tick
+        max_entries: u32,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct ScheduledProfile {
+    logical_name: ObisCode,
+    next_capture: Option<u32>,
+}
+
+/// Captures ProfileGeneric objects on their capture_period boundaries.
+///
+/// Profiles with a capture_period of 0 (event driven) are left alone. The
+/// capture_period is read at every tick, so changing it through SET
+/// reschedules the profile.
+#[derive(Debug, Clone, Default)]
+pub struct CaptureScheduler {
+    profiles: Vec<ScheduledProfile>,
+    jump_policy: ClockJumpPolicy,
+    time_offset: i32,
+    last_tick: Option<u32>,
+}
+
+impl CaptureScheduler {
+    /// Creates a scheduler without profiles.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a ProfileGeneric to schedule (builder style).
+    pub fn with_profile(mut self, logical_name: ObisCode) -> Self {
+        self.add_profile(logical_name);
+        self
+    }
+
+    /// Sets how forward clock jumps are handled.
+    pub fn with_jump_policy(mut self, jump_policy: ClockJumpPolicy) -> Self {
+        self.jump_policy = jump_policy;
+        self
+    }
+
+    /// Aligns boundaries to local time, given as offset to the clock source in seconds.
+    ///
+    /// With a UTC clock and UTC+1, an offset of 3600 puts daily captures on
+    /// local midnight.
+    pub fn with_time_offset(mut self, seconds: i32) -> Self {
+        self.time_offset = seconds;
+        self
+    }
+
+    /// Adds a ProfileGeneric to schedule.
+    pub fn add_profile(&mut self, logical_name: ObisCode) {
+        if !self.profiles.iter().any(|profile| profile.logical_name == logical_name) {
+            self.profiles.push(ScheduledProfile { logical_name, next_capture: None });
+        }
+    }
+
+    /// Stops scheduling a ProfileGeneric.
+    pub fn remove_profile(&mut self, logical_name: &ObisCode) {
+        self.profiles.retain(|profile| profile.logical_name != *logical_name);
+    }
+
+    /// Returns the earliest scheduled capture time.
+    ///
+    /// Profiles are scheduled on their first tick.
+    pub fn next_capture(&self) -> Option<u32> {
+        self.profiles.iter().filter_map(|profile| profile.next_capture).min()
+    }
+
+    /// Captures every profile whose boundary has been reached at `now`.
+    ///
+    /// Returns the number of entries captured.
+    pub fn tick(&mut self, now: u32, objects: &mut ObjectRegistry) -> usize {
+        self.tick_by(now, objects, |objects, logical_name| {
+            objects
+                .invoke_method(
+                    PROFILE_GENERIC_CLASS_ID,
+                    logical_name,
+                    CAPTURE_METHOD,
+                    Some(Data::Integer(0)),
+                )
+                .is_ok()
+        })
+    }
+
+    /// Like [`tick`](Self::tick), and appends a record of every captured
+    /// entry to `journal` (see [`persistence::capture`]).
+    ///
+    /// [`persistence::capture`]: crate::cosem::persistence::capture
+    #[cfg(feature = "persistence")]
+    pub fn tick_journaled(
+        &mut self,
+        now: u32,
+        objects: &mut ObjectRegistry,
+        journal: &mut Vec<u8>,
+    ) -> usize {
+        self.tick_by(now, objects, |objects, logical_name| {
+            crate::cosem::persistence::capture(objects, logical_name)
+                .map(|record| journal.extend(record))
+                .is_ok()
+        })
+    }
+
+    fn tick_by(
+        &mut self,
+        now: u32,
+        objects: &mut ObjectRegistry,
+        mut capture: impl FnMut(&mut ObjectRegistry, &ObisCode) -> bool,
+    ) -> usize {
+        if self.last_tick.is_some_and(|last| now < last) {
+            // Backward jump: reschedule on the next boundary after the new time
+            for profile in &mut self.profiles {
+                profile.next_capture = None;
+            }
+        }
+        self.last_tick = Some(now);
+
+        let mut captures = 0;
+        for profile in &mut self.profiles {
+            let period = match objects
+                .get(PROFILE_GENERIC_CLASS_ID, &profile.logical_name)
+                .map(|object| object.get_attribute(CAPTURE_PERIOD_ATTRIBUTE))
+            {
+                Some(Ok(Data::DoubleLongUnsigned(period))) if period > 0 => period,
+                _ => {
+                    profile.next_capture = None;
+                    continue;
+                }
+            };
+
+            let Some(next) = profile.next_capture else {
+                profile.next_capture = Some(boundary_at_or_after(now, period, self.time_offset));
+                continue;
+            };
+            if now < next {
+                continue;
+            }
+
+            let crossed = (now - next) / period + 1;
+            let entries = match self.jump_policy {
+                ClockJumpPolicy::Skip => 1,
+                ClockJumpPolicy::FillGaps { max_entries } => crossed.min(max_entries.max(1)),
+            };
+            for _ in 0..entries {
+                if capture(objects, &profile.logical_name) {
+                    captures += 1;
+                }
+            }
+            profile.next_capture = Some(next.saturating_add(crossed.saturating_mul(period)));
+        }
+        captures
+    }
+
+    /// Ticks with the current time of a clock source.
+    pub fn tick_with(&mut self, clock: &impl ClockSource, objects: &mut ObjectRegistry) -> usize {
+        self.tick(clock.now(), objects)
+    }
+}
+
+#[cfg(feature = "std")]
+impl CaptureScheduler {
+    /// Runs the scheduler on a background thread.
+    ///
+    /// The registry is locked only while capturing.
+    pub fn spawn(
+        mut self,
+        clock: impl ClockSource + Send + 'static,
+        objects: Arc<Mutex<ObjectRegistry>>,
+    ) -> CaptureSchedulerHandle {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = std::thread::spawn({
+            let stop = Arc::clone(&stop);
+            move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let now = clock.now();
+                    self.tick(now, &mut objects.lock().unwrap_or_else(|e| e.into_inner()));
+                    std::thread::sleep(self.sleep_duration(now));
+                }
+                self
+            }
+        });
+        CaptureSchedulerHandle { stop, thread }
+    }
+
+    /// Runs the scheduler on an async runtime, until the future is dropped.
+    ///
+    /// `sleep` is the runtime's timer, e.g. `tokio::time::sleep`.
+    pub async fn run<S, F>(
+        &mut self,
+        clock: &impl ClockSource,
+        objects: &Mutex<ObjectRegistry>,
+        mut sleep: S,
+    ) where
+        S: FnMut(Duration) -> F,
+        F: Future<Output = ()>,
+    {
+        loop {
+            let now = clock.now();
+            self.tick(now, &mut objects.lock().unwrap_or_else(|e| e.into_inner()));
+            sleep(self.sleep_duration(now)).await;
+        }
+    }
+
+    fn sleep_duration(&self, now: u32) -> Duration {
+        self.next_capture()
+            .map_or(MAX_SLEEP, |next| Duration::from_secs(next.saturating_sub(now).into()))
+            .clamp(Duration::from_millis(10), MAX_SLEEP)
+    }
+}
+
+/// First boundary of `period` at or after `now`, in the time base shifted by `time_offset`.
+fn boundary_at_or_after(now: u32, period: u32, time_offset: i32) -> u32 {
+    let local = i64::from(now) + i64::from(time_offset);
+    let boundary = local.div_euclid(i64::from(period)) * i64::from(period);
+    let boundary = if boundary < local { boundary + i64::from(period) } else { boundary };
+    (boundary - i64::from(time_offset)).clamp(0, i64::from(u32::MAX)) as u32
+}
+
+/// Handle of a scheduler running on a background thread.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct CaptureSchedulerHandle {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<CaptureScheduler>,
+}
+
+#[cfg(feature = "std")]
+impl CaptureSchedulerHandle {
+    /// Stops the thread and returns the scheduler.
+    pub fn stop(self) -> CaptureScheduler {
+        self.stop.store(true, Ordering::Relaxed);
+        self.thread.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::data::DataObject;
+    use crate::cosem::{CaptureObjectDefinition, ProfileGeneric};
+    use crate::{Date, DateTime, Time};
+
+    const COUNTER: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 15, e: 0, f: 255 };
+    const LOAD_PROFILE: ObisCode = ObisCode { a: 1, b: 0, c: 99, d: 1, e: 0, f: 255 };
+
+    fn objects(capture_period: u32) -> ObjectRegistry {
+        ObjectRegistry::new()
+            .with_object(DataObject::new(COUNTER, Data::LongUnsigned(0)))
+            .with_object(ProfileGeneric::with_fifo(
+                LOAD_PROFILE,
+                vec![CaptureObjectDefinition {
+                    class_id: 1,
+                    logical_name: COUNTER,
+                    attribute_index: 2,
+                    data_index: 0,
+                }],
+                capture_period,
+                100,
+            ))
+    }
+
+    fn entries(objects: &ObjectRegistry) -> Data {
+        objects.get(7, &LOAD_PROFILE).unwrap().get_attribute(7).unwrap()
+    }
+
+    #[test]
+    fn test_aligned_captures() {
+        let mut objects = objects(900);
+        let mut scheduler = CaptureScheduler::new().with_profile(LOAD_PROFILE);
+
+        assert_eq!(scheduler.tick(899, &mut objects), 0);
+        assert_eq!(scheduler.next_capture(), Some(900));
+        assert_eq!(scheduler.tick(900, &mut objects), 1);
+        assert_eq!(scheduler.tick(901, &mut objects), 0);
+        assert_eq!(scheduler.tick(1_799, &mut objects), 0);
+        assert_eq!(scheduler.tick(1_805, &mut objects), 1);
+        assert_eq!(scheduler.next_capture(), Some(2_700));
+        assert_eq!(entries(&objects), Data::DoubleLongUnsigned(2));
+    }
+
+    #[test]
+    fn test_first_tick_on_boundary_captures() {
+        let mut objects = objects(60);
+        let mut scheduler = CaptureScheduler::new().with_profile(LOAD_PROFILE);
+
+        assert_eq!(scheduler.tick(120, &mut objects), 0);
+        assert_eq!(scheduler.next_capture(), Some(120));
+        assert_eq!(scheduler.tick(120, &mut objects), 1);
+    }
+
+    #[test]
+    fn test_forward_jump_skips_missed_periods() {
+        let mut objects = objects(900);
+        let mut scheduler = CaptureScheduler::new().with_profile(LOAD_PROFILE);
+
+        scheduler.tick(0, &mut objects);
+        assert_eq!(scheduler.tick(3_700, &mut objects), 1);
+        assert_eq!(scheduler.next_capture(), Some(4_500));
+    }
+
+    #[test]
+    fn test_forward_jump_fills_gaps() {
+        let mut objects = objects(900);
+        let mut scheduler = CaptureScheduler::new()
+            .with_profile(LOAD_PROFILE)
+            .with_jump_policy(ClockJumpPolicy::FillGaps { max_entries: 3 });
+
+        scheduler.tick(1, &mut objects);
+        assert_eq!(scheduler.tick(2_000, &mut objects), 2); // 900 and 1800
+        assert_eq!(scheduler.tick(90_000, &mut objects), 3); // capped
+        assert_eq!(scheduler.next_capture(), Some(90_900));
+    }
+
+    #[test]
+    fn test_backward_jump_reschedules() {
+        let mut objects = objects(900);
+        let mut scheduler = CaptureScheduler::new().with_profile(LOAD_PROFILE);
+
+        scheduler.tick(1_000, &mut objects);
+        assert_eq!(scheduler.tick(1_800, &mut objects), 1);
+        // Clock set back by 20 minutes
+        assert_eq!(scheduler.tick(600, &mut objects), 0);
+        assert_eq!(scheduler.next_capture(), Some(900));
+        assert_eq!(scheduler.tick(900, &mut objects), 1);
+    }
+
+    #[test]
+    fn test_capture_period_changes() {
+        let mut objects = objects(0);
+        let mut scheduler = CaptureScheduler::new().with_profile(LOAD_PROFILE);
+
+        // Event driven profiles are not scheduled
+        assert_eq!(scheduler.tick(1_000, &mut objects), 0);
+        assert_eq!(scheduler.next_capture(), None);
+
+        objects
+            .get_mut(7, &LOAD_PROFILE)
+            .unwrap()
+            .set_attribute(4, Data::DoubleLongUnsigned(60))
+            .unwrap();
+        scheduler.tick(1_000, &mut objects);
+        assert_eq!(scheduler.next_capture(), Some(1_020));
+
+        scheduler.remove_profile(&LOAD_PROFILE);
+        assert_eq!(scheduler.next_capture(), None);
+    }
+
+    #[test]
+    fn test_time_offset() {
+        let mut objects = objects(86_400);
+        // UTC+1: local midnight is 23:00 UTC
+        let mut scheduler =
+            CaptureScheduler::new().with_profile(LOAD_PROFILE).with_time_offset(3_600);
+
+        scheduler.tick(3_600, &mut objects);
+        assert_eq!(scheduler.next_capture(), Some(82_800));
+    }
+
+    #[test]
+    fn test_clock_object_source() {
+        let mut clock = Clock::new(ObisCode::new(0, 0, 1, 0, 0, 255));
+        clock.time = DateTime::new(
+            Date::new(2024, 3, 1, 0xFF),
+            Time::new(Some(12), Some(15), Some(0), Some(0)),
+            None,
+            None,
+        );
+        assert_eq!(clock.now(), 1_709_295_300);
+
+        let mut objects = objects(900);
+        let mut scheduler = CaptureScheduler::new().with_profile(LOAD_PROFILE);
+        scheduler.tick_with(&clock, &mut objects);
+        assert_eq!(scheduler.tick_with(&clock, &mut objects), 1);
+
+        clock.shift_time(-60).unwrap();
+        assert_eq!(scheduler.tick_with(&clock, &mut objects), 0);
+        assert_eq!(scheduler.next_capture(), Some(1_709_295_300));
+    }
+
+    #[test]
+    fn test_closure_source() {
+        let time = 42;
+        assert_eq!((|| time).now(), 42);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_system_clock() {
+        assert!(SystemClock.now() > 1_700_000_000);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_spawn_and_stop() {
+        let objects = Arc::new(Mutex::new(objects(1)));
+        let handle = CaptureScheduler::new()
+            .with_profile(LOAD_PROFILE)
+            .spawn(SystemClock, Arc::clone(&objects));
+        std::thread::sleep(Duration::from_millis(50));
+        let scheduler = handle.stop();
+
+        assert!(scheduler.next_capture().is_some());
+    }
+
+    #[cfg(feature = "std")]
+    #[tokio::test]
+    async fn test_run_async() {
+        let objects = Mutex::new(objects(1));
+        let mut scheduler = CaptureScheduler::new().with_profile(LOAD_PROFILE);
+
+        let run = scheduler.run(&SystemClock, &objects, tokio::time::sleep);
+        assert!(tokio::time::timeout(Duration::from_millis(2_500), run).await.is_err());
+
+        assert!(matches!(entries(&objects.lock().unwrap()), Data::DoubleLongUnsigned(1..)));
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_tick_journaled() {
+        let mut objects = objects(900);
+        let mut scheduler = CaptureScheduler::new().with_profile(LOAD_PROFILE);
+        let mut journal = Vec::new();
+
+        scheduler.tick_journaled(1_000, &mut objects, &mut journal);
+        assert!(journal.is_empty());
+        assert_eq!(scheduler.tick_journaled(1_800, &mut objects, &mut journal), 1);
+
+        let records = crate::cosem::persistence::parse_records(&journal).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(entries(&objects), Data::DoubleLongUnsigned(1));
+    }
+}
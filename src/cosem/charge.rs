@@ -0,0 +1,662 @@
+//! COSEM Interface Class 113: Charge
+//!
+//! A Charge collects money from the credits of a prepayment Account: for
+//! the energy consumed, per period of time (standing charges), or as a share
+//! of payments.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.19.20.0.255)
+//! - Attribute 2: `total_amount_paid` - Sum of the collections (read-only)
+//! - Attribute 3: `charge_type` - Consumption, time or payment event based
+//! - Attribute 4: `priority` - 1 (highest) to 255
+//! - Attribute 5: `unit_charge_active` - Unit charge in force (read-only)
+//! - Attribute 6: `unit_charge_passive` - Unit charge activated later
+//! - Attribute 7: `unit_charge_activation_time` - When the passive unit
+//!   charge is activated
+//! - Attribute 8: `period` - Collection period in seconds
+//! - Attribute 9: `charge_configuration` - Bit string: percentage based
+//!   collection, continuous collection
+//! - Attribute 10: `last_collection_time` - (read-only)
+//! - Attribute 11: `last_collection_amount` - (read-only)
+//! - Attribute 12: `total_amount_remaining` - Debt left to collect
+//!   (read-only)
+//! - Attribute 13: `proportion` - Share of payments, in 0.01 %
+//!
+//! A unit charge is `{charge_per_unit_scaling {commodity_scale, price_scale},
+//! commodity {class_id, logical_name, attribute_index}, charge_table
+//! [{index, charge_per_unit}]}`.
+//!
+//! ## Methods
+//! - Method 1: `update_unit_charge(unit_charge)` - Writes the passive unit
+//!   charge
+//! - Method 2: `activate_passive_unit_charge(integer)` - Activates it now
+//! - Method 3: `collect(integer)` - Collects the charge
+//! - Method 4: `update_total_amount_remaining(double-long)` - Adds to the debt
+//! - Method 5: `set_total_amount_remaining(double-long)` - Sets the debt
+//!
+//! ## Collection
+//!
+//! A consumption based charge collects the increase of the commodity value
+//! since the previous collection (the first collection only takes the
+//! reference), a time based charge collects one unit per collection. The
+//! amount is `units * 10^commodity_scale * charge_per_unit * 10^price_scale`,
+//! using the first element of the charge table; tariff-indexed tables are not
+//! evaluated. Payment event based charges collect nothing. While
+//! `total_amount_remaining` is positive, collections are capped to it and
+//! reduce it; once it reaches zero, the charge collects in full again.
+//!
+//! In a registry, `collect` reads the commodity and debits the collected
+//! amount from the credit in use of the [`Account`](crate::cosem::account)
+//! referencing the charge. Collections are triggered by invoking `collect`,
+//! typically from a script run every `period`. [`ObjectRegistry::tick`]
+//! activates the passive unit charge at its activation time and stamps the
+//! collections; times are local seconds since 1970-01-01.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::charge::{Charge, ChargeTableElement, ChargeType, UnitCharge};
+//! use dlms_cosem::cosem::register_monitor::ValueDefinition;
+//! use dlms_cosem::ObisCode;
+//!
+//! let energy = ValueDefinition { class_id: 3, logical_name: ObisCode::new(1, 0, 1, 8, 0, 255), attribute_index: 2 };
+//! let mut charge = Charge::new(ObisCode::new(0, 0, 19, 20, 0, 255), ChargeType::ConsumptionBased, 1)
+//!     .with_unit_charge(UnitCharge {
+//!         // Wh counted, 0.25 per kWh charged in cents
+//!         commodity_scale: -3,
+//!         price_scale: 0,
+//!         commodity: energy,
+//!         charge_table: vec![ChargeTableElement { index: vec![1], charge_per_unit: 25 }],
+//!     });
+//!
+//! assert_eq!(charge.collect(Some(120_000.0)), 0);
+//! assert_eq!(charge.collect(Some(124_000.0)), 100);
+//! assert_eq!(charge.total_amount_paid, 100);
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::action::ActionResult;
+use crate::cosem::account;
+use crate::cosem::activity_calendar::{NOT_SCHEDULED, transition_seconds};
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::cosem::push_setup::date_time_at;
+use crate::cosem::register_monitor::{ValueDefinition, numeric};
+use crate::cosem::script_table::ScriptReference;
+use crate::cosem::{CosemObject, ObjectRegistry};
+use crate::data::{Data, DateTime};
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Charge interface class id
+pub(crate) const CHARGE_CLASS_ID: u16 = 113;
+
+/// `charge_configuration` flag: the charge is a percentage of the payments.
+pub const PERCENTAGE_BASED_COLLECTION: u8 = 0x80;
+/// `charge_configuration` flag: the charge is collected even when the
+/// supply is disconnected.
+pub const CONTINUOUS_COLLECTION: u8 = 0x80 >> 1;
+
+/// Basis of a charge (attribute 3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ChargeType {
+    /// Charged per unit consumed
+    ConsumptionBased = 0,
+    /// Charged per period of time
+    TimeBased = 1,
+    /// Charged as a share of the payments
+    PaymentEventBased = 2,
+}
+
+impl ChargeType {
+    /// Converts a charge_type enum value.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::ConsumptionBased),
+            1 => Ok(Self::TimeBased),
+            2 => Ok(Self::PaymentEventBased),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Element of a charge table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChargeTableElement {
+    /// Tariff index
+    pub index: Vec<u8>,
+    /// Price per unit, scaled by `price_scale`
+    pub charge_per_unit: i16,
+}
+
+/// Unit charge (attributes 5 and 6).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UnitCharge {
+    /// Power of ten of the commodity units
+    pub commodity_scale: i8,
+    /// Power of ten of the prices
+    pub price_scale: i8,
+    /// Attribute holding the commodity, e.g. the value of an energy Register
+    pub commodity: ValueDefinition,
+    /// Prices
+    pub charge_table: Vec<ChargeTableElement>,
+}
+
+impl UnitCharge {
+    /// Returns the amount charged for `units` of commodity, rounded.
+    pub fn charge(&self, units: f64) -> i32 {
+        let Some(element) = self.charge_table.first() else {
+            return 0;
+        };
+        let scale = i32::from(self.commodity_scale) + i32::from(self.price_scale);
+        (units * f64::from(element.charge_per_unit) * 10f64.powi(scale)).round() as i32
+    }
+
+    /// Encodes the unit charge.
+    pub fn to_data(&self) -> Data {
+        Data::Structure(vec![
+            Data::Structure(vec![
+                Data::Integer(self.commodity_scale),
+                Data::Integer(self.price_scale),
+            ]),
+            self.commodity.to_data(),
+            Data::Structure(
+                self.charge_table
+                    .iter()
+                    .map(|element| {
+                        Data::Structure(vec![
+                            Data::OctetString(element.index.clone()),
+                            Data::Long(element.charge_per_unit),
+                        ])
+                    })
+                    .collect(),
+            ),
+        ])
+    }
+
+    /// Parses a unit charge.
+    pub fn from_data(data: &Data) -> Result<Self, DataAccessResult> {
+        let Data::Structure(fields) = data else {
+            return Err(DataAccessResult::TypeUnmatched);
+        };
+        match fields.as_slice() {
+            [Data::Structure(scaling), commodity, Data::Structure(charge_table)] => {
+                let [Data::Integer(commodity_scale), Data::Integer(price_scale)] =
+                    scaling.as_slice()
+                else {
+                    return Err(DataAccessResult::TypeUnmatched);
+                };
+                Ok(Self {
+                    commodity_scale: *commodity_scale,
+                    price_scale: *price_scale,
+                    commodity: ValueDefinition::from_data(commodity)?,
+                    charge_table: charge_table
+                        .iter()
+                        .map(|element| match element {
+                            Data::Structure(fields) => match fields.as_slice() {
+                                [Data::OctetString(index), Data::Long(charge_per_unit)] => {
+                                    Ok(ChargeTableElement {
+                                        index: index.clone(),
+                                        charge_per_unit: *charge_per_unit,
+                                    })
+                                }
+                                _ => Err(DataAccessResult::TypeUnmatched),
+                            },
+                            _ => Err(DataAccessResult::TypeUnmatched),
+                        })
+                        .collect::<Result<_, _>>()?,
+                })
+            }
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+impl Default for UnitCharge {
+    /// A unit charge without commodity nor prices.
+    fn default() -> Self {
+        Self {
+            commodity_scale: 0,
+            price_scale: 0,
+            commodity: ValueDefinition {
+                class_id: 0,
+                logical_name: ObisCode::new(0, 0, 0, 0, 0, 0),
+                attribute_index: 0,
+            },
+            charge_table: Vec::new(),
+        }
+    }
+}
+
+/// Charge object - COSEM Interface Class 113
+///
+/// Reference: Blue Book 4.6.4
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Charge {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Sum of the collections
+    pub total_amount_paid: i32,
+    /// Attribute 3: Basis of the charge
+    pub charge_type: ChargeType,
+    /// Attribute 4: Priority, 1 is collected first
+    pub priority: u8,
+    /// Attribute 5: Unit charge in force
+    pub unit_charge_active: UnitCharge,
+    /// Attribute 6: Unit charge activated at `unit_charge_activation_time`
+    pub unit_charge_passive: UnitCharge,
+    /// Attribute 7: Local activation time of the passive unit charge
+    pub unit_charge_activation_time: DateTime,
+    /// Attribute 8: Collection period in seconds
+    pub period: u32,
+    /// Attribute 9: Configuration flags
+    pub charge_configuration: u8,
+    /// Attribute 10: Local time of the last collection
+    pub last_collection_time: DateTime,
+    /// Attribute 11: Amount of the last collection
+    pub last_collection_amount: i32,
+    /// Attribute 12: Debt left to collect
+    pub total_amount_remaining: i32,
+    /// Attribute 13: Share of payments, in 0.01 %
+    pub proportion: u16,
+    /// Commodity value at the last consumption based collection
+    last_commodity_value: Option<f64>,
+    /// Time of the last tick
+    now: Option<u32>,
+}
+
+impl Charge {
+    /// Creates a charge without unit charge, collecting nothing.
+    pub fn new(logical_name: ObisCode, charge_type: ChargeType, priority: u8) -> Self {
+        Self {
+            logical_name,
+            total_amount_paid: 0,
+            charge_type,
+            priority,
+            unit_charge_active: UnitCharge::default(),
+            unit_charge_passive: UnitCharge::default(),
+            unit_charge_activation_time: NOT_SCHEDULED,
+            period: 0,
+            charge_configuration: 0,
+            last_collection_time: NOT_SCHEDULED,
+            last_collection_amount: 0,
+            total_amount_remaining: 0,
+            proportion: 0,
+            last_commodity_value: None,
+            now: None,
+        }
+    }
+
+    /// Sets the active unit charge (builder style).
+    pub fn with_unit_charge(mut self, unit_charge: UnitCharge) -> Self {
+        self.unit_charge_active = unit_charge;
+        self
+    }
+
+    /// Sets the collection period in seconds (builder style).
+    pub fn with_period(mut self, seconds: u32) -> Self {
+        self.period = seconds;
+        self
+    }
+
+    /// Sets the debt to collect (builder style).
+    pub fn with_total_amount_remaining(mut self, amount: i32) -> Self {
+        self.total_amount_remaining = amount;
+        self
+    }
+
+    /// Makes the passive unit charge active, as the
+    /// `activate_passive_unit_charge` method does.
+    pub fn activate_passive_unit_charge(&mut self) {
+        self.unit_charge_active = self.unit_charge_passive.clone();
+    }
+
+    /// Collects the charge given the commodity value, if readable, and
+    /// returns the amount collected.
+    pub fn collect(&mut self, commodity_value: Option<f64>) -> i32 {
+        let units = match self.charge_type {
+            ChargeType::ConsumptionBased => {
+                let previous = self.last_commodity_value;
+                self.last_commodity_value = commodity_value.or(previous);
+                match (previous, commodity_value) {
+                    (Some(previous), Some(value)) => (value - previous).max(0.0),
+                    _ => 0.0,
+                }
+            }
+            ChargeType::TimeBased => 1.0,
+            ChargeType::PaymentEventBased => 0.0,
+        };
+        let mut amount = self.unit_charge_active.charge(units);
+        if self.total_amount_remaining > 0 {
+            amount = amount.min(self.total_amount_remaining);
+            self.total_amount_remaining -= amount;
+        }
+        self.last_collection_amount = amount;
+        self.total_amount_paid = self.total_amount_paid.saturating_add(amount);
+        if let Some(now) = self.now {
+            self.last_collection_time = date_time_at(now);
+        }
+        amount
+    }
+
+    /// Collects the charge, reading the commodity from `objects`, and debits
+    /// the amount from the credit in use of the account referencing the
+    /// charge. Returns the amount collected.
+    pub fn collect_from(&mut self, objects: &mut ObjectRegistry) -> i32 {
+        let commodity_value =
+            self.unit_charge_active.commodity.read(objects).ok().and_then(|value| numeric(&value));
+        let amount = self.collect(commodity_value);
+        if amount != 0 {
+            account::debit(objects, &self.logical_name, amount);
+        }
+        amount
+    }
+
+    /// Activates the passive unit charge once its activation time is reached.
+    pub fn update(&mut self, now: u32) {
+        self.now = Some(now);
+        let activation = &self.unit_charge_activation_time;
+        let due = activation.date.year != 0xFFFF
+            && transition_seconds(activation, activation.date.year.into())
+                .is_some_and(|at| at <= i64::from(now));
+        if due {
+            self.activate_passive_unit_charge();
+            self.unit_charge_activation_time = NOT_SCHEDULED;
+        }
+    }
+}
+
+impl CosemObject for Charge {
+    fn class_id(&self) -> u16 {
+        CHARGE_CLASS_ID
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::DoubleLong(self.total_amount_paid)),
+            3 => Ok(Data::Enum(self.charge_type as u8)),
+            4 => Ok(Data::Unsigned(self.priority)),
+            5 => Ok(self.unit_charge_active.to_data()),
+            6 => Ok(self.unit_charge_passive.to_data()),
+            7 => Ok(Data::DateTime(self.unit_charge_activation_time.clone())),
+            8 => Ok(Data::DoubleLongUnsigned(self.period)),
+            9 => Ok(Data::BitString(vec![self.charge_configuration])),
+            10 => Ok(Data::DateTime(self.last_collection_time.clone())),
+            11 => Ok(Data::DoubleLong(self.last_collection_amount)),
+            12 => Ok(Data::DoubleLong(self.total_amount_remaining)),
+            13 => Ok(Data::LongUnsigned(self.proportion)),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (3, Data::Enum(charge_type)) => self.charge_type = ChargeType::from_u8(charge_type)?,
+            (4, Data::Unsigned(priority)) if priority > 0 => self.priority = priority,
+            (4, Data::Unsigned(_)) => return Err(DataAccessResult::OtherReason),
+            (6, value) => self.unit_charge_passive = UnitCharge::from_data(&value)?,
+            (7, Data::DateTime(time)) => self.unit_charge_activation_time = time,
+            (8, Data::DoubleLongUnsigned(seconds)) => self.period = seconds,
+            (9, Data::BitString(bits)) => {
+                self.charge_configuration = bits.first().copied().unwrap_or(0)
+            }
+            (13, Data::LongUnsigned(proportion)) if proportion <= 10_000 => {
+                self.proportion = proportion
+            }
+            (13, Data::LongUnsigned(_)) => return Err(DataAccessResult::OtherReason),
+            (3 | 4 | 7 | 8 | 9 | 13, _) => return Err(DataAccessResult::TypeUnmatched),
+            (1 | 2 | 5 | 10..=12, _) => return Err(DataAccessResult::ReadWriteDenied),
+            _ => return Err(DataAccessResult::ObjectUndefined),
+        }
+        Ok(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match (method_id, params) {
+            (1, Some(unit_charge)) => {
+                self.unit_charge_passive =
+                    UnitCharge::from_data(&unit_charge).map_err(|_| ActionResult::TypeUnmatched)?
+            }
+            (2, _) => self.activate_passive_unit_charge(),
+            (3, _) => {
+                self.collect(None);
+            }
+            (4, Some(Data::DoubleLong(amount))) => {
+                self.total_amount_remaining = self.total_amount_remaining.saturating_add(amount)
+            }
+            (5, Some(Data::DoubleLong(amount))) => self.total_amount_remaining = amount,
+            (1 | 4 | 5, _) => return Err(ActionResult::TypeUnmatched),
+            _ => return Err(ActionResult::ObjectUndefined),
+        }
+        Ok(None)
+    }
+
+    /// Collects with the commodity read from the registry and debits the
+    /// account's credit.
+    fn invoke_method_with_objects(
+        &mut self,
+        objects: &mut ObjectRegistry,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match method_id {
+            3 => {
+                self.collect_from(objects);
+                Ok(None)
+            }
+            _ => self.invoke_method(method_id, params),
+        }
+    }
+
+    /// Activates the passive unit charge when due.
+    fn tick(
+        &mut self,
+        now: u32,
+        _objects: &ObjectRegistry,
+        _executor: &mut dyn FnMut(&ScriptReference),
+    ) {
+        self.update(now);
+    }
+
+    /// Persists the configuration attributes, then the collection state.
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        let mut state =
+            ObjectState::from_attributes(self, [3, 4, 6, 7, 8, 9, 13, 2, 5, 10, 11, 12]).ok()?;
+        state.attributes.push(self.last_commodity_value.map_or(Data::Null, Data::Float64));
+        Some(state)
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        match state.restore_attributes(self, [3, 4, 6, 7, 8, 9, 13])?.as_slice() {
+            [
+                Data::DoubleLong(total_amount_paid),
+                unit_charge_active,
+                Data::DateTime(last_collection_time),
+                Data::DoubleLong(last_collection_amount),
+                Data::DoubleLong(total_amount_remaining),
+                last_commodity_value,
+            ] => {
+                self.total_amount_paid = *total_amount_paid;
+                self.unit_charge_active = UnitCharge::from_data(unit_charge_active)?;
+                self.last_collection_time = last_collection_time.clone();
+                self.last_collection_amount = *last_collection_amount;
+                self.total_amount_remaining = *total_amount_remaining;
+                self.last_commodity_value = numeric(last_commodity_value);
+                Ok(())
+            }
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::account::{Account, PaymentMode};
+    use crate::cosem::credit::{Credit, CreditStatus, CreditType};
+    use crate::cosem::register::Register;
+    use crate::{ScalerUnit, Unit};
+
+    const ACCOUNT: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 0, e: 0, f: 255 };
+    const TOKEN_CREDIT: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 10, e: 0, f: 255 };
+    const EMERGENCY_CREDIT: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 10, e: 1, f: 255 };
+    const CHARGE: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 20, e: 0, f: 255 };
+    const ENERGY: ObisCode = ObisCode { a: 1, b: 0, c: 1, d: 8, e: 0, f: 255 };
+
+    /// 0.25 per kWh of an energy register in Wh.
+    fn per_kwh(charge_per_unit: i16) -> UnitCharge {
+        UnitCharge {
+            commodity_scale: -3,
+            price_scale: 0,
+            commodity: ValueDefinition { class_id: 3, logical_name: ENERGY, attribute_index: 2 },
+            charge_table: vec![ChargeTableElement { index: vec![1], charge_per_unit }],
+        }
+    }
+
+    fn credit(objects: &ObjectRegistry, credit: &ObisCode) -> (Data, Data) {
+        let credit = objects.get(112, credit).unwrap();
+        (credit.get_attribute(2).unwrap(), credit.get_attribute(8).unwrap())
+    }
+
+    #[test]
+    fn test_collect_debits_credit_in_use() {
+        let mut objects = ObjectRegistry::new()
+            .with_object(Register::new(
+                ENERGY,
+                Data::DoubleLongUnsigned(120_000),
+                ScalerUnit { scaler: 0, unit: Unit::WattHour },
+            ))
+            .with_object(
+                Account::new(ACCOUNT, PaymentMode::Prepayment)
+                    .with_credit(TOKEN_CREDIT, 100)
+                    .with_credit(EMERGENCY_CREDIT, 0)
+                    .with_charge(CHARGE),
+            )
+            .with_object(
+                Credit::new(TOKEN_CREDIT, CreditType::Token, 1).with_preset_credit_amount(150),
+            )
+            .with_object(
+                Credit::new(EMERGENCY_CREDIT, CreditType::Emergency, 2)
+                    .with_preset_credit_amount(500),
+            )
+            .with_object(
+                Charge::new(CHARGE, ChargeType::ConsumptionBased, 1).with_unit_charge(per_kwh(25)),
+            );
+
+        assert_eq!(objects.invoke_method(113, &CHARGE, 3, Some(Data::Integer(0))), Ok(None));
+        assert_eq!(credit(&objects, &TOKEN_CREDIT).0, Data::DoubleLong(150));
+
+        let energy = objects.get_mut(3, &ENERGY).unwrap();
+        energy.set_attribute(2, Data::DoubleLongUnsigned(124_000)).unwrap();
+        objects.invoke_method(113, &CHARGE, 3, Some(Data::Integer(0))).unwrap();
+        assert_eq!(
+            credit(&objects, &TOKEN_CREDIT),
+            (Data::DoubleLong(50), Data::Enum(CreditStatus::InUse as u8))
+        );
+
+        // The emergency credit is only used once invoked
+        let energy = objects.get_mut(3, &ENERGY).unwrap();
+        energy.set_attribute(2, Data::DoubleLongUnsigned(128_000)).unwrap();
+        objects.invoke_method(113, &CHARGE, 3, Some(Data::Integer(0))).unwrap();
+        assert_eq!(
+            credit(&objects, &TOKEN_CREDIT),
+            (Data::DoubleLong(-50), Data::Enum(CreditStatus::Exhausted as u8))
+        );
+
+        objects.invoke_method(112, &EMERGENCY_CREDIT, 3, Some(Data::Enum(2))).unwrap();
+        let energy = objects.get_mut(3, &ENERGY).unwrap();
+        energy.set_attribute(2, Data::DoubleLongUnsigned(130_000)).unwrap();
+        objects.invoke_method(113, &CHARGE, 3, Some(Data::Integer(0))).unwrap();
+        assert_eq!(credit(&objects, &TOKEN_CREDIT).0, Data::DoubleLong(-50));
+        assert_eq!(
+            credit(&objects, &EMERGENCY_CREDIT),
+            (Data::DoubleLong(450), Data::Enum(CreditStatus::InUse as u8))
+        );
+
+        let charge = objects.get(113, &CHARGE).unwrap();
+        assert_eq!(charge.get_attribute(2), Ok(Data::DoubleLong(250)));
+        assert_eq!(charge.get_attribute(11), Ok(Data::DoubleLong(50)));
+    }
+
+    #[test]
+    fn test_debt_and_unit_charges() {
+        let mut charge = Charge::new(CHARGE, ChargeType::TimeBased, 1)
+            .with_unit_charge(per_kwh(30_000))
+            .with_period(86_400)
+            .with_total_amount_remaining(70);
+        assert_eq!(charge.collect(None), 30);
+        assert_eq!(charge.collect(None), 30);
+        assert_eq!(charge.collect(None), 10);
+        assert_eq!(charge.total_amount_paid, 70);
+        assert_eq!(charge.get_attribute(12), Ok(Data::DoubleLong(0)));
+        assert_eq!(charge.invoke_method(4, Some(Data::DoubleLong(40))), Ok(None));
+        assert_eq!(charge.invoke_method(4, Some(Data::DoubleLong(5))), Ok(None));
+        assert_eq!(charge.get_attribute(12), Ok(Data::DoubleLong(45)));
+        assert_eq!(charge.invoke_method(5, Some(Data::DoubleLong(0))), Ok(None));
+        assert_eq!(charge.collect(None), 30);
+
+        assert_eq!(charge.invoke_method(1, Some(per_kwh(20_000).to_data())), Ok(None));
+        assert_eq!(charge.invoke_method(2, Some(Data::Integer(0))), Ok(None));
+        assert_eq!(charge.collect(None), 20);
+
+        charge.unit_charge_passive = per_kwh(10_000);
+        charge.unit_charge_activation_time = date_time_at(1_738_368_000);
+        charge.update(1_738_367_999);
+        assert_eq!(charge.unit_charge_active, per_kwh(20_000));
+        charge.update(1_738_368_000);
+        assert_eq!(charge.unit_charge_active, per_kwh(10_000));
+        assert_eq!(charge.unit_charge_activation_time, NOT_SCHEDULED);
+        assert_eq!(charge.collect(None), 10);
+        assert_eq!(charge.get_attribute(10), Ok(Data::DateTime(date_time_at(1_738_368_000))));
+        assert_eq!(charge.invoke_method(1, Some(Data::Null)), Err(ActionResult::TypeUnmatched));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let mut charge = Charge::new(CHARGE, ChargeType::ConsumptionBased, 1);
+        assert_eq!(charge.set_attribute(6, per_kwh(25).to_data()), Ok(()));
+        assert_eq!(charge.get_attribute(6), Ok(per_kwh(25).to_data()));
+        assert_eq!(charge.get_attribute(5), Ok(UnitCharge::default().to_data()));
+        assert_eq!(charge.set_attribute(13, Data::LongUnsigned(2_500)), Ok(()));
+        assert_eq!(charge.set_attribute(9, Data::BitString(vec![CONTINUOUS_COLLECTION])), Ok(()));
+        assert_eq!(charge.get_attribute(9), Ok(Data::BitString(vec![0x40])));
+
+        assert_eq!(
+            charge.set_attribute(13, Data::LongUnsigned(10_001)),
+            Err(DataAccessResult::OtherReason)
+        );
+        assert_eq!(charge.set_attribute(3, Data::Enum(3)), Err(DataAccessResult::TypeUnmatched));
+        assert_eq!(
+            charge.set_attribute(6, Data::Structure(vec![])),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        assert_eq!(
+            charge.set_attribute(5, per_kwh(25).to_data()),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+        assert_eq!(
+            charge.set_attribute(12, Data::DoubleLong(0)),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+    }
+}
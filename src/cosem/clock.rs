@@ -46,6 +46,8 @@ use crate::action::ActionResult;
 use crate::cosem::CosemObject;
 use crate::get::DataAccessResult;
 use crate::{Data, DateTime, ObisCode};
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
 
 /// Helper: Create a wildcard DateTime
 fn wildcard_datetime() -> DateTime {
@@ -659,6 +661,25 @@ impl CosemObject for Clock {
             _ => Err(ActionResult::ObjectUndefined),
         }
     }
+
+    /// Persists attributes 2 to 9 and the preset time.
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        let mut state = ObjectState::from_attributes(self, 2..=9).ok()?;
+        state.attributes.push(Data::DateTime(self.preset_time.clone()));
+        Some(state)
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        match state.restore_attributes(self, 2..=9)?.as_slice() {
+            [Data::DateTime(preset_time)] => {
+                self.preset_time = preset_time.clone();
+                Ok(())
+            }
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
 }
 
 #[cfg(test)]
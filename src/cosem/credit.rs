@@ -0,0 +1,416 @@
+//! COSEM Interface Class 112: Credit
+//!
+//! A Credit holds an amount of money (or energy) available to a prepayment
+//! Account: credit bought with tokens, emergency credit, etc. The Account
+//! consumes its credits by priority.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.19.10.0.255)
+//! - Attribute 2: `current_credit_amount` - Remaining amount (read-only)
+//! - Attribute 3: `credit_type` - Token, reserved, emergency, time or
+//!   consumption based
+//! - Attribute 4: `priority` - 1 (highest) to 255
+//! - Attribute 5: `warning_threshold` - Amount below which to warn
+//! - Attribute 6: `limit` - Amount at or below which the credit is exhausted
+//! - Attribute 7: `credit_configuration` - Bit string, see the `REQUIRES_*`,
+//!   [`RESETTABLE`] and [`RECEIVES_TOKENS`] flags
+//! - Attribute 8: `credit_status` - Enabled, selectable, selected, in use or
+//!   exhausted (read-only)
+//! - Attribute 9: `preset_credit_amount` - Amount given by a reset
+//! - Attribute 10: `credit_available_threshold` - Amount from which a
+//!   selectable credit may be selected
+//! - Attribute 11: `period` - Period of time based credits
+//!
+//! ## Methods
+//! - Method 1: `update_amount(double-long)` - Adds a (signed) amount
+//! - Method 2: `set_amount_to_value(double-long)` - Sets the amount
+//! - Method 3: `invoke_credit(enum)` - Selects a selectable credit, e.g. the
+//!   customer accepting emergency credit
+//!
+//! ## Status
+//!
+//! A credit whose amount falls to its limit is exhausted; when it rises above
+//! the limit again, it is enabled, or selectable for emergency credit. A
+//! debit (negative update) marks the credit in use.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::credit::{Credit, CreditStatus, CreditType};
+//! use dlms_cosem::ObisCode;
+//!
+//! let mut emergency = Credit::new(ObisCode::new(0, 0, 19, 10, 1, 255), CreditType::Emergency, 2)
+//!     .with_preset_credit_amount(500);
+//! assert_eq!(emergency.credit_status, CreditStatus::Selectable);
+//!
+//! emergency.invoke_credit().unwrap();
+//! emergency.update_amount(-500);
+//! assert_eq!(emergency.current_credit_amount, 0);
+//! assert_eq!(emergency.credit_status, CreditStatus::Exhausted);
+//! ```
+
+use alloc::vec;
+
+use crate::action::ActionResult;
+use crate::cosem::CosemObject;
+use crate::cosem::activity_calendar::NOT_SCHEDULED;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::data::{Data, DateTime};
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Credit interface class id
+pub(crate) const CREDIT_CLASS_ID: u16 = 112;
+
+/// `credit_configuration` flag: the credit requires a visual indication.
+pub const REQUIRES_VISUAL_INDICATION: u8 = 0x80;
+/// `credit_configuration` flag: selecting the credit requires a confirmation.
+pub const REQUIRES_CONFIRMATION: u8 = 0x80 >> 1;
+/// `credit_configuration` flag: the credit must be paid back.
+pub const REQUIRES_PAYBACK: u8 = 0x80 >> 2;
+/// `credit_configuration` flag: the credit may be reset to its preset amount.
+pub const RESETTABLE: u8 = 0x80 >> 3;
+/// `credit_configuration` flag: the credit receives amounts from tokens.
+pub const RECEIVES_TOKENS: u8 = 0x80 >> 4;
+
+/// Kind of credit (attribute 3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CreditType {
+    /// Credit bought with tokens
+    Token = 0,
+    /// Credit set aside, e.g. for friendly hours
+    Reserved = 1,
+    /// Credit lent to the customer when out of credit
+    Emergency = 2,
+    /// Credit granted per period of time
+    TimeBased = 3,
+    /// Credit granted per consumption
+    ConsumptionBased = 4,
+}
+
+impl CreditType {
+    /// Converts a credit_type enum value.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::Token),
+            1 => Ok(Self::Reserved),
+            2 => Ok(Self::Emergency),
+            3 => Ok(Self::TimeBased),
+            4 => Ok(Self::ConsumptionBased),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Status of a credit (attribute 8).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CreditStatus {
+    /// Available for use
+    Enabled = 0,
+    /// Available once selected with `invoke_credit`
+    Selectable = 1,
+    /// Selected, available for use
+    SelectedInvoked = 2,
+    /// Being consumed
+    InUse = 3,
+    /// Used up
+    Exhausted = 4,
+}
+
+impl CreditStatus {
+    /// Converts a credit_status enum value.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::Enabled),
+            1 => Ok(Self::Selectable),
+            2 => Ok(Self::SelectedInvoked),
+            3 => Ok(Self::InUse),
+            4 => Ok(Self::Exhausted),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+
+    /// Returns whether a credit with this status may be consumed.
+    pub fn is_usable(self) -> bool {
+        matches!(self, Self::Enabled | Self::SelectedInvoked | Self::InUse)
+    }
+}
+
+/// Credit object - COSEM Interface Class 112
+///
+/// Reference: Blue Book 4.6.3
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Credit {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Remaining amount
+    pub current_credit_amount: i32,
+    /// Attribute 3: Kind of credit
+    pub credit_type: CreditType,
+    /// Attribute 4: Priority, 1 is consumed first
+    pub priority: u8,
+    /// Attribute 5: Amount below which to warn
+    pub warning_threshold: i32,
+    /// Attribute 6: Amount at or below which the credit is exhausted
+    pub limit: i32,
+    /// Attribute 7: Configuration flags
+    pub credit_configuration: u8,
+    /// Attribute 8: Status
+    pub credit_status: CreditStatus,
+    /// Attribute 9: Amount given by a reset
+    pub preset_credit_amount: i32,
+    /// Attribute 10: Amount from which a selectable credit may be selected
+    pub credit_available_threshold: i32,
+    /// Attribute 11: Period of time based credits
+    pub period: DateTime,
+}
+
+impl Credit {
+    /// Creates an exhausted credit of zero; credit above the limit enables
+    /// it, or makes it selectable for emergency credit.
+    pub fn new(logical_name: ObisCode, credit_type: CreditType, priority: u8) -> Self {
+        let mut credit = Self {
+            logical_name,
+            current_credit_amount: 0,
+            credit_type,
+            priority,
+            warning_threshold: 0,
+            limit: 0,
+            credit_configuration: 0,
+            credit_status: CreditStatus::Exhausted,
+            preset_credit_amount: 0,
+            credit_available_threshold: 0,
+            period: NOT_SCHEDULED,
+        };
+        credit.update_status(false);
+        credit
+    }
+
+    /// Sets the preset amount and the current amount to it (builder style).
+    pub fn with_preset_credit_amount(mut self, amount: i32) -> Self {
+        self.preset_credit_amount = amount;
+        self.set_amount_to_value(amount);
+        self
+    }
+
+    /// Sets the limit (builder style).
+    pub fn with_limit(mut self, limit: i32) -> Self {
+        self.limit = limit;
+        self.update_status(false);
+        self
+    }
+
+    /// Sets the warning threshold (builder style).
+    pub fn with_warning_threshold(mut self, threshold: i32) -> Self {
+        self.warning_threshold = threshold;
+        self
+    }
+
+    /// Sets the configuration flags (builder style).
+    pub fn with_credit_configuration(mut self, configuration: u8) -> Self {
+        self.credit_configuration = configuration;
+        self
+    }
+
+    /// Adds a signed amount, as the `update_amount` method does.
+    pub fn update_amount(&mut self, amount: i32) {
+        self.current_credit_amount = self.current_credit_amount.saturating_add(amount);
+        self.update_status(amount < 0);
+    }
+
+    /// Sets the amount, as the `set_amount_to_value` method does.
+    pub fn set_amount_to_value(&mut self, amount: i32) {
+        self.current_credit_amount = amount;
+        self.update_status(false);
+    }
+
+    /// Selects a selectable credit, as the `invoke_credit` method does.
+    pub fn invoke_credit(&mut self) -> Result<(), ActionResult> {
+        if self.credit_status != CreditStatus::Selectable
+            || self.current_credit_amount < self.credit_available_threshold
+        {
+            return Err(ActionResult::ReadWriteDenied);
+        }
+        self.credit_status = CreditStatus::SelectedInvoked;
+        Ok(())
+    }
+
+    /// Returns whether the credit may be consumed.
+    pub fn is_usable(&self) -> bool {
+        self.credit_status.is_usable() && self.current_credit_amount > self.limit
+    }
+
+    fn update_status(&mut self, debited: bool) {
+        self.credit_status = match self.credit_status {
+            _ if self.current_credit_amount <= self.limit => CreditStatus::Exhausted,
+            CreditStatus::Exhausted if self.credit_type == CreditType::Emergency => {
+                CreditStatus::Selectable
+            }
+            CreditStatus::Exhausted => CreditStatus::Enabled,
+            CreditStatus::Enabled | CreditStatus::SelectedInvoked if debited => CreditStatus::InUse,
+            status => status,
+        };
+    }
+}
+
+impl CosemObject for Credit {
+    fn class_id(&self) -> u16 {
+        CREDIT_CLASS_ID
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::DoubleLong(self.current_credit_amount)),
+            3 => Ok(Data::Enum(self.credit_type as u8)),
+            4 => Ok(Data::Unsigned(self.priority)),
+            5 => Ok(Data::DoubleLong(self.warning_threshold)),
+            6 => Ok(Data::DoubleLong(self.limit)),
+            7 => Ok(Data::BitString(vec![self.credit_configuration])),
+            8 => Ok(Data::Enum(self.credit_status as u8)),
+            9 => Ok(Data::DoubleLong(self.preset_credit_amount)),
+            10 => Ok(Data::DoubleLong(self.credit_available_threshold)),
+            11 => Ok(Data::DateTime(self.period.clone())),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (3, Data::Enum(credit_type)) => self.credit_type = CreditType::from_u8(credit_type)?,
+            (4, Data::Unsigned(priority)) if priority > 0 => self.priority = priority,
+            (4, Data::Unsigned(_)) => return Err(DataAccessResult::OtherReason),
+            (5, Data::DoubleLong(threshold)) => self.warning_threshold = threshold,
+            (6, Data::DoubleLong(limit)) => {
+                self.limit = limit;
+                self.update_status(false);
+            }
+            (7, Data::BitString(bits)) => {
+                self.credit_configuration = bits.first().copied().unwrap_or(0)
+            }
+            (9, Data::DoubleLong(amount)) => self.preset_credit_amount = amount,
+            (10, Data::DoubleLong(threshold)) => self.credit_available_threshold = threshold,
+            (11, Data::DateTime(period)) => self.period = period,
+            (3..=7 | 9..=11, _) => return Err(DataAccessResult::TypeUnmatched),
+            (1 | 2 | 8, _) => return Err(DataAccessResult::ReadWriteDenied),
+            _ => return Err(DataAccessResult::ObjectUndefined),
+        }
+        Ok(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match (method_id, params) {
+            (1, Some(Data::DoubleLong(amount))) => self.update_amount(amount),
+            (2, Some(Data::DoubleLong(amount))) => self.set_amount_to_value(amount),
+            (3, _) => self.invoke_credit()?,
+            (1 | 2, _) => return Err(ActionResult::TypeUnmatched),
+            _ => return Err(ActionResult::ObjectUndefined),
+        }
+        Ok(None)
+    }
+
+    /// Persists the configuration attributes, then the amount and status.
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [3, 4, 5, 6, 7, 9, 10, 11, 2, 8]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        match state.restore_attributes(self, [3, 4, 5, 6, 7, 9, 10, 11])?.as_slice() {
+            [Data::DoubleLong(amount), Data::Enum(status)] => {
+                self.current_credit_amount = *amount;
+                self.credit_status = CreditStatus::from_u8(*status)?;
+                Ok(())
+            }
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN_CREDIT: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 10, e: 0, f: 255 };
+
+    #[test]
+    fn test_amount_and_status() {
+        let mut credit = Credit::new(TOKEN_CREDIT, CreditType::Token, 1);
+        assert_eq!(credit.credit_status, CreditStatus::Exhausted);
+        assert!(!credit.is_usable());
+
+        assert_eq!(credit.invoke_method(1, Some(Data::DoubleLong(2_000))), Ok(None));
+        assert_eq!(credit.get_attribute(2), Ok(Data::DoubleLong(2_000)));
+        assert_eq!(credit.get_attribute(8), Ok(Data::Enum(CreditStatus::Enabled as u8)));
+
+        credit.update_amount(-500);
+        assert_eq!(credit.current_credit_amount, 1_500);
+        assert_eq!(credit.credit_status, CreditStatus::InUse);
+        assert!(credit.is_usable());
+
+        credit.update_amount(-1_600);
+        assert_eq!(credit.credit_status, CreditStatus::Exhausted);
+
+        assert_eq!(credit.invoke_method(2, Some(Data::DoubleLong(300))), Ok(None));
+        assert_eq!(credit.current_credit_amount, 300);
+        assert_eq!(credit.credit_status, CreditStatus::Enabled);
+        assert_eq!(credit.invoke_method(1, Some(Data::Long(1))), Err(ActionResult::TypeUnmatched));
+    }
+
+    #[test]
+    fn test_invoke_emergency_credit() {
+        let mut credit = Credit::new(TOKEN_CREDIT, CreditType::Emergency, 2)
+            .with_preset_credit_amount(500)
+            .with_limit(-100);
+        credit.credit_available_threshold = 600;
+        assert_eq!(credit.credit_status, CreditStatus::Selectable);
+        assert!(!credit.is_usable());
+        assert_eq!(credit.invoke_method(3, None), Err(ActionResult::ReadWriteDenied));
+
+        credit.credit_available_threshold = 0;
+        assert_eq!(credit.invoke_method(3, Some(Data::Enum(2))), Ok(None));
+        assert_eq!(credit.credit_status, CreditStatus::SelectedInvoked);
+        assert_eq!(credit.invoke_method(3, None), Err(ActionResult::ReadWriteDenied));
+
+        credit.update_amount(-600);
+        assert_eq!(credit.credit_status, CreditStatus::Exhausted);
+        credit.update_amount(700);
+        assert_eq!(credit.credit_status, CreditStatus::Selectable);
+    }
+
+    #[test]
+    fn test_attributes() {
+        let mut credit = Credit::new(TOKEN_CREDIT, CreditType::Token, 1);
+        assert_eq!(credit.set_attribute(3, Data::Enum(4)), Ok(()));
+        assert_eq!(credit.credit_type, CreditType::ConsumptionBased);
+        assert_eq!(credit.set_attribute(7, Data::BitString(vec![RECEIVES_TOKENS])), Ok(()));
+        assert_eq!(credit.get_attribute(7), Ok(Data::BitString(vec![0x08])));
+        assert_eq!(credit.set_attribute(6, Data::DoubleLong(-1_000)), Ok(()));
+        assert_eq!(credit.credit_status, CreditStatus::Enabled);
+
+        assert_eq!(credit.set_attribute(3, Data::Enum(5)), Err(DataAccessResult::TypeUnmatched));
+        assert_eq!(credit.set_attribute(4, Data::Unsigned(0)), Err(DataAccessResult::OtherReason));
+        assert_eq!(
+            credit.set_attribute(2, Data::DoubleLong(1)),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+        assert_eq!(credit.set_attribute(8, Data::Enum(3)), Err(DataAccessResult::ReadWriteDenied));
+        assert_eq!(credit.set_attribute(12, Data::Null), Err(DataAccessResult::ObjectUndefined));
+    }
+}
@@ -27,6 +27,8 @@
 use crate::cosem::CosemObject;
 use crate::data::Data;
 use crate::obis_code::ObisCode;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
 
 #[cfg(feature = "encode")]
 use crate::action::ActionResult;
@@ -109,6 +111,16 @@ impl CosemObject for DataObject {
         // Data class has no methods
         Err(ActionResult::ObjectUndefined)
     }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [2]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), crate::get::DataAccessResult> {
+        state.restore_attributes(self, [2]).map(drop)
+    }
 }
 
 #[cfg(test)]
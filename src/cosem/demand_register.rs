@@ -55,6 +55,8 @@ use crate::data::Data;
 use crate::get::DataAccessResult;
 use crate::obis_code::ObisCode;
 use crate::unit::ScalerUnit;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
 
 #[cfg(feature = "serde")]
 use serde::Serialize;
@@ -425,6 +427,16 @@ impl CosemObject for DemandRegister {
             _ => Err(ActionResult::ObjectUndefined),
         }
     }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, 2..=9).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, 2..=9).map(drop)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,368 @@
+//! COSEM Interface Class 70: Disconnect Control
+//!
+//! The Disconnect Control object manages the supply disconnector (relay) of
+//! a meter. Its control mode selects which remote, manual and local
+//! transitions between the control states are allowed.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (0.0.96.3.10.255)
+//! - Attribute 2: `output_state` - Boolean, whether the supply is connected
+//! - Attribute 3: `control_state` - Enum, see [`ControlState`]
+//! - Attribute 4: `control_mode` - Enum, 0-6
+//!
+//! ## Methods
+//! - Method 1: `remote_disconnect(data)`
+//! - Method 2: `remote_reconnect(data)`
+//!
+//! ## State transitions
+//!
+//! | Transition | Name                | From                   | To                     |
+//! |------------|---------------------|------------------------|------------------------|
+//! | a          | `remote_reconnect`  | Disconnected           | Ready_for_reconnection |
+//! | b          | `remote_disconnect` | Connected              | Disconnected           |
+//! | c          | `remote_disconnect` | Ready_for_reconnection | Disconnected           |
+//! | d          | `remote_reconnect`  | Disconnected / Ready   | Connected              |
+//! | e          | `manual_reconnect`  | Ready_for_reconnection | Connected              |
+//! | f          | `manual_disconnect` | Connected              | Ready_for_reconnection |
+//! | g          | `local_disconnect`  | Connected              | Ready_for_reconnection |
+//! | h          | `local_reconnect`   | Ready_for_reconnection | Connected              |
+//!
+//! | Mode | Disconnection    | Reconnection     |
+//! |------|------------------|------------------|
+//! | 0    | -                | -                |
+//! | 1    | b, c, f, g       | d, e             |
+//! | 2    | b, c, f, g       | a, e             |
+//! | 3    | b, c, g          | d, e             |
+//! | 4    | b, c, g          | a, e             |
+//! | 5    | b, c, f, g       | d, e, h          |
+//! | 6    | b, c, g          | d, e, h          |
+//!
+//! In mode 0 the supply is always connected and the methods are denied.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::disconnect_control::{ControlState, DisconnectControl};
+//! use dlms_cosem::cosem::CosemObject;
+//! use dlms_cosem::Data;
+//!
+//! let mut disconnect_control = DisconnectControl::new(2);
+//! disconnect_control.invoke_method(1, Some(Data::Integer(0))).unwrap();
+//! assert_eq!(disconnect_control.get_attribute(2), Ok(Data::Boolean(false)));
+//!
+//! // Mode 2: the remote reconnection only arms the relay, the customer
+//! // reconnects with the button.
+//! disconnect_control.invoke_method(2, Some(Data::Integer(0))).unwrap();
+//! assert_eq!(disconnect_control.control_state, ControlState::ReadyForReconnection);
+//! assert!(disconnect_control.manual_reconnect());
+//! assert!(disconnect_control.output_state());
+//! ```
+
+use crate::action::ActionResult;
+use crate::cosem::CosemObject;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Logical name of the Disconnect Control object.
+pub const DISCONNECT_CONTROL: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 3, e: 10, f: 255 };
+
+/// Largest control mode.
+const MAX_CONTROL_MODE: u8 = 6;
+
+/// Control state (attribute 3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ControlState {
+    /// Supply disconnected
+    Disconnected = 0,
+    /// Supply connected
+    Connected = 1,
+    /// Supply disconnected, may be reconnected manually
+    ReadyForReconnection = 2,
+}
+
+impl ControlState {
+    /// Convert u8 to ControlState
+    ///
+    /// Returns Err if value is not in range 0-2.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::Disconnected),
+            1 => Ok(Self::Connected),
+            2 => Ok(Self::ReadyForReconnection),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Disconnect Control object - COSEM Interface Class 70
+///
+/// Reference: Blue Book 4.5.8
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DisconnectControl {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 3: Control state
+    pub control_state: ControlState,
+    /// Attribute 4: Control mode (0-6)
+    control_mode: u8,
+}
+
+impl DisconnectControl {
+    /// Creates a connected Disconnect Control object (0.0.96.3.10.255).
+    ///
+    /// Control modes above 6 are capped to 6.
+    pub fn new(control_mode: u8) -> Self {
+        Self {
+            logical_name: DISCONNECT_CONTROL,
+            control_state: ControlState::Connected,
+            control_mode: control_mode.min(MAX_CONTROL_MODE),
+        }
+    }
+
+    /// Attribute 2: whether the supply is connected.
+    pub fn output_state(&self) -> bool {
+        self.control_state == ControlState::Connected
+    }
+
+    /// Attribute 4: the control mode.
+    pub fn control_mode(&self) -> u8 {
+        self.control_mode
+    }
+
+    /// Sets the control mode; mode 0 connects the supply.
+    pub fn set_control_mode(&mut self, control_mode: u8) -> Result<(), DataAccessResult> {
+        if control_mode > MAX_CONTROL_MODE {
+            return Err(DataAccessResult::OtherReason);
+        }
+        self.control_mode = control_mode;
+        if control_mode == 0 {
+            self.control_state = ControlState::Connected;
+        }
+        Ok(())
+    }
+
+    /// Remote disconnection (transitions b and c).
+    pub fn remote_disconnect(&mut self) -> Result<(), ActionResult> {
+        if self.control_mode == 0 {
+            return Err(ActionResult::ReadWriteDenied);
+        }
+        self.control_state = ControlState::Disconnected;
+        Ok(())
+    }
+
+    /// Remote reconnection: connects the supply in modes 1, 3, 5 and 6
+    /// (transition d), only arms it for a manual reconnection in modes 2 and
+    /// 4 (transition a).
+    pub fn remote_reconnect(&mut self) -> Result<(), ActionResult> {
+        match (self.control_mode, self.control_state) {
+            (0, _) => Err(ActionResult::ReadWriteDenied),
+            (_, ControlState::Connected) => Ok(()),
+            (2 | 4, _) => {
+                self.control_state = ControlState::ReadyForReconnection;
+                Ok(())
+            }
+            _ => {
+                self.control_state = ControlState::Connected;
+                Ok(())
+            }
+        }
+    }
+
+    /// Manual disconnection with the button (transition f), allowed in modes
+    /// 1, 2 and 5. Returns whether the state changed.
+    pub fn manual_disconnect(&mut self) -> bool {
+        matches!(self.control_mode, 1 | 2 | 5)
+            && self.transition(ControlState::Connected, ControlState::ReadyForReconnection)
+    }
+
+    /// Manual reconnection with the button (transition e). Returns whether
+    /// the state changed.
+    pub fn manual_reconnect(&mut self) -> bool {
+        self.control_mode != 0
+            && self.transition(ControlState::ReadyForReconnection, ControlState::Connected)
+    }
+
+    /// Local disconnection, e.g. by a limiter (transition g). Returns whether
+    /// the state changed.
+    pub fn local_disconnect(&mut self) -> bool {
+        self.control_mode != 0
+            && self.transition(ControlState::Connected, ControlState::ReadyForReconnection)
+    }
+
+    /// Local reconnection, e.g. when a limiter threshold is no longer
+    /// exceeded (transition h), allowed in modes 5 and 6. Returns whether
+    /// the state changed.
+    pub fn local_reconnect(&mut self) -> bool {
+        matches!(self.control_mode, 5 | 6)
+            && self.transition(ControlState::ReadyForReconnection, ControlState::Connected)
+    }
+
+    fn transition(&mut self, from: ControlState, to: ControlState) -> bool {
+        let allowed = self.control_state == from;
+        if allowed {
+            self.control_state = to;
+        }
+        allowed
+    }
+}
+
+impl Default for DisconnectControl {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl CosemObject for DisconnectControl {
+    fn class_id(&self) -> u16 {
+        70
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::Boolean(self.output_state())),
+            3 => Ok(Data::Enum(self.control_state as u8)),
+            4 => Ok(Data::Enum(self.control_mode)),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match id {
+            4 => match value {
+                Data::Enum(control_mode) => self.set_control_mode(control_mode),
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            1..=3 => Err(DataAccessResult::ReadWriteDenied),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        _parameters: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match method_id {
+            1 => self.remote_disconnect()?,
+            2 => self.remote_reconnect()?,
+            _ => return Err(ActionResult::ObjectUndefined),
+        }
+        Ok(None)
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [4, 3]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        match state.restore_attributes(self, [4])?.as_slice() {
+            [Data::Enum(control_state)] => {
+                self.control_state = ControlState::from_u8(*control_state)?;
+                Ok(())
+            }
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_transitions_per_mode() {
+        for mode in 1..=6 {
+            let mut disconnect_control = DisconnectControl::new(mode);
+            disconnect_control.invoke_method(1, Some(Data::Integer(0))).unwrap();
+            assert_eq!(disconnect_control.get_attribute(3), Ok(Data::Enum(0)));
+            assert_eq!(disconnect_control.get_attribute(2), Ok(Data::Boolean(false)));
+
+            disconnect_control.invoke_method(2, Some(Data::Integer(0))).unwrap();
+            let expected = if matches!(mode, 2 | 4) {
+                ControlState::ReadyForReconnection
+            } else {
+                ControlState::Connected
+            };
+            assert_eq!(disconnect_control.control_state, expected, "mode {}", mode);
+
+            // Disconnecting from ready for reconnection (transition c).
+            disconnect_control.control_state = ControlState::ReadyForReconnection;
+            disconnect_control.invoke_method(1, None).unwrap();
+            assert_eq!(disconnect_control.control_state, ControlState::Disconnected);
+        }
+
+        let mut disconnect_control = DisconnectControl::new(0);
+        assert_eq!(disconnect_control.invoke_method(1, None), Err(ActionResult::ReadWriteDenied));
+        assert_eq!(disconnect_control.invoke_method(2, None), Err(ActionResult::ReadWriteDenied));
+        assert!(disconnect_control.output_state());
+        assert_eq!(disconnect_control.invoke_method(3, None), Err(ActionResult::ObjectUndefined));
+    }
+
+    #[test]
+    fn test_manual_and_local_transitions() {
+        // (mode, manual_disconnect, local_reconnect)
+        for (mode, manual_disconnect, local_reconnect) in [
+            (1, true, false),
+            (2, true, false),
+            (3, false, false),
+            (4, false, false),
+            (5, true, true),
+            (6, false, true),
+        ] {
+            let mut disconnect_control = DisconnectControl::new(mode);
+            assert_eq!(disconnect_control.manual_disconnect(), manual_disconnect, "mode {}", mode);
+
+            disconnect_control.control_state = ControlState::Connected;
+            assert!(disconnect_control.local_disconnect());
+            assert_eq!(disconnect_control.local_reconnect(), local_reconnect, "mode {}", mode);
+
+            disconnect_control.control_state = ControlState::ReadyForReconnection;
+            assert!(disconnect_control.manual_reconnect());
+            assert!(disconnect_control.output_state());
+        }
+
+        // No manual reconnection after a remote disconnection.
+        let mut disconnect_control = DisconnectControl::new(1);
+        disconnect_control.remote_disconnect().unwrap();
+        assert!(!disconnect_control.manual_reconnect());
+        assert!(!disconnect_control.local_disconnect());
+    }
+
+    #[test]
+    fn test_control_mode_attribute() {
+        let mut disconnect_control = DisconnectControl::new(3);
+        disconnect_control.remote_disconnect().unwrap();
+        assert_eq!(disconnect_control.get_attribute(4), Ok(Data::Enum(3)));
+        assert_eq!(
+            disconnect_control.set_attribute(4, Data::Enum(7)),
+            Err(DataAccessResult::OtherReason)
+        );
+        assert_eq!(
+            disconnect_control.set_attribute(4, Data::Unsigned(1)),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        assert_eq!(
+            disconnect_control.set_attribute(3, Data::Enum(1)),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+
+        // Mode 0 connects the supply.
+        disconnect_control.set_attribute(4, Data::Enum(0)).unwrap();
+        assert_eq!(disconnect_control.get_attribute(2), Ok(Data::Boolean(true)));
+    }
+}
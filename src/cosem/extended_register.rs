@@ -31,6 +31,8 @@ use crate::data::{Data, Date, DateTime, Time};
 use crate::get::DataAccessResult;
 use crate::obis_code::ObisCode;
 use crate::unit::ScalerUnit;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
 
 #[cfg(feature = "encode")]
 use crate::action::ActionResult;
@@ -236,6 +238,16 @@ impl CosemObject for ExtendedRegister {
     ) -> Result<Option<Data>, ActionResult> {
         Err(ActionResult::ObjectUndefined)
     }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [2, 3, 4, 5]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, [2, 3, 4, 5]).map(drop)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,345 @@
+//! COSEM Interface Class 23: IEC HDLC Setup
+//!
+//! Holds the parameters of the HDLC based data link layer of a port: baud
+//! rate, window sizes, maximum information field lengths, timeouts and the
+//! physical device address.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.22.0.0.255)
+//! - Attribute 2: `comm_speed` - Baud rate enum (0 = 300 ... 9 = 115200)
+//! - Attribute 3: `window_size_transmit` - 1 to 7
+//! - Attribute 4: `window_size_receive` - 1 to 7
+//! - Attribute 5: `max_info_field_length_transmit` - 32 to 2030 bytes
+//! - Attribute 6: `max_info_field_length_receive` - 32 to 2030 bytes
+//! - Attribute 7: `inter_octet_time_out` - 20 to 6000 ms
+//! - Attribute 8: `inactivity_time_out` - Seconds, 0 to disable
+//! - Attribute 9: `device_address` - 0x0010 to 0x3FFD
+//!
+//! ## Methods
+//! None.
+//!
+//! Values out of range are rejected with `OtherReason`.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::CosemObject;
+//! use dlms_cosem::cosem::iec_hdlc_setup::{CommSpeed, IecHdlcSetup};
+//! use dlms_cosem::get::DataAccessResult;
+//! use dlms_cosem::{Data, ObisCode};
+//!
+//! let mut hdlc_setup = IecHdlcSetup::new(ObisCode::new(0, 0, 22, 0, 0, 255))
+//!     .with_comm_speed(CommSpeed::Baud19200)
+//!     .with_device_address(0x0123)
+//!     .unwrap();
+//!
+//! assert_eq!(hdlc_setup.comm_speed.baud_rate(), 19_200);
+//! assert_eq!(hdlc_setup.set_attribute(3, Data::Unsigned(7)), Ok(()));
+//! assert_eq!(hdlc_setup.set_attribute(3, Data::Unsigned(8)), Err(DataAccessResult::OtherReason));
+//! ```
+
+use core::ops::RangeInclusive;
+
+use crate::action::ActionResult;
+use crate::cosem::CosemObject;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Valid window sizes (attributes 3 and 4).
+const WINDOW_SIZES: RangeInclusive<u8> = 1..=7;
+
+/// Valid maximum information field lengths (attributes 5 and 6).
+const MAX_INFO_FIELD_LENGTHS: RangeInclusive<u16> = 32..=2030;
+
+/// Valid inter octet timeouts in milliseconds (attribute 7).
+const INTER_OCTET_TIME_OUTS: RangeInclusive<u16> = 20..=6000;
+
+/// Usable physical device addresses (attribute 9).
+const DEVICE_ADDRESSES: RangeInclusive<u16> = 0x0010..=0x3FFD;
+
+/// Baud rate of the port (attribute 2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CommSpeed {
+    Baud300 = 0,
+    Baud600 = 1,
+    Baud1200 = 2,
+    Baud2400 = 3,
+    Baud4800 = 4,
+    Baud9600 = 5,
+    Baud19200 = 6,
+    Baud38400 = 7,
+    Baud57600 = 8,
+    Baud115200 = 9,
+}
+
+impl CommSpeed {
+    /// Converts a comm_speed enum value.
+    ///
+    /// Returns Err if value is not in range 0-9.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::Baud300),
+            1 => Ok(Self::Baud600),
+            2 => Ok(Self::Baud1200),
+            3 => Ok(Self::Baud2400),
+            4 => Ok(Self::Baud4800),
+            5 => Ok(Self::Baud9600),
+            6 => Ok(Self::Baud19200),
+            7 => Ok(Self::Baud38400),
+            8 => Ok(Self::Baud57600),
+            9 => Ok(Self::Baud115200),
+            _ => Err(DataAccessResult::OtherReason),
+        }
+    }
+
+    /// Returns the baud rate in bits per second.
+    pub fn baud_rate(self) -> u32 {
+        match self {
+            Self::Baud300 => 300,
+            Self::Baud600 => 600,
+            Self::Baud1200 => 1_200,
+            Self::Baud2400 => 2_400,
+            Self::Baud4800 => 4_800,
+            Self::Baud9600 => 9_600,
+            Self::Baud19200 => 19_200,
+            Self::Baud38400 => 38_400,
+            Self::Baud57600 => 57_600,
+            Self::Baud115200 => 115_200,
+        }
+    }
+}
+
+/// IEC HDLC Setup object - COSEM Interface Class 23
+///
+/// Reference: Blue Book 4.7.3
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct IecHdlcSetup {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Baud rate
+    pub comm_speed: CommSpeed,
+    /// Attribute 3: Frames sent before an acknowledgement
+    pub window_size_transmit: u8,
+    /// Attribute 4: Frames received before an acknowledgement
+    pub window_size_receive: u8,
+    /// Attribute 5: Maximum information field length sent
+    pub max_info_field_length_transmit: u16,
+    /// Attribute 6: Maximum information field length received
+    pub max_info_field_length_receive: u16,
+    /// Attribute 7: Milliseconds of silence ending a frame
+    pub inter_octet_time_out: u16,
+    /// Attribute 8: Seconds without frames before disconnecting, 0 to disable
+    pub inactivity_time_out: u16,
+    /// Attribute 9: Physical device address
+    pub device_address: u16,
+}
+
+impl IecHdlcSetup {
+    /// Creates an HDLC setup with the default values of the Blue Book:
+    /// 9600 baud, windows of 1, 128 byte information fields, 25 ms inter
+    /// octet and 120 s inactivity timeouts, device address 0x0010.
+    pub fn new(logical_name: ObisCode) -> Self {
+        Self {
+            logical_name,
+            comm_speed: CommSpeed::Baud9600,
+            window_size_transmit: 1,
+            window_size_receive: 1,
+            max_info_field_length_transmit: 128,
+            max_info_field_length_receive: 128,
+            inter_octet_time_out: 25,
+            inactivity_time_out: 120,
+            device_address: *DEVICE_ADDRESSES.start(),
+        }
+    }
+
+    /// Sets the baud rate (builder style).
+    pub fn with_comm_speed(mut self, comm_speed: CommSpeed) -> Self {
+        self.comm_speed = comm_speed;
+        self
+    }
+
+    /// Sets the transmit and receive window sizes (builder style).
+    pub fn with_window_sizes(
+        mut self,
+        transmit: u8,
+        receive: u8,
+    ) -> Result<Self, DataAccessResult> {
+        self.window_size_transmit = in_range(transmit, WINDOW_SIZES)?;
+        self.window_size_receive = in_range(receive, WINDOW_SIZES)?;
+        Ok(self)
+    }
+
+    /// Sets the transmit and receive maximum information field lengths
+    /// (builder style).
+    pub fn with_max_info_field_lengths(
+        mut self,
+        transmit: u16,
+        receive: u16,
+    ) -> Result<Self, DataAccessResult> {
+        self.max_info_field_length_transmit = in_range(transmit, MAX_INFO_FIELD_LENGTHS)?;
+        self.max_info_field_length_receive = in_range(receive, MAX_INFO_FIELD_LENGTHS)?;
+        Ok(self)
+    }
+
+    /// Sets the inactivity timeout in seconds, 0 to disable (builder style).
+    pub fn with_inactivity_time_out(mut self, seconds: u16) -> Self {
+        self.inactivity_time_out = seconds;
+        self
+    }
+
+    /// Sets the physical device address (builder style).
+    pub fn with_device_address(mut self, device_address: u16) -> Result<Self, DataAccessResult> {
+        self.device_address = in_range(device_address, DEVICE_ADDRESSES)?;
+        Ok(self)
+    }
+}
+
+/// Returns `value` if within `range`.
+fn in_range<T: PartialOrd>(value: T, range: RangeInclusive<T>) -> Result<T, DataAccessResult> {
+    if range.contains(&value) { Ok(value) } else { Err(DataAccessResult::OtherReason) }
+}
+
+impl CosemObject for IecHdlcSetup {
+    fn class_id(&self) -> u16 {
+        23
+    }
+
+    fn version(&self) -> u8 {
+        1
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::Enum(self.comm_speed as u8)),
+            3 => Ok(Data::Unsigned(self.window_size_transmit)),
+            4 => Ok(Data::Unsigned(self.window_size_receive)),
+            5 => Ok(Data::LongUnsigned(self.max_info_field_length_transmit)),
+            6 => Ok(Data::LongUnsigned(self.max_info_field_length_receive)),
+            7 => Ok(Data::LongUnsigned(self.inter_octet_time_out)),
+            8 => Ok(Data::LongUnsigned(self.inactivity_time_out)),
+            9 => Ok(Data::LongUnsigned(self.device_address)),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (2, Data::Enum(speed)) => self.comm_speed = CommSpeed::from_u8(speed)?,
+            (3, Data::Unsigned(size)) => self.window_size_transmit = in_range(size, WINDOW_SIZES)?,
+            (4, Data::Unsigned(size)) => self.window_size_receive = in_range(size, WINDOW_SIZES)?,
+            (5, Data::LongUnsigned(length)) => {
+                self.max_info_field_length_transmit = in_range(length, MAX_INFO_FIELD_LENGTHS)?
+            }
+            (6, Data::LongUnsigned(length)) => {
+                self.max_info_field_length_receive = in_range(length, MAX_INFO_FIELD_LENGTHS)?
+            }
+            (7, Data::LongUnsigned(millis)) => {
+                self.inter_octet_time_out = in_range(millis, INTER_OCTET_TIME_OUTS)?
+            }
+            (8, Data::LongUnsigned(seconds)) => self.inactivity_time_out = seconds,
+            (9, Data::LongUnsigned(address)) => {
+                self.device_address = in_range(address, DEVICE_ADDRESSES)?
+            }
+            (2..=9, _) => return Err(DataAccessResult::TypeUnmatched),
+            (1, _) => return Err(DataAccessResult::ReadWriteDenied),
+            _ => return Err(DataAccessResult::ObjectUndefined),
+        }
+        Ok(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        _method_id: i8,
+        _params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        Err(ActionResult::ObjectUndefined)
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, 2..=9).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, 2..=9).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HDLC_SETUP: ObisCode = ObisCode { a: 0, b: 0, c: 22, d: 0, e: 0, f: 255 };
+
+    #[test]
+    fn test_comm_speed() {
+        let rates: Vec<_> =
+            (0..=9).map(|value| CommSpeed::from_u8(value).unwrap().baud_rate()).collect();
+        assert_eq!(rates, [300, 600, 1_200, 2_400, 4_800, 9_600, 19_200, 38_400, 57_600, 115_200]);
+        assert_eq!(CommSpeed::from_u8(10), Err(DataAccessResult::OtherReason));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let mut hdlc_setup = IecHdlcSetup::new(HDLC_SETUP);
+        assert_eq!(hdlc_setup.get_attribute(2), Ok(Data::Enum(5)));
+        assert_eq!(hdlc_setup.get_attribute(5), Ok(Data::LongUnsigned(128)));
+        assert_eq!(hdlc_setup.get_attribute(9), Ok(Data::LongUnsigned(0x10)));
+
+        assert_eq!(hdlc_setup.set_attribute(2, Data::Enum(9)), Ok(()));
+        assert_eq!(hdlc_setup.set_attribute(4, Data::Unsigned(7)), Ok(()));
+        assert_eq!(hdlc_setup.set_attribute(6, Data::LongUnsigned(2030)), Ok(()));
+        assert_eq!(hdlc_setup.set_attribute(7, Data::LongUnsigned(200)), Ok(()));
+        assert_eq!(hdlc_setup.set_attribute(8, Data::LongUnsigned(0)), Ok(()));
+        assert_eq!(hdlc_setup.set_attribute(9, Data::LongUnsigned(0x3FFD)), Ok(()));
+        assert_eq!(hdlc_setup.comm_speed, CommSpeed::Baud115200);
+        assert_eq!(hdlc_setup.window_size_receive, 7);
+        assert_eq!(hdlc_setup.max_info_field_length_receive, 2030);
+        assert_eq!(hdlc_setup.inter_octet_time_out, 200);
+        assert_eq!(hdlc_setup.inactivity_time_out, 0);
+        assert_eq!(hdlc_setup.device_address, 0x3FFD);
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut hdlc_setup = IecHdlcSetup::new(HDLC_SETUP);
+        let out_of_range = [
+            (2, Data::Enum(10)),
+            (3, Data::Unsigned(0)),
+            (4, Data::Unsigned(8)),
+            (5, Data::LongUnsigned(31)),
+            (6, Data::LongUnsigned(2031)),
+            (7, Data::LongUnsigned(19)),
+            (9, Data::LongUnsigned(0x0F)),
+            (9, Data::LongUnsigned(0x3FFE)),
+        ];
+        for (id, value) in out_of_range {
+            assert_eq!(hdlc_setup.set_attribute(id, value), Err(DataAccessResult::OtherReason));
+        }
+        assert_eq!(hdlc_setup, IecHdlcSetup::new(HDLC_SETUP));
+
+        assert_eq!(
+            hdlc_setup.set_attribute(3, Data::LongUnsigned(1)),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        assert_eq!(hdlc_setup.set_attribute(1, Data::Null), Err(DataAccessResult::ReadWriteDenied));
+        assert_eq!(
+            IecHdlcSetup::new(HDLC_SETUP).with_window_sizes(7, 0),
+            Err(DataAccessResult::OtherReason)
+        );
+        assert_eq!(
+            IecHdlcSetup::new(HDLC_SETUP).with_max_info_field_lengths(256, 4096),
+            Err(DataAccessResult::OtherReason)
+        );
+    }
+}
@@ -0,0 +1,504 @@
+//! COSEM Interface Class 18: Image Transfer
+//!
+//! The Image Transfer object receives a binary image (firmware) in blocks,
+//! verifies it and activates it.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (0.0.44.0.0.255)
+//! - Attribute 2: `image_block_size` - Size of the blocks the client sends
+//! - Attribute 3: `image_transferred_blocks_status` - Bit string, one bit
+//!   per block, most significant bit first
+//! - Attribute 4: `image_first_not_transferred_block_number`
+//! - Attribute 5: `image_transfer_enabled` - Boolean
+//! - Attribute 6: `image_transfer_status` - Enum, see [`ImageTransferStatus`]
+//! - Attribute 7: `image_to_activate_info` - Array of `(size, identification, signature)`
+//!
+//! ## Methods
+//! - Method 1: `image_transfer_initiate(identifier, size)` - Starts a transfer;
+//!   initiating the transfer in progress again resumes it
+//! - Method 2: `image_block_transfer(number, value)` - Transfers one block
+//! - Method 3: `image_verify` - Verifies the complete image
+//! - Method 4: `image_activate` - Activates the verified image
+//!
+//! The image is kept in memory until it is activated, so images larger than
+//! [`DEFAULT_MAX_IMAGE_SIZE`] (or the size set with
+//! [`ImageTransfer::with_max_image_size`]) are rejected when initiated.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::image_transfer::{ImageTransfer, ImageTransferStatus};
+//! use dlms_cosem::cosem::CosemObject;
+//! use dlms_cosem::Data;
+//!
+//! let mut image_transfer = ImageTransfer::new(4);
+//! let initiate = Data::Structure(vec![
+//!     Data::OctetString(b"FW-2.0".to_vec()),
+//!     Data::DoubleLongUnsigned(6),
+//! ]);
+//! image_transfer.invoke_method(1, Some(initiate)).unwrap();
+//! for (number, block) in [&b"FIRM"[..], &b"WA"[..]].into_iter().enumerate() {
+//!     let block = Data::Structure(vec![
+//!         Data::DoubleLongUnsigned(number as u32),
+//!         Data::OctetString(block.to_vec()),
+//!     ]);
+//!     image_transfer.invoke_method(2, Some(block)).unwrap();
+//! }
+//! image_transfer.invoke_method(3, Some(Data::Integer(0))).unwrap();
+//! image_transfer.invoke_method(4, Some(Data::Integer(0))).unwrap();
+//!
+//! assert_eq!(image_transfer.image_transfer_status, ImageTransferStatus::ActivationSuccessful);
+//! assert_eq!(image_transfer.image(), b"FIRMWA");
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::action::ActionResult;
+use crate::cosem::CosemObject;
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Logical name of the Image Transfer object.
+pub const IMAGE_TRANSFER: ObisCode = ObisCode { a: 0, b: 0, c: 44, d: 0, e: 0, f: 255 };
+
+/// Largest image accepted by default, in bytes (16 MiB).
+pub const DEFAULT_MAX_IMAGE_SIZE: u32 = 16 * 1024 * 1024;
+
+/// Image transfer status (attribute 6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ImageTransferStatus {
+    /// No transfer initiated
+    NotInitiated = 0,
+    /// Transfer initiated, blocks are being transferred
+    Initiated = 1,
+    /// Verification in progress
+    VerificationInitiated = 2,
+    /// Image verified
+    VerificationSuccessful = 3,
+    /// Image incomplete or rejected by the verification
+    VerificationFailed = 4,
+    /// Activation in progress
+    ActivationInitiated = 5,
+    /// Image activated
+    ActivationSuccessful = 6,
+    /// Activation failed
+    ActivationFailed = 7,
+}
+
+impl ImageTransferStatus {
+    /// Convert u8 to ImageTransferStatus
+    ///
+    /// Returns Err if value is not in range 0-7.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::NotInitiated),
+            1 => Ok(Self::Initiated),
+            2 => Ok(Self::VerificationInitiated),
+            3 => Ok(Self::VerificationSuccessful),
+            4 => Ok(Self::VerificationFailed),
+            5 => Ok(Self::ActivationInitiated),
+            6 => Ok(Self::ActivationSuccessful),
+            7 => Ok(Self::ActivationFailed),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// One element of the image to activate info (attribute 7).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageToActivateInfo {
+    /// Size of the image in bytes
+    pub size: u32,
+    /// Identifier of the image
+    pub identification: Vec<u8>,
+    /// Signature of the image, empty if the image is not signed
+    pub signature: Vec<u8>,
+}
+
+impl ImageToActivateInfo {
+    fn to_data(&self) -> Data {
+        Data::Structure(vec![
+            Data::DoubleLongUnsigned(self.size),
+            Data::OctetString(self.identification.clone()),
+            Data::OctetString(self.signature.clone()),
+        ])
+    }
+}
+
+/// Checks an image before activation: `(identifier, image) -> accepted`.
+pub type ImageVerifier = fn(&[u8], &[u8]) -> bool;
+
+/// Image Transfer object - COSEM Interface Class 18
+///
+/// Reference: Blue Book 4.4.6
+#[derive(Debug, Clone)]
+pub struct ImageTransfer {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Block size in bytes
+    pub image_block_size: u32,
+    /// Attribute 3: One bit per block, set once the block is transferred
+    pub image_transferred_blocks_status: Vec<u8>,
+    /// Attribute 4: Number of the first block not transferred
+    pub image_first_not_transferred_block_number: u32,
+    /// Attribute 5: Whether transfers may be initiated
+    pub image_transfer_enabled: bool,
+    /// Attribute 6: Status of the transfer
+    pub image_transfer_status: ImageTransferStatus,
+    /// Attribute 7: Images ready to be activated
+    pub image_to_activate_info: Vec<ImageToActivateInfo>,
+    image_identifier: Vec<u8>,
+    image_size: u32,
+    image: Vec<u8>,
+    max_image_size: u32,
+    verifier: Option<ImageVerifier>,
+}
+
+impl ImageTransfer {
+    /// Creates an enabled Image Transfer object (0.0.44.0.0.255).
+    ///
+    /// Images up to [`DEFAULT_MAX_IMAGE_SIZE`] bytes are accepted.
+    pub fn new(image_block_size: u32) -> Self {
+        Self {
+            logical_name: IMAGE_TRANSFER,
+            image_block_size: image_block_size.max(1),
+            image_transferred_blocks_status: Vec::new(),
+            image_first_not_transferred_block_number: 0,
+            image_transfer_enabled: true,
+            image_transfer_status: ImageTransferStatus::NotInitiated,
+            image_to_activate_info: Vec::new(),
+            image_identifier: Vec::new(),
+            image_size: 0,
+            image: Vec::new(),
+            max_image_size: DEFAULT_MAX_IMAGE_SIZE,
+            verifier: None,
+        }
+    }
+
+    /// Sets the largest image accepted by `image_transfer_initiate` (builder style).
+    ///
+    /// The image is buffered in memory, so this bounds the memory a client can
+    /// make the object allocate.
+    pub fn with_max_image_size(mut self, max_image_size: u32) -> Self {
+        self.max_image_size = max_image_size;
+        self
+    }
+
+    /// Returns the largest image accepted, in bytes.
+    pub fn max_image_size(&self) -> u32 {
+        self.max_image_size
+    }
+
+    /// Sets the function verifying complete images (builder style).
+    ///
+    /// Without a verifier, every complete image is accepted.
+    pub fn with_verifier(mut self, verifier: ImageVerifier) -> Self {
+        self.verifier = Some(verifier);
+        self
+    }
+
+    /// Returns the identifier of the image being transferred.
+    pub fn image_identifier(&self) -> &[u8] {
+        &self.image_identifier
+    }
+
+    /// Returns the image received so far; blocks not transferred are zeroed.
+    pub fn image(&self) -> &[u8] {
+        &self.image
+    }
+
+    /// Returns the number of blocks of the image being transferred.
+    pub fn block_count(&self) -> u32 {
+        self.image_size.div_ceil(self.image_block_size)
+    }
+
+    /// Returns whether a block has been transferred.
+    pub fn is_block_transferred(&self, number: u32) -> bool {
+        self.image_transferred_blocks_status
+            .get(number as usize / 8)
+            .is_some_and(|byte| byte & (0x80 >> (number % 8)) != 0)
+    }
+
+    fn initiate(&mut self, identifier: Vec<u8>, size: u32) -> Result<(), ActionResult> {
+        if !self.image_transfer_enabled {
+            return Err(ActionResult::ReadWriteDenied);
+        }
+        if size == 0 || size > self.max_image_size {
+            return Err(ActionResult::OtherReason);
+        }
+        let resumed = self.image_transfer_status == ImageTransferStatus::Initiated
+            && self.image_identifier == identifier
+            && self.image_size == size;
+        if !resumed {
+            self.image_identifier = identifier;
+            self.image_size = size;
+            self.image = vec![0; size as usize];
+            self.image_transferred_blocks_status = vec![0; self.block_count().div_ceil(8) as usize];
+            self.image_first_not_transferred_block_number = 0;
+            self.image_to_activate_info.clear();
+            self.image_transfer_status = ImageTransferStatus::Initiated;
+        }
+        Ok(())
+    }
+
+    fn block_transfer(&mut self, number: u32, block: &[u8]) -> Result<(), ActionResult> {
+        if self.image_transfer_status != ImageTransferStatus::Initiated {
+            return Err(ActionResult::ReadWriteDenied);
+        }
+        if number >= self.block_count() {
+            return Err(ActionResult::OtherReason);
+        }
+        let start = (number * self.image_block_size) as usize;
+        let end = (start + self.image_block_size as usize).min(self.image.len());
+        if block.len() != end - start {
+            return Err(ActionResult::OtherReason);
+        }
+        self.image[start..end].copy_from_slice(block);
+        self.image_transferred_blocks_status[number as usize / 8] |= 0x80 >> (number % 8);
+        self.image_first_not_transferred_block_number =
+            (self.image_first_not_transferred_block_number..self.block_count())
+                .find(|number| !self.is_block_transferred(*number))
+                .unwrap_or(self.block_count());
+        Ok(())
+    }
+
+    fn verify(&mut self) -> Result<(), ActionResult> {
+        match self.image_transfer_status {
+            ImageTransferStatus::Initiated | ImageTransferStatus::VerificationFailed => {}
+            ImageTransferStatus::VerificationSuccessful => return Ok(()),
+            _ => return Err(ActionResult::ReadWriteDenied),
+        }
+        let complete = self.image_first_not_transferred_block_number == self.block_count();
+        if complete
+            && self.verifier.is_none_or(|verify| verify(&self.image_identifier, &self.image))
+        {
+            self.image_to_activate_info = vec![ImageToActivateInfo {
+                size: self.image_size,
+                identification: self.image_identifier.clone(),
+                signature: Vec::new(),
+            }];
+            self.image_transfer_status = ImageTransferStatus::VerificationSuccessful;
+            Ok(())
+        } else {
+            self.image_transfer_status = ImageTransferStatus::VerificationFailed;
+            Err(ActionResult::OtherReason)
+        }
+    }
+
+    fn activate(&mut self) -> Result<(), ActionResult> {
+        match self.image_transfer_status {
+            ImageTransferStatus::Initiated => self.verify()?,
+            ImageTransferStatus::VerificationSuccessful
+            | ImageTransferStatus::ActivationSuccessful => {}
+            _ => return Err(ActionResult::ReadWriteDenied),
+        }
+        self.image_transfer_status = ImageTransferStatus::ActivationSuccessful;
+        Ok(())
+    }
+}
+
+impl CosemObject for ImageTransfer {
+    fn class_id(&self) -> u16 {
+        18
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::DoubleLongUnsigned(self.image_block_size)),
+            3 => Ok(Data::BitString(self.image_transferred_blocks_status.clone())),
+            4 => Ok(Data::DoubleLongUnsigned(self.image_first_not_transferred_block_number)),
+            5 => Ok(Data::Boolean(self.image_transfer_enabled)),
+            6 => Ok(Data::Enum(self.image_transfer_status as u8)),
+            7 => Ok(Data::Structure(
+                self.image_to_activate_info.iter().map(ImageToActivateInfo::to_data).collect(),
+            )),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match id {
+            5 => match value {
+                Data::Boolean(enabled) => {
+                    self.image_transfer_enabled = enabled;
+                    Ok(())
+                }
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            1..=7 => Err(DataAccessResult::ReadWriteDenied),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        parameters: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match (method_id, parameters) {
+            (1, Some(Data::Structure(parameters))) => match <[Data; 2]>::try_from(parameters) {
+                Ok([Data::OctetString(identifier), Data::DoubleLongUnsigned(size)]) => {
+                    self.initiate(identifier, size)?
+                }
+                _ => return Err(ActionResult::TypeUnmatched),
+            },
+            (2, Some(Data::Structure(parameters))) => match parameters.as_slice() {
+                [Data::DoubleLongUnsigned(number), Data::OctetString(block)] => {
+                    self.block_transfer(*number, block)?
+                }
+                _ => return Err(ActionResult::TypeUnmatched),
+            },
+            (3, _) => self.verify()?,
+            (4, _) => self.activate()?,
+            (1 | 2, _) => return Err(ActionResult::TypeUnmatched),
+            _ => return Err(ActionResult::ObjectUndefined),
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn initiate(identifier: &[u8], size: u32) -> Option<Data> {
+        Some(Data::Structure(vec![
+            Data::OctetString(identifier.to_vec()),
+            Data::DoubleLongUnsigned(size),
+        ]))
+    }
+
+    fn block(number: u32, value: &[u8]) -> Option<Data> {
+        Some(Data::Structure(vec![
+            Data::DoubleLongUnsigned(number),
+            Data::OctetString(value.to_vec()),
+        ]))
+    }
+
+    #[test]
+    fn test_block_status() {
+        let mut image_transfer = ImageTransfer::new(2);
+        image_transfer.invoke_method(1, initiate(b"FW", 19)).unwrap();
+        assert_eq!(image_transfer.block_count(), 10);
+        assert_eq!(image_transfer.get_attribute(3), Ok(Data::BitString(vec![0, 0])));
+
+        image_transfer.invoke_method(2, block(0, b"ab")).unwrap();
+        image_transfer.invoke_method(2, block(2, b"ef")).unwrap();
+        image_transfer.invoke_method(2, block(9, b"s")).unwrap();
+        assert_eq!(image_transfer.get_attribute(3), Ok(Data::BitString(vec![0xA0, 0x40])));
+        assert_eq!(image_transfer.get_attribute(4), Ok(Data::DoubleLongUnsigned(1)));
+
+        // Wrong length, out of range.
+        assert_eq!(image_transfer.invoke_method(2, block(1, b"c")), Err(ActionResult::OtherReason));
+        assert_eq!(
+            image_transfer.invoke_method(2, block(10, b"xx")),
+            Err(ActionResult::OtherReason)
+        );
+        assert_eq!(
+            image_transfer.invoke_method(2, Some(Data::Unsigned(1))),
+            Err(ActionResult::TypeUnmatched)
+        );
+
+        // Initiating the same image again keeps the transferred blocks.
+        image_transfer.invoke_method(1, initiate(b"FW", 19)).unwrap();
+        assert_eq!(image_transfer.get_attribute(4), Ok(Data::DoubleLongUnsigned(1)));
+        image_transfer.invoke_method(1, initiate(b"FW2", 19)).unwrap();
+        assert_eq!(image_transfer.get_attribute(4), Ok(Data::DoubleLongUnsigned(0)));
+    }
+
+    #[test]
+    fn test_verify_and_activate() {
+        let mut image_transfer = ImageTransfer::new(4).with_verifier(|_, image| image[0] == b'F');
+        assert_eq!(image_transfer.invoke_method(4, None), Err(ActionResult::ReadWriteDenied));
+        image_transfer.invoke_method(1, initiate(b"FW", 6)).unwrap();
+        image_transfer.invoke_method(2, block(0, b"FIRM")).unwrap();
+
+        // Incomplete image.
+        assert_eq!(image_transfer.invoke_method(3, None), Err(ActionResult::OtherReason));
+        assert_eq!(image_transfer.get_attribute(6), Ok(Data::Enum(4)));
+
+        image_transfer.invoke_method(1, initiate(b"FW", 6)).unwrap();
+        image_transfer.invoke_method(2, block(0, b"FIRM")).unwrap();
+        image_transfer.invoke_method(2, block(1, b"WA")).unwrap();
+        image_transfer.invoke_method(3, None).unwrap();
+        assert_eq!(
+            image_transfer.get_attribute(7),
+            Ok(Data::Structure(vec![Data::Structure(vec![
+                Data::DoubleLongUnsigned(6),
+                Data::OctetString(b"FW".to_vec()),
+                Data::OctetString(vec![]),
+            ])]))
+        );
+        image_transfer.invoke_method(4, None).unwrap();
+        assert_eq!(image_transfer.image_transfer_status, ImageTransferStatus::ActivationSuccessful);
+
+        // Rejected by the verifier.
+        image_transfer.invoke_method(1, initiate(b"FW", 4)).unwrap();
+        image_transfer.invoke_method(2, block(0, b"XIRM")).unwrap();
+        assert_eq!(image_transfer.invoke_method(4, None), Err(ActionResult::OtherReason));
+        assert_eq!(image_transfer.image_transfer_status, ImageTransferStatus::VerificationFailed);
+    }
+
+    #[test]
+    fn test_transfer_disabled() {
+        let mut image_transfer = ImageTransfer::new(64);
+        assert_eq!(image_transfer.get_attribute(5), Ok(Data::Boolean(true)));
+        assert_eq!(
+            image_transfer.set_attribute(5, Data::Unsigned(0)),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        image_transfer.set_attribute(5, Data::Boolean(false)).unwrap();
+        assert_eq!(
+            image_transfer.invoke_method(1, initiate(b"FW", 10)),
+            Err(ActionResult::ReadWriteDenied)
+        );
+        image_transfer.set_attribute(5, Data::Boolean(true)).unwrap();
+        assert_eq!(
+            image_transfer.invoke_method(1, initiate(b"FW", DEFAULT_MAX_IMAGE_SIZE + 1)),
+            Err(ActionResult::OtherReason)
+        );
+        assert_eq!(
+            image_transfer.invoke_method(1, initiate(b"FW", 0)),
+            Err(ActionResult::OtherReason)
+        );
+        assert_eq!(
+            image_transfer.set_attribute(2, Data::DoubleLongUnsigned(1)),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+    }
+    #[test]
+    fn test_large_image() {
+        // Blocks larger than 255 bytes and more than 2040 blocks.
+        let mut image_transfer = ImageTransfer::new(1024);
+        image_transfer.invoke_method(1, initiate(b"FW", 3000 * 1024)).unwrap();
+        assert_eq!(image_transfer.block_count(), 3000);
+        for number in 0..3000 {
+            image_transfer.invoke_method(2, block(number, &[number as u8; 1024])).unwrap();
+        }
+        assert_eq!(image_transfer.get_attribute(4), Ok(Data::DoubleLongUnsigned(3000)));
+        let Ok(Data::BitString(status)) = image_transfer.get_attribute(3) else {
+            panic!("expected a bit string");
+        };
+        assert_eq!(status.len(), 375);
+        assert!(status.iter().all(|byte| *byte == 0xFF));
+        image_transfer.invoke_method(3, None).unwrap();
+
+        let mut image_transfer = ImageTransfer::new(64).with_max_image_size(1000);
+        assert_eq!(image_transfer.max_image_size(), 1000);
+        image_transfer.invoke_method(1, initiate(b"FW", 1000)).unwrap();
+        assert_eq!(
+            image_transfer.invoke_method(1, initiate(b"FW", 1001)),
+            Err(ActionResult::OtherReason)
+        );
+    }
+}
@@ -0,0 +1,429 @@
+//! COSEM Interface Class 42: IPv4 Setup
+//!
+//! Holds the IPv4 configuration of an interface: address, subnet mask,
+//! gateway, DNS servers, multicast addresses and whether DHCP is used.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.25.1.0.255)
+//! - Attribute 2: `DL_reference` - Logical name of the data link layer setup object
+//! - Attribute 3: `IP_address` - double-long-unsigned
+//! - Attribute 4: `multicast_IP_address` - Array of multicast addresses
+//! - Attribute 5: `IP_options` - Array of `{IP_option_type, IP_option_length, IP_option_data}`
+//! - Attribute 6: `subnet_mask` - Contiguous mask
+//! - Attribute 7: `gateway_IP_address`
+//! - Attribute 8: `use_DHCP_flag` - Boolean
+//! - Attribute 9: `primary_DNS_address`
+//! - Attribute 10: `secondary_DNS_address`
+//!
+//! ## Methods
+//! - Method 1: `add_mc_IP_address(data)` - Adds a multicast address
+//! - Method 2: `delete_mc_IP_address(data)` - Removes a multicast address
+//! - Method 3: `get_nbof_mc_IP_addresses(data)` - Returns the number of
+//!   multicast addresses
+//!
+//! Addresses are double-long-unsigned values in network order and convert
+//! to and from [`Ipv4Addr`]. Non contiguous subnet masks and non multicast
+//! addresses in the multicast list are rejected with `OtherReason`.
+//!
+//! # Example
+//! ```
+//! use std::net::Ipv4Addr;
+//!
+//! use dlms_cosem::cosem::CosemObject;
+//! use dlms_cosem::cosem::ipv4_setup::Ipv4Setup;
+//! use dlms_cosem::{Data, ObisCode};
+//!
+//! let mut ipv4_setup = Ipv4Setup::new(ObisCode::new(0, 0, 25, 1, 0, 255))
+//!     .with_ip_address(Ipv4Addr::new(192, 0, 2, 10))
+//!     .with_subnet_mask(Ipv4Addr::new(255, 255, 255, 0))
+//!     .unwrap()
+//!     .with_gateway(Ipv4Addr::new(192, 0, 2, 1));
+//!
+//! assert_eq!(ipv4_setup.get_attribute(3), Ok(Data::DoubleLongUnsigned(0xC000_020A)));
+//! assert_eq!(ipv4_setup.prefix_length(), 24);
+//!
+//! let multicast = u32::from(Ipv4Addr::new(224, 0, 1, 1));
+//! ipv4_setup.invoke_method(1, Some(Data::DoubleLongUnsigned(multicast))).unwrap();
+//! assert_eq!(ipv4_setup.multicast_ip_addresses, [Ipv4Addr::new(224, 0, 1, 1)]);
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+use std::net::Ipv4Addr;
+
+use crate::action::ActionResult;
+use crate::cosem::CosemObject;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// An IP option (element of attribute 5).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct IpOption {
+    /// Option type, e.g. 0x44 for security
+    pub option_type: u8,
+    /// Option data
+    pub data: Vec<u8>,
+}
+
+/// IPv4 Setup object - COSEM Interface Class 42
+///
+/// Reference: Blue Book 4.9.3
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Ipv4Setup {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Logical name of the data link layer setup object
+    pub dl_reference: ObisCode,
+    /// Attribute 3: Address of the interface
+    pub ip_address: Ipv4Addr,
+    /// Attribute 4: Multicast addresses the interface listens to
+    pub multicast_ip_addresses: Vec<Ipv4Addr>,
+    /// Attribute 5: IP options
+    pub ip_options: Vec<IpOption>,
+    /// Attribute 6: Subnet mask
+    pub subnet_mask: Ipv4Addr,
+    /// Attribute 7: Default gateway
+    pub gateway_ip_address: Ipv4Addr,
+    /// Attribute 8: Whether the configuration is obtained through DHCP
+    pub use_dhcp: bool,
+    /// Attribute 9: Primary DNS server
+    pub primary_dns_address: Ipv4Addr,
+    /// Attribute 10: Secondary DNS server
+    pub secondary_dns_address: Ipv4Addr,
+}
+
+impl Ipv4Setup {
+    /// Creates an unconfigured IPv4 setup: all addresses unspecified, no
+    /// data link layer reference, DHCP off.
+    pub fn new(logical_name: ObisCode) -> Self {
+        Self {
+            logical_name,
+            dl_reference: ObisCode::new(0, 0, 0, 0, 0, 0),
+            ip_address: Ipv4Addr::UNSPECIFIED,
+            multicast_ip_addresses: Vec::new(),
+            ip_options: Vec::new(),
+            subnet_mask: Ipv4Addr::UNSPECIFIED,
+            gateway_ip_address: Ipv4Addr::UNSPECIFIED,
+            use_dhcp: false,
+            primary_dns_address: Ipv4Addr::UNSPECIFIED,
+            secondary_dns_address: Ipv4Addr::UNSPECIFIED,
+        }
+    }
+
+    /// Sets the data link layer setup object (builder style).
+    pub fn with_dl_reference(mut self, dl_reference: ObisCode) -> Self {
+        self.dl_reference = dl_reference;
+        self
+    }
+
+    /// Sets the address (builder style).
+    pub fn with_ip_address(mut self, ip_address: Ipv4Addr) -> Self {
+        self.ip_address = ip_address;
+        self
+    }
+
+    /// Sets the subnet mask, which must be contiguous (builder style).
+    pub fn with_subnet_mask(mut self, subnet_mask: Ipv4Addr) -> Result<Self, DataAccessResult> {
+        self.subnet_mask = subnet_mask_from(subnet_mask.into())?;
+        Ok(self)
+    }
+
+    /// Sets the default gateway (builder style).
+    pub fn with_gateway(mut self, gateway: Ipv4Addr) -> Self {
+        self.gateway_ip_address = gateway;
+        self
+    }
+
+    /// Sets the primary and secondary DNS servers (builder style).
+    pub fn with_dns(mut self, primary: Ipv4Addr, secondary: Ipv4Addr) -> Self {
+        self.primary_dns_address = primary;
+        self.secondary_dns_address = secondary;
+        self
+    }
+
+    /// Sets whether DHCP is used (builder style).
+    pub fn with_dhcp(mut self, use_dhcp: bool) -> Self {
+        self.use_dhcp = use_dhcp;
+        self
+    }
+
+    /// Returns the prefix length of the subnet mask.
+    pub fn prefix_length(&self) -> u8 {
+        u32::from(self.subnet_mask).leading_ones() as u8
+    }
+
+    /// Adds a multicast address, if not listed yet.
+    pub fn add_multicast_ip_address(&mut self, address: Ipv4Addr) -> Result<(), DataAccessResult> {
+        let address = multicast_from(address.into())?;
+        if !self.multicast_ip_addresses.contains(&address) {
+            self.multicast_ip_addresses.push(address);
+        }
+        Ok(())
+    }
+
+    /// Removes a multicast address; returns whether it was listed.
+    pub fn delete_multicast_ip_address(&mut self, address: Ipv4Addr) -> bool {
+        let count = self.multicast_ip_addresses.len();
+        self.multicast_ip_addresses.retain(|listed| *listed != address);
+        self.multicast_ip_addresses.len() != count
+    }
+
+    fn parse_ip_options(data: &Data) -> Result<Vec<IpOption>, DataAccessResult> {
+        let Data::Structure(options) = data else {
+            return Err(DataAccessResult::TypeUnmatched);
+        };
+        options
+            .iter()
+            .map(|option| match option {
+                Data::Structure(fields) => match fields.as_slice() {
+                    [Data::Enum(option_type), Data::Unsigned(length), Data::OctetString(data)] => {
+                        if usize::from(*length) != data.len() {
+                            return Err(DataAccessResult::OtherReason);
+                        }
+                        Ok(IpOption { option_type: *option_type, data: data.clone() })
+                    }
+                    _ => Err(DataAccessResult::TypeUnmatched),
+                },
+                _ => Err(DataAccessResult::TypeUnmatched),
+            })
+            .collect()
+    }
+}
+
+/// Checks that a subnet mask is contiguous.
+fn subnet_mask_from(mask: u32) -> Result<Ipv4Addr, DataAccessResult> {
+    if mask.leading_ones() + mask.trailing_zeros() == 32 {
+        Ok(mask.into())
+    } else {
+        Err(DataAccessResult::OtherReason)
+    }
+}
+
+/// Checks that an address is a multicast address.
+fn multicast_from(address: u32) -> Result<Ipv4Addr, DataAccessResult> {
+    let address = Ipv4Addr::from(address);
+    if address.is_multicast() { Ok(address) } else { Err(DataAccessResult::OtherReason) }
+}
+
+/// Encodes an address as double-long-unsigned.
+fn address_data(address: Ipv4Addr) -> Data {
+    Data::DoubleLongUnsigned(address.into())
+}
+
+impl CosemObject for Ipv4Setup {
+    fn class_id(&self) -> u16 {
+        42
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::OctetString(self.dl_reference.encode().to_vec())),
+            3 => Ok(address_data(self.ip_address)),
+            4 => Ok(Data::Structure(
+                self.multicast_ip_addresses.iter().copied().map(address_data).collect(),
+            )),
+            5 => Ok(Data::Structure(
+                self.ip_options
+                    .iter()
+                    .map(|option| {
+                        Data::Structure(vec![
+                            Data::Enum(option.option_type),
+                            Data::Unsigned(option.data.len() as u8),
+                            Data::OctetString(option.data.clone()),
+                        ])
+                    })
+                    .collect(),
+            )),
+            6 => Ok(address_data(self.subnet_mask)),
+            7 => Ok(address_data(self.gateway_ip_address)),
+            8 => Ok(Data::Boolean(self.use_dhcp)),
+            9 => Ok(address_data(self.primary_dns_address)),
+            10 => Ok(address_data(self.secondary_dns_address)),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (2, Data::OctetString(name)) => {
+                self.dl_reference = ObisCode::try_from(name.as_slice())
+                    .map_err(|_| DataAccessResult::TypeUnmatched)?
+            }
+            (3, Data::DoubleLongUnsigned(address)) => self.ip_address = address.into(),
+            (4, Data::Structure(addresses)) => {
+                self.multicast_ip_addresses = addresses
+                    .iter()
+                    .map(|address| match address {
+                        Data::DoubleLongUnsigned(address) => multicast_from(*address),
+                        _ => Err(DataAccessResult::TypeUnmatched),
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            (5, value) => self.ip_options = Self::parse_ip_options(&value)?,
+            (6, Data::DoubleLongUnsigned(mask)) => self.subnet_mask = subnet_mask_from(mask)?,
+            (7, Data::DoubleLongUnsigned(address)) => self.gateway_ip_address = address.into(),
+            (8, Data::Boolean(flag)) => self.use_dhcp = flag,
+            (9, Data::DoubleLongUnsigned(address)) => self.primary_dns_address = address.into(),
+            (10, Data::DoubleLongUnsigned(address)) => self.secondary_dns_address = address.into(),
+            (2..=10, _) => return Err(DataAccessResult::TypeUnmatched),
+            (1, _) => return Err(DataAccessResult::ReadWriteDenied),
+            _ => return Err(DataAccessResult::ObjectUndefined),
+        }
+        Ok(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match (method_id, params) {
+            (1, Some(Data::DoubleLongUnsigned(address))) => {
+                self.add_multicast_ip_address(address.into())
+                    .map_err(|_| ActionResult::OtherReason)?;
+                Ok(None)
+            }
+            (2, Some(Data::DoubleLongUnsigned(address))) => {
+                if self.delete_multicast_ip_address(address.into()) {
+                    Ok(None)
+                } else {
+                    Err(ActionResult::OtherReason)
+                }
+            }
+            (3, _) => Ok(Some(Data::LongUnsigned(self.multicast_ip_addresses.len() as u16))),
+            (1 | 2, _) => Err(ActionResult::TypeUnmatched),
+            _ => Err(ActionResult::ObjectUndefined),
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, 2..=10).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, 2..=10).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IPV4_SETUP: ObisCode = ObisCode { a: 0, b: 0, c: 25, d: 1, e: 0, f: 255 };
+
+    #[test]
+    fn test_attributes() {
+        let mut ipv4_setup = Ipv4Setup::new(IPV4_SETUP);
+        assert_eq!(
+            ipv4_setup.set_attribute(2, Data::OctetString(vec![0, 0, 25, 2, 0, 255])),
+            Ok(())
+        );
+        assert_eq!(ipv4_setup.set_attribute(3, Data::DoubleLongUnsigned(0x0A00_0005)), Ok(()));
+        assert_eq!(ipv4_setup.set_attribute(6, Data::DoubleLongUnsigned(0xFFFF_F000)), Ok(()));
+        assert_eq!(ipv4_setup.set_attribute(7, Data::DoubleLongUnsigned(0x0A00_0001)), Ok(()));
+        assert_eq!(ipv4_setup.set_attribute(8, Data::Boolean(true)), Ok(()));
+        assert_eq!(ipv4_setup.set_attribute(9, Data::DoubleLongUnsigned(0x0808_0808)), Ok(()));
+        assert_eq!(ipv4_setup.set_attribute(10, Data::DoubleLongUnsigned(0x0101_0101)), Ok(()));
+        let options = Data::Structure(vec![Data::Structure(vec![
+            Data::Enum(0x44),
+            Data::Unsigned(2),
+            Data::OctetString(vec![1, 2]),
+        ])]);
+        assert_eq!(ipv4_setup.set_attribute(5, options.clone()), Ok(()));
+
+        let configured = Ipv4Setup::new(IPV4_SETUP)
+            .with_dl_reference(ObisCode::new(0, 0, 25, 2, 0, 255))
+            .with_ip_address(Ipv4Addr::new(10, 0, 0, 5))
+            .with_subnet_mask(Ipv4Addr::new(255, 255, 240, 0))
+            .unwrap()
+            .with_gateway(Ipv4Addr::new(10, 0, 0, 1))
+            .with_dhcp(true)
+            .with_dns(Ipv4Addr::new(8, 8, 8, 8), Ipv4Addr::new(1, 1, 1, 1));
+        assert_eq!(
+            ipv4_setup,
+            Ipv4Setup {
+                ip_options: vec![IpOption { option_type: 0x44, data: vec![1, 2] }],
+                ..configured
+            }
+        );
+        assert_eq!(ipv4_setup.prefix_length(), 20);
+        assert_eq!(ipv4_setup.get_attribute(5), Ok(options));
+        assert_eq!(ipv4_setup.get_attribute(8), Ok(Data::Boolean(true)));
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut ipv4_setup = Ipv4Setup::new(IPV4_SETUP);
+        let invalid = [
+            (6, Data::DoubleLongUnsigned(0xFF00_FF00), DataAccessResult::OtherReason),
+            (
+                4,
+                Data::Structure(vec![Data::DoubleLongUnsigned(0x0A00_0001)]),
+                DataAccessResult::OtherReason,
+            ),
+            (
+                5,
+                Data::Structure(vec![Data::Structure(vec![
+                    Data::Enum(0x44),
+                    Data::Unsigned(3),
+                    Data::OctetString(vec![1, 2]),
+                ])]),
+                DataAccessResult::OtherReason,
+            ),
+            (8, Data::Unsigned(1), DataAccessResult::TypeUnmatched),
+            (3, Data::OctetString(vec![10, 0, 0, 1]), DataAccessResult::TypeUnmatched),
+            (1, Data::Null, DataAccessResult::ReadWriteDenied),
+            (11, Data::Null, DataAccessResult::ObjectUndefined),
+        ];
+        for (id, value, error) in invalid {
+            assert_eq!(ipv4_setup.set_attribute(id, value), Err(error));
+        }
+        assert_eq!(ipv4_setup, Ipv4Setup::new(IPV4_SETUP));
+        assert_eq!(
+            Ipv4Setup::new(IPV4_SETUP).with_subnet_mask(Ipv4Addr::new(255, 0, 255, 0)),
+            Err(DataAccessResult::OtherReason)
+        );
+    }
+
+    #[test]
+    fn test_multicast_methods() {
+        let mut ipv4_setup = Ipv4Setup::new(IPV4_SETUP);
+        let address = |a, b, c, d| Some(Data::DoubleLongUnsigned(Ipv4Addr::new(a, b, c, d).into()));
+
+        assert_eq!(ipv4_setup.invoke_method(1, address(224, 0, 1, 1)), Ok(None));
+        assert_eq!(ipv4_setup.invoke_method(1, address(239, 1, 2, 3)), Ok(None));
+        assert_eq!(ipv4_setup.invoke_method(1, address(224, 0, 1, 1)), Ok(None));
+        assert_eq!(
+            ipv4_setup.invoke_method(1, address(192, 0, 2, 1)),
+            Err(ActionResult::OtherReason)
+        );
+        assert_eq!(
+            ipv4_setup.invoke_method(3, Some(Data::Integer(0))),
+            Ok(Some(Data::LongUnsigned(2)))
+        );
+
+        assert_eq!(ipv4_setup.invoke_method(2, address(224, 0, 1, 1)), Ok(None));
+        assert_eq!(
+            ipv4_setup.invoke_method(2, address(224, 0, 1, 1)),
+            Err(ActionResult::OtherReason)
+        );
+        assert_eq!(ipv4_setup.multicast_ip_addresses, [Ipv4Addr::new(239, 1, 2, 3)]);
+        assert_eq!(ipv4_setup.invoke_method(1, Some(Data::Null)), Err(ActionResult::TypeUnmatched));
+    }
+}
@@ -0,0 +1,520 @@
+//! COSEM Interface Class 48: IPv6 Setup
+//!
+//! Holds the IPv6 configuration of an interface: how addresses are
+//! configured, the unicast, multicast and gateway addresses, DNS servers,
+//! traffic class and neighbour discovery parameters.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.25.7.0.255)
+//! - Attribute 2: `DL_reference` - Logical name of the data link layer setup object
+//! - Attribute 3: `address_config_mode` - enum
+//! - Attribute 4: `unicast_IPv6_addresses` - Array of 16 byte octet-strings
+//! - Attribute 5: `multicast_IPv6_addresses` - Array of 16 byte octet-strings
+//! - Attribute 6: `gateway_IPv6_addresses` - Array of 16 byte octet-strings
+//! - Attribute 7: `primary_DNS_address` - 16 bytes, empty if none
+//! - Attribute 8: `secondary_DNS_address` - 16 bytes, empty if none
+//! - Attribute 9: `traffic_class`
+//! - Attribute 10: `neighbor_discovery_setup` - Array of `{max_retry,
+//!   retry_time, send_period}`
+//!
+//! ## Methods
+//! - Method 1: `add_IPv6_address(data)` - Adds `{address_type, address}`
+//! - Method 2: `remove_IPv6_address(data)` - Removes `{address_type, address}`
+//!
+//! Addresses convert to and from [`Ipv6Addr`]. Multicast addresses in the
+//! unicast or gateway lists, and other addresses in the multicast list, are
+//! rejected with `OtherReason`.
+//!
+//! # Example
+//! ```
+//! use std::net::Ipv6Addr;
+//!
+//! use dlms_cosem::cosem::CosemObject;
+//! use dlms_cosem::cosem::ipv6_setup::{AddressConfigMode, Ipv6AddressType, Ipv6Setup};
+//! use dlms_cosem::{Data, ObisCode};
+//!
+//! let address: Ipv6Addr = "2001:db8::10".parse().unwrap();
+//! let mut ipv6_setup = Ipv6Setup::new(ObisCode::new(0, 0, 25, 7, 0, 255))
+//!     .with_address_config_mode(AddressConfigMode::Manual);
+//! ipv6_setup.add_address(Ipv6AddressType::Unicast, address).unwrap();
+//!
+//! assert_eq!(
+//!     ipv6_setup.get_attribute(4),
+//!     Ok(Data::Structure(vec![Data::OctetString(address.octets().to_vec())]))
+//! );
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+use std::net::Ipv6Addr;
+
+use crate::action::ActionResult;
+use crate::cosem::CosemObject;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// How the addresses are configured (attribute 3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AddressConfigMode {
+    /// Stateless address auto-configuration
+    AutoConfiguration = 0,
+    /// Stateful configuration through DHCPv6
+    Dhcpv6 = 1,
+    /// Manual configuration
+    Manual = 2,
+    /// Neighbour discovery
+    NeighbourDiscovery = 3,
+}
+
+impl AddressConfigMode {
+    /// Converts an address_config_mode enum value.
+    ///
+    /// Returns Err if value is not in range 0-3.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::AutoConfiguration),
+            1 => Ok(Self::Dhcpv6),
+            2 => Ok(Self::Manual),
+            3 => Ok(Self::NeighbourDiscovery),
+            _ => Err(DataAccessResult::OtherReason),
+        }
+    }
+}
+
+/// List an address is added to or removed from by the methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Ipv6AddressType {
+    /// Attribute 4
+    Unicast = 0,
+    /// Attribute 5
+    Multicast = 1,
+    /// Attribute 6
+    Gateway = 2,
+}
+
+impl Ipv6AddressType {
+    /// Converts an address_type enum value.
+    ///
+    /// Returns Err if value is not in range 0-2.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::Unicast),
+            1 => Ok(Self::Multicast),
+            2 => Ok(Self::Gateway),
+            _ => Err(DataAccessResult::OtherReason),
+        }
+    }
+}
+
+/// Neighbour discovery parameters (element of attribute 10).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NeighborDiscoverySetup {
+    /// Router solicitations sent before giving up
+    pub max_retry: u8,
+    /// Seconds between router solicitations
+    pub retry_time: u16,
+    /// Seconds between router advertisements
+    pub send_period: u32,
+}
+
+/// IPv6 Setup object - COSEM Interface Class 48
+///
+/// Reference: Blue Book 4.9.5
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Ipv6Setup {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Logical name of the data link layer setup object
+    pub dl_reference: ObisCode,
+    /// Attribute 3: How the addresses are configured
+    pub address_config_mode: AddressConfigMode,
+    /// Attribute 4: Unicast addresses of the interface
+    pub unicast_ipv6_addresses: Vec<Ipv6Addr>,
+    /// Attribute 5: Multicast addresses the interface listens to
+    pub multicast_ipv6_addresses: Vec<Ipv6Addr>,
+    /// Attribute 6: Gateways
+    pub gateway_ipv6_addresses: Vec<Ipv6Addr>,
+    /// Attribute 7: Primary DNS server
+    pub primary_dns_address: Option<Ipv6Addr>,
+    /// Attribute 8: Secondary DNS server
+    pub secondary_dns_address: Option<Ipv6Addr>,
+    /// Attribute 9: Traffic class of the packets sent
+    pub traffic_class: u8,
+    /// Attribute 10: Neighbour discovery parameters
+    pub neighbor_discovery_setup: Vec<NeighborDiscoverySetup>,
+}
+
+impl Ipv6Setup {
+    /// Creates an IPv6 setup using auto-configuration, without addresses.
+    pub fn new(logical_name: ObisCode) -> Self {
+        Self {
+            logical_name,
+            dl_reference: ObisCode::new(0, 0, 0, 0, 0, 0),
+            address_config_mode: AddressConfigMode::AutoConfiguration,
+            unicast_ipv6_addresses: Vec::new(),
+            multicast_ipv6_addresses: Vec::new(),
+            gateway_ipv6_addresses: Vec::new(),
+            primary_dns_address: None,
+            secondary_dns_address: None,
+            traffic_class: 0,
+            neighbor_discovery_setup: Vec::new(),
+        }
+    }
+
+    /// Sets the data link layer setup object (builder style).
+    pub fn with_dl_reference(mut self, dl_reference: ObisCode) -> Self {
+        self.dl_reference = dl_reference;
+        self
+    }
+
+    /// Sets how the addresses are configured (builder style).
+    pub fn with_address_config_mode(mut self, mode: AddressConfigMode) -> Self {
+        self.address_config_mode = mode;
+        self
+    }
+
+    /// Sets the primary and secondary DNS servers (builder style).
+    pub fn with_dns(mut self, primary: Option<Ipv6Addr>, secondary: Option<Ipv6Addr>) -> Self {
+        self.primary_dns_address = primary;
+        self.secondary_dns_address = secondary;
+        self
+    }
+
+    /// Sets the traffic class (builder style).
+    pub fn with_traffic_class(mut self, traffic_class: u8) -> Self {
+        self.traffic_class = traffic_class;
+        self
+    }
+
+    /// Adds an address to a list, if not listed yet.
+    pub fn add_address(
+        &mut self,
+        address_type: Ipv6AddressType,
+        address: Ipv6Addr,
+    ) -> Result<(), DataAccessResult> {
+        check_address(address_type, address)?;
+        let addresses = self.addresses_mut(address_type);
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+        Ok(())
+    }
+
+    /// Removes an address from a list; returns whether it was listed.
+    pub fn remove_address(&mut self, address_type: Ipv6AddressType, address: Ipv6Addr) -> bool {
+        let addresses = self.addresses_mut(address_type);
+        let count = addresses.len();
+        addresses.retain(|listed| *listed != address);
+        addresses.len() != count
+    }
+
+    fn addresses_mut(&mut self, address_type: Ipv6AddressType) -> &mut Vec<Ipv6Addr> {
+        match address_type {
+            Ipv6AddressType::Unicast => &mut self.unicast_ipv6_addresses,
+            Ipv6AddressType::Multicast => &mut self.multicast_ipv6_addresses,
+            Ipv6AddressType::Gateway => &mut self.gateway_ipv6_addresses,
+        }
+    }
+
+    /// Parses the `{address_type, address}` parameter of the methods.
+    fn parse_method_params(
+        params: Option<Data>,
+    ) -> Result<(Ipv6AddressType, Ipv6Addr), ActionResult> {
+        match params {
+            Some(Data::Structure(fields)) => match fields.as_slice() {
+                [Data::Enum(address_type), address] => Ok((
+                    Ipv6AddressType::from_u8(*address_type)
+                        .map_err(|_| ActionResult::OtherReason)?,
+                    address_from(address).map_err(|_| ActionResult::TypeUnmatched)?,
+                )),
+                _ => Err(ActionResult::TypeUnmatched),
+            },
+            _ => Err(ActionResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Checks that an address suits a list.
+fn check_address(address_type: Ipv6AddressType, address: Ipv6Addr) -> Result<(), DataAccessResult> {
+    if address.is_multicast() == (address_type == Ipv6AddressType::Multicast) {
+        Ok(())
+    } else {
+        Err(DataAccessResult::OtherReason)
+    }
+}
+
+/// Parses a 16 byte octet-string.
+fn address_from(data: &Data) -> Result<Ipv6Addr, DataAccessResult> {
+    match data {
+        Data::OctetString(octets) => <[u8; 16]>::try_from(octets.as_slice())
+            .map(Ipv6Addr::from)
+            .map_err(|_| DataAccessResult::TypeUnmatched),
+        _ => Err(DataAccessResult::TypeUnmatched),
+    }
+}
+
+/// Encodes an address as a 16 byte octet-string.
+fn address_data(address: &Ipv6Addr) -> Data {
+    Data::OctetString(address.octets().to_vec())
+}
+
+/// Encodes an optional address, an empty octet-string if none.
+fn dns_data(address: &Option<Ipv6Addr>) -> Data {
+    address.as_ref().map_or(Data::OctetString(Vec::new()), address_data)
+}
+
+/// Parses an optional address.
+fn dns_from(data: &Data) -> Result<Option<Ipv6Addr>, DataAccessResult> {
+    match data {
+        Data::OctetString(octets) if octets.is_empty() => Ok(None),
+        data => address_from(data).map(Some),
+    }
+}
+
+/// Parses an address list (attributes 4 to 6).
+fn addresses_from(
+    address_type: Ipv6AddressType,
+    data: &Data,
+) -> Result<Vec<Ipv6Addr>, DataAccessResult> {
+    let Data::Structure(addresses) = data else {
+        return Err(DataAccessResult::TypeUnmatched);
+    };
+    addresses
+        .iter()
+        .map(|address| {
+            let address = address_from(address)?;
+            check_address(address_type, address)?;
+            Ok(address)
+        })
+        .collect()
+}
+
+impl CosemObject for Ipv6Setup {
+    fn class_id(&self) -> u16 {
+        48
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        let addresses =
+            |list: &[Ipv6Addr]| Data::Structure(list.iter().map(address_data).collect());
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::OctetString(self.dl_reference.encode().to_vec())),
+            3 => Ok(Data::Enum(self.address_config_mode as u8)),
+            4 => Ok(addresses(&self.unicast_ipv6_addresses)),
+            5 => Ok(addresses(&self.multicast_ipv6_addresses)),
+            6 => Ok(addresses(&self.gateway_ipv6_addresses)),
+            7 => Ok(dns_data(&self.primary_dns_address)),
+            8 => Ok(dns_data(&self.secondary_dns_address)),
+            9 => Ok(Data::Unsigned(self.traffic_class)),
+            10 => Ok(Data::Structure(
+                self.neighbor_discovery_setup
+                    .iter()
+                    .map(|setup| {
+                        Data::Structure(vec![
+                            Data::Unsigned(setup.max_retry),
+                            Data::LongUnsigned(setup.retry_time),
+                            Data::DoubleLongUnsigned(setup.send_period),
+                        ])
+                    })
+                    .collect(),
+            )),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (2, Data::OctetString(name)) => {
+                self.dl_reference = ObisCode::try_from(name.as_slice())
+                    .map_err(|_| DataAccessResult::TypeUnmatched)?
+            }
+            (3, Data::Enum(mode)) => self.address_config_mode = AddressConfigMode::from_u8(mode)?,
+            (4, value) => {
+                self.unicast_ipv6_addresses = addresses_from(Ipv6AddressType::Unicast, &value)?
+            }
+            (5, value) => {
+                self.multicast_ipv6_addresses = addresses_from(Ipv6AddressType::Multicast, &value)?
+            }
+            (6, value) => {
+                self.gateway_ipv6_addresses = addresses_from(Ipv6AddressType::Gateway, &value)?
+            }
+            (7, value) => self.primary_dns_address = dns_from(&value)?,
+            (8, value) => self.secondary_dns_address = dns_from(&value)?,
+            (9, Data::Unsigned(traffic_class)) => self.traffic_class = traffic_class,
+            (10, Data::Structure(setups)) => {
+                self.neighbor_discovery_setup = setups
+                    .iter()
+                    .map(|setup| match setup {
+                        Data::Structure(fields) => match fields.as_slice() {
+                            [
+                                Data::Unsigned(max_retry),
+                                Data::LongUnsigned(retry_time),
+                                Data::DoubleLongUnsigned(send_period),
+                            ] => Ok(NeighborDiscoverySetup {
+                                max_retry: *max_retry,
+                                retry_time: *retry_time,
+                                send_period: *send_period,
+                            }),
+                            _ => Err(DataAccessResult::TypeUnmatched),
+                        },
+                        _ => Err(DataAccessResult::TypeUnmatched),
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            (2..=10, _) => return Err(DataAccessResult::TypeUnmatched),
+            (1, _) => return Err(DataAccessResult::ReadWriteDenied),
+            _ => return Err(DataAccessResult::ObjectUndefined),
+        }
+        Ok(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match method_id {
+            1 => {
+                let (address_type, address) = Self::parse_method_params(params)?;
+                self.add_address(address_type, address).map_err(|_| ActionResult::OtherReason)?;
+                Ok(None)
+            }
+            2 => {
+                let (address_type, address) = Self::parse_method_params(params)?;
+                if self.remove_address(address_type, address) {
+                    Ok(None)
+                } else {
+                    Err(ActionResult::OtherReason)
+                }
+            }
+            _ => Err(ActionResult::ObjectUndefined),
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, 2..=10).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, 2..=10).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IPV6_SETUP: ObisCode = ObisCode { a: 0, b: 0, c: 25, d: 7, e: 0, f: 255 };
+
+    fn ip(text: &str) -> Ipv6Addr {
+        text.parse().unwrap()
+    }
+
+    fn octets(text: &str) -> Data {
+        Data::OctetString(ip(text).octets().to_vec())
+    }
+
+    #[test]
+    fn test_attributes() {
+        let mut ipv6_setup = Ipv6Setup::new(IPV6_SETUP);
+        let neighbor_discovery = Data::Structure(vec![Data::Structure(vec![
+            Data::Unsigned(3),
+            Data::LongUnsigned(10),
+            Data::DoubleLongUnsigned(600),
+        ])]);
+        assert_eq!(ipv6_setup.set_attribute(3, Data::Enum(2)), Ok(()));
+        assert_eq!(
+            ipv6_setup.set_attribute(
+                4,
+                Data::Structure(vec![octets("2001:db8::10"), octets("fe80::10")])
+            ),
+            Ok(())
+        );
+        assert_eq!(ipv6_setup.set_attribute(5, Data::Structure(vec![octets("ff02::1")])), Ok(()));
+        assert_eq!(ipv6_setup.set_attribute(6, Data::Structure(vec![octets("fe80::1")])), Ok(()));
+        assert_eq!(ipv6_setup.set_attribute(7, octets("2001:4860:4860::8888")), Ok(()));
+        assert_eq!(ipv6_setup.set_attribute(8, Data::OctetString(vec![])), Ok(()));
+        assert_eq!(ipv6_setup.set_attribute(9, Data::Unsigned(0xB8)), Ok(()));
+        assert_eq!(ipv6_setup.set_attribute(10, neighbor_discovery.clone()), Ok(()));
+
+        assert_eq!(ipv6_setup.address_config_mode, AddressConfigMode::Manual);
+        assert_eq!(ipv6_setup.unicast_ipv6_addresses, [ip("2001:db8::10"), ip("fe80::10")]);
+        assert_eq!(ipv6_setup.gateway_ipv6_addresses, [ip("fe80::1")]);
+        assert_eq!(ipv6_setup.primary_dns_address, Some(ip("2001:4860:4860::8888")));
+        assert_eq!(ipv6_setup.secondary_dns_address, None);
+        assert_eq!(ipv6_setup.get_attribute(8), Ok(Data::OctetString(vec![])));
+        assert_eq!(ipv6_setup.get_attribute(5), Ok(Data::Structure(vec![octets("ff02::1")])));
+        assert_eq!(ipv6_setup.get_attribute(10), Ok(neighbor_discovery));
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut ipv6_setup = Ipv6Setup::new(IPV6_SETUP);
+        let invalid = [
+            (3, Data::Enum(4), DataAccessResult::OtherReason),
+            (4, Data::Structure(vec![octets("ff02::1")]), DataAccessResult::OtherReason),
+            (5, Data::Structure(vec![octets("2001:db8::1")]), DataAccessResult::OtherReason),
+            (
+                6,
+                Data::Structure(vec![Data::OctetString(vec![0; 4])]),
+                DataAccessResult::TypeUnmatched,
+            ),
+            (7, Data::OctetString(vec![0; 15]), DataAccessResult::TypeUnmatched),
+            (9, Data::Enum(0), DataAccessResult::TypeUnmatched),
+            (1, Data::Null, DataAccessResult::ReadWriteDenied),
+        ];
+        for (id, value, error) in invalid {
+            assert_eq!(ipv6_setup.set_attribute(id, value), Err(error));
+        }
+        assert_eq!(ipv6_setup, Ipv6Setup::new(IPV6_SETUP));
+    }
+
+    #[test]
+    fn test_address_methods() {
+        let mut ipv6_setup = Ipv6Setup::new(IPV6_SETUP);
+        let params = |address_type, text| {
+            Some(Data::Structure(vec![Data::Enum(address_type), octets(text)]))
+        };
+
+        assert_eq!(ipv6_setup.invoke_method(1, params(0, "2001:db8::10")), Ok(None));
+        assert_eq!(ipv6_setup.invoke_method(1, params(1, "ff05::fb")), Ok(None));
+        assert_eq!(ipv6_setup.invoke_method(1, params(2, "fe80::1")), Ok(None));
+        assert_eq!(ipv6_setup.invoke_method(1, params(2, "fe80::1")), Ok(None));
+        assert_eq!(
+            ipv6_setup.invoke_method(1, params(1, "2001:db8::11")),
+            Err(ActionResult::OtherReason)
+        );
+        assert_eq!(
+            ipv6_setup.invoke_method(1, params(3, "2001:db8::11")),
+            Err(ActionResult::OtherReason)
+        );
+        assert_eq!(ipv6_setup.invoke_method(1, Some(Data::Null)), Err(ActionResult::TypeUnmatched));
+        assert_eq!(ipv6_setup.gateway_ipv6_addresses, [ip("fe80::1")]);
+
+        assert_eq!(ipv6_setup.invoke_method(2, params(0, "2001:db8::10")), Ok(None));
+        assert_eq!(
+            ipv6_setup.invoke_method(2, params(0, "2001:db8::10")),
+            Err(ActionResult::OtherReason)
+        );
+        assert!(ipv6_setup.unicast_ipv6_addresses.is_empty());
+        assert_eq!(ipv6_setup.multicast_ipv6_addresses, [ip("ff05::fb")]);
+    }
+}
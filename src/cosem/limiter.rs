@@ -0,0 +1,516 @@
+//! COSEM Interface Class 71: Limiter
+//!
+//! A Limiter watches an attribute of another object (typically the
+//! instantaneous power) against a threshold, and executes a script when the
+//! value stays over the threshold, or back under it, long enough. During an
+//! emergency the emergency threshold replaces the normal one.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.17.0.0.255)
+//! - Attribute 2: `monitored_value` - `{class_id, logical_name, attribute_index}`
+//! - Attribute 3: `threshold_active` - Threshold in force (read-only)
+//! - Attribute 4: `threshold_normal`
+//! - Attribute 5: `threshold_emergency`
+//! - Attribute 6: `min_over_threshold_duration` - Seconds
+//! - Attribute 7: `min_under_threshold_duration` - Seconds
+//! - Attribute 8: `emergency_profile` - `{emergency_profile_id,
+//!   emergency_activation_time, emergency_duration}` (duration in seconds)
+//! - Attribute 9: `emergency_profile_group_id_list` - Array of group ids
+//! - Attribute 10: `emergency_profile_active` - Boolean (read-only)
+//! - Attribute 11: `actions` - `{action_over_threshold, action_under_threshold}`
+//!
+//! ## Methods
+//! None.
+//!
+//! ## Evaluation
+//!
+//! The emergency profile is active from its activation time for its
+//! duration, if its id is in the group id list. The value is over the
+//! threshold when it is greater than the threshold in force;
+//! `action_over_threshold` is executed once the value has stayed over it for
+//! `min_over_threshold_duration`, `action_under_threshold` once it has stayed
+//! at or under it for `min_under_threshold_duration`. [`Limiter::update`]
+//! evaluates a value; in a registry, [`ObjectRegistry::tick`] reads the
+//! monitored value. Times are local seconds since 1970-01-01.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::limiter::{Limiter, LimiterActions};
+//! use dlms_cosem::cosem::register_monitor::ValueDefinition;
+//! use dlms_cosem::cosem::script_table::ScriptReference;
+//! use dlms_cosem::{Data, ObisCode};
+//!
+//! let script = |selector| ScriptReference {
+//!     script_logical_name: ObisCode::new(0, 0, 10, 0, 106, 255),
+//!     script_selector: selector,
+//! };
+//! let mut limiter = Limiter::new(
+//!     ObisCode::new(0, 0, 17, 0, 0, 255),
+//!     ValueDefinition { class_id: 3, logical_name: ObisCode::new(1, 0, 1, 7, 0, 255), attribute_index: 2 },
+//!     LimiterActions { action_over_threshold: script(1), action_under_threshold: script(2) },
+//! )
+//! .with_threshold_normal(Data::DoubleLongUnsigned(5_000))
+//! .with_min_over_threshold_duration(60);
+//!
+//! let mut fired = Vec::new();
+//! limiter.update(0, &Data::DoubleLongUnsigned(6_000), &mut |script| fired.push(script.script_selector));
+//! limiter.update(30, &Data::DoubleLongUnsigned(6_000), &mut |script| fired.push(script.script_selector));
+//! assert!(fired.is_empty());
+//! limiter.update(60, &Data::DoubleLongUnsigned(6_000), &mut |script| fired.push(script.script_selector));
+//! assert_eq!(fired, [1]);
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::action::ActionResult;
+use crate::cosem::activity_calendar::transition_seconds;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::cosem::register_monitor::{ValueDefinition, numeric};
+use crate::cosem::script_table::ScriptReference;
+use crate::cosem::{CosemObject, ObjectRegistry};
+use crate::data::{Data, Date, DateTime, Time};
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// An emergency period, during which the emergency threshold is in force.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EmergencyProfile {
+    /// Id, matched against the group id list
+    pub emergency_profile_id: u16,
+    /// Local start time
+    pub emergency_activation_time: DateTime,
+    /// Duration in seconds
+    pub emergency_duration: u32,
+}
+
+impl Default for EmergencyProfile {
+    fn default() -> Self {
+        Self {
+            emergency_profile_id: 0,
+            emergency_activation_time: DateTime::new(
+                Date::new(0xFFFF, 0xFF, 0xFF, 0xFF),
+                Time::new(None, None, None, None),
+                None,
+                None,
+            ),
+            emergency_duration: 0,
+        }
+    }
+}
+
+/// Scripts executed by a Limiter (attribute 11).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LimiterActions {
+    /// Executed when the value has stayed over the threshold
+    pub action_over_threshold: ScriptReference,
+    /// Executed when the value has stayed at or under the threshold
+    pub action_under_threshold: ScriptReference,
+}
+
+/// Limiter object - COSEM Interface Class 71
+///
+/// Reference: Blue Book 4.5.9
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Limiter {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Monitored value
+    pub monitored_value: ValueDefinition,
+    /// Attribute 4: Threshold outside emergencies
+    pub threshold_normal: Data,
+    /// Attribute 5: Threshold during an emergency
+    pub threshold_emergency: Data,
+    /// Attribute 6: Seconds over the threshold before `action_over_threshold`
+    pub min_over_threshold_duration: u32,
+    /// Attribute 7: Seconds under the threshold before `action_under_threshold`
+    pub min_under_threshold_duration: u32,
+    /// Attribute 8: Emergency profile
+    pub emergency_profile: EmergencyProfile,
+    /// Attribute 9: Emergency profile ids that may be activated
+    pub emergency_profile_group_id_list: Vec<u16>,
+    /// Attribute 11: Scripts executed
+    pub actions: LimiterActions,
+    /// Attribute 10: Whether the emergency profile was active at the last update
+    emergency_profile_active: bool,
+    /// Whether `action_over_threshold` was executed last
+    over_threshold: bool,
+    /// Since when the value is on the other side of the threshold
+    crossed_since: Option<u32>,
+}
+
+impl Limiter {
+    /// Creates a Limiter with null thresholds and no minimum durations.
+    pub fn new(
+        logical_name: ObisCode,
+        monitored_value: ValueDefinition,
+        actions: LimiterActions,
+    ) -> Self {
+        Self {
+            logical_name,
+            monitored_value,
+            threshold_normal: Data::Null,
+            threshold_emergency: Data::Null,
+            min_over_threshold_duration: 0,
+            min_under_threshold_duration: 0,
+            emergency_profile: EmergencyProfile::default(),
+            emergency_profile_group_id_list: Vec::new(),
+            actions,
+            emergency_profile_active: false,
+            over_threshold: false,
+            crossed_since: None,
+        }
+    }
+
+    /// Sets the normal threshold (builder style).
+    pub fn with_threshold_normal(mut self, threshold: Data) -> Self {
+        self.threshold_normal = threshold;
+        self
+    }
+
+    /// Sets the emergency threshold (builder style).
+    pub fn with_threshold_emergency(mut self, threshold: Data) -> Self {
+        self.threshold_emergency = threshold;
+        self
+    }
+
+    /// Sets the minimum over threshold duration in seconds (builder style).
+    pub fn with_min_over_threshold_duration(mut self, seconds: u32) -> Self {
+        self.min_over_threshold_duration = seconds;
+        self
+    }
+
+    /// Sets the minimum under threshold duration in seconds (builder style).
+    pub fn with_min_under_threshold_duration(mut self, seconds: u32) -> Self {
+        self.min_under_threshold_duration = seconds;
+        self
+    }
+
+    /// Sets the emergency profile and the group ids it may match (builder style).
+    pub fn with_emergency_profile(
+        mut self,
+        profile: EmergencyProfile,
+        group_ids: Vec<u16>,
+    ) -> Self {
+        self.emergency_profile = profile;
+        self.emergency_profile_group_id_list = group_ids;
+        self
+    }
+
+    /// Returns whether the emergency profile was active at the last update.
+    pub fn emergency_profile_active(&self) -> bool {
+        self.emergency_profile_active
+    }
+
+    /// Returns the threshold in force at the last update.
+    pub fn threshold_active(&self) -> &Data {
+        if self.emergency_profile_active {
+            &self.threshold_emergency
+        } else {
+            &self.threshold_normal
+        }
+    }
+
+    /// Returns whether the value is considered over the threshold.
+    pub fn over_threshold(&self) -> bool {
+        self.over_threshold
+    }
+
+    /// Evaluates `value` at `now`, passing the script to execute, if any, to
+    /// `executor`.
+    pub fn update(&mut self, now: u32, value: &Data, executor: &mut dyn FnMut(&ScriptReference)) {
+        self.emergency_profile_active = self.emergency_active_at(now);
+        let (Some(value), Some(threshold)) = (numeric(value), numeric(self.threshold_active()))
+        else {
+            return;
+        };
+
+        let over = value > threshold;
+        if over == self.over_threshold {
+            self.crossed_since = None;
+            return;
+        }
+        let since = *self.crossed_since.get_or_insert(now);
+        let min_duration =
+            if over { self.min_over_threshold_duration } else { self.min_under_threshold_duration };
+        if now.saturating_sub(since) >= min_duration {
+            self.over_threshold = over;
+            self.crossed_since = None;
+            executor(if over {
+                &self.actions.action_over_threshold
+            } else {
+                &self.actions.action_under_threshold
+            });
+        }
+    }
+
+    fn emergency_active_at(&self, now: u32) -> bool {
+        let profile = &self.emergency_profile;
+        let activation = &profile.emergency_activation_time;
+        self.emergency_profile_group_id_list.contains(&profile.emergency_profile_id)
+            && transition_seconds(activation, activation.date.year.into()).is_some_and(|start| {
+                (start..start + i64::from(profile.emergency_duration)).contains(&i64::from(now))
+            })
+    }
+}
+
+impl CosemObject for Limiter {
+    fn class_id(&self) -> u16 {
+        71
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(self.monitored_value.to_data()),
+            3 => Ok(self.threshold_active().clone()),
+            4 => Ok(self.threshold_normal.clone()),
+            5 => Ok(self.threshold_emergency.clone()),
+            6 => Ok(Data::DoubleLongUnsigned(self.min_over_threshold_duration)),
+            7 => Ok(Data::DoubleLongUnsigned(self.min_under_threshold_duration)),
+            8 => Ok(Data::Structure(vec![
+                Data::LongUnsigned(self.emergency_profile.emergency_profile_id),
+                Data::DateTime(self.emergency_profile.emergency_activation_time.clone()),
+                Data::DoubleLongUnsigned(self.emergency_profile.emergency_duration),
+            ])),
+            9 => Ok(Data::Structure(
+                self.emergency_profile_group_id_list
+                    .iter()
+                    .copied()
+                    .map(Data::LongUnsigned)
+                    .collect(),
+            )),
+            10 => Ok(Data::Boolean(self.emergency_profile_active)),
+            11 => Ok(Data::Structure(vec![
+                self.actions.action_over_threshold.to_data(),
+                self.actions.action_under_threshold.to_data(),
+            ])),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (2, value) => self.monitored_value = ValueDefinition::from_data(&value)?,
+            (4, value) => self.threshold_normal = value,
+            (5, value) => self.threshold_emergency = value,
+            (6, Data::DoubleLongUnsigned(seconds)) => self.min_over_threshold_duration = seconds,
+            (7, Data::DoubleLongUnsigned(seconds)) => self.min_under_threshold_duration = seconds,
+            (8, Data::Structure(profile)) => match profile.as_slice() {
+                [
+                    Data::LongUnsigned(emergency_profile_id),
+                    Data::DateTime(emergency_activation_time),
+                    Data::DoubleLongUnsigned(emergency_duration),
+                ] => {
+                    self.emergency_profile = EmergencyProfile {
+                        emergency_profile_id: *emergency_profile_id,
+                        emergency_activation_time: emergency_activation_time.clone(),
+                        emergency_duration: *emergency_duration,
+                    }
+                }
+                _ => return Err(DataAccessResult::TypeUnmatched),
+            },
+            (9, Data::Structure(group_ids)) => {
+                self.emergency_profile_group_id_list = group_ids
+                    .iter()
+                    .map(|group_id| match group_id {
+                        Data::LongUnsigned(group_id) => Ok(*group_id),
+                        _ => Err(DataAccessResult::TypeUnmatched),
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            (11, Data::Structure(actions)) => match actions.as_slice() {
+                [over, under] => {
+                    self.actions = LimiterActions {
+                        action_over_threshold: ScriptReference::from_data(over)?,
+                        action_under_threshold: ScriptReference::from_data(under)?,
+                    }
+                }
+                _ => return Err(DataAccessResult::TypeUnmatched),
+            },
+            (6..=9 | 11, _) => return Err(DataAccessResult::TypeUnmatched),
+            (1 | 3 | 10, _) => return Err(DataAccessResult::ReadWriteDenied),
+            _ => return Err(DataAccessResult::ObjectUndefined),
+        }
+        Ok(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        _method_id: i8,
+        _params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        Err(ActionResult::ObjectUndefined)
+    }
+
+    /// Reads the monitored value and updates the limiter; unreadable values
+    /// are skipped.
+    fn tick(
+        &mut self,
+        now: u32,
+        objects: &ObjectRegistry,
+        executor: &mut dyn FnMut(&ScriptReference),
+    ) {
+        if let Ok(value) = self.monitored_value.read(objects) {
+            self.update(now, &value, executor);
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [2, 4, 5, 6, 7, 8, 9, 11]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, [2, 4, 5, 6, 7, 8, 9, 11]).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::disconnect_control::{ControlState, DISCONNECT_CONTROL, DisconnectControl};
+    use crate::cosem::register::Register;
+    use crate::cosem::script_table::{ScriptAction, ScriptTable};
+    use crate::{ScalerUnit, Unit};
+
+    const POWER: ObisCode = ObisCode { a: 1, b: 0, c: 1, d: 7, e: 0, f: 255 };
+    const LIMITER: ObisCode = ObisCode { a: 0, b: 0, c: 17, d: 0, e: 0, f: 255 };
+    const SCRIPTS: ObisCode = ObisCode { a: 0, b: 0, c: 10, d: 0, e: 106, f: 255 };
+
+    fn script(selector: u16) -> ScriptReference {
+        ScriptReference { script_logical_name: SCRIPTS, script_selector: selector }
+    }
+
+    fn power_limiter() -> Limiter {
+        Limiter::new(
+            LIMITER,
+            ValueDefinition { class_id: 3, logical_name: POWER, attribute_index: 2 },
+            LimiterActions { action_over_threshold: script(1), action_under_threshold: script(2) },
+        )
+        .with_threshold_normal(Data::DoubleLongUnsigned(5_000))
+        .with_threshold_emergency(Data::DoubleLongUnsigned(2_000))
+        .with_min_over_threshold_duration(60)
+        .with_min_under_threshold_duration(120)
+    }
+
+    fn updates(limiter: &mut Limiter, values: &[(u32, u32)]) -> Vec<u16> {
+        let mut fired = Vec::new();
+        for (now, value) in values {
+            limiter.update(*now, &Data::DoubleLongUnsigned(*value), &mut |script| {
+                fired.push(script.script_selector)
+            });
+        }
+        fired
+    }
+
+    #[test]
+    fn test_min_durations() {
+        let mut limiter = power_limiter();
+
+        // A short peak is tolerated.
+        assert_eq!(updates(&mut limiter, &[(0, 6_000), (30, 4_000), (90, 6_000)]), []);
+        assert_eq!(updates(&mut limiter, &[(149, 6_000)]), []);
+        assert_eq!(updates(&mut limiter, &[(150, 7_000), (200, 7_000)]), [1]);
+        assert!(limiter.over_threshold());
+        // Back under the threshold for 2 minutes.
+        assert_eq!(updates(&mut limiter, &[(300, 5_000), (400, 6_000), (500, 1_000)]), []);
+        assert_eq!(updates(&mut limiter, &[(620, 1_000)]), [2]);
+    }
+
+    #[test]
+    fn test_emergency_profile() {
+        let activation = DateTime::new(
+            Date::new(1970, 1, 1, 0xFF),
+            Time::new(Some(1), Some(0), Some(0), None),
+            None,
+            None,
+        );
+        let profile = EmergencyProfile {
+            emergency_profile_id: 7,
+            emergency_activation_time: activation,
+            emergency_duration: 3_600,
+        };
+        let mut limiter = power_limiter().with_emergency_profile(profile.clone(), vec![7]);
+
+        assert_eq!(updates(&mut limiter, &[(3_000, 3_000), (3_600, 3_000)]), []);
+        assert!(limiter.emergency_profile_active());
+        assert_eq!(limiter.get_attribute(3), Ok(Data::DoubleLongUnsigned(2_000)));
+        assert_eq!(updates(&mut limiter, &[(3_660, 3_000)]), [1]);
+        // The emergency ends: the normal threshold applies again.
+        assert_eq!(updates(&mut limiter, &[(7_200, 3_000), (7_320, 3_000)]), [2]);
+        assert_eq!(limiter.get_attribute(10), Ok(Data::Boolean(false)));
+
+        // A profile id missing from the group list is never activated.
+        let mut limiter = power_limiter().with_emergency_profile(profile, vec![1, 2]);
+        assert_eq!(updates(&mut limiter, &[(3_600, 3_000), (3_700, 3_000)]), []);
+    }
+
+    #[test]
+    fn test_disconnects_through_registry() {
+        let mut objects = ObjectRegistry::new()
+            .with_object(Register::new(
+                POWER,
+                Data::DoubleLongUnsigned(8_000),
+                ScalerUnit { scaler: 0, unit: Unit::Watt },
+            ))
+            .with_object(DisconnectControl::new(1))
+            .with_object(power_limiter())
+            .with_object(
+                ScriptTable::new(SCRIPTS)
+                    .with_script(
+                        1,
+                        vec![ScriptAction::execute(70, DISCONNECT_CONTROL, 1, Data::Integer(0))],
+                    )
+                    .with_script(
+                        2,
+                        vec![ScriptAction::execute(70, DISCONNECT_CONTROL, 2, Data::Integer(0))],
+                    ),
+            );
+        let tick = |objects: &mut ObjectRegistry, now| {
+            let mut due = Vec::new();
+            objects.tick(now, &mut |script| due.push(*script));
+            due.iter().for_each(|script| script.execute(objects).unwrap());
+        };
+
+        tick(&mut objects, 0);
+        tick(&mut objects, 60);
+        let state = |objects: &ObjectRegistry| {
+            objects.get(70, &DISCONNECT_CONTROL).unwrap().get_attribute(3)
+        };
+        assert_eq!(state(&objects), Ok(Data::Enum(ControlState::Disconnected as u8)));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let limiter =
+            power_limiter().with_emergency_profile(EmergencyProfile::default(), vec![1, 2]);
+        let mut written = Limiter::new(
+            LIMITER,
+            ValueDefinition { class_id: 1, logical_name: LIMITER, attribute_index: 2 },
+            LimiterActions { action_over_threshold: script(9), action_under_threshold: script(9) },
+        );
+        for id in [2, 4, 5, 6, 7, 8, 9, 11] {
+            written.set_attribute(id, limiter.get_attribute(id).unwrap()).unwrap();
+        }
+        assert_eq!(written, limiter);
+        assert_eq!(written.set_attribute(3, Data::Null), Err(DataAccessResult::ReadWriteDenied));
+        assert_eq!(
+            written.set_attribute(6, Data::Unsigned(1)),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+    }
+}
@@ -0,0 +1,854 @@
+//! COSEM Interface Class 72: M-Bus Client
+//!
+//! An M-Bus Client represents one wired or wireless M-Bus slave device, e.g.
+//! a gas or water meter, attached to the M-Bus port of the electricity meter.
+//! Channel x of the meter has the M-Bus Client 0-x:24.1.0.255, and the
+//! values captured from the device are stored in the Extended Registers
+//! 0-x:24.2.y.255 (class 4), with their capture time.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.1.24.1.0.255)
+//! - Attribute 2: `mbus_port_reference` - Logical name of the M-Bus port setup
+//! - Attribute 3: `capture_definition` - Array of `{data_information_block, value_information_block}`
+//! - Attribute 4: `capture_period` - Seconds between captures, 0 if not periodic
+//! - Attribute 5: `primary_address` - Primary address of the device, 0 if not installed
+//! - Attribute 6: `identification_number` - Identification number (BCD)
+//! - Attribute 7: `manufacturer_id` - Manufacturer id
+//! - Attribute 8: `version` - Version of the device
+//! - Attribute 9: `device_type` - Medium, e.g. 3 for gas and 7 for water
+//! - Attribute 10: `access_number` - Access number of the last response
+//! - Attribute 11: `status` - Status byte of the last response
+//! - Attribute 12: `alarm` - Error bits of the status byte since the last reset
+//! - Attribute 13: `configuration` - Configuration of the device
+//! - Attribute 14: `encryption_key_status` - Whether the key is set, transferred or in use
+//!
+//! Attributes 10-12 and 14 are read-only.
+//!
+//! ## Methods
+//! - Method 1: `slave_install(unsigned)` - Installs the device at a primary address
+//! - Method 2: `slave_deinstall(integer)` - Deinstalls the device
+//! - Method 3: `capture(integer)` - Reads the device into the value registers
+//! - Method 4: `reset_alarm(integer)` - Clears `alarm`
+//! - Method 5: `synchronize_clock(integer)` - Sends the time of the meter to the device
+//! - Method 6: `data_send(array)` - Sends `{dib, vib, data}` records to the device
+//! - Method 7: `set_encryption_key(octet-string)` - Sets the key decrypting the device data
+//! - Method 8: `transfer_key(octet-string)` - Sends the key, encrypted with the device key
+//!
+//! ## Bus access
+//!
+//! Talking to the devices (wired frames, wireless telegrams, decoding the
+//! data records) is specific to the M-Bus hardware and is delegated to an
+//! [`MBusPort`]; without one, the methods reaching the device fail with a
+//! hardware fault. `capture` stores the data records selected by
+//! `capture_definition`, in order, in the value registers 0-x:24.2.1.255,
+//! 0-x:24.2.2.255, and so on; an empty capture definition selects every
+//! record. Outside a registry, `capture` only updates the access number,
+//! status and alarm. Periodic captures are left to the caller, e.g. a Single
+//! Action Schedule invoking `capture` every `capture_period`.
+//! [`ObjectRegistry::tick`] stamps the captures and clock synchronisations;
+//! times are local seconds since 1970-01-01.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::extended_register::ExtendedRegister;
+//! use dlms_cosem::cosem::mbus_client::{
+//!     MBusClient, MBusCommand, MBusIdentification, MBusPort, MBusRecord, MBusResponse,
+//! };
+//! use dlms_cosem::cosem::{CosemObject, ObjectRegistry};
+//! use dlms_cosem::{Data, Date, DateTime, ObisCode, ScalerUnit, Time, Unit};
+//!
+//! /// A gas meter reading 1234.5 m³.
+//! #[derive(Debug)]
+//! struct GasMeter;
+//!
+//! impl MBusPort for GasMeter {
+//!     fn install(&mut self, _primary_address: u8) -> Option<MBusIdentification> {
+//!         Some(MBusIdentification {
+//!             identification_number: 0x1234_5678,
+//!             manufacturer_id: 0x2C2D,
+//!             version: 1,
+//!             device_type: 3,
+//!         })
+//!     }
+//!
+//!     fn read(&mut self, _primary_address: u8, _key: Option<&[u8]>) -> Option<MBusResponse> {
+//!         let volume = MBusRecord { dib: vec![0x0C], vib: vec![0x13], value: Data::DoubleLongUnsigned(1_234_500) };
+//!         Some(MBusResponse { access_number: 1, status: 0, encrypted: false, records: vec![volume] })
+//!     }
+//!
+//!     fn send(&mut self, _primary_address: u8, _command: MBusCommand) -> bool {
+//!         true
+//!     }
+//! }
+//!
+//! let client = ObisCode::new(0, 1, 24, 1, 0, 255);
+//! let volume = ObisCode::new(0, 1, 24, 2, 1, 255);
+//! let mut objects = ObjectRegistry::new()
+//!     .with_object(MBusClient::new(client).with_port(Box::new(GasMeter)))
+//!     .with_object(ExtendedRegister::new(
+//!         volume,
+//!         Data::DoubleLongUnsigned(0),
+//!         ScalerUnit { scaler: -3, unit: Unit::CubicMeter },
+//!         Data::Null,
+//!         DateTime::new(Date::new(0xFFFF, 0xFF, 0xFF, 0xFF), Time::new(None, None, None, None), None, None),
+//!     ));
+//!
+//! objects.invoke_method(72, &client, 1, Some(Data::Unsigned(5))).unwrap();
+//! objects.invoke_method(72, &client, 3, Some(Data::Integer(0))).unwrap();
+//! assert_eq!(objects.get(72, &client).unwrap().get_attribute(5), Ok(Data::Unsigned(5)));
+//! assert_eq!(objects.get(4, &volume).unwrap().get_attribute(2), Ok(Data::DoubleLongUnsigned(1_234_500)));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::action::ActionResult;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::cosem::push_setup::date_time_at;
+use crate::cosem::script_table::ScriptReference;
+use crate::cosem::{CosemObject, ObjectRegistry};
+use crate::data::{Data, DateTime};
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Extended Register interface class id, of the value registers.
+const EXTENDED_REGISTER_CLASS_ID: u16 = 4;
+
+/// Error bits of the M-Bus status byte: application errors, power low,
+/// permanent and temporary errors.
+const ALARM_BITS: u8 = 0x1F;
+
+/// Highest primary address of a device; 251-255 are reserved.
+const MAX_PRIMARY_ADDRESS: u8 = 250;
+
+/// Returns the value register y (1-based) of the M-Bus channel of `client`.
+pub fn value_register(client: &ObisCode, y: u8) -> ObisCode {
+    ObisCode::new(0, client.b, 24, 2, y, 255)
+}
+
+/// Whether the encryption key of the device is set and transferred
+/// (attribute 14).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum EncryptionKeyStatus {
+    /// No key is set
+    NoEncryptionKey = 0,
+    /// The key is set in the meter only
+    EncryptionKeySet = 1,
+    /// The key is transferred to the device only
+    EncryptionKeyTransferred = 2,
+    /// The key is set in the meter and transferred to the device
+    EncryptionKeySetAndTransferred = 3,
+    /// The device sends data encrypted with the key
+    EncryptionKeyInUse = 4,
+}
+
+impl EncryptionKeyStatus {
+    /// Converts an encryption_key_status enum value.
+    ///
+    /// Returns Err if value is not in range 0-4.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::NoEncryptionKey),
+            1 => Ok(Self::EncryptionKeySet),
+            2 => Ok(Self::EncryptionKeyTransferred),
+            3 => Ok(Self::EncryptionKeySetAndTransferred),
+            4 => Ok(Self::EncryptionKeyInUse),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Element of the capture definition (attribute 3): the data and value
+/// information blocks identifying a data record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CaptureDefinitionElement {
+    /// Data information block (DIF and DIFEs)
+    pub data_information_block: Vec<u8>,
+    /// Value information block (VIF and VIFEs)
+    pub value_information_block: Vec<u8>,
+}
+
+impl CaptureDefinitionElement {
+    /// Returns whether a data record has these information blocks.
+    pub fn matches(&self, record: &MBusRecord) -> bool {
+        self.data_information_block == record.dib && self.value_information_block == record.vib
+    }
+
+    fn to_data(&self) -> Data {
+        Data::Structure(alloc::vec![
+            Data::OctetString(self.data_information_block.clone()),
+            Data::OctetString(self.value_information_block.clone()),
+        ])
+    }
+
+    fn from_data(data: &Data) -> Result<Self, DataAccessResult> {
+        match data {
+            Data::Structure(fields) => match fields.as_slice() {
+                [Data::OctetString(dib), Data::OctetString(vib)] => Ok(Self {
+                    data_information_block: dib.clone(),
+                    value_information_block: vib.clone(),
+                }),
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Identification of a device, read when it is installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MBusIdentification {
+    /// Identification number (BCD)
+    pub identification_number: u32,
+    /// Manufacturer id
+    pub manufacturer_id: u16,
+    /// Version of the device
+    pub version: u8,
+    /// Medium
+    pub device_type: u8,
+}
+
+/// A data record of a device, decoded by the [`MBusPort`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MBusRecord {
+    /// Data information block
+    pub dib: Vec<u8>,
+    /// Value information block
+    pub vib: Vec<u8>,
+    /// Value of the record
+    pub value: Data,
+}
+
+/// Response of a device to a read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MBusResponse {
+    /// Access number of the response
+    pub access_number: u8,
+    /// Status byte of the response
+    pub status: u8,
+    /// Whether the records were encrypted with the key of the device
+    pub encrypted: bool,
+    /// Decoded data records
+    pub records: Vec<MBusRecord>,
+}
+
+/// Command sent to a device by [`MBusPort::send`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MBusCommand {
+    /// Frees the primary address of the device
+    Deinstall,
+    /// Clears the error state of the device
+    ResetAlarm,
+    /// Sets the clock of the device
+    SynchronizeClock(DateTime),
+    /// Writes data records to the device
+    DataSend(Vec<MBusRecord>),
+    /// Sets the key of the device, encrypted with its current key
+    TransferKey(Vec<u8>),
+}
+
+/// Access to the devices on the M-Bus port of the meter.
+pub trait MBusPort: fmt::Debug + Send {
+    /// Installs the device waiting for installation at `primary_address`,
+    /// or returns `None` if none answers.
+    fn install(&mut self, primary_address: u8) -> Option<MBusIdentification>;
+
+    /// Reads the data records of a device, decrypted with `key` if set, or
+    /// returns `None` if it does not answer.
+    fn read(&mut self, primary_address: u8, key: Option<&[u8]>) -> Option<MBusResponse>;
+
+    /// Sends a command to a device; returns whether it acknowledged it.
+    fn send(&mut self, primary_address: u8, command: MBusCommand) -> bool;
+}
+
+/// M-Bus Client object - COSEM Interface Class 72
+///
+/// Reference: Blue Book 4.8.3
+#[derive(Debug)]
+pub struct MBusClient {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Logical name of the M-Bus port setup
+    pub mbus_port_reference: ObisCode,
+    /// Attribute 3: Data records captured
+    pub capture_definition: Vec<CaptureDefinitionElement>,
+    /// Attribute 4: Seconds between captures, 0 if not periodic
+    pub capture_period: u32,
+    /// Attribute 5: Primary address, 0 if not installed
+    pub primary_address: u8,
+    /// Attribute 6: Identification number
+    pub identification_number: u32,
+    /// Attribute 7: Manufacturer id
+    pub manufacturer_id: u16,
+    /// Attribute 8: Version
+    pub version: u8,
+    /// Attribute 9: Device type
+    pub device_type: u8,
+    /// Attribute 10: Access number of the last response
+    pub access_number: u8,
+    /// Attribute 11: Status byte of the last response
+    pub status: u8,
+    /// Attribute 12: Error bits since the last reset_alarm
+    pub alarm: u8,
+    /// Attribute 13: Configuration
+    pub configuration: u16,
+    /// Attribute 14: Encryption key status
+    pub encryption_key_status: EncryptionKeyStatus,
+    encryption_key: Vec<u8>,
+    port: Option<Box<dyn MBusPort>>,
+    /// Time of the last tick
+    now: Option<u32>,
+}
+
+impl MBusClient {
+    /// Creates a client without device installed, port or capture
+    /// definition, on the M-Bus port setup 0-0:24.6.0.255.
+    pub fn new(logical_name: ObisCode) -> Self {
+        Self {
+            logical_name,
+            mbus_port_reference: ObisCode::new(0, 0, 24, 6, 0, 255),
+            capture_definition: Vec::new(),
+            capture_period: 0,
+            primary_address: 0,
+            identification_number: 0,
+            manufacturer_id: 0,
+            version: 0,
+            device_type: 0,
+            access_number: 0,
+            status: 0,
+            alarm: 0,
+            configuration: 0,
+            encryption_key_status: EncryptionKeyStatus::NoEncryptionKey,
+            encryption_key: Vec::new(),
+            port: None,
+            now: None,
+        }
+    }
+
+    /// Sets the port reaching the device (builder style).
+    pub fn with_port(mut self, port: Box<dyn MBusPort>) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Sets the data records captured (builder style).
+    pub fn with_capture_definition(mut self, definition: Vec<CaptureDefinitionElement>) -> Self {
+        self.capture_definition = definition;
+        self
+    }
+
+    /// Sets the seconds between captures (builder style).
+    pub fn with_capture_period(mut self, seconds: u32) -> Self {
+        self.capture_period = seconds;
+        self
+    }
+
+    /// Returns whether a device is installed.
+    pub fn is_installed(&self) -> bool {
+        self.primary_address != 0
+    }
+
+    /// Installs the device waiting for installation at `primary_address`
+    /// and stores its identification.
+    ///
+    /// Fails with ReadWriteDenied if a device is already installed.
+    pub fn slave_install(&mut self, primary_address: u8) -> Result<(), ActionResult> {
+        if !(1..=MAX_PRIMARY_ADDRESS).contains(&primary_address) {
+            return Err(ActionResult::OtherReason);
+        }
+        if self.is_installed() {
+            return Err(ActionResult::ReadWriteDenied);
+        }
+        let port = self.port.as_mut().ok_or(ActionResult::HardwareFault)?;
+        let identification = port.install(primary_address).ok_or(ActionResult::TemporaryFailure)?;
+        self.primary_address = primary_address;
+        self.identification_number = identification.identification_number;
+        self.manufacturer_id = identification.manufacturer_id;
+        self.version = identification.version;
+        self.device_type = identification.device_type;
+        Ok(())
+    }
+
+    /// Deinstalls the device, clearing its identification and key.
+    pub fn slave_deinstall(&mut self) -> Result<(), ActionResult> {
+        self.send(MBusCommand::Deinstall)?;
+        self.primary_address = 0;
+        self.identification_number = 0;
+        self.manufacturer_id = 0;
+        self.version = 0;
+        self.device_type = 0;
+        self.access_number = 0;
+        self.status = 0;
+        self.encryption_key.clear();
+        self.encryption_key_status = EncryptionKeyStatus::NoEncryptionKey;
+        Ok(())
+    }
+
+    /// Reads the device and returns the records selected by the capture
+    /// definition, in order.
+    ///
+    /// Updates the access number, the status and the alarm, and marks the
+    /// key in use once the device sends encrypted data with a key set and
+    /// transferred.
+    pub fn read_records(&mut self) -> Result<Vec<MBusRecord>, ActionResult> {
+        if !self.is_installed() {
+            return Err(ActionResult::ReadWriteDenied);
+        }
+        let key = (!self.encryption_key.is_empty()).then_some(self.encryption_key.as_slice());
+        let port = self.port.as_mut().ok_or(ActionResult::HardwareFault)?;
+        let response =
+            port.read(self.primary_address, key).ok_or(ActionResult::TemporaryFailure)?;
+        self.access_number = response.access_number;
+        self.status = response.status;
+        self.alarm |= response.status & ALARM_BITS;
+        if response.encrypted
+            && self.encryption_key_status == EncryptionKeyStatus::EncryptionKeySetAndTransferred
+        {
+            self.encryption_key_status = EncryptionKeyStatus::EncryptionKeyInUse;
+        }
+        if self.capture_definition.is_empty() {
+            return Ok(response.records);
+        }
+        Ok(self
+            .capture_definition
+            .iter()
+            .filter_map(|element| response.records.iter().find(|record| element.matches(record)))
+            .cloned()
+            .collect())
+    }
+
+    /// Reads the device into the value registers of `objects`, stamped with
+    /// the time of the last tick; returns the number of values stored.
+    ///
+    /// Records without a value register, or with a value it rejects, are
+    /// skipped.
+    pub fn capture(&mut self, objects: &mut ObjectRegistry) -> Result<usize, ActionResult> {
+        let records = self.read_records()?;
+        let capture_time = self.now.map(date_time_at);
+        let mut captured = 0;
+        for (y, record) in (1..=u8::MAX).zip(records) {
+            let register = value_register(&self.logical_name, y);
+            let Some(register) = objects.get_mut(EXTENDED_REGISTER_CLASS_ID, &register) else {
+                continue;
+            };
+            if register.set_attribute(2, record.value).is_ok() {
+                if let Some(capture_time) = &capture_time {
+                    let _ = register.set_attribute(5, Data::DateTime(capture_time.clone()));
+                }
+                captured += 1;
+            }
+        }
+        Ok(captured)
+    }
+
+    /// Sets the key decrypting the data of the device; an empty key removes
+    /// it.
+    pub fn set_encryption_key(&mut self, key: &[u8]) -> Result<(), ActionResult> {
+        if !key.is_empty() && key.len() != 16 {
+            return Err(ActionResult::OtherReason);
+        }
+        self.encryption_key = key.to_vec();
+        self.encryption_key_status = match (key.is_empty(), self.encryption_key_status) {
+            (true, _) => EncryptionKeyStatus::NoEncryptionKey,
+            (false, EncryptionKeyStatus::EncryptionKeyTransferred) => {
+                EncryptionKeyStatus::EncryptionKeySetAndTransferred
+            }
+            (false, _) => EncryptionKeyStatus::EncryptionKeySet,
+        };
+        Ok(())
+    }
+
+    /// Sends a key to the device, encrypted by the caller with the current
+    /// key of the device.
+    pub fn transfer_key(&mut self, encrypted_key: &[u8]) -> Result<(), ActionResult> {
+        self.send(MBusCommand::TransferKey(encrypted_key.to_vec()))?;
+        self.encryption_key_status = match self.encryption_key_status {
+            EncryptionKeyStatus::EncryptionKeySet => {
+                EncryptionKeyStatus::EncryptionKeySetAndTransferred
+            }
+            _ => EncryptionKeyStatus::EncryptionKeyTransferred,
+        };
+        Ok(())
+    }
+
+    /// Sends a command to the installed device.
+    fn send(&mut self, command: MBusCommand) -> Result<(), ActionResult> {
+        if !self.is_installed() {
+            return Err(ActionResult::ReadWriteDenied);
+        }
+        let port = self.port.as_mut().ok_or(ActionResult::HardwareFault)?;
+        if port.send(self.primary_address, command) {
+            Ok(())
+        } else {
+            Err(ActionResult::TemporaryFailure)
+        }
+    }
+}
+
+fn records_from(data: &Data) -> Result<Vec<MBusRecord>, ActionResult> {
+    let Data::Structure(records) = data else {
+        return Err(ActionResult::TypeUnmatched);
+    };
+    records
+        .iter()
+        .map(|record| match record {
+            Data::Structure(fields) => match fields.as_slice() {
+                [Data::OctetString(dib), Data::OctetString(vib), value] => {
+                    Ok(MBusRecord { dib: dib.clone(), vib: vib.clone(), value: value.clone() })
+                }
+                _ => Err(ActionResult::TypeUnmatched),
+            },
+            _ => Err(ActionResult::TypeUnmatched),
+        })
+        .collect()
+}
+
+impl CosemObject for MBusClient {
+    fn class_id(&self) -> u16 {
+        72
+    }
+
+    fn version(&self) -> u8 {
+        1
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::OctetString(self.mbus_port_reference.encode().to_vec())),
+            3 => Ok(Data::Structure(
+                self.capture_definition.iter().map(CaptureDefinitionElement::to_data).collect(),
+            )),
+            4 => Ok(Data::DoubleLongUnsigned(self.capture_period)),
+            5 => Ok(Data::Unsigned(self.primary_address)),
+            6 => Ok(Data::DoubleLongUnsigned(self.identification_number)),
+            7 => Ok(Data::LongUnsigned(self.manufacturer_id)),
+            8 => Ok(Data::Unsigned(self.version)),
+            9 => Ok(Data::Unsigned(self.device_type)),
+            10 => Ok(Data::Unsigned(self.access_number)),
+            11 => Ok(Data::Unsigned(self.status)),
+            12 => Ok(Data::Unsigned(self.alarm)),
+            13 => Ok(Data::LongUnsigned(self.configuration)),
+            14 => Ok(Data::Enum(self.encryption_key_status as u8)),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (1 | 10 | 11 | 12 | 14, _) => Err(DataAccessResult::ReadWriteDenied),
+            (2, Data::OctetString(name)) => {
+                self.mbus_port_reference = ObisCode::try_from(name.as_slice())
+                    .map_err(|_| DataAccessResult::TypeUnmatched)?;
+                Ok(())
+            }
+            (3, Data::Structure(elements)) => {
+                self.capture_definition = elements
+                    .iter()
+                    .map(CaptureDefinitionElement::from_data)
+                    .collect::<Result<_, _>>()?;
+                Ok(())
+            }
+            (4, Data::DoubleLongUnsigned(period)) => {
+                self.capture_period = period;
+                Ok(())
+            }
+            (5, Data::Unsigned(address)) if address > MAX_PRIMARY_ADDRESS => {
+                Err(DataAccessResult::OtherReason)
+            }
+            (5, Data::Unsigned(address)) => {
+                self.primary_address = address;
+                Ok(())
+            }
+            (6, Data::DoubleLongUnsigned(number)) => {
+                self.identification_number = number;
+                Ok(())
+            }
+            (7, Data::LongUnsigned(manufacturer_id)) => {
+                self.manufacturer_id = manufacturer_id;
+                Ok(())
+            }
+            (8, Data::Unsigned(version)) => {
+                self.version = version;
+                Ok(())
+            }
+            (9, Data::Unsigned(device_type)) => {
+                self.device_type = device_type;
+                Ok(())
+            }
+            (13, Data::LongUnsigned(configuration)) => {
+                self.configuration = configuration;
+                Ok(())
+            }
+            (2..=9 | 13, _) => Err(DataAccessResult::TypeUnmatched),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    /// Outside a registry, `capture` stores no values.
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match (method_id, params) {
+            (1, Some(Data::Unsigned(primary_address))) => self.slave_install(primary_address),
+            (2, Some(Data::Integer(_))) => self.slave_deinstall(),
+            (3, Some(Data::Integer(_))) => self.read_records().map(drop),
+            (4, Some(Data::Integer(_))) => {
+                self.alarm = 0;
+                Ok(())
+            }
+            (5, Some(Data::Integer(_))) => {
+                let now = self.now.ok_or(ActionResult::TemporaryFailure)?;
+                self.send(MBusCommand::SynchronizeClock(date_time_at(now)))
+            }
+            (6, Some(records)) => self.send(MBusCommand::DataSend(records_from(&records)?)),
+            (7, Some(Data::OctetString(key))) => self.set_encryption_key(&key),
+            (8, Some(Data::OctetString(key))) => self.transfer_key(&key),
+            (1..=8, _) => Err(ActionResult::TypeUnmatched),
+            _ => Err(ActionResult::ObjectUndefined),
+        }
+        .map(|()| None)
+    }
+
+    /// Captures into the value registers of the registry.
+    fn invoke_method_with_objects(
+        &mut self,
+        objects: &mut ObjectRegistry,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match (method_id, params) {
+            (3, Some(Data::Integer(_))) => self.capture(objects).map(|_| None),
+            (method_id, params) => self.invoke_method(method_id, params),
+        }
+    }
+
+    fn tick(
+        &mut self,
+        now: u32,
+        _objects: &ObjectRegistry,
+        _executor: &mut dyn FnMut(&ScriptReference),
+    ) {
+        self.now = Some(now);
+    }
+
+    /// Persists the configuration and the device, then the last response,
+    /// the key status and the key, in plaintext: the snapshot must be stored
+    /// securely.
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        let mut state =
+            ObjectState::from_attributes(self, [2, 3, 4, 5, 6, 7, 8, 9, 13, 10, 11, 12, 14])
+                .ok()?;
+        state.attributes.push(Data::OctetString(self.encryption_key.clone()));
+        Some(state)
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        match state.restore_attributes(self, [2, 3, 4, 5, 6, 7, 8, 9, 13])?.as_slice() {
+            [
+                Data::Unsigned(access_number),
+                Data::Unsigned(status),
+                Data::Unsigned(alarm),
+                Data::Enum(key_status),
+                Data::OctetString(key),
+            ] => {
+                self.access_number = *access_number;
+                self.status = *status;
+                self.alarm = *alarm;
+                self.encryption_key_status = EncryptionKeyStatus::from_u8(*key_status)?;
+                self.encryption_key = key.clone();
+                Ok(())
+            }
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::activity_calendar::NOT_SCHEDULED;
+    use crate::cosem::extended_register::ExtendedRegister;
+    use crate::unit::{ScalerUnit, Unit};
+    use alloc::vec;
+
+    const CLIENT: ObisCode = ObisCode { a: 0, b: 1, c: 24, d: 1, e: 0, f: 255 };
+
+    /// A water meter at primary address 7, recording the commands it gets.
+    #[derive(Debug, Default)]
+    struct WaterMeter {
+        key: Vec<u8>,
+        commands: Vec<MBusCommand>,
+    }
+
+    impl MBusPort for WaterMeter {
+        fn install(&mut self, primary_address: u8) -> Option<MBusIdentification> {
+            (primary_address == 7).then_some(MBusIdentification {
+                identification_number: 0x8765_4321,
+                manufacturer_id: 0x4024,
+                version: 2,
+                device_type: 7,
+            })
+        }
+
+        fn read(&mut self, primary_address: u8, key: Option<&[u8]>) -> Option<MBusResponse> {
+            let record = |vib: u8, value| MBusRecord { dib: vec![0x0C], vib: vec![vib], value };
+            (primary_address == 7).then(|| MBusResponse {
+                access_number: 42,
+                status: 0x04,
+                encrypted: !self.key.is_empty() && key == Some(self.key.as_slice()),
+                records: vec![
+                    record(0x6D, Data::DoubleLongUnsigned(0)),
+                    record(0x13, Data::DoubleLongUnsigned(56_789)),
+                    record(0x3B, Data::DoubleLongUnsigned(12)),
+                ],
+            })
+        }
+
+        fn send(&mut self, primary_address: u8, command: MBusCommand) -> bool {
+            if let MBusCommand::TransferKey(key) = &command {
+                self.key = key.clone();
+            }
+            self.commands.push(command);
+            primary_address == 7
+        }
+    }
+
+    fn element(vib: u8) -> CaptureDefinitionElement {
+        CaptureDefinitionElement {
+            data_information_block: vec![0x0C],
+            value_information_block: vec![vib],
+        }
+    }
+
+    fn register(y: u8) -> ExtendedRegister {
+        ExtendedRegister::new(
+            value_register(&CLIENT, y),
+            Data::DoubleLongUnsigned(0),
+            ScalerUnit { scaler: -3, unit: Unit::CubicMeter },
+            Data::Null,
+            NOT_SCHEDULED,
+        )
+    }
+
+    #[test]
+    fn test_install_and_deinstall() {
+        let mut client = MBusClient::new(CLIENT);
+        assert_eq!(
+            client.invoke_method(1, Some(Data::Unsigned(7))),
+            Err(ActionResult::HardwareFault)
+        );
+
+        let mut client = client.with_port(Box::<WaterMeter>::default());
+        assert_eq!(
+            client.invoke_method(1, Some(Data::Unsigned(251))),
+            Err(ActionResult::OtherReason)
+        );
+        assert_eq!(
+            client.invoke_method(1, Some(Data::Unsigned(8))),
+            Err(ActionResult::TemporaryFailure)
+        );
+        assert_eq!(
+            client.invoke_method(3, Some(Data::Integer(0))),
+            Err(ActionResult::ReadWriteDenied)
+        );
+
+        assert_eq!(client.invoke_method(1, Some(Data::Unsigned(7))), Ok(None));
+        assert_eq!(client.get_attribute(5), Ok(Data::Unsigned(7)));
+        assert_eq!(client.get_attribute(6), Ok(Data::DoubleLongUnsigned(0x8765_4321)));
+        assert_eq!(client.get_attribute(7), Ok(Data::LongUnsigned(0x4024)));
+        assert_eq!(client.get_attribute(9), Ok(Data::Unsigned(7)));
+        assert_eq!(
+            client.invoke_method(1, Some(Data::Unsigned(7))),
+            Err(ActionResult::ReadWriteDenied)
+        );
+
+        assert_eq!(client.invoke_method(3, Some(Data::Integer(0))), Ok(None));
+        assert_eq!(client.get_attribute(10), Ok(Data::Unsigned(42)));
+        assert_eq!(client.get_attribute(12), Ok(Data::Unsigned(0x04)));
+        assert_eq!(client.invoke_method(4, Some(Data::Integer(0))), Ok(None));
+        assert_eq!(client.get_attribute(12), Ok(Data::Unsigned(0)));
+        assert_eq!(
+            client.set_attribute(12, Data::Unsigned(1)),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+
+        assert_eq!(client.invoke_method(2, Some(Data::Integer(0))), Ok(None));
+        assert!(!client.is_installed());
+        assert_eq!(client.get_attribute(6), Ok(Data::DoubleLongUnsigned(0)));
+    }
+
+    #[test]
+    fn test_capture_into_value_registers() {
+        let mut objects = ObjectRegistry::new()
+            .with_object(
+                MBusClient::new(CLIENT)
+                    .with_port(Box::<WaterMeter>::default())
+                    .with_capture_definition(vec![element(0x13), element(0x3B)]),
+            )
+            .with_object(register(1))
+            .with_object(register(2));
+        objects.invoke_method(72, &CLIENT, 1, Some(Data::Unsigned(7))).unwrap();
+        objects.tick(1_738_368_000, &mut |_| {});
+
+        assert_eq!(objects.invoke_method(72, &CLIENT, 3, Some(Data::Integer(0))), Ok(None));
+        let volume = objects.get(4, &value_register(&CLIENT, 1)).unwrap();
+        assert_eq!(volume.get_attribute(2), Ok(Data::DoubleLongUnsigned(56_789)));
+        assert_eq!(volume.get_attribute(5), Ok(Data::DateTime(date_time_at(1_738_368_000))));
+        let flow = objects.get(4, &value_register(&CLIENT, 2)).unwrap();
+        assert_eq!(flow.get_attribute(2), Ok(Data::DoubleLongUnsigned(12)));
+
+        let time = objects.invoke_method(72, &CLIENT, 5, Some(Data::Integer(0)));
+        assert_eq!(time, Ok(None));
+        let records = Data::Structure(vec![Data::Structure(vec![
+            Data::OctetString(vec![0x01]),
+            Data::OctetString(vec![0xFD, 0x1A]),
+            Data::Unsigned(1),
+        ])]);
+        assert_eq!(objects.invoke_method(72, &CLIENT, 6, Some(records)), Ok(None));
+        assert_eq!(
+            objects.invoke_method(72, &CLIENT, 6, Some(Data::Unsigned(1))),
+            Err(ActionResult::TypeUnmatched)
+        );
+    }
+
+    #[test]
+    fn test_encryption_key_status() {
+        let mut client = MBusClient::new(CLIENT).with_port(Box::<WaterMeter>::default());
+        client.slave_install(7).unwrap();
+        let key_status = |client: &MBusClient| client.get_attribute(14).unwrap();
+
+        assert_eq!(
+            client.invoke_method(7, Some(Data::OctetString(vec![0x11; 8]))),
+            Err(ActionResult::OtherReason)
+        );
+        assert_eq!(client.invoke_method(8, Some(Data::OctetString(vec![0x22; 16]))), Ok(None));
+        assert_eq!(key_status(&client), Data::Enum(2));
+        assert_eq!(client.invoke_method(7, Some(Data::OctetString(vec![0x22; 16]))), Ok(None));
+        assert_eq!(key_status(&client), Data::Enum(3));
+        client.read_records().unwrap();
+        assert_eq!(key_status(&client), Data::Enum(4));
+
+        assert_eq!(client.invoke_method(7, Some(Data::OctetString(vec![]))), Ok(None));
+        assert_eq!(key_status(&client), Data::Enum(0));
+        assert_eq!(client.invoke_method(7, Some(Data::OctetString(vec![0x33; 16]))), Ok(None));
+        assert_eq!(key_status(&client), Data::Enum(1));
+        assert_eq!(client.invoke_method(8, Some(Data::OctetString(vec![0x33; 16]))), Ok(None));
+        assert_eq!(key_status(&client), Data::Enum(3));
+
+        client.slave_deinstall().unwrap();
+        assert_eq!(key_status(&client), Data::Enum(0));
+    }
+}
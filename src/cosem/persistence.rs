@@ -0,0 +1,553 @@
+//! Persistence of the object model
+//!
+//! Snapshots the state of the objects in an [`ObjectRegistry`] so a meter or
+//! simulator survives restarts: register values, profile buffers, clock
+//! settings and internal fields that are not attributes (such as
+//! `ProfileGeneric::executed_time` and the preset time of a [`Clock`]).
+//!
+//! The snapshot is a sequence of A-XDR encoded [`Record`]s. Each object is
+//! stored as one [`Record::Object`] followed by one [`Record::Entry`] per
+//! buffer entry, so profile buffers are not limited by the length of a single
+//! structure. Entries captured afterwards can be appended to the snapshot as
+//! [`Record::AppendedEntry`] without rewriting the buffer (see [`capture`]);
+//! restoring replays them as captures.
+//!
+//! Objects are restored into an existing registry: the configuration (which
+//! objects exist, their logical names) comes from the firmware or device
+//! description, the snapshot only carries their state. Objects whose class
+//! does not implement [`CosemObject::save_state`] are not persisted.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::data::DataObject;
+//! use dlms_cosem::cosem::{CaptureObjectDefinition, CosemObject, ObjectRegistry, ProfileGeneric};
+//! use dlms_cosem::cosem::persistence;
+//! use dlms_cosem::{Data, ObisCode};
+//!
+//! let counter = ObisCode::new(0, 0, 96, 15, 0, 255);
+//! let load_profile = ObisCode::new(1, 0, 99, 1, 0, 255);
+//! let objects = || {
+//!     ObjectRegistry::new().with_object(DataObject::new(counter, Data::LongUnsigned(1))).with_object(
+//!         ProfileGeneric::with_fifo(
+//!             load_profile,
+//!             vec![CaptureObjectDefinition { class_id: 1, logical_name: counter, attribute_index: 2, data_index: 0 }],
+//!             900,
+//!             96,
+//!         ),
+//!     )
+//! };
+//!
+//! let mut meter = objects();
+//! let mut snapshot = persistence::snapshot(&meter);
+//! meter.get_mut(1, &counter).unwrap().set_attribute(2, Data::LongUnsigned(2)).unwrap();
+//! snapshot.extend(persistence::capture(&mut meter, &load_profile).unwrap());
+//!
+//! // After a restart
+//! let mut restored = objects();
+//! persistence::restore(&mut restored, &snapshot).unwrap();
+//! let buffer = restored.get(7, &load_profile).unwrap().get_attribute(2).unwrap();
+//! assert_eq!(buffer, Data::Structure(vec![Data::Structure(vec![Data::LongUnsigned(2)])]));
+//! ```
+//!
+//! [`Clock`]: crate::cosem::clock::Clock
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::cosem::profile_generic::read_capture_object;
+use crate::cosem::{CaptureObjectDefinition, CosemObject, ObjectRegistry};
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// ProfileGeneric interface class id
+const PROFILE_GENERIC_CLASS_ID: u16 = 7;
+
+/// ProfileGeneric attribute 3: capture_objects
+const CAPTURE_OBJECTS_ATTRIBUTE: i8 = 3;
+
+const OBJECT_RECORD: u8 = 0;
+const ENTRY_RECORD: u8 = 1;
+const APPENDED_ENTRY_RECORD: u8 = 2;
+const INVOCATION_COUNTER_RECORD: u8 = 3;
+
+/// Persisted state of one object.
+///
+/// `attributes` holds class-specific values (attributes and internal
+/// fields) in an order defined by the class; `entries` holds the buffer of a
+/// profile.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ObjectState {
+    /// Class-specific state values
+    pub attributes: Vec<Data>,
+    /// Buffer entries, in buffer order
+    pub entries: Vec<Vec<Data>>,
+}
+
+impl ObjectState {
+    /// Reads the state from attributes of an object.
+    pub fn from_attributes(
+        object: &(impl CosemObject + ?Sized),
+        attribute_ids: impl IntoIterator<Item = i8>,
+    ) -> Result<Self, DataAccessResult> {
+        let attributes = attribute_ids
+            .into_iter()
+            .map(|id| object.get_attribute(id))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { attributes, entries: Vec::new() })
+    }
+
+    /// Writes the state back to attributes of an object, in the order of
+    /// [`from_attributes`](Self::from_attributes).
+    ///
+    /// Returns the state values following the attributes.
+    pub fn restore_attributes(
+        self,
+        object: &mut (impl CosemObject + ?Sized),
+        attribute_ids: impl IntoIterator<Item = i8>,
+    ) -> Result<Vec<Data>, DataAccessResult> {
+        let mut values = self.attributes.into_iter();
+        for id in attribute_ids {
+            let value = values.next().ok_or(DataAccessResult::TypeUnmatched)?;
+            object.set_attribute(id, value)?;
+        }
+        Ok(values.collect())
+    }
+}
+
+/// One record of a snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Record {
+    /// State of an object; followed by the [`Record::Entry`]s of its buffer
+    Object {
+        /// Class id of the object
+        class_id: u16,
+        /// Logical name of the object
+        logical_name: ObisCode,
+        /// Class-specific state values ([`ObjectState::attributes`])
+        attributes: Vec<Data>,
+    },
+    /// Buffer entry of the preceding [`Record::Object`]
+    Entry(Vec<Data>),
+    /// Entry captured after the snapshot, replayed as a capture
+    AppendedEntry {
+        /// Class id of the profile
+        class_id: u16,
+        /// Logical name of the profile
+        logical_name: ObisCode,
+        /// Captured entry
+        entry: Vec<Data>,
+    },
+    /// Invocation counter of the server (`None`) or of a client system title
+    InvocationCounter {
+        /// Client system title, `None` for the server's own counter
+        system_title: Option<[u8; 8]>,
+        /// Counter value
+        value: u32,
+    },
+}
+
+impl Record {
+    /// Encodes the record as A-XDR structure.
+    pub fn encode(&self) -> Vec<u8> {
+        let name = |logical_name: &ObisCode| Data::OctetString(logical_name.encode().to_vec());
+        let fields = match self {
+            Record::Object { class_id, logical_name, attributes } => vec![
+                Data::Unsigned(OBJECT_RECORD),
+                Data::LongUnsigned(*class_id),
+                name(logical_name),
+                Data::Structure(attributes.clone()),
+            ],
+            Record::Entry(entry) => {
+                vec![Data::Unsigned(ENTRY_RECORD), Data::Structure(entry.clone())]
+            }
+            Record::AppendedEntry { class_id, logical_name, entry } => vec![
+                Data::Unsigned(APPENDED_ENTRY_RECORD),
+                Data::LongUnsigned(*class_id),
+                name(logical_name),
+                Data::Structure(entry.clone()),
+            ],
+            Record::InvocationCounter { system_title, value } => vec![
+                Data::Unsigned(INVOCATION_COUNTER_RECORD),
+                Data::OctetString(system_title.map(|title| title.to_vec()).unwrap_or_default()),
+                Data::DoubleLongUnsigned(*value),
+            ],
+        };
+        Data::Structure(fields).encode()
+    }
+
+    /// Parses one record, returning the remaining input.
+    pub fn parse(input: &[u8]) -> Result<(&[u8], Self), PersistenceError> {
+        let (rest, data) = Data::parse(input).map_err(|_| PersistenceError::ParseError)?;
+        let Data::Structure(fields) = data else {
+            return Err(PersistenceError::ParseError);
+        };
+        let logical_name =
+            |bytes: &[u8]| ObisCode::try_from(bytes).map_err(|_| PersistenceError::ParseError);
+        let record = match fields.as_slice() {
+            [
+                Data::Unsigned(OBJECT_RECORD),
+                Data::LongUnsigned(class_id),
+                Data::OctetString(name),
+                Data::Structure(attributes),
+            ] => Record::Object {
+                class_id: *class_id,
+                logical_name: logical_name(name)?,
+                attributes: attributes.clone(),
+            },
+            [Data::Unsigned(ENTRY_RECORD), Data::Structure(entry)] => Record::Entry(entry.clone()),
+            [
+                Data::Unsigned(APPENDED_ENTRY_RECORD),
+                Data::LongUnsigned(class_id),
+                Data::OctetString(name),
+                Data::Structure(entry),
+            ] => Record::AppendedEntry {
+                class_id: *class_id,
+                logical_name: logical_name(name)?,
+                entry: entry.clone(),
+            },
+            [
+                Data::Unsigned(INVOCATION_COUNTER_RECORD),
+                Data::OctetString(title),
+                Data::DoubleLongUnsigned(value),
+            ] => Record::InvocationCounter {
+                system_title: match title.len() {
+                    0 => None,
+                    _ => Some(
+                        title.as_slice().try_into().map_err(|_| PersistenceError::ParseError)?,
+                    ),
+                },
+                value: *value,
+            },
+            _ => return Err(PersistenceError::ParseError),
+        };
+        Ok((rest, record))
+    }
+}
+
+/// Errors while restoring a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PersistenceError {
+    /// A record could not be decoded (corrupt or truncated snapshot).
+    ParseError,
+    /// An entry record does not follow an object record.
+    UnexpectedRecord,
+    /// The snapshot names an object that is not in the registry.
+    UnknownObject(u16, ObisCode),
+    /// The object rejected its state.
+    Rejected(u16, ObisCode, DataAccessResult),
+}
+
+impl fmt::Display for PersistenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistenceError::ParseError => write!(f, "Parse error"),
+            PersistenceError::UnexpectedRecord => write!(f, "Unexpected record"),
+            PersistenceError::UnknownObject(class_id, logical_name) => {
+                write!(f, "Unknown object: class {} {}", class_id, logical_name)
+            }
+            PersistenceError::Rejected(class_id, logical_name, result) => {
+                write!(f, "State of class {} {} rejected: {:?}", class_id, logical_name, result)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PersistenceError {}
+
+/// Returns the records of every persisted object of a registry.
+pub fn records(objects: &ObjectRegistry) -> Vec<Record> {
+    let mut records = Vec::new();
+    for object in objects.iter() {
+        let Some(state) = object.save_state() else {
+            continue;
+        };
+        records.push(Record::Object {
+            class_id: object.class_id(),
+            logical_name: *object.logical_name(),
+            attributes: state.attributes,
+        });
+        records.extend(state.entries.into_iter().map(Record::Entry));
+    }
+    records
+}
+
+/// Encodes a snapshot of every persisted object of a registry.
+pub fn snapshot(objects: &ObjectRegistry) -> Vec<u8> {
+    encode_records(&records(objects))
+}
+
+/// Encodes a sequence of records.
+pub fn encode_records(records: &[Record]) -> Vec<u8> {
+    records.iter().flat_map(Record::encode).collect()
+}
+
+/// Parses every record of a snapshot.
+pub fn parse_records(mut input: &[u8]) -> Result<Vec<Record>, PersistenceError> {
+    let mut records = Vec::new();
+    while !input.is_empty() {
+        let (rest, record) = Record::parse(input)?;
+        records.push(record);
+        input = rest;
+    }
+    Ok(records)
+}
+
+/// Restores a snapshot into a registry.
+pub fn restore(objects: &mut ObjectRegistry, snapshot: &[u8]) -> Result<(), PersistenceError> {
+    apply(objects, parse_records(snapshot)?)
+}
+
+/// Applies records to a registry, in order.
+///
+/// [`Record::InvocationCounter`]s are not part of the object model and are
+/// rejected as [`PersistenceError::UnexpectedRecord`].
+pub fn apply(
+    objects: &mut ObjectRegistry,
+    records: impl IntoIterator<Item = Record>,
+) -> Result<(), PersistenceError> {
+    let mut pending: Option<(u16, ObisCode, ObjectState)> = None;
+    for record in records {
+        if let Record::Entry(entry) = record {
+            let (_, _, state) = pending.as_mut().ok_or(PersistenceError::UnexpectedRecord)?;
+            state.entries.push(entry);
+            continue;
+        }
+        if let Some((class_id, logical_name, state)) = pending.take() {
+            object_mut(objects, class_id, &logical_name)?
+                .restore_state(state)
+                .map_err(|result| PersistenceError::Rejected(class_id, logical_name, result))?;
+        }
+        match record {
+            Record::Object { class_id, logical_name, attributes } => {
+                let state = ObjectState { attributes, entries: Vec::new() };
+                pending = Some((class_id, logical_name, state));
+            }
+            Record::AppendedEntry { class_id, logical_name, entry } => {
+                object_mut(objects, class_id, &logical_name)?
+                    .append_entry(entry)
+                    .map_err(|result| PersistenceError::Rejected(class_id, logical_name, result))?;
+            }
+            Record::Entry(_) | Record::InvocationCounter { .. } => {
+                return Err(PersistenceError::UnexpectedRecord);
+            }
+        }
+    }
+    if let Some((class_id, logical_name, state)) = pending {
+        object_mut(objects, class_id, &logical_name)?
+            .restore_state(state)
+            .map_err(|result| PersistenceError::Rejected(class_id, logical_name, result))?;
+    }
+    Ok(())
+}
+
+/// Captures one entry of a ProfileGeneric, like its `capture` method, and
+/// returns the [`Record::AppendedEntry`] to append to the snapshot.
+pub fn capture(
+    objects: &mut ObjectRegistry,
+    logical_name: &ObisCode,
+) -> Result<Vec<u8>, PersistenceError> {
+    let unknown = || PersistenceError::UnknownObject(PROFILE_GENERIC_CLASS_ID, *logical_name);
+    let rejected =
+        |result| PersistenceError::Rejected(PROFILE_GENERIC_CLASS_ID, *logical_name, result);
+    let profile = objects.get(PROFILE_GENERIC_CLASS_ID, logical_name).ok_or_else(unknown)?;
+    let Data::Structure(columns) =
+        profile.get_attribute(CAPTURE_OBJECTS_ATTRIBUTE).map_err(rejected)?
+    else {
+        return Err(rejected(DataAccessResult::TypeUnmatched));
+    };
+    let entry = columns
+        .iter()
+        .map(|column| {
+            CaptureObjectDefinition::from_data(column)
+                .ok()
+                .and_then(|column| read_capture_object(objects, &column))
+                .unwrap_or(Data::Null)
+        })
+        .collect::<Vec<_>>();
+    object_mut(objects, PROFILE_GENERIC_CLASS_ID, logical_name)?
+        .append_entry(entry.clone())
+        .map_err(rejected)?;
+    let record = Record::AppendedEntry {
+        class_id: PROFILE_GENERIC_CLASS_ID,
+        logical_name: *logical_name,
+        entry,
+    };
+    Ok(record.encode())
+}
+
+fn object_mut<'a>(
+    objects: &'a mut ObjectRegistry,
+    class_id: u16,
+    logical_name: &ObisCode,
+) -> Result<&'a mut (dyn CosemObject + Send + 'static), PersistenceError> {
+    objects
+        .get_mut(class_id, logical_name)
+        .ok_or(PersistenceError::UnknownObject(class_id, *logical_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::ProfileGeneric;
+    use crate::cosem::clock::Clock;
+    use crate::cosem::data::DataObject;
+    use crate::cosem::demand_register::DemandRegister;
+    use crate::cosem::extended_register::ExtendedRegister;
+    use crate::cosem::register::Register;
+    use crate::data::{Date, DateTime, Time};
+    use crate::unit::{ScalerUnit, Unit};
+
+    const COUNTER: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 15, e: 0, f: 255 };
+    const ENERGY: ObisCode = ObisCode { a: 1, b: 0, c: 1, d: 8, e: 0, f: 255 };
+    const MAX_DEMAND: ObisCode = ObisCode { a: 1, b: 0, c: 1, d: 6, e: 0, f: 255 };
+    const DEMAND: ObisCode = ObisCode { a: 1, b: 0, c: 1, d: 4, e: 0, f: 255 };
+    const CLOCK: ObisCode = ObisCode { a: 0, b: 0, c: 1, d: 0, e: 0, f: 255 };
+    const LOAD_PROFILE: ObisCode = ObisCode { a: 1, b: 0, c: 99, d: 1, e: 0, f: 255 };
+
+    fn date_time(hour: u8) -> DateTime {
+        DateTime::new(
+            Date::new(2024, 6, 1, 6),
+            Time::new(Some(hour), Some(0), Some(0), Some(0)),
+            Some(0),
+            Some(0),
+        )
+    }
+
+    fn objects(profile_entries: u32) -> ObjectRegistry {
+        let watt_hour = ScalerUnit { scaler: 0, unit: Unit::WattHour };
+        ObjectRegistry::new()
+            .with_object(DataObject::new(COUNTER, Data::LongUnsigned(0)))
+            .with_object(Register::new(ENERGY, Data::DoubleLongUnsigned(0), watt_hour))
+            .with_object(ExtendedRegister::new(
+                MAX_DEMAND,
+                Data::DoubleLongUnsigned(0),
+                watt_hour,
+                Data::Unsigned(0),
+                date_time(0),
+            ))
+            .with_object(DemandRegister {
+                logical_name: DEMAND,
+                current_average_value: Data::DoubleLongUnsigned(0),
+                last_average_value: Data::DoubleLongUnsigned(0),
+                scaler_unit: watt_hour,
+                status: Data::Null,
+                capture_time: date_time(0),
+                start_time_current: date_time(0),
+                period: 900,
+                number_of_periods: 1,
+            })
+            .with_object(Clock::new(CLOCK))
+            .with_object(ProfileGeneric::with_fifo(
+                LOAD_PROFILE,
+                vec![CaptureObjectDefinition {
+                    class_id: 1,
+                    logical_name: COUNTER,
+                    attribute_index: 2,
+                    data_index: 0,
+                }],
+                900,
+                profile_entries,
+            ))
+    }
+
+    fn set(
+        objects: &mut ObjectRegistry,
+        class_id: u16,
+        logical_name: ObisCode,
+        id: i8,
+        value: Data,
+    ) {
+        objects.get_mut(class_id, &logical_name).unwrap().set_attribute(id, value).unwrap();
+    }
+
+    fn buffer(objects: &ObjectRegistry) -> Data {
+        objects.get(7, &LOAD_PROFILE).unwrap().get_attribute(2).unwrap()
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let mut meter = objects(1000);
+        set(&mut meter, 3, ENERGY, 2, Data::DoubleLongUnsigned(12345));
+        set(&mut meter, 4, MAX_DEMAND, 5, Data::DateTime(date_time(12)));
+        set(&mut meter, 5, DEMAND, 8, Data::DoubleLongUnsigned(1800));
+        set(&mut meter, 8, CLOCK, 3, Data::Long(60));
+        meter
+            .get_mut(8, &CLOCK)
+            .unwrap()
+            .invoke_method(5, Some(Data::OctetString(date_time(18).encode())))
+            .unwrap();
+        // More entries than fit in one structure
+        for value in 0..300 {
+            set(&mut meter, 1, COUNTER, 2, Data::LongUnsigned(value));
+            capture(&mut meter, &LOAD_PROFILE).unwrap();
+        }
+
+        let snapshot = snapshot(&meter);
+        let mut restored = objects(1000);
+        assert_ne!(records(&restored), records(&meter));
+        restore(&mut restored, &snapshot).unwrap();
+        assert_eq!(records(&restored), records(&meter));
+        assert_eq!(
+            restored.get(7, &LOAD_PROFILE).unwrap().get_attribute(7),
+            Ok(Data::DoubleLongUnsigned(300))
+        );
+    }
+
+    #[test]
+    fn test_appended_entries() {
+        let mut meter = objects(3);
+        let mut snapshot = snapshot(&meter);
+        for value in 1..=5 {
+            set(&mut meter, 1, COUNTER, 2, Data::LongUnsigned(value));
+            let record = capture(&mut meter, &LOAD_PROFILE).unwrap();
+            assert_eq!(
+                Record::parse(&record).unwrap().1,
+                Record::AppendedEntry {
+                    class_id: 7,
+                    logical_name: LOAD_PROFILE,
+                    entry: vec![Data::LongUnsigned(value)],
+                }
+            );
+            snapshot.extend(record);
+        }
+
+        // Appended entries are replayed as captures, dropping the oldest.
+        let mut restored = objects(3);
+        restore(&mut restored, &snapshot).unwrap();
+        let entry = |value| Data::Structure(vec![Data::LongUnsigned(value)]);
+        assert_eq!(buffer(&restored), Data::Structure(vec![entry(3), entry(4), entry(5)]));
+        assert_eq!(buffer(&restored), buffer(&meter));
+    }
+
+    #[test]
+    fn test_restore_errors() {
+        let mut objects = objects(10);
+        let snapshot = snapshot(&objects);
+
+        assert_eq!(
+            restore(&mut objects, &snapshot[..snapshot.len() - 1]),
+            Err(PersistenceError::ParseError)
+        );
+        let entry = Record::Entry(vec![Data::Null]).encode();
+        assert_eq!(restore(&mut objects, &entry), Err(PersistenceError::UnexpectedRecord));
+
+        let unknown = ObisCode::new(0, 0, 96, 99, 0, 255);
+        let record = Record::Object { class_id: 1, logical_name: unknown, attributes: vec![] };
+        assert_eq!(
+            restore(&mut objects, &record.encode()),
+            Err(PersistenceError::UnknownObject(1, unknown))
+        );
+        let record = Record::Object { class_id: 8, logical_name: CLOCK, attributes: vec![] };
+        assert_eq!(
+            restore(&mut objects, &record.encode()),
+            Err(PersistenceError::Rejected(8, CLOCK, DataAccessResult::TypeUnmatched))
+        );
+        let record = Record::AppendedEntry { class_id: 1, logical_name: COUNTER, entry: vec![] };
+        assert_eq!(
+            restore(&mut objects, &record.encode()),
+            Err(PersistenceError::Rejected(1, COUNTER, DataAccessResult::ObjectClassInconsistent))
+        );
+    }
+}
@@ -20,13 +20,11 @@
 //! - Basic attribute access
 //! - Methods: reset(), capture()
 //!
-//! ## Phase 5.3 Deferred Features
+//! ## Selective Access
 //!
-//! The following features will be implemented in Phase 5.3:
-//! - RangeDescriptor (filter by date range)
-//! - EntryDescriptor (filter by row/column)
-//! - Selective access integration
-//! - Advanced sort methods (Largest, Smallest, etc.)
+//! Buffer reads with selective access (`get_attribute_selective`, attribute 2)
+//! support RangeDescriptor (selector 1, filter by value or date range) and
+//! EntryDescriptor (selector 2, filter by row/column); see [`ProfileGeneric::select`].
 //!
 //! ## Example Usage
 //!
@@ -72,8 +70,11 @@
 //! - **Gurux**: `gxprofilegeneric.h` / `gxprofilegeneric.c`
 
 use crate::action::ActionResult;
-use crate::cosem::CosemObject;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::cosem::{CosemObject, ObjectRegistry};
 use crate::get::DataAccessResult;
+use crate::selective_access::{EntryDescriptor, RangeDescriptor, SelectiveAccessDescriptor};
 use crate::{Data, ObisCode};
 
 #[cfg(not(feature = "std"))]
@@ -363,14 +364,8 @@ impl CosemObject for ProfileGeneric {
                                 _ => return Err(DataAccessResult::TypeUnmatched),
                             };
                             let logical_name = match &fields[1] {
-                                Data::OctetString(bytes) => {
-                                    if bytes.len() != 6 {
-                                        return Err(DataAccessResult::TypeUnmatched);
-                                    }
-                                    ObisCode::new(
-                                        bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5],
-                                    )
-                                }
+                                Data::OctetString(bytes) => ObisCode::try_from(bytes.as_slice())
+                                    .map_err(|_| DataAccessResult::TypeUnmatched)?,
                                 _ => return Err(DataAccessResult::TypeUnmatched),
                             };
                             let attribute_index = match &fields[2] {
@@ -425,14 +420,8 @@ impl CosemObject for ProfileGeneric {
                         _ => return Err(DataAccessResult::TypeUnmatched),
                     };
                     let logical_name = match &fields[1] {
-                        Data::OctetString(bytes) => {
-                            if bytes.len() != 6 {
-                                return Err(DataAccessResult::TypeUnmatched);
-                            }
-                            ObisCode::new(
-                                bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5],
-                            )
-                        }
+                        Data::OctetString(bytes) => ObisCode::try_from(bytes.as_slice())
+                            .map_err(|_| DataAccessResult::TypeUnmatched)?,
                         _ => return Err(DataAccessResult::TypeUnmatched),
                     };
                     let attribute_index = match &fields[2] {
@@ -466,6 +455,21 @@ impl CosemObject for ProfileGeneric {
         }
     }
 
+    fn get_attribute_selective(
+        &self,
+        id: i8,
+        selector: u8,
+        parameters: &Data,
+    ) -> Result<Data, DataAccessResult> {
+        if id != 2 {
+            return Err(DataAccessResult::ScopeOfAccessViolated);
+        }
+        let descriptor = SelectiveAccessDescriptor::from_access_selector(selector, parameters)
+            .map_err(|_| DataAccessResult::TypeUnmatched)?;
+        let rows = self.select(&descriptor)?;
+        Ok(Data::Structure(rows.into_iter().map(Data::Structure).collect()))
+    }
+
     fn invoke_method(
         &mut self,
         id: i8,
@@ -477,6 +481,50 @@ impl CosemObject for ProfileGeneric {
             _ => Err(ActionResult::ObjectUndefined),
         }
     }
+
+    fn invoke_method_with_objects(
+        &mut self,
+        objects: &mut ObjectRegistry,
+        id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match id {
+            2 => {
+                self.capture_from(objects);
+                Ok(Some(Data::Integer(0)))
+            }
+            _ => self.invoke_method(id, params),
+        }
+    }
+
+    /// Persists the configuration attributes, `executed_time` and the buffer.
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        let mut state = ObjectState::from_attributes(self, [3, 4, 5, 6, 8]).ok()?;
+        state.attributes.push(Data::DoubleLongUnsigned(self.executed_time));
+        state.entries = self.buffer.iter().cloned().collect();
+        Some(state)
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        let entries = state.entries.clone();
+        match state.restore_attributes(self, [3, 4, 5, 6, 8])?.as_slice() {
+            [Data::DoubleLongUnsigned(executed_time)] => {
+                self.executed_time = *executed_time;
+                self.buffer = entries.into();
+                self.entries_in_use = self.buffer.len() as u32;
+                Ok(())
+            }
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    fn append_entry(&mut self, entry: Vec<Data>) -> Result<(), DataAccessResult> {
+        self.add_entry(entry);
+        Ok(())
+    }
 }
 
 impl ProfileGeneric {
@@ -672,13 +720,10 @@ impl ProfileGeneric {
 
     /// Method 2: Capture
     ///
-    /// Manually triggers a capture. Reads all objects defined in capture_objects
-    /// and adds a new entry to the buffer.
-    ///
-    /// ## Phase 5.2 Implementation
-    ///
-    /// In Phase 5.2, this method creates a mock entry with placeholder values.
-    /// Real object attribute lookup will be implemented in the integration phase.
+    /// Manually triggers a capture without access to other objects: every
+    /// column is captured as `Data::Null`. Invoked through
+    /// [`ObjectRegistry::invoke_method`] (as the server does), the columns are
+    /// read from the registry instead; see [`capture_from`](Self::capture_from).
     ///
     /// ## Parameters
     ///
@@ -720,19 +765,52 @@ impl ProfileGeneric {
     /// assert_eq!(profile.entries_in_use, 1);
     /// ```
     fn capture(&mut self, _params: Option<Data>) -> Result<Option<Data>, ActionResult> {
-        // Phase 5.2: Create mock entry matching capture_objects structure
-        let mock_entry: Vec<Data> = self
+        self.capture_from(&ObjectRegistry::new());
+        Ok(Some(Data::Integer(0))) // Success
+    }
+
+    /// Capture one entry by reading the capture objects from a registry
+    ///
+    /// Each column's attribute is read from the object registered under its
+    /// `class_id` and `logical_name`; a non-zero `data_index` selects that
+    /// element (1-based) of a structure or array value. Columns that cannot be
+    /// read (missing object, access error, data_index out of range) are
+    /// captured as `Data::Null` so the entry keeps its shape.
+    ///
+    /// The entry is added according to `sort_method`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use dlms_cosem::cosem::register::Register;
+    /// use dlms_cosem::cosem::{CaptureObjectDefinition, ObjectRegistry, ProfileGeneric};
+    /// use dlms_cosem::{Data, ObisCode, ScalerUnit, Unit};
+    ///
+    /// let energy = ObisCode::new(1, 0, 1, 8, 0, 255);
+    /// let registry = ObjectRegistry::new().with_object(Register::new(
+    ///     energy,
+    ///     Data::DoubleLongUnsigned(12345),
+    ///     ScalerUnit { scaler: 0, unit: Unit::WattHour },
+    /// ));
+    ///
+    /// let mut profile = ProfileGeneric::with_fifo(
+    ///     ObisCode::new(1, 0, 99, 1, 0, 255),
+    ///     vec![CaptureObjectDefinition { class_id: 3, logical_name: energy, attribute_index: 2, data_index: 0 }],
+    ///     900,
+    ///     96,
+    /// );
+    ///
+    /// profile.capture_from(&registry);
+    /// assert_eq!(profile.buffer[0], vec![Data::DoubleLongUnsigned(12345)]);
+    /// ```
+    pub fn capture_from(&mut self, objects: &ObjectRegistry) {
+        let entry: Vec<Data> = self
             .capture_objects
             .iter()
-            .map(|_obj| {
-                // TODO Phase 5.3: Read actual object attributes
-                // For now, use placeholder value
-                Data::DoubleLongUnsigned(0)
-            })
+            .map(|column| read_capture_object(objects, column).unwrap_or(Data::Null))
             .collect();
 
-        self.add_entry(mock_entry);
-        Ok(Some(Data::Integer(0))) // Success
+        self.add_entry(entry);
     }
 
     /// Add entry to buffer according to sort_method
@@ -883,6 +961,110 @@ impl ProfileGeneric {
         extract_numeric_value(&entry[column_idx])
     }
 
+    /// Apply a selective access descriptor to the buffer
+    ///
+    /// Returns the selected entries, each reduced to the selected columns.
+    ///
+    /// - **Range** (selector 1): entries whose `restricting_object` column lies
+    ///   in `from_value..=to_value`, with the `selected_values` columns (all
+    ///   columns if empty). Numeric values, date-times (also as 12-byte
+    ///   octet-strings), dates and times can be compared.
+    /// - **Entry** (selector 2): entries `from_entry..=to_entry` and columns
+    ///   `from_selected_value..=to_selected_value` (1-based, `to` = 0 means
+    ///   "up to the last"). `from_entry` = 0 selects the newest `to_entry + 1`
+    ///   entries, matching [`EntryDescriptor::last_n_entries`].
+    ///
+    /// ## Errors
+    ///
+    /// `ScopeOfAccessViolated` if a referenced column is not a capture object.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use dlms_cosem::cosem::ProfileGeneric;
+    /// use dlms_cosem::selective_access::{EntryDescriptor, SelectiveAccessDescriptor};
+    /// use dlms_cosem::{Data, ObisCode};
+    ///
+    /// let mut profile = ProfileGeneric::new(ObisCode::new(1, 0, 99, 1, 0, 255), 10);
+    /// for value in [100, 200, 300] {
+    ///     profile.buffer.push_back(vec![Data::DoubleLongUnsigned(value)]);
+    /// }
+    ///
+    /// let rows = profile
+    ///     .select(&SelectiveAccessDescriptor::Entry(EntryDescriptor::last_n_entries(2)))
+    ///     .unwrap();
+    /// assert_eq!(rows, vec![vec![Data::DoubleLongUnsigned(200)], vec![Data::DoubleLongUnsigned(300)]]);
+    /// ```
+    pub fn select(
+        &self,
+        descriptor: &SelectiveAccessDescriptor,
+    ) -> Result<Vec<Vec<Data>>, DataAccessResult> {
+        match descriptor {
+            SelectiveAccessDescriptor::Range(range) => self.select_range(range),
+            SelectiveAccessDescriptor::Entry(entry) => Ok(self.select_entries(entry)),
+        }
+    }
+
+    fn select_range(&self, range: &RangeDescriptor) -> Result<Vec<Vec<Data>>, DataAccessResult> {
+        let restricting_column = self
+            .find_column_index(&range.restricting_object)
+            .ok_or(DataAccessResult::ScopeOfAccessViolated)?;
+        let columns = range
+            .selected_values
+            .iter()
+            .map(|column| self.find_column_index(column))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(DataAccessResult::ScopeOfAccessViolated)?;
+
+        Ok(self
+            .buffer
+            .iter()
+            .filter(|entry| {
+                entry.get(restricting_column).is_some_and(|value| {
+                    compare_values(value, &range.from_value).is_some_and(|ord| ord.is_ge())
+                        && compare_values(value, &range.to_value).is_some_and(|ord| ord.is_le())
+                })
+            })
+            .map(|entry| {
+                if columns.is_empty() {
+                    entry.clone()
+                } else {
+                    columns.iter().filter_map(|&column| entry.get(column).cloned()).collect()
+                }
+            })
+            .collect())
+    }
+
+    fn select_entries(&self, descriptor: &EntryDescriptor) -> Vec<Vec<Data>> {
+        let len = self.buffer.len();
+        let (first, last) = if descriptor.from_entry == 0 {
+            (len.saturating_sub(descriptor.to_entry as usize + 1), len)
+        } else if descriptor.to_entry == 0 {
+            (descriptor.from_entry as usize - 1, len)
+        } else {
+            (descriptor.from_entry as usize - 1, len.min(descriptor.to_entry as usize))
+        };
+
+        self.buffer
+            .iter()
+            .take(last)
+            .skip(first)
+            .map(|entry| {
+                let first_column = usize::from(descriptor.from_selected_value.max(1)) - 1;
+                let last_column = match descriptor.to_selected_value {
+                    0 => entry.len(),
+                    to => entry.len().min(usize::from(to)),
+                };
+                entry.get(first_column..last_column).unwrap_or_default().to_vec()
+            })
+            .collect()
+    }
+
+    /// Find the buffer column of a capture object
+    fn find_column_index(&self, column: &CaptureObjectDefinition) -> Option<usize> {
+        self.capture_objects.iter().position(|obj| obj.refers_to_same_attribute(column))
+    }
+
     /// Find the index of the sort_object column in capture_objects
     ///
     /// ## Returns
@@ -931,6 +1113,74 @@ pub fn extract_numeric_value(data: &Data) -> Option<f64> {
     }
 }
 
+/// Read the value of one capture object column
+pub(crate) fn read_capture_object(
+    objects: &ObjectRegistry,
+    column: &CaptureObjectDefinition,
+) -> Option<Data> {
+    let value = objects
+        .get(column.class_id, &column.logical_name)?
+        .get_attribute(column.attribute_index)
+        .ok()?;
+    match (column.data_index, value) {
+        (0, value) => Some(value),
+        (index, Data::Structure(mut elements)) => {
+            let index = usize::from(index) - 1;
+            (index < elements.len()).then(|| elements.swap_remove(index))
+        }
+        _ => None,
+    }
+}
+
+/// Compare two buffer values for range selection
+///
+/// Numeric values compare across integer/float types; date-times compare by
+/// their local date and time fields (wildcards as 0), whether they are
+/// `DateTime` or 12-byte octet-strings. Returns `None` for incomparable values.
+fn compare_values(a: &Data, b: &Data) -> Option<core::cmp::Ordering> {
+    if let (Some(a), Some(b)) = (extract_numeric_value(a), extract_numeric_value(b)) {
+        return a.partial_cmp(&b);
+    }
+    if let (Some(a), Some(b)) = (date_time_key(a), date_time_key(b)) {
+        return Some(a.cmp(&b));
+    }
+    match (a, b) {
+        (Data::Date(a), Data::Date(b)) => {
+            Some((a.year, a.month, a.day_of_month).cmp(&(b.year, b.month, b.day_of_month)))
+        }
+        (Data::Time(a), Data::Time(b)) => Some(time_key(a).cmp(&time_key(b))),
+        (Data::OctetString(a), Data::OctetString(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+fn date_time_key(data: &Data) -> Option<(u16, u8, u8, [u8; 4])> {
+    match data {
+        Data::DateTime(dt) => {
+            Some((dt.date.year, dt.date.month, dt.date.day_of_month, time_key(&dt.time)))
+        }
+        Data::OctetString(bytes) if bytes.len() == 12 => {
+            let field = |byte: u8| if byte == 0xFF { 0 } else { byte };
+            Some((
+                u16::from_be_bytes([bytes[0], bytes[1]]),
+                bytes[2],
+                bytes[3],
+                [field(bytes[5]), field(bytes[6]), field(bytes[7]), field(bytes[8])],
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn time_key(time: &crate::data::Time) -> [u8; 4] {
+    [
+        time.hour.unwrap_or(0),
+        time.minute.unwrap_or(0),
+        time.second.unwrap_or(0),
+        time.hundredth.unwrap_or(0),
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1622,7 +1872,91 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(profile.buffer.len(), 1);
         assert_eq!(profile.entries_in_use, 1);
-        assert_eq!(profile.buffer[0], vec![Data::DoubleLongUnsigned(0)]);
+        // Without a registry the columns cannot be read
+        assert_eq!(profile.buffer[0], vec![Data::Null]);
+    }
+
+    #[test]
+    fn test_capture_from_registry() {
+        use crate::cosem::register::Register;
+        use crate::{ScalerUnit, Unit};
+
+        let energy = ObisCode::new(1, 0, 1, 8, 0, 255);
+        let column = |attribute_index, data_index| CaptureObjectDefinition {
+            class_id: 3,
+            logical_name: energy,
+            attribute_index,
+            data_index,
+        };
+        let mut profile = ProfileGeneric::with_fifo(
+            ObisCode::new(1, 0, 99, 1, 0, 255),
+            vec![
+                column(2, 0),
+                column(3, 2), // unit of scaler_unit
+                column(3, 3), // out of range
+                column(9, 0), // no such attribute
+                CaptureObjectDefinition {
+                    class_id: 8,
+                    logical_name: ObisCode::new(0, 0, 1, 0, 0, 255),
+                    attribute_index: 2,
+                    data_index: 0,
+                }, // no such object
+            ],
+            900,
+            10,
+        );
+        let registry = ObjectRegistry::new().with_object(Register::new(
+            energy,
+            Data::DoubleLongUnsigned(4200),
+            ScalerUnit { scaler: 0, unit: Unit::WattHour },
+        ));
+
+        profile.capture_from(&registry);
+
+        assert_eq!(
+            profile.buffer[0],
+            vec![
+                Data::DoubleLongUnsigned(4200),
+                Data::Enum(Unit::WattHour.as_i8() as u8),
+                Data::Null,
+                Data::Null,
+                Data::Null,
+            ]
+        );
+        assert_eq!(profile.entries_in_use, 1);
+    }
+
+    #[test]
+    fn test_capture_through_registry_invoke_method() {
+        use crate::cosem::data::DataObject;
+
+        let counter = ObisCode::new(0, 0, 96, 15, 0, 255);
+        let profile_name = ObisCode::new(0, 0, 99, 98, 0, 255);
+        let mut registry = ObjectRegistry::new()
+            .with_object(DataObject::new(counter, Data::LongUnsigned(1)))
+            .with_object(ProfileGeneric::with_lifo(
+                profile_name,
+                vec![CaptureObjectDefinition {
+                    class_id: 1,
+                    logical_name: counter,
+                    attribute_index: 2,
+                    data_index: 0,
+                }],
+                0,
+                10,
+            ));
+
+        registry.invoke_method(7, &profile_name, 2, Some(Data::Integer(0))).unwrap();
+        registry.get_mut(1, &counter).unwrap().set_attribute(2, Data::LongUnsigned(2)).unwrap();
+        registry.invoke_method(7, &profile_name, 2, Some(Data::Integer(0))).unwrap();
+
+        assert_eq!(
+            registry.get(7, &profile_name).unwrap().get_attribute(2),
+            Ok(Data::Structure(vec![
+                Data::Structure(vec![Data::LongUnsigned(2)]),
+                Data::Structure(vec![Data::LongUnsigned(1)]),
+            ]))
+        );
     }
 
     #[test]
@@ -2121,32 +2455,160 @@ mod tests {
         // No runtime assertions needed - this is a compile-time test
     }
 
-    // ===== STEP 8: Placeholder Tests (5 tests) - Phase 5.3 Features =====
+    // ===== Selective Access Tests =====
+
+    fn clock_column() -> CaptureObjectDefinition {
+        CaptureObjectDefinition {
+            class_id: 8,
+            logical_name: ObisCode::new(0, 0, 1, 0, 0, 255),
+            attribute_index: 2,
+            data_index: 0,
+        }
+    }
+
+    fn energy_column() -> CaptureObjectDefinition {
+        CaptureObjectDefinition {
+            class_id: 3,
+            logical_name: ObisCode::new(1, 0, 1, 8, 0, 255),
+            attribute_index: 2,
+            data_index: 0,
+        }
+    }
+
+    fn timestamp(hour: u8) -> Data {
+        Data::DateTime(crate::DateTime::new(
+            crate::Date::new(2024, 1, 1, 1),
+            crate::Time::new(Some(hour), Some(0), Some(0), Some(0)),
+            Some(0),
+            None,
+        ))
+    }
+
+    /// Load profile with 4 hourly entries: (timestamp, energy)
+    fn load_profile() -> ProfileGeneric {
+        let mut profile = ProfileGeneric::new(ObisCode::new(1, 0, 99, 1, 0, 255), 10);
+        profile.capture_objects = vec![clock_column(), energy_column()];
+        for hour in 0..4 {
+            profile
+                .buffer
+                .push_back(vec![timestamp(hour), Data::DoubleLongUnsigned(100 * hour as u32)]);
+        }
+        profile.entries_in_use = 4;
+        profile
+    }
 
     #[test]
-    fn test_selective_access_range_descriptor_placeholder() {
-        // Phase 5.3: RangeDescriptor for filtering by date range
-        let profile = ProfileGeneric::new(ObisCode::new(1, 0, 99, 1, 0, 255), 10);
-        // TODO Phase 5.3: Implement RangeDescriptor
-        // Example: Get entries from 2024-01-01 to 2024-01-31
-        // let range = RangeDescriptor { from: DateTime, to: DateTime };
-        // let filtered = profile.get_with_range(range);
-        assert_eq!(profile.class_id(), 7); // Placeholder assertion
+    fn test_selective_access_range_descriptor_date_time() {
+        let profile = load_profile();
+        let range =
+            RangeDescriptor::new(clock_column(), timestamp(1), timestamp(2), vec![energy_column()]);
+
+        let result = profile.get_attribute_selective(2, 1, &range.encode()).unwrap();
+
+        assert_eq!(
+            result,
+            Data::Structure(vec![
+                Data::Structure(vec![Data::DoubleLongUnsigned(100)]),
+                Data::Structure(vec![Data::DoubleLongUnsigned(200)]),
+            ])
+        );
     }
 
     #[test]
-    fn test_selective_access_entry_descriptor_placeholder() {
-        // Phase 5.3: EntryDescriptor for filtering by row/column indices
-        let mut profile = ProfileGeneric::new(ObisCode::new(1, 0, 99, 1, 0, 255), 10);
-        profile.buffer.push_back(vec![Data::DoubleLongUnsigned(100)]);
-        profile.buffer.push_back(vec![Data::DoubleLongUnsigned(200)]);
-        profile.buffer.push_back(vec![Data::DoubleLongUnsigned(300)]);
-        profile.entries_in_use = 3;
-        // TODO Phase 5.3: Implement EntryDescriptor
-        // Example: Get rows 1-2, column 0
-        // let descriptor = EntryDescriptor { from_row: 1, to_row: 2, from_col: 0, to_col: 0 };
-        // let filtered = profile.get_with_entry_descriptor(descriptor);
-        assert_eq!(profile.buffer.len(), 3); // Placeholder assertion
+    fn test_selective_access_range_descriptor_numeric() {
+        let profile = load_profile();
+        // Bounds of a different numeric type than the buffer values still compare
+        let range =
+            RangeDescriptor::new(energy_column(), Data::Long(150), Data::Long64(1000), vec![]);
+
+        let rows = profile.select(&SelectiveAccessDescriptor::Range(range)).unwrap();
+
+        assert_eq!(rows, vec![profile.buffer[2].clone(), profile.buffer[3].clone()]);
+    }
+
+    #[test]
+    fn test_selective_access_range_descriptor_octet_string_date_time() {
+        let mut profile = load_profile();
+        for (hour, entry) in profile.buffer.iter_mut().enumerate() {
+            entry[0] =
+                Data::OctetString(vec![0x07, 0xE8, 1, 1, 1, hour as u8, 0, 0, 0, 0x80, 0, 0]);
+        }
+        let range = RangeDescriptor::new(clock_column(), timestamp(3), timestamp(23), vec![]);
+
+        let rows = profile.select(&SelectiveAccessDescriptor::Range(range)).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][1], Data::DoubleLongUnsigned(300));
+    }
+
+    #[test]
+    fn test_selective_access_range_descriptor_unknown_column() {
+        let profile = load_profile();
+        let mut column = energy_column();
+        column.attribute_index = 3;
+        let range = RangeDescriptor::new(column, Data::Unsigned(0), Data::Unsigned(1), vec![]);
+
+        assert_eq!(
+            profile.select(&SelectiveAccessDescriptor::Range(range)),
+            Err(DataAccessResult::ScopeOfAccessViolated)
+        );
+    }
+
+    #[test]
+    fn test_selective_access_entry_descriptor() {
+        let profile = load_profile();
+
+        let rows =
+            profile.get_attribute_selective(2, 2, &EntryDescriptor::new(2, 3, 2, 2).encode());
+        assert_eq!(
+            rows,
+            Ok(Data::Structure(vec![
+                Data::Structure(vec![Data::DoubleLongUnsigned(100)]),
+                Data::Structure(vec![Data::DoubleLongUnsigned(200)]),
+            ]))
+        );
+
+        // to_entry = 0 and to_selected_value = 0 mean "up to the last"
+        let all =
+            profile.select(&SelectiveAccessDescriptor::Entry(EntryDescriptor::new(1, 0, 1, 0)));
+        assert_eq!(all, Ok(profile.buffer.iter().cloned().collect()));
+
+        // Entries beyond the buffer are ignored
+        let past_end =
+            profile.select(&SelectiveAccessDescriptor::Entry(EntryDescriptor::new(4, 100, 1, 1)));
+        assert_eq!(past_end, Ok(vec![vec![timestamp(3)]]));
+        let empty =
+            profile.select(&SelectiveAccessDescriptor::Entry(EntryDescriptor::new(5, 0, 1, 0)));
+        assert_eq!(empty, Ok(vec![]));
+    }
+
+    #[test]
+    fn test_selective_access_entry_descriptor_last_n_entries() {
+        let profile = load_profile();
+
+        let rows = profile
+            .select(&SelectiveAccessDescriptor::Entry(EntryDescriptor::last_n_entries(2)))
+            .unwrap();
+
+        assert_eq!(rows, vec![profile.buffer[2].clone(), profile.buffer[3].clone()]);
+    }
+
+    #[test]
+    fn test_selective_access_errors() {
+        let profile = load_profile();
+
+        assert_eq!(
+            profile.get_attribute_selective(3, 2, &EntryDescriptor::new(1, 0, 1, 0).encode()),
+            Err(DataAccessResult::ScopeOfAccessViolated)
+        );
+        assert_eq!(
+            profile.get_attribute_selective(2, 3, &Data::Null),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        assert_eq!(
+            profile.get_attribute_selective(2, 2, &Data::Unsigned(1)),
+            Err(DataAccessResult::TypeUnmatched)
+        );
     }
 
     // ===== PHASE 5.3.2: Advanced Sort Methods Tests =====
@@ -0,0 +1,822 @@
+//! COSEM Interface Class 40: Push Setup
+//!
+//! A Push Setup sends the values of a list of attributes to a destination,
+//! without being asked, as a DataNotification. Pushes are triggered through
+//! the `push` method (typically by a script or a single action schedule) and
+//! sent within the communication windows, after a random delay and with
+//! retries.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.25.9.0.255)
+//! - Attribute 2: `push_object_list` - Array of capture object definitions
+//! - Attribute 3: `send_destination_and_method` - `{transport_service,
+//!   destination, message}`
+//! - Attribute 4: `communication_window` - Array of `{start_time, end_time}`
+//! - Attribute 5: `randomisation_start_interval` - Seconds
+//! - Attribute 6: `number_of_retries`
+//! - Attribute 7: `repetition_delay` - Seconds
+//!
+//! ## Methods
+//! - Method 1: `push(data)` - Triggers a push
+//!
+//! ## Scheduling
+//!
+//! A triggered push waits for a communication window (an empty list allows
+//! pushing at any time), then for a random delay of up to
+//! `randomisation_start_interval`. The notification is assembled from the
+//! registry and given to the [`PushSink`]; when the sink fails, it is sent
+//! again after `repetition_delay`, at most `number_of_retries` times. If the
+//! window closes meanwhile, a new random delay is drawn in the next window.
+//! [`PushSetup::update`] drives the push; in a registry,
+//! [`ObjectRegistry::tick`] does. Times are local seconds since 1970-01-01.
+//!
+//! Window dates may contain wildcards; such windows recur on every matching
+//! day and end within a day of their start.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::push_setup::{PushSetup, PushSink, SendDestinationAndMethod};
+//! use dlms_cosem::cosem::register::Register;
+//! use dlms_cosem::cosem::{CaptureObjectDefinition, ObjectRegistry};
+//! use dlms_cosem::{Data, DataNotification, ObisCode, ScalerUnit, Unit};
+//!
+//! #[derive(Debug)]
+//! struct Stdout;
+//!
+//! impl PushSink for Stdout {
+//!     fn send(&mut self, _destination: &SendDestinationAndMethod, notification: &DataNotification) -> bool {
+//!         println!("{:02X?}", notification.encode());
+//!         true
+//!     }
+//! }
+//!
+//! let energy = ObisCode::new(1, 0, 1, 8, 0, 255);
+//! let objects = ObjectRegistry::new().with_object(Register::new(
+//!     energy,
+//!     Data::DoubleLongUnsigned(12345),
+//!     ScalerUnit { scaler: 0, unit: Unit::WattHour },
+//! ));
+//! let mut push_setup = PushSetup::new(ObisCode::new(0, 0, 25, 9, 0, 255))
+//!     .with_push_object(CaptureObjectDefinition { class_id: 3, logical_name: energy, attribute_index: 2, data_index: 0 })
+//!     .with_sink(Box::new(Stdout));
+//!
+//! push_setup.trigger();
+//! push_setup.update(1_738_368_000, &objects);
+//! assert!(!push_setup.pending());
+//!
+//! let notification = push_setup.push(1_738_368_000, &objects);
+//! assert_eq!(notification.notification_body(), &Data::Structure(vec![Data::DoubleLongUnsigned(12345)]));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::action::ActionResult;
+use crate::calendar::seconds_of_day;
+use crate::cosem::activity_calendar::{civil_from_days, weekday};
+use crate::cosem::capture_scheduler::days_from_civil;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::cosem::profile_generic::read_capture_object;
+use crate::cosem::script_table::ScriptReference;
+use crate::cosem::single_action_schedule::date_matches;
+use crate::cosem::{CaptureObjectDefinition, CosemObject, ObjectRegistry};
+use crate::data::{Data, Date, DateTime, Time};
+use crate::data_notification::DataNotification;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Push Setup interface class id
+const PUSH_SETUP_CLASS_ID: u16 = 40;
+
+/// Transport service of `send_destination_and_method`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TransportService {
+    /// TCP, destination `address:port`
+    Tcp,
+    /// UDP, destination `address:port`
+    Udp,
+    /// FTP
+    Ftp,
+    /// SMTP, destination an e-mail address
+    Smtp,
+    /// SMS, destination a phone number
+    Sms,
+    /// HDLC
+    Hdlc,
+    /// M-Bus
+    MBus,
+    /// ZigBee
+    ZigBee,
+    /// Manufacturer specific (200-255)
+    ManufacturerSpecific(u8),
+}
+
+impl TransportService {
+    /// Converts a transport_service enum value.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::Tcp),
+            1 => Ok(Self::Udp),
+            2 => Ok(Self::Ftp),
+            3 => Ok(Self::Smtp),
+            4 => Ok(Self::Sms),
+            5 => Ok(Self::Hdlc),
+            6 => Ok(Self::MBus),
+            7 => Ok(Self::ZigBee),
+            200..=255 => Ok(Self::ManufacturerSpecific(value)),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+
+    /// Returns the transport_service enum value.
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::Tcp => 0,
+            Self::Udp => 1,
+            Self::Ftp => 2,
+            Self::Smtp => 3,
+            Self::Sms => 4,
+            Self::Hdlc => 5,
+            Self::MBus => 6,
+            Self::ZigBee => 7,
+            Self::ManufacturerSpecific(value) => value,
+        }
+    }
+}
+
+/// Message type of `send_destination_and_method`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MessageType {
+    /// A-XDR encoded xDLMS APDU
+    AxdrApdu,
+    /// XML encoded xDLMS APDU
+    XmlApdu,
+    /// Manufacturer specific (128-255)
+    ManufacturerSpecific(u8),
+}
+
+impl MessageType {
+    /// Converts a message enum value.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::AxdrApdu),
+            1 => Ok(Self::XmlApdu),
+            128..=255 => Ok(Self::ManufacturerSpecific(value)),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+
+    /// Returns the message enum value.
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::AxdrApdu => 0,
+            Self::XmlApdu => 1,
+            Self::ManufacturerSpecific(value) => value,
+        }
+    }
+}
+
+/// Where and how pushes are sent (attribute 3).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SendDestinationAndMethod {
+    /// Transport service
+    pub transport_service: TransportService,
+    /// Destination address, e.g. `b"192.0.2.1:4059"`
+    pub destination: Vec<u8>,
+    /// Encoding of the notification
+    pub message: MessageType,
+}
+
+impl SendDestinationAndMethod {
+    /// Encodes the destination as `{transport_service, destination, message}`.
+    pub fn to_data(&self) -> Data {
+        Data::Structure(vec![
+            Data::Enum(self.transport_service.to_u8()),
+            Data::OctetString(self.destination.clone()),
+            Data::Enum(self.message.to_u8()),
+        ])
+    }
+
+    /// Parses `{transport_service, destination, message}`.
+    pub fn from_data(data: &Data) -> Result<Self, DataAccessResult> {
+        match data {
+            Data::Structure(fields) => match fields.as_slice() {
+                [
+                    Data::Enum(transport_service),
+                    Data::OctetString(destination),
+                    Data::Enum(message),
+                ] => Ok(Self {
+                    transport_service: TransportService::from_u8(*transport_service)?,
+                    destination: destination.clone(),
+                    message: MessageType::from_u8(*message)?,
+                }),
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// A period during which pushes may be sent (element of attribute 4).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CommunicationWindow {
+    /// Local start time, wildcards allowed in the date
+    pub start_time: DateTime,
+    /// Local end time (excluded), wildcards allowed in the date
+    pub end_time: DateTime,
+}
+
+impl CommunicationWindow {
+    /// Returns whether `now` falls within the window.
+    pub fn contains(&self, now: u32) -> bool {
+        let now = i64::from(now);
+        matching_days(&self.start_time.date, now.div_euclid(86_400)).any(|start_day| {
+            let start = start_day * 86_400 + i64::from(seconds_of_day(&self.start_time.time));
+            let end = matching_days(&self.end_time.date, start_day + 1)
+                .map(|day| day * 86_400 + i64::from(seconds_of_day(&self.end_time.time)))
+                .filter(|end| *end > start)
+                .min();
+            start <= now && end.is_some_and(|end| now < end)
+        })
+    }
+}
+
+/// The days up to `last_day` a date may refer to: the date itself if fully
+/// specified, otherwise `last_day` and the day before if they match.
+fn matching_days(date: &Date, last_day: i64) -> impl Iterator<Item = i64> + '_ {
+    let specified = date.year != 0xFFFF
+        && (1..=12).contains(&date.month)
+        && (1..=31).contains(&date.day_of_month);
+    let days = if specified {
+        let day = days_from_civil(date.year.into(), date.month.into(), date.day_of_month.into());
+        day..=day
+    } else {
+        last_day - 1..=last_day
+    };
+    days.filter(move |day| date_matches(date, *day))
+}
+
+/// Local date and time of seconds since 1970-01-01, without deviation.
+pub(crate) fn date_time_at(now: u32) -> DateTime {
+    let days = i64::from(now / 86_400);
+    let seconds = now % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    DateTime::new(
+        Date::new(year as u16, month as u8, day as u8, weekday(days) as u8),
+        Time::new(
+            Some((seconds / 3_600) as u8),
+            Some((seconds / 60 % 60) as u8),
+            Some((seconds % 60) as u8),
+            Some(0),
+        ),
+        None,
+        None,
+    )
+}
+
+/// Random delay in `[0, max]` seconds.
+fn random_delay(max: u16) -> u32 {
+    let mut bytes = [0; 2];
+    if max == 0 || getrandom::getrandom(&mut bytes).is_err() {
+        return 0;
+    }
+    u32::from(u16::from_be_bytes(bytes)) % (u32::from(max) + 1)
+}
+
+/// Sends the notifications of a Push Setup, e.g. over a TCP connection to
+/// the destination.
+pub trait PushSink: fmt::Debug + Send {
+    /// Sends a notification; returns whether it was delivered.
+    fn send(
+        &mut self,
+        destination: &SendDestinationAndMethod,
+        notification: &DataNotification,
+    ) -> bool;
+}
+
+/// A triggered push not sent yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PendingPush {
+    /// When to send, drawn once within a window
+    attempt_at: Option<u32>,
+    /// Failed attempts so far
+    failures: u8,
+}
+
+/// Push Setup object - COSEM Interface Class 40
+///
+/// Reference: Blue Book 4.4.8
+#[derive(Debug)]
+pub struct PushSetup {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Attributes pushed
+    pub push_object_list: Vec<CaptureObjectDefinition>,
+    /// Attribute 3: Destination
+    pub send_destination_and_method: SendDestinationAndMethod,
+    /// Attribute 4: Windows allowed for pushing, any time if empty
+    pub communication_window: Vec<CommunicationWindow>,
+    /// Attribute 5: Upper bound of the random delay in seconds
+    pub randomisation_start_interval: u16,
+    /// Attribute 6: Attempts after the first failed one
+    pub number_of_retries: u8,
+    /// Attribute 7: Seconds between attempts
+    pub repetition_delay: u16,
+    sink: Option<Box<dyn PushSink>>,
+    pending: Option<PendingPush>,
+    invoke_id: u32,
+}
+
+impl PushSetup {
+    /// Creates a Push Setup with an empty object list, pushing over TCP to an
+    /// empty destination at any time, without delay or retries.
+    pub fn new(logical_name: ObisCode) -> Self {
+        Self {
+            logical_name,
+            push_object_list: Vec::new(),
+            send_destination_and_method: SendDestinationAndMethod {
+                transport_service: TransportService::Tcp,
+                destination: Vec::new(),
+                message: MessageType::AxdrApdu,
+            },
+            communication_window: Vec::new(),
+            randomisation_start_interval: 0,
+            number_of_retries: 0,
+            repetition_delay: 0,
+            sink: None,
+            pending: None,
+            invoke_id: 0,
+        }
+    }
+
+    /// Adds an attribute to push (builder style).
+    pub fn with_push_object(mut self, object: CaptureObjectDefinition) -> Self {
+        self.push_object_list.push(object);
+        self
+    }
+
+    /// Sets the destination (builder style).
+    pub fn with_destination(mut self, destination: SendDestinationAndMethod) -> Self {
+        self.send_destination_and_method = destination;
+        self
+    }
+
+    /// Adds a communication window (builder style).
+    pub fn with_communication_window(mut self, window: CommunicationWindow) -> Self {
+        self.communication_window.push(window);
+        self
+    }
+
+    /// Sets the upper bound of the random delay in seconds (builder style).
+    pub fn with_randomisation_start_interval(mut self, seconds: u16) -> Self {
+        self.randomisation_start_interval = seconds;
+        self
+    }
+
+    /// Sets the number of retries and the delay between attempts in seconds
+    /// (builder style).
+    pub fn with_retries(mut self, number_of_retries: u8, repetition_delay: u16) -> Self {
+        self.number_of_retries = number_of_retries;
+        self.repetition_delay = repetition_delay;
+        self
+    }
+
+    /// Sets the sink sending the notifications (builder style).
+    ///
+    /// Without a sink, every attempt fails.
+    pub fn with_sink(mut self, sink: Box<dyn PushSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Triggers a push, as the `push` method does.
+    ///
+    /// A push already pending is not triggered twice.
+    pub fn trigger(&mut self) {
+        self.pending.get_or_insert(PendingPush { attempt_at: None, failures: 0 });
+    }
+
+    /// Returns whether a triggered push was neither sent nor given up yet.
+    pub fn pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Returns when the pending push is sent, once drawn within a window.
+    pub fn next_attempt(&self) -> Option<u32> {
+        self.pending.and_then(|pending| pending.attempt_at)
+    }
+
+    /// Returns whether pushing is allowed at `now`.
+    pub fn in_window(&self, now: u32) -> bool {
+        self.communication_window.is_empty()
+            || self.communication_window.iter().any(|window| window.contains(now))
+    }
+
+    /// Assembles a notification stamped `now` from the pushed attributes.
+    ///
+    /// The attributes are read from `objects`, except those of this object;
+    /// a non-zero `data_index` selects that element (1-based) of a structure.
+    /// Attributes that cannot be read are pushed as `Data::Null`.
+    pub fn push(&mut self, now: u32, objects: &ObjectRegistry) -> DataNotification {
+        let body = self
+            .push_object_list
+            .iter()
+            .map(|object| {
+                if object.class_id == PUSH_SETUP_CLASS_ID
+                    && object.logical_name == self.logical_name
+                {
+                    self.read_own_attribute(object)
+                } else {
+                    read_capture_object(objects, object)
+                }
+                .unwrap_or(Data::Null)
+            })
+            .collect();
+        self.invoke_id = (self.invoke_id + 1) & 0x00ff_ffff;
+        DataNotification::new(self.invoke_id, date_time_at(now), Data::Structure(body))
+    }
+
+    /// Sends the pending push if due at `now`, scheduling a retry on failure.
+    pub fn update(&mut self, now: u32, objects: &ObjectRegistry) {
+        let Some(mut pending) = self.pending else {
+            return;
+        };
+        if !self.in_window(now) {
+            pending.attempt_at = None;
+            self.pending = Some(pending);
+            return;
+        }
+        let attempt_at = *pending
+            .attempt_at
+            .get_or_insert_with(|| now + random_delay(self.randomisation_start_interval));
+        if now < attempt_at {
+            self.pending = Some(pending);
+            return;
+        }
+
+        let notification = self.push(now, objects);
+        let destination = &self.send_destination_and_method;
+        let delivered =
+            self.sink.as_mut().is_some_and(|sink| sink.send(destination, &notification));
+        self.pending = if delivered || pending.failures >= self.number_of_retries {
+            None
+        } else {
+            Some(PendingPush {
+                attempt_at: Some(now + u32::from(self.repetition_delay)),
+                failures: pending.failures + 1,
+            })
+        };
+    }
+
+    fn read_own_attribute(&self, object: &CaptureObjectDefinition) -> Option<Data> {
+        match (object.data_index, self.get_attribute(object.attribute_index).ok()?) {
+            (0, value) => Some(value),
+            (index, Data::Structure(mut elements)) => {
+                let index = usize::from(index) - 1;
+                (index < elements.len()).then(|| elements.swap_remove(index))
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_push_object_list(
+        data: &Data,
+    ) -> Result<Vec<CaptureObjectDefinition>, DataAccessResult> {
+        match data {
+            Data::Structure(objects) => objects
+                .iter()
+                .map(|object| {
+                    CaptureObjectDefinition::from_data(object)
+                        .map_err(|_| DataAccessResult::TypeUnmatched)
+                })
+                .collect(),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+
+    fn parse_communication_window(
+        data: &Data,
+    ) -> Result<Vec<CommunicationWindow>, DataAccessResult> {
+        match data {
+            Data::Structure(windows) => windows
+                .iter()
+                .map(|window| match window {
+                    Data::Structure(times) => match times.as_slice() {
+                        [Data::DateTime(start_time), Data::DateTime(end_time)] => {
+                            Ok(CommunicationWindow {
+                                start_time: start_time.clone(),
+                                end_time: end_time.clone(),
+                            })
+                        }
+                        _ => Err(DataAccessResult::TypeUnmatched),
+                    },
+                    _ => Err(DataAccessResult::TypeUnmatched),
+                })
+                .collect(),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+impl CosemObject for PushSetup {
+    fn class_id(&self) -> u16 {
+        PUSH_SETUP_CLASS_ID
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::Structure(
+                self.push_object_list
+                    .iter()
+                    .map(|object| {
+                        Data::Structure(vec![
+                            Data::LongUnsigned(object.class_id),
+                            Data::OctetString(object.logical_name.encode().to_vec()),
+                            Data::Integer(object.attribute_index),
+                            Data::LongUnsigned(object.data_index),
+                        ])
+                    })
+                    .collect(),
+            )),
+            3 => Ok(self.send_destination_and_method.to_data()),
+            4 => Ok(Data::Structure(
+                self.communication_window
+                    .iter()
+                    .map(|window| {
+                        Data::Structure(vec![
+                            Data::DateTime(window.start_time.clone()),
+                            Data::DateTime(window.end_time.clone()),
+                        ])
+                    })
+                    .collect(),
+            )),
+            5 => Ok(Data::LongUnsigned(self.randomisation_start_interval)),
+            6 => Ok(Data::Unsigned(self.number_of_retries)),
+            7 => Ok(Data::LongUnsigned(self.repetition_delay)),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (2, value) => self.push_object_list = Self::parse_push_object_list(&value)?,
+            (3, value) => {
+                self.send_destination_and_method = SendDestinationAndMethod::from_data(&value)?
+            }
+            (4, value) => self.communication_window = Self::parse_communication_window(&value)?,
+            (5, Data::LongUnsigned(seconds)) => self.randomisation_start_interval = seconds,
+            (6, Data::Unsigned(retries)) => self.number_of_retries = retries,
+            (7, Data::LongUnsigned(seconds)) => self.repetition_delay = seconds,
+            (5..=7, _) => return Err(DataAccessResult::TypeUnmatched),
+            (1, _) => return Err(DataAccessResult::ReadWriteDenied),
+            _ => return Err(DataAccessResult::ObjectUndefined),
+        }
+        Ok(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        _params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match method_id {
+            1 => {
+                self.trigger();
+                Ok(None)
+            }
+            _ => Err(ActionResult::ObjectUndefined),
+        }
+    }
+
+    /// Sends the pending push if due, reading the pushed attributes from the
+    /// registry.
+    fn tick(
+        &mut self,
+        now: u32,
+        objects: &ObjectRegistry,
+        _executor: &mut dyn FnMut(&ScriptReference),
+    ) {
+        self.update(now, objects);
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [2, 3, 4, 5, 6, 7]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, [2, 3, 4, 5, 6, 7]).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::register::Register;
+    use crate::{ScalerUnit, Unit};
+    use std::sync::{Arc, Mutex};
+
+    const PUSH_SETUP: ObisCode = ObisCode { a: 0, b: 0, c: 25, d: 9, e: 0, f: 255 };
+    const ENERGY: ObisCode = ObisCode { a: 1, b: 0, c: 1, d: 8, e: 0, f: 255 };
+
+    /// 2025-02-01 00:00 (Saturday)
+    const MIDNIGHT: u32 = 1_738_368_000;
+
+    /// Records the attempts and delivers from the `fail_first`+1-th on.
+    #[derive(Debug, Clone, Default)]
+    struct RecordingSink {
+        attempts: Arc<Mutex<Vec<DataNotification>>>,
+        fail_first: usize,
+    }
+
+    impl PushSink for RecordingSink {
+        fn send(
+            &mut self,
+            _destination: &SendDestinationAndMethod,
+            notification: &DataNotification,
+        ) -> bool {
+            let mut attempts = self.attempts.lock().unwrap();
+            attempts.push(notification.clone());
+            attempts.len() > self.fail_first
+        }
+    }
+
+    fn energy() -> ObjectRegistry {
+        ObjectRegistry::new().with_object(Register::new(
+            ENERGY,
+            Data::DoubleLongUnsigned(12345),
+            ScalerUnit { scaler: 0, unit: Unit::WattHour },
+        ))
+    }
+
+    fn column(
+        class_id: u16,
+        logical_name: ObisCode,
+        attribute_index: i8,
+    ) -> CaptureObjectDefinition {
+        CaptureObjectDefinition { class_id, logical_name, attribute_index, data_index: 0 }
+    }
+
+    fn daily(start_hour: u8, end_hour: u8) -> CommunicationWindow {
+        let at = |hour| {
+            DateTime::new(
+                Date::new(0xFFFF, 0xFF, 0xFF, 0xFF),
+                Time::new(Some(hour), Some(0), Some(0), Some(0)),
+                None,
+                None,
+            )
+        };
+        CommunicationWindow { start_time: at(start_hour), end_time: at(end_hour) }
+    }
+
+    #[test]
+    fn test_push_notification() {
+        let objects = energy();
+        let mut push_setup = PushSetup::new(PUSH_SETUP)
+            .with_push_object(column(40, PUSH_SETUP, 1))
+            .with_push_object(column(3, ENERGY, 2))
+            .with_push_object(CaptureObjectDefinition { data_index: 2, ..column(3, ENERGY, 3) })
+            .with_push_object(column(3, ObisCode::new(1, 0, 2, 8, 0, 255), 2));
+
+        let notification = push_setup.push(MIDNIGHT + 3_661, &objects);
+        assert_eq!(notification.invoke_id(), 1);
+        assert_eq!(
+            notification.date_time(),
+            &DateTime::new(
+                Date::new(2025, 2, 1, 6),
+                Time::new(Some(1), Some(1), Some(1), Some(0)),
+                None,
+                None
+            )
+        );
+        assert_eq!(
+            notification.notification_body(),
+            &Data::Structure(vec![
+                Data::OctetString(PUSH_SETUP.encode().to_vec()),
+                Data::DoubleLongUnsigned(12345),
+                Data::Enum(Unit::WattHour as u8),
+                Data::Null,
+            ])
+        );
+        assert_eq!(push_setup.push(MIDNIGHT, &objects).invoke_id(), 2);
+    }
+
+    #[test]
+    fn test_window_and_retries() {
+        let sink = RecordingSink { fail_first: 2, ..Default::default() };
+        let attempts = sink.attempts.clone();
+        let mut objects = energy().with_object(
+            PushSetup::new(PUSH_SETUP)
+                .with_push_object(column(3, ENERGY, 2))
+                .with_communication_window(daily(8, 9))
+                .with_retries(3, 600)
+                .with_sink(Box::new(sink)),
+        );
+
+        // Triggered at 07:00, sent from 08:00 and retried every 10 minutes.
+        assert_eq!(objects.invoke_method(40, &PUSH_SETUP, 1, Some(Data::Integer(0))), Ok(None));
+        for minutes in [7 * 60, 8 * 60, 8 * 60 + 5, 8 * 60 + 10, 8 * 60 + 20, 8 * 60 + 30] {
+            objects.tick(MIDNIGHT + minutes * 60, &mut |_| {});
+        }
+        let attempts = attempts.lock().unwrap();
+        let times: Vec<_> =
+            attempts.iter().map(|attempt| attempt.date_time().time.minute).collect();
+        assert_eq!(times, [Some(0), Some(10), Some(20)]);
+        assert_eq!(attempts[2].invoke_id(), 3);
+
+        // Given up after the retries, the second one in the next window.
+        let mut push_setup =
+            PushSetup::new(PUSH_SETUP).with_communication_window(daily(8, 9)).with_retries(1, 600);
+        push_setup.trigger();
+        push_setup.update(MIDNIGHT + 8 * 3_600 + 55 * 60, &objects);
+        assert_eq!(push_setup.next_attempt(), Some(MIDNIGHT + 9 * 3_600 + 5 * 60));
+        push_setup.update(MIDNIGHT + 9 * 3_600 + 5 * 60, &objects);
+        assert_eq!(push_setup.next_attempt(), None);
+        assert!(push_setup.pending());
+        push_setup.update(MIDNIGHT + 86_400 + 8 * 3_600, &objects);
+        assert!(!push_setup.pending());
+    }
+
+    #[test]
+    fn test_randomisation() {
+        let sink = RecordingSink::default();
+        let attempts = sink.attempts.clone();
+        let objects = energy();
+        let mut push_setup = PushSetup::new(PUSH_SETUP)
+            .with_randomisation_start_interval(60)
+            .with_sink(Box::new(sink));
+
+        push_setup.trigger();
+        push_setup.update(MIDNIGHT, &objects);
+        let attempt_at = push_setup.next_attempt().unwrap_or(MIDNIGHT);
+        assert!((MIDNIGHT..=MIDNIGHT + 60).contains(&attempt_at));
+
+        push_setup.update(MIDNIGHT + 60, &objects);
+        assert!(!push_setup.pending());
+        assert_eq!(attempts.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_attributes() {
+        let mut push_setup = PushSetup::new(PUSH_SETUP);
+        let destination = SendDestinationAndMethod {
+            transport_service: TransportService::Udp,
+            destination: b"192.0.2.1:4059".to_vec(),
+            message: MessageType::AxdrApdu,
+        };
+        let list = Data::Structure(vec![Data::Structure(vec![
+            Data::LongUnsigned(3),
+            Data::OctetString(ENERGY.encode().to_vec()),
+            Data::Integer(2),
+            Data::LongUnsigned(0),
+        ])]);
+        let windows = Data::Structure(vec![Data::Structure(vec![
+            Data::DateTime(daily(8, 9).start_time),
+            Data::DateTime(daily(8, 9).end_time),
+        ])]);
+
+        assert_eq!(push_setup.set_attribute(2, list.clone()), Ok(()));
+        assert_eq!(push_setup.set_attribute(3, destination.to_data()), Ok(()));
+        assert_eq!(push_setup.set_attribute(4, windows.clone()), Ok(()));
+        assert_eq!(push_setup.set_attribute(5, Data::LongUnsigned(30)), Ok(()));
+        assert_eq!(push_setup.set_attribute(6, Data::Unsigned(2)), Ok(()));
+        assert_eq!(push_setup.set_attribute(7, Data::LongUnsigned(120)), Ok(()));
+        assert_eq!(push_setup.get_attribute(2), Ok(list));
+        assert_eq!(push_setup.get_attribute(3), Ok(destination.to_data()));
+        assert_eq!(push_setup.get_attribute(4), Ok(windows));
+        assert_eq!(push_setup.communication_window, [daily(8, 9)]);
+        assert_eq!(push_setup.randomisation_start_interval, 30);
+        assert_eq!(push_setup.number_of_retries, 2);
+        assert_eq!(push_setup.repetition_delay, 120);
+
+        let unknown_transport =
+            Data::Structure(vec![Data::Enum(100), Data::OctetString(vec![]), Data::Enum(0)]);
+        assert_eq!(
+            push_setup.set_attribute(3, unknown_transport),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        assert_eq!(
+            push_setup.set_attribute(6, Data::LongUnsigned(2)),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        assert_eq!(push_setup.set_attribute(1, Data::Null), Err(DataAccessResult::ReadWriteDenied));
+        assert_eq!(push_setup.invoke_method(2, None), Err(ActionResult::ObjectUndefined));
+    }
+}
@@ -35,6 +35,8 @@ use crate::data::Data;
 use crate::get::DataAccessResult;
 use crate::obis_code::ObisCode;
 use crate::unit::ScalerUnit;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
 
 #[cfg(feature = "encode")]
 use crate::action::ActionResult;
@@ -203,6 +205,16 @@ impl CosemObject for Register {
             _ => Err(ActionResult::ObjectUndefined),
         }
     }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [2, 3]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, [2, 3]).map(drop)
+    }
 }
 
 #[cfg(test)]
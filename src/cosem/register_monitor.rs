@@ -0,0 +1,393 @@
+//! COSEM Interface Class 21: Register Monitor
+//!
+//! A Register Monitor watches an attribute of another object (typically the
+//! value of a Register) against a list of thresholds, and executes a script
+//! when the value crosses a threshold upwards or downwards.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.16.0.0.255)
+//! - Attribute 2: `thresholds` - Array of values, of the type of the
+//!   monitored value
+//! - Attribute 3: `monitored_value` - `{class_id, logical_name, attribute_index}`
+//! - Attribute 4: `actions` - Array of `{action_up, action_down}`, one per
+//!   threshold, each `{script_logical_name, script_selector}`
+//!
+//! ## Methods
+//! None.
+//!
+//! ## Evaluation
+//!
+//! The value is above a threshold when it is greater than the threshold.
+//! [`RegisterMonitor::update`] compares a value with the previous one: a
+//! threshold passed upwards fires its `action_up`, downwards its
+//! `action_down`. The first value only sets the reference. In a registry,
+//! [`ObjectRegistry::tick`] reads the monitored value and updates the
+//! monitors. Values and thresholds are compared numerically; other types
+//! never cross.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::register_monitor::{ActionSet, RegisterMonitor, ValueDefinition};
+//! use dlms_cosem::cosem::script_table::ScriptReference;
+//! use dlms_cosem::{Data, ObisCode};
+//!
+//! let script = |selector| ScriptReference {
+//!     script_logical_name: ObisCode::new(0, 0, 10, 0, 106, 255),
+//!     script_selector: selector,
+//! };
+//! let mut monitor = RegisterMonitor::new(
+//!     ObisCode::new(0, 0, 16, 0, 0, 255),
+//!     ValueDefinition { class_id: 3, logical_name: ObisCode::new(1, 0, 1, 7, 0, 255), attribute_index: 2 },
+//! )
+//! .with_threshold(Data::DoubleLongUnsigned(5_000), ActionSet { action_up: script(1), action_down: script(2) });
+//!
+//! let mut fired = Vec::new();
+//! monitor.update(&Data::DoubleLongUnsigned(4_000), &mut |script| fired.push(script.script_selector));
+//! monitor.update(&Data::DoubleLongUnsigned(6_000), &mut |script| fired.push(script.script_selector));
+//! monitor.update(&Data::DoubleLongUnsigned(3_000), &mut |script| fired.push(script.script_selector));
+//! assert_eq!(fired, [1, 2]);
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::action::ActionResult;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::cosem::script_table::ScriptReference;
+use crate::cosem::{CosemObject, ObjectRegistry};
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// An attribute of another object, as monitored by Register Monitor and Limiter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ValueDefinition {
+    /// Class id of the object
+    pub class_id: u16,
+    /// Logical name of the object
+    pub logical_name: ObisCode,
+    /// Attribute read
+    pub attribute_index: i8,
+}
+
+impl ValueDefinition {
+    /// Reads the value from `objects`.
+    pub fn read(&self, objects: &ObjectRegistry) -> Result<Data, DataAccessResult> {
+        objects.lookup(self.class_id, &self.logical_name)?.get_attribute(self.attribute_index)
+    }
+
+    /// Encodes the definition as `{class_id, logical_name, attribute_index}`.
+    pub fn to_data(&self) -> Data {
+        Data::Structure(vec![
+            Data::LongUnsigned(self.class_id),
+            Data::OctetString(self.logical_name.encode().to_vec()),
+            Data::Integer(self.attribute_index),
+        ])
+    }
+
+    /// Parses a `{class_id, logical_name, attribute_index}` structure.
+    pub fn from_data(data: &Data) -> Result<Self, DataAccessResult> {
+        match data {
+            Data::Structure(elements) => match elements.as_slice() {
+                [
+                    Data::LongUnsigned(class_id),
+                    Data::OctetString(name),
+                    Data::Integer(attribute_index),
+                ] => Ok(Self {
+                    class_id: *class_id,
+                    logical_name: ObisCode::try_from(name.as_slice())
+                        .map_err(|_| DataAccessResult::TypeUnmatched)?,
+                    attribute_index: *attribute_index,
+                }),
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Scripts executed when a threshold is crossed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ActionSet {
+    /// Executed when the value rises above the threshold
+    pub action_up: ScriptReference,
+    /// Executed when the value falls to or below the threshold
+    pub action_down: ScriptReference,
+}
+
+/// Register Monitor object - COSEM Interface Class 21
+///
+/// Reference: Blue Book 4.5.6
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RegisterMonitor {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Thresholds
+    pub thresholds: Vec<Data>,
+    /// Attribute 3: Monitored value
+    pub monitored_value: ValueDefinition,
+    /// Attribute 4: Action sets, one per threshold
+    pub actions: Vec<ActionSet>,
+    /// Value of the previous update
+    last_value: Option<f64>,
+}
+
+impl RegisterMonitor {
+    /// Creates a Register Monitor without thresholds.
+    pub fn new(logical_name: ObisCode, monitored_value: ValueDefinition) -> Self {
+        Self {
+            logical_name,
+            thresholds: Vec::new(),
+            monitored_value,
+            actions: Vec::new(),
+            last_value: None,
+        }
+    }
+
+    /// Adds a threshold and its action set (builder style).
+    pub fn with_threshold(mut self, threshold: Data, actions: ActionSet) -> Self {
+        self.thresholds.push(threshold);
+        self.actions.push(actions);
+        self
+    }
+
+    /// Compares `value` with the previous value, passing the scripts of the
+    /// thresholds crossed to `executor`, in threshold order.
+    pub fn update(&mut self, value: &Data, executor: &mut dyn FnMut(&ScriptReference)) {
+        let Some(value) = numeric(value) else {
+            return;
+        };
+        if let Some(last) = self.last_value.replace(value) {
+            for (threshold, actions) in self.thresholds.iter().zip(&self.actions) {
+                match numeric(threshold) {
+                    Some(threshold) if last <= threshold && threshold < value => {
+                        executor(&actions.action_up)
+                    }
+                    Some(threshold) if value <= threshold && threshold < last => {
+                        executor(&actions.action_down)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl CosemObject for RegisterMonitor {
+    fn class_id(&self) -> u16 {
+        21
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::Structure(self.thresholds.clone())),
+            3 => Ok(self.monitored_value.to_data()),
+            4 => Ok(Data::Structure(
+                self.actions
+                    .iter()
+                    .map(|actions| {
+                        Data::Structure(vec![
+                            actions.action_up.to_data(),
+                            actions.action_down.to_data(),
+                        ])
+                    })
+                    .collect(),
+            )),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (2, Data::Structure(thresholds)) => self.thresholds = thresholds,
+            (3, value) => {
+                self.monitored_value = ValueDefinition::from_data(&value)?;
+                self.last_value = None;
+            }
+            (4, Data::Structure(actions)) => {
+                self.actions = actions
+                    .iter()
+                    .map(|actions| match actions {
+                        Data::Structure(pair) => match pair.as_slice() {
+                            [up, down] => Ok(ActionSet {
+                                action_up: ScriptReference::from_data(up)?,
+                                action_down: ScriptReference::from_data(down)?,
+                            }),
+                            _ => Err(DataAccessResult::TypeUnmatched),
+                        },
+                        _ => Err(DataAccessResult::TypeUnmatched),
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            (2 | 4, _) => return Err(DataAccessResult::TypeUnmatched),
+            (1, _) => return Err(DataAccessResult::ReadWriteDenied),
+            _ => return Err(DataAccessResult::ObjectUndefined),
+        }
+        Ok(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        _method_id: i8,
+        _params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        Err(ActionResult::ObjectUndefined)
+    }
+
+    /// Reads the monitored value and updates the monitor; unreadable values
+    /// are skipped.
+    fn tick(
+        &mut self,
+        _now: u32,
+        objects: &ObjectRegistry,
+        executor: &mut dyn FnMut(&ScriptReference),
+    ) {
+        if let Ok(value) = self.monitored_value.read(objects) {
+            self.update(&value, executor);
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [2, 3, 4]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, [2, 3, 4]).map(|_| ())
+    }
+}
+
+/// Numeric value of a simple data type, for threshold comparisons.
+pub(crate) fn numeric(data: &Data) -> Option<f64> {
+    match data {
+        Data::Integer(value) => Some((*value).into()),
+        Data::Unsigned(value) | Data::Enum(value) => Some((*value).into()),
+        Data::Long(value) => Some((*value).into()),
+        Data::LongUnsigned(value) => Some((*value).into()),
+        Data::DoubleLong(value) => Some((*value).into()),
+        Data::DoubleLongUnsigned(value) => Some((*value).into()),
+        Data::Long64(value) => Some(*value as f64),
+        Data::Long64Unsigned(value) => Some(*value as f64),
+        Data::Float32(value) => Some((*value).into()),
+        Data::Float64(value) => Some(*value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::register::Register;
+    use crate::cosem::script_table::{ScriptAction, ScriptTable};
+    use crate::{ScalerUnit, Unit};
+
+    const POWER: ObisCode = ObisCode { a: 1, b: 0, c: 1, d: 7, e: 0, f: 255 };
+    const MONITOR: ObisCode = ObisCode { a: 0, b: 0, c: 16, d: 0, e: 0, f: 255 };
+    const SCRIPTS: ObisCode = ObisCode { a: 0, b: 0, c: 10, d: 0, e: 106, f: 255 };
+
+    fn script(selector: u16) -> ScriptReference {
+        ScriptReference { script_logical_name: SCRIPTS, script_selector: selector }
+    }
+
+    fn monitor() -> RegisterMonitor {
+        RegisterMonitor::new(
+            MONITOR,
+            ValueDefinition { class_id: 3, logical_name: POWER, attribute_index: 2 },
+        )
+        .with_threshold(
+            Data::DoubleLongUnsigned(1_000),
+            ActionSet { action_up: script(1), action_down: script(2) },
+        )
+        .with_threshold(
+            Data::DoubleLongUnsigned(2_000),
+            ActionSet { action_up: script(3), action_down: script(4) },
+        )
+    }
+
+    #[test]
+    fn test_crossings() {
+        let mut monitor = monitor();
+        let mut fired = Vec::new();
+        let mut update = |monitor: &mut RegisterMonitor, value| {
+            monitor.update(&Data::DoubleLongUnsigned(value), &mut |script| {
+                fired.push(script.script_selector)
+            })
+        };
+
+        update(&mut monitor, 1_500);
+        update(&mut monitor, 1_800);
+        // Both thresholds at once, and exactly on a threshold.
+        update(&mut monitor, 2_500);
+        update(&mut monitor, 1_000);
+        update(&mut monitor, 1_001);
+        assert_eq!(fired, [3, 2, 4, 1]);
+    }
+
+    #[test]
+    fn test_registry_tick() {
+        let power = |value| {
+            Register::new(
+                POWER,
+                Data::DoubleLongUnsigned(value),
+                ScalerUnit { scaler: 0, unit: Unit::Watt },
+            )
+        };
+        let mut objects = ObjectRegistry::new()
+            .with_object(power(500))
+            .with_object(monitor())
+            .with_object(ScriptTable::new(SCRIPTS).with_script(
+                1,
+                vec![ScriptAction::write(
+                    3,
+                    POWER,
+                    3,
+                    Data::Structure(vec![Data::Integer(3), Data::Enum(Unit::Watt as u8)]),
+                )],
+            ));
+        let mut due = Vec::new();
+
+        objects.tick(0, &mut |script| due.push(*script));
+        objects.insert(alloc::boxed::Box::new(power(1_200)));
+        objects.tick(1, &mut |script| due.push(*script));
+        assert_eq!(due, [script(1)]);
+        for script in due {
+            script.execute(&mut objects).unwrap();
+        }
+        assert_eq!(
+            objects.get(3, &POWER).unwrap().get_attribute(3),
+            Ok(Data::Structure(vec![Data::Integer(3), Data::Enum(Unit::Watt as u8)]))
+        );
+    }
+
+    #[test]
+    fn test_attributes() {
+        let monitor = monitor();
+        let mut written = RegisterMonitor::new(
+            MONITOR,
+            ValueDefinition { class_id: 1, logical_name: MONITOR, attribute_index: 2 },
+        );
+        for id in 2..=4 {
+            written.set_attribute(id, monitor.get_attribute(id).unwrap()).unwrap();
+        }
+        assert_eq!(written, monitor);
+        assert_eq!(
+            written.set_attribute(4, Data::Structure(vec![Data::Unsigned(1)])),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        assert_eq!(written.set_attribute(1, Data::Null), Err(DataAccessResult::ReadWriteDenied));
+    }
+}
@@ -0,0 +1,262 @@
+//! Object registry
+//!
+//! A collection of COSEM objects keyed by `(class_id, logical_name)`, the way
+//! objects are addressed by the LN services (GET/SET/ACTION). It is the object
+//! model of a logical device, shared by the server and by objects that refer to
+//! other objects (e.g. capture objects of a ProfileGeneric).
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::data::DataObject;
+//! use dlms_cosem::cosem::register::Register;
+//! use dlms_cosem::cosem::ObjectRegistry;
+//! use dlms_cosem::{Data, ObisCode, ScalerUnit, Unit};
+//!
+//! let energy = ObisCode::new(1, 0, 1, 8, 0, 255);
+//! let registry = ObjectRegistry::new()
+//!     .with_object(DataObject::new(ObisCode::new(0, 0, 96, 1, 0, 255), Data::Unsigned(1)))
+//!     .with_object(Register::new(
+//!         energy,
+//!         Data::DoubleLongUnsigned(12345),
+//!         ScalerUnit { scaler: 0, unit: Unit::WattHour },
+//!     ));
+//!
+//! assert_eq!(registry.len(), 2);
+//! assert!(registry.get(3, &energy).is_some());
+//! assert!(registry.get(1, &energy).is_none());
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::btree_map::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::action::ActionResult;
+use crate::cosem::CosemObject;
+use crate::cosem::script_table::ScriptReference;
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Boxed COSEM object stored in a registry.
+pub type BoxedCosemObject = Box<dyn CosemObject + Send>;
+
+/// COSEM objects keyed by `(class_id, logical_name)`.
+///
+/// Iteration order is by class id, then logical name.
+#[derive(Default)]
+pub struct ObjectRegistry {
+    objects: BTreeMap<(u16, ObisCode), BoxedCosemObject>,
+}
+
+impl ObjectRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an object (builder style), replacing any object with the same key.
+    pub fn with_object(mut self, object: impl CosemObject + Send + 'static) -> Self {
+        self.insert(Box::new(object));
+        self
+    }
+
+    /// Adds an object, returning the object previously registered under the same key.
+    pub fn insert(&mut self, object: BoxedCosemObject) -> Option<BoxedCosemObject> {
+        self.objects.insert((object.class_id(), *object.logical_name()), object)
+    }
+
+    /// Removes and returns an object.
+    pub fn remove(&mut self, class_id: u16, logical_name: &ObisCode) -> Option<BoxedCosemObject> {
+        self.objects.remove(&(class_id, *logical_name))
+    }
+
+    /// Returns the object with the given class id and logical name.
+    pub fn get(&self, class_id: u16, logical_name: &ObisCode) -> Option<&(dyn CosemObject + Send)> {
+        self.objects.get(&(class_id, *logical_name)).map(|object| object.as_ref())
+    }
+
+    /// Returns the object with the given class id and logical name, mutably.
+    pub fn get_mut(
+        &mut self,
+        class_id: u16,
+        logical_name: &ObisCode,
+    ) -> Option<&mut (dyn CosemObject + Send + 'static)> {
+        self.objects.get_mut(&(class_id, *logical_name)).map(|object| object.as_mut())
+    }
+
+    /// Looks up an object for a service request.
+    ///
+    /// Fails with [`DataAccessResult::ObjectClassInconsistent`] if the logical
+    /// name exists with a different class id, and with
+    /// [`DataAccessResult::ObjectUndefined`] if it does not exist at all.
+    pub fn lookup(
+        &self,
+        class_id: u16,
+        logical_name: &ObisCode,
+    ) -> Result<&(dyn CosemObject + Send), DataAccessResult> {
+        self.get(class_id, logical_name).ok_or_else(|| self.missing(logical_name))
+    }
+
+    /// Mutable counterpart of [`lookup`](Self::lookup).
+    pub fn lookup_mut(
+        &mut self,
+        class_id: u16,
+        logical_name: &ObisCode,
+    ) -> Result<&mut (dyn CosemObject + Send + 'static), DataAccessResult> {
+        let error = self.missing(logical_name);
+        self.get_mut(class_id, logical_name).ok_or(error)
+    }
+
+    /// Invokes a method of an object, giving it access to the other objects.
+    ///
+    /// The object is taken out of the registry for the duration of the call
+    /// and passed the remaining objects through
+    /// [`CosemObject::invoke_method_with_objects`].
+    pub fn invoke_method(
+        &mut self,
+        class_id: u16,
+        logical_name: &ObisCode,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        let Some(mut object) = self.remove(class_id, logical_name) else {
+            return Err(match self.missing(logical_name) {
+                DataAccessResult::ObjectClassInconsistent => ActionResult::ObjectClassInconsistent,
+                _ => ActionResult::ObjectUndefined,
+            });
+        };
+        let result = object.invoke_method_with_objects(self, method_id, params);
+        self.insert(object);
+        result
+    }
+
+    /// Ticks every object at `now`, giving it access to the other objects
+    /// (see [`CosemObject::tick`]).
+    ///
+    /// The objects are borrowed while ticking, so scripts are passed to
+    /// `executor` instead of being executed; collect them and execute them
+    /// afterwards with [`ScriptReference::execute`].
+    pub fn tick(&mut self, now: u32, executor: &mut dyn FnMut(&ScriptReference)) {
+        let keys: Vec<_> = self.objects.keys().copied().collect();
+        for (class_id, logical_name) in keys {
+            if let Some(mut object) = self.remove(class_id, &logical_name) {
+                object.tick(now, self, executor);
+                self.insert(object);
+            }
+        }
+    }
+
+    /// Returns `true` if an object with this logical name exists, whatever its class.
+    pub fn contains_logical_name(&self, logical_name: &ObisCode) -> bool {
+        self.objects.keys().any(|(_, name)| name == logical_name)
+    }
+
+    /// Returns the number of objects.
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Returns `true` if the registry holds no objects.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Iterates over all objects.
+    pub fn iter(&self) -> impl Iterator<Item = &(dyn CosemObject + Send)> {
+        self.objects.values().map(|object| object.as_ref() as &(dyn CosemObject + Send))
+    }
+
+    fn missing(&self, logical_name: &ObisCode) -> DataAccessResult {
+        if self.contains_logical_name(logical_name) {
+            DataAccessResult::ObjectClassInconsistent
+        } else {
+            DataAccessResult::ObjectUndefined
+        }
+    }
+}
+
+impl fmt::Debug for ObjectRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.objects.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::data::DataObject;
+    use crate::cosem::register::Register;
+    use crate::{ScalerUnit, Unit};
+
+    #[test]
+    fn test_insert_and_lookup() {
+        let name = ObisCode::new(0, 0, 96, 1, 0, 255);
+        let mut registry =
+            ObjectRegistry::new().with_object(DataObject::new(name, Data::Unsigned(1)));
+
+        assert_eq!(registry.lookup(1, &name).unwrap().get_attribute(2), Ok(Data::Unsigned(1)));
+        registry.lookup_mut(1, &name).unwrap().set_attribute(2, Data::Unsigned(2)).unwrap();
+        assert_eq!(registry.get(1, &name).unwrap().get_attribute(2), Ok(Data::Unsigned(2)));
+
+        let replaced = registry.insert(Box::new(DataObject::new(name, Data::Unsigned(3))));
+        assert!(replaced.is_some());
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn test_lookup_errors() {
+        let name = ObisCode::new(0, 0, 96, 1, 0, 255);
+        let registry = ObjectRegistry::new().with_object(DataObject::new(name, Data::Unsigned(1)));
+
+        assert_eq!(
+            registry.lookup(3, &name).err(),
+            Some(DataAccessResult::ObjectClassInconsistent)
+        );
+        assert_eq!(
+            registry.lookup(1, &ObisCode::new(0, 0, 96, 1, 1, 255)).err(),
+            Some(DataAccessResult::ObjectUndefined)
+        );
+    }
+
+    #[test]
+    fn test_remove() {
+        let name = ObisCode::new(0, 0, 96, 1, 0, 255);
+        let mut registry =
+            ObjectRegistry::new().with_object(DataObject::new(name, Data::Unsigned(1)));
+
+        assert!(registry.remove(1, &name).is_some());
+        assert!(registry.is_empty());
+        assert!(registry.remove(1, &name).is_none());
+    }
+
+    #[test]
+    fn test_invoke_method() {
+        let energy = ObisCode::new(1, 0, 1, 8, 0, 255);
+        let mut registry = ObjectRegistry::new().with_object(Register::new(
+            energy,
+            Data::DoubleLongUnsigned(100),
+            ScalerUnit { scaler: 0, unit: Unit::WattHour },
+        ));
+
+        assert_eq!(
+            registry.invoke_method(3, &energy, 1, Some(Data::DoubleLongUnsigned(0))),
+            Ok(None)
+        );
+        assert_eq!(
+            registry.get(3, &energy).unwrap().get_attribute(2),
+            Ok(Data::DoubleLongUnsigned(0))
+        );
+        assert_eq!(registry.invoke_method(3, &energy, 9, None), Err(ActionResult::ObjectUndefined));
+        assert!(registry.get(3, &energy).is_some());
+
+        assert_eq!(
+            registry.invoke_method(1, &energy, 1, None),
+            Err(ActionResult::ObjectClassInconsistent)
+        );
+        assert_eq!(
+            registry.invoke_method(3, &ObisCode::new(1, 0, 2, 8, 0, 255), 1, None),
+            Err(ActionResult::ObjectUndefined)
+        );
+    }
+}
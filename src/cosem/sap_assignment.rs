@@ -0,0 +1,242 @@
+//! COSEM Interface Class 17: SAP Assignment
+//!
+//! The SAP Assignment object of the management logical device lists the
+//! logical devices of a physical device with the SAP (HDLC upper address or
+//! wrapper port) they are addressed by.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (0.0.41.0.0.255)
+//! - Attribute 2: `SAP_assignment_list` - Array of `(SAP, logical_device_name)`
+//!
+//! ## Methods
+//! - Method 1: `connect_logical_device(data)` - Assigns a SAP to a logical
+//!   device name; an empty name removes the assignment
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::sap_assignment::SapAssignment;
+//! use dlms_cosem::cosem::CosemObject;
+//! use dlms_cosem::Data;
+//!
+//! let sap_assignment = SapAssignment::new()
+//!     .with_logical_device(1, b"SIM0000000000001".to_vec())
+//!     .with_logical_device(17, b"SIM0000000000017".to_vec());
+//!
+//! assert_eq!(sap_assignment.logical_device_name(17), Some(&b"SIM0000000000017"[..]));
+//! let Data::Structure(list) = sap_assignment.get_attribute(2).unwrap() else { unreachable!() };
+//! assert_eq!(list.len(), 2);
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::action::ActionResult;
+use crate::cosem::CosemObject;
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+
+/// Logical name of the SAP Assignment object.
+pub const SAP_ASSIGNMENT: ObisCode = ObisCode { a: 0, b: 0, c: 41, d: 0, e: 0, f: 255 };
+
+/// One element of the SAP assignment list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SapAssignmentElement {
+    /// SAP of the logical device
+    pub sap: u16,
+    /// Logical device name (attribute 2 of 0.0.42.0.0.255 in that device)
+    pub logical_device_name: Vec<u8>,
+}
+
+impl SapAssignmentElement {
+    fn to_data(&self) -> Data {
+        Data::Structure(vec![
+            Data::LongUnsigned(self.sap),
+            Data::OctetString(self.logical_device_name.clone()),
+        ])
+    }
+
+    fn from_data(data: &Data) -> Result<Self, DataAccessResult> {
+        match data {
+            Data::Structure(elements) => match elements.as_slice() {
+                [Data::LongUnsigned(sap), Data::OctetString(name)] => {
+                    Ok(Self { sap: *sap, logical_device_name: name.clone() })
+                }
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// SAP Assignment object - COSEM Interface Class 17
+///
+/// Reference: Blue Book 4.4.6
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SapAssignment {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Logical devices and their SAPs, ordered by SAP
+    pub sap_assignment_list: Vec<SapAssignmentElement>,
+}
+
+impl Default for SapAssignment {
+    fn default() -> Self {
+        Self { logical_name: SAP_ASSIGNMENT, sap_assignment_list: Vec::new() }
+    }
+}
+
+impl SapAssignment {
+    /// Creates an empty SAP Assignment object (0.0.41.0.0.255).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns a SAP to a logical device (builder style).
+    pub fn with_logical_device(mut self, sap: u16, logical_device_name: Vec<u8>) -> Self {
+        self.connect_logical_device(sap, logical_device_name);
+        self
+    }
+
+    /// Assigns a SAP to a logical device, replacing any previous assignment
+    /// of the SAP. An empty name removes the assignment.
+    pub fn connect_logical_device(&mut self, sap: u16, logical_device_name: Vec<u8>) {
+        self.sap_assignment_list.retain(|element| element.sap != sap);
+        if !logical_device_name.is_empty() {
+            let index = self.sap_assignment_list.partition_point(|element| element.sap < sap);
+            self.sap_assignment_list
+                .insert(index, SapAssignmentElement { sap, logical_device_name });
+        }
+    }
+
+    /// Returns the name of the logical device at a SAP, if assigned.
+    pub fn logical_device_name(&self, sap: u16) -> Option<&[u8]> {
+        self.sap_assignment_list
+            .iter()
+            .find(|element| element.sap == sap)
+            .map(|element| element.logical_device_name.as_slice())
+    }
+
+    /// Parses attribute 2 back into elements (client side).
+    pub fn parse_assignment_list(
+        data: &Data,
+    ) -> Result<Vec<SapAssignmentElement>, DataAccessResult> {
+        match data {
+            Data::Structure(elements) => {
+                elements.iter().map(SapAssignmentElement::from_data).collect()
+            }
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+impl CosemObject for SapAssignment {
+    fn class_id(&self) -> u16 {
+        17
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::Structure(
+                self.sap_assignment_list.iter().map(SapAssignmentElement::to_data).collect(),
+            )),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, _value: Data) -> Result<(), DataAccessResult> {
+        match id {
+            1 | 2 => Err(DataAccessResult::ReadWriteDenied),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        parameters: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match method_id {
+            1 => {
+                let element = parameters
+                    .as_ref()
+                    .map(SapAssignmentElement::from_data)
+                    .ok_or(ActionResult::TypeUnmatched)?
+                    .map_err(|_| ActionResult::TypeUnmatched)?;
+                self.connect_logical_device(element.sap, element.logical_device_name);
+                Ok(None)
+            }
+            _ => Err(ActionResult::ObjectUndefined),
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [2]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        let [list] = <[Data; 1]>::try_from(state.attributes)
+            .map_err(|_| DataAccessResult::TypeUnmatched)?;
+        self.sap_assignment_list = Self::parse_assignment_list(&list)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(sap: u16, name: &[u8]) -> Data {
+        Data::Structure(vec![Data::LongUnsigned(sap), Data::OctetString(name.to_vec())])
+    }
+
+    #[test]
+    fn test_assignment_list() {
+        let sap_assignment = SapAssignment::new()
+            .with_logical_device(17, b"LD17".to_vec())
+            .with_logical_device(1, b"LD01".to_vec());
+        assert_eq!(
+            sap_assignment.get_attribute(2),
+            Ok(Data::Structure(vec![element(1, b"LD01"), element(17, b"LD17")]))
+        );
+        let parsed =
+            SapAssignment::parse_assignment_list(&sap_assignment.get_attribute(2).unwrap());
+        assert_eq!(parsed, Ok(sap_assignment.sap_assignment_list.clone()));
+        assert_eq!(
+            sap_assignment.clone().set_attribute(2, Data::Structure(vec![])),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+    }
+
+    #[test]
+    fn test_connect_logical_device() {
+        let mut sap_assignment = SapAssignment::new().with_logical_device(1, b"LD01".to_vec());
+
+        sap_assignment.invoke_method(1, Some(element(2, b"LD02"))).unwrap();
+        sap_assignment.invoke_method(1, Some(element(1, b"MGMT"))).unwrap();
+        assert_eq!(sap_assignment.logical_device_name(1), Some(&b"MGMT"[..]));
+        assert_eq!(sap_assignment.logical_device_name(2), Some(&b"LD02"[..]));
+
+        // An empty name disconnects the logical device.
+        sap_assignment.invoke_method(1, Some(element(2, b""))).unwrap();
+        assert_eq!(sap_assignment.logical_device_name(2), None);
+
+        assert_eq!(
+            sap_assignment.invoke_method(1, Some(Data::Unsigned(1))),
+            Err(ActionResult::TypeUnmatched)
+        );
+        assert_eq!(sap_assignment.invoke_method(2, None), Err(ActionResult::ObjectUndefined));
+    }
+}
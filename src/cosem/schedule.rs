@@ -0,0 +1,446 @@
+//! COSEM Interface Class 10: Schedule
+//!
+//! A Schedule is a table of entries, each executing a script of a Script
+//! Table at a switch time on selected weekdays within a date range.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.12.0.0.255)
+//! - Attribute 2: `entries` - Array of `{index, enable, script_logical_name,
+//!   script_selector, switch_time, validity_window, exec_weekdays,
+//!   exec_specdays, begin_date, end_date}`
+//!
+//! `enable` is a boolean, `exec_weekdays` a bit string starting with Monday,
+//! `validity_window` a number of minutes (0xFFFF: no limit). Begin and end
+//! dates with wildcards do not limit the entry.
+//! Special days are not evaluated: `exec_specdays` is only stored.
+//!
+//! ## Methods
+//! - Method 1: `enable_disable(data)` - `{first_index, last_index}` of the
+//!   entries to disable, then of the entries to enable (0 selects none)
+//! - Method 2: `insert(entry)` - Adds an entry, replacing the entry with the
+//!   same index
+//! - Method 3: `delete(data)` - `{first_index, last_index}` of the entries
+//!   to delete
+//!
+//! Schedules are executed by a
+//! [`ScriptScheduler`](crate::cosem::script_scheduler::ScriptScheduler).
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::schedule::{Schedule, ScheduleEntry};
+//! use dlms_cosem::cosem::script_table::ScriptReference;
+//! use dlms_cosem::{ObisCode, Time};
+//!
+//! let script = ScriptReference { script_logical_name: ObisCode::new(0, 0, 10, 0, 100, 255), script_selector: 2 };
+//! let mut entry = ScheduleEntry::new(1, script, Time::new(Some(7), Some(0), Some(0), None));
+//! entry.exec_weekdays = [true, true, true, true, true, false, false];
+//! let schedule = Schedule::new(ObisCode::new(0, 0, 12, 0, 0, 255)).with_entry(entry);
+//!
+//! // Monday 2025-03-03 07:00 is 1_740_985_200 seconds since 1970-01-01.
+//! let due = schedule.due(1_740_985_000, 1_740_985_200);
+//! assert_eq!(due.len(), 1);
+//! assert_eq!(due[0].time, 1_740_985_200);
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::action::ActionResult;
+use crate::calendar::seconds_of_day;
+use crate::cosem::CosemObject;
+use crate::cosem::activity_calendar::weekday;
+use crate::cosem::capture_scheduler::days_from_civil;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::cosem::script_table::ScriptReference;
+use crate::data::{Data, Date, Time};
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Validity window meaning "no limit".
+pub const NO_VALIDITY_LIMIT: u16 = 0xFFFF;
+
+/// Days an evaluation looks back after a forward clock jump.
+const MAX_DAYS_BACK: i64 = 366;
+
+/// Date meaning "no limit".
+const ANY_DATE: Date = Date::new(0xFFFF, 0xFF, 0xFF, 0xFF);
+
+/// A script due at a switch time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DueScript {
+    /// Switch time, in local seconds since 1970-01-01
+    pub time: u32,
+    /// Script to execute
+    pub script: ScriptReference,
+}
+
+/// An entry of a Schedule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ScheduleEntry {
+    /// Index, unique in the schedule
+    pub index: u16,
+    /// Whether the entry is executed
+    pub enable: bool,
+    /// Script executed
+    pub script: ScriptReference,
+    /// Local time of execution
+    pub switch_time: Time,
+    /// Minutes after the switch time the entry may still be executed
+    /// (after a power failure or a clock jump)
+    pub validity_window: u16,
+    /// Weekdays of execution, starting with Monday
+    pub exec_weekdays: [bool; 7],
+    /// Special day types of execution (not evaluated)
+    pub exec_specdays: Vec<u8>,
+    /// First day of execution
+    pub begin_date: Date,
+    /// Last day of execution
+    pub end_date: Date,
+}
+
+impl ScheduleEntry {
+    /// Creates an enabled entry executed every day, without validity limit.
+    pub fn new(index: u16, script: ScriptReference, switch_time: Time) -> Self {
+        Self {
+            index,
+            enable: true,
+            script,
+            switch_time,
+            validity_window: NO_VALIDITY_LIMIT,
+            exec_weekdays: [true; 7],
+            exec_specdays: Vec::new(),
+            begin_date: ANY_DATE,
+            end_date: ANY_DATE,
+        }
+    }
+
+    fn to_data(&self) -> Data {
+        let weekdays = self
+            .exec_weekdays
+            .iter()
+            .enumerate()
+            .fold(0u8, |bits, (day, enabled)| bits | (u8::from(*enabled) << (7 - day)));
+        Data::Structure(vec![
+            Data::LongUnsigned(self.index),
+            Data::Boolean(self.enable),
+            Data::OctetString(self.script.script_logical_name.encode().to_vec()),
+            Data::LongUnsigned(self.script.script_selector),
+            Data::Time(self.switch_time.clone()),
+            Data::LongUnsigned(self.validity_window),
+            Data::BitString(vec![weekdays]),
+            Data::BitString(self.exec_specdays.clone()),
+            Data::Date(self.begin_date.clone()),
+            Data::Date(self.end_date.clone()),
+        ])
+    }
+
+    fn from_data(data: &Data) -> Result<Self, DataAccessResult> {
+        match data {
+            Data::Structure(elements) => match elements.as_slice() {
+                [
+                    Data::LongUnsigned(index),
+                    Data::Boolean(enable),
+                    script_logical_name,
+                    Data::LongUnsigned(script_selector),
+                    Data::Time(switch_time),
+                    Data::LongUnsigned(validity_window),
+                    Data::BitString(weekdays),
+                    Data::BitString(exec_specdays),
+                    Data::Date(begin_date),
+                    Data::Date(end_date),
+                ] => {
+                    let bits = weekdays.first().copied().unwrap_or(0);
+                    Ok(Self {
+                        index: *index,
+                        enable: *enable,
+                        script: ScriptReference::from_data(&Data::Structure(vec![
+                            script_logical_name.clone(),
+                            Data::LongUnsigned(*script_selector),
+                        ]))?,
+                        switch_time: switch_time.clone(),
+                        validity_window: *validity_window,
+                        exec_weekdays: core::array::from_fn(|day| bits & (0x80 >> day) != 0),
+                        exec_specdays: exec_specdays.clone(),
+                        begin_date: begin_date.clone(),
+                        end_date: end_date.clone(),
+                    })
+                }
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+
+    /// Whether the entry is executed on a day since 1970-01-01.
+    fn runs_on(&self, days: i64) -> bool {
+        let bound = |date: &Date| {
+            (date.year != 0xFFFF
+                && (1..=12).contains(&date.month)
+                && (1..=31).contains(&date.day_of_month))
+            .then(|| days_from_civil(date.year.into(), date.month.into(), date.day_of_month.into()))
+        };
+        self.enable
+            && self.exec_weekdays[weekday(days) as usize - 1]
+            && bound(&self.begin_date).is_none_or(|begin| begin <= days)
+            && bound(&self.end_date).is_none_or(|end| days <= end)
+    }
+}
+
+/// Schedule object - COSEM Interface Class 10
+///
+/// Reference: Blue Book 4.5.3
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Schedule {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Entries, sorted by index
+    entries: Vec<ScheduleEntry>,
+}
+
+impl Schedule {
+    /// Creates a Schedule without entries.
+    pub fn new(logical_name: ObisCode) -> Self {
+        Self { logical_name, entries: Vec::new() }
+    }
+
+    /// Adds an entry (builder style), replacing the entry with the same index.
+    pub fn with_entry(mut self, entry: ScheduleEntry) -> Self {
+        self.insert(entry);
+        self
+    }
+
+    /// Returns the entries, sorted by index.
+    pub fn entries(&self) -> &[ScheduleEntry] {
+        &self.entries
+    }
+
+    /// Adds an entry, replacing the entry with the same index.
+    pub fn insert(&mut self, entry: ScheduleEntry) {
+        match self.entries.binary_search_by_key(&entry.index, |entry| entry.index) {
+            Ok(position) => self.entries[position] = entry,
+            Err(position) => self.entries.insert(position, entry),
+        }
+    }
+
+    /// Deletes the entries with an index in `first..=last`.
+    pub fn delete(&mut self, first: u16, last: u16) {
+        self.entries.retain(|entry| !(first..=last).contains(&entry.index));
+    }
+
+    /// Enables or disables the entries with an index in `first..=last`.
+    ///
+    /// A first index of 0 selects no entry.
+    pub fn set_enabled(&mut self, first: u16, last: u16, enable: bool) {
+        for entry in &mut self.entries {
+            if first != 0 && (first..=last).contains(&entry.index) {
+                entry.enable = enable;
+            }
+        }
+    }
+
+    /// Returns the scripts of the entries whose switch time is in
+    /// `(from, to]`, in local seconds since 1970-01-01, sorted by time.
+    ///
+    /// An entry is due once per tick, at its latest switch time, and only if
+    /// `to` is within its validity window.
+    pub fn due(&self, from: u32, to: u32) -> Vec<DueScript> {
+        let (from, to) = (i64::from(from), i64::from(to));
+        let last_day = to.div_euclid(86_400);
+        let first_day = from.div_euclid(86_400).max(last_day - MAX_DAYS_BACK);
+        let mut due: Vec<DueScript> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let window = i64::from(entry.validity_window) * 60;
+                (first_day..=last_day)
+                    .rev()
+                    .filter(|day| entry.runs_on(*day))
+                    .map(|day| day * 86_400 + i64::from(seconds_of_day(&entry.switch_time)))
+                    .find(|at| from < *at && *at <= to)
+                    .filter(|at| entry.validity_window == NO_VALIDITY_LIMIT || to - at <= window)
+                    .map(|at| DueScript { time: at as u32, script: entry.script })
+            })
+            .collect();
+        due.sort_by_key(|script| script.time);
+        due
+    }
+
+    /// Parses entries (attribute 2).
+    pub fn parse_entries(data: &Data) -> Result<Vec<ScheduleEntry>, DataAccessResult> {
+        match data {
+            Data::Structure(elements) => elements.iter().map(ScheduleEntry::from_data).collect(),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+
+    fn index_range(data: &Data) -> Result<(u16, u16), ActionResult> {
+        match data {
+            Data::Structure(range) => match range.as_slice() {
+                [Data::LongUnsigned(first), Data::LongUnsigned(last)] => Ok((*first, *last)),
+                _ => Err(ActionResult::TypeUnmatched),
+            },
+            _ => Err(ActionResult::TypeUnmatched),
+        }
+    }
+}
+
+impl CosemObject for Schedule {
+    fn class_id(&self) -> u16 {
+        10
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::Structure(self.entries.iter().map(ScheduleEntry::to_data).collect())),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match id {
+            2 => {
+                let entries = Self::parse_entries(&value)?;
+                self.entries.clear();
+                entries.into_iter().for_each(|entry| self.insert(entry));
+                Ok(())
+            }
+            1 => Err(DataAccessResult::ReadWriteDenied),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        let params = params.ok_or(ActionResult::TypeUnmatched)?;
+        match method_id {
+            1 => match &params {
+                Data::Structure(ranges) if ranges.len() == 4 => {
+                    let (disable_first, disable_last) =
+                        Self::index_range(&Data::Structure(ranges[..2].to_vec()))?;
+                    let (enable_first, enable_last) =
+                        Self::index_range(&Data::Structure(ranges[2..].to_vec()))?;
+                    self.set_enabled(disable_first, disable_last, false);
+                    self.set_enabled(enable_first, enable_last, true);
+                    Ok(None)
+                }
+                _ => Err(ActionResult::TypeUnmatched),
+            },
+            2 => {
+                let entry =
+                    ScheduleEntry::from_data(&params).map_err(|_| ActionResult::TypeUnmatched)?;
+                self.insert(entry);
+                Ok(None)
+            }
+            3 => {
+                let (first, last) = Self::index_range(&params)?;
+                self.delete(first, last);
+                Ok(None)
+            }
+            _ => Err(ActionResult::ObjectUndefined),
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [2]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, [2]).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: ScriptReference = ScriptReference {
+        script_logical_name: ObisCode { a: 0, b: 0, c: 10, d: 0, e: 100, f: 255 },
+        script_selector: 1,
+    };
+
+    /// Local seconds of a date and hour.
+    fn at(year: i64, month: i64, day: i64, hour: i64) -> u32 {
+        (days_from_civil(year, month, day) * 86_400 + hour * 3_600) as u32
+    }
+
+    fn entry(index: u16, hour: u8) -> ScheduleEntry {
+        ScheduleEntry::new(
+            index,
+            ScriptReference { script_selector: index, ..SCRIPT },
+            Time::new(Some(hour), Some(0), Some(0), None),
+        )
+    }
+
+    #[test]
+    fn test_due_entries() {
+        let mut weekdays = entry(1, 7);
+        weekdays.exec_weekdays = [true, true, true, true, true, false, false];
+        let mut march = entry(2, 6);
+        march.begin_date = Date::new(2025, 3, 1, 0xFF);
+        march.end_date = Date::new(2025, 3, 31, 0xFF);
+        let mut short_window = entry(3, 5);
+        short_window.validity_window = 180;
+        let schedule = Schedule::new(ObisCode::new(0, 0, 12, 0, 0, 255))
+            .with_entry(weekdays)
+            .with_entry(march)
+            .with_entry(short_window);
+        let selectors = |from, to| {
+            schedule.due(from, to).iter().map(|due| due.script.script_selector).collect::<Vec<_>>()
+        };
+
+        // Monday 2025-03-03.
+        assert_eq!(selectors(at(2025, 3, 3, 0), at(2025, 3, 3, 8)), vec![3, 2, 1]);
+        // Saturday 2025-03-08: no weekday entry.
+        assert_eq!(selectors(at(2025, 3, 8, 0), at(2025, 3, 8, 8)), vec![3, 2]);
+        // Monday 2025-04-07: out of the date range.
+        assert_eq!(selectors(at(2025, 4, 7, 0), at(2025, 4, 7, 8)), vec![3, 1]);
+        // Power restored at 09:00: the 05:00 entry is out of its 3 hour window.
+        assert_eq!(selectors(at(2025, 4, 7, 4), at(2025, 4, 7, 9)), vec![1]);
+        assert_eq!(selectors(at(2025, 4, 7, 8), at(2025, 4, 7, 9)), Vec::<u16>::new());
+    }
+
+    #[test]
+    fn test_entries_and_methods() {
+        let mut schedule = Schedule::new(ObisCode::new(0, 0, 12, 0, 0, 255));
+        let mut entry_2 = entry(2, 6);
+        entry_2.exec_weekdays = [false, true, false, false, false, false, true];
+        schedule.invoke_method(2, Some(entry_2.to_data())).unwrap();
+        schedule.invoke_method(2, Some(entry(1, 7).to_data())).unwrap();
+        assert_eq!(schedule.entries().iter().map(|entry| entry.index).collect::<Vec<_>>(), [1, 2]);
+
+        let entries = schedule.get_attribute(2).unwrap();
+        let mut written = Schedule::new(ObisCode::new(0, 0, 12, 0, 0, 255));
+        written.set_attribute(2, entries).unwrap();
+        assert_eq!(written, schedule);
+        let Data::Structure(fields) = entry(1, 7).to_data() else { panic!() };
+        assert_eq!(fields[1], Data::Boolean(true));
+
+        // Disable 1..=2, then enable 2..=2.
+        let ranges = [1, 2, 2, 2].map(Data::LongUnsigned).to_vec();
+        schedule.invoke_method(1, Some(Data::Structure(ranges))).unwrap();
+        assert!(!schedule.entries()[0].enable);
+        assert!(schedule.entries()[1].enable);
+
+        let range = Data::Structure(vec![Data::LongUnsigned(2), Data::LongUnsigned(9)]);
+        schedule.invoke_method(3, Some(range)).unwrap();
+        assert_eq!(schedule.entries().len(), 1);
+        assert_eq!(schedule.invoke_method(3, None), Err(ActionResult::TypeUnmatched));
+    }
+}
@@ -0,0 +1,237 @@
+//! Script scheduler for Single Action Schedule and Schedule objects
+//!
+//! Executes the scripts of the Single Action Schedule (class 22) and
+//! Schedule (class 10) objects of an [`ObjectRegistry`] when their execution
+//! times are reached. The schedules are read through their attributes at
+//! every tick, so changes made through SET or ACTION apply immediately.
+//!
+//! Time is expressed as local seconds since 1970-01-01 and comes from a
+//! [`ClockSource`], e.g. a COSEM [`Clock`](crate::cosem::clock::Clock) object.
+//!
+//! ## Clock jumps
+//!
+//! - **Forward**: the schedules are evaluated over the skipped interval; a
+//!   Single Action Schedule executes its script once, a Schedule entry once
+//!   if still within its validity window.
+//! - **Backward**: nothing is executed for the jump itself; execution times
+//!   after the new time are executed again when reached, as
+//!   [`CaptureScheduler`](crate::cosem::capture_scheduler::CaptureScheduler)
+//!   captures again.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::data::DataObject;
+//! use dlms_cosem::cosem::script_scheduler::ScriptScheduler;
+//! use dlms_cosem::cosem::script_table::{ScriptAction, ScriptReference, ScriptTable};
+//! use dlms_cosem::cosem::single_action_schedule::{ScheduleType, SingleActionSchedule};
+//! use dlms_cosem::cosem::{CosemObject, ObjectRegistry};
+//! use dlms_cosem::{Data, Date, ObisCode, Time};
+//!
+//! let billing_period = ObisCode::new(0, 0, 96, 15, 1, 255);
+//! let script = ScriptReference { script_logical_name: ObisCode::new(0, 0, 10, 0, 1, 255), script_selector: 1 };
+//! let mut objects = ObjectRegistry::new()
+//!     .with_object(DataObject::new(billing_period, Data::Unsigned(0)))
+//!     .with_object(ScriptTable::new(script.script_logical_name).with_script(
+//!         1,
+//!         vec![ScriptAction::write(1, billing_period, 2, Data::Unsigned(1))],
+//!     ))
+//!     .with_object(
+//!         SingleActionSchedule::new(ObisCode::new(0, 0, 15, 0, 0, 255), script, ScheduleType::SingleExecution)
+//!             .with_execution_time(Time::new(Some(0), Some(0), Some(0), None), Date::new(0xFFFF, 0xFF, 1, 0xFF))
+//!             .unwrap(),
+//!     );
+//!
+//! let mut scheduler = ScriptScheduler::new();
+//! assert_eq!(scheduler.tick(1_738_367_000, &mut objects), 0);
+//! assert_eq!(scheduler.tick(1_738_368_000, &mut objects), 1); // 2025-02-01 00:00
+//! assert_eq!(objects.get(1, &billing_period).unwrap().get_attribute(2), Ok(Data::Unsigned(1)));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::cosem::capture_scheduler::ClockSource;
+use crate::cosem::schedule::{DueScript, Schedule};
+use crate::cosem::script_table::ScriptReference;
+use crate::cosem::single_action_schedule::{ScheduleType, SingleActionSchedule};
+use crate::cosem::{CosemObject, ObjectRegistry};
+use crate::get::DataAccessResult;
+
+/// Schedule interface class id
+const SCHEDULE_CLASS_ID: u16 = 10;
+
+/// Single Action Schedule interface class id
+const SINGLE_ACTION_SCHEDULE_CLASS_ID: u16 = 22;
+
+/// Executes the scripts of the schedules of a registry when they are due.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptScheduler {
+    last_tick: Option<u32>,
+}
+
+impl ScriptScheduler {
+    /// Creates a scheduler; the first tick sets its start time.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the scripts of the schedules of `objects` due in `(from, to]`,
+    /// sorted by time.
+    ///
+    /// Schedules whose attributes cannot be read are skipped.
+    pub fn due_scripts(&self, from: u32, to: u32, objects: &ObjectRegistry) -> Vec<DueScript> {
+        let mut due = Vec::new();
+        for object in objects.iter() {
+            match object.class_id() {
+                SINGLE_ACTION_SCHEDULE_CLASS_ID => {
+                    if let Ok(schedule) = single_action_schedule(object)
+                        && let Some(time) = schedule.due(from, to)
+                    {
+                        due.push(DueScript { time, script: schedule.executed_script });
+                    }
+                }
+                SCHEDULE_CLASS_ID => {
+                    if let Ok(schedule) = schedule(object) {
+                        due.extend(schedule.due(from, to));
+                    }
+                }
+                _ => {}
+            }
+        }
+        due.sort_by_key(|script| script.time);
+        due
+    }
+
+    /// Executes the scripts due since the previous tick.
+    ///
+    /// Returns the number of scripts executed successfully.
+    pub fn tick(&mut self, now: u32, objects: &mut ObjectRegistry) -> usize {
+        let Some(from) = self.last_tick.replace(now).filter(|last| *last < now) else {
+            // First tick or backward jump: start from the new time
+            return 0;
+        };
+        self.due_scripts(from, now, objects)
+            .iter()
+            .filter(|due| due.script.execute(objects).is_ok())
+            .count()
+    }
+
+    /// Ticks with the current time of a clock source.
+    pub fn tick_with(&mut self, clock: &impl ClockSource, objects: &mut ObjectRegistry) -> usize {
+        self.tick(clock.now(), objects)
+    }
+}
+
+/// Reads a Single Action Schedule through its attributes.
+///
+/// The type was checked when the execution times were written.
+fn single_action_schedule(
+    object: &(dyn CosemObject + Send),
+) -> Result<SingleActionSchedule, DataAccessResult> {
+    let executed_script = ScriptReference::from_data(&object.get_attribute(2)?)?;
+    let execution_time = SingleActionSchedule::parse_execution_time(&object.get_attribute(4)?)?;
+    let mut schedule = SingleActionSchedule::new(
+        *object.logical_name(),
+        executed_script,
+        ScheduleType::Unrestricted,
+    );
+    schedule.set_execution_time(execution_time)?;
+    Ok(schedule)
+}
+
+/// Reads a Schedule through its attributes.
+fn schedule(object: &(dyn CosemObject + Send)) -> Result<Schedule, DataAccessResult> {
+    let entries = Schedule::parse_entries(&object.get_attribute(2)?)?;
+    Ok(entries.into_iter().fold(Schedule::new(*object.logical_name()), Schedule::with_entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::data::DataObject;
+    use crate::cosem::schedule::ScheduleEntry;
+    use crate::cosem::script_table::{ScriptAction, ScriptTable};
+    use crate::data::{Data, Date, Time};
+    use crate::obis_code::ObisCode;
+
+    const TARIFF: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 14, e: 0, f: 255 };
+    const SCRIPTS: ObisCode = ObisCode { a: 0, b: 0, c: 10, d: 0, e: 100, f: 255 };
+    const DAY: u32 = 86_400;
+
+    fn script(selector: u16) -> ScriptReference {
+        ScriptReference { script_logical_name: SCRIPTS, script_selector: selector }
+    }
+
+    fn at(hour: u8) -> Time {
+        Time::new(Some(hour), Some(0), Some(0), None)
+    }
+
+    /// Peak tariff from 07:00 (Schedule), off-peak from 22:00 (Single
+    /// Action Schedule), on 1970-01-01 (a Thursday) and after.
+    fn objects() -> ObjectRegistry {
+        ObjectRegistry::new()
+            .with_object(DataObject::new(TARIFF, Data::Unsigned(0)))
+            .with_object(
+                ScriptTable::new(SCRIPTS)
+                    .with_script(1, vec![ScriptAction::write(1, TARIFF, 2, Data::Unsigned(1))])
+                    .with_script(2, vec![ScriptAction::write(1, TARIFF, 2, Data::Unsigned(2))]),
+            )
+            .with_object(
+                Schedule::new(ObisCode::new(0, 0, 12, 0, 0, 255)).with_entry(ScheduleEntry::new(
+                    1,
+                    script(1),
+                    at(7),
+                )),
+            )
+            .with_object(
+                SingleActionSchedule::new(
+                    ObisCode::new(0, 0, 15, 1, 0, 255),
+                    script(2),
+                    ScheduleType::SameTime,
+                )
+                .with_execution_time(at(22), Date::new(0xFFFF, 0xFF, 0xFF, 0xFF))
+                .unwrap(),
+            )
+    }
+
+    fn tariff(objects: &ObjectRegistry) -> Data {
+        objects.get(1, &TARIFF).unwrap().get_attribute(2).unwrap()
+    }
+
+    #[test]
+    fn test_executes_due_scripts() {
+        let mut objects = objects();
+        let mut scheduler = ScriptScheduler::new();
+
+        assert_eq!(scheduler.tick(6 * 3_600, &mut objects), 0);
+        assert_eq!(scheduler.tick(7 * 3_600, &mut objects), 1);
+        assert_eq!(tariff(&objects), Data::Unsigned(1));
+        assert_eq!(scheduler.tick(8 * 3_600, &mut objects), 0);
+        assert_eq!(scheduler.tick(22 * 3_600, &mut objects), 1);
+        assert_eq!(tariff(&objects), Data::Unsigned(2));
+
+        // Forward jump over the next morning: scripts run in time order.
+        let due = scheduler.due_scripts(22 * 3_600, DAY + 23 * 3_600, &objects);
+        assert_eq!(due.iter().map(|due| due.script.script_selector).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(scheduler.tick(DAY + 23 * 3_600, &mut objects), 2);
+        assert_eq!(tariff(&objects), Data::Unsigned(2));
+    }
+
+    #[test]
+    fn test_backward_jump_and_clock_source() {
+        let mut objects = objects();
+        let mut scheduler = ScriptScheduler::new();
+
+        scheduler.tick(8 * 3_600, &mut objects);
+        // Clock set back to 06:00: the 07:00 entry runs again when reached.
+        assert_eq!(scheduler.tick(6 * 3_600, &mut objects), 0);
+        assert_eq!(scheduler.tick_with(&|| 7 * 3_600, &mut objects), 1);
+
+        // A schedule changed through SET applies at the next tick.
+        objects
+            .get_mut(22, &ObisCode::new(0, 0, 15, 1, 0, 255))
+            .unwrap()
+            .set_attribute(2, script(3).to_data())
+            .unwrap();
+        assert_eq!(scheduler.tick(22 * 3_600, &mut objects), 0);
+    }
+}
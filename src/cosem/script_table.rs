@@ -0,0 +1,484 @@
+//! COSEM Interface Class 9: Script Table
+//!
+//! A Script Table holds scripts: lists of actions writing attributes or
+//! executing methods of other objects of the logical device. Schedules
+//! (Single Action Schedule, Schedule, Activity Calendar) and the meter
+//! itself trigger scripts to reset billing periods, switch tariffs, capture
+//! profiles, etc.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.10.0.100.255 for the
+//!   tariffication script table)
+//! - Attribute 2: `scripts` - Array of `{script_identifier, actions}`, each
+//!   action being `{service_id, class_id, logical_name, index, parameter}`
+//!
+//! ## Methods
+//! - Method 1: `execute(script_identifier)` - Executes the actions of the
+//!   script in order, stopping at the first failing action
+//!
+//! Actions are executed against the other objects of the logical device
+//! (see [`ObjectRegistry::invoke_method`]), so a script cannot reach the
+//! table it belongs to.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::data::DataObject;
+//! use dlms_cosem::cosem::script_table::{ScriptAction, ScriptTable};
+//! use dlms_cosem::cosem::{CosemObject, ObjectRegistry};
+//! use dlms_cosem::{Data, ObisCode};
+//!
+//! let tariff = ObisCode::new(0, 0, 96, 14, 0, 255);
+//! let scripts = ObisCode::new(0, 0, 10, 0, 100, 255);
+//! let mut objects = ObjectRegistry::new()
+//!     .with_object(DataObject::new(tariff, Data::Unsigned(1)))
+//!     .with_object(
+//!         ScriptTable::new(scripts)
+//!             .with_script(1, vec![ScriptAction::write(1, tariff, 2, Data::Unsigned(1))])
+//!             .with_script(2, vec![ScriptAction::write(1, tariff, 2, Data::Unsigned(2))]),
+//!     );
+//!
+//! objects.invoke_method(9, &scripts, 1, Some(Data::LongUnsigned(2))).unwrap();
+//! assert_eq!(objects.get(1, &tariff).unwrap().get_attribute(2), Ok(Data::Unsigned(2)));
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::action::ActionResult;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::cosem::{CosemObject, ObjectRegistry};
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Logical name of the tariffication Script Table.
+pub const TARIFFICATION_SCRIPT_TABLE: ObisCode =
+    ObisCode { a: 0, b: 0, c: 10, d: 0, e: 100, f: 255 };
+
+/// Service of a script action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ScriptService {
+    /// Writes the parameter to an attribute
+    WriteAttribute = 1,
+    /// Executes a method with the parameter
+    ExecuteMethod = 2,
+}
+
+impl ScriptService {
+    /// Convert u8 to ScriptService
+    ///
+    /// Returns Err if value is not 1 or 2.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            1 => Ok(Self::WriteAttribute),
+            2 => Ok(Self::ExecuteMethod),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// One action of a script.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ScriptAction {
+    /// Write an attribute or execute a method
+    pub service_id: ScriptService,
+    /// Class id of the target object
+    pub class_id: u16,
+    /// Logical name of the target object
+    pub logical_name: ObisCode,
+    /// Attribute or method index
+    pub index: i8,
+    /// Value written or method parameter
+    pub parameter: Data,
+}
+
+impl ScriptAction {
+    /// An action writing `value` to an attribute.
+    pub fn write(class_id: u16, logical_name: ObisCode, attribute_id: i8, value: Data) -> Self {
+        Self {
+            service_id: ScriptService::WriteAttribute,
+            class_id,
+            logical_name,
+            index: attribute_id,
+            parameter: value,
+        }
+    }
+
+    /// An action executing a method with `parameter`.
+    pub fn execute(class_id: u16, logical_name: ObisCode, method_id: i8, parameter: Data) -> Self {
+        Self {
+            service_id: ScriptService::ExecuteMethod,
+            class_id,
+            logical_name,
+            index: method_id,
+            parameter,
+        }
+    }
+
+    fn apply(&self, objects: &mut ObjectRegistry) -> Result<(), ActionResult> {
+        match self.service_id {
+            ScriptService::WriteAttribute => objects
+                .lookup_mut(self.class_id, &self.logical_name)
+                .and_then(|object| object.set_attribute(self.index, self.parameter.clone()))
+                .map_err(action_result),
+            ScriptService::ExecuteMethod => objects
+                .invoke_method(
+                    self.class_id,
+                    &self.logical_name,
+                    self.index,
+                    Some(self.parameter.clone()),
+                )
+                .map(|_| ()),
+        }
+    }
+}
+
+/// A script: a list of actions executed together.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Script {
+    /// Identifier passed to `execute`
+    pub script_identifier: u16,
+    /// Actions, executed in order
+    pub actions: Vec<ScriptAction>,
+}
+
+/// A script of a Script Table, as referenced by schedules and calendars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ScriptReference {
+    /// Logical name of the Script Table
+    pub script_logical_name: ObisCode,
+    /// Identifier of the script in the table
+    pub script_selector: u16,
+}
+
+impl ScriptReference {
+    /// Executes the script through its Script Table in `objects`.
+    pub fn execute(&self, objects: &mut ObjectRegistry) -> Result<(), ActionResult> {
+        objects
+            .invoke_method(
+                9,
+                &self.script_logical_name,
+                1,
+                Some(Data::LongUnsigned(self.script_selector)),
+            )
+            .map(|_| ())
+    }
+
+    /// Encodes the reference as `{script_logical_name, script_selector}`.
+    pub fn to_data(&self) -> Data {
+        Data::Structure(vec![
+            Data::OctetString(self.script_logical_name.encode().to_vec()),
+            Data::LongUnsigned(self.script_selector),
+        ])
+    }
+
+    /// Parses a `{script_logical_name, script_selector}` structure.
+    pub fn from_data(data: &Data) -> Result<Self, DataAccessResult> {
+        match data {
+            Data::Structure(elements) => match elements.as_slice() {
+                [Data::OctetString(logical_name), Data::LongUnsigned(script_selector)] => {
+                    Ok(Self {
+                        script_logical_name: ObisCode::try_from(logical_name.as_slice())
+                            .map_err(|_| DataAccessResult::TypeUnmatched)?,
+                        script_selector: *script_selector,
+                    })
+                }
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Script Table object - COSEM Interface Class 9
+///
+/// Reference: Blue Book 4.5.2
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ScriptTable {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Scripts
+    pub scripts: Vec<Script>,
+}
+
+impl ScriptTable {
+    /// Creates a Script Table without scripts.
+    pub fn new(logical_name: ObisCode) -> Self {
+        Self { logical_name, scripts: Vec::new() }
+    }
+
+    /// Adds a script (builder style), replacing a script with the same identifier.
+    pub fn with_script(mut self, script_identifier: u16, actions: Vec<ScriptAction>) -> Self {
+        self.scripts.retain(|script| script.script_identifier != script_identifier);
+        self.scripts.push(Script { script_identifier, actions });
+        self
+    }
+
+    /// Returns the script with an identifier.
+    pub fn script(&self, script_identifier: u16) -> Option<&Script> {
+        self.scripts.iter().find(|script| script.script_identifier == script_identifier)
+    }
+
+    /// Executes the actions of a script against `objects`.
+    ///
+    /// Fails with `OtherReason` if the script does not exist, or with the
+    /// result of the first action that fails; the actions before it remain
+    /// applied.
+    pub fn execute(
+        &self,
+        script_identifier: u16,
+        objects: &mut ObjectRegistry,
+    ) -> Result<(), ActionResult> {
+        let script = self.script(script_identifier).ok_or(ActionResult::OtherReason)?;
+        script.actions.iter().try_for_each(|action| action.apply(objects))
+    }
+
+    fn scripts_data(&self) -> Data {
+        Data::Structure(
+            self.scripts
+                .iter()
+                .map(|script| {
+                    let actions = script
+                        .actions
+                        .iter()
+                        .map(|action| {
+                            Data::Structure(vec![
+                                Data::Enum(action.service_id as u8),
+                                Data::LongUnsigned(action.class_id),
+                                Data::OctetString(action.logical_name.encode().to_vec()),
+                                Data::Integer(action.index),
+                                action.parameter.clone(),
+                            ])
+                        })
+                        .collect();
+                    Data::Structure(vec![
+                        Data::LongUnsigned(script.script_identifier),
+                        Data::Structure(actions),
+                    ])
+                })
+                .collect(),
+        )
+    }
+
+    fn parse_scripts(data: &Data) -> Result<Vec<Script>, DataAccessResult> {
+        elements(data)?
+            .iter()
+            .map(|script| match elements(script)? {
+                [Data::LongUnsigned(script_identifier), actions] => {
+                    let actions = elements(actions)?
+                        .iter()
+                        .map(|action| match elements(action)? {
+                            [
+                                Data::Enum(service_id),
+                                Data::LongUnsigned(class_id),
+                                Data::OctetString(logical_name),
+                                Data::Integer(index),
+                                parameter,
+                            ] => Ok(ScriptAction {
+                                service_id: ScriptService::from_u8(*service_id)?,
+                                class_id: *class_id,
+                                logical_name: ObisCode::try_from(logical_name.as_slice())
+                                    .map_err(|_| DataAccessResult::TypeUnmatched)?,
+                                index: *index,
+                                parameter: parameter.clone(),
+                            }),
+                            _ => Err(DataAccessResult::TypeUnmatched),
+                        })
+                        .collect::<Result<_, _>>()?;
+                    Ok(Script { script_identifier: *script_identifier, actions })
+                }
+                _ => Err(DataAccessResult::TypeUnmatched),
+            })
+            .collect()
+    }
+
+    fn script_identifier(params: Option<Data>) -> Result<u16, ActionResult> {
+        match params {
+            Some(Data::LongUnsigned(script_identifier)) => Ok(script_identifier),
+            _ => Err(ActionResult::TypeUnmatched),
+        }
+    }
+}
+
+impl CosemObject for ScriptTable {
+    fn class_id(&self) -> u16 {
+        9
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(self.scripts_data()),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match id {
+            2 => {
+                self.scripts = Self::parse_scripts(&value)?;
+                Ok(())
+            }
+            1 => Err(DataAccessResult::ReadWriteDenied),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    /// Without the other objects of the logical device, only scripts
+    /// without actions can be executed.
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        self.invoke_method_with_objects(&mut ObjectRegistry::new(), method_id, params)
+    }
+
+    fn invoke_method_with_objects(
+        &mut self,
+        objects: &mut ObjectRegistry,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match method_id {
+            1 => {
+                self.execute(Self::script_identifier(params)?, objects)?;
+                Ok(None)
+            }
+            _ => Err(ActionResult::ObjectUndefined),
+        }
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [2]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, [2]).map(|_| ())
+    }
+}
+
+/// The result of an action failing on a write access.
+fn action_result(result: DataAccessResult) -> ActionResult {
+    match result {
+        DataAccessResult::HardwareFault => ActionResult::HardwareFault,
+        DataAccessResult::TemporaryFailure => ActionResult::TemporaryFailure,
+        DataAccessResult::ReadWriteDenied => ActionResult::ReadWriteDenied,
+        DataAccessResult::ObjectUndefined => ActionResult::ObjectUndefined,
+        DataAccessResult::ObjectClassInconsistent => ActionResult::ObjectClassInconsistent,
+        DataAccessResult::ObjectUnavailable => ActionResult::ObjectUnavailable,
+        DataAccessResult::TypeUnmatched => ActionResult::TypeUnmatched,
+        DataAccessResult::ScopeOfAccessViolated => ActionResult::ScopeOfAccessViolated,
+        _ => ActionResult::OtherReason,
+    }
+}
+
+fn elements(data: &Data) -> Result<&[Data], DataAccessResult> {
+    match data {
+        Data::Structure(elements) => Ok(elements),
+        _ => Err(DataAccessResult::TypeUnmatched),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::ProfileGeneric;
+    use crate::cosem::data::DataObject;
+    use crate::selective_access::CaptureObjectDefinition;
+
+    const TARIFF: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 14, e: 0, f: 255 };
+    const BILLING: ObisCode = ObisCode { a: 1, b: 0, c: 98, d: 1, e: 0, f: 255 };
+
+    fn objects() -> ObjectRegistry {
+        ObjectRegistry::new()
+            .with_object(DataObject::new(TARIFF, Data::Unsigned(1)))
+            .with_object(ProfileGeneric::with_fifo(
+                BILLING,
+                vec![CaptureObjectDefinition {
+                    class_id: 1,
+                    logical_name: TARIFF,
+                    attribute_index: 2,
+                    data_index: 0,
+                }],
+                0,
+                12,
+            ))
+            .with_object(
+                ScriptTable::new(TARIFFICATION_SCRIPT_TABLE)
+                    .with_script(
+                        1,
+                        vec![
+                            ScriptAction::write(1, TARIFF, 2, Data::Unsigned(2)),
+                            ScriptAction::execute(7, BILLING, 2, Data::Integer(0)),
+                        ],
+                    )
+                    .with_script(2, vec![ScriptAction::write(1, BILLING, 2, Data::Unsigned(0))]),
+            )
+    }
+
+    fn execute(objects: &mut ObjectRegistry, script: u16) -> Result<Option<Data>, ActionResult> {
+        objects.invoke_method(9, &TARIFFICATION_SCRIPT_TABLE, 1, Some(Data::LongUnsigned(script)))
+    }
+
+    #[test]
+    fn test_execute_writes_and_methods() {
+        let mut objects = objects();
+
+        assert_eq!(execute(&mut objects, 1), Ok(None));
+        assert_eq!(objects.get(1, &TARIFF).unwrap().get_attribute(2), Ok(Data::Unsigned(2)));
+        assert_eq!(
+            objects.get(7, &BILLING).unwrap().get_attribute(7),
+            Ok(Data::DoubleLongUnsigned(1))
+        );
+
+        // The billing profile is not a Data object.
+        assert_eq!(execute(&mut objects, 2), Err(ActionResult::ObjectClassInconsistent));
+        assert_eq!(execute(&mut objects, 3), Err(ActionResult::OtherReason));
+        assert_eq!(
+            objects.invoke_method(9, &TARIFFICATION_SCRIPT_TABLE, 1, Some(Data::Unsigned(1))),
+            Err(ActionResult::TypeUnmatched)
+        );
+    }
+
+    #[test]
+    fn test_scripts_attribute() {
+        let table = ScriptTable::new(TARIFFICATION_SCRIPT_TABLE)
+            .with_script(1, vec![ScriptAction::write(1, TARIFF, 2, Data::Unsigned(2))])
+            .with_script(7, vec![]);
+
+        let mut written = ScriptTable::new(TARIFFICATION_SCRIPT_TABLE);
+        written.set_attribute(2, table.get_attribute(2).unwrap()).unwrap();
+        assert_eq!(written, table);
+
+        let reference =
+            ScriptReference { script_logical_name: TARIFFICATION_SCRIPT_TABLE, script_selector: 7 };
+        assert_eq!(ScriptReference::from_data(&reference.to_data()), Ok(reference));
+        // Without other objects, only empty scripts can run.
+        assert_eq!(written.invoke_method(1, Some(Data::LongUnsigned(7))), Ok(None));
+        assert_eq!(
+            written.invoke_method(1, Some(Data::LongUnsigned(1))),
+            Err(ActionResult::ObjectUndefined)
+        );
+        assert_eq!(
+            written.set_attribute(2, Data::Structure(vec![Data::Unsigned(1)])),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+    }
+}
@@ -0,0 +1,1138 @@
+//! COSEM Interface Class 64: Security Setup (version 1)
+//!
+//! The Security Setup object holds the security context of an association:
+//! the security policy and suite, the system titles, the keys and the
+//! certificates. Keys are rotated and certificates managed through its
+//! methods.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (0.0.43.0.e.255)
+//! - Attribute 2: `security_policy` - Enum, see [`SecurityPolicy`]
+//! - Attribute 3: `security_suite` - Enum, 0-2
+//! - Attribute 4: `client_system_title` - Octet string, empty if unknown
+//! - Attribute 5: `server_system_title` - Octet string
+//! - Attribute 6: `certificates` - Array of `{certificate_entity,
+//!   certificate_type, serial_number, issuer, subject, subject_alt_name}`
+//!
+//! All attributes are read-only; the policy is changed with
+//! `security_activate`.
+//!
+//! ## Methods
+//! - Method 1: `security_activate(security_policy)` - Strengthens the policy
+//! - Method 2: `key_transfer(data)` - Array of `{key_id, key_wrapped}`, see
+//!   [`key_transfer`](crate::key_transfer)
+//! - Method 3: `key_agreement(data)` - Array of `{key_id, key_data}`
+//! - Method 4: `generate_key_pair(key_pair_type)`
+//! - Method 5: `generate_certificate_request(key_pair_type)` - Returns a
+//!   PKCS #10 request
+//! - Method 6: `import_certificate(certificate)` - DER X.509 certificate
+//! - Method 7: `export_certificate(certificate_identification)`
+//! - Method 8: `remove_certificate(certificate_identification)`
+//!
+//! ## Keys
+//!
+//! The keys are never readable. `key_transfer` unwraps them with the master
+//! key (KEK) and replaces them only if every key of the request unwraps and
+//! has the key size of the security suite. A new master key in the same
+//! request is itself wrapped under the previous one.
+//!
+//! Key agreement and key pairs use elliptic curve cryptography (security
+//! suites 1 and 2), which is delegated to an [`EccProvider`]; without one,
+//! methods 3 to 5 fail with `OtherReason`.
+//!
+//! ## Certificates
+//!
+//! An imported certificate is classified from its content: the entity by
+//! comparing the common name of its subject with the system titles (as 16
+//! hexadecimal digits) or by its `keyCertSign` key usage, the type by its
+//! `digitalSignature` or `keyAgreement` key usage. The issuer and subject of
+//! `certificate_info` are the DER encoded names.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::security_setup::SecuritySetup;
+//! use dlms_cosem::cosem::CosemObject;
+//! use dlms_cosem::key_transfer::{KeyId, WrappedKey};
+//! use dlms_cosem::{ObisCode, SecuritySuite};
+//!
+//! let kek = [0x0F; 16];
+//! let mut security_setup =
+//!     SecuritySetup::new(ObisCode::new(0, 0, 43, 0, 1, 255), SecuritySuite::V0, *b"DLMS0001")
+//!         .with_key(KeyId::MasterKey, &kek)
+//!         .unwrap();
+//!
+//! let new_key = WrappedKey::new(KeyId::GlobalUnicastEncryptionKey, &kek, &[0xA5; 16]).unwrap();
+//! security_setup.invoke_method(2, Some(WrappedKey::key_transfer_data(&[new_key]))).unwrap();
+//! assert_eq!(security_setup.key(KeyId::GlobalUnicastEncryptionKey), Some(&[0xA5; 16][..]));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::SecuritySuite;
+use crate::action::ActionResult;
+use crate::cosem::CosemObject;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::key_transfer::{KeyId, KeyWrapError, WrappedKey, unwrap_key};
+use crate::obis_code::ObisCode;
+
+/// DER tag of a SEQUENCE
+const SEQUENCE: u8 = 0x30;
+
+/// DER tag of a SET
+const SET: u8 = 0x31;
+
+/// OID of the commonName attribute (2.5.4.3)
+const COMMON_NAME_OID: &[u8] = &[0x55, 0x04, 0x03];
+
+/// OID of the keyUsage extension (2.5.29.15)
+const KEY_USAGE_OID: &[u8] = &[0x55, 0x1D, 0x0F];
+
+/// OID of the subjectAltName extension (2.5.29.17)
+const SUBJECT_ALT_NAME_OID: &[u8] = &[0x55, 0x1D, 0x11];
+
+/// Security policy (attribute 2): which APDUs must be protected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SecurityPolicy(u8);
+
+impl SecurityPolicy {
+    /// No protection required
+    pub const NONE: SecurityPolicy = SecurityPolicy(0x00);
+
+    /// Requests must be authenticated
+    pub const AUTHENTICATED_REQUEST: SecurityPolicy = SecurityPolicy(0x04);
+
+    /// Requests must be encrypted
+    pub const ENCRYPTED_REQUEST: SecurityPolicy = SecurityPolicy(0x08);
+
+    /// Requests must be digitally signed
+    pub const DIGITALLY_SIGNED_REQUEST: SecurityPolicy = SecurityPolicy(0x10);
+
+    /// Responses are authenticated
+    pub const AUTHENTICATED_RESPONSE: SecurityPolicy = SecurityPolicy(0x20);
+
+    /// Responses are encrypted
+    pub const ENCRYPTED_RESPONSE: SecurityPolicy = SecurityPolicy(0x40);
+
+    /// Responses are digitally signed
+    pub const DIGITALLY_SIGNED_RESPONSE: SecurityPolicy = SecurityPolicy(0x80);
+
+    /// Creates a policy from raw bits.
+    pub const fn from_bits(bits: u8) -> Self {
+        SecurityPolicy(bits)
+    }
+
+    /// Returns the raw bits of this policy.
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns `true` if this policy contains the specified flags.
+    pub const fn contains(&self, other: SecurityPolicy) -> bool {
+        (self.0 & other.0) == other.0
+    }
+
+    /// Returns `true` if this policy intersects with the specified flags.
+    pub const fn intersects(&self, other: SecurityPolicy) -> bool {
+        (self.0 & other.0) != 0
+    }
+}
+
+impl core::ops::BitOr for SecurityPolicy {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        SecurityPolicy(self.0 | rhs.0)
+    }
+}
+
+/// Owner of a certificate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateEntity {
+    /// The server (this meter)
+    Server = 0,
+    /// The client
+    Client = 1,
+    /// A certification authority
+    CertificationAuthority = 2,
+    /// Any other entity
+    Other = 3,
+}
+
+impl CertificateEntity {
+    /// Converts a certificate_entity enum value.
+    ///
+    /// Returns Err if value is not in range 0-3.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::Server),
+            1 => Ok(Self::Client),
+            2 => Ok(Self::CertificationAuthority),
+            3 => Ok(Self::Other),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Use of the key of a certificate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateType {
+    /// Digital signature (ECDSA)
+    DigitalSignature = 0,
+    /// Key agreement (ECDH)
+    KeyAgreement = 1,
+    /// TLS
+    Tls = 2,
+    /// Any other use
+    Other = 3,
+}
+
+impl CertificateType {
+    /// Converts a certificate_type enum value.
+    ///
+    /// Returns Err if value is not in range 0-3.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::DigitalSignature),
+            1 => Ok(Self::KeyAgreement),
+            2 => Ok(Self::Tls),
+            3 => Ok(Self::Other),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Type of a key pair of the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyPairType {
+    /// Digital signature key pair
+    DigitalSignature = 0,
+    /// Key agreement key pair
+    KeyAgreement = 1,
+    /// TLS key pair
+    Tls = 2,
+}
+
+impl KeyPairType {
+    /// Converts a key_pair_type enum value.
+    ///
+    /// Returns Err if value is not in range 0-2.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::DigitalSignature),
+            1 => Ok(Self::KeyAgreement),
+            2 => Ok(Self::Tls),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// A certificate stored in the Security Setup object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate {
+    /// Owner of the certificate
+    pub entity: CertificateEntity,
+    /// Use of the certified key
+    pub certificate_type: CertificateType,
+    /// Serial number (INTEGER contents)
+    pub serial_number: Vec<u8>,
+    /// DER encoded issuer name
+    pub issuer: Vec<u8>,
+    /// DER encoded subject name
+    pub subject: Vec<u8>,
+    /// Value of the subjectAltName extension, empty if absent
+    pub subject_alt_name: Vec<u8>,
+    /// The DER encoded certificate
+    pub der: Vec<u8>,
+    /// Common name of the subject
+    common_name: Vec<u8>,
+}
+
+impl Certificate {
+    /// Parses a DER X.509 certificate, classifying it against the system
+    /// titles of the Security Setup object.
+    ///
+    /// Returns `None` if the certificate is malformed.
+    pub fn parse(
+        der: &[u8],
+        server_system_title: &[u8],
+        client_system_title: &[u8],
+    ) -> Option<Self> {
+        let mut input = der;
+        let (SEQUENCE, certificate, _) = element(&mut input)? else { return None };
+        let mut certificate = certificate;
+        let (SEQUENCE, mut tbs, _) = element(&mut certificate)? else { return None };
+
+        if tbs.first() == Some(&0xA0) {
+            element(&mut tbs)?; // version
+        }
+        let (0x02, serial_number, _) = element(&mut tbs)? else { return None };
+        element(&mut tbs)?; // signature algorithm
+        let (SEQUENCE, _, issuer) = element(&mut tbs)? else { return None };
+        element(&mut tbs)?; // validity
+        let (SEQUENCE, subject_rdns, subject) = element(&mut tbs)? else { return None };
+        element(&mut tbs)?; // subject public key info
+
+        let mut key_usage = None;
+        let mut subject_alt_name = Vec::new();
+        while !tbs.is_empty() {
+            let (tag, contents, _) = element(&mut tbs)?;
+            if tag == 0xA3 {
+                let mut extensions = contents;
+                let (SEQUENCE, mut extensions, _) = element(&mut extensions)? else { return None };
+                while !extensions.is_empty() {
+                    let (SEQUENCE, mut extension, _) = element(&mut extensions)? else {
+                        return None;
+                    };
+                    let (0x06, oid, _) = element(&mut extension)? else { return None };
+                    let mut value = element(&mut extension)?;
+                    if value.0 == 0x01 {
+                        value = element(&mut extension)?; // critical
+                    }
+                    match (oid, value) {
+                        (KEY_USAGE_OID, (0x04, mut value, _)) => {
+                            let (0x03, bits, _) = element(&mut value)? else { return None };
+                            key_usage = Some(bits.get(1).copied().unwrap_or(0));
+                        }
+                        (SUBJECT_ALT_NAME_OID, (0x04, value, _)) => {
+                            subject_alt_name = value.to_vec()
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let common_name = common_name(subject_rdns)?;
+        let entity = if hex_matches(&common_name, server_system_title) {
+            CertificateEntity::Server
+        } else if hex_matches(&common_name, client_system_title) {
+            CertificateEntity::Client
+        } else if key_usage.is_some_and(|usage| usage & 0x04 != 0) {
+            CertificateEntity::CertificationAuthority
+        } else {
+            CertificateEntity::Other
+        };
+        let certificate_type = match key_usage {
+            Some(usage) if usage & 0x80 != 0 => CertificateType::DigitalSignature,
+            Some(usage) if usage & 0x08 != 0 => CertificateType::KeyAgreement,
+            _ => CertificateType::Other,
+        };
+
+        Some(Self {
+            entity,
+            certificate_type,
+            serial_number: serial_number.to_vec(),
+            issuer: issuer.to_vec(),
+            subject: subject.to_vec(),
+            subject_alt_name,
+            der: der.to_vec(),
+            common_name,
+        })
+    }
+
+    /// Returns the certificate_info structure of attribute 6.
+    pub fn to_data(&self) -> Data {
+        Data::Structure(vec![
+            Data::Enum(self.entity as u8),
+            Data::Enum(self.certificate_type as u8),
+            Data::OctetString(self.serial_number.clone()),
+            Data::OctetString(self.issuer.clone()),
+            Data::OctetString(self.subject.clone()),
+            Data::OctetString(self.subject_alt_name.clone()),
+        ])
+    }
+
+    fn matches(&self, identification: &CertificateIdentification) -> bool {
+        match identification {
+            CertificateIdentification::Entity { entity, certificate_type, system_title } => {
+                self.entity == *entity
+                    && self.certificate_type == *certificate_type
+                    && hex_matches(&self.common_name, system_title)
+            }
+            CertificateIdentification::SerialNumber { serial_number, issuer } => {
+                self.serial_number == *serial_number && self.issuer == *issuer
+            }
+        }
+    }
+}
+
+/// Identifies a certificate to export or remove.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CertificateIdentification {
+    /// The certificate of an entity's system title for a key use
+    Entity {
+        /// Owner of the certificate
+        entity: CertificateEntity,
+        /// Use of the certified key
+        certificate_type: CertificateType,
+        /// System title of the owner
+        system_title: Vec<u8>,
+    },
+    /// The certificate with this serial number from this issuer
+    SerialNumber {
+        /// Serial number (INTEGER contents)
+        serial_number: Vec<u8>,
+        /// DER encoded issuer name
+        issuer: Vec<u8>,
+    },
+}
+
+impl CertificateIdentification {
+    /// Encodes the certificate_identification parameter.
+    pub fn to_data(&self) -> Data {
+        match self {
+            Self::Entity { entity, certificate_type, system_title } => Data::Structure(vec![
+                Data::Enum(0),
+                Data::Structure(vec![
+                    Data::Enum(*entity as u8),
+                    Data::Enum(*certificate_type as u8),
+                    Data::OctetString(system_title.clone()),
+                ]),
+            ]),
+            Self::SerialNumber { serial_number, issuer } => Data::Structure(vec![
+                Data::Enum(1),
+                Data::Structure(vec![
+                    Data::OctetString(serial_number.clone()),
+                    Data::OctetString(issuer.clone()),
+                ]),
+            ]),
+        }
+    }
+
+    /// Parses the certificate_identification parameter.
+    pub fn from_data(data: &Data) -> Result<Self, DataAccessResult> {
+        let Data::Structure(fields) = data else {
+            return Err(DataAccessResult::TypeUnmatched);
+        };
+        let [Data::Enum(identification_type), Data::Structure(options)] = fields.as_slice() else {
+            return Err(DataAccessResult::TypeUnmatched);
+        };
+        match (identification_type, options.as_slice()) {
+            (
+                0,
+                [Data::Enum(entity), Data::Enum(certificate_type), Data::OctetString(system_title)],
+            ) => Ok(Self::Entity {
+                entity: CertificateEntity::from_u8(*entity)?,
+                certificate_type: CertificateType::from_u8(*certificate_type)?,
+                system_title: system_title.clone(),
+            }),
+            (1, [Data::OctetString(serial_number), Data::OctetString(issuer)]) => {
+                Ok(Self::SerialNumber {
+                    serial_number: serial_number.clone(),
+                    issuer: issuer.clone(),
+                })
+            }
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Elliptic curve operations of security suites 1 and 2 (ECDSA and ECDH),
+/// holding the private keys of the server.
+pub trait EccProvider: fmt::Debug + Send {
+    /// Generates a key pair of this type, replacing the previous one.
+    fn generate_key_pair(&mut self, key_pair_type: KeyPairType) -> Result<(), ActionResult>;
+
+    /// Returns a DER PKCS #10 certificate request for the public key of this
+    /// type, with the server system title as subject.
+    fn certificate_request(
+        &mut self,
+        key_pair_type: KeyPairType,
+        server_system_title: &[u8],
+    ) -> Result<Vec<u8>, ActionResult>;
+
+    /// Agrees a key with the client.
+    ///
+    /// `key_data` is the client's signed ephemeral public key, verified with
+    /// a certificate of `certificates`. Returns the server's signed
+    /// ephemeral public key and the agreed key.
+    fn key_agreement(
+        &mut self,
+        key_id: KeyId,
+        key_data: &[u8],
+        certificates: &[Certificate],
+    ) -> Result<(Vec<u8>, Vec<u8>), ActionResult>;
+}
+
+/// Security Setup object - COSEM Interface Class 64
+///
+/// Reference: Blue Book 4.4.7
+#[derive(Debug)]
+pub struct SecuritySetup {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 3: Security suite
+    pub security_suite: SecuritySuite,
+    /// Attribute 4: Client system title, empty if unknown
+    pub client_system_title: Vec<u8>,
+    /// Attribute 5: Server system title
+    pub server_system_title: [u8; 8],
+    /// Attribute 2: Security policy
+    security_policy: SecurityPolicy,
+    /// Attribute 6: Certificates
+    certificates: Vec<Certificate>,
+    keys: BTreeMap<KeyId, Vec<u8>>,
+    ecc: Option<Box<dyn EccProvider>>,
+}
+
+impl SecuritySetup {
+    /// Creates a Security Setup object without keys, certificates nor
+    /// security policy.
+    pub fn new(
+        logical_name: ObisCode,
+        security_suite: SecuritySuite,
+        server_system_title: [u8; 8],
+    ) -> Self {
+        Self {
+            logical_name,
+            security_suite,
+            client_system_title: Vec::new(),
+            server_system_title,
+            security_policy: SecurityPolicy::NONE,
+            certificates: Vec::new(),
+            keys: BTreeMap::new(),
+            ecc: None,
+        }
+    }
+
+    /// Sets the client system title (builder style).
+    pub fn with_client_system_title(mut self, client_system_title: [u8; 8]) -> Self {
+        self.client_system_title = client_system_title.to_vec();
+        self
+    }
+
+    /// Sets a key (builder style).
+    pub fn with_key(mut self, key_id: KeyId, key: &[u8]) -> Result<Self, KeyWrapError> {
+        self.set_key(key_id, key)?;
+        Ok(self)
+    }
+
+    /// Sets the security policy (builder style).
+    pub fn with_security_policy(mut self, security_policy: SecurityPolicy) -> Self {
+        self.security_policy = security_policy;
+        self
+    }
+
+    /// Sets the provider of the elliptic curve operations (builder style).
+    pub fn with_ecc_provider(mut self, ecc: impl EccProvider + 'static) -> Self {
+        self.ecc = Some(Box::new(ecc));
+        self
+    }
+
+    /// Returns a key, if set.
+    pub fn key(&self, key_id: KeyId) -> Option<&[u8]> {
+        self.keys.get(&key_id).map(Vec::as_slice)
+    }
+
+    /// Sets a key; it must have the key size of the security suite.
+    pub fn set_key(&mut self, key_id: KeyId, key: &[u8]) -> Result<(), KeyWrapError> {
+        if key.len() != self.security_suite.key_size() {
+            return Err(KeyWrapError::InvalidKeyLength);
+        }
+        self.keys.insert(key_id, key.to_vec());
+        Ok(())
+    }
+
+    /// Returns the security policy.
+    pub fn security_policy(&self) -> SecurityPolicy {
+        self.security_policy
+    }
+
+    /// Returns the certificates.
+    pub fn certificates(&self) -> &[Certificate] {
+        &self.certificates
+    }
+
+    /// Strengthens the security policy (method 1).
+    ///
+    /// Flags already set cannot be cleared; encryption requires the global
+    /// unicast key, authentication the authentication key and digital
+    /// signatures an elliptic curve security suite.
+    pub fn security_activate(
+        &mut self,
+        security_policy: SecurityPolicy,
+    ) -> Result<(), ActionResult> {
+        let encryption = SecurityPolicy::ENCRYPTED_REQUEST | SecurityPolicy::ENCRYPTED_RESPONSE;
+        let authentication =
+            SecurityPolicy::AUTHENTICATED_REQUEST | SecurityPolicy::AUTHENTICATED_RESPONSE;
+        let signature =
+            SecurityPolicy::DIGITALLY_SIGNED_REQUEST | SecurityPolicy::DIGITALLY_SIGNED_RESPONSE;
+        if !security_policy.contains(self.security_policy)
+            || (security_policy.intersects(encryption)
+                && !self.keys.contains_key(&KeyId::GlobalUnicastEncryptionKey))
+            || (security_policy.intersects(authentication)
+                && !self.keys.contains_key(&KeyId::AuthenticationKey))
+            || (security_policy.intersects(signature) && self.security_suite == SecuritySuite::V0)
+        {
+            return Err(ActionResult::OtherReason);
+        }
+        self.security_policy = security_policy;
+        Ok(())
+    }
+
+    /// Replaces keys wrapped under the master key (method 2).
+    ///
+    /// No key is replaced unless every key unwraps and has the key size of
+    /// the security suite.
+    pub fn key_transfer(&mut self, keys: &[WrappedKey]) -> Result<(), ActionResult> {
+        let kek = self.keys.get(&KeyId::MasterKey).ok_or(ActionResult::OtherReason)?;
+        let keys = keys
+            .iter()
+            .map(|key| match unwrap_key(kek, &key.key_wrapped) {
+                Ok(unwrapped) if unwrapped.len() == self.security_suite.key_size() => {
+                    Ok((key.key_id, unwrapped))
+                }
+                _ => Err(ActionResult::OtherReason),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.keys.extend(keys);
+        Ok(())
+    }
+
+    /// Stores a certificate (method 6), replacing the certificate with the
+    /// same serial number and issuer.
+    pub fn import_certificate(&mut self, der: &[u8]) -> Result<(), ActionResult> {
+        let certificate =
+            Certificate::parse(der, &self.server_system_title, &self.client_system_title)
+                .ok_or(ActionResult::OtherReason)?;
+        self.certificates.retain(|stored| {
+            stored.serial_number != certificate.serial_number || stored.issuer != certificate.issuer
+        });
+        self.certificates.push(certificate);
+        Ok(())
+    }
+
+    /// Returns the first certificate identified (method 7).
+    pub fn export_certificate(
+        &self,
+        identification: &CertificateIdentification,
+    ) -> Option<&Certificate> {
+        self.certificates.iter().find(|certificate| certificate.matches(identification))
+    }
+
+    /// Removes the certificates identified (method 8); returns whether one
+    /// was found.
+    pub fn remove_certificate(&mut self, identification: &CertificateIdentification) -> bool {
+        let count = self.certificates.len();
+        self.certificates.retain(|certificate| !certificate.matches(identification));
+        self.certificates.len() != count
+    }
+
+    fn key_agreement(&mut self, parameters: &Data) -> Result<Data, ActionResult> {
+        let Data::Structure(elements) = parameters else {
+            return Err(ActionResult::TypeUnmatched);
+        };
+        let ecc = match self.security_suite {
+            SecuritySuite::V0 => None,
+            _ => self.ecc.as_mut(),
+        }
+        .ok_or(ActionResult::OtherReason)?;
+        let mut responses = Vec::new();
+        let mut keys = Vec::new();
+        for element in elements {
+            let (key_id, key_data) = match element {
+                Data::Structure(fields) => match fields.as_slice() {
+                    [Data::Enum(key_id), Data::OctetString(key_data)] => (*key_id, key_data),
+                    _ => return Err(ActionResult::TypeUnmatched),
+                },
+                _ => return Err(ActionResult::TypeUnmatched),
+            };
+            let key_id = KeyId::from_u8(key_id).map_err(|_| ActionResult::TypeUnmatched)?;
+            if key_id == KeyId::MasterKey {
+                return Err(ActionResult::OtherReason);
+            }
+            let (response, key) = ecc.key_agreement(key_id, key_data, &self.certificates)?;
+            if key.len() != self.security_suite.key_size() {
+                return Err(ActionResult::OtherReason);
+            }
+            responses
+                .push(Data::Structure(vec![Data::Enum(key_id as u8), Data::OctetString(response)]));
+            keys.push((key_id, key));
+        }
+        self.keys.extend(keys);
+        Ok(Data::Structure(responses))
+    }
+
+    /// Returns the ECC provider, if the security suite uses one.
+    fn ecc(&mut self) -> Result<&mut Box<dyn EccProvider>, ActionResult> {
+        match self.security_suite {
+            SecuritySuite::V0 => Err(ActionResult::OtherReason),
+            _ => self.ecc.as_mut().ok_or(ActionResult::OtherReason),
+        }
+    }
+}
+
+impl CosemObject for SecuritySetup {
+    fn class_id(&self) -> u16 {
+        64
+    }
+
+    fn version(&self) -> u8 {
+        1
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::Enum(self.security_policy.bits())),
+            3 => Ok(Data::Enum(self.security_suite as u8)),
+            4 => Ok(Data::OctetString(self.client_system_title.clone())),
+            5 => Ok(Data::OctetString(self.server_system_title.to_vec())),
+            6 => Ok(Data::Structure(self.certificates.iter().map(Certificate::to_data).collect())),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, _value: Data) -> Result<(), DataAccessResult> {
+        match id {
+            1..=6 => Err(DataAccessResult::ReadWriteDenied),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        parameters: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        let key_pair_type = |parameters: &Option<Data>| match parameters {
+            Some(Data::Enum(key_pair_type)) => {
+                KeyPairType::from_u8(*key_pair_type).map_err(|_| ActionResult::TypeUnmatched)
+            }
+            _ => Err(ActionResult::TypeUnmatched),
+        };
+        let identification = |parameters: &Option<Data>| {
+            parameters
+                .as_ref()
+                .map(CertificateIdentification::from_data)
+                .ok_or(ActionResult::TypeUnmatched)?
+                .map_err(|_| ActionResult::TypeUnmatched)
+        };
+
+        match (method_id, &parameters) {
+            (1, Some(Data::Enum(security_policy))) => {
+                self.security_activate(SecurityPolicy::from_bits(*security_policy))?;
+                Ok(None)
+            }
+            (2, Some(keys)) => {
+                let keys = WrappedKey::parse_key_transfer_data(keys)
+                    .map_err(|_| ActionResult::TypeUnmatched)?;
+                self.key_transfer(&keys)?;
+                Ok(None)
+            }
+            (3, Some(key_agreement)) => self.key_agreement(key_agreement).map(Some),
+            (4, _) => {
+                let key_pair_type = key_pair_type(&parameters)?;
+                self.ecc()?.generate_key_pair(key_pair_type)?;
+                Ok(None)
+            }
+            (5, _) => {
+                let key_pair_type = key_pair_type(&parameters)?;
+                let server_system_title = self.server_system_title;
+                let request =
+                    self.ecc()?.certificate_request(key_pair_type, &server_system_title)?;
+                Ok(Some(Data::OctetString(request)))
+            }
+            (6, Some(Data::OctetString(certificate))) => {
+                self.import_certificate(certificate)?;
+                Ok(None)
+            }
+            (7, _) => {
+                let identification = identification(&parameters)?;
+                let certificate = self
+                    .export_certificate(&identification)
+                    .ok_or(ActionResult::ObjectUnavailable)?;
+                Ok(Some(Data::OctetString(certificate.der.clone())))
+            }
+            (8, _) => {
+                let identification = identification(&parameters)?;
+                if !self.remove_certificate(&identification) {
+                    return Err(ActionResult::ObjectUnavailable);
+                }
+                Ok(None)
+            }
+            (1..=3 | 6, _) => Err(ActionResult::TypeUnmatched),
+            _ => Err(ActionResult::ObjectUndefined),
+        }
+    }
+
+    /// Persists the policy, the client system title, the certificates and
+    /// the keys, in plaintext: the snapshot must be stored securely.
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        let certificates = self.certificates.iter().map(|certificate| certificate.der.clone());
+        let keys = self.keys.iter().map(|(key_id, key)| {
+            Data::Structure(vec![Data::Enum(*key_id as u8), Data::OctetString(key.clone())])
+        });
+        let attributes = vec![
+            Data::Enum(self.security_policy.bits()),
+            Data::OctetString(self.client_system_title.clone()),
+            Data::Structure(certificates.map(Data::OctetString).collect()),
+            Data::Structure(keys.collect()),
+        ];
+        Some(ObjectState { attributes, entries: Vec::new() })
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        let [
+            Data::Enum(security_policy),
+            Data::OctetString(client_system_title),
+            Data::Structure(certificates),
+            Data::Structure(keys),
+        ] = state.attributes.as_slice()
+        else {
+            return Err(DataAccessResult::TypeUnmatched);
+        };
+        self.security_policy = SecurityPolicy::from_bits(*security_policy);
+        self.client_system_title = client_system_title.clone();
+        self.certificates.clear();
+        for certificate in certificates {
+            match certificate {
+                Data::OctetString(der) => {
+                    self.import_certificate(der).map_err(|_| DataAccessResult::OtherReason)?
+                }
+                _ => return Err(DataAccessResult::TypeUnmatched),
+            }
+        }
+        for key in keys {
+            match key {
+                Data::Structure(fields) => match fields.as_slice() {
+                    [Data::Enum(key_id), Data::OctetString(key)] => self
+                        .set_key(KeyId::from_u8(*key_id)?, key)
+                        .map_err(|_| DataAccessResult::TypeUnmatched)?,
+                    _ => return Err(DataAccessResult::TypeUnmatched),
+                },
+                _ => return Err(DataAccessResult::TypeUnmatched),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads one DER element from `input`: `(tag, contents, element)`.
+fn element<'a>(input: &mut &'a [u8]) -> Option<(u8, &'a [u8], &'a [u8])> {
+    let all = *input;
+    let (&tag, rest) = all.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (length, rest) = match first {
+        0..=0x7F => (usize::from(first), rest),
+        0x81..=0x84 => {
+            let count = usize::from(first & 0x7F);
+            let length = rest.get(..count)?;
+            (length.iter().fold(0, |length, byte| length << 8 | usize::from(*byte)), &rest[count..])
+        }
+        _ => return None,
+    };
+    let contents = rest.get(..length)?;
+    *input = &rest[length..];
+    Some((tag, contents, &all[..all.len() - input.len()]))
+}
+
+/// Returns the value of the first commonName attribute of a name.
+fn common_name(mut rdns: &[u8]) -> Option<Vec<u8>> {
+    while !rdns.is_empty() {
+        let (SET, mut attributes, _) = element(&mut rdns)? else { return None };
+        while !attributes.is_empty() {
+            let (SEQUENCE, mut attribute, _) = element(&mut attributes)? else { return None };
+            let (0x06, oid, _) = element(&mut attribute)? else { return None };
+            let (_, value, _) = element(&mut attribute)?;
+            if oid == COMMON_NAME_OID {
+                return Some(value.to_vec());
+            }
+        }
+    }
+    Some(Vec::new())
+}
+
+/// Returns whether `text` is `bytes` in hexadecimal, in either case.
+fn hex_matches(text: &[u8], bytes: &[u8]) -> bool {
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    !bytes.is_empty()
+        && text.len() == bytes.len() * 2
+        && bytes.iter().zip(text.chunks(2)).all(|(byte, digits)| {
+            digits[0].eq_ignore_ascii_case(&DIGITS[usize::from(byte >> 4)])
+                && digits[1].eq_ignore_ascii_case(&DIGITS[usize::from(byte & 0x0F)])
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_transfer::wrap_key;
+
+    const LOGICAL_NAME: ObisCode = ObisCode { a: 0, b: 0, c: 43, d: 0, e: 1, f: 255 };
+    const SERVER: [u8; 8] = *b"MTR00001";
+    const CLIENT: [u8; 8] = *b"HES00001";
+    const KEK: [u8; 16] = [0x0F; 16];
+
+    fn tlv(tag: u8, contents: &[u8]) -> Vec<u8> {
+        let mut tlv = vec![tag];
+        match contents.len() {
+            length @ 0..=0x7F => tlv.push(length as u8),
+            length @ 0x80..=0xFF => tlv.extend([0x81, length as u8]),
+            length => tlv.extend([0x82, (length >> 8) as u8, length as u8]),
+        }
+        tlv.extend(contents);
+        tlv
+    }
+
+    fn name(common_name: &str) -> Vec<u8> {
+        let attribute = [tlv(0x06, COMMON_NAME_OID), tlv(0x0C, common_name.as_bytes())].concat();
+        tlv(SEQUENCE, &tlv(SET, &tlv(SEQUENCE, &attribute)))
+    }
+
+    /// A certificate with a fake key and signature.
+    fn certificate(serial: u8, issuer: &str, subject: &str, key_usage: u8) -> Vec<u8> {
+        let algorithm =
+            tlv(SEQUENCE, &tlv(0x06, &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x02]));
+        let key_usage = tlv(0x03, &[0x07, key_usage]);
+        let extension =
+            [tlv(0x06, KEY_USAGE_OID), tlv(0x01, &[0xFF]), tlv(0x04, &key_usage)].concat();
+        let tbs = [
+            tlv(0xA0, &tlv(0x02, &[2])),
+            tlv(0x02, &[serial]),
+            algorithm.clone(),
+            name(issuer),
+            tlv(SEQUENCE, &[]),
+            name(subject),
+            tlv(SEQUENCE, &[0; 64]),
+            tlv(0xA3, &tlv(SEQUENCE, &tlv(SEQUENCE, &extension))),
+        ]
+        .concat();
+        tlv(SEQUENCE, &[tlv(SEQUENCE, &tbs), algorithm, tlv(0x03, &[0; 65])].concat())
+    }
+
+    fn security_setup(security_suite: SecuritySuite) -> SecuritySetup {
+        SecuritySetup::new(LOGICAL_NAME, security_suite, SERVER)
+            .with_client_system_title(CLIENT)
+            .with_key(KeyId::MasterKey, &KEK)
+            .unwrap()
+    }
+
+    fn key_transfer(keys: &[(KeyId, &[u8; 16])], kek: &[u8]) -> Option<Data> {
+        let keys: Vec<_> =
+            keys.iter().map(|(key_id, key)| WrappedKey::new(*key_id, kek, *key).unwrap()).collect();
+        Some(WrappedKey::key_transfer_data(&keys))
+    }
+
+    #[test]
+    fn test_key_transfer() {
+        let mut security_setup = security_setup(SecuritySuite::V0);
+        let keys = [
+            (KeyId::GlobalUnicastEncryptionKey, &[0x11; 16]),
+            (KeyId::AuthenticationKey, &[0x22; 16]),
+            (KeyId::MasterKey, &[0x33; 16]),
+        ];
+        assert_eq!(security_setup.invoke_method(2, key_transfer(&keys, &KEK)), Ok(None));
+        assert_eq!(security_setup.key(KeyId::GlobalUnicastEncryptionKey), Some(&[0x11; 16][..]));
+        assert_eq!(security_setup.key(KeyId::AuthenticationKey), Some(&[0x22; 16][..]));
+        assert_eq!(security_setup.key(KeyId::MasterKey), Some(&[0x33; 16][..]));
+
+        // The previous KEK no longer unwraps; nothing is replaced if one key fails.
+        let keys = [(KeyId::GlobalBroadcastEncryptionKey, &[0x44; 16])];
+        assert_eq!(
+            security_setup.invoke_method(2, key_transfer(&keys, &KEK)),
+            Err(ActionResult::OtherReason)
+        );
+        let mut parameters = key_transfer(&keys, &[0x33; 16]).unwrap();
+        let Data::Structure(elements) = &mut parameters else { unreachable!() };
+        elements.push(Data::Structure(vec![
+            Data::Enum(0),
+            Data::OctetString(wrap_key(&[0x33; 16], &[0x55; 32]).unwrap()),
+        ]));
+        assert_eq!(
+            security_setup.invoke_method(2, Some(parameters)),
+            Err(ActionResult::OtherReason)
+        );
+        assert_eq!(security_setup.key(KeyId::GlobalBroadcastEncryptionKey), None);
+        assert_eq!(security_setup.key(KeyId::GlobalUnicastEncryptionKey), Some(&[0x11; 16][..]));
+    }
+
+    #[test]
+    fn test_security_activate() {
+        let mut security_setup = security_setup(SecuritySuite::V0);
+        let encrypted = SecurityPolicy::ENCRYPTED_REQUEST | SecurityPolicy::ENCRYPTED_RESPONSE;
+        // No global unicast key yet.
+        assert_eq!(
+            security_setup.invoke_method(1, Some(Data::Enum(encrypted.bits()))),
+            Err(ActionResult::OtherReason)
+        );
+
+        security_setup.set_key(KeyId::GlobalUnicastEncryptionKey, &[0x11; 16]).unwrap();
+        security_setup.invoke_method(1, Some(Data::Enum(encrypted.bits()))).unwrap();
+        assert_eq!(security_setup.get_attribute(2), Ok(Data::Enum(0x48)));
+        // The policy cannot be weakened, nor use signatures with suite 0.
+        let weaker = SecurityPolicy::ENCRYPTED_REQUEST.bits();
+        assert_eq!(
+            security_setup.invoke_method(1, Some(Data::Enum(weaker))),
+            Err(ActionResult::OtherReason)
+        );
+        let signed = encrypted | SecurityPolicy::DIGITALLY_SIGNED_RESPONSE;
+        assert_eq!(
+            security_setup.invoke_method(1, Some(Data::Enum(signed.bits()))),
+            Err(ActionResult::OtherReason)
+        );
+        assert_eq!(
+            security_setup.set_attribute(2, Data::Enum(0)),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+    }
+
+    #[test]
+    fn test_certificates() {
+        let mut security_setup = security_setup(SecuritySuite::V1);
+        let ca = certificate(1, "Root CA", "Root CA", 0x06);
+        let server = certificate(2, "Root CA", "4D54523030303031", 0x80);
+        let client = certificate(3, "Root CA", "4845533030303031", 0x08);
+        for der in [&ca, &server, &client] {
+            security_setup.invoke_method(6, Some(Data::OctetString(der.clone()))).unwrap();
+        }
+        let classification: Vec<_> = security_setup
+            .certificates()
+            .iter()
+            .map(|certificate| (certificate.entity, certificate.certificate_type))
+            .collect();
+        assert_eq!(
+            classification,
+            [
+                (CertificateEntity::CertificationAuthority, CertificateType::Other),
+                (CertificateEntity::Server, CertificateType::DigitalSignature),
+                (CertificateEntity::Client, CertificateType::KeyAgreement),
+            ]
+        );
+        let Ok(Data::Structure(info)) = security_setup.get_attribute(6) else { panic!() };
+        assert_eq!(info[1], security_setup.certificates()[1].to_data());
+
+        let by_entity = CertificateIdentification::Entity {
+            entity: CertificateEntity::Client,
+            certificate_type: CertificateType::KeyAgreement,
+            system_title: CLIENT.to_vec(),
+        };
+        assert_eq!(
+            security_setup.invoke_method(7, Some(by_entity.to_data())),
+            Ok(Some(Data::OctetString(client)))
+        );
+        let by_serial = CertificateIdentification::SerialNumber {
+            serial_number: vec![2],
+            issuer: name("Root CA"),
+        };
+        assert_eq!(security_setup.invoke_method(8, Some(by_serial.to_data())), Ok(None));
+        assert_eq!(
+            security_setup.invoke_method(7, Some(by_serial.to_data())),
+            Err(ActionResult::ObjectUnavailable)
+        );
+        assert_eq!(security_setup.certificates().len(), 2);
+
+        assert_eq!(
+            security_setup.invoke_method(6, Some(Data::OctetString(server[..40].to_vec()))),
+            Err(ActionResult::OtherReason)
+        );
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_persist_long_certificate() {
+        use crate::cosem::ObjectRegistry;
+        use crate::cosem::persistence::{restore, snapshot};
+
+        let subject = "4D54523030303031".repeat(20);
+        let server = certificate(2, "Root CA", &subject, 0x80);
+        assert!(server.len() > 255);
+        let mut security_setup = security_setup(SecuritySuite::V1);
+        security_setup.invoke_method(6, Some(Data::OctetString(server.clone()))).unwrap();
+        let objects = ObjectRegistry::new().with_object(security_setup);
+
+        let mut restored = ObjectRegistry::new().with_object(SecuritySetup::new(
+            LOGICAL_NAME,
+            SecuritySuite::V1,
+            SERVER,
+        ));
+        restore(&mut restored, &snapshot(&objects)).unwrap();
+        assert_eq!(
+            restored.get_mut(64, &LOGICAL_NAME).unwrap().invoke_method(
+                7,
+                Some(
+                    CertificateIdentification::SerialNumber {
+                        serial_number: vec![2],
+                        issuer: name("Root CA"),
+                    }
+                    .to_data()
+                )
+            ),
+            Ok(Some(Data::OctetString(server)))
+        );
+    }
+
+    #[derive(Debug)]
+    struct FakeEcc;
+
+    impl EccProvider for FakeEcc {
+        fn generate_key_pair(&mut self, _key_pair_type: KeyPairType) -> Result<(), ActionResult> {
+            Ok(())
+        }
+
+        fn certificate_request(
+            &mut self,
+            key_pair_type: KeyPairType,
+            server_system_title: &[u8],
+        ) -> Result<Vec<u8>, ActionResult> {
+            Ok([&[key_pair_type as u8][..], server_system_title].concat())
+        }
+
+        fn key_agreement(
+            &mut self,
+            _key_id: KeyId,
+            key_data: &[u8],
+            _certificates: &[Certificate],
+        ) -> Result<(Vec<u8>, Vec<u8>), ActionResult> {
+            Ok((key_data.iter().rev().copied().collect(), key_data[..16].to_vec()))
+        }
+    }
+
+    #[test]
+    fn test_ecc_methods() {
+        let mut security_setup = security_setup(SecuritySuite::V1);
+        assert_eq!(
+            security_setup.invoke_method(4, Some(Data::Enum(0))),
+            Err(ActionResult::OtherReason)
+        );
+
+        let mut security_setup = security_setup.with_ecc_provider(FakeEcc);
+        assert_eq!(security_setup.invoke_method(4, Some(Data::Enum(1))), Ok(None));
+        assert_eq!(
+            security_setup.invoke_method(5, Some(Data::Enum(0))),
+            Ok(Some(Data::OctetString([&[0][..], &SERVER].concat())))
+        );
+        assert_eq!(
+            security_setup.invoke_method(5, Some(Data::Enum(3))),
+            Err(ActionResult::TypeUnmatched)
+        );
+
+        let key_data: Vec<u8> = (0..32).collect();
+        let agreement = Data::Structure(vec![Data::Structure(vec![
+            Data::Enum(0),
+            Data::OctetString(key_data.clone()),
+        ])]);
+        let response = security_setup.invoke_method(3, Some(agreement)).unwrap();
+        assert_eq!(
+            response,
+            Some(Data::Structure(vec![Data::Structure(vec![
+                Data::Enum(0),
+                Data::OctetString(key_data.iter().rev().copied().collect()),
+            ])]))
+        );
+        assert_eq!(security_setup.key(KeyId::GlobalUnicastEncryptionKey), Some(&key_data[..16]));
+    }
+}
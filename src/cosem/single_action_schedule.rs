@@ -0,0 +1,404 @@
+//! COSEM Interface Class 22: Single Action Schedule
+//!
+//! A Single Action Schedule executes one script of a Script Table at the
+//! dates and times of its `execution_time` list, e.g. the end of billing
+//! period on the first day of every month at midnight.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.15.0.0.255 for the end
+//!   of billing period)
+//! - Attribute 2: `executed_script` - `{script_logical_name, script_selector}`
+//! - Attribute 3: `type` - Enum, see [`ScheduleType`]
+//! - Attribute 4: `execution_time` - Array of `{time, date}`
+//!
+//! Execution times are local times. Dates may contain wildcards (when the
+//! type allows them), a day of month of 0xFE (last day) or 0xFD (second
+//! last day) and a day of week; times must specify the hour and minute.
+//! Writing attribute 3 or 4 fails with `OtherReason` if the execution times
+//! do not satisfy the type.
+//!
+//! ## Methods
+//! None.
+//!
+//! Schedules are executed by a
+//! [`ScriptScheduler`](crate::cosem::script_scheduler::ScriptScheduler).
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::script_table::ScriptReference;
+//! use dlms_cosem::cosem::single_action_schedule::{ScheduleType, SingleActionSchedule};
+//! use dlms_cosem::{Date, ObisCode, Time};
+//!
+//! // End of billing period: every 1st of the month at 00:00.
+//! let end_of_billing = SingleActionSchedule::new(
+//!     ObisCode::new(0, 0, 15, 0, 0, 255),
+//!     ScriptReference { script_logical_name: ObisCode::new(0, 0, 10, 0, 1, 255), script_selector: 1 },
+//!     ScheduleType::SingleExecution,
+//! )
+//! .with_execution_time(Time::new(Some(0), Some(0), Some(0), None), Date::new(0xFFFF, 0xFF, 1, 0xFF))
+//! .unwrap();
+//!
+//! // 2025-02-01 00:00 is 1_738_368_000 seconds since 1970-01-01.
+//! assert_eq!(end_of_billing.due(1_738_367_000, 1_738_368_000), Some(1_738_368_000));
+//! assert_eq!(end_of_billing.due(1_738_368_000, 1_738_369_000), None);
+//! ```
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::action::ActionResult;
+use crate::calendar::seconds_of_day;
+use crate::cosem::CosemObject;
+use crate::cosem::activity_calendar::{civil_from_days, weekday};
+use crate::cosem::capture_scheduler::days_from_civil;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::cosem::script_table::ScriptReference;
+use crate::data::{Data, Date, Time};
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Days an evaluation looks back after a forward clock jump.
+const MAX_DAYS_BACK: i64 = 366;
+
+/// Constraints on the execution times (attribute 3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ScheduleType {
+    /// One execution time, wildcards allowed in the date
+    SingleExecution = 1,
+    /// Execution times with the same time, no wildcards in the dates
+    SameTimeSpecifiedDates = 2,
+    /// Execution times with the same time, wildcards allowed in the dates
+    SameTime = 3,
+    /// Execution times with different times, no wildcards in the dates
+    SpecifiedDates = 4,
+    /// Execution times with different times, wildcards allowed in the dates
+    Unrestricted = 5,
+}
+
+impl ScheduleType {
+    /// Convert u8 to ScheduleType
+    ///
+    /// Returns Err if value is not in range 1-5.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            1 => Ok(Self::SingleExecution),
+            2 => Ok(Self::SameTimeSpecifiedDates),
+            3 => Ok(Self::SameTime),
+            4 => Ok(Self::SpecifiedDates),
+            5 => Ok(Self::Unrestricted),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+
+    /// Checks that execution times satisfy the type.
+    pub fn check(&self, execution_time: &[ExecutionTime]) -> Result<(), DataAccessResult> {
+        let single = *self != Self::SingleExecution || execution_time.len() <= 1;
+        let same_time = !matches!(self, Self::SameTimeSpecifiedDates | Self::SameTime)
+            || execution_time
+                .windows(2)
+                .all(|times| seconds_of_day(&times[0].time) == seconds_of_day(&times[1].time));
+        let specified_dates = !matches!(self, Self::SameTimeSpecifiedDates | Self::SpecifiedDates)
+            || execution_time.iter().all(|execution| {
+                execution.date.year != 0xFFFF
+                    && (1..=12).contains(&execution.date.month)
+                    && (1..=31).contains(&execution.date.day_of_month)
+            });
+        let specified_times = execution_time
+            .iter()
+            .all(|execution| execution.time.hour.is_some() && execution.time.minute.is_some());
+        if single && same_time && specified_dates && specified_times {
+            Ok(())
+        } else {
+            Err(DataAccessResult::OtherReason)
+        }
+    }
+}
+
+/// An execution time: a local time on the days matching a date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExecutionTime {
+    /// Time of day
+    pub time: Time,
+    /// Date, possibly with wildcards
+    pub date: Date,
+}
+
+/// Single Action Schedule object - COSEM Interface Class 22
+///
+/// Reference: Blue Book 4.5.7
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SingleActionSchedule {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Script executed
+    pub executed_script: ScriptReference,
+    /// Attribute 3: Type
+    schedule_type: ScheduleType,
+    /// Attribute 4: Execution times
+    execution_time: Vec<ExecutionTime>,
+}
+
+impl SingleActionSchedule {
+    /// Creates a schedule without execution times.
+    pub fn new(
+        logical_name: ObisCode,
+        executed_script: ScriptReference,
+        schedule_type: ScheduleType,
+    ) -> Self {
+        Self { logical_name, executed_script, schedule_type, execution_time: Vec::new() }
+    }
+
+    /// Adds an execution time (builder style).
+    ///
+    /// Fails with `OtherReason` if the execution times would not satisfy the type.
+    pub fn with_execution_time(mut self, time: Time, date: Date) -> Result<Self, DataAccessResult> {
+        let mut execution_time = self.execution_time.clone();
+        execution_time.push(ExecutionTime { time, date });
+        self.set_execution_time(execution_time)?;
+        Ok(self)
+    }
+
+    /// Returns the type.
+    pub fn schedule_type(&self) -> ScheduleType {
+        self.schedule_type
+    }
+
+    /// Returns the execution times.
+    pub fn execution_time(&self) -> &[ExecutionTime] {
+        &self.execution_time
+    }
+
+    /// Replaces the execution times, checked against the type.
+    pub fn set_execution_time(
+        &mut self,
+        execution_time: Vec<ExecutionTime>,
+    ) -> Result<(), DataAccessResult> {
+        self.schedule_type.check(&execution_time)?;
+        self.execution_time = execution_time;
+        Ok(())
+    }
+
+    /// Changes the type, checked against the execution times.
+    pub fn set_schedule_type(
+        &mut self,
+        schedule_type: ScheduleType,
+    ) -> Result<(), DataAccessResult> {
+        schedule_type.check(&self.execution_time)?;
+        self.schedule_type = schedule_type;
+        Ok(())
+    }
+
+    /// Returns the latest execution time in `(from, to]`, in local seconds
+    /// since 1970-01-01.
+    ///
+    /// The script is executed once, even if a clock jump skipped several
+    /// execution times.
+    pub fn due(&self, from: u32, to: u32) -> Option<u32> {
+        due_times(&self.execution_time, from, to).max()
+    }
+
+    /// Parses execution times (attribute 4).
+    pub fn parse_execution_time(data: &Data) -> Result<Vec<ExecutionTime>, DataAccessResult> {
+        match data {
+            Data::Structure(elements) => elements
+                .iter()
+                .map(|element| match element {
+                    Data::Structure(pair) => match pair.as_slice() {
+                        [Data::Time(time), Data::Date(date)] => {
+                            Ok(ExecutionTime { time: time.clone(), date: date.clone() })
+                        }
+                        _ => Err(DataAccessResult::TypeUnmatched),
+                    },
+                    _ => Err(DataAccessResult::TypeUnmatched),
+                })
+                .collect(),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+impl CosemObject for SingleActionSchedule {
+    fn class_id(&self) -> u16 {
+        22
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(self.executed_script.to_data()),
+            3 => Ok(Data::Enum(self.schedule_type as u8)),
+            4 => Ok(Data::Structure(
+                self.execution_time
+                    .iter()
+                    .map(|execution| {
+                        Data::Structure(vec![
+                            Data::Time(execution.time.clone()),
+                            Data::Date(execution.date.clone()),
+                        ])
+                    })
+                    .collect(),
+            )),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (2, value) => {
+                self.executed_script = ScriptReference::from_data(&value)?;
+                Ok(())
+            }
+            (3, Data::Enum(schedule_type)) => {
+                self.set_schedule_type(ScheduleType::from_u8(schedule_type)?)
+            }
+            (4, value) => self.set_execution_time(Self::parse_execution_time(&value)?),
+            (3, _) => Err(DataAccessResult::TypeUnmatched),
+            (1, _) => Err(DataAccessResult::ReadWriteDenied),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn invoke_method(
+        &mut self,
+        _method_id: i8,
+        _params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        Err(ActionResult::ObjectUndefined)
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, [2, 3, 4]).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        // The times are cleared first so that any type can be restored.
+        self.execution_time.clear();
+        state.restore_attributes(self, [2, 3, 4]).map(|_| ())
+    }
+}
+
+/// Execution times in `(from, to]` of a list of times on matching dates,
+/// looking back at most [`MAX_DAYS_BACK`] days.
+fn due_times(
+    execution_time: &[ExecutionTime],
+    from: u32,
+    to: u32,
+) -> impl Iterator<Item = u32> + '_ {
+    let (from, to) = (i64::from(from), i64::from(to));
+    let last_day = to.div_euclid(86_400);
+    let first_day = from.div_euclid(86_400).max(last_day - MAX_DAYS_BACK);
+    (first_day..=last_day).flat_map(move |day| {
+        execution_time.iter().filter_map(move |execution| {
+            let at = day * 86_400 + i64::from(seconds_of_day(&execution.time));
+            (from < at && at <= to && date_matches(&execution.date, day)).then_some(at as u32)
+        })
+    })
+}
+
+/// Whether a date, possibly with wildcards, falls on a day since 1970-01-01.
+///
+/// A day of month of 0xFE is the last day of the month, 0xFD the second
+/// last day. The daylight saving months (0xFD, 0xFE) never match.
+pub(crate) fn date_matches(date: &Date, days: i64) -> bool {
+    let (year, month, day) = civil_from_days(days);
+    let length =
+        days_from_civil(year + month / 12, month % 12 + 1, 1) - days_from_civil(year, month, 1);
+    let day_of_month = match date.day_of_month {
+        0xFF => true,
+        0xFE => day == length,
+        0xFD => day == length - 1,
+        day_of_month => i64::from(day_of_month) == day,
+    };
+    (date.year == 0xFFFF || i64::from(date.year) == year)
+        && (date.month == 0xFF || i64::from(date.month) == month)
+        && day_of_month
+        && (date.day_of_week == 0xFF || i64::from(date.day_of_week) == weekday(days))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const END_OF_BILLING: ObisCode = ObisCode { a: 0, b: 0, c: 15, d: 0, e: 0, f: 255 };
+    const SCRIPT: ScriptReference = ScriptReference {
+        script_logical_name: ObisCode { a: 0, b: 0, c: 10, d: 0, e: 1, f: 255 },
+        script_selector: 1,
+    };
+
+    fn at(hour: u8, minute: u8) -> Time {
+        Time::new(Some(hour), Some(minute), Some(0), None)
+    }
+
+    /// Local seconds of a date at midnight.
+    fn day(year: i64, month: i64, day: i64) -> u32 {
+        (days_from_civil(year, month, day) * 86_400) as u32
+    }
+
+    #[test]
+    fn test_wildcard_dates() {
+        // Every last day of the month, and every Monday, at 23:30.
+        let schedule = SingleActionSchedule::new(END_OF_BILLING, SCRIPT, ScheduleType::SameTime)
+            .with_execution_time(at(23, 30), Date::new(0xFFFF, 0xFF, 0xFE, 0xFF))
+            .unwrap()
+            .with_execution_time(at(23, 30), Date::new(0xFFFF, 0xFF, 0xFF, 1))
+            .unwrap();
+        let half_past_eleven = 23 * 3_600 + 1_800;
+
+        // Friday 2024-02-29 is the last day of February in a leap year.
+        let leap_day = day(2024, 2, 29);
+        assert_eq!(schedule.due(leap_day, leap_day + 86_399), Some(leap_day + half_past_eleven));
+        assert_eq!(schedule.due(day(2024, 2, 28), day(2024, 2, 29)), None);
+        // Monday 2024-03-04.
+        let monday = day(2024, 3, 4);
+        assert_eq!(schedule.due(monday, monday + 86_399), Some(monday + half_past_eleven));
+        // A forward jump over several execution times executes the latest once.
+        assert_eq!(schedule.due(leap_day, monday + 86_399), Some(monday + half_past_eleven));
+    }
+
+    #[test]
+    fn test_type_constraints() {
+        let schedule =
+            SingleActionSchedule::new(END_OF_BILLING, SCRIPT, ScheduleType::SingleExecution)
+                .with_execution_time(at(0, 0), Date::new(0xFFFF, 0xFF, 1, 0xFF))
+                .unwrap();
+        assert_eq!(
+            schedule.clone().with_execution_time(at(0, 0), Date::new(0xFFFF, 0xFF, 15, 0xFF)),
+            Err(DataAccessResult::OtherReason)
+        );
+
+        let mut specified =
+            SingleActionSchedule::new(END_OF_BILLING, SCRIPT, ScheduleType::SpecifiedDates);
+        // Wildcards in the dates are not allowed...
+        assert_eq!(
+            specified.set_attribute(4, schedule.get_attribute(4).unwrap()),
+            Err(DataAccessResult::OtherReason)
+        );
+        // ...nor in the time.
+        let no_minute = Data::Structure(vec![Data::Structure(vec![
+            Data::Time(Time::new(Some(0), None, None, None)),
+            Data::Date(Date::new(2025, 1, 1, 0xFF)),
+        ])]);
+        assert_eq!(specified.set_attribute(4, no_minute), Err(DataAccessResult::OtherReason));
+
+        specified.set_attribute(3, Data::Enum(1)).unwrap();
+        specified.set_attribute(4, schedule.get_attribute(4).unwrap()).unwrap();
+        assert_eq!(specified.set_attribute(3, Data::Enum(4)), Err(DataAccessResult::OtherReason));
+        assert_eq!(specified.set_attribute(3, Data::Enum(6)), Err(DataAccessResult::TypeUnmatched));
+        assert_eq!(specified.get_attribute(3), Ok(Data::Enum(1)));
+        assert_eq!(specified.get_attribute(2), Ok(SCRIPT.to_data()));
+    }
+}
@@ -0,0 +1,256 @@
+//! COSEM Interface Class 41: TCP-UDP Setup
+//!
+//! Holds the parameters of the TCP or UDP based transport layer: the port
+//! the DLMS/COSEM wrapper listens on, the IP setup object it uses, and the
+//! TCP connection parameters.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.25.0.0.255)
+//! - Attribute 2: `TCP-UDP_port` - Listening port, 4059 by default
+//! - Attribute 3: `IP_reference` - Logical name of the IPv4 or IPv6 Setup object
+//! - Attribute 4: `MSS` - Maximum segment size, 40 to 65535 bytes
+//! - Attribute 5: `nb_of_sim_conn` - Maximum simultaneous connections, at least 1
+//! - Attribute 6: `inactivity_time_out` - Seconds, 0 to disable
+//!
+//! ## Methods
+//! None.
+//!
+//! Values out of range are rejected with `OtherReason`.
+//!
+//! # Example
+//! ```
+//! use std::net::{Ipv4Addr, SocketAddr};
+//!
+//! use dlms_cosem::cosem::ipv4_setup::Ipv4Setup;
+//! use dlms_cosem::cosem::tcp_udp_setup::TcpUdpSetup;
+//! use dlms_cosem::ObisCode;
+//!
+//! let ipv4_setup = Ipv4Setup::new(ObisCode::new(0, 0, 25, 1, 0, 255))
+//!     .with_ip_address(Ipv4Addr::new(192, 0, 2, 10));
+//! let tcp_udp_setup = TcpUdpSetup::new(ObisCode::new(0, 0, 25, 0, 0, 255), ipv4_setup.logical_name)
+//!     .with_port(4061);
+//!
+//! assert_eq!(
+//!     tcp_udp_setup.socket_addr(ipv4_setup.ip_address.into()),
+//!     "192.0.2.10:4061".parse::<SocketAddr>().unwrap()
+//! );
+//! ```
+
+use core::ops::RangeFrom;
+use std::net::{IpAddr, SocketAddr};
+
+use crate::action::ActionResult;
+use crate::cosem::CosemObject;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::data::Data;
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Port registered by IANA for DLMS/COSEM.
+pub const DLMS_PORT: u16 = 4059;
+
+/// Valid maximum segment sizes (attribute 4).
+const MSS: RangeFrom<u16> = 40..;
+
+/// TCP-UDP Setup object - COSEM Interface Class 41
+///
+/// Reference: Blue Book 4.9.2
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TcpUdpSetup {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Listening port
+    pub port: u16,
+    /// Attribute 3: Logical name of the IPv4 or IPv6 Setup object
+    pub ip_reference: ObisCode,
+    /// Attribute 4: Maximum segment size
+    pub mss: u16,
+    /// Attribute 5: Maximum simultaneous connections
+    pub max_simultaneous_connections: u8,
+    /// Attribute 6: Seconds without data before closing, 0 to disable
+    pub inactivity_time_out: u16,
+}
+
+impl TcpUdpSetup {
+    /// Creates a TCP-UDP setup with the default values of the Blue Book:
+    /// port 4059, MSS of 576 bytes, one connection, 180 s inactivity timeout.
+    pub fn new(logical_name: ObisCode, ip_reference: ObisCode) -> Self {
+        Self {
+            logical_name,
+            port: DLMS_PORT,
+            ip_reference,
+            mss: 576,
+            max_simultaneous_connections: 1,
+            inactivity_time_out: 180,
+        }
+    }
+
+    /// Sets the listening port (builder style).
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Sets the maximum segment size (builder style).
+    pub fn with_mss(mut self, mss: u16) -> Result<Self, DataAccessResult> {
+        if !MSS.contains(&mss) {
+            return Err(DataAccessResult::OtherReason);
+        }
+        self.mss = mss;
+        Ok(self)
+    }
+
+    /// Sets the maximum simultaneous connections (builder style).
+    pub fn with_max_simultaneous_connections(
+        mut self,
+        connections: u8,
+    ) -> Result<Self, DataAccessResult> {
+        if connections == 0 {
+            return Err(DataAccessResult::OtherReason);
+        }
+        self.max_simultaneous_connections = connections;
+        Ok(self)
+    }
+
+    /// Sets the inactivity timeout in seconds, 0 to disable (builder style).
+    pub fn with_inactivity_time_out(mut self, seconds: u16) -> Self {
+        self.inactivity_time_out = seconds;
+        self
+    }
+
+    /// Returns the socket address to listen on at `ip`, typically the
+    /// address of the referenced IP setup object.
+    pub fn socket_addr(&self, ip: IpAddr) -> SocketAddr {
+        SocketAddr::new(ip, self.port)
+    }
+}
+
+impl CosemObject for TcpUdpSetup {
+    fn class_id(&self) -> u16 {
+        41
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::LongUnsigned(self.port)),
+            3 => Ok(Data::OctetString(self.ip_reference.encode().to_vec())),
+            4 => Ok(Data::LongUnsigned(self.mss)),
+            5 => Ok(Data::Unsigned(self.max_simultaneous_connections)),
+            6 => Ok(Data::LongUnsigned(self.inactivity_time_out)),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, value: Data) -> Result<(), DataAccessResult> {
+        match (id, value) {
+            (2, Data::LongUnsigned(port)) => self.port = port,
+            (3, Data::OctetString(name)) => {
+                self.ip_reference = ObisCode::try_from(name.as_slice())
+                    .map_err(|_| DataAccessResult::TypeUnmatched)?
+            }
+            (4, Data::LongUnsigned(mss)) if MSS.contains(&mss) => self.mss = mss,
+            (5, Data::Unsigned(connections)) if connections > 0 => {
+                self.max_simultaneous_connections = connections
+            }
+            (6, Data::LongUnsigned(seconds)) => self.inactivity_time_out = seconds,
+            (4, Data::LongUnsigned(_)) | (5, Data::Unsigned(_)) => {
+                return Err(DataAccessResult::OtherReason);
+            }
+            (2..=6, _) => return Err(DataAccessResult::TypeUnmatched),
+            (1, _) => return Err(DataAccessResult::ReadWriteDenied),
+            _ => return Err(DataAccessResult::ObjectUndefined),
+        }
+        Ok(())
+    }
+
+    fn invoke_method(
+        &mut self,
+        _method_id: i8,
+        _params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        Err(ActionResult::ObjectUndefined)
+    }
+
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, 2..=6).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        state.restore_attributes(self, 2..=6).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv6Addr;
+
+    const TCP_UDP_SETUP: ObisCode = ObisCode { a: 0, b: 0, c: 25, d: 0, e: 0, f: 255 };
+    const IPV6_SETUP: ObisCode = ObisCode { a: 0, b: 0, c: 25, d: 7, e: 0, f: 255 };
+
+    #[test]
+    fn test_attributes() {
+        let mut tcp_udp_setup = TcpUdpSetup::new(TCP_UDP_SETUP, IPV6_SETUP);
+        assert_eq!(tcp_udp_setup.get_attribute(2), Ok(Data::LongUnsigned(4059)));
+        assert_eq!(
+            tcp_udp_setup.get_attribute(3),
+            Ok(Data::OctetString(vec![0, 0, 25, 7, 0, 255]))
+        );
+        assert_eq!(tcp_udp_setup.get_attribute(4), Ok(Data::LongUnsigned(576)));
+
+        assert_eq!(tcp_udp_setup.set_attribute(2, Data::LongUnsigned(4063)), Ok(()));
+        assert_eq!(tcp_udp_setup.set_attribute(4, Data::LongUnsigned(1460)), Ok(()));
+        assert_eq!(tcp_udp_setup.set_attribute(5, Data::Unsigned(4)), Ok(()));
+        assert_eq!(tcp_udp_setup.set_attribute(6, Data::LongUnsigned(0)), Ok(()));
+        assert_eq!(
+            tcp_udp_setup,
+            TcpUdpSetup::new(TCP_UDP_SETUP, IPV6_SETUP)
+                .with_port(4063)
+                .with_mss(1460)
+                .and_then(|setup| setup.with_max_simultaneous_connections(4))
+                .map(|setup| setup.with_inactivity_time_out(0))
+                .unwrap()
+        );
+        assert_eq!(tcp_udp_setup.socket_addr(Ipv6Addr::LOCALHOST.into()).to_string(), "[::1]:4063");
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut tcp_udp_setup = TcpUdpSetup::new(TCP_UDP_SETUP, IPV6_SETUP);
+        assert_eq!(
+            tcp_udp_setup.set_attribute(4, Data::LongUnsigned(39)),
+            Err(DataAccessResult::OtherReason)
+        );
+        assert_eq!(
+            tcp_udp_setup.set_attribute(5, Data::Unsigned(0)),
+            Err(DataAccessResult::OtherReason)
+        );
+        assert_eq!(
+            tcp_udp_setup.set_attribute(3, Data::OctetString(vec![0, 0, 25])),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        assert_eq!(
+            tcp_udp_setup.set_attribute(2, Data::Unsigned(1)),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+        assert_eq!(
+            tcp_udp_setup.set_attribute(1, Data::Null),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+        assert_eq!(tcp_udp_setup, TcpUdpSetup::new(TCP_UDP_SETUP, IPV6_SETUP));
+        assert_eq!(tcp_udp_setup.clone().with_mss(20), Err(DataAccessResult::OtherReason));
+    }
+}
@@ -0,0 +1,504 @@
+//! COSEM Interface Class 115: Token Gateway
+//!
+//! The Token Gateway receives the tokens carrying payments and management
+//! commands, e.g. the 20-digit tokens of the STS standard, entered remotely
+//! with the `enter` method or locally on the keypad of the meter.
+//!
+//! ## Attributes
+//! - Attribute 1: `logical_name` - OBIS code (e.g. 0.0.19.40.0.255)
+//! - Attribute 2: `token` - Last token received
+//! - Attribute 3: `token_time` - Local time it was received
+//! - Attribute 4: `token_description` - Array of octet strings describing it
+//! - Attribute 5: `token_delivery_method` - Remote, local or manual
+//! - Attribute 6: `token_status` - `{token_status_code, token_status_data}`
+//!
+//! All attributes are read-only.
+//!
+//! ## Methods
+//! - Method 1: `enter(octet-string)` - Enters a token
+//!
+//! ## Processing
+//!
+//! Decoding a token (format, authentication, validation against tokens
+//! already used) is specific to the token standard and is delegated to a
+//! [`TokenDecoder`]; without one, every token fails authentication. A
+//! decoded payment is shared among the credits of the
+//! [`Account`](crate::cosem::account) according to its
+//! `token_gateway_configuration`, the rounding remainder going to the first
+//! credit, and added with their `update_amount` method. Tokens fail
+//! execution when the account is missing or closed, or when the gateway is
+//! used outside a registry. [`ObjectRegistry::tick`] stamps the tokens;
+//! times are local seconds since 1970-01-01.
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::cosem::account::{Account, PaymentMode};
+//! use dlms_cosem::cosem::credit::{Credit, CreditType};
+//! use dlms_cosem::cosem::token_gateway::{DecodedToken, TokenAction, TokenDecoder, TokenGateway};
+//! use dlms_cosem::cosem::{CosemObject, ObjectRegistry};
+//! use dlms_cosem::token::{TokenStatus, TokenStatusCode};
+//! use dlms_cosem::{Data, ObisCode};
+//!
+//! /// Tokens are the amount as ASCII digits.
+//! #[derive(Debug)]
+//! struct Digits;
+//!
+//! impl TokenDecoder for Digits {
+//!     fn decode(&mut self, token: &[u8]) -> Result<DecodedToken, TokenStatus> {
+//!         core::str::from_utf8(token)
+//!             .ok()
+//!             .and_then(|digits| digits.parse().ok())
+//!             .map(|amount| DecodedToken { action: TokenAction::AddCredit(amount), description: vec![] })
+//!             .ok_or(TokenStatus::new(TokenStatusCode::FormatFailure))
+//!     }
+//! }
+//!
+//! let account = ObisCode::new(0, 0, 19, 0, 0, 255);
+//! let credit = ObisCode::new(0, 0, 19, 10, 0, 255);
+//! let gateway = ObisCode::new(0, 0, 19, 40, 0, 255);
+//! let mut objects = ObjectRegistry::new()
+//!     .with_object(Account::new(account, PaymentMode::Prepayment).with_credit(credit, 100))
+//!     .with_object(Credit::new(credit, CreditType::Token, 1))
+//!     .with_object(TokenGateway::new(gateway).with_decoder(Box::new(Digits)));
+//!
+//! objects.invoke_method(115, &gateway, 1, Some(Data::OctetString(b"2000".to_vec()))).unwrap();
+//! assert_eq!(objects.get(112, &credit).unwrap().get_attribute(2), Ok(Data::DoubleLong(2000)));
+//! assert_eq!(
+//!     objects.get(115, &gateway).unwrap().get_attribute(6),
+//!     Ok(TokenStatus::new(TokenStatusCode::ExecutionOk).to_data())
+//! );
+//! ```
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::action::ActionResult;
+use crate::cosem::account::{self, TokenGatewayConfiguration};
+use crate::cosem::activity_calendar::NOT_SCHEDULED;
+use crate::cosem::credit::CREDIT_CLASS_ID;
+#[cfg(feature = "persistence")]
+use crate::cosem::persistence::ObjectState;
+use crate::cosem::push_setup::date_time_at;
+use crate::cosem::script_table::ScriptReference;
+use crate::cosem::{CosemObject, ObjectRegistry};
+use crate::data::{Data, DateTime};
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+use crate::token::{TokenStatus, TokenStatusCode};
+
+/// Logical name of the Account credited by default.
+const ACCOUNT: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 0, e: 0, f: 255 };
+
+/// How a token reached the meter (attribute 5).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TokenDeliveryMethod {
+    /// Through the `enter` method
+    Remote = 0,
+    /// On the keypad of the meter
+    Local = 1,
+    /// By other means, e.g. a smart card
+    Manual = 2,
+}
+
+/// What a token does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenAction {
+    /// A payment, shared among the credits of the account
+    AddCredit(i32),
+    /// Sets the credits of the account to zero
+    ClearCredit,
+}
+
+/// A token decoded by a [`TokenDecoder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedToken {
+    /// What the token does
+    pub action: TokenAction,
+    /// Description reported in `token_description`
+    pub description: Vec<Vec<u8>>,
+}
+
+/// Decodes the tokens of a token standard, e.g. STS: checks their format,
+/// authenticates them with the meter key and rejects tokens already used.
+pub trait TokenDecoder: fmt::Debug + Send {
+    /// Decodes a token, or returns the failure status to report.
+    fn decode(&mut self, token: &[u8]) -> Result<DecodedToken, TokenStatus>;
+}
+
+/// Token Gateway object - COSEM Interface Class 115
+///
+/// Reference: Blue Book 4.6.5
+#[derive(Debug)]
+pub struct TokenGateway {
+    /// Attribute 1: Logical name (OBIS code)
+    pub logical_name: ObisCode,
+    /// Attribute 2: Last token received
+    pub token: Vec<u8>,
+    /// Attribute 3: Local time the last token was received
+    pub token_time: DateTime,
+    /// Attribute 4: Description of the last token
+    pub token_description: Vec<Vec<u8>>,
+    /// Attribute 5: How the last token was delivered
+    pub token_delivery_method: TokenDeliveryMethod,
+    /// Attribute 6: Outcome of the last token
+    pub token_status: TokenStatus,
+    /// Logical name of the Account credited
+    pub account: ObisCode,
+    decoder: Option<Box<dyn TokenDecoder>>,
+    /// Time of the last tick
+    now: Option<u32>,
+}
+
+impl TokenGateway {
+    /// Creates a gateway crediting the Account 0.0.19.0.0.255, without
+    /// decoder.
+    pub fn new(logical_name: ObisCode) -> Self {
+        Self {
+            logical_name,
+            token: Vec::new(),
+            token_time: NOT_SCHEDULED,
+            token_description: Vec::new(),
+            token_delivery_method: TokenDeliveryMethod::Remote,
+            token_status: TokenStatus::new(TokenStatusCode::FormatOk),
+            account: ACCOUNT,
+            decoder: None,
+            now: None,
+        }
+    }
+
+    /// Sets the Account credited (builder style).
+    pub fn with_account(mut self, account: ObisCode) -> Self {
+        self.account = account;
+        self
+    }
+
+    /// Sets the decoder of the tokens (builder style).
+    pub fn with_decoder(mut self, decoder: Box<dyn TokenDecoder>) -> Self {
+        self.decoder = Some(decoder);
+        self
+    }
+
+    /// Enters a token delivered by `delivery_method`, crediting the account
+    /// in `objects`, and returns its status.
+    ///
+    /// This is the `enter` method for remote tokens; call it directly, with
+    /// the gateway taken out of the registry, for tokens entered locally.
+    pub fn enter(
+        &mut self,
+        token: &[u8],
+        delivery_method: TokenDeliveryMethod,
+        objects: &mut ObjectRegistry,
+    ) -> &TokenStatus {
+        if let Some(action) = self.receive(token, delivery_method) {
+            let executed = account::token_gateway_configuration(objects, &self.account)
+                .is_some_and(|configuration| execute(action, &configuration, objects));
+            self.token_status = TokenStatus::new(if executed {
+                TokenStatusCode::ExecutionOk
+            } else {
+                TokenStatusCode::ExecutionFailure
+            });
+        }
+        &self.token_status
+    }
+
+    /// Records and decodes a token; returns its action if it decoded.
+    fn receive(
+        &mut self,
+        token: &[u8],
+        delivery_method: TokenDeliveryMethod,
+    ) -> Option<TokenAction> {
+        self.token = token.to_vec();
+        if let Some(now) = self.now {
+            self.token_time = date_time_at(now);
+        }
+        self.token_delivery_method = delivery_method;
+        self.token_description.clear();
+        let decoded = match self.decoder.as_mut() {
+            Some(decoder) => decoder.decode(token),
+            None => Err(TokenStatus::new(TokenStatusCode::AuthenticationFailure)),
+        };
+        match decoded {
+            Ok(decoded) => {
+                self.token_description = decoded.description;
+                self.token_status = TokenStatus::new(TokenStatusCode::ValidationOk);
+                Some(decoded.action)
+            }
+            Err(status) => {
+                self.token_status = status;
+                None
+            }
+        }
+    }
+}
+
+/// Applies a token action to the credits of a token gateway configuration;
+/// returns false, changing nothing, if a credit is missing.
+fn execute(
+    action: TokenAction,
+    configuration: &[TokenGatewayConfiguration],
+    objects: &mut ObjectRegistry,
+) -> bool {
+    if configuration.is_empty()
+        || configuration
+            .iter()
+            .any(|share| objects.get(CREDIT_CLASS_ID, &share.credit_reference).is_none())
+    {
+        return false;
+    }
+    let updates: Vec<_> = match action {
+        TokenAction::AddCredit(amount) => {
+            let mut shares: Vec<_> = configuration
+                .iter()
+                .map(|share| i64::from(amount) * i64::from(share.token_proportion) / 100)
+                .collect();
+            shares[0] += i64::from(amount) - shares.iter().sum::<i64>();
+            configuration
+                .iter()
+                .zip(shares)
+                .map(|(share, amount)| (share.credit_reference, 1, amount as i32))
+                .collect()
+        }
+        TokenAction::ClearCredit => {
+            configuration.iter().map(|share| (share.credit_reference, 2, 0)).collect()
+        }
+    };
+    updates.into_iter().all(|(credit, method_id, amount)| {
+        objects
+            .invoke_method(CREDIT_CLASS_ID, &credit, method_id, Some(Data::DoubleLong(amount)))
+            .is_ok()
+    })
+}
+
+impl CosemObject for TokenGateway {
+    fn class_id(&self) -> u16 {
+        115
+    }
+
+    fn version(&self) -> u8 {
+        0
+    }
+
+    fn logical_name(&self) -> &ObisCode {
+        &self.logical_name
+    }
+
+    fn get_attribute(&self, id: i8) -> Result<Data, DataAccessResult> {
+        match id {
+            1 => Ok(Data::OctetString(self.logical_name.encode().to_vec())),
+            2 => Ok(Data::OctetString(self.token.clone())),
+            3 => Ok(Data::DateTime(self.token_time.clone())),
+            4 => Ok(Data::Structure(
+                self.token_description.iter().cloned().map(Data::OctetString).collect(),
+            )),
+            5 => Ok(Data::Enum(self.token_delivery_method as u8)),
+            6 => Ok(self.token_status.to_data()),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    fn set_attribute(&mut self, id: i8, _value: Data) -> Result<(), DataAccessResult> {
+        match id {
+            1..=6 => Err(DataAccessResult::ReadWriteDenied),
+            _ => Err(DataAccessResult::ObjectUndefined),
+        }
+    }
+
+    /// Outside a registry, decoded tokens fail execution.
+    fn invoke_method(
+        &mut self,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match (method_id, params) {
+            (1, Some(Data::OctetString(token))) => {
+                if self.receive(&token, TokenDeliveryMethod::Remote).is_some() {
+                    self.token_status = TokenStatus::new(TokenStatusCode::ExecutionFailure);
+                }
+                Ok(None)
+            }
+            (1, _) => Err(ActionResult::TypeUnmatched),
+            _ => Err(ActionResult::ObjectUndefined),
+        }
+    }
+
+    /// Enters the token, crediting the account in the registry.
+    fn invoke_method_with_objects(
+        &mut self,
+        objects: &mut ObjectRegistry,
+        method_id: i8,
+        params: Option<Data>,
+    ) -> Result<Option<Data>, ActionResult> {
+        match (method_id, params) {
+            (1, Some(Data::OctetString(token))) => {
+                self.enter(&token, TokenDeliveryMethod::Remote, objects);
+                Ok(None)
+            }
+            (method_id, params) => self.invoke_method(method_id, params),
+        }
+    }
+
+    fn tick(
+        &mut self,
+        now: u32,
+        _objects: &ObjectRegistry,
+        _executor: &mut dyn FnMut(&ScriptReference),
+    ) {
+        self.now = Some(now);
+    }
+
+    /// Persists the last token and its outcome.
+    #[cfg(feature = "persistence")]
+    fn save_state(&self) -> Option<ObjectState> {
+        ObjectState::from_attributes(self, 2..=6).ok()
+    }
+
+    #[cfg(feature = "persistence")]
+    fn restore_state(&mut self, state: ObjectState) -> Result<(), DataAccessResult> {
+        match state.attributes.as_slice() {
+            [
+                Data::OctetString(token),
+                Data::DateTime(token_time),
+                Data::Structure(description),
+                Data::Enum(delivery_method),
+                token_status,
+            ] => {
+                self.token = token.clone();
+                self.token_time = token_time.clone();
+                self.token_description = description
+                    .iter()
+                    .map(|line| match line {
+                        Data::OctetString(line) => Ok(line.clone()),
+                        _ => Err(DataAccessResult::TypeUnmatched),
+                    })
+                    .collect::<Result<_, _>>()?;
+                self.token_delivery_method = match delivery_method {
+                    0 => TokenDeliveryMethod::Remote,
+                    1 => TokenDeliveryMethod::Local,
+                    2 => TokenDeliveryMethod::Manual,
+                    _ => return Err(DataAccessResult::TypeUnmatched),
+                };
+                self.token_status = TokenStatus::from_data(token_status)?;
+                Ok(())
+            }
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::account::{Account, PaymentMode};
+    use crate::cosem::credit::{Credit, CreditType};
+    use alloc::vec;
+
+    const GATEWAY: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 40, e: 0, f: 255 };
+    const TOKEN_CREDIT: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 10, e: 0, f: 255 };
+    const RESERVED_CREDIT: ObisCode = ObisCode { a: 0, b: 0, c: 19, d: 10, e: 2, f: 255 };
+
+    /// Accepts "+<amount>" and "clear", each once.
+    #[derive(Debug, Default)]
+    struct OneTime {
+        used: Vec<Vec<u8>>,
+    }
+
+    impl TokenDecoder for OneTime {
+        fn decode(&mut self, token: &[u8]) -> Result<DecodedToken, TokenStatus> {
+            if self.used.iter().any(|used| used == token) {
+                return Err(TokenStatus {
+                    code: TokenStatusCode::ValidationFailure,
+                    data: vec![0x80],
+                });
+            }
+            let action = match token {
+                b"clear" => TokenAction::ClearCredit,
+                [b'+', digits @ ..] => core::str::from_utf8(digits)
+                    .ok()
+                    .and_then(|digits| digits.parse().ok())
+                    .map(TokenAction::AddCredit)
+                    .ok_or(TokenStatus::new(TokenStatusCode::FormatFailure))?,
+                _ => return Err(TokenStatus::new(TokenStatusCode::FormatFailure)),
+            };
+            self.used.push(token.to_vec());
+            Ok(DecodedToken { action, description: vec![b"test token".to_vec()] })
+        }
+    }
+
+    fn objects() -> ObjectRegistry {
+        ObjectRegistry::new()
+            .with_object(
+                Account::new(ACCOUNT, PaymentMode::Prepayment)
+                    .with_credit(TOKEN_CREDIT, 70)
+                    .with_credit(RESERVED_CREDIT, 30),
+            )
+            .with_object(Credit::new(TOKEN_CREDIT, CreditType::Token, 1))
+            .with_object(Credit::new(RESERVED_CREDIT, CreditType::Reserved, 2))
+            .with_object(TokenGateway::new(GATEWAY).with_decoder(Box::<OneTime>::default()))
+    }
+
+    fn amounts(objects: &ObjectRegistry) -> [Data; 2] {
+        [TOKEN_CREDIT, RESERVED_CREDIT]
+            .map(|credit| objects.get(112, &credit).unwrap().get_attribute(2).unwrap())
+    }
+
+    fn enter(objects: &mut ObjectRegistry, token: &[u8]) -> Data {
+        let token = Some(Data::OctetString(token.to_vec()));
+        assert_eq!(objects.invoke_method(115, &GATEWAY, 1, token), Ok(None));
+        objects.get(115, &GATEWAY).unwrap().get_attribute(6).unwrap()
+    }
+
+    #[test]
+    fn test_enter_shares_payment() {
+        let mut objects = objects();
+        objects.tick(1_738_368_000, &mut |_| {});
+
+        let executed = TokenStatus::new(TokenStatusCode::ExecutionOk).to_data();
+        assert_eq!(enter(&mut objects, b"+1001"), executed);
+        assert_eq!(amounts(&objects), [Data::DoubleLong(701), Data::DoubleLong(300)]);
+        let gateway = objects.get(115, &GATEWAY).unwrap();
+        assert_eq!(gateway.get_attribute(2), Ok(Data::OctetString(b"+1001".to_vec())));
+        assert_eq!(gateway.get_attribute(3), Ok(Data::DateTime(date_time_at(1_738_368_000))));
+        assert_eq!(
+            gateway.get_attribute(4),
+            Ok(Data::Structure(vec![Data::OctetString(b"test token".to_vec())]))
+        );
+        assert_eq!(gateway.get_attribute(5), Ok(Data::Enum(0)));
+
+        assert_eq!(
+            enter(&mut objects, b"+1001"),
+            Data::Structure(vec![Data::Enum(6), Data::BitString(vec![0x80])])
+        );
+        assert_eq!(amounts(&objects), [Data::DoubleLong(701), Data::DoubleLong(300)]);
+
+        assert_eq!(enter(&mut objects, b"clear"), executed);
+        assert_eq!(amounts(&objects), [Data::DoubleLong(0), Data::DoubleLong(0)]);
+    }
+
+    #[test]
+    fn test_rejected_tokens() {
+        let mut objects = objects();
+        let status = |code| TokenStatus::new(code).to_data();
+        assert_eq!(enter(&mut objects, b"1001"), status(TokenStatusCode::FormatFailure));
+
+        objects.invoke_method(111, &ACCOUNT, 1, None).unwrap();
+        objects.invoke_method(111, &ACCOUNT, 2, None).unwrap();
+        assert_eq!(enter(&mut objects, b"+10"), status(TokenStatusCode::ExecutionFailure));
+        assert_eq!(amounts(&objects), [Data::DoubleLong(0), Data::DoubleLong(0)]);
+
+        let mut gateway = TokenGateway::new(GATEWAY);
+        let token = Some(Data::OctetString(b"+10".to_vec()));
+        assert_eq!(gateway.invoke_method(1, token.clone()), Ok(None));
+        assert_eq!(gateway.token_status.code, TokenStatusCode::AuthenticationFailure);
+
+        let mut gateway = gateway.with_decoder(Box::<OneTime>::default());
+        assert_eq!(gateway.invoke_method(1, token), Ok(None));
+        assert_eq!(gateway.token_status.code, TokenStatusCode::ExecutionFailure);
+        assert_eq!(gateway.invoke_method(1, Some(Data::Null)), Err(ActionResult::TypeUnmatched));
+        assert_eq!(
+            gateway.set_attribute(2, Data::OctetString(vec![])),
+            Err(DataAccessResult::ReadWriteDenied)
+        );
+    }
+}
@@ -9,6 +9,7 @@ use core::fmt;
 #[cfg(feature = "parse")]
 use nom::{
     IResult, Parser,
+    bytes::streaming::take,
     combinator::fail,
     multi::length_count,
     number::streaming::{be_f32, be_f64, be_i16, be_i32, be_i64, be_u16, be_u32, be_u64, i8, u8},
@@ -689,6 +690,7 @@ impl Serialize for DateTime {
 #[non_exhaustive]
 pub enum Data {
     Null,
+    Boolean(bool),
     OctetString(Vec<u8>),
     Utf8String(String),
     BitString(Vec<u8>),
@@ -806,6 +808,52 @@ impl ByteBuffer for Vec<u8> {
     }
 }
 
+/// Parses an A-XDR length: one byte up to 127, otherwise 0x81 to 0x84
+/// followed by the length in that many bytes.
+#[cfg(feature = "parse")]
+fn parse_length(input: &[u8]) -> IResult<&[u8], usize> {
+    let (input, first) = u8(input)?;
+    match first {
+        0x00..=0x7F => Ok((input, first.into())),
+        0x81..=0x84 => {
+            let (input, bytes) = take(usize::from(first & 0x7F)).parse(input)?;
+            Ok((input, bytes.iter().fold(0, |length, byte| length << 8 | usize::from(*byte))))
+        }
+        _ => Err(nom::Err::Failure(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::LengthValue,
+        ))),
+    }
+}
+
+/// Appends an A-XDR length in its shortest form.
+#[cfg(feature = "encode")]
+fn push_length(buffer: &mut Vec<u8>, length: usize) {
+    match length {
+        0..=0x7F => buffer.push_u8(length as u8),
+        0x80..=0xFF => buffer.push_bytes(&[0x81, length as u8]),
+        0x100..=0xFFFF => {
+            buffer.push_u8(0x82);
+            buffer.push_u16(length as u16);
+        }
+        _ => {
+            buffer.push_u8(0x84);
+            buffer.push_u32(length as u32);
+        }
+    }
+}
+
+/// Number of bytes [`push_length`] appends.
+#[cfg(feature = "encode")]
+fn length_len(length: usize) -> usize {
+    match length {
+        0..=0x7F => 1,
+        0x80..=0xFF => 2,
+        0x100..=0xFFFF => 3,
+        _ => 5,
+    }
+}
+
 impl Data {
     #[cfg(feature = "parse")]
     pub fn parse(input: &[u8]) -> IResult<&[u8], Self> {
@@ -827,16 +875,20 @@ impl Data {
                 (input, Data::Time(time))
             }
             DataType::Null => (input, Data::Null),
+            DataType::Bool => {
+                let (input, value) = u8(input)?;
+                (input, Data::Boolean(value != 0))
+            }
             DataType::Structure => {
-                let (input, structure) = length_count(u8, Self::parse).parse(input)?;
+                let (input, structure) = length_count(parse_length, Self::parse).parse(input)?;
                 (input, Data::Structure(structure))
             }
             DataType::OctetString => {
-                let (input, bytes) = length_count(u8, u8).parse(input)?;
+                let (input, bytes) = length_count(parse_length, u8).parse(input)?;
                 (input, Data::OctetString(bytes))
             }
             DataType::BitString => {
-                let (input, bytes) = length_count(u8, u8).parse(input)?;
+                let (input, bytes) = length_count(parse_length, u8).parse(input)?;
                 (input, Data::BitString(bytes))
             }
             DataType::Float32 => {
@@ -884,13 +936,20 @@ impl Data {
                 (input, Data::Unsigned(n))
             }
             DataType::Utf8String => {
-                let (input, bytes) = length_count(u8, u8).parse(input)?;
+                let (input, bytes) = length_count(parse_length, u8).parse(input)?;
                 let string = String::from_utf8(bytes).map_err(|_| {
                     nom::Err::Failure(nom::error::Error::new(input, nom::error::ErrorKind::Fail))
                 })?;
                 (input, Data::Utf8String(string))
             }
-            dt => unimplemented!("decoding data type {:?}", dt),
+            // Types without a Data variant (array, visible-string,
+            // ...) are rejected rather than misread
+            _ => {
+                return Err(nom::Err::Failure(nom::error::Error::new(
+                    input,
+                    nom::error::ErrorKind::Switch,
+                )));
+            }
         })
     }
 
@@ -907,6 +966,12 @@ impl Data {
                 buffer.push_u8(0x00);
             }
 
+            // Boolean: tag + 0x00 (false) or 0x01 (true)
+            Data::Boolean(value) => {
+                buffer.push_u8(0x03);
+                buffer.push_u8(*value as u8);
+            }
+
             // Integer: tag + i8 value
             Data::Integer(value) => {
                 buffer.push_u8(0x0F);
@@ -976,7 +1041,7 @@ impl Data {
             // OctetString: tag + length + bytes
             Data::OctetString(bytes) => {
                 buffer.push_u8(0x09);
-                buffer.push_u8(bytes.len() as u8);
+                push_length(&mut buffer, bytes.len());
                 buffer.push_bytes(bytes);
             }
 
@@ -984,14 +1049,14 @@ impl Data {
             Data::Utf8String(string) => {
                 buffer.push_u8(0x0C);
                 let bytes = string.as_bytes();
-                buffer.push_u8(bytes.len() as u8);
+                push_length(&mut buffer, bytes.len());
                 buffer.push_bytes(bytes);
             }
 
             // BitString: tag + length + bytes
             Data::BitString(bytes) => {
                 buffer.push_u8(0x04);
-                buffer.push_u8(bytes.len() as u8);
+                push_length(&mut buffer, bytes.len());
                 buffer.push_bytes(bytes);
             }
 
@@ -1016,7 +1081,7 @@ impl Data {
             // Structure: tag + count + encoded elements
             Data::Structure(elements) => {
                 buffer.push_u8(0x02);
-                buffer.push_u8(elements.len() as u8);
+                push_length(&mut buffer, elements.len());
                 for element in elements {
                     buffer.push_bytes(&element.encode());
                 }
@@ -1031,26 +1096,28 @@ impl Data {
     /// Useful for pre-allocating buffers
     pub fn encoded_len(&self) -> usize {
         match self {
-            Data::Null => 1,                                  // Just the tag
-            Data::Integer(_) => 2,                            // Tag + i8
-            Data::Unsigned(_) => 2,                           // Tag + u8
-            Data::Long(_) => 3,                               // Tag + i16
-            Data::LongUnsigned(_) => 3,                       // Tag + u16
-            Data::DoubleLong(_) => 5,                         // Tag + i32
-            Data::DoubleLongUnsigned(_) => 5,                 // Tag + u32
-            Data::Long64(_) => 9,                             // Tag + i64
-            Data::Long64Unsigned(_) => 9,                     // Tag + u64
-            Data::Enum(_) => 2,                               // Tag + u8
-            Data::Float32(_) => 5,                            // Tag + f32
-            Data::Float64(_) => 9,                            // Tag + f64
-            Data::OctetString(bytes) => 1 + 1 + bytes.len(),  // Tag + length + data
-            Data::Utf8String(string) => 1 + 1 + string.len(), // Tag + length + UTF-8 bytes
-            Data::BitString(bytes) => 1 + 1 + bytes.len(),    // Tag + length + data
-            Data::DateTime(_) => 1 + 12,                      // Tag + 12 bytes for DateTime
-            Data::Date(_) => 1 + 5,                           // Tag + 5 bytes for Date
-            Data::Time(_) => 1 + 4,                           // Tag + 4 bytes for Time
+            Data::Null => 1,                                           // Just the tag
+            Data::Boolean(_) => 2,                                     // Tag + u8
+            Data::Integer(_) => 2,                                     // Tag + i8
+            Data::Unsigned(_) => 2,                                    // Tag + u8
+            Data::Long(_) => 3,                                        // Tag + i16
+            Data::LongUnsigned(_) => 3,                                // Tag + u16
+            Data::DoubleLong(_) => 5,                                  // Tag + i32
+            Data::DoubleLongUnsigned(_) => 5,                          // Tag + u32
+            Data::Long64(_) => 9,                                      // Tag + i64
+            Data::Long64Unsigned(_) => 9,                              // Tag + u64
+            Data::Enum(_) => 2,                                        // Tag + u8
+            Data::Float32(_) => 5,                                     // Tag + f32
+            Data::Float64(_) => 9,                                     // Tag + f64
+            Data::OctetString(b) => 1 + length_len(b.len()) + b.len(), // Tag + length + data
+            Data::Utf8String(s) => 1 + length_len(s.len()) + s.len(),  // Tag + length + UTF-8 bytes
+            Data::BitString(b) => 1 + length_len(b.len()) + b.len(),   // Tag + length + data
+            Data::DateTime(_) => 1 + 12, // Tag + 12 bytes for DateTime
+            Data::Date(_) => 1 + 5,      // Tag + 5 bytes for Date
+            Data::Time(_) => 1 + 4,      // Tag + 4 bytes for Time
             Data::Structure(elements) => {
-                1 + 1 + elements.iter().map(|e| e.encoded_len()).sum::<usize>()
+                1 + length_len(elements.len())
+                    + elements.iter().map(|e| e.encoded_len()).sum::<usize>()
             }
         }
     }
@@ -2087,6 +2154,58 @@ mod tests {
         assert_eq!(parsed, original);
     }
 
+    #[test]
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    fn test_encode_long_lengths() {
+        // A-XDR lengths above 127 use 0x81, 0x82 or 0x84 and the length bytes
+        for (len, header) in [
+            (127, vec![0x09, 0x7F]),
+            (128, vec![0x09, 0x81, 0x80]),
+            (255, vec![0x09, 0x81, 0xFF]),
+            (300, vec![0x09, 0x82, 0x01, 0x2C]),
+            (70_000, vec![0x09, 0x84, 0x00, 0x01, 0x11, 0x70]),
+        ] {
+            let data = Data::OctetString(vec![0x5A; len]);
+            let encoded = data.encode();
+            assert_eq!(encoded[..header.len()], header[..]);
+            assert_eq!(encoded.len(), header.len() + len);
+            assert_eq!(data.encoded_len(), encoded.len());
+            assert_eq!(Data::parse(&encoded).unwrap(), (&[][..], data));
+        }
+
+        let structure = Data::Structure(vec![Data::Unsigned(1); 256]);
+        let encoded = structure.encode();
+        assert_eq!(encoded[..5], [0x02, 0x82, 0x01, 0x00, 0x11]);
+        assert_eq!(structure.encoded_len(), encoded.len());
+        assert_eq!(Data::parse(&encoded).unwrap(), (&[][..], structure));
+
+        let string = Data::Utf8String("x".repeat(200));
+        assert_eq!(string.encode()[..3], [0x0C, 0x81, 200]);
+        assert_eq!(Data::parse(&string.encode()).unwrap().1, string);
+    }
+
+    #[test]
+    #[cfg(feature = "parse")]
+    fn test_parse_lengths() {
+        // The long form is accepted even where the short form would do
+        assert_eq!(
+            Data::parse(&[0x09, 0x82, 0x00, 0x02, 0xAA, 0xBB]).unwrap().1,
+            Data::OctetString(vec![0xAA, 0xBB])
+        );
+        assert_eq!(
+            Data::parse(&[0x04, 0x83, 0x00, 0x00, 0x01, 0xFF]).unwrap().1,
+            Data::BitString(vec![0xFF])
+        );
+        // Indefinite and over-long lengths are rejected
+        assert!(matches!(Data::parse(&[0x09, 0x80, 0x00]), Err(nom::Err::Failure(_))));
+        assert!(matches!(Data::parse(&[0x09, 0x85, 0, 0, 0, 0, 1, 0]), Err(nom::Err::Failure(_))));
+        // A length beyond the input needs more data
+        assert!(matches!(
+            Data::parse(&[0x09, 0x82, 0x01, 0x00, 0xAA]),
+            Err(nom::Err::Incomplete(_))
+        ));
+    }
+
     #[test]
     #[cfg(feature = "encode")]
     fn test_encode_utf8_string() {
@@ -2110,6 +2229,29 @@ mod tests {
         assert_eq!(data.encoded_len(), 2);
     }
 
+    #[test]
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    fn test_boolean() {
+        assert_eq!(Data::Boolean(true).encode(), vec![0x03, 0x01]);
+        assert_eq!(Data::Boolean(false).encode(), vec![0x03, 0x00]);
+        assert_eq!(Data::Boolean(true).encoded_len(), 2);
+        assert_eq!(Data::parse(&[0x03, 0x01]).unwrap().1, Data::Boolean(true));
+        assert_eq!(Data::parse(&[0x03, 0x00]).unwrap().1, Data::Boolean(false));
+        // Any non-zero value is true
+        assert_eq!(Data::parse(&[0x03, 0xFF]).unwrap().1, Data::Boolean(true));
+    }
+
+    #[test]
+    #[cfg(feature = "parse")]
+    fn test_parse_unsupported_types_fails() {
+        // array, visible-string and bcd have no Data variant
+        for encoded in [&[0x01, 0x00][..], &[0x0A, 0x01, b'A'], &[0x0D, 0x12]] {
+            assert!(matches!(Data::parse(encoded), Err(nom::Err::Failure(_))));
+        }
+        // Also when nested in a structure
+        assert!(Data::parse(&[0x02, 0x02, 0x11, 0x01, 0x0D, 0x00]).is_err());
+    }
+
     #[test]
     #[cfg(feature = "encode")]
     fn test_encode_date() {
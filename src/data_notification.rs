@@ -5,8 +5,13 @@ use nom::{
     number::streaming::{be_u32, u8},
 };
 
+#[cfg(feature = "encode")]
+use crate::data::ByteBuffer;
 use crate::{Data, DateTime};
 
+#[cfg(all(not(feature = "std"), feature = "encode"))]
+use alloc::vec::Vec;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct LongInvokeIdAndPriority(pub(crate) u32);
 
@@ -70,6 +75,25 @@ pub struct DataNotification {
 }
 
 impl DataNotification {
+    /// Creates an unconfirmed, normal priority notification.
+    ///
+    /// Only the lower 24 bits of `invoke_id` are used.
+    pub fn new(invoke_id: u32, date_time: DateTime, notification_body: Data) -> Self {
+        Self {
+            long_invoke_id_and_priority: LongInvokeIdAndPriority(invoke_id & 0x00ffffff),
+            date_time,
+            notification_body,
+        }
+    }
+
+    pub fn date_time(&self) -> &DateTime {
+        &self.date_time
+    }
+
+    pub fn notification_body(&self) -> &Data {
+        &self.notification_body
+    }
+
     pub fn priority(&self) -> Priority {
         self.long_invoke_id_and_priority.priority()
     }
@@ -97,6 +121,19 @@ impl DataNotification {
         let (input, notification_body) = Data::parse(input)?;
         Ok((input, Self { long_invoke_id_and_priority, date_time, notification_body }))
     }
+
+    /// Encode the DataNotification APDU (tag 0x0F)
+    #[cfg(feature = "encode")]
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.push_u8(0x0F);
+        buffer.push_u32(self.long_invoke_id_and_priority.0);
+        let date_time = self.date_time.encode();
+        buffer.push_u8(date_time.len() as u8);
+        buffer.push_bytes(&date_time);
+        buffer.push_bytes(&self.notification_body.encode());
+        buffer
+    }
 }
 
 #[cfg(test)]
@@ -334,4 +371,25 @@ mod tests {
         assert!(debug_str.contains("date_time"));
         assert!(debug_str.contains("notification_body"));
     }
+
+    #[test]
+    #[cfg(all(feature = "encode", feature = "parse"))]
+    fn test_data_notification_encode() {
+        use crate::data::{Date, Time};
+
+        let date = Date::new(2024, 1, 15, 1);
+        let time = Time::new(Some(12), Some(30), Some(0), Some(0));
+        let date_time = DateTime::new(date, time, Some(-60), Some(0));
+        let body = Data::Structure(vec![Data::LongUnsigned(7), Data::OctetString(vec![1, 2])]);
+        let notification = DataNotification::new(0x0100_0042, date_time, body);
+        assert_eq!(notification.invoke_id(), 0x42);
+        assert_eq!(notification.service_class(), ServiceClass::Unconfirmed);
+
+        let encoded = notification.encode();
+        assert_eq!(encoded[..6], [0x0F, 0x00, 0x00, 0x00, 0x42, 0x0C]);
+        assert_eq!(
+            crate::Apdu::parse(&encoded),
+            Ok((&[][..], crate::Apdu::DataNotification(notification)))
+        );
+    }
 }
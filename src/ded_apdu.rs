@@ -244,7 +244,9 @@ impl GeneralDedCiphering {
 
         // Calculate payload length
         let ic_len = if has_invocation_counter { 4 } else { 0 };
-        let payload_len = remaining_len - 1 - ic_len;
+        let payload_len = remaining_len.checked_sub(1 + ic_len).ok_or(nom::Err::Error(
+            nom::error::Error::new(input, nom::error::ErrorKind::LengthValue),
+        ))?;
 
         // Encrypted payload
         let (input, payload) = count(u8, payload_len).parse(input)?;
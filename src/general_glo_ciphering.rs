@@ -307,14 +307,16 @@ impl GeneralGloCiphering {
         let mut system_title = [0u8; 8];
         let (input, _) = fill(u8, &mut system_title).parse(input)?;
 
-        let (input, mut payload_len) = match u8(input)? {
+        let (input, payload_len) = match u8(input)? {
             (input, 0x82) => {
                 let (input, len) = be_u16(input)?;
                 (input, len as usize)
             }
             (input, len) => (input, len as usize),
         };
-        payload_len -= 5;
+        let payload_len = payload_len.checked_sub(5).ok_or(nom::Err::Error(
+            nom::error::Error::new(input, nom::error::ErrorKind::LengthValue),
+        ))?;
 
         // Green Book 9.2.7.2.4.1
         let (input, security_control) = SecurityControl::parse(input)?;
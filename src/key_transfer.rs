@@ -0,0 +1,270 @@
+//! Key transfer of the Security Setup (class 64)
+//!
+//! The `key_transfer` method carries new keys wrapped under the master key
+//! (KEK) with the AES key wrap algorithm of RFC 3394, so that they never
+//! appear in plaintext on the wire, not even in a ciphered APDU.
+//!
+//! These types are shared by the client, which wraps the keys it rotates, and
+//! the [`SecuritySetup`](crate::cosem::security_setup) object of the server,
+//! which unwraps them.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use dlms_cosem::key_transfer::{KeyId, KeyWrapError, WrappedKey, unwrap_key};
+//!
+//! let kek = [0x0F; 16];
+//! let new_key = [0xA5; 16];
+//! let wrapped = WrappedKey::new(KeyId::GlobalUnicastEncryptionKey, &kek, &new_key).unwrap();
+//! assert_eq!(wrapped.key_wrapped.len(), 24);
+//! assert_eq!(unwrap_key(&kek, &wrapped.key_wrapped), Ok(new_key.to_vec()));
+//! assert_eq!(unwrap_key(&[0; 16], &wrapped.key_wrapped), Err(KeyWrapError::IntegrityCheckFailed));
+//! ```
+
+use core::fmt;
+
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::{Aes128, Aes256};
+
+use crate::Data;
+use crate::get::DataAccessResult;
+
+#[cfg(all(not(feature = "std"), feature = "encode"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// Initial value of RFC 3394, checked when unwrapping.
+const DEFAULT_IV: [u8; 8] = [0xA6; 8];
+
+/// Key identifier of `key_transfer` and `key_agreement`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum KeyId {
+    /// Global unicast encryption key (GUEK)
+    GlobalUnicastEncryptionKey = 0,
+    /// Global broadcast encryption key (GBEK)
+    GlobalBroadcastEncryptionKey = 1,
+    /// Authentication key (GAK)
+    AuthenticationKey = 2,
+    /// Master key (KEK), which wraps the other keys
+    MasterKey = 3,
+}
+
+impl KeyId {
+    /// Converts a key_id enum value.
+    ///
+    /// Returns Err if value is not in range 0-3.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::GlobalUnicastEncryptionKey),
+            1 => Ok(Self::GlobalBroadcastEncryptionKey),
+            2 => Ok(Self::AuthenticationKey),
+            3 => Ok(Self::MasterKey),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
+
+/// Errors of the AES key wrap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyWrapError {
+    /// The KEK is neither 16 nor 32 bytes long
+    InvalidKekLength,
+    /// The key is shorter than 16 bytes or not a multiple of 8 bytes
+    InvalidKeyLength,
+    /// The wrapped key was not wrapped under this KEK, or was altered
+    IntegrityCheckFailed,
+}
+
+impl fmt::Display for KeyWrapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyWrapError::InvalidKekLength => write!(f, "KEK must be 16 or 32 bytes"),
+            KeyWrapError::InvalidKeyLength => {
+                write!(f, "Key must be a multiple of 8 bytes, at least 16")
+            }
+            KeyWrapError::IntegrityCheckFailed => write!(f, "Key wrap integrity check failed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for KeyWrapError {}
+
+/// Wraps `key` under `kek` (AES-128 or AES-256 key wrap, RFC 3394).
+///
+/// The wrapped key is 8 bytes longer than the key.
+pub fn wrap_key(kek: &[u8], key: &[u8]) -> Result<Vec<u8>, KeyWrapError> {
+    if key.len() < 16 || !key.len().is_multiple_of(8) {
+        return Err(KeyWrapError::InvalidKeyLength);
+    }
+    match kek.len() {
+        16 => Ok(wrap_with(&Aes128::new(GenericArray::from_slice(kek)), key)),
+        32 => Ok(wrap_with(&Aes256::new(GenericArray::from_slice(kek)), key)),
+        _ => Err(KeyWrapError::InvalidKekLength),
+    }
+}
+
+/// Unwraps a key wrapped under `kek` and checks its integrity.
+pub fn unwrap_key(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, KeyWrapError> {
+    if wrapped.len() < 24 || !wrapped.len().is_multiple_of(8) {
+        return Err(KeyWrapError::InvalidKeyLength);
+    }
+    match kek.len() {
+        16 => unwrap_with(&Aes128::new(GenericArray::from_slice(kek)), wrapped),
+        32 => unwrap_with(&Aes256::new(GenericArray::from_slice(kek)), wrapped),
+        _ => Err(KeyWrapError::InvalidKekLength),
+    }
+}
+
+fn wrap_with(cipher: &impl BlockEncrypt, key: &[u8]) -> Vec<u8> {
+    let n = key.len() / 8;
+    let mut a = DEFAULT_IV;
+    let mut r = key.to_vec();
+    for j in 0..6 {
+        for i in 0..n {
+            let mut block = GenericArray::default();
+            block[..8].copy_from_slice(&a);
+            block[8..].copy_from_slice(&r[i * 8..i * 8 + 8]);
+            cipher.encrypt_block(&mut block);
+            let t = ((n * j + i + 1) as u64).to_be_bytes();
+            a.iter_mut().zip(&block[..8]).zip(t).for_each(|((a, b), t)| *a = b ^ t);
+            r[i * 8..i * 8 + 8].copy_from_slice(&block[8..]);
+        }
+    }
+    let mut wrapped = a.to_vec();
+    wrapped.extend(r);
+    wrapped
+}
+
+fn unwrap_with(cipher: &impl BlockDecrypt, wrapped: &[u8]) -> Result<Vec<u8>, KeyWrapError> {
+    let n = wrapped.len() / 8 - 1;
+    let mut a: [u8; 8] = wrapped[..8].try_into().unwrap_or_default();
+    let mut r = wrapped[8..].to_vec();
+    for j in (0..6).rev() {
+        for i in (0..n).rev() {
+            let t = ((n * j + i + 1) as u64).to_be_bytes();
+            let mut block = GenericArray::default();
+            block[..8].iter_mut().zip(a).zip(t).for_each(|((b, a), t)| *b = a ^ t);
+            block[8..].copy_from_slice(&r[i * 8..i * 8 + 8]);
+            cipher.decrypt_block(&mut block);
+            a.copy_from_slice(&block[..8]);
+            r[i * 8..i * 8 + 8].copy_from_slice(&block[8..]);
+        }
+    }
+    if a == DEFAULT_IV { Ok(r) } else { Err(KeyWrapError::IntegrityCheckFailed) }
+}
+
+/// One element of the `key_transfer` parameter: `{key_id, key_wrapped}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedKey {
+    /// Which key is transferred
+    pub key_id: KeyId,
+    /// The key wrapped under the master key
+    pub key_wrapped: Vec<u8>,
+}
+
+impl WrappedKey {
+    /// Wraps `key` under `kek` for the key `key_id`.
+    pub fn new(key_id: KeyId, kek: &[u8], key: &[u8]) -> Result<Self, KeyWrapError> {
+        Ok(Self { key_id, key_wrapped: wrap_key(kek, key)? })
+    }
+
+    /// Encodes the `key_transfer` parameter for these keys.
+    #[cfg(feature = "encode")]
+    pub fn key_transfer_data(keys: &[WrappedKey]) -> Data {
+        Data::Structure(
+            keys.iter()
+                .map(|key| {
+                    Data::Structure(vec![
+                        Data::Enum(key.key_id as u8),
+                        Data::OctetString(key.key_wrapped.clone()),
+                    ])
+                })
+                .collect(),
+        )
+    }
+
+    /// Parses the `key_transfer` parameter.
+    pub fn parse_key_transfer_data(data: &Data) -> Result<Vec<Self>, DataAccessResult> {
+        let Data::Structure(keys) = data else {
+            return Err(DataAccessResult::TypeUnmatched);
+        };
+        keys.iter()
+            .map(|key| match key {
+                Data::Structure(fields) => match fields.as_slice() {
+                    [Data::Enum(key_id), Data::OctetString(key_wrapped)] => Ok(Self {
+                        key_id: KeyId::from_u8(*key_id)?,
+                        key_wrapped: key_wrapped.clone(),
+                    }),
+                    _ => Err(DataAccessResult::TypeUnmatched),
+                },
+                _ => Err(DataAccessResult::TypeUnmatched),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_rfc3394_vectors() {
+        // RFC 3394 4.1: 128-bit key data with a 128-bit KEK
+        let kek = hex("000102030405060708090A0B0C0D0E0F");
+        let key = hex("00112233445566778899AABBCCDDEEFF");
+        let wrapped = hex("1FA68B0A8112B447AEF34BD8FB5A7B829D3E862371D2CFE5");
+        assert_eq!(wrap_key(&kek, &key), Ok(wrapped.clone()));
+        assert_eq!(unwrap_key(&kek, &wrapped), Ok(key));
+
+        // RFC 3394 4.6: 256-bit key data with a 256-bit KEK
+        let kek = hex("000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F");
+        let key = hex("00112233445566778899AABBCCDDEEFF000102030405060708090A0B0C0D0E0F");
+        let wrapped =
+            hex("28C9F404C4B810F4CBCCB35CFB87F8263F5786E2D80ED326CBC7F0E71A99F43BFB988B9B7A02DD21");
+        assert_eq!(wrap_key(&kek, &key), Ok(wrapped.clone()));
+        assert_eq!(unwrap_key(&kek, &wrapped), Ok(key));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let kek = [0x11; 16];
+        assert_eq!(wrap_key(&kek[..15], &[0; 16]), Err(KeyWrapError::InvalidKekLength));
+        assert_eq!(wrap_key(&kek, &[0; 12]), Err(KeyWrapError::InvalidKeyLength));
+        assert_eq!(unwrap_key(&kek, &[0; 16]), Err(KeyWrapError::InvalidKeyLength));
+
+        let mut wrapped = wrap_key(&kek, &[0x22; 16]).unwrap();
+        wrapped[20] ^= 1;
+        assert_eq!(unwrap_key(&kek, &wrapped), Err(KeyWrapError::IntegrityCheckFailed));
+    }
+
+    #[test]
+    #[cfg(feature = "encode")]
+    fn test_key_transfer_data() {
+        let keys = [
+            WrappedKey::new(KeyId::GlobalUnicastEncryptionKey, &[1; 16], &[2; 16]).unwrap(),
+            WrappedKey::new(KeyId::AuthenticationKey, &[1; 16], &[3; 16]).unwrap(),
+        ];
+        let data = WrappedKey::key_transfer_data(&keys);
+        assert_eq!(WrappedKey::parse_key_transfer_data(&data), Ok(keys.to_vec()));
+
+        let unknown_key = Data::Structure(vec![Data::Structure(vec![
+            Data::Enum(4),
+            Data::OctetString(vec![0; 24]),
+        ])]);
+        assert_eq!(
+            WrappedKey::parse_key_transfer_data(&unknown_key),
+            Err(DataAccessResult::TypeUnmatched)
+        );
+    }
+}
@@ -37,7 +37,7 @@ mod control_information;
 mod data;
 pub use data::*;
 mod data_notification;
-use data_notification::*;
+pub use data_notification::*;
 mod general_glo_ciphering;
 pub use general_glo_ciphering::GeneralGloCiphering;
 #[cfg(feature = "encode")]
@@ -63,6 +63,7 @@ pub use unit::{ScalerUnit, Unit};
 pub mod action;
 #[cfg(feature = "association")]
 pub mod association;
+pub mod calendar;
 #[cfg(any(feature = "client", feature = "async-client"))]
 pub mod client;
 #[cfg(feature = "async-client")]
@@ -72,10 +73,14 @@ pub mod cosem;
 pub mod get;
 #[cfg(feature = "hdlcparse")]
 pub mod hdlc;
+pub mod key_transfer;
 #[cfg(feature = "mbusparse")]
 pub mod mbus;
 pub mod selective_access;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod set;
+pub mod token;
 #[cfg(any(feature = "client", feature = "async-client"))]
 pub mod transport;
 
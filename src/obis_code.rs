@@ -1,3 +1,4 @@
+use core::array::TryFromSliceError;
 use core::fmt::{self, Debug, Display};
 
 #[cfg(feature = "parse")]
@@ -75,6 +76,31 @@ impl ObisCode {
     }
 }
 
+impl From<[u8; 6]> for ObisCode {
+    fn from([a, b, c, d, e, f]: [u8; 6]) -> Self {
+        Self::new(a, b, c, d, e, f)
+    }
+}
+
+/// Convert the 6 raw bytes of a logical name, e.g. the contents of an
+/// octet-string attribute, into an OBIS code.
+///
+/// # Example
+/// ```
+/// use dlms_cosem::ObisCode;
+///
+/// let code = ObisCode::try_from(&[1, 0, 1, 8, 0, 255][..]).unwrap();
+/// assert_eq!(code, ObisCode::new(1, 0, 1, 8, 0, 255));
+/// assert!(ObisCode::try_from(&[1, 0, 1, 8, 0][..]).is_err());
+/// ```
+impl TryFrom<&[u8]> for ObisCode {
+    type Error = TryFromSliceError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        <[u8; 6]>::try_from(bytes).map(Self::from)
+    }
+}
+
 impl Display for ObisCode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}-{}:{}.{}.{}*{}", self.a, self.b, self.c, self.d, self.e, self.f)
@@ -410,6 +436,16 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_try_from_slice() {
+        let code = ObisCode::try_from(&[1, 0, 99, 98, 0, 255][..]).unwrap();
+        assert_eq!(code, ObisCode::new(1, 0, 99, 98, 0, 255));
+
+        assert!(ObisCode::try_from(&[][..]).is_err());
+        assert!(ObisCode::try_from(&[1, 0, 99, 98, 0][..]).is_err());
+        assert!(ObisCode::try_from(&[1, 0, 99, 98, 0, 255, 0][..]).is_err());
+    }
+
     #[test]
     fn test_new() {
         let code = ObisCode::new(1, 0, 1, 8, 0, 255);
@@ -179,6 +179,99 @@ pub struct CaptureObjectDefinition {
     pub data_index: u16,
 }
 
+impl CaptureObjectDefinition {
+    /// Decode a capture object definition from its Structure(4) encoding
+    ///
+    /// This is the inverse of the encoding used for capture objects,
+    /// `restricting_object` and `selected_values`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use dlms_cosem::selective_access::CaptureObjectDefinition;
+    /// use dlms_cosem::{Data, ObisCode};
+    ///
+    /// let data = Data::Structure(vec![
+    ///     Data::LongUnsigned(8),
+    ///     Data::OctetString(vec![0, 0, 1, 0, 0, 255]),
+    ///     Data::Integer(2),
+    ///     Data::LongUnsigned(0),
+    /// ]);
+    ///
+    /// let clock = CaptureObjectDefinition::from_data(&data).unwrap();
+    /// assert_eq!(clock.logical_name, ObisCode::new(0, 0, 1, 0, 0, 255));
+    /// ```
+    pub fn from_data(data: &Data) -> Result<Self, &'static str> {
+        match data {
+            Data::Structure(fields) => match fields.as_slice() {
+                [
+                    Data::LongUnsigned(class_id),
+                    Data::OctetString(logical_name),
+                    Data::Integer(attribute_index),
+                    Data::LongUnsigned(data_index),
+                ] => Ok(Self {
+                    class_id: *class_id,
+                    logical_name: ObisCode::try_from(logical_name.as_slice())
+                        .map_err(|_| "invalid capture object definition fields")?,
+                    attribute_index: *attribute_index,
+                    data_index: *data_index,
+                }),
+                _ => Err("invalid capture object definition fields"),
+            },
+            _ => Err("capture object definition must be a Structure"),
+        }
+    }
+
+    /// Returns `true` if both definitions refer to the same attribute
+    ///
+    /// `data_index` is ignored: clients usually send 0 for whole attributes.
+    pub fn refers_to_same_attribute(&self, other: &Self) -> bool {
+        self.class_id == other.class_id
+            && self.logical_name == other.logical_name
+            && self.attribute_index == other.attribute_index
+    }
+}
+
+/// Decoded access selector for selective access
+///
+/// Servers decode the `AccessSelector` of a GET request with
+/// [`SelectiveAccessDescriptor::from_access_selector`] and apply it to the
+/// buffer (see `ProfileGeneric::select`).
+///
+/// ## Example
+///
+/// ```rust
+/// use dlms_cosem::selective_access::{EntryDescriptor, SelectiveAccessDescriptor};
+///
+/// let descriptor = EntryDescriptor::new(1, 10, 1, 0);
+/// # #[cfg(feature = "encode")]
+/// # {
+/// let decoded = SelectiveAccessDescriptor::from_access_selector(2, &descriptor.encode()).unwrap();
+/// assert_eq!(decoded, SelectiveAccessDescriptor::Entry(descriptor));
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SelectiveAccessDescriptor {
+    /// Selector 1: filter by value range
+    Range(RangeDescriptor),
+    /// Selector 2: filter by entry and column indices
+    Entry(EntryDescriptor),
+}
+
+impl SelectiveAccessDescriptor {
+    /// Decode the parameters of an access selector
+    ///
+    /// Returns an error for selectors other than 1 and 2 and for malformed parameters.
+    pub fn from_access_selector(selector: u8, parameters: &Data) -> Result<Self, &'static str> {
+        match selector {
+            1 => RangeDescriptor::from_data(parameters).map(Self::Range),
+            2 => EntryDescriptor::from_data(parameters).map(Self::Entry),
+            _ => Err("unsupported access selector"),
+        }
+    }
+}
+
 /// Range descriptor for selective access (Selector 1)
 ///
 /// Filters ProfileGeneric buffer entries by comparing values in a specified
@@ -355,6 +448,51 @@ impl RangeDescriptor {
         ])
     }
 
+    /// Decode a RangeDescriptor from the `parameters` of access selector 1
+    ///
+    /// This is the inverse of [`encode`](Self::encode).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use dlms_cosem::selective_access::{RangeDescriptor, CaptureObjectDefinition};
+    /// use dlms_cosem::{ObisCode, Data};
+    ///
+    /// let clock = CaptureObjectDefinition {
+    ///     class_id: 8,
+    ///     logical_name: ObisCode::new(0, 0, 1, 0, 0, 255),
+    ///     attribute_index: 2,
+    ///     data_index: 0,
+    /// };
+    ///
+    /// let range = RangeDescriptor::new(clock, Data::Unsigned(0), Data::Unsigned(100), vec![]);
+    /// # #[cfg(feature = "encode")]
+    /// assert_eq!(RangeDescriptor::from_data(&range.encode()), Ok(range));
+    /// ```
+    pub fn from_data(data: &Data) -> Result<Self, &'static str> {
+        let Data::Structure(fields) = data else {
+            return Err("range descriptor must be a Structure");
+        };
+        let [restricting_object, from_value, to_value, selected_values] = fields.as_slice() else {
+            return Err("range descriptor must have 4 elements");
+        };
+        let selected_values = match selected_values {
+            Data::Structure(columns) => columns
+                .iter()
+                .map(CaptureObjectDefinition::from_data)
+                .collect::<Result<Vec<_>, _>>()?,
+            Data::Null => Vec::new(),
+            _ => return Err("selected_values must be an array"),
+        };
+
+        Ok(Self {
+            restricting_object: CaptureObjectDefinition::from_data(restricting_object)?,
+            from_value: from_value.clone(),
+            to_value: to_value.clone(),
+            selected_values,
+        })
+    }
+
     /// Validate that the RangeDescriptor is semantically correct
     ///
     /// Checks:
@@ -525,6 +663,26 @@ impl EntryDescriptor {
         ])
     }
 
+    /// Decode an EntryDescriptor from the `parameters` of access selector 2
+    ///
+    /// This is the inverse of [`encode`](Self::encode).
+    pub fn from_data(data: &Data) -> Result<Self, &'static str> {
+        match data {
+            Data::Structure(fields) => match fields.as_slice() {
+                [
+                    Data::DoubleLongUnsigned(from_entry),
+                    Data::DoubleLongUnsigned(to_entry),
+                    Data::LongUnsigned(from_selected_value),
+                    Data::LongUnsigned(to_selected_value),
+                ] => {
+                    Ok(Self::new(*from_entry, *to_entry, *from_selected_value, *to_selected_value))
+                }
+                _ => Err("invalid entry descriptor fields"),
+            },
+            _ => Err("entry descriptor must be a Structure"),
+        }
+    }
+
     /// Create EntryDescriptor for last N entries (all columns)
     ///
     /// # Arguments
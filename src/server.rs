@@ -0,0 +1,1348 @@
+//! DLMS server (meter side).
+//!
+//! This module answers client requests from an [`ObjectRegistry`] of COSEM objects:
+//! - [`DlmsServer`] - Settings and object model of the server
+//! - [`ServerSession`] - State of one client association
+//!
+//! Like `DlmsSession` on the client side, the server
+//! is decoupled from the transport layer: it takes a received APDU and returns the
+//! APDU to send back. This makes it usable for meter simulators, test fixtures and
+//! embedded firmware alike.
+//!
+//! # Supported services
+//!
+//! - AARQ → AARE: application context, authentication (none, LLS password or
+//!   HLS challenge), conformance and PDU-size negotiation
+//! - HLS pass 3/4: `reply_to_HLS_authentication` on the current association
+//!   (0.0.40.0.0.255) completes a pending HLS association
+//! - GET-Request Normal / With-List → GET-Response
+//! - SET-Request Normal / With-List → SET-Response
+//! - ACTION-Request Normal / With-List → ACTION-Response
+//! - RLRQ → RLRE
+//! - Block transfer: responses longer than the client's maximum receive PDU
+//!   size are sent with GET-Response-With-Datablock / ACTION-Response-With-Pblock;
+//!   SET data blocks and ACTION parameter blocks are reassembled. Abandoned
+//!   transfers expire after [`ServerSettings::block_transfer_timeout`].
+//! - Ciphering: with [`ServerSettings::security`], GLO/DED-ciphered GET, SET
+//!   and ACTION requests are deciphered, checked against replayed invocation
+//!   counters and answered ciphered (see [`ServerSecurity`])
+//! - Client associations: with [`ServerSettings::associations`], each client
+//!   SAP has its own credentials and Association LN object, whose object list
+//!   decides what the client may access; associations may be pre-established
+//!   (see [`ClientAssociation`])
+//! - Logical devices: a [`PhysicalDevice`] routes sessions to one server per
+//!   logical device by SAP and keeps the SAP Assignment object of the
+//!   management logical device up to date
+//!
+//! Each service must be part of the negotiated conformance. `With-List`
+//! requests are answered even without the multiple-references bit, since many
+//! clients (including this crate's `DlmsClient`) do not propose it.
+//!
+//! Objects are addressed by `(class_id, logical_name)`. Errors returned by
+//! [`CosemObject`](crate::cosem::CosemObject) are sent back as the service's
+//! `DataAccessResult` / `ActionResult`.
+//!
+//! # Example
+//!
+//! ```
+//! use dlms_cosem::association::{AarqApdu, AareApdu};
+//! use dlms_cosem::cosem::data::DataObject;
+//! use dlms_cosem::get::{GetDataResult, GetRequest, GetRequestNormal, GetResponse};
+//! use dlms_cosem::server::{DlmsServer, ServerSession, ServerSettings};
+//! use dlms_cosem::{Data, ObisCode};
+//!
+//! let name = ObisCode::new(0, 0, 96, 1, 0, 255);
+//! let mut server = DlmsServer::new(ServerSettings::default())
+//!     .with_object(DataObject::new(name, Data::Unsigned(42)));
+//! let mut session = ServerSession::new();
+//!
+//! let aare = server.handle(&mut session, &AarqApdu::new_simple_ln(1024).encode()).unwrap();
+//! assert!(AareApdu::parse(&aare).unwrap().1.is_accepted());
+//!
+//! let request = GetRequest::Normal(GetRequestNormal {
+//!     invoke_id: 0xC1,
+//!     class_id: 1,
+//!     instance_id: name,
+//!     attribute_id: 2,
+//!     access_selection: None,
+//! });
+//! let response = server.handle(&mut session, &request.encode()).unwrap();
+//! match GetResponse::parse(&response).unwrap().1 {
+//!     GetResponse::Normal(response) => {
+//!         assert_eq!(response.result, GetDataResult::Data(Data::Unsigned(42)))
+//!     }
+//!     _ => unreachable!(),
+//! }
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt;
+use std::time::Duration;
+
+use crate::SecurityControl;
+use crate::action::{
+    self, ActionRequest, ActionResponse, ActionResponseNormal, ActionResponseWithList, ActionResult,
+};
+use crate::association::{
+    AARQ_TAG, AareApdu, AarqApdu, AcseServiceUserDiagnostics, ApplicationContextName,
+    AssociationResult, AuthenticationValue, Conformance, DLMS_VERSION, InitiateResponse,
+    MechanismName, RLRQ_TAG, ReleaseRequestApdu, ReleaseResponseApdu, ReleaseResponseReason,
+};
+use crate::cosem::association_ln::{HlsFunction, default_hls_function};
+use crate::cosem::{CosemObject, ObjectRegistry};
+use crate::data::Data;
+use crate::get::{
+    AccessSelector, DataAccessResult, GetDataResult, GetRequest, GetResponse, GetResponseNormal,
+    GetResponseWithList,
+};
+use crate::obis_code::ObisCode;
+use crate::set::{SetRequest, SetResponse, SetResponseNormal, SetResponseWithList};
+
+mod association;
+mod block;
+#[cfg(feature = "persistence")]
+mod persistence;
+mod physical_device;
+mod security;
+#[cfg(feature = "sim")]
+pub mod sim;
+
+pub use association::{ClientAssociation, MANAGEMENT_CLIENT_SAP, PUBLIC_CLIENT_SAP};
+use block::BlockTransfer;
+pub use physical_device::{MANAGEMENT_LOGICAL_DEVICE_SAP, PhysicalDevice};
+use security::{InvocationCounters, SessionCiphering};
+pub use security::{SecurityPolicy, ServerSecurity};
+
+/// APDU tag of GET-Request
+pub const GET_REQUEST_TAG: u8 = 0xC0;
+
+/// APDU tag of SET-Request
+pub const SET_REQUEST_TAG: u8 = 0xC1;
+
+/// APDU tag of ACTION-Request
+pub const ACTION_REQUEST_TAG: u8 = 0xC3;
+
+/// APDU tag of ExceptionResponse
+pub const EXCEPTION_RESPONSE_TAG: u8 = 0xD8;
+
+/// Logical name of the current association, target of `reply_to_HLS_authentication`.
+const CURRENT_ASSOCIATION: ObisCode = ObisCode { a: 0, b: 0, c: 40, d: 0, e: 0, f: 255 };
+
+/// Association LN interface class id
+const ASSOCIATION_LN_CLASS_ID: u16 = 15;
+
+/// Association LN method 1: reply_to_HLS_authentication
+const REPLY_TO_HLS_AUTHENTICATION: i8 = 1;
+
+/// Length of the server challenge (StoC) sent in the AARE.
+const HLS_CHALLENGE_LENGTH: usize = 16;
+
+/// Default conformance offered by the server.
+///
+/// LN services with attribute lists, selective access and block transfer.
+pub const DEFAULT_SERVER_CONFORMANCE: Conformance = Conformance::GET
+    .union(Conformance::SET)
+    .union(Conformance::ACTION)
+    .union(Conformance::SELECTIVE_ACCESS)
+    .union(Conformance::MULTIPLE_REFERENCES)
+    .union(Conformance::BLOCK_TRANSFER_WITH_GET_OR_READ)
+    .union(Conformance::BLOCK_TRANSFER_WITH_SET_OR_WRITE)
+    .union(Conformance::BLOCK_TRANSFER_WITH_ACTION);
+
+/// Default time after which an abandoned block transfer is aborted.
+pub const DEFAULT_BLOCK_TRANSFER_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Default maximum size of a SET value or ACTION parameters received in blocks.
+pub const DEFAULT_MAX_BLOCK_TRANSFER_SIZE: usize = 1024 * 1024;
+
+/// Errors that prevent the server from answering a request.
+///
+/// Errors concerning a single object (unknown object, denied access, ...) are
+/// not reported here: they are answered with a `DataAccessResult` or
+/// `ActionResult` in the response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerError {
+    /// The request is empty or could not be decoded, e.g. it carries a value
+    /// of a data type the crate does not support (array, visible-string, ...).
+    ParseError,
+    /// The APDU tag (or service variant) is not supported.
+    UnsupportedApdu(u8),
+    /// A data service was requested before an association was established.
+    NotAssociated,
+    /// The service is not part of the negotiated conformance.
+    ServiceNotAllowed,
+    /// The request exceeds the server's maximum receive PDU size, or the
+    /// response exceeds the client's and cannot be sent in blocks.
+    PduTooLong,
+    /// A ciphered request could not be deciphered: unknown system title or
+    /// key, unsupported security suite, or a plaintext of another service.
+    DecipheringError,
+    /// The invocation counter of a ciphered request is not greater than the
+    /// last one accepted from the client, or the server's counter is exhausted.
+    InvocationCounterError,
+    /// No logical device is addressed by the server SAP of the session.
+    UnknownLogicalDevice(u16),
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerError::ParseError => write!(f, "Parse error"),
+            ServerError::UnsupportedApdu(tag) => write!(f, "Unsupported APDU: 0x{:02X}", tag),
+            ServerError::NotAssociated => write!(f, "Not associated"),
+            ServerError::ServiceNotAllowed => write!(f, "Service not allowed by conformance"),
+            ServerError::PduTooLong => write!(f, "PDU too long"),
+            ServerError::DecipheringError => write!(f, "Deciphering error"),
+            ServerError::InvocationCounterError => write!(f, "Invocation counter error"),
+            ServerError::UnknownLogicalDevice(sap) => write!(f, "Unknown logical device: {}", sap),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ServerError {}
+
+impl ServerError {
+    /// Encodes the ExceptionResponse APDU a server sends instead of a response.
+    ///
+    /// Layout: tag 0xD8, state-error, service-error.
+    pub fn exception_response(&self) -> Vec<u8> {
+        const SERVICE_NOT_ALLOWED: u8 = 1;
+        const SERVICE_UNKNOWN: u8 = 2;
+        const OPERATION_NOT_POSSIBLE: u8 = 1;
+        const SERVICE_NOT_SUPPORTED: u8 = 2;
+        const OTHER_REASON: u8 = 3;
+        const PDU_TOO_LONG: u8 = 4;
+        const DECIPHERING_ERROR: u8 = 5;
+        const INVOCATION_COUNTER_ERROR: u8 = 6;
+
+        let (state_error, service_error) = match self {
+            ServerError::ParseError => (SERVICE_UNKNOWN, OTHER_REASON),
+            ServerError::UnsupportedApdu(_) => (SERVICE_UNKNOWN, SERVICE_NOT_SUPPORTED),
+            ServerError::NotAssociated
+            | ServerError::ServiceNotAllowed
+            | ServerError::UnknownLogicalDevice(_) => (SERVICE_NOT_ALLOWED, OPERATION_NOT_POSSIBLE),
+            ServerError::PduTooLong => (SERVICE_NOT_ALLOWED, PDU_TOO_LONG),
+            ServerError::DecipheringError => (SERVICE_NOT_ALLOWED, DECIPHERING_ERROR),
+            ServerError::InvocationCounterError => (SERVICE_NOT_ALLOWED, INVOCATION_COUNTER_ERROR),
+        };
+        vec![EXCEPTION_RESPONSE_TAG, state_error, service_error]
+    }
+}
+
+/// Settings for the DLMS server.
+#[derive(Debug, Clone)]
+pub struct ServerSettings {
+    /// Services offered by the server; the negotiated conformance is the
+    /// intersection with the client's proposal.
+    /// Default: [`DEFAULT_SERVER_CONFORMANCE`].
+    pub conformance: Conformance,
+    /// Maximum PDU size the server can receive.
+    /// Default: 0xFFFF (65535).
+    pub max_pdu_size: u16,
+    /// LLS password. When set, clients must authenticate with Low Level
+    /// Security and this password.
+    /// Default: None (no authentication).
+    pub password: Option<Vec<u8>>,
+    /// HLS secret. When set, clients may authenticate with High Level
+    /// Security: the AARE carries a server challenge, and the association is
+    /// pending until the client invokes `reply_to_HLS_authentication`.
+    /// Default: None.
+    pub hls_secret: Option<Vec<u8>>,
+    /// Function computing HLS responses; it also decides which HLS
+    /// mechanisms are accepted.
+    /// Default: [`default_hls_function`].
+    pub hls_function: HlsFunction,
+    /// Time without a block after which a block transfer is aborted.
+    /// Default: [`DEFAULT_BLOCK_TRANSFER_TIMEOUT`] (60 s).
+    pub block_transfer_timeout: Duration,
+    /// Maximum size in bytes of a SET value or ACTION parameters received in
+    /// blocks. A transfer growing beyond it is aborted.
+    /// Default: [`DEFAULT_MAX_BLOCK_TRANSFER_SIZE`] (1 MiB).
+    pub max_block_transfer_size: usize,
+    /// Ciphering settings. When set, clients may associate with the ciphered
+    /// LN context and send GLO/DED-ciphered requests.
+    /// Default: None.
+    pub security: Option<ServerSecurity>,
+    /// Associations of the client SAPs. When set, only these clients may
+    /// associate, each with its own credentials and object list; the
+    /// password and HLS secret above are not used.
+    /// Default: empty (any client, no object list enforced).
+    pub associations: Vec<ClientAssociation>,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            conformance: DEFAULT_SERVER_CONFORMANCE,
+            max_pdu_size: 0xFFFF,
+            password: None,
+            hls_secret: None,
+            hls_function: default_hls_function,
+            block_transfer_timeout: DEFAULT_BLOCK_TRANSFER_TIMEOUT,
+            max_block_transfer_size: DEFAULT_MAX_BLOCK_TRANSFER_SIZE,
+            security: None,
+            associations: Vec::new(),
+        }
+    }
+}
+
+/// State of one client association.
+///
+/// A server keeps one session per connected client; all sessions share the
+/// object model of the [`DlmsServer`].
+#[derive(Debug, Clone, Default)]
+pub struct ServerSession {
+    /// Whether the client is currently associated.
+    pub associated: bool,
+    /// Application context of the association.
+    pub application_context_name: Option<ApplicationContextName>,
+    /// Authentication mechanism used by the client.
+    pub mechanism_name: Option<MechanismName>,
+    /// Negotiated conformance block.
+    pub negotiated_conformance: Conformance,
+    /// Maximum PDU size the client can receive.
+    pub client_max_receive_pdu_size: u16,
+    /// Challenges (CtoS, StoC) of a pending HLS authentication.
+    hls_challenges: Option<(Vec<u8>, Vec<u8>)>,
+    /// Block transfer in progress.
+    block_transfer: Option<BlockTransfer>,
+    /// Ciphering context of a ciphered association.
+    ciphering: Option<SessionCiphering>,
+    /// Security control of the ciphered request being answered.
+    request_security: Option<SecurityControl>,
+    /// Client SAP the session was opened for.
+    client_sap: Option<u16>,
+    /// SAP of the logical device the session is bound to.
+    server_sap: Option<u16>,
+    /// Association object of the client's [`ClientAssociation`].
+    association: Option<ObisCode>,
+}
+
+impl ServerSession {
+    /// Creates a session that is not associated yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` while HLS authentication waits for pass 3.
+    ///
+    /// Only `reply_to_HLS_authentication` is answered in this state.
+    pub fn is_authentication_pending(&self) -> bool {
+        self.hls_challenges.is_some()
+    }
+
+    fn require(&self, service: Conformance) -> Result<(), ServerError> {
+        if !self.associated {
+            Err(ServerError::NotAssociated)
+        } else if !self.negotiated_conformance.contains(service) {
+            Err(ServerError::ServiceNotAllowed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The logic core of a DLMS server.
+///
+/// Holds the server settings and the object model, and answers requests for
+/// a [`ServerSession`].
+#[derive(Debug, Default)]
+pub struct DlmsServer {
+    settings: ServerSettings,
+    objects: ObjectRegistry,
+    counters: InvocationCounters,
+}
+
+impl DlmsServer {
+    /// Creates a server without objects.
+    pub fn new(settings: ServerSettings) -> Self {
+        let counters = InvocationCounters::new(&settings);
+        Self { settings, objects: ObjectRegistry::new(), counters }
+    }
+
+    /// Replaces the object model.
+    pub fn with_objects(mut self, objects: ObjectRegistry) -> Self {
+        self.objects = objects;
+        self
+    }
+
+    /// Adds an object to the object model.
+    pub fn with_object(mut self, object: impl CosemObject + Send + 'static) -> Self {
+        self.objects = self.objects.with_object(object);
+        self
+    }
+
+    /// Returns the current settings.
+    pub fn settings(&self) -> &ServerSettings {
+        &self.settings
+    }
+
+    /// Returns the object model.
+    pub fn objects(&self) -> &ObjectRegistry {
+        &self.objects
+    }
+
+    /// Returns the object model, mutably.
+    pub fn objects_mut(&mut self) -> &mut ObjectRegistry {
+        &mut self.objects
+    }
+
+    /// Answers an encoded request APDU.
+    ///
+    /// Returns the encoded response APDU. Requests that cannot be answered
+    /// with a response of the same service return a [`ServerError`].
+    /// GLO/DED-ciphered requests are answered with ciphered responses.
+    pub fn handle(
+        &mut self,
+        session: &mut ServerSession,
+        request: &[u8],
+    ) -> Result<Vec<u8>, ServerError> {
+        let tag = *request.first().ok_or(ServerError::ParseError)?;
+        if security::deciphered_tag(tag).is_some() {
+            if request.len() > usize::from(self.settings.max_pdu_size) {
+                return Err(ServerError::PduTooLong);
+            }
+            return self.handle_ciphered(session, request);
+        }
+        if matches!(tag, GET_REQUEST_TAG | SET_REQUEST_TAG | ACTION_REQUEST_TAG)
+            && session.is_ciphered()
+            && self
+                .settings
+                .security
+                .as_ref()
+                .is_some_and(|security| security.policy == SecurityPolicy::AllRequests)
+        {
+            return Err(ServerError::ServiceNotAllowed);
+        }
+        self.dispatch(session, request)
+    }
+
+    /// Answers a plaintext request APDU.
+    fn dispatch(
+        &mut self,
+        session: &mut ServerSession,
+        request: &[u8],
+    ) -> Result<Vec<u8>, ServerError> {
+        let tag = *request.first().ok_or(ServerError::ParseError)?;
+        if matches!(tag, GET_REQUEST_TAG | SET_REQUEST_TAG | ACTION_REQUEST_TAG) {
+            if request.len() > usize::from(self.settings.max_pdu_size) {
+                return Err(ServerError::PduTooLong);
+            }
+            session.expire_block_transfer(self.settings.block_transfer_timeout);
+        }
+        match tag {
+            AARQ_TAG => {
+                let (_, aarq) = AarqApdu::parse(request).map_err(|_| ServerError::ParseError)?;
+                Ok(self.handle_aarq(session, &aarq).encode())
+            }
+            RLRQ_TAG => {
+                let (_, rlrq) =
+                    ReleaseRequestApdu::parse(request).map_err(|_| ServerError::ParseError)?;
+                Ok(self.handle_release_request(session, &rlrq).encode())
+            }
+            GET_REQUEST_TAG => {
+                let (_, get) = GetRequest::parse(request).map_err(|_| ServerError::ParseError)?;
+                Ok(self.handle_get_request(session, &get)?.encode())
+            }
+            SET_REQUEST_TAG => {
+                let (_, set) = SetRequest::parse(request).map_err(|_| ServerError::ParseError)?;
+                Ok(self.handle_set_request(session, set)?.encode())
+            }
+            ACTION_REQUEST_TAG => {
+                let (_, action) =
+                    ActionRequest::parse(request).map_err(|_| ServerError::ParseError)?;
+                if session.is_authentication_pending() {
+                    return Ok(self.handle_hls_reply(session, action)?.encode());
+                }
+                Ok(self.handle_action_request(session, action)?.encode())
+            }
+            tag => Err(ServerError::UnsupportedApdu(tag)),
+        }
+    }
+
+    /// Processes an AARQ APDU and returns the AARE to send back.
+    ///
+    /// On acceptance, the session becomes associated with the negotiated
+    /// conformance and PDU size.
+    pub fn handle_aarq(&self, session: &mut ServerSession, aarq: &AarqApdu) -> AareApdu {
+        self.reset_session(session);
+        let context = aarq.application_context_name;
+        let reject = |diagnostic| {
+            AareApdu::new_rejected(context, AssociationResult::RejectedPermanent, diagnostic)
+        };
+
+        let security = self.settings.security.as_ref();
+        let context_supported = match context {
+            ApplicationContextName::LogicalNameReferencing => {
+                security.is_none_or(|security| security.policy != SecurityPolicy::AllRequests)
+            }
+            ApplicationContextName::LogicalNameReferencingWithCiphering => security.is_some(),
+            _ => false,
+        };
+        if !context_supported {
+            return reject(AcseServiceUserDiagnostics::ApplicationContextNameNotSupported);
+        }
+
+        let client = self.client_association(session.client_sap);
+        if client.is_none() && !self.settings.associations.is_empty() {
+            return reject(AcseServiceUserDiagnostics::NoReasonGiven);
+        }
+        let (password, hls_secret) = self.credentials(session);
+
+        let mechanism = aarq.mechanism_name.unwrap_or(MechanismName::LowestLevelSecurity);
+        let mut client_challenge = None;
+        match mechanism {
+            MechanismName::LowestLevelSecurity => {
+                if password.is_some() || hls_secret.is_some() {
+                    return reject(AcseServiceUserDiagnostics::AuthenticationRequired);
+                }
+            }
+            MechanismName::LowLevelSecurity => {
+                match (password, &aarq.calling_authentication_value) {
+                    (Some(password), Some(AuthenticationValue::CharString(value)))
+                        if value == password => {}
+                    (Some(_), _) => {
+                        return reject(AcseServiceUserDiagnostics::AuthenticationFailure);
+                    }
+                    (None, _) => {
+                        return reject(
+                            AcseServiceUserDiagnostics::AuthenticationMechanismNameNotRecognised,
+                        );
+                    }
+                }
+            }
+            _ => {
+                let Some(secret) = hls_secret else {
+                    return reject(
+                        AcseServiceUserDiagnostics::AuthenticationMechanismNameNotRecognised,
+                    );
+                };
+                let ctos = match &aarq.calling_authentication_value {
+                    Some(
+                        AuthenticationValue::CharString(ctos)
+                        | AuthenticationValue::BitString(ctos),
+                    ) => ctos,
+                    None => return reject(AcseServiceUserDiagnostics::AuthenticationFailure),
+                };
+                if (self.settings.hls_function)(mechanism, secret, ctos).is_none() {
+                    return reject(
+                        AcseServiceUserDiagnostics::AuthenticationMechanismNameNotRecognised,
+                    );
+                }
+                client_challenge = Some(ctos.clone());
+            }
+        }
+
+        let Some(initiate) = &aarq.user_information else {
+            return reject(AcseServiceUserDiagnostics::NoReasonGiven);
+        };
+        if initiate.proposed_dlms_version_number < DLMS_VERSION {
+            return reject(AcseServiceUserDiagnostics::NoReasonGiven);
+        }
+
+        let ciphering = match context {
+            ApplicationContextName::LogicalNameReferencingWithCiphering => {
+                let client_system_title =
+                    aarq.calling_ap_title.as_deref().and_then(|title| title.try_into().ok());
+                let dedicated_key = match initiate.dedicated_key.as_deref() {
+                    Some(key) => key.try_into().ok().map(Some),
+                    None => Some(None),
+                };
+                match (client_system_title, dedicated_key) {
+                    (Some(client_system_title), Some(dedicated_key)) => {
+                        Some(SessionCiphering { client_system_title, dedicated_key })
+                    }
+                    _ => return reject(AcseServiceUserDiagnostics::NoReasonGiven),
+                }
+            }
+            _ => None,
+        };
+
+        let server_challenge = match client_challenge {
+            Some(_) => {
+                let mut stoc = vec![0u8; HLS_CHALLENGE_LENGTH];
+                if getrandom::getrandom(&mut stoc).is_err() {
+                    return reject(AcseServiceUserDiagnostics::NoReasonGiven);
+                }
+                Some(stoc)
+            }
+            None => None,
+        };
+
+        let conformance = initiate.proposed_conformance & self.settings.conformance;
+        session.associated = server_challenge.is_none();
+        session.application_context_name = Some(context);
+        session.mechanism_name = Some(mechanism);
+        session.negotiated_conformance = conformance;
+        session.client_max_receive_pdu_size = initiate.client_max_receive_pdu_size;
+        session.association = client.map(|client| client.logical_name);
+
+        let mut aare = AareApdu::new_accepted(
+            context,
+            InitiateResponse::new_ln(conformance, self.settings.max_pdu_size),
+        );
+        if let (Some(_), Some(security)) = (&ciphering, security) {
+            aare.responding_ap_title = Some(security.system_title.to_vec());
+        }
+        session.ciphering = ciphering;
+        if mechanism != MechanismName::LowestLevelSecurity {
+            aare.mechanism_name = Some(mechanism);
+        }
+        if let (Some(ctos), Some(stoc)) = (client_challenge, server_challenge) {
+            aare.result_source_diagnostic = AcseServiceUserDiagnostics::AuthenticationRequired;
+            aare.responding_authentication_value =
+                Some(AuthenticationValue::CharString(stoc.clone()));
+            session.hls_challenges = Some((ctos, stoc));
+        }
+        aare
+    }
+
+    /// Processes pass 3 of HLS authentication and returns pass 4.
+    ///
+    /// The request must invoke `reply_to_HLS_authentication` on the current
+    /// association with `f(StoC)`; the response carries `f(CtoS)`. A wrong
+    /// response ends the association attempt.
+    pub fn handle_hls_reply(
+        &self,
+        session: &mut ServerSession,
+        request: ActionRequest,
+    ) -> Result<ActionResponse, ServerError> {
+        let ActionRequest::Normal(request) = request else {
+            return Err(ServerError::NotAssociated);
+        };
+        if request.class_id != ASSOCIATION_LN_CLASS_ID
+            || !self.is_current_association(session, &request.instance_id)
+            || request.method_id != REPLY_TO_HLS_AUTHENTICATION
+        {
+            return Err(ServerError::NotAssociated);
+        }
+        let (Some((ctos, stoc)), (_, Some(secret)), Some(mechanism)) =
+            (session.hls_challenges.take(), self.credentials(session), session.mechanism_name)
+        else {
+            return Err(ServerError::NotAssociated);
+        };
+
+        let hls = self.settings.hls_function;
+        let result = match request.method_invocation_parameters {
+            Some(Data::OctetString(response))
+                if hls(mechanism, secret, &stoc).as_ref() == Some(&response) =>
+            {
+                match hls(mechanism, secret, &ctos) {
+                    Some(server_response) => {
+                        session.associated = true;
+                        ActionResult::Success(Some(action::GetDataResult::Data(Data::OctetString(
+                            server_response,
+                        ))))
+                    }
+                    None => ActionResult::OtherReason,
+                }
+            }
+            _ => ActionResult::ReadWriteDenied,
+        };
+        if !session.associated {
+            self.reset_session(session);
+        }
+
+        Ok(ActionResponse::Normal(ActionResponseNormal { invoke_id: request.invoke_id, result }))
+    }
+
+    /// Processes a Release Request APDU and returns the Release Response.
+    pub fn handle_release_request(
+        &self,
+        session: &mut ServerSession,
+        _rlrq: &ReleaseRequestApdu,
+    ) -> ReleaseResponseApdu {
+        self.reset_session(session);
+        ReleaseResponseApdu::with_reason(ReleaseResponseReason::Normal)
+    }
+
+    /// Processes a GET-Request and returns the GET-Response.
+    ///
+    /// A response exceeding the client's PDU size is sent in blocks.
+    pub fn handle_get_request(
+        &self,
+        session: &mut ServerSession,
+        request: &GetRequest,
+    ) -> Result<GetResponse, ServerError> {
+        session.require(Conformance::GET)?;
+        let response = match request {
+            GetRequest::Normal(request) => {
+                if request.access_selection.is_some() {
+                    session.require(Conformance::SELECTIVE_ACCESS)?;
+                }
+                GetResponse::Normal(GetResponseNormal {
+                    invoke_id: request.invoke_id,
+                    result: self.get(
+                        session,
+                        request.class_id,
+                        &request.instance_id,
+                        request.attribute_id,
+                        request.access_selection.as_ref(),
+                    ),
+                })
+            }
+            GetRequest::WithList(request) => {
+                let results = request
+                    .attribute_descriptor_list
+                    .iter()
+                    .map(|item| {
+                        self.get(session, item.class_id, &item.instance_id, item.attribute_id, None)
+                    })
+                    .collect();
+                GetResponse::WithList(GetResponseWithList { invoke_id: request.invoke_id, results })
+            }
+            GetRequest::NextDataBlock(request) => return self.next_get_block(session, request),
+        };
+        session.block_transfer = None;
+        self.fit_get_response(session, response)
+    }
+
+    /// Processes a SET-Request and returns the SET-Response.
+    ///
+    /// Values sent in data blocks are written once the last block arrived.
+    pub fn handle_set_request(
+        &mut self,
+        session: &mut ServerSession,
+        request: SetRequest,
+    ) -> Result<SetResponse, ServerError> {
+        session.require(Conformance::SET)?;
+        if !matches!(request, SetRequest::WithDataBlock(_)) {
+            session.block_transfer = None;
+        }
+        match request {
+            SetRequest::Normal(request) => Ok(SetResponse::Normal(SetResponseNormal {
+                invoke_id: request.invoke_id,
+                result: self.set(
+                    session,
+                    request.class_id,
+                    &request.instance_id,
+                    request.attribute_id,
+                    request.access_selection.as_ref(),
+                    request.value,
+                ),
+            })),
+            SetRequest::WithList(request) => {
+                let mut values = request.value_list.into_iter();
+                let results = request
+                    .attribute_descriptor_list
+                    .iter()
+                    .map(|item| match values.next() {
+                        Some(value) => self.set(
+                            session,
+                            item.class_id,
+                            &item.instance_id,
+                            item.attribute_id,
+                            None,
+                            value,
+                        ),
+                        None => DataAccessResult::OtherReason,
+                    })
+                    .collect();
+                Ok(SetResponse::WithList(SetResponseWithList {
+                    invoke_id: request.invoke_id,
+                    results,
+                }))
+            }
+            SetRequest::FirstDataBlock(request) => self.first_set_block(session, request),
+            SetRequest::WithDataBlock(request) => self.next_set_block(session, request),
+        }
+    }
+
+    /// Processes an ACTION-Request and returns the ACTION-Response.
+    ///
+    /// Parameters sent in blocks are passed to the method once the last block
+    /// arrived; return parameters exceeding the client's PDU size are sent in
+    /// blocks.
+    pub fn handle_action_request(
+        &mut self,
+        session: &mut ServerSession,
+        request: ActionRequest,
+    ) -> Result<ActionResponse, ServerError> {
+        session.require(Conformance::ACTION)?;
+        if !matches!(request, ActionRequest::WithPBlock(_) | ActionRequest::NextPBlock(_)) {
+            session.block_transfer = None;
+        }
+        match request {
+            ActionRequest::Normal(request) => {
+                let result = self.action(
+                    session,
+                    request.class_id,
+                    &request.instance_id,
+                    request.method_id,
+                    request.method_invocation_parameters,
+                );
+                self.fit_action_response(
+                    session,
+                    ActionResponse::Normal(ActionResponseNormal {
+                        invoke_id: request.invoke_id,
+                        result,
+                    }),
+                )
+            }
+            ActionRequest::WithList(request) => {
+                let results = request
+                    .method_descriptors
+                    .into_iter()
+                    .map(|item| {
+                        self.action(
+                            session,
+                            item.class_id,
+                            &item.instance_id,
+                            item.method_id,
+                            item.method_invocation_parameters,
+                        )
+                    })
+                    .collect();
+                Ok(ActionResponse::WithList(ActionResponseWithList {
+                    invoke_id: request.invoke_id,
+                    results,
+                }))
+            }
+            ActionRequest::WithFirstPBlock(request) => self.first_action_block(session, request),
+            ActionRequest::WithPBlock(request) => self.next_action_block(session, request),
+            ActionRequest::NextPBlock(request) => self.next_action_result_block(session, &request),
+            ActionRequest::WithListAndFirstPBlock(_) => {
+                Err(ServerError::UnsupportedApdu(ACTION_REQUEST_TAG))
+            }
+        }
+    }
+
+    fn get(
+        &self,
+        session: &ServerSession,
+        class_id: u16,
+        instance_id: &ObisCode,
+        attribute_id: i8,
+        access_selection: Option<&AccessSelector>,
+    ) -> GetDataResult {
+        if let Err(error) =
+            self.check_attribute_access(session, class_id, instance_id, attribute_id, false)
+        {
+            return GetDataResult::DataAccessError(error);
+        }
+        if !self.attribute_protection_met(session, class_id, instance_id, attribute_id, false) {
+            return GetDataResult::DataAccessError(DataAccessResult::ReadWriteDenied);
+        }
+        let instance_id = &self.resolve(session, class_id, instance_id);
+        match self.objects.lookup(class_id, instance_id).and_then(|object| match access_selection {
+            Some(selection) => object.get_attribute_selective(
+                attribute_id,
+                selection.selector,
+                &selection.parameters,
+            ),
+            None => object.get_attribute(attribute_id),
+        }) {
+            Ok(data) => GetDataResult::Data(data),
+            Err(error) => GetDataResult::DataAccessError(error),
+        }
+    }
+
+    fn set(
+        &mut self,
+        session: &ServerSession,
+        class_id: u16,
+        instance_id: &ObisCode,
+        attribute_id: i8,
+        access_selection: Option<&AccessSelector>,
+        value: Data,
+    ) -> DataAccessResult {
+        if access_selection.is_some() {
+            return DataAccessResult::ScopeOfAccessViolated;
+        }
+        if let Err(error) =
+            self.check_attribute_access(session, class_id, instance_id, attribute_id, true)
+        {
+            return error;
+        }
+        if !self.attribute_protection_met(session, class_id, instance_id, attribute_id, true) {
+            return DataAccessResult::ReadWriteDenied;
+        }
+        let instance_id = &self.resolve(session, class_id, instance_id);
+        match self
+            .objects
+            .lookup_mut(class_id, instance_id)
+            .and_then(|object| object.set_attribute(attribute_id, value))
+        {
+            Ok(()) => DataAccessResult::Success,
+            Err(error) => error,
+        }
+    }
+
+    fn action(
+        &mut self,
+        session: &ServerSession,
+        class_id: u16,
+        instance_id: &ObisCode,
+        method_id: i8,
+        parameters: Option<Data>,
+    ) -> ActionResult {
+        if let Err(error) = self.check_method_access(session, class_id, instance_id, method_id) {
+            return error;
+        }
+        if !self.method_protection_met(session, class_id, instance_id, method_id) {
+            return ActionResult::ReadWriteDenied;
+        }
+        let instance_id = &self.resolve(session, class_id, instance_id);
+        match self.objects.invoke_method(class_id, instance_id, method_id, parameters) {
+            Ok(data) => ActionResult::Success(data.map(action::GetDataResult::Data)),
+            Err(error) => error,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::{ActionRequestNormal, ActionRequestWithList, MethodDescriptor};
+    use crate::association::{InitiateRequest, ReleaseRequestReason};
+    use crate::cosem::ProfileGeneric;
+    use crate::cosem::data::DataObject;
+    use crate::cosem::register::Register;
+    use crate::get::{AttributeDescriptor, GetRequestNormal, GetRequestWithList};
+    use crate::selective_access::EntryDescriptor;
+    use crate::set::{SetRequestNormal, SetRequestWithList};
+    use crate::{ScalerUnit, Unit};
+
+    const DEVICE_ID: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 1, e: 0, f: 255 };
+    const ENERGY: ObisCode = ObisCode { a: 1, b: 0, c: 1, d: 8, e: 0, f: 255 };
+
+    fn server(settings: ServerSettings) -> DlmsServer {
+        DlmsServer::new(settings)
+            .with_object(DataObject::new(DEVICE_ID, Data::Unsigned(7)))
+            .with_object(Register::new(
+                ENERGY,
+                Data::DoubleLongUnsigned(1000),
+                ScalerUnit { scaler: 0, unit: Unit::WattHour },
+            ))
+    }
+
+    fn associated(server: &mut DlmsServer) -> ServerSession {
+        let mut session = ServerSession::new();
+        let aare = server.handle(&mut session, &AarqApdu::new_simple_ln(512).encode()).unwrap();
+        assert!(AareApdu::parse(&aare).unwrap().1.is_accepted());
+        session
+    }
+
+    fn get_normal(class_id: u16, instance_id: ObisCode, attribute_id: i8) -> GetRequest {
+        GetRequest::Normal(GetRequestNormal {
+            invoke_id: 0xC1,
+            class_id,
+            instance_id,
+            attribute_id,
+            access_selection: None,
+        })
+    }
+
+    #[test]
+    fn test_association_negotiation() {
+        let server = server(ServerSettings { max_pdu_size: 1024, ..Default::default() });
+        let mut session = ServerSession::new();
+        let mut aarq = AarqApdu::new_simple_ln(256);
+        aarq.user_information = Some(InitiateRequest::new(
+            Conformance::GET | Conformance::SELECTIVE_ACCESS | Conformance::ACTION,
+            256,
+        ));
+
+        let aare = server.handle_aarq(&mut session, &aarq);
+        assert!(aare.is_accepted());
+        let initiate = aare.user_information.unwrap();
+        assert_eq!(
+            initiate.negotiated_conformance,
+            Conformance::GET | Conformance::SELECTIVE_ACCESS | Conformance::ACTION
+        );
+        assert_eq!(initiate.server_max_receive_pdu_size, 1024);
+        assert!(session.associated);
+        assert_eq!(session.client_max_receive_pdu_size, 256);
+    }
+
+    #[test]
+    fn test_association_rejections() {
+        let server =
+            server(ServerSettings { password: Some(b"secret".to_vec()), ..Default::default() });
+        let mut session = ServerSession::new();
+
+        let aare = server.handle_aarq(&mut session, &AarqApdu::new_simple_ln(256));
+        assert_eq!(
+            aare.result_source_diagnostic,
+            AcseServiceUserDiagnostics::AuthenticationRequired
+        );
+
+        let aare =
+            server.handle_aarq(&mut session, &AarqApdu::new_with_password(256, b"wrong".to_vec()));
+        assert_eq!(
+            aare.result_source_diagnostic,
+            AcseServiceUserDiagnostics::AuthenticationFailure
+        );
+        assert!(!session.associated);
+
+        let aare =
+            server.handle_aarq(&mut session, &AarqApdu::new_with_password(256, b"secret".to_vec()));
+        assert!(aare.is_accepted());
+        assert_eq!(aare.mechanism_name, Some(MechanismName::LowLevelSecurity));
+
+        let mut aarq = AarqApdu::new_simple_ln(256);
+        aarq.application_context_name = ApplicationContextName::ShortNameReferencing;
+        let aare = server.handle_aarq(&mut session, &aarq);
+        assert_eq!(
+            aare.result_source_diagnostic,
+            AcseServiceUserDiagnostics::ApplicationContextNameNotSupported
+        );
+        assert!(!session.associated);
+    }
+
+    #[test]
+    fn test_hls_authentication() {
+        let secret = b"0123456789ABCDEF".to_vec();
+        let mut server =
+            server(ServerSettings { hls_secret: Some(secret.clone()), ..Default::default() });
+        let mut session = ServerSession::new();
+
+        let mut aarq = AarqApdu::new_simple_ln(256);
+        aarq.mechanism_name = Some(MechanismName::HighLevelSecurity);
+        aarq.calling_authentication_value =
+            Some(AuthenticationValue::CharString(b"CLIENTCHALLENGE!".to_vec()));
+        let aare = server.handle_aarq(&mut session, &aarq);
+        assert!(aare.is_accepted());
+        assert_eq!(
+            aare.result_source_diagnostic,
+            AcseServiceUserDiagnostics::AuthenticationRequired
+        );
+        let Some(AuthenticationValue::CharString(stoc)) = aare.responding_authentication_value
+        else {
+            panic!("expected server challenge");
+        };
+        assert!(session.is_authentication_pending());
+        assert_eq!(
+            server.handle(&mut session, &get_normal(1, DEVICE_ID, 2).encode()),
+            Err(ServerError::NotAssociated)
+        );
+
+        let reply = |parameters| {
+            ActionRequest::Normal(ActionRequestNormal {
+                invoke_id: 0xC1,
+                class_id: 15,
+                instance_id: CURRENT_ASSOCIATION,
+                method_id: 1,
+                method_invocation_parameters: Some(Data::OctetString(parameters)),
+            })
+            .encode()
+        };
+        let f = |challenge: &[u8]| {
+            default_hls_function(MechanismName::HighLevelSecurity, &secret, challenge).unwrap()
+        };
+
+        let response = server.handle(&mut session, &reply(f(&stoc))).unwrap();
+        assert_eq!(
+            ActionResponse::parse(&response).unwrap().1,
+            ActionResponse::Normal(ActionResponseNormal {
+                invoke_id: 0xC1,
+                result: ActionResult::Success(Some(action::GetDataResult::Data(
+                    Data::OctetString(f(b"CLIENTCHALLENGE!"))
+                ))),
+            })
+        );
+        assert!(session.associated);
+        assert!(!session.is_authentication_pending());
+
+        // A wrong reply ends the association attempt
+        server.handle_aarq(&mut session, &aarq);
+        let response = server.handle(&mut session, &reply(vec![0; 16])).unwrap();
+        match ActionResponse::parse(&response).unwrap().1 {
+            ActionResponse::Normal(response) => {
+                assert_eq!(response.result, ActionResult::ReadWriteDenied)
+            }
+            _ => unreachable!(),
+        }
+        assert!(!session.associated);
+        assert!(!session.is_authentication_pending());
+
+        // HLS without a secret is not offered
+        let plain = self::server(ServerSettings::default());
+        assert_eq!(
+            plain.handle_aarq(&mut session, &aarq).result_source_diagnostic,
+            AcseServiceUserDiagnostics::AuthenticationMechanismNameNotRecognised
+        );
+    }
+
+    #[test]
+    fn test_get_normal() {
+        let mut server = server(ServerSettings::default());
+        let mut session = associated(&mut server);
+
+        let response = server.handle(&mut session, &get_normal(3, ENERGY, 2).encode()).unwrap();
+        assert_eq!(
+            GetResponse::parse(&response).unwrap().1,
+            GetResponse::Normal(GetResponseNormal {
+                invoke_id: 0xC1,
+                result: GetDataResult::Data(Data::DoubleLongUnsigned(1000)),
+            })
+        );
+    }
+
+    #[test]
+    fn test_get_errors() {
+        let mut server = server(ServerSettings::default());
+        let mut session = associated(&mut server);
+
+        let mut result = |request| match server.handle_get_request(&mut session, &request).unwrap()
+        {
+            GetResponse::Normal(response) => response.result,
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            result(get_normal(1, ENERGY, 2)),
+            GetDataResult::DataAccessError(DataAccessResult::ObjectClassInconsistent)
+        );
+        assert_eq!(
+            result(get_normal(1, ObisCode::new(0, 0, 96, 1, 1, 255), 2)),
+            GetDataResult::DataAccessError(DataAccessResult::ObjectUndefined)
+        );
+        assert_eq!(
+            result(get_normal(1, DEVICE_ID, 9)),
+            GetDataResult::DataAccessError(DataAccessResult::ObjectUndefined)
+        );
+    }
+
+    #[test]
+    fn test_get_with_list() {
+        let mut server = server(ServerSettings::default());
+        let mut session = associated(&mut server);
+
+        let request = GetRequest::WithList(GetRequestWithList {
+            invoke_id: 0xC2,
+            attribute_descriptor_list: vec![
+                AttributeDescriptor { class_id: 1, instance_id: DEVICE_ID, attribute_id: 2 },
+                AttributeDescriptor { class_id: 3, instance_id: DEVICE_ID, attribute_id: 2 },
+            ],
+        });
+        let response = server.handle(&mut session, &request.encode()).unwrap();
+        assert_eq!(
+            GetResponse::parse(&response).unwrap().1,
+            GetResponse::WithList(GetResponseWithList {
+                invoke_id: 0xC2,
+                results: vec![
+                    GetDataResult::Data(Data::Unsigned(7)),
+                    GetDataResult::DataAccessError(DataAccessResult::ObjectClassInconsistent),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_get_selective_access() {
+        let load_profile = ObisCode::new(1, 0, 99, 1, 0, 255);
+        let mut profile = ProfileGeneric::new(load_profile, 10);
+        for value in [100, 200, 300] {
+            profile.buffer.push_back(vec![Data::DoubleLongUnsigned(value)]);
+        }
+        let mut server = server(ServerSettings::default()).with_object(profile);
+        let mut session = associated(&mut server);
+
+        let selective = |class_id, instance_id| {
+            GetRequest::Normal(GetRequestNormal {
+                access_selection: Some(AccessSelector {
+                    selector: 2,
+                    parameters: EntryDescriptor::last_n_entries(1).encode(),
+                }),
+                ..match get_normal(class_id, instance_id, 2) {
+                    GetRequest::Normal(request) => request,
+                    _ => unreachable!(),
+                }
+            })
+        };
+        let result = |server: &mut DlmsServer, session: &mut ServerSession, request: GetRequest| {
+            let response = server.handle(session, &request.encode()).unwrap();
+            match GetResponse::parse(&response).unwrap().1 {
+                GetResponse::Normal(response) => response.result,
+                _ => unreachable!(),
+            }
+        };
+
+        assert_eq!(
+            result(&mut server, &mut session, selective(7, load_profile)),
+            GetDataResult::Data(Data::Structure(vec![Data::Structure(vec![
+                Data::DoubleLongUnsigned(300)
+            ])]))
+        );
+        assert_eq!(
+            result(&mut server, &mut session, selective(3, ENERGY)),
+            GetDataResult::DataAccessError(DataAccessResult::ScopeOfAccessViolated)
+        );
+
+        // Selective access must be negotiated
+        session.negotiated_conformance = Conformance::GET;
+        assert_eq!(
+            server.handle_get_request(&mut session, &selective(7, load_profile)),
+            Err(ServerError::ServiceNotAllowed)
+        );
+    }
+
+    #[test]
+    fn test_set_normal_and_with_list() {
+        let mut server = server(ServerSettings::default());
+        let mut session = associated(&mut server);
+
+        let request = SetRequest::Normal(SetRequestNormal {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id: DEVICE_ID,
+            attribute_id: 2,
+            access_selection: None,
+            value: Data::Unsigned(8),
+        });
+        let response = server.handle(&mut session, &request.encode()).unwrap();
+        assert_eq!(
+            SetResponse::parse(&response).unwrap().1,
+            SetResponse::Normal(SetResponseNormal {
+                invoke_id: 0xC1,
+                result: DataAccessResult::Success
+            })
+        );
+        assert_eq!(
+            server.objects().get(1, &DEVICE_ID).unwrap().get_attribute(2),
+            Ok(Data::Unsigned(8))
+        );
+
+        let request = SetRequest::WithList(SetRequestWithList {
+            invoke_id: 0xC2,
+            attribute_descriptor_list: vec![
+                AttributeDescriptor { class_id: 1, instance_id: DEVICE_ID, attribute_id: 1 },
+                AttributeDescriptor { class_id: 3, instance_id: ENERGY, attribute_id: 2 },
+            ],
+            value_list: vec![Data::Null, Data::DoubleLongUnsigned(5)],
+        });
+        let response = server.handle(&mut session, &request.encode()).unwrap();
+        assert_eq!(
+            SetResponse::parse(&response).unwrap().1,
+            SetResponse::WithList(SetResponseWithList {
+                invoke_id: 0xC2,
+                results: vec![DataAccessResult::ReadWriteDenied, DataAccessResult::Success],
+            })
+        );
+    }
+
+    #[test]
+    fn test_unsupported_value_type_is_rejected() {
+        let mut server = server(ServerSettings::default());
+        let mut session = associated(&mut server);
+
+        // SET-Request-Normal of 0-0:96.1.0.255 attribute 2 with a visible-string value
+        let mut request = SetRequest::Normal(SetRequestNormal {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id: DEVICE_ID,
+            attribute_id: 2,
+            access_selection: None,
+            value: Data::Null,
+        })
+        .encode();
+        request.pop();
+        request.extend([0x0A, 0x01, b'A']);
+        assert_eq!(server.handle(&mut session, &request), Err(ServerError::ParseError));
+
+        // ACTION-Request-Normal with an array parameter
+        let mut request = ActionRequest::Normal(ActionRequestNormal {
+            invoke_id: 0xC2,
+            class_id: 3,
+            instance_id: ENERGY,
+            method_id: 1,
+            method_invocation_parameters: Some(Data::Null),
+        })
+        .encode();
+        request.pop();
+        request.extend([0x01, 0x00]);
+        assert_eq!(server.handle(&mut session, &request), Err(ServerError::ParseError));
+
+        // The association is still usable
+        let response = server.handle(&mut session, &get_normal(1, DEVICE_ID, 2).encode());
+        assert!(response.is_ok());
+    }
+
+    #[test]
+    fn test_action_normal_and_with_list() {
+        let mut server = server(ServerSettings::default());
+        let mut session = associated(&mut server);
+
+        let request = ActionRequest::Normal(ActionRequestNormal {
+            invoke_id: 0xC1,
+            class_id: 3,
+            instance_id: ENERGY,
+            method_id: 1,
+            method_invocation_parameters: Some(Data::Integer(0)),
+        });
+        let response = server.handle(&mut session, &request.encode()).unwrap();
+        assert_eq!(
+            ActionResponse::parse(&response).unwrap().1,
+            ActionResponse::Normal(ActionResponseNormal {
+                invoke_id: 0xC1,
+                result: ActionResult::Success(None)
+            })
+        );
+
+        let request = ActionRequest::WithList(ActionRequestWithList {
+            invoke_id: 0xC2,
+            method_descriptors: vec![
+                MethodDescriptor {
+                    class_id: 1,
+                    instance_id: DEVICE_ID,
+                    method_id: 1,
+                    method_invocation_parameters: None,
+                },
+                MethodDescriptor {
+                    class_id: 3,
+                    instance_id: ObisCode::new(1, 0, 2, 8, 0, 255),
+                    method_id: 1,
+                    method_invocation_parameters: None,
+                },
+            ],
+        });
+        match server.handle_action_request(&mut session, request).unwrap() {
+            ActionResponse::WithList(response) => {
+                assert_eq!(response.results[1], ActionResult::ObjectUndefined);
+                assert_ne!(response.results[0], ActionResult::Success(None));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_requires_association_and_conformance() {
+        let mut server = server(ServerSettings::default());
+        let mut session = ServerSession::new();
+        let request = get_normal(1, DEVICE_ID, 2).encode();
+        assert_eq!(server.handle(&mut session, &request), Err(ServerError::NotAssociated));
+
+        let mut aarq = AarqApdu::new_simple_ln(256);
+        aarq.user_information = Some(InitiateRequest::new(Conformance::SET, 256));
+        assert!(server.handle_aarq(&mut session, &aarq).is_accepted());
+        assert_eq!(server.handle(&mut session, &request), Err(ServerError::ServiceNotAllowed));
+    }
+
+    #[test]
+    fn test_release() {
+        let mut server = server(ServerSettings::default());
+        let mut session = associated(&mut server);
+
+        let rlrq = ReleaseRequestApdu::with_reason(ReleaseRequestReason::Normal);
+        let rlre = server.handle(&mut session, &rlrq.encode()).unwrap();
+        assert_eq!(
+            ReleaseResponseApdu::parse(&rlre).unwrap().1.reason,
+            Some(ReleaseResponseReason::Normal)
+        );
+        assert!(!session.associated);
+    }
+
+    #[test]
+    fn test_unsupported_apdu() {
+        let mut server = server(ServerSettings::default());
+        let mut session = associated(&mut server);
+        assert_eq!(
+            server.handle(&mut session, &[0xD8, 0x01]),
+            Err(ServerError::UnsupportedApdu(0xD8))
+        );
+        assert_eq!(server.handle(&mut session, &[]), Err(ServerError::ParseError));
+        assert_eq!(ServerError::UnsupportedApdu(0xD8).exception_response(), vec![0xD8, 2, 2]);
+        assert_eq!(ServerError::NotAssociated.exception_response(), vec![0xD8, 1, 1]);
+    }
+}
@@ -0,0 +1,474 @@
+//! Client associations of a logical device.
+//!
+//! Each client SAP (public client 16, management client 1, ...) has its own
+//! Association LN object (0.0.40.0.e.255) listing the objects it may access,
+//! and its own authentication. A [`ClientAssociation`] in
+//! [`ServerSettings::associations`](super::ServerSettings::associations) binds
+//! a client SAP to such an object; sessions are opened for a client SAP with
+//! [`DlmsServer::open_session`].
+//!
+//! Once associated, every GET, SET and ACTION is checked against the object
+//! list of the client's association: objects that are not listed are
+//! undefined, attributes need read or write access and methods need access.
+//! The current association (0.0.40.0.0.255) addresses the client's
+//! association object.
+//!
+//! Without configured associations, any client may associate with the
+//! credentials of the server settings and no object list is enforced.
+
+use alloc::vec::Vec;
+
+use super::{
+    ASSOCIATION_LN_CLASS_ID, CURRENT_ASSOCIATION, DlmsServer, ServerSession, ServerSettings,
+};
+use crate::action::ActionResult;
+use crate::association::{ApplicationContextName, MechanismName};
+use crate::cosem::association_ln::AssociationLn;
+use crate::cosem::{AttributeAccess, MethodAccess, ObjectListElement};
+use crate::get::DataAccessResult;
+use crate::obis_code::ObisCode;
+
+/// Client SAP of the public client.
+pub const PUBLIC_CLIENT_SAP: u16 = 16;
+
+/// Client SAP of the management client.
+pub const MANAGEMENT_CLIENT_SAP: u16 = 1;
+
+/// Association of one client SAP with the logical device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientAssociation {
+    /// Client SAP (HDLC client address or wrapper source wPort)
+    pub client_sap: u16,
+    /// Logical name of the Association LN object holding the object list
+    /// and access rights of the client (0.0.40.0.e.255).
+    pub logical_name: ObisCode,
+    /// LLS password the client must authenticate with.
+    /// Default: None.
+    pub password: Option<Vec<u8>>,
+    /// HLS secret the client may authenticate with.
+    /// Default: None.
+    pub hls_secret: Option<Vec<u8>>,
+    /// Pre-established association: data services are answered without an
+    /// AARQ, and a release falls back to the pre-established association.
+    /// Default: false.
+    pub pre_established: bool,
+}
+
+impl ClientAssociation {
+    /// Creates the association of a client SAP without authentication.
+    pub fn new(client_sap: u16, logical_name: ObisCode) -> Self {
+        Self { client_sap, logical_name, password: None, hls_secret: None, pre_established: false }
+    }
+
+    /// Requires Low Level Security with a password.
+    pub fn with_password(mut self, password: Vec<u8>) -> Self {
+        self.password = Some(password);
+        self
+    }
+
+    /// Accepts High Level Security with a secret.
+    pub fn with_hls_secret(mut self, hls_secret: Vec<u8>) -> Self {
+        self.hls_secret = Some(hls_secret);
+        self
+    }
+
+    /// Makes the association pre-established.
+    pub fn pre_established(mut self) -> Self {
+        self.pre_established = true;
+        self
+    }
+}
+
+impl ServerSettings {
+    /// Adds the association of a client SAP (builder style).
+    pub fn with_association(mut self, association: ClientAssociation) -> Self {
+        self.associations.retain(|existing| existing.client_sap != association.client_sap);
+        self.associations.push(association);
+        self
+    }
+}
+
+impl ServerSession {
+    /// Creates a session of a client SAP that is not associated yet.
+    pub fn for_client(client_sap: u16) -> Self {
+        Self { client_sap: Some(client_sap), ..Self::default() }
+    }
+
+    /// Returns the client SAP of the session, if known.
+    pub fn client_sap(&self) -> Option<u16> {
+        self.client_sap
+    }
+
+    /// Returns the logical name of the client's association object, once
+    /// associated through a [`ClientAssociation`].
+    pub fn association(&self) -> Option<ObisCode> {
+        self.association
+    }
+}
+
+impl DlmsServer {
+    /// Opens the session of a client SAP.
+    ///
+    /// The session of a pre-established association is associated right
+    /// away, with the server's conformance and PDU size.
+    pub fn open_session(&self, client_sap: u16) -> ServerSession {
+        let mut session = ServerSession::for_client(client_sap);
+        if let Some(association) = self
+            .client_association(Some(client_sap))
+            .filter(|association| association.pre_established)
+        {
+            session.associated = true;
+            session.application_context_name = Some(ApplicationContextName::LogicalNameReferencing);
+            session.mechanism_name = Some(MechanismName::LowestLevelSecurity);
+            session.negotiated_conformance = self.settings.conformance;
+            session.client_max_receive_pdu_size = self.settings.max_pdu_size;
+            session.association = Some(association.logical_name);
+        }
+        session
+    }
+
+    /// Returns the session to its state after [`open_session`](Self::open_session).
+    pub(super) fn reset_session(&self, session: &mut ServerSession) {
+        let server_sap = session.server_sap;
+        *session = match session.client_sap {
+            Some(client_sap) => self.open_session(client_sap),
+            None => ServerSession::new(),
+        };
+        session.server_sap = server_sap;
+    }
+
+    /// Returns the configured association of a client SAP.
+    pub(super) fn client_association(&self, client_sap: Option<u16>) -> Option<&ClientAssociation> {
+        let client_sap = client_sap?;
+        self.settings.associations.iter().find(|association| association.client_sap == client_sap)
+    }
+
+    /// Returns the LLS password and HLS secret a session authenticates with.
+    pub(super) fn credentials(&self, session: &ServerSession) -> (Option<&[u8]>, Option<&[u8]>) {
+        match self.client_association(session.client_sap) {
+            Some(association) => {
+                (association.password.as_deref(), association.hls_secret.as_deref())
+            }
+            None => (self.settings.password.as_deref(), self.settings.hls_secret.as_deref()),
+        }
+    }
+
+    /// Returns `true` if `logical_name` addresses the session's association object.
+    pub(super) fn is_current_association(
+        &self,
+        session: &ServerSession,
+        logical_name: &ObisCode,
+    ) -> bool {
+        *logical_name == CURRENT_ASSOCIATION || session.association == Some(*logical_name)
+    }
+
+    /// Maps the current association (0.0.40.0.0.255) to the session's
+    /// association object.
+    pub(super) fn resolve(
+        &self,
+        session: &ServerSession,
+        class_id: u16,
+        logical_name: &ObisCode,
+    ) -> ObisCode {
+        match session.association {
+            Some(association)
+                if class_id == ASSOCIATION_LN_CLASS_ID && *logical_name == CURRENT_ASSOCIATION =>
+            {
+                association
+            }
+            _ => *logical_name,
+        }
+    }
+
+    /// Returns the object list of the session's association object, or of
+    /// the current association without a configured association.
+    pub(super) fn object_list(&self, session: &ServerSession) -> Option<Vec<ObjectListElement>> {
+        let logical_name = session.association.unwrap_or(CURRENT_ASSOCIATION);
+        let object_list = self
+            .objects
+            .lookup(ASSOCIATION_LN_CLASS_ID, &logical_name)
+            .and_then(|association| association.get_attribute(2))
+            .ok()?;
+        AssociationLn::parse_object_list(&object_list).ok()
+    }
+
+    /// Finds an object in an object list, which may list the session's
+    /// association object under its own name or as the current association.
+    pub(super) fn find_element(
+        &self,
+        session: &ServerSession,
+        list: Vec<ObjectListElement>,
+        class_id: u16,
+        logical_name: &ObisCode,
+    ) -> Option<ObjectListElement> {
+        let resolved = self.resolve(session, class_id, logical_name);
+        list.into_iter().find(|element| {
+            element.class_id == class_id
+                && (element.logical_name == *logical_name || element.logical_name == resolved)
+        })
+    }
+
+    /// Checks an attribute against the object list of the session's association.
+    pub(super) fn check_attribute_access(
+        &self,
+        session: &ServerSession,
+        class_id: u16,
+        logical_name: &ObisCode,
+        attribute_id: i8,
+        write: bool,
+    ) -> Result<(), DataAccessResult> {
+        if session.association.is_none() {
+            return Ok(());
+        }
+        let element = self
+            .object_list(session)
+            .and_then(|list| self.find_element(session, list, class_id, logical_name))
+            .ok_or(DataAccessResult::ObjectUndefined)?;
+        let access = element
+            .access_rights
+            .attribute_access(attribute_id)
+            .unwrap_or(AttributeAccess::NO_ACCESS);
+        let required = match write {
+            true => AttributeAccess::WRITE_ONLY,
+            false => AttributeAccess::READ_ONLY,
+        };
+        match access.contains(required) {
+            true => Ok(()),
+            false => Err(DataAccessResult::ReadWriteDenied),
+        }
+    }
+
+    /// Checks a method against the object list of the session's association.
+    pub(super) fn check_method_access(
+        &self,
+        session: &ServerSession,
+        class_id: u16,
+        logical_name: &ObisCode,
+        method_id: i8,
+    ) -> Result<(), ActionResult> {
+        if session.association.is_none() {
+            return Ok(());
+        }
+        let element = self
+            .object_list(session)
+            .and_then(|list| self.find_element(session, list, class_id, logical_name))
+            .ok_or(ActionResult::ObjectUndefined)?;
+        match element.access_rights.method_access(method_id).unwrap_or(MethodAccess::NO_ACCESS) {
+            MethodAccess::NO_ACCESS => Err(ActionResult::ReadWriteDenied),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::association::{AareApdu, AarqApdu, ReleaseRequestApdu, ReleaseRequestReason};
+    use crate::cosem::association_ln::AccessRights;
+    use crate::cosem::data::DataObject;
+    use crate::data::Data;
+    use crate::get::{GetDataResult, GetRequest, GetRequestNormal, GetResponse};
+    use crate::set::{SetRequest, SetRequestNormal, SetResponse};
+
+    const PUBLIC_ASSOCIATION: ObisCode = ObisCode { a: 0, b: 0, c: 40, d: 0, e: 1, f: 255 };
+    const MANAGEMENT_ASSOCIATION: ObisCode = ObisCode { a: 0, b: 0, c: 40, d: 0, e: 2, f: 255 };
+    const CLOCK_SETTING: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 1, e: 0, f: 255 };
+    const SECRET_VALUE: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 1, e: 1, f: 255 };
+
+    fn element(
+        class_id: u16,
+        logical_name: ObisCode,
+        access: AttributeAccess,
+    ) -> ObjectListElement {
+        ObjectListElement {
+            class_id,
+            version: 0,
+            logical_name,
+            access_rights: AccessRights::new()
+                .with_attribute(1, AttributeAccess::READ_ONLY)
+                .with_attribute(2, access),
+        }
+    }
+
+    fn server(public: ClientAssociation) -> DlmsServer {
+        let settings = ServerSettings::default().with_association(public).with_association(
+            ClientAssociation::new(MANAGEMENT_CLIENT_SAP, MANAGEMENT_ASSOCIATION)
+                .with_password(b"12345678".to_vec()),
+        );
+        let read_write = AttributeAccess::READ_ONLY | AttributeAccess::WRITE_ONLY;
+        DlmsServer::new(settings)
+            .with_object(DataObject::new(CLOCK_SETTING, Data::Unsigned(1)))
+            .with_object(DataObject::new(SECRET_VALUE, Data::Unsigned(2)))
+            .with_object(
+                AssociationLn::new()
+                    .with_logical_name(PUBLIC_ASSOCIATION)
+                    .with_partners(16, 1)
+                    .with_object(element(15, CURRENT_ASSOCIATION, AttributeAccess::READ_ONLY))
+                    .with_object(element(1, CLOCK_SETTING, AttributeAccess::READ_ONLY)),
+            )
+            .with_object(
+                AssociationLn::new()
+                    .with_logical_name(MANAGEMENT_ASSOCIATION)
+                    .with_partners(1, 1)
+                    .with_object(element(1, CLOCK_SETTING, read_write))
+                    .with_object(element(1, SECRET_VALUE, AttributeAccess::READ_ONLY)),
+            )
+    }
+
+    fn associate(server: &mut DlmsServer, client_sap: u16, aarq: AarqApdu) -> ServerSession {
+        let mut session = server.open_session(client_sap);
+        let aare = server.handle(&mut session, &aarq.encode()).unwrap();
+        assert!(AareApdu::parse(&aare).unwrap().1.is_accepted());
+        session
+    }
+
+    fn get(
+        server: &mut DlmsServer,
+        session: &mut ServerSession,
+        class_id: u16,
+        instance_id: ObisCode,
+        attribute_id: i8,
+    ) -> GetDataResult {
+        let request = GetRequest::Normal(GetRequestNormal {
+            invoke_id: 0xC1,
+            class_id,
+            instance_id,
+            attribute_id,
+            access_selection: None,
+        });
+        let response = server.handle(session, &request.encode()).unwrap();
+        match GetResponse::parse(&response).unwrap().1 {
+            GetResponse::Normal(response) => response.result,
+            response => panic!("unexpected response {:?}", response),
+        }
+    }
+
+    fn set(
+        server: &mut DlmsServer,
+        session: &mut ServerSession,
+        instance_id: ObisCode,
+        value: Data,
+    ) -> DataAccessResult {
+        let request = SetRequest::Normal(SetRequestNormal {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id,
+            attribute_id: 2,
+            access_selection: None,
+            value,
+        });
+        let response = server.handle(session, &request.encode()).unwrap();
+        match SetResponse::parse(&response).unwrap().1 {
+            SetResponse::Normal(response) => response.result,
+            response => panic!("unexpected response {:?}", response),
+        }
+    }
+
+    #[test]
+    fn test_access_rights_per_client() {
+        let mut server = server(ClientAssociation::new(PUBLIC_CLIENT_SAP, PUBLIC_ASSOCIATION));
+
+        let mut public = associate(&mut server, PUBLIC_CLIENT_SAP, AarqApdu::new_simple_ln(512));
+        assert_eq!(public.association(), Some(PUBLIC_ASSOCIATION));
+        assert_eq!(
+            get(&mut server, &mut public, 1, CLOCK_SETTING, 2),
+            GetDataResult::Data(Data::Unsigned(1))
+        );
+        assert_eq!(
+            get(&mut server, &mut public, 1, SECRET_VALUE, 2),
+            GetDataResult::DataAccessError(DataAccessResult::ObjectUndefined)
+        );
+        assert_eq!(
+            set(&mut server, &mut public, CLOCK_SETTING, Data::Unsigned(3)),
+            DataAccessResult::ReadWriteDenied
+        );
+
+        let aarq = AarqApdu::new_with_password(512, b"12345678".to_vec());
+        let mut management = associate(&mut server, MANAGEMENT_CLIENT_SAP, aarq);
+        assert_eq!(
+            get(&mut server, &mut management, 1, SECRET_VALUE, 2),
+            GetDataResult::Data(Data::Unsigned(2))
+        );
+        assert_eq!(
+            set(&mut server, &mut management, CLOCK_SETTING, Data::Unsigned(3)),
+            DataAccessResult::Success
+        );
+    }
+
+    #[test]
+    fn test_client_credentials() {
+        let server = server(ClientAssociation::new(PUBLIC_CLIENT_SAP, PUBLIC_ASSOCIATION));
+
+        // The management client needs its password, the public client none.
+        let mut session = server.open_session(MANAGEMENT_CLIENT_SAP);
+        let aare = server.handle_aarq(&mut session, &AarqApdu::new_simple_ln(512));
+        assert!(!aare.is_accepted());
+        let aarq = AarqApdu::new_with_password(512, b"wrong".to_vec());
+        assert!(!server.handle_aarq(&mut session, &aarq).is_accepted());
+
+        // Clients without an association are rejected.
+        let mut session = server.open_session(0x20);
+        assert!(!server.handle_aarq(&mut session, &AarqApdu::new_simple_ln(512)).is_accepted());
+        let mut session = ServerSession::new();
+        assert!(!server.handle_aarq(&mut session, &AarqApdu::new_simple_ln(512)).is_accepted());
+    }
+
+    #[test]
+    fn test_current_association() {
+        let mut server = server(ClientAssociation::new(PUBLIC_CLIENT_SAP, PUBLIC_ASSOCIATION));
+        let mut session = associate(&mut server, PUBLIC_CLIENT_SAP, AarqApdu::new_simple_ln(512));
+
+        assert_eq!(
+            get(&mut server, &mut session, 15, CURRENT_ASSOCIATION, 1),
+            GetDataResult::Data(Data::OctetString(PUBLIC_ASSOCIATION.encode().to_vec()))
+        );
+        assert_eq!(
+            get(&mut server, &mut session, 15, CURRENT_ASSOCIATION, 3),
+            GetDataResult::DataAccessError(DataAccessResult::ReadWriteDenied)
+        );
+        // The management association is not in the public object list.
+        assert_eq!(
+            get(&mut server, &mut session, 15, MANAGEMENT_ASSOCIATION, 1),
+            GetDataResult::DataAccessError(DataAccessResult::ObjectUndefined)
+        );
+    }
+
+    #[test]
+    fn test_pre_established_association() {
+        let public =
+            ClientAssociation::new(PUBLIC_CLIENT_SAP, PUBLIC_ASSOCIATION).pre_established();
+        let mut server = server(public);
+
+        let mut session = server.open_session(PUBLIC_CLIENT_SAP);
+        assert!(session.associated);
+        assert_eq!(
+            get(&mut server, &mut session, 1, CLOCK_SETTING, 2),
+            GetDataResult::Data(Data::Unsigned(1))
+        );
+
+        // A release falls back to the pre-established association.
+        let rlrq = ReleaseRequestApdu::with_reason(ReleaseRequestReason::Normal);
+        server.handle(&mut session, &rlrq.encode()).unwrap();
+        assert!(session.associated);
+        assert_eq!(session.association(), Some(PUBLIC_ASSOCIATION));
+
+        // Other clients still need an AARQ.
+        let mut session = server.open_session(MANAGEMENT_CLIENT_SAP);
+        assert!(!session.associated);
+        assert!(
+            server
+                .handle(
+                    &mut session,
+                    &GetRequest::Normal(GetRequestNormal {
+                        invoke_id: 0xC1,
+                        class_id: 1,
+                        instance_id: CLOCK_SETTING,
+                        attribute_id: 2,
+                        access_selection: None,
+                    })
+                    .encode()
+                )
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,861 @@
+//! Block transfer of long GET, SET and ACTION data.
+//!
+//! A response that does not fit into the client's maximum receive PDU size is
+//! sent in blocks (GET-Response-With-Datablock, ACTION-Response-With-Pblock),
+//! each one requested by the client with the number of the block it received.
+//! Long SET values and ACTION parameters are received in blocks the same way,
+//! each block acknowledged until the last one.
+//!
+//! A session holds at most one transfer. It is aborted by any other request,
+//! by an unexpected block number, when no block arrives within
+//! [`ServerSettings::block_transfer_timeout`](super::ServerSettings::block_transfer_timeout),
+//! or when the received data exceeds
+//! [`ServerSettings::max_block_transfer_size`](super::ServerSettings::max_block_transfer_size).
+
+use alloc::vec::Vec;
+use std::time::{Duration, Instant};
+
+use super::{DlmsServer, ServerError, ServerSession};
+use crate::action::{
+    ActionRequestNextPBlock, ActionRequestWithFirstPBlock, ActionRequestWithPBlock, ActionResponse,
+    ActionResponseNextPBlock, ActionResponseNormal, ActionResponseWithPBlock, ActionResult,
+    DataBlockSa,
+};
+use crate::association::Conformance;
+use crate::data::Data;
+use crate::get::{
+    AccessSelector, DataAccessResult, GetDataBlockResult, GetDataResult, GetRequestNext,
+    GetResponse, GetResponseWithDataBlock,
+};
+use crate::obis_code::ObisCode;
+use crate::set::{
+    SetRequestFirstDataBlock, SetRequestWithDataBlock, SetResponse, SetResponseDataBlock,
+    SetResponseLastDataBlock,
+};
+
+/// Header of GET-Response-With-Datablock: tag, choice, invoke-id, last-block,
+/// block-number and result choice.
+const GET_BLOCK_OVERHEAD: usize = 9;
+
+/// Header of ACTION-Response-With-Pblock: tag, choice, invoke-id, last-block and
+/// block-number.
+const ACTION_BLOCK_OVERHEAD: usize = 8;
+
+/// Block transfer in progress on a session.
+#[derive(Debug, Clone)]
+pub(super) struct BlockTransfer {
+    kind: TransferKind,
+    invoke_id: u8,
+    /// Number of the last block sent or received.
+    block_number: u32,
+    /// Data still to send, or data received so far.
+    data: Vec<u8>,
+    /// Time of the last block.
+    updated: Instant,
+}
+
+#[derive(Debug, Clone)]
+enum TransferKind {
+    /// GET response being sent.
+    Get,
+    /// SET value being received.
+    Set {
+        class_id: u16,
+        instance_id: ObisCode,
+        attribute_id: i8,
+        access_selection: Option<AccessSelector>,
+    },
+    /// ACTION parameters being received.
+    ActionParameters { class_id: u16, instance_id: ObisCode, method_id: i8 },
+    /// ACTION return parameters being sent.
+    ActionResult,
+}
+
+impl BlockTransfer {
+    fn new(kind: TransferKind, invoke_id: u8, block_number: u32, data: Vec<u8>) -> Self {
+        Self { kind, invoke_id, block_number, data, updated: Instant::now() }
+    }
+
+    /// Takes the next block to send, at most `max_pdu_size - overhead` bytes.
+    ///
+    /// Returns the block and whether it is the last one.
+    fn next_block(&mut self, max_pdu_size: u16, overhead: usize) -> (Vec<u8>, bool) {
+        let size = usize::from(max_pdu_size).saturating_sub(overhead).max(1);
+        let block = self.data.drain(..size.min(self.data.len())).collect();
+        self.block_number += 1;
+        self.updated = Instant::now();
+        (block, self.data.is_empty())
+    }
+
+    /// Appends a received block if it is the next one and the data received
+    /// stays within `max_size` bytes.
+    fn receive_block(&mut self, block_number: u32, raw_data: &[u8], max_size: usize) -> bool {
+        if block_number != self.block_number.wrapping_add(1)
+            || self.data.len() + raw_data.len() > max_size
+        {
+            return false;
+        }
+        self.data.extend_from_slice(raw_data);
+        self.block_number = block_number;
+        self.updated = Instant::now();
+        true
+    }
+}
+
+impl ServerSession {
+    /// Returns `true` while a GET, SET or ACTION block transfer is in progress.
+    pub fn is_block_transfer_pending(&self) -> bool {
+        self.block_transfer.is_some()
+    }
+
+    /// Aborts the block transfer in progress, if any.
+    ///
+    /// Returns `true` if a transfer was aborted.
+    pub fn abort_block_transfer(&mut self) -> bool {
+        self.block_transfer.take().is_some()
+    }
+
+    /// Aborts a transfer whose last block is `timeout` or longer ago.
+    pub(super) fn expire_block_transfer(&mut self, timeout: Duration) {
+        if self
+            .block_transfer
+            .as_ref()
+            .is_some_and(|transfer| transfer.updated.elapsed() >= timeout)
+        {
+            self.block_transfer = None;
+        }
+    }
+
+    /// Returns `true` if `response` exceeds the client's maximum receive PDU size.
+    ///
+    /// A size of 0 means the client did not state a limit.
+    fn exceeds_pdu_size(&self, response: &[u8]) -> bool {
+        self.client_max_receive_pdu_size != 0
+            && response.len() + self.ciphering_overhead()
+                > usize::from(self.client_max_receive_pdu_size)
+    }
+
+    /// Checks that a response exceeding the client's PDU size may be sent in blocks.
+    fn require_long_response(&self, service: Conformance) -> Result<(), ServerError> {
+        self.require(service).map_err(|error| match error {
+            ServerError::ServiceNotAllowed => ServerError::PduTooLong,
+            error => error,
+        })
+    }
+}
+
+/// Decodes block-transferred data into a single value.
+fn decode_value(data: &[u8]) -> Option<Data> {
+    match Data::parse(data) {
+        Ok(([], value)) => Some(value),
+        _ => None,
+    }
+}
+
+impl DlmsServer {
+    /// Starts sending `response` in blocks if it exceeds the client's PDU size.
+    ///
+    /// Without negotiated block transfer, a response that does not fit is
+    /// answered with [`ServerError::PduTooLong`].
+    pub(super) fn fit_get_response(
+        &self,
+        session: &mut ServerSession,
+        response: GetResponse,
+    ) -> Result<GetResponse, ServerError> {
+        let encoded = response.encode();
+        if !session.exceeds_pdu_size(&encoded) {
+            return Ok(response);
+        }
+        session.require_long_response(Conformance::BLOCK_TRANSFER_WITH_GET_OR_READ)?;
+
+        // Raw data is the encoded Data of a Normal response, or the encoded
+        // result list of a With-List response.
+        let (invoke_id, data) = match &response {
+            GetResponse::Normal(normal) => match normal.result {
+                GetDataResult::Data(_) => (normal.invoke_id, encoded[4..].to_vec()),
+                GetDataResult::DataAccessError(_) => return Ok(response),
+            },
+            GetResponse::WithList(list) => (list.invoke_id, encoded[3..].to_vec()),
+            GetResponse::WithDataBlock(_) => return Ok(response),
+        };
+        Ok(self.send_get_block(session, BlockTransfer::new(TransferKind::Get, invoke_id, 0, data)))
+    }
+
+    /// Answers GET-Request-Next with the requested block.
+    ///
+    /// The client acknowledges the block it received; any other block number
+    /// aborts the transfer.
+    pub(super) fn next_get_block(
+        &self,
+        session: &mut ServerSession,
+        request: &GetRequestNext,
+    ) -> Result<GetResponse, ServerError> {
+        session.require(Conformance::BLOCK_TRANSFER_WITH_GET_OR_READ)?;
+        let result = match session.block_transfer.take() {
+            Some(transfer @ BlockTransfer { kind: TransferKind::Get, .. })
+                if transfer.block_number == request.block_number =>
+            {
+                return Ok(self.send_get_block(session, transfer));
+            }
+            Some(BlockTransfer { kind: TransferKind::Get, .. }) => DataAccessResult::LongGetAborted,
+            _ => DataAccessResult::NoLongGetInProgress,
+        };
+        Ok(GetResponse::WithDataBlock(GetResponseWithDataBlock {
+            invoke_id: request.invoke_id,
+            last_block: true,
+            block_number: request.block_number,
+            result: GetDataBlockResult::DataAccessError(result),
+        }))
+    }
+
+    fn send_get_block(
+        &self,
+        session: &mut ServerSession,
+        mut transfer: BlockTransfer,
+    ) -> GetResponse {
+        let (block, last_block) = transfer.next_block(
+            session.client_max_receive_pdu_size,
+            GET_BLOCK_OVERHEAD + session.ciphering_overhead(),
+        );
+        let response = GetResponse::WithDataBlock(GetResponseWithDataBlock {
+            invoke_id: transfer.invoke_id,
+            last_block,
+            block_number: transfer.block_number,
+            result: GetDataBlockResult::RawData(block),
+        });
+        if !last_block {
+            session.block_transfer = Some(transfer);
+        }
+        response
+    }
+
+    /// Processes the first block of a long SET value.
+    pub(super) fn first_set_block(
+        &mut self,
+        session: &mut ServerSession,
+        request: SetRequestFirstDataBlock,
+    ) -> Result<SetResponse, ServerError> {
+        session.require(Conformance::BLOCK_TRANSFER_WITH_SET_OR_WRITE)?;
+        if request.block_number != 1 {
+            return Ok(SetResponse::LastDataBlock(SetResponseLastDataBlock {
+                invoke_id: request.invoke_id,
+                result: DataAccessResult::DataBlockNumberInvalid,
+                block_number: request.block_number,
+            }));
+        }
+        if request.raw_data.len() > self.settings.max_block_transfer_size {
+            return Ok(SetResponse::LastDataBlock(SetResponseLastDataBlock {
+                invoke_id: request.invoke_id,
+                result: DataAccessResult::LongSetAborted,
+                block_number: request.block_number,
+            }));
+        }
+        let kind = TransferKind::Set {
+            class_id: request.class_id,
+            instance_id: request.instance_id,
+            attribute_id: request.attribute_id,
+            access_selection: request.access_selection,
+        };
+        let transfer = BlockTransfer::new(kind, request.invoke_id, 1, request.raw_data);
+        Ok(self.acknowledge_set_block(session, transfer, request.last_block))
+    }
+
+    /// Processes a further block of a long SET value.
+    pub(super) fn next_set_block(
+        &mut self,
+        session: &mut ServerSession,
+        request: SetRequestWithDataBlock,
+    ) -> Result<SetResponse, ServerError> {
+        session.require(Conformance::BLOCK_TRANSFER_WITH_SET_OR_WRITE)?;
+        let result = match session.block_transfer.take() {
+            Some(mut transfer @ BlockTransfer { kind: TransferKind::Set { .. }, .. }) => {
+                let max_size = self.settings.max_block_transfer_size;
+                if transfer.receive_block(request.block_number, &request.raw_data, max_size) {
+                    return Ok(self.acknowledge_set_block(session, transfer, request.last_block));
+                }
+                DataAccessResult::LongSetAborted
+            }
+            _ => DataAccessResult::NoLongSetInProgress,
+        };
+        Ok(SetResponse::LastDataBlock(SetResponseLastDataBlock {
+            invoke_id: request.invoke_id,
+            result,
+            block_number: request.block_number,
+        }))
+    }
+
+    /// Acknowledges a received SET block, or writes the value after the last one.
+    fn acknowledge_set_block(
+        &mut self,
+        session: &mut ServerSession,
+        transfer: BlockTransfer,
+        last_block: bool,
+    ) -> SetResponse {
+        let BlockTransfer { invoke_id, block_number, .. } = transfer;
+        if !last_block {
+            session.block_transfer = Some(transfer);
+            return SetResponse::DataBlock(SetResponseDataBlock { invoke_id, block_number });
+        }
+
+        let result = match (transfer.kind, decode_value(&transfer.data)) {
+            (
+                TransferKind::Set { class_id, instance_id, attribute_id, access_selection },
+                Some(value),
+            ) => self.set(
+                session,
+                class_id,
+                &instance_id,
+                attribute_id,
+                access_selection.as_ref(),
+                value,
+            ),
+            (TransferKind::Set { .. }, None) => DataAccessResult::TypeUnmatched,
+            _ => DataAccessResult::NoLongSetInProgress,
+        };
+        SetResponse::LastDataBlock(SetResponseLastDataBlock { invoke_id, result, block_number })
+    }
+
+    /// Processes the first block of long ACTION parameters.
+    pub(super) fn first_action_block(
+        &mut self,
+        session: &mut ServerSession,
+        request: ActionRequestWithFirstPBlock,
+    ) -> Result<ActionResponse, ServerError> {
+        session.require(Conformance::BLOCK_TRANSFER_WITH_ACTION)?;
+        let pblock = request.pblock;
+        if pblock.block_number != 1 || pblock.raw_data.len() > self.settings.max_block_transfer_size
+        {
+            return Ok(ActionResponse::Normal(ActionResponseNormal {
+                invoke_id: request.invoke_id,
+                result: ActionResult::LongActionAborted,
+            }));
+        }
+        let kind = TransferKind::ActionParameters {
+            class_id: request.class_id,
+            instance_id: request.instance_id,
+            method_id: request.method_id,
+        };
+        let transfer = BlockTransfer::new(kind, request.invoke_id, 1, pblock.raw_data);
+        self.acknowledge_action_block(session, transfer, pblock.last_block)
+    }
+
+    /// Processes a further block of long ACTION parameters.
+    pub(super) fn next_action_block(
+        &mut self,
+        session: &mut ServerSession,
+        request: ActionRequestWithPBlock,
+    ) -> Result<ActionResponse, ServerError> {
+        session.require(Conformance::BLOCK_TRANSFER_WITH_ACTION)?;
+        let pblock = request.pblock;
+        let result = match session.block_transfer.take() {
+            Some(
+                mut transfer @ BlockTransfer { kind: TransferKind::ActionParameters { .. }, .. },
+            ) => {
+                let max_size = self.settings.max_block_transfer_size;
+                if transfer.receive_block(pblock.block_number, &pblock.raw_data, max_size) {
+                    return self.acknowledge_action_block(session, transfer, pblock.last_block);
+                }
+                ActionResult::LongActionAborted
+            }
+            _ => ActionResult::NoLongActionInProgress,
+        };
+        Ok(ActionResponse::Normal(ActionResponseNormal { invoke_id: request.invoke_id, result }))
+    }
+
+    /// Acknowledges a received ACTION block, or invokes the method after the last one.
+    fn acknowledge_action_block(
+        &mut self,
+        session: &mut ServerSession,
+        transfer: BlockTransfer,
+        last_block: bool,
+    ) -> Result<ActionResponse, ServerError> {
+        let BlockTransfer { invoke_id, block_number, .. } = transfer;
+        if !last_block {
+            session.block_transfer = Some(transfer);
+            return Ok(ActionResponse::NextPBlock(ActionResponseNextPBlock {
+                invoke_id,
+                block_number,
+            }));
+        }
+
+        let TransferKind::ActionParameters { class_id, instance_id, method_id } = transfer.kind
+        else {
+            let result = ActionResult::NoLongActionInProgress;
+            return Ok(ActionResponse::Normal(ActionResponseNormal { invoke_id, result }));
+        };
+        let parameters = match transfer.data.is_empty() {
+            true => None,
+            false => match decode_value(&transfer.data) {
+                Some(parameters) => Some(parameters),
+                None => {
+                    let result = ActionResult::TypeUnmatched;
+                    return Ok(ActionResponse::Normal(ActionResponseNormal { invoke_id, result }));
+                }
+            },
+        };
+        let result = self.action(session, class_id, &instance_id, method_id, parameters);
+        self.fit_action_response(
+            session,
+            ActionResponse::Normal(ActionResponseNormal { invoke_id, result }),
+        )
+    }
+
+    /// Starts sending the return parameters of `response` in blocks if it
+    /// exceeds the client's PDU size.
+    pub(super) fn fit_action_response(
+        &self,
+        session: &mut ServerSession,
+        response: ActionResponse,
+    ) -> Result<ActionResponse, ServerError> {
+        let encoded = response.encode();
+        if !session.exceeds_pdu_size(&encoded) {
+            return Ok(response);
+        }
+        let ActionResponse::Normal(ActionResponseNormal {
+            invoke_id,
+            result: ActionResult::Success(Some(crate::action::GetDataResult::Data(data))),
+        }) = response
+        else {
+            return Err(ServerError::PduTooLong);
+        };
+        session.require_long_response(Conformance::BLOCK_TRANSFER_WITH_ACTION)?;
+
+        let transfer = BlockTransfer::new(TransferKind::ActionResult, invoke_id, 0, data.encode());
+        Ok(self.send_action_block(session, transfer))
+    }
+
+    /// Answers ACTION-Request-Next-Pblock with the next block of return parameters.
+    pub(super) fn next_action_result_block(
+        &self,
+        session: &mut ServerSession,
+        request: &ActionRequestNextPBlock,
+    ) -> Result<ActionResponse, ServerError> {
+        session.require(Conformance::BLOCK_TRANSFER_WITH_ACTION)?;
+        let result = match session.block_transfer.take() {
+            Some(transfer @ BlockTransfer { kind: TransferKind::ActionResult, .. })
+                if transfer.block_number == request.block_number =>
+            {
+                return Ok(self.send_action_block(session, transfer));
+            }
+            Some(BlockTransfer { kind: TransferKind::ActionResult, .. }) => {
+                ActionResult::LongActionAborted
+            }
+            _ => ActionResult::NoLongActionInProgress,
+        };
+        Ok(ActionResponse::Normal(ActionResponseNormal { invoke_id: request.invoke_id, result }))
+    }
+
+    fn send_action_block(
+        &self,
+        session: &mut ServerSession,
+        mut transfer: BlockTransfer,
+    ) -> ActionResponse {
+        let (raw_data, last_block) = transfer.next_block(
+            session.client_max_receive_pdu_size,
+            ACTION_BLOCK_OVERHEAD + session.ciphering_overhead(),
+        );
+        let response = ActionResponse::WithPBlock(ActionResponseWithPBlock {
+            invoke_id: transfer.invoke_id,
+            pblock: DataBlockSa { last_block, block_number: transfer.block_number, raw_data },
+        });
+        if !last_block {
+            session.block_transfer = Some(transfer);
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::{ActionRequest, ActionRequestNormal};
+    use crate::association::{AareApdu, AarqApdu, InitiateRequest};
+    use crate::cosem::CosemObject;
+    use crate::get::{GetRequest, GetRequestNormal};
+    use crate::server::ServerSettings;
+    use crate::set::SetRequest;
+
+    const ECHO: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 1, e: 0, f: 255 };
+
+    /// Object with a writable value (attribute 2) and a method returning its parameters.
+    struct Echo(Data);
+
+    impl CosemObject for Echo {
+        fn class_id(&self) -> u16 {
+            1
+        }
+
+        fn version(&self) -> u8 {
+            0
+        }
+
+        fn logical_name(&self) -> &ObisCode {
+            &ECHO
+        }
+
+        fn get_attribute(&self, attribute_id: i8) -> Result<Data, DataAccessResult> {
+            match attribute_id {
+                2 => Ok(self.0.clone()),
+                _ => Err(DataAccessResult::ObjectUndefined),
+            }
+        }
+
+        fn set_attribute(&mut self, attribute_id: i8, value: Data) -> Result<(), DataAccessResult> {
+            match attribute_id {
+                2 => {
+                    self.0 = value;
+                    Ok(())
+                }
+                _ => Err(DataAccessResult::ReadWriteDenied),
+            }
+        }
+
+        fn invoke_method(
+            &mut self,
+            method_id: i8,
+            params: Option<Data>,
+        ) -> Result<Option<Data>, ActionResult> {
+            match method_id {
+                1 => Ok(params),
+                _ => Err(ActionResult::ObjectUndefined),
+            }
+        }
+    }
+
+    fn long_value() -> Data {
+        Data::OctetString((0..200).collect())
+    }
+
+    /// Associates with a client maximum receive PDU size of 128 bytes.
+    fn associated(
+        settings: ServerSettings,
+        conformance: Conformance,
+    ) -> (DlmsServer, ServerSession) {
+        let mut server = DlmsServer::new(settings).with_object(Echo(long_value()));
+        let mut session = ServerSession::new();
+        let mut aarq = AarqApdu::new_simple_ln(128);
+        aarq.user_information = Some(InitiateRequest::new(conformance, 128));
+        let aare = server.handle(&mut session, &aarq.encode()).unwrap();
+        assert!(AareApdu::parse(&aare).unwrap().1.is_accepted());
+        (server, session)
+    }
+
+    fn get(
+        server: &mut DlmsServer,
+        session: &mut ServerSession,
+        request: GetRequest,
+    ) -> GetResponse {
+        let response = server.handle(session, &request.encode()).unwrap();
+        assert!(response.len() <= 128);
+        GetResponse::parse(&response).unwrap().1
+    }
+
+    fn next_block(block_number: u32) -> GetRequest {
+        GetRequest::NextDataBlock(GetRequestNext { invoke_id: 0xC1, block_number })
+    }
+
+    fn get_echo() -> GetRequest {
+        GetRequest::Normal(GetRequestNormal {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id: ECHO,
+            attribute_id: 2,
+            access_selection: None,
+        })
+    }
+
+    fn block_error(result: DataAccessResult, block_number: u32) -> GetResponse {
+        GetResponse::WithDataBlock(GetResponseWithDataBlock {
+            invoke_id: 0xC1,
+            last_block: true,
+            block_number,
+            result: GetDataBlockResult::DataAccessError(result),
+        })
+    }
+
+    #[test]
+    fn test_get_with_data_blocks() {
+        let (mut server, mut session) =
+            associated(ServerSettings::default(), Conformance::TYPICAL_CLIENT_LN);
+
+        let mut received = Vec::new();
+        let mut request = get_echo();
+        for expected_block in 1.. {
+            let GetResponse::WithDataBlock(response) = get(&mut server, &mut session, request)
+            else {
+                panic!("expected a data block");
+            };
+            assert_eq!(response.block_number, expected_block);
+            let GetDataBlockResult::RawData(data) = response.result else {
+                panic!("expected raw data");
+            };
+            received.extend(data);
+            if response.last_block {
+                break;
+            }
+            request = next_block(response.block_number);
+        }
+        assert_eq!(received, long_value().encode());
+        assert!(!session.is_block_transfer_pending());
+
+        assert_eq!(
+            get(&mut server, &mut session, next_block(3)),
+            block_error(DataAccessResult::NoLongGetInProgress, 3)
+        );
+    }
+
+    #[test]
+    fn test_get_block_transfer_aborted() {
+        let (mut server, mut session) =
+            associated(ServerSettings::default(), Conformance::TYPICAL_CLIENT_LN);
+
+        get(&mut server, &mut session, get_echo());
+        assert!(session.is_block_transfer_pending());
+        assert_eq!(
+            get(&mut server, &mut session, next_block(2)),
+            block_error(DataAccessResult::LongGetAborted, 2)
+        );
+        assert!(!session.is_block_transfer_pending());
+
+        // Abandoned transfers expire
+        let settings =
+            ServerSettings { block_transfer_timeout: Duration::ZERO, ..ServerSettings::default() };
+        let (mut server, mut session) = associated(settings, Conformance::TYPICAL_CLIENT_LN);
+        get(&mut server, &mut session, get_echo());
+        assert_eq!(
+            get(&mut server, &mut session, next_block(1)),
+            block_error(DataAccessResult::NoLongGetInProgress, 1)
+        );
+    }
+
+    #[test]
+    fn test_response_too_long_without_block_transfer() {
+        let conformance = Conformance::GET.union(Conformance::ACTION);
+        let (mut server, mut session) = associated(ServerSettings::default(), conformance);
+
+        assert_eq!(server.handle(&mut session, &get_echo().encode()), Err(ServerError::PduTooLong));
+        assert!(server.handle(&mut session, &next_block(1).encode()).is_err());
+
+        // Requests longer than the server accepts
+        let settings = ServerSettings { max_pdu_size: 16, ..ServerSettings::default() };
+        let (mut server, mut session) = associated(settings, Conformance::TYPICAL_CLIENT_LN);
+        let request = ActionRequest::Normal(ActionRequestNormal {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id: ECHO,
+            method_id: 1,
+            method_invocation_parameters: Some(Data::OctetString(vec![0; 16])),
+        });
+        assert_eq!(server.handle(&mut session, &request.encode()), Err(ServerError::PduTooLong));
+    }
+
+    #[test]
+    fn test_set_with_data_blocks() {
+        let (mut server, mut session) =
+            associated(ServerSettings::default(), Conformance::TYPICAL_CLIENT_LN);
+        let value = Data::Utf8String("block transfer".into());
+        let encoded = value.encode();
+        let (first, second) = encoded.split_at(8);
+
+        let request = SetRequest::FirstDataBlock(SetRequestFirstDataBlock {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id: ECHO,
+            attribute_id: 2,
+            access_selection: None,
+            last_block: false,
+            block_number: 1,
+            raw_data: first.to_vec(),
+        });
+        let response = server.handle_set_request(&mut session, request).unwrap();
+        assert_eq!(
+            response,
+            SetResponse::DataBlock(SetResponseDataBlock { invoke_id: 0xC1, block_number: 1 })
+        );
+
+        let next = |last_block, block_number| {
+            SetRequest::WithDataBlock(SetRequestWithDataBlock {
+                invoke_id: 0xC1,
+                last_block,
+                block_number,
+                raw_data: second.to_vec(),
+            })
+        };
+        let last_response = |result, block_number| {
+            SetResponse::LastDataBlock(SetResponseLastDataBlock {
+                invoke_id: 0xC1,
+                result,
+                block_number,
+            })
+        };
+        assert_eq!(
+            server.handle_set_request(&mut session, next(true, 2)),
+            Ok(last_response(DataAccessResult::Success, 2))
+        );
+        assert_eq!(server.objects().get(1, &ECHO).unwrap().get_attribute(2), Ok(value));
+
+        assert_eq!(
+            server.handle_set_request(&mut session, next(true, 3)),
+            Ok(last_response(DataAccessResult::NoLongSetInProgress, 3))
+        );
+    }
+
+    #[test]
+    fn test_set_with_data_blocks_unsupported_type() {
+        let (mut server, mut session) =
+            associated(ServerSettings::default(), Conformance::TYPICAL_CLIENT_LN);
+
+        // A visible-string value, which has no Data variant
+        let request = SetRequest::FirstDataBlock(SetRequestFirstDataBlock {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id: ECHO,
+            attribute_id: 2,
+            access_selection: None,
+            last_block: true,
+            block_number: 1,
+            raw_data: vec![0x0A, 0x01, b'A'],
+        });
+        assert_eq!(
+            server.handle_set_request(&mut session, request),
+            Ok(SetResponse::LastDataBlock(SetResponseLastDataBlock {
+                invoke_id: 0xC1,
+                result: DataAccessResult::TypeUnmatched,
+                block_number: 1,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_block_transfer_size_limit() {
+        let settings = ServerSettings { max_block_transfer_size: 64, ..ServerSettings::default() };
+        let (mut server, mut session) = associated(settings, Conformance::TYPICAL_CLIENT_LN);
+
+        let request = SetRequest::FirstDataBlock(SetRequestFirstDataBlock {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id: ECHO,
+            attribute_id: 2,
+            access_selection: None,
+            last_block: false,
+            block_number: 1,
+            raw_data: vec![0; 16],
+        });
+        server.handle_set_request(&mut session, request).unwrap();
+        let next = |block_number| {
+            SetRequest::WithDataBlock(SetRequestWithDataBlock {
+                invoke_id: 0xC1,
+                last_block: false,
+                block_number,
+                raw_data: vec![0; 16],
+            })
+        };
+        for block_number in 2..=4 {
+            assert_eq!(
+                server.handle_set_request(&mut session, next(block_number)),
+                Ok(SetResponse::DataBlock(SetResponseDataBlock { invoke_id: 0xC1, block_number }))
+            );
+        }
+        assert_eq!(
+            server.handle_set_request(&mut session, next(5)),
+            Ok(SetResponse::LastDataBlock(SetResponseLastDataBlock {
+                invoke_id: 0xC1,
+                result: DataAccessResult::LongSetAborted,
+                block_number: 5,
+            }))
+        );
+        assert!(!session.is_block_transfer_pending());
+
+        let pblock = |last_block, block_number, length| DataBlockSa {
+            last_block,
+            block_number,
+            raw_data: vec![0; length],
+        };
+        let request = ActionRequest::WithFirstPBlock(ActionRequestWithFirstPBlock {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id: ECHO,
+            method_id: 1,
+            pblock: pblock(false, 1, 48),
+        });
+        server.handle_action_request(&mut session, request).unwrap();
+        let request = ActionRequest::WithPBlock(ActionRequestWithPBlock {
+            invoke_id: 0xC1,
+            pblock: pblock(true, 2, 32),
+        });
+        let aborted = ActionResponse::Normal(ActionResponseNormal {
+            invoke_id: 0xC1,
+            result: ActionResult::LongActionAborted,
+        });
+        assert_eq!(server.handle_action_request(&mut session, request), Ok(aborted.clone()));
+        assert!(!session.is_block_transfer_pending());
+
+        // A first block over the limit is refused outright
+        let request = ActionRequest::WithFirstPBlock(ActionRequestWithFirstPBlock {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id: ECHO,
+            method_id: 1,
+            pblock: pblock(false, 1, 65),
+        });
+        assert_eq!(server.handle_action_request(&mut session, request), Ok(aborted));
+        assert!(!session.is_block_transfer_pending());
+    }
+
+    #[test]
+    fn test_action_with_pblocks() {
+        let (mut server, mut session) =
+            associated(ServerSettings::default(), Conformance::TYPICAL_CLIENT_LN);
+        let parameters = long_value().encode();
+        let (first, second) = parameters.split_at(100);
+
+        let request = ActionRequest::WithFirstPBlock(ActionRequestWithFirstPBlock {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id: ECHO,
+            method_id: 1,
+            pblock: DataBlockSa { last_block: false, block_number: 1, raw_data: first.to_vec() },
+        });
+        assert_eq!(
+            server.handle_action_request(&mut session, request),
+            Ok(ActionResponse::NextPBlock(ActionResponseNextPBlock {
+                invoke_id: 0xC1,
+                block_number: 1
+            }))
+        );
+
+        // The echoed parameters do not fit either and come back in blocks
+        let mut request = ActionRequest::WithPBlock(ActionRequestWithPBlock {
+            invoke_id: 0xC1,
+            pblock: DataBlockSa { last_block: true, block_number: 2, raw_data: second.to_vec() },
+        });
+        let mut received = Vec::new();
+        loop {
+            let response = server.handle(&mut session, &request.encode()).unwrap();
+            assert!(response.len() <= 128);
+            let ActionResponse::WithPBlock(response) = ActionResponse::parse(&response).unwrap().1
+            else {
+                panic!("expected a pblock");
+            };
+            received.extend(response.pblock.raw_data);
+            if response.pblock.last_block {
+                break;
+            }
+            request = ActionRequest::NextPBlock(ActionRequestNextPBlock {
+                invoke_id: 0xC1,
+                block_number: response.pblock.block_number,
+            });
+        }
+        assert_eq!(received, parameters);
+
+        let request =
+            ActionRequest::NextPBlock(ActionRequestNextPBlock { invoke_id: 0xC1, block_number: 9 });
+        assert_eq!(
+            server.handle_action_request(&mut session, request),
+            Ok(ActionResponse::Normal(ActionResponseNormal {
+                invoke_id: 0xC1,
+                result: ActionResult::NoLongActionInProgress
+            }))
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Persistence of the server state.
+//!
+//! A server snapshot holds the object model (see
+//! [`cosem::persistence`](crate::cosem::persistence)) followed by the
+//! invocation counters: the server's own counter and the last counter
+//! accepted per client system title. Restoring the counters keeps ciphered
+//! responses from reusing counter values and replayed requests from being
+//! accepted after a restart.
+
+use alloc::vec::Vec;
+
+use super::DlmsServer;
+use crate::cosem::persistence::{self, PersistenceError, Record};
+
+impl DlmsServer {
+    /// Encodes a snapshot of the object model and the invocation counters.
+    pub fn snapshot(&self) -> Vec<u8> {
+        let mut records = persistence::records(&self.objects);
+        records.push(Record::InvocationCounter { system_title: None, value: self.counters.server });
+        records.extend(self.counters.clients.iter().map(|(title, value)| {
+            Record::InvocationCounter { system_title: Some(*title), value: *value }
+        }));
+        persistence::encode_records(&records)
+    }
+
+    /// Restores a snapshot taken with [`snapshot`](Self::snapshot), including
+    /// entries appended to it afterwards.
+    pub fn restore(&mut self, snapshot: &[u8]) -> Result<(), PersistenceError> {
+        let (counters, records): (Vec<_>, Vec<_>) = persistence::parse_records(snapshot)?
+            .into_iter()
+            .partition(|record| matches!(record, Record::InvocationCounter { .. }));
+        persistence::apply(&mut self.objects, records)?;
+        for record in counters {
+            match record {
+                Record::InvocationCounter { system_title: None, value } => {
+                    self.counters.server = value;
+                }
+                Record::InvocationCounter { system_title: Some(title), value } => {
+                    self.counters.clients.insert(title, value);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::data::DataObject;
+    use crate::data::Data;
+    use crate::obis_code::ObisCode;
+    use crate::server::{ServerSecurity, ServerSettings};
+
+    const VALUE: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 1, e: 0, f: 255 };
+
+    fn server(invocation_counter: u32) -> DlmsServer {
+        let security = ServerSecurity::new(*b"SRV00001", [0x11; 16])
+            .with_invocation_counter(invocation_counter);
+        DlmsServer::new(ServerSettings { security: Some(security), ..Default::default() })
+            .with_object(DataObject::new(VALUE, Data::Unsigned(1)))
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let mut meter = server(100);
+        meter.set_client_invocation_counter(*b"CLI00001", 7);
+        meter
+            .objects_mut()
+            .get_mut(1, &VALUE)
+            .unwrap()
+            .set_attribute(2, Data::Unsigned(2))
+            .unwrap();
+
+        let mut restored = server(0);
+        restored.restore(&meter.snapshot()).unwrap();
+        assert_eq!(restored.invocation_counter(), 100);
+        assert_eq!(restored.client_invocation_counter(b"CLI00001"), Some(7));
+        assert_eq!(
+            restored.objects().get(1, &VALUE).unwrap().get_attribute(2),
+            Ok(Data::Unsigned(2))
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Physical devices hosting several logical devices.
+//!
+//! A physical meter hosts one [`DlmsServer`] per logical device, each with
+//! its own object model and associations. Logical devices are addressed by
+//! their SAP: the HDLC upper address or the wrapper destination wPort. The
+//! management logical device (SAP 1) holds the SAP Assignment object
+//! (class 17) listing the logical devices by the name of their Logical
+//! Device Name object (0.0.42.0.0.255).
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use super::{DlmsServer, ServerError, ServerSession};
+use crate::cosem::sap_assignment::SapAssignment;
+use crate::data::Data;
+use crate::obis_code::ObisCode;
+
+/// SAP of the management logical device.
+pub const MANAGEMENT_LOGICAL_DEVICE_SAP: u16 = 1;
+
+/// Logical name of the Logical Device Name object (class 1).
+const LOGICAL_DEVICE_NAME: ObisCode = ObisCode { a: 0, b: 0, c: 42, d: 0, e: 0, f: 255 };
+
+/// Logical devices of a physical device, by SAP.
+#[derive(Debug, Default)]
+pub struct PhysicalDevice {
+    logical_devices: BTreeMap<u16, DlmsServer>,
+}
+
+impl PhysicalDevice {
+    /// Creates a physical device without logical devices.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a logical device at a SAP (builder style).
+    pub fn with_logical_device(mut self, sap: u16, server: DlmsServer) -> Self {
+        self.insert(sap, server);
+        self
+    }
+
+    /// Adds a logical device at a SAP, returning the one it replaces.
+    ///
+    /// The SAP Assignment object of the management logical device is
+    /// refreshed from the Logical Device Name objects.
+    pub fn insert(&mut self, sap: u16, server: DlmsServer) -> Option<DlmsServer> {
+        let replaced = self.logical_devices.insert(sap, server);
+        self.refresh_sap_assignment();
+        replaced
+    }
+
+    /// Returns the logical device at a SAP.
+    pub fn logical_device(&self, sap: u16) -> Option<&DlmsServer> {
+        self.logical_devices.get(&sap)
+    }
+
+    /// Returns the logical device at a SAP, mutably.
+    pub fn logical_device_mut(&mut self, sap: u16) -> Option<&mut DlmsServer> {
+        self.logical_devices.get_mut(&sap)
+    }
+
+    /// Returns the SAPs of the logical devices, in ascending order.
+    pub fn saps(&self) -> impl Iterator<Item = u16> + '_ {
+        self.logical_devices.keys().copied()
+    }
+
+    /// Opens the session of a client SAP with the logical device at
+    /// `server_sap`, or returns `None` if there is no such logical device.
+    pub fn open_session(&self, client_sap: u16, server_sap: u16) -> Option<ServerSession> {
+        let mut session = self.logical_devices.get(&server_sap)?.open_session(client_sap);
+        session.server_sap = Some(server_sap);
+        Some(session)
+    }
+
+    /// Answers an encoded request APDU with the logical device of the session.
+    pub fn handle(
+        &mut self,
+        session: &mut ServerSession,
+        request: &[u8],
+    ) -> Result<Vec<u8>, ServerError> {
+        let sap = session.server_sap.unwrap_or(MANAGEMENT_LOGICAL_DEVICE_SAP);
+        let server =
+            self.logical_devices.get_mut(&sap).ok_or(ServerError::UnknownLogicalDevice(sap))?;
+        server.handle(session, request)
+    }
+
+    /// Rebuilds the SAP Assignment object of the management logical device.
+    fn refresh_sap_assignment(&mut self) {
+        let sap_assignment = self
+            .logical_devices
+            .iter()
+            .filter_map(|(sap, server)| {
+                match server.objects().lookup(1, &LOGICAL_DEVICE_NAME).ok()?.get_attribute(2) {
+                    Ok(Data::OctetString(name)) => Some((*sap, name)),
+                    _ => None,
+                }
+            })
+            .fold(SapAssignment::new(), |sap_assignment, (sap, name)| {
+                sap_assignment.with_logical_device(sap, name)
+            });
+        if let Some(management) = self.logical_devices.get_mut(&MANAGEMENT_LOGICAL_DEVICE_SAP) {
+            management.objects_mut().insert(Box::new(sap_assignment));
+        }
+    }
+}
+
+impl ServerSession {
+    /// Returns the SAP of the logical device the session is bound to, if any.
+    pub fn server_sap(&self) -> Option<u16> {
+        self.server_sap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::association::{AareApdu, AarqApdu};
+    use crate::cosem::data::DataObject;
+    use crate::cosem::sap_assignment::{SAP_ASSIGNMENT, SapAssignmentElement};
+    use crate::get::{GetDataResult, GetRequest, GetRequestNormal, GetResponse};
+    use crate::server::{PUBLIC_CLIENT_SAP, ServerSettings};
+
+    fn logical_device(name: &[u8]) -> DlmsServer {
+        DlmsServer::new(ServerSettings::default())
+            .with_object(DataObject::new(LOGICAL_DEVICE_NAME, Data::OctetString(name.to_vec())))
+    }
+
+    fn get_name(device: &mut PhysicalDevice, session: &mut ServerSession) -> GetDataResult {
+        let request = GetRequest::Normal(GetRequestNormal {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id: LOGICAL_DEVICE_NAME,
+            attribute_id: 2,
+            access_selection: None,
+        });
+        let response = device.handle(session, &request.encode()).unwrap();
+        match GetResponse::parse(&response).unwrap().1 {
+            GetResponse::Normal(response) => response.result,
+            response => panic!("unexpected response {:?}", response),
+        }
+    }
+
+    #[test]
+    fn test_routing_by_sap() {
+        let mut device = PhysicalDevice::new()
+            .with_logical_device(MANAGEMENT_LOGICAL_DEVICE_SAP, logical_device(b"MGMT"))
+            .with_logical_device(17, logical_device(b"LD17"));
+        assert_eq!(device.saps().collect::<Vec<_>>(), vec![1, 17]);
+
+        for (sap, name) in [(1, b"MGMT"), (17, b"LD17")] {
+            let mut session = device.open_session(PUBLIC_CLIENT_SAP, sap).unwrap();
+            let aarq = AarqApdu::new_simple_ln(512).encode();
+            let aare = device.handle(&mut session, &aarq).unwrap();
+            assert!(AareApdu::parse(&aare).unwrap().1.is_accepted());
+            assert_eq!(session.server_sap(), Some(sap));
+            assert_eq!(
+                get_name(&mut device, &mut session),
+                GetDataResult::Data(Data::OctetString(name.to_vec()))
+            );
+        }
+
+        assert!(device.open_session(PUBLIC_CLIENT_SAP, 2).is_none());
+        let mut session = device.open_session(PUBLIC_CLIENT_SAP, 17).unwrap();
+        session.server_sap = Some(2);
+        assert_eq!(
+            device.handle(&mut session, &AarqApdu::new_simple_ln(512).encode()),
+            Err(ServerError::UnknownLogicalDevice(2))
+        );
+    }
+
+    #[test]
+    fn test_sap_assignment() {
+        let mut device = PhysicalDevice::new()
+            .with_logical_device(17, logical_device(b"LD17"))
+            .with_logical_device(MANAGEMENT_LOGICAL_DEVICE_SAP, logical_device(b"MGMT"));
+        device.insert(18, logical_device(b"LD18"));
+
+        let management = device.logical_device(MANAGEMENT_LOGICAL_DEVICE_SAP).unwrap();
+        let list = management.objects().lookup(17, &SAP_ASSIGNMENT).unwrap().get_attribute(2);
+        let element =
+            |sap, name: &[u8]| SapAssignmentElement { sap, logical_device_name: name.to_vec() };
+        assert_eq!(
+            SapAssignment::parse_assignment_list(&list.unwrap()),
+            Ok(vec![element(1, b"MGMT"), element(17, b"LD17"), element(18, b"LD18")])
+        );
+        assert!(device.logical_device(17).unwrap().objects().lookup(17, &SAP_ASSIGNMENT).is_err());
+    }
+}
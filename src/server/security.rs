@@ -0,0 +1,922 @@
+//! Ciphered APDUs (general-glo-ciphering and general-ded-ciphering).
+//!
+//! With [`ServerSettings::security`](super::ServerSettings::security) set, the
+//! server accepts associations with the ciphered LN application context. The
+//! AARQ's calling-AP-title is the client system title; the AARE carries the
+//! server's. The dedicated key of the InitiateRequest, if any, is kept for
+//! DED-ciphered APDUs of the association.
+//!
+//! A ciphered request is checked before it is answered:
+//! - the system title must be the client's and the security suite AES-128;
+//! - the security control must ask for encryption, authentication or both;
+//! - the invocation counter must be greater than the last one accepted from
+//!   that client system title, across associations;
+//! - an authenticated request must carry a valid 12-byte GCM tag, computed
+//!   with the [authentication key](ServerSecurity::authentication_key) as
+//!   additional data;
+//! - the plaintext must be a request of the same service.
+//!
+//! Only then is the client's invocation counter advanced. The response is
+//! ciphered with the same key and security control, the server system title
+//! and the server's own invocation counter. Attributes
+//! and methods with an authenticated access mode in the current association
+//! (0.0.40.0.0.255) must be requested with authentication, otherwise they
+//! are denied.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use aes::Aes128;
+use aes_gcm::aead::consts::U12;
+use aes_gcm::aead::{AeadInPlace, KeyInit};
+use aes_gcm::{AesGcm, Nonce, Tag};
+use cipher::Key;
+
+use super::{
+    ACTION_REQUEST_TAG, DlmsServer, GET_REQUEST_TAG, SET_REQUEST_TAG, ServerError, ServerSession,
+    ServerSettings,
+};
+use crate::cosem::AttributeAccess;
+use crate::cosem::association_ln::AccessRights;
+use crate::obis_code::ObisCode;
+use crate::{GeneralDedCiphering, GeneralGloCiphering, SecurityControl, SecuritySuite};
+
+/// APDU tag of glo-get-request
+const GLO_GET_REQUEST_TAG: u8 = 0xC8;
+
+/// APDU tag of glo-set-request
+const GLO_SET_REQUEST_TAG: u8 = 0xC9;
+
+/// APDU tag of glo-action-request
+const GLO_ACTION_REQUEST_TAG: u8 = 0xCB;
+
+/// APDU tag of ded-get-request
+const DED_GET_REQUEST_TAG: u8 = 0xD0;
+
+/// APDU tag of ded-set-request
+const DED_SET_REQUEST_TAG: u8 = 0xD1;
+
+/// APDU tag of ded-action-request
+const DED_ACTION_REQUEST_TAG: u8 = 0xD3;
+
+/// Header added by ciphering: tag, system title with its length, length
+/// (up to 3 bytes), security control and invocation counter.
+const CIPHERING_OVERHEAD: usize = 18;
+
+/// Length of the GCM tag of authenticated APDUs.
+const AUTHENTICATION_TAG_SIZE: usize = 12;
+
+/// AES-128-GCM with the 12-byte tag of security suites 0 and 1.
+type Aes128Gcm12 = AesGcm<Aes128, U12, U12>;
+
+/// Which data requests must be ciphered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SecurityPolicy {
+    /// Plaintext and ciphered associations are accepted; only attributes and
+    /// methods with an authenticated access mode require an authenticated
+    /// request.
+    #[default]
+    AccessRights,
+    /// Only ciphered associations are accepted, and every GET, SET and
+    /// ACTION request must be ciphered.
+    AllRequests,
+}
+
+/// Ciphering settings of a server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerSecurity {
+    /// System title of the server, sent in the AARE and used for responses.
+    pub system_title: [u8; 8],
+    /// Global unicast encryption key (AES-128).
+    pub global_key: [u8; 16],
+    /// Authentication key (AK), part of the additional data of the GCM tag.
+    /// Without it, authenticated requests are rejected.
+    /// Default: `None`.
+    pub authentication_key: Option<[u8; 16]>,
+    /// Invocation counter of the first ciphered response.
+    /// Default: 0.
+    pub invocation_counter: u32,
+    /// Which data requests must be ciphered.
+    /// Default: [`SecurityPolicy::AccessRights`].
+    pub policy: SecurityPolicy,
+}
+
+impl ServerSecurity {
+    /// Creates ciphering settings with a system title and global unicast key.
+    pub fn new(system_title: [u8; 8], global_key: [u8; 16]) -> Self {
+        Self {
+            system_title,
+            global_key,
+            authentication_key: None,
+            invocation_counter: 0,
+            policy: SecurityPolicy::default(),
+        }
+    }
+
+    /// Sets the authentication key, needed to check and add GCM tags.
+    pub fn with_authentication_key(mut self, authentication_key: [u8; 16]) -> Self {
+        self.authentication_key = Some(authentication_key);
+        self
+    }
+
+    /// Sets the invocation counter of the first ciphered response.
+    pub fn with_invocation_counter(mut self, invocation_counter: u32) -> Self {
+        self.invocation_counter = invocation_counter;
+        self
+    }
+
+    /// Sets which data requests must be ciphered.
+    pub fn with_policy(mut self, policy: SecurityPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+/// Invocation counters of the server and of its clients.
+#[derive(Debug, Clone, Default)]
+pub(super) struct InvocationCounters {
+    /// Counter of the next ciphered response.
+    pub(super) server: u32,
+    /// Last counter accepted per client system title.
+    pub(super) clients: BTreeMap<[u8; 8], u32>,
+}
+
+impl InvocationCounters {
+    pub(super) fn new(settings: &ServerSettings) -> Self {
+        let server = settings.security.as_ref().map_or(0, |security| security.invocation_counter);
+        Self { server, clients: BTreeMap::new() }
+    }
+}
+
+/// Ciphering context of an association.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct SessionCiphering {
+    /// Client system title (calling-AP-title of the AARQ).
+    pub(super) client_system_title: [u8; 8],
+    /// Dedicated key proposed in the InitiateRequest.
+    pub(super) dedicated_key: Option<[u8; 16]>,
+}
+
+impl ServerSession {
+    /// Returns `true` if the association uses the ciphered application context.
+    pub fn is_ciphered(&self) -> bool {
+        self.ciphering.is_some()
+    }
+
+    /// Returns the client system title of a ciphered association.
+    pub fn client_system_title(&self) -> Option<[u8; 8]> {
+        self.ciphering.as_ref().map(|ciphering| ciphering.client_system_title)
+    }
+
+    /// Returns `true` if the request being answered was ciphered with authentication.
+    ///
+    /// The security control is only kept once the request's tag was verified.
+    fn is_request_authenticated(&self) -> bool {
+        self.request_security.is_some_and(|security| security.authentication())
+    }
+
+    /// Returns the bytes ciphering adds to the response being built.
+    pub(super) fn ciphering_overhead(&self) -> usize {
+        match self.request_security {
+            Some(security) if security.authentication() => {
+                CIPHERING_OVERHEAD + AUTHENTICATION_TAG_SIZE
+            }
+            Some(_) => CIPHERING_OVERHEAD,
+            None => 0,
+        }
+    }
+}
+
+/// Initialization vector of AES-GCM: system title and invocation counter.
+fn initialization_vector(system_title: &[u8; 8], invocation_counter: u32) -> Nonce<U12> {
+    let mut iv = Nonce::<U12>::default();
+    iv[..8].copy_from_slice(system_title);
+    iv[8..].copy_from_slice(&invocation_counter.to_be_bytes());
+    iv
+}
+
+/// Additional authenticated data: security control and authentication key,
+/// followed by the plaintext if it is not encrypted.
+fn additional_data(
+    security_control: SecurityControl,
+    authentication_key: &[u8; 16],
+    plaintext: &[u8],
+) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(1 + authentication_key.len() + plaintext.len());
+    aad.push(security_control.encode());
+    aad.extend_from_slice(authentication_key);
+    aad.extend_from_slice(plaintext);
+    aad
+}
+
+/// Ciphers an APDU as its security control asks; authenticated APDUs end
+/// with their tag.
+fn protect(
+    plaintext: &[u8],
+    key: &[u8; 16],
+    authentication_key: Option<&[u8; 16]>,
+    security_control: SecurityControl,
+    system_title: &[u8; 8],
+    invocation_counter: u32,
+) -> Result<Vec<u8>, ServerError> {
+    let cipher = Aes128Gcm12::new(Key::<Aes128>::from_slice(key));
+    let iv = initialization_vector(system_title, invocation_counter);
+    let mut payload = plaintext.to_vec();
+    if !security_control.authentication() {
+        // Encryption only: the tag is dropped
+        return match security_control.encryption() {
+            true => cipher
+                .encrypt_in_place_detached(&iv, &[], &mut payload)
+                .map(|_| payload)
+                .map_err(|_| ServerError::DecipheringError),
+            false => Err(ServerError::DecipheringError),
+        };
+    }
+
+    let authentication_key = authentication_key.ok_or(ServerError::DecipheringError)?;
+    let tag = match security_control.encryption() {
+        true => {
+            let aad = additional_data(security_control, authentication_key, &[]);
+            cipher.encrypt_in_place_detached(&iv, &aad, &mut payload)
+        }
+        false => {
+            let aad = additional_data(security_control, authentication_key, plaintext);
+            cipher.encrypt_in_place_detached(&iv, &aad, &mut [])
+        }
+    }
+    .map_err(|_| ServerError::DecipheringError)?;
+    payload.extend_from_slice(&tag);
+    Ok(payload)
+}
+
+/// Checks the tag of an authenticated APDU and deciphers it.
+fn unprotect(
+    mut payload: Vec<u8>,
+    key: &[u8; 16],
+    authentication_key: Option<&[u8; 16]>,
+    security_control: SecurityControl,
+    system_title: &[u8; 8],
+    invocation_counter: u32,
+) -> Result<Vec<u8>, ServerError> {
+    let cipher = Aes128Gcm12::new(Key::<Aes128>::from_slice(key));
+    let iv = initialization_vector(system_title, invocation_counter);
+    if !security_control.authentication() {
+        // Encryption only: GCM without a tag is AES-CTR, so ciphering deciphers
+        return match security_control.encryption() {
+            true => cipher
+                .encrypt_in_place_detached(&iv, &[], &mut payload)
+                .map(|_| payload)
+                .map_err(|_| ServerError::DecipheringError),
+            false => Err(ServerError::DecipheringError),
+        };
+    }
+
+    let authentication_key = authentication_key.ok_or(ServerError::DecipheringError)?;
+    let tag_start =
+        payload.len().checked_sub(AUTHENTICATION_TAG_SIZE).ok_or(ServerError::DecipheringError)?;
+    let tag = Tag::<U12>::clone_from_slice(&payload[tag_start..]);
+    payload.truncate(tag_start);
+    match security_control.encryption() {
+        true => {
+            let aad = additional_data(security_control, authentication_key, &[]);
+            cipher.decrypt_in_place_detached(&iv, &aad, &mut payload, &tag)
+        }
+        false => {
+            let aad = additional_data(security_control, authentication_key, &payload);
+            cipher.decrypt_in_place_detached(&iv, &aad, &mut [], &tag)
+        }
+    }
+    .map_err(|_| ServerError::DecipheringError)?;
+    Ok(payload)
+}
+
+/// Returns the plaintext request tag of a ciphered request tag, and whether
+/// it is DED-ciphered.
+pub(super) fn deciphered_tag(tag: u8) -> Option<(u8, bool)> {
+    match tag {
+        GLO_GET_REQUEST_TAG => Some((GET_REQUEST_TAG, false)),
+        GLO_SET_REQUEST_TAG => Some((SET_REQUEST_TAG, false)),
+        GLO_ACTION_REQUEST_TAG => Some((ACTION_REQUEST_TAG, false)),
+        DED_GET_REQUEST_TAG => Some((GET_REQUEST_TAG, true)),
+        DED_SET_REQUEST_TAG => Some((SET_REQUEST_TAG, true)),
+        DED_ACTION_REQUEST_TAG => Some((ACTION_REQUEST_TAG, true)),
+        _ => None,
+    }
+}
+
+impl DlmsServer {
+    /// Returns the invocation counter of the next ciphered response.
+    ///
+    /// Firmware persists it so that counters never repeat after a restart.
+    pub fn invocation_counter(&self) -> u32 {
+        self.counters.server
+    }
+
+    /// Returns the last invocation counter accepted from a client system title.
+    pub fn client_invocation_counter(&self, client_system_title: &[u8; 8]) -> Option<u32> {
+        self.counters.clients.get(client_system_title).copied()
+    }
+
+    /// Restores the last invocation counter accepted from a client system title.
+    pub fn set_client_invocation_counter(
+        &mut self,
+        client_system_title: [u8; 8],
+        invocation_counter: u32,
+    ) {
+        self.counters.clients.insert(client_system_title, invocation_counter);
+    }
+
+    /// Deciphers a GLO or DED request, answers it and ciphers the response.
+    pub(super) fn handle_ciphered(
+        &mut self,
+        session: &mut ServerSession,
+        request: &[u8],
+    ) -> Result<Vec<u8>, ServerError> {
+        let tag = request[0];
+        let (Some(security), Some((service_tag, dedicated))) =
+            (&self.settings.security, deciphered_tag(tag))
+        else {
+            return Err(ServerError::UnsupportedApdu(tag));
+        };
+        let Some(ciphering) = &session.ciphering else {
+            return Err(match session.associated {
+                true => ServerError::ServiceNotAllowed,
+                false => ServerError::NotAssociated,
+            });
+        };
+
+        let apdu = match dedicated {
+            false => GeneralGloCiphering::parse(&request[1..]).map(|(_, apdu)| apdu),
+            true => GeneralDedCiphering::parse(&request[1..]).map(|(_, apdu)| {
+                GeneralGloCiphering::new(
+                    apdu.system_title,
+                    apdu.security_control,
+                    apdu.invocation_counter,
+                    apdu.payload,
+                )
+            }),
+        }
+        .map_err(|_| ServerError::ParseError)?;
+
+        let key = match dedicated {
+            false => security.global_key,
+            true => ciphering.dedicated_key.ok_or(ServerError::DecipheringError)?,
+        };
+        let client_system_title = ciphering.client_system_title;
+        let security_control = apdu.security_control;
+        if apdu.system_title != client_system_title
+            || !matches!(security_control.suite(), Some(SecuritySuite::V0 | SecuritySuite::V1))
+            || !(security_control.encryption() || security_control.authentication())
+        {
+            return Err(ServerError::DecipheringError);
+        }
+        let invocation_counter = apdu.invocation_counter.ok_or(ServerError::DecipheringError)?;
+        if self
+            .client_invocation_counter(&client_system_title)
+            .is_some_and(|last| invocation_counter <= last)
+        {
+            return Err(ServerError::InvocationCounterError);
+        }
+
+        let plaintext = unprotect(
+            apdu.payload,
+            &key,
+            security.authentication_key.as_ref(),
+            security_control,
+            &client_system_title,
+            invocation_counter,
+        )?;
+        if plaintext.first() != Some(&service_tag) {
+            return Err(ServerError::DecipheringError);
+        }
+        self.set_client_invocation_counter(client_system_title, invocation_counter);
+
+        session.request_security = Some(security_control);
+        let response = self.dispatch(session, &plaintext);
+        session.request_security = None;
+        self.cipher_response(&response?, &key, security_control, dedicated)
+    }
+
+    /// Ciphers a GET, SET or ACTION response with the server system title.
+    fn cipher_response(
+        &mut self,
+        response: &[u8],
+        key: &[u8; 16],
+        security_control: SecurityControl,
+        dedicated: bool,
+    ) -> Result<Vec<u8>, ServerError> {
+        let Some(security) = &self.settings.security else {
+            return Err(ServerError::DecipheringError);
+        };
+        let invocation_counter = self.counters.server;
+        self.counters.server =
+            invocation_counter.checked_add(1).ok_or(ServerError::InvocationCounterError)?;
+
+        let system_title = security.system_title;
+        let payload = protect(
+            response,
+            key,
+            security.authentication_key.as_ref(),
+            security_control,
+            &system_title,
+            invocation_counter,
+        )?;
+        let apdu = GeneralGloCiphering::new(
+            system_title,
+            security_control,
+            Some(invocation_counter),
+            payload,
+        );
+
+        // GLO response tags are the plaintext tags + 0x08 (C4 → CC, ...),
+        // DED response tags + 0x10 (C4 → D4, ...).
+        let mut encoded = Vec::with_capacity(response.len() + CIPHERING_OVERHEAD);
+        match dedicated {
+            false => {
+                encoded.push(response[0] + 0x08);
+                encoded.extend_from_slice(&apdu.encode());
+            }
+            true => {
+                let apdu = GeneralDedCiphering::new(
+                    apdu.system_title,
+                    apdu.security_control,
+                    apdu.invocation_counter,
+                    apdu.payload,
+                );
+                encoded.push(response[0] + 0x10);
+                encoded.extend_from_slice(&apdu.encode());
+            }
+        }
+        Ok(encoded)
+    }
+
+    /// Checks that the request being answered may read or write an attribute.
+    ///
+    /// With ciphering configured, attributes whose access mode in the current
+    /// association is authenticated must be requested with authentication.
+    pub(super) fn attribute_protection_met(
+        &self,
+        session: &ServerSession,
+        class_id: u16,
+        instance_id: &ObisCode,
+        attribute_id: i8,
+        write: bool,
+    ) -> bool {
+        let required = match write {
+            true => AttributeAccess::AUTHENTICATED_WRITE,
+            false => AttributeAccess::AUTHENTICATED_READ,
+        };
+        session.is_request_authenticated()
+            || !self.access_rights(session, class_id, instance_id).is_some_and(|rights| {
+                rights
+                    .attribute_access(attribute_id)
+                    .is_some_and(|access| access.contains(required))
+            })
+    }
+
+    /// Checks that the request being answered may invoke a method.
+    ///
+    /// With ciphering configured, methods whose access mode in the current
+    /// association is authenticated must be invoked with authentication.
+    pub(super) fn method_protection_met(
+        &self,
+        session: &ServerSession,
+        class_id: u16,
+        instance_id: &ObisCode,
+        method_id: i8,
+    ) -> bool {
+        session.is_request_authenticated()
+            || !self.access_rights(session, class_id, instance_id).is_some_and(|rights| {
+                rights
+                    .method_access(method_id)
+                    .is_some_and(|access| access.requires_authentication())
+            })
+    }
+
+    /// Returns the access rights of an object in the current association.
+    ///
+    /// `None` without ciphering settings, association object or listing.
+    fn access_rights(
+        &self,
+        session: &ServerSession,
+        class_id: u16,
+        instance_id: &ObisCode,
+    ) -> Option<AccessRights> {
+        self.settings.security.as_ref()?;
+        let object_list = self.object_list(session)?;
+        self.find_element(session, object_list, class_id, instance_id)
+            .map(|element| element.access_rights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GloGetRequest;
+    use crate::association::{AareApdu, AarqApdu, MechanismName};
+    use crate::cosem::ObjectListElement;
+    use crate::cosem::association_ln::AssociationLn;
+    use crate::cosem::data::DataObject;
+    use crate::data::Data;
+    use crate::get::{DataAccessResult, GetDataResult, GetRequest, GetRequestNormal, GetResponse};
+
+    const SERVER_TITLE: [u8; 8] = *b"SRV00001";
+    const CLIENT_TITLE: [u8; 8] = *b"CLI00001";
+    const GLOBAL_KEY: [u8; 16] = [0x11; 16];
+    const DEDICATED_KEY: [u8; 16] = [0x22; 16];
+    const AUTHENTICATION_KEY: [u8; 16] = [0x33; 16];
+    const ENCRYPTED: u8 = 0x20;
+    const AUTHENTICATED: u8 = 0x10;
+    const PUBLIC: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 1, e: 0, f: 255 };
+    const PROTECTED: ObisCode = ObisCode { a: 0, b: 0, c: 96, d: 1, e: 1, f: 255 };
+
+    fn server(policy: SecurityPolicy) -> DlmsServer {
+        let security = ServerSecurity::new(SERVER_TITLE, GLOBAL_KEY)
+            .with_authentication_key(AUTHENTICATION_KEY)
+            .with_invocation_counter(100)
+            .with_policy(policy);
+        let association = AssociationLn::new().with_object(ObjectListElement {
+            class_id: 1,
+            version: 0,
+            logical_name: PROTECTED,
+            access_rights: AccessRights::new().with_attribute(
+                2,
+                AttributeAccess::READ_ONLY | AttributeAccess::AUTHENTICATED_READ,
+            ),
+        });
+        DlmsServer::new(ServerSettings { security: Some(security), ..Default::default() })
+            .with_object(DataObject::new(PUBLIC, Data::Unsigned(1)))
+            .with_object(DataObject::new(PROTECTED, Data::Unsigned(2)))
+            .with_object(association)
+    }
+
+    fn ciphered_aarq(dedicated_key: Option<[u8; 16]>) -> Vec<u8> {
+        let mut aarq = AarqApdu::new_with_ciphering(512, CLIENT_TITLE);
+        aarq.mechanism_name = Some(MechanismName::LowestLevelSecurity);
+        if let Some(initiate) = &mut aarq.user_information {
+            initiate.dedicated_key = dedicated_key.map(|key| key.to_vec());
+        }
+        aarq.encode()
+    }
+
+    fn associate(server: &mut DlmsServer, dedicated_key: Option<[u8; 16]>) -> ServerSession {
+        let mut session = ServerSession::new();
+        let aare = server.handle(&mut session, &ciphered_aarq(dedicated_key)).unwrap();
+        let aare = AareApdu::parse(&aare).unwrap().1;
+        assert!(aare.is_accepted());
+        assert_eq!(aare.responding_ap_title, Some(SERVER_TITLE.to_vec()));
+        assert!(session.is_ciphered());
+        assert_eq!(session.client_system_title(), Some(CLIENT_TITLE));
+        session
+    }
+
+    fn get(instance_id: ObisCode) -> Vec<u8> {
+        GetRequest::Normal(GetRequestNormal {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id,
+            attribute_id: 2,
+            access_selection: None,
+        })
+        .encode()
+    }
+
+    /// Ciphers a payload with the full 16-byte GCM tag, independently of
+    /// [`protect`], and keeps the first 12 tag bytes if authenticated.
+    fn gcm(
+        payload: &[u8],
+        key: &[u8; 16],
+        system_title: [u8; 8],
+        invocation_counter: u32,
+        security_control: u8,
+    ) -> (Vec<u8>, Vec<u8>) {
+        let cipher = aes_gcm::Aes128Gcm::new(Key::<Aes128>::from_slice(key));
+        let mut iv = [0u8; 12];
+        iv[..8].copy_from_slice(&system_title);
+        iv[8..].copy_from_slice(&invocation_counter.to_be_bytes());
+        let mut aad = vec![security_control];
+        aad.extend_from_slice(&AUTHENTICATION_KEY);
+        let mut ciphertext = payload.to_vec();
+        let tag = match security_control & ENCRYPTED != 0 {
+            true => cipher.encrypt_in_place_detached(&iv.into(), &aad, &mut ciphertext),
+            false => {
+                aad.extend_from_slice(payload);
+                cipher.encrypt_in_place_detached(&iv.into(), &aad, &mut [])
+            }
+        }
+        .unwrap();
+        match security_control & AUTHENTICATED != 0 {
+            true => (ciphertext, tag[..AUTHENTICATION_TAG_SIZE].to_vec()),
+            false => (ciphertext, Vec::new()),
+        }
+    }
+
+    /// A GLO (0xC8) or DED (0xD0) get-request as a client ciphers it.
+    fn ciphered_get(
+        tag: u8,
+        instance_id: ObisCode,
+        key: &[u8; 16],
+        invocation_counter: u32,
+        security_control: u8,
+    ) -> Vec<u8> {
+        let (mut payload, authentication_tag) =
+            gcm(&get(instance_id), key, CLIENT_TITLE, invocation_counter, security_control);
+        payload.extend_from_slice(&authentication_tag);
+        let security_control = SecurityControl::new(security_control);
+        let mut request = vec![tag];
+        match tag {
+            DED_GET_REQUEST_TAG => request.extend_from_slice(
+                &GeneralDedCiphering::new(
+                    CLIENT_TITLE,
+                    security_control,
+                    Some(invocation_counter),
+                    payload,
+                )
+                .encode(),
+            ),
+            _ => request.extend_from_slice(
+                &GeneralGloCiphering::new(
+                    CLIENT_TITLE,
+                    security_control,
+                    Some(invocation_counter),
+                    payload,
+                )
+                .encode(),
+            ),
+        }
+        request
+    }
+
+    fn glo_get(instance_id: ObisCode, invocation_counter: u32, authenticated: bool) -> Vec<u8> {
+        let security_control = match authenticated {
+            true => ENCRYPTED | AUTHENTICATED,
+            false => ENCRYPTED,
+        };
+        ciphered_get(
+            GLO_GET_REQUEST_TAG,
+            instance_id,
+            &GLOBAL_KEY,
+            invocation_counter,
+            security_control,
+        )
+    }
+
+    /// Checks the tag of a ciphered response and returns its invocation counter and result.
+    fn decipher(response: &[u8], tag: u8, key: &[u8; 16]) -> (u32, GetDataResult) {
+        assert_eq!(response[0], tag);
+        let apdu = match tag {
+            0xCC => GeneralGloCiphering::parse(&response[1..]).unwrap().1,
+            _ => {
+                let apdu = GeneralDedCiphering::parse(&response[1..]).unwrap().1;
+                GeneralGloCiphering::new(
+                    apdu.system_title,
+                    apdu.security_control,
+                    apdu.invocation_counter,
+                    apdu.payload,
+                )
+            }
+        };
+        assert_eq!(apdu.system_title, SERVER_TITLE);
+        let invocation_counter = apdu.invocation_counter.unwrap();
+        let security_control = apdu.security_control.encode();
+        let mut payload = apdu.payload;
+        let authentication_tag = match security_control & AUTHENTICATED != 0 {
+            true => payload.split_off(payload.len() - AUTHENTICATION_TAG_SIZE),
+            false => Vec::new(),
+        };
+        let plaintext = match security_control & ENCRYPTED != 0 {
+            true => gcm(&payload, key, SERVER_TITLE, invocation_counter, ENCRYPTED).0,
+            false => payload,
+        };
+        let (_, expected_tag) =
+            gcm(&plaintext, key, SERVER_TITLE, invocation_counter, security_control);
+        assert_eq!(authentication_tag, expected_tag);
+        match GetResponse::parse(&plaintext).unwrap().1 {
+            GetResponse::Normal(response) => (invocation_counter, response.result),
+            response => panic!("unexpected response {:?}", response),
+        }
+    }
+
+    #[test]
+    fn test_glo_get() {
+        let mut server = server(SecurityPolicy::AccessRights);
+        let mut session = associate(&mut server, None);
+
+        let response = server.handle(&mut session, &glo_get(PUBLIC, 1, true)).unwrap();
+        assert_eq!(
+            decipher(&response, 0xCC, &GLOBAL_KEY),
+            (100, GetDataResult::Data(Data::Unsigned(1)))
+        );
+        let response = server.handle(&mut session, &glo_get(PROTECTED, 2, true)).unwrap();
+        assert_eq!(
+            decipher(&response, 0xCC, &GLOBAL_KEY),
+            (101, GetDataResult::Data(Data::Unsigned(2)))
+        );
+
+        assert_eq!(server.invocation_counter(), 102);
+        assert_eq!(server.client_invocation_counter(&CLIENT_TITLE), Some(2));
+    }
+
+    #[test]
+    fn test_ded_get() {
+        let mut server = server(SecurityPolicy::AccessRights);
+        let mut session = associate(&mut server, Some(DEDICATED_KEY));
+
+        let request =
+            ciphered_get(DED_GET_REQUEST_TAG, PUBLIC, &DEDICATED_KEY, 1, ENCRYPTED | AUTHENTICATED);
+        let response = server.handle(&mut session, &request).unwrap();
+        assert_eq!(
+            decipher(&response, 0xD4, &DEDICATED_KEY),
+            (100, GetDataResult::Data(Data::Unsigned(1)))
+        );
+
+        // Without a dedicated key, DED requests cannot be deciphered.
+        let mut session = associate(&mut server, None);
+        let request = ciphered_get(DED_GET_REQUEST_TAG, PUBLIC, &DEDICATED_KEY, 2, ENCRYPTED);
+        assert_eq!(server.handle(&mut session, &request), Err(ServerError::DecipheringError));
+    }
+
+    #[test]
+    fn test_invocation_counter_replay() {
+        let mut server = server(SecurityPolicy::AccessRights);
+        let mut session = associate(&mut server, None);
+
+        server.handle(&mut session, &glo_get(PUBLIC, 5, true)).unwrap();
+        for counter in [5, 4] {
+            let error = server.handle(&mut session, &glo_get(PUBLIC, counter, true)).unwrap_err();
+            assert_eq!(error, ServerError::InvocationCounterError);
+            assert_eq!(error.exception_response(), vec![0xD8, 1, 6]);
+        }
+
+        // The counter outlives the association.
+        let mut session = associate(&mut server, None);
+        assert_eq!(
+            server.handle(&mut session, &glo_get(PUBLIC, 5, true)),
+            Err(ServerError::InvocationCounterError)
+        );
+        assert!(server.handle(&mut session, &glo_get(PUBLIC, 6, true)).is_ok());
+    }
+
+    #[test]
+    fn test_deciphering_errors() {
+        let mut server = server(SecurityPolicy::AccessRights);
+
+        // Ciphered requests need a ciphered association.
+        let mut session = ServerSession::new();
+        assert_eq!(
+            server.handle(&mut session, &glo_get(PUBLIC, 1, true)),
+            Err(ServerError::NotAssociated)
+        );
+
+        let mut session = associate(&mut server, None);
+        let wrong_key = GloGetRequest::new(
+            &get(PUBLIC),
+            Key::<Aes128>::from_slice(&DEDICATED_KEY),
+            CLIENT_TITLE,
+            1,
+            SecurityControl::new(0),
+        )
+        .unwrap();
+        assert_eq!(
+            server.handle(&mut session, &wrong_key.encode()),
+            Err(ServerError::DecipheringError)
+        );
+
+        let wrong_title = GloGetRequest::new(
+            &get(PUBLIC),
+            Key::<Aes128>::from_slice(&GLOBAL_KEY),
+            SERVER_TITLE,
+            2,
+            SecurityControl::new(0),
+        )
+        .unwrap();
+        assert_eq!(
+            server.handle(&mut session, &wrong_title.encode()),
+            Err(ServerError::DecipheringError)
+        );
+
+        // Rejected requests do not consume invocation counters.
+        assert!(server.handle(&mut session, &glo_get(PUBLIC, 1, true)).is_ok());
+    }
+
+    #[test]
+    fn test_access_rights_policy() {
+        let mut server = server(SecurityPolicy::AccessRights);
+        let mut session = associate(&mut server, None);
+
+        let denied = GetDataResult::DataAccessError(DataAccessResult::ReadWriteDenied);
+        let response = server.handle(&mut session, &glo_get(PROTECTED, 1, false)).unwrap();
+        assert_eq!(decipher(&response, 0xCC, &GLOBAL_KEY).1, denied);
+
+        let response = server.handle(&mut session, &get(PROTECTED)).unwrap();
+        match GetResponse::parse(&response).unwrap().1 {
+            GetResponse::Normal(response) => assert_eq!(response.result, denied),
+            response => panic!("unexpected response {:?}", response),
+        }
+        assert!(server.handle(&mut session, &get(PUBLIC)).is_ok());
+
+        // Plaintext associations remain possible.
+        let mut session = ServerSession::new();
+        let aare = server.handle(&mut session, &AarqApdu::new_simple_ln(512).encode()).unwrap();
+        assert!(AareApdu::parse(&aare).unwrap().1.is_accepted());
+        assert!(!session.is_ciphered());
+    }
+
+    #[test]
+    fn test_all_requests_policy() {
+        let mut server = server(SecurityPolicy::AllRequests);
+
+        let mut session = ServerSession::new();
+        let aare = server.handle(&mut session, &AarqApdu::new_simple_ln(512).encode()).unwrap();
+        assert!(!AareApdu::parse(&aare).unwrap().1.is_accepted());
+
+        let mut session = associate(&mut server, None);
+        assert_eq!(server.handle(&mut session, &get(PUBLIC)), Err(ServerError::ServiceNotAllowed));
+        assert!(server.handle(&mut session, &glo_get(PUBLIC, 1, true)).is_ok());
+    }
+
+    #[test]
+    fn test_forged_tag() {
+        let mut server = server(SecurityPolicy::AccessRights);
+        let mut session = associate(&mut server, None);
+
+        let mut forged = glo_get(PROTECTED, 7, true);
+        *forged.last_mut().unwrap() ^= 0x01;
+        assert_eq!(server.handle(&mut session, &forged), Err(ServerError::DecipheringError));
+
+        // Flipping a ciphertext bit is caught as well.
+        let mut forged = glo_get(PROTECTED, 7, true);
+        forged[16] ^= 0x01;
+        assert_eq!(server.handle(&mut session, &forged), Err(ServerError::DecipheringError));
+
+        // An unverified request does not advance the client's counter.
+        assert_eq!(server.client_invocation_counter(&CLIENT_TITLE), None);
+        let response = server.handle(&mut session, &glo_get(PROTECTED, 7, true)).unwrap();
+        assert_eq!(
+            decipher(&response, 0xCC, &GLOBAL_KEY),
+            (100, GetDataResult::Data(Data::Unsigned(2)))
+        );
+        assert_eq!(server.client_invocation_counter(&CLIENT_TITLE), Some(7));
+
+        let mut forged = glo_get(PROTECTED, 8, true);
+        *forged.last_mut().unwrap() ^= 0x01;
+        assert_eq!(server.handle(&mut session, &forged), Err(ServerError::DecipheringError));
+        assert_eq!(server.client_invocation_counter(&CLIENT_TITLE), Some(7));
+    }
+
+    #[test]
+    fn test_authentication_only() {
+        let mut server = server(SecurityPolicy::AccessRights);
+        let mut session = associate(&mut server, None);
+
+        let request = ciphered_get(GLO_GET_REQUEST_TAG, PROTECTED, &GLOBAL_KEY, 1, AUTHENTICATED);
+        assert_eq!(
+            request[16..],
+            [get(PROTECTED), request[request.len() - 12..].to_vec()].concat()
+        );
+        let response = server.handle(&mut session, &request).unwrap();
+        assert_eq!(response[11], AUTHENTICATED);
+        assert_eq!(
+            decipher(&response, 0xCC, &GLOBAL_KEY),
+            (100, GetDataResult::Data(Data::Unsigned(2)))
+        );
+
+        // The plaintext of an authenticated-only request cannot be altered.
+        let mut forged = ciphered_get(GLO_GET_REQUEST_TAG, PUBLIC, &GLOBAL_KEY, 2, AUTHENTICATED);
+        forged[25] = PROTECTED.e;
+        assert_eq!(server.handle(&mut session, &forged), Err(ServerError::DecipheringError));
+    }
+
+    #[test]
+    fn test_unprotected_security_control() {
+        let mut server = server(SecurityPolicy::AccessRights);
+        let mut session = associate(&mut server, None);
+
+        // Neither encryption nor authentication: the plaintext is not accepted.
+        let mut request = vec![GLO_GET_REQUEST_TAG, 0x08];
+        request.extend_from_slice(&CLIENT_TITLE);
+        request.extend_from_slice(&[5 + get(PUBLIC).len() as u8, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        request.extend_from_slice(&get(PUBLIC));
+        assert_eq!(server.handle(&mut session, &request), Err(ServerError::DecipheringError));
+        assert_eq!(server.client_invocation_counter(&CLIENT_TITLE), None);
+    }
+
+    #[test]
+    fn test_authentication_without_key() {
+        let security = ServerSecurity::new(SERVER_TITLE, GLOBAL_KEY);
+        let mut server =
+            DlmsServer::new(ServerSettings { security: Some(security), ..Default::default() })
+                .with_object(DataObject::new(PUBLIC, Data::Unsigned(1)));
+        let mut session = associate(&mut server, None);
+
+        assert_eq!(
+            server.handle(&mut session, &glo_get(PUBLIC, 1, true)),
+            Err(ServerError::DecipheringError)
+        );
+        let response = server.handle(&mut session, &glo_get(PUBLIC, 2, false)).unwrap();
+        assert_eq!(
+            decipher(&response, 0xCC, &GLOBAL_KEY).1,
+            GetDataResult::Data(Data::Unsigned(1))
+        );
+    }
+}
@@ -0,0 +1,444 @@
+//! Meter simulator
+//!
+//! A [`Simulator`] is a [`DlmsServer`] built from a device description, plus
+//! synthetic data: clocks follow the simulated time, registers count energy or
+//! fluctuate around a base value, and load profiles are captured on their
+//! period boundaries. [`serve`] answers clients over TCP with the framing of
+//! choice; the `dlms-sim` binary wraps all of this in a command line tool.
+//!
+//! # Device description
+//!
+//! The device is described in JSON:
+//!
+//! ```json
+//! {
+//!   "logical_device_name": "SIM0000000000001",
+//!   "authentication": { "mechanism": "hls", "secret": "0123456789ABCDEF" },
+//!   "max_pdu_size": 1024,
+//!   "time_zone": 60,
+//!   "seed": 42,
+//!   "objects": [
+//!     { "class": 8, "obis": "0.0.1.0.0.255" },
+//!     {
+//!       "class": 3, "obis": "1.0.1.8.0.255",
+//!       "value": { "type": "double-long-unsigned", "value": 1000000 },
+//!       "scaler": 0, "unit": 30,
+//!       "simulate": { "kind": "counter", "rate": 500, "jitter": 0.2 },
+//!       "access": { "attributes": { "1": "read-only", "2": "read-only", "3": "read-only" },
+//!                   "methods": { "1": "authenticated-access" } }
+//!     },
+//!     {
+//!       "class": 7, "obis": "1.0.99.1.0.255", "capture_period": 900, "entries": 96,
+//!       "capture_objects": [
+//!         { "class": 8, "obis": "0.0.1.0.0.255", "attribute": 2 },
+//!         { "class": 3, "obis": "1.0.1.8.0.255", "attribute": 2 }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! - `authentication.mechanism`: `none`, `lls` (password) or `hls`
+//!   (mechanism 2, see [`default_hls_function`](crate::cosem::association_ln::default_hls_function));
+//!   the secret is given as text (`secret`) or hex (`secret_hex`)
+//! - `security` (optional): `system_title` (8 bytes hex), `global_key`
+//!   (16 bytes hex), `authentication_key` (16 bytes hex, needed for
+//!   authenticated requests), `invocation_counter` and `policy`
+//!   (`access-rights` or `all-requests`) of GLO/DED ciphering, see
+//!   [`ServerSecurity`](crate::server::ServerSecurity)
+//! - `time_zone`: minutes ahead of UTC, used for clocks and profile boundaries
+//! - `objects[].class`: 1 (Data), 3 (Register), 4 (ExtendedRegister),
+//!   5 (DemandRegister), 7 (ProfileGeneric) or 8 (Clock)
+//! - `objects[].obis`: `a.b.c.d.e.f` or `a-b:c.d.e*f`
+//! - `objects[].value`: typed value `{"type": ..., "value": ...}` with types
+//!   `null`, `boolean`, `integer`, `unsigned`, `long`, `long-unsigned`, `double-long`,
+//!   `double-long-unsigned`, `long64`, `long64-unsigned`, `enum`, `float32`,
+//!   `float64`, `octet-string` (hex), `visible-string`, `utf8-string` and
+//!   `structure`/`array` (list of typed values)
+//! - `objects[].scaler` / `unit`: scaler and Blue Book unit code (e.g. 30 = Wh)
+//! - `objects[].access`: access rights listed in the association object
+//!   (0.0.40.0.0.255), which is always added; by default all attributes are
+//!   read-only
+//! - `objects[].simulate`: `counter` (grows by `rate` raw units per hour) or
+//!   `gauge` (`base` value), each varied by up to ± `jitter` (a fraction)
+//! - ProfileGeneric: `capture_period` (seconds, 0 = not captured periodically),
+//!   `entries`, `sort` (`fifo` or `lifo`), `capture_objects`
+//!   (`class`, `obis`, `attribute`, `data_index`)
+//! - DemandRegister: `period` and `number_of_periods`
+//!
+//! # Example
+//! ```
+//! use dlms_cosem::server::sim::Simulator;
+//! use dlms_cosem::{Data, ObisCode};
+//!
+//! let mut simulator = Simulator::from_json(
+//!     r#"{"objects": [{"class": 3, "obis": "1.0.1.8.0.255",
+//!         "simulate": {"kind": "counter", "rate": 1000}}]}"#,
+//! )
+//! .unwrap();
+//!
+//! let energy = ObisCode::new(1, 0, 1, 8, 0, 255);
+//! simulator.tick(1_700_000_000);
+//! simulator.tick(1_700_003_600); // one hour later
+//! let register = simulator.server().objects().get(3, &energy).unwrap();
+//! assert_eq!(register.get_attribute(2), Ok(Data::DoubleLongUnsigned(1000)));
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::cosem::capture_scheduler::CaptureScheduler;
+use crate::data::{Data, Date, DateTime, Time};
+use crate::obis_code::ObisCode;
+use crate::server::{DlmsServer, ServerError, ServerSession};
+
+mod device;
+mod json;
+mod link;
+
+pub use link::{Framing, serve, serve_connection};
+
+use device::{Device, Generator, GeneratorKind};
+
+/// Seed of the value generators when the description does not set one.
+const DEFAULT_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+/// Errors loading a device description.
+#[derive(Debug)]
+pub enum SimError {
+    /// The description file could not be read.
+    Io(io::Error),
+    /// The description is not valid JSON.
+    Json(String),
+    /// The description does not describe a supported device.
+    InvalidDescription(String),
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimError::Io(error) => write!(f, "I/O error: {}", error),
+            SimError::Json(error) => write!(f, "Invalid JSON: {}", error),
+            SimError::InvalidDescription(error) => {
+                write!(f, "Invalid device description: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SimError {}
+
+impl From<io::Error> for SimError {
+    fn from(error: io::Error) -> Self {
+        SimError::Io(error)
+    }
+}
+
+/// A simulated meter: server, object model and synthetic data.
+#[derive(Debug)]
+pub struct Simulator {
+    server: DlmsServer,
+    generators: Vec<Generator>,
+    clocks: Vec<ObisCode>,
+    scheduler: CaptureScheduler,
+    time_zone: i16,
+    last_tick: Option<u32>,
+    rng: u64,
+}
+
+impl Simulator {
+    /// Creates a simulator from a JSON device description.
+    pub fn from_json(json: &str) -> Result<Self, SimError> {
+        let device = Device::from_json(json)?;
+        let scheduler = device
+            .profiles
+            .iter()
+            .fold(CaptureScheduler::new(), |scheduler, profile| scheduler.with_profile(*profile))
+            .with_time_offset(i32::from(device.time_zone) * 60);
+
+        Ok(Self {
+            server: DlmsServer::new(device.settings).with_objects(device.objects),
+            generators: device.generators,
+            clocks: device.clocks,
+            scheduler,
+            time_zone: device.time_zone,
+            last_tick: None,
+            rng: device.seed.unwrap_or(DEFAULT_SEED),
+        })
+    }
+
+    /// Loads a JSON device description from a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SimError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Sets the seed of the value generators.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = seed;
+        self
+    }
+
+    /// Returns the server.
+    pub fn server(&self) -> &DlmsServer {
+        &self.server
+    }
+
+    /// Returns the server, mutably.
+    pub fn server_mut(&mut self) -> &mut DlmsServer {
+        &mut self.server
+    }
+
+    /// Answers an encoded request APDU; see [`DlmsServer::handle`].
+    pub fn handle(
+        &mut self,
+        session: &mut ServerSession,
+        request: &[u8],
+    ) -> Result<Vec<u8>, ServerError> {
+        self.server.handle(session, request)
+    }
+
+    /// Advances the simulation to `now` (seconds since 1970-01-01, UTC).
+    ///
+    /// Sets the clocks, updates simulated values for the time elapsed since the
+    /// previous tick and captures due load profiles. The first tick only sets
+    /// the starting point of counters.
+    pub fn tick(&mut self, now: u32) {
+        let elapsed = self.last_tick.map_or(0, |last| now.saturating_sub(last));
+        self.last_tick = Some(now);
+
+        let local_time = date_time(i64::from(now) + i64::from(self.time_zone) * 60, self.time_zone);
+        let objects = self.server.objects_mut();
+        for clock in &self.clocks {
+            if let Ok(clock) = objects.lookup_mut(8, clock) {
+                let _ = clock.set_attribute(2, Data::OctetString(local_time.encode()));
+            }
+        }
+
+        for index in 0..self.generators.len() {
+            let variation = self.next_variation();
+            let generator = &mut self.generators[index];
+            let objects = self.server.objects_mut();
+            let Ok(object) = objects.lookup_mut(generator.class_id, &generator.logical_name) else {
+                continue;
+            };
+            let Ok(current) = object.get_attribute(2) else {
+                continue;
+            };
+            let value = match generator.kind {
+                GeneratorKind::Counter { rate, jitter } => {
+                    let total = generator.remainder
+                        + rate * f64::from(elapsed) / 3600.0 * (1.0 + jitter * variation);
+                    let whole = total.floor();
+                    generator.remainder = total - whole;
+                    number(&current).map(|current| current + whole)
+                }
+                GeneratorKind::Gauge { base, jitter } => Some(base * (1.0 + jitter * variation)),
+            };
+            if let Some(value) = value.and_then(|value| with_number(&current, value)) {
+                let _ = object.set_attribute(2, value);
+            }
+        }
+
+        self.scheduler.tick(now, self.server.objects_mut());
+    }
+
+    /// Returns a pseudo-random value in [-1, 1] (xorshift64*).
+    fn next_variation(&mut self) -> f64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let random = self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11;
+        random as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+    }
+}
+
+fn number(data: &Data) -> Option<f64> {
+    Some(match *data {
+        Data::Integer(value) => value.into(),
+        Data::Unsigned(value) => value.into(),
+        Data::Long(value) => value.into(),
+        Data::LongUnsigned(value) => value.into(),
+        Data::DoubleLong(value) => value.into(),
+        Data::DoubleLongUnsigned(value) => value.into(),
+        Data::Long64(value) => value as f64,
+        Data::Long64Unsigned(value) => value as f64,
+        Data::Float32(value) => value.into(),
+        Data::Float64(value) => value,
+        _ => return None,
+    })
+}
+
+/// Converts `value` to the numeric type of `template`, saturating at its bounds.
+fn with_number(template: &Data, value: f64) -> Option<Data> {
+    let rounded = value.round();
+    Some(match template {
+        Data::Integer(_) => Data::Integer(rounded as i8),
+        Data::Unsigned(_) => Data::Unsigned(rounded as u8),
+        Data::Long(_) => Data::Long(rounded as i16),
+        Data::LongUnsigned(_) => Data::LongUnsigned(rounded as u16),
+        Data::DoubleLong(_) => Data::DoubleLong(rounded as i32),
+        Data::DoubleLongUnsigned(_) => Data::DoubleLongUnsigned(rounded as u32),
+        Data::Long64(_) => Data::Long64(rounded as i64),
+        Data::Long64Unsigned(_) => Data::Long64Unsigned(rounded as u64),
+        Data::Float32(_) => Data::Float32(value as f32),
+        Data::Float64(_) => Data::Float64(value),
+        _ => return None,
+    })
+}
+
+/// Converts local seconds since 1970-01-01 to a COSEM date-time.
+fn date_time(local_seconds: i64, time_zone: i16) -> DateTime {
+    let days = local_seconds.div_euclid(86_400);
+    let seconds = local_seconds.rem_euclid(86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    // 1970-01-01 was a Thursday; COSEM counts Monday as 1
+    let day_of_week = (days + 3).rem_euclid(7) + 1;
+
+    DateTime::new(
+        Date::new(year as u16, month as u8, day as u8, day_of_week as u8),
+        Time::new(
+            Some((seconds / 3600) as u8),
+            Some((seconds / 60 % 60) as u8),
+            Some((seconds % 60) as u8),
+            Some(0),
+        ),
+        Some(time_zone),
+        Some(0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosem::CosemObject;
+    use crate::cosem::clock::Clock;
+
+    const DESCRIPTION: &str = r#"{
+        "time_zone": 60,
+        "objects": [
+            { "class": 8, "obis": "0.0.1.0.0.255" },
+            { "class": 3, "obis": "1.0.1.8.0.255",
+              "value": { "type": "double-long-unsigned", "value": 5000 },
+              "scaler": 0, "unit": 30,
+              "simulate": { "kind": "counter", "rate": 400, "jitter": 0.5 } },
+            { "class": 3, "obis": "1.0.32.7.0.255",
+              "value": { "type": "long-unsigned", "value": 2300 },
+              "scaler": -1, "unit": 35,
+              "simulate": { "kind": "gauge", "base": 2300, "jitter": 0.05 } },
+            { "class": 7, "obis": "1.0.99.1.0.255", "capture_period": 900, "entries": 4,
+              "capture_objects": [
+                { "class": 8, "obis": "0.0.1.0.0.255", "attribute": 2 },
+                { "class": 3, "obis": "1.0.1.8.0.255", "attribute": 2 }
+              ] }
+        ]
+    }"#;
+
+    const CLOCK: ObisCode = ObisCode { a: 0, b: 0, c: 1, d: 0, e: 0, f: 255 };
+    const ENERGY: ObisCode = ObisCode { a: 1, b: 0, c: 1, d: 8, e: 0, f: 255 };
+    const VOLTAGE: ObisCode = ObisCode { a: 1, b: 0, c: 32, d: 7, e: 0, f: 255 };
+    const LOAD_PROFILE: ObisCode = ObisCode { a: 1, b: 0, c: 99, d: 1, e: 0, f: 255 };
+
+    // 2024-03-01 00:00:00 UTC, a Friday
+    const START: u32 = 1_709_251_200;
+
+    fn value(simulator: &Simulator, class_id: u16, name: &ObisCode, attribute: i8) -> Data {
+        simulator.server().objects().get(class_id, name).unwrap().get_attribute(attribute).unwrap()
+    }
+
+    #[test]
+    fn test_objects_from_description() {
+        let simulator = Simulator::from_json(DESCRIPTION).unwrap();
+        let objects = simulator.server().objects();
+        assert_eq!(objects.len(), 5); // plus the association
+        assert_eq!(
+            value(&simulator, 3, &VOLTAGE, 3),
+            Data::Structure(vec![Data::Integer(-1), Data::Enum(35)])
+        );
+
+        let association = value(&simulator, 15, &ObisCode::new(0, 0, 40, 0, 0, 255), 2);
+        let Data::Structure(elements) = association else { panic!("expected object list") };
+        assert_eq!(elements.len(), 5);
+    }
+
+    #[test]
+    fn test_invalid_descriptions() {
+        let error = |json| Simulator::from_json(json).unwrap_err().to_string();
+        assert!(error("[").starts_with("Invalid JSON"));
+        assert!(
+            error(r#"{"objects": [{"class": 99, "obis": "0.0.1.0.0.255"}]}"#)
+                .contains("objects[0]: class 99 is not supported")
+        );
+        assert!(error(r#"{"objects": [{"class": 8, "obis": "0.0.1.0.0"}]}"#).contains("obis"));
+        assert!(error(r#"{"authentication": {"mechanism": "hls"}}"#).contains("secret"));
+    }
+
+    #[test]
+    fn test_tick_updates_clock_and_values() {
+        let mut simulator = Simulator::from_json(DESCRIPTION).unwrap();
+        simulator.tick(START);
+        assert_eq!(value(&simulator, 3, &ENERGY, 2), Data::DoubleLongUnsigned(5000));
+
+        let mut clock = Clock::new(CLOCK);
+        let Data::OctetString(time) = value(&simulator, 8, &CLOCK, 2) else {
+            panic!("expected time")
+        };
+        clock.set_attribute(2, Data::OctetString(time)).unwrap();
+        let date = &clock.time.date;
+        assert_eq!((date.year, date.month, date.day_of_month, date.day_of_week), (2024, 3, 1, 5));
+        assert_eq!(clock.time.time.hour, Some(1)); // UTC+1
+
+        simulator.tick(START + 3600);
+        let Data::DoubleLongUnsigned(energy) = value(&simulator, 3, &ENERGY, 2) else {
+            panic!("expected double-long-unsigned")
+        };
+        assert!((5200..=5600).contains(&energy), "energy {}", energy);
+
+        let Data::LongUnsigned(voltage) = value(&simulator, 3, &VOLTAGE, 2) else {
+            panic!("expected long-unsigned")
+        };
+        assert!((2185..=2415).contains(&voltage), "voltage {}", voltage);
+    }
+
+    #[test]
+    fn test_load_profile_capture() {
+        let mut simulator = Simulator::from_json(DESCRIPTION).unwrap();
+        for minute in 0..=60 {
+            simulator.tick(START + minute * 60);
+        }
+        // Captured at 00:15, 00:30, 00:45 and 01:00
+        assert_eq!(value(&simulator, 7, &LOAD_PROFILE, 7), Data::DoubleLongUnsigned(4));
+    }
+
+    #[test]
+    fn test_seeded_generators_are_reproducible() {
+        let run = |seed| {
+            let mut simulator = Simulator::from_json(DESCRIPTION).unwrap().with_seed(seed);
+            simulator.tick(START);
+            simulator.tick(START + 900);
+            value(&simulator, 3, &ENERGY, 2)
+        };
+        assert_eq!(run(7), run(7));
+    }
+
+    #[test]
+    fn test_date_time() {
+        let date_time = date_time(951_782_400, 0); // 2000-02-29 00:00:00
+        assert_eq!(date_time.date.year, 2000);
+        assert_eq!(date_time.date.month, 2);
+        assert_eq!(date_time.date.day_of_month, 29);
+        assert_eq!(date_time.date.day_of_week, 2);
+    }
+}
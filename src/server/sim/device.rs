@@ -0,0 +1,620 @@
+//! Device descriptions: JSON → COSEM objects.
+//!
+//! See the [module documentation](super) for the format.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::SimError;
+use super::json::JsonValue;
+use crate::ScalerUnit;
+use crate::association::MechanismName;
+use crate::cosem::association_ln::{AccessRights, AssociationLn};
+use crate::cosem::clock::Clock;
+use crate::cosem::data::DataObject;
+use crate::cosem::demand_register::DemandRegister;
+use crate::cosem::extended_register::ExtendedRegister;
+use crate::cosem::register::Register;
+use crate::cosem::{
+    AttributeAccess, CaptureObjectDefinition, MethodAccess, ObjectListElement, ObjectRegistry,
+    ProfileGeneric,
+};
+use crate::data::{Data, Date, DateTime, Time};
+use crate::obis_code::ObisCode;
+use crate::server::{SecurityPolicy, ServerSecurity, ServerSettings};
+use crate::unit::Unit;
+
+/// Logical name of the COSEM logical device name object.
+const LOGICAL_DEVICE_NAME: ObisCode = ObisCode { a: 0, b: 0, c: 42, d: 0, e: 0, f: 255 };
+
+/// How a simulated value changes over time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum GeneratorKind {
+    /// Cumulative value (energy, volume) growing by `rate` raw units per hour.
+    Counter { rate: f64, jitter: f64 },
+    /// Instantaneous value (voltage, power) fluctuating around `base`.
+    Gauge { base: f64, jitter: f64 },
+}
+
+/// A simulated attribute 2 of a Data, Register or ExtendedRegister object.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Generator {
+    pub class_id: u16,
+    pub logical_name: ObisCode,
+    pub kind: GeneratorKind,
+    /// Fraction of a raw unit not yet added to a counter.
+    pub remainder: f64,
+}
+
+/// A device description turned into objects and settings.
+#[derive(Debug)]
+pub(crate) struct Device {
+    pub objects: ObjectRegistry,
+    pub settings: ServerSettings,
+    pub generators: Vec<Generator>,
+    pub clocks: Vec<ObisCode>,
+    pub profiles: Vec<ObisCode>,
+    pub time_zone: i16,
+    pub seed: Option<u64>,
+}
+
+impl Device {
+    /// Parses a JSON device description.
+    pub fn from_json(json: &str) -> Result<Self, SimError> {
+        let root = JsonValue::parse(json).map_err(SimError::Json)?;
+        if root.as_object().is_none() {
+            return Err(invalid("device description must be an object"));
+        }
+
+        let time_zone = optional_integer(&root, "time_zone", 0).map_err(|error| invalid(&error))?;
+        let mut device = Device {
+            objects: ObjectRegistry::new(),
+            settings: ServerSettings::default(),
+            generators: Vec::new(),
+            clocks: Vec::new(),
+            profiles: Vec::new(),
+            time_zone,
+            seed: root
+                .get("seed")
+                .map(|seed| integer(seed, "seed"))
+                .transpose()
+                .map_err(|error| invalid(&error))?,
+        };
+        device.settings.max_pdu_size =
+            optional_integer(&root, "max_pdu_size", 0xFFFF).map_err(|error| invalid(&error))?;
+
+        let (mechanism, secret) = match root.get("authentication") {
+            Some(authentication) => parse_authentication(authentication)?,
+            None => (MechanismName::LowestLevelSecurity, Vec::new()),
+        };
+        match mechanism {
+            MechanismName::LowLevelSecurity => device.settings.password = Some(secret.clone()),
+            MechanismName::HighLevelSecurity => device.settings.hls_secret = Some(secret.clone()),
+            _ => {}
+        }
+        if let Some(security) = root.get("security") {
+            device.settings.security =
+                Some(parse_security(security).map_err(|error| invalid(&error))?);
+        }
+
+        let mut access_rights = Vec::new();
+        if let Some(name) = root.get("logical_device_name") {
+            let name =
+                name.as_str().ok_or_else(|| invalid("logical_device_name: expected string"))?;
+            device
+                .objects
+                .insert(Box::new(DataObject::new(LOGICAL_DEVICE_NAME, octets(name.as_bytes()))));
+            access_rights.push((1, LOGICAL_DEVICE_NAME, read_only(2)));
+        }
+
+        let objects = root
+            .get("objects")
+            .map(|objects| objects.as_array().ok_or_else(|| invalid("objects: expected array")))
+            .transpose()?
+            .unwrap_or_default();
+        for (index, object) in objects.iter().enumerate() {
+            let rights = device
+                .add_object(object)
+                .map_err(|error| invalid(&format!("objects[{}]: {}", index, error)))?;
+            access_rights.push(rights);
+        }
+
+        let association = AssociationLn::new()
+            .with_authentication(mechanism, secret)
+            .with_object_list_from(&device.objects, |object| {
+                access_rights
+                    .iter()
+                    .find(|(class_id, name, _)| {
+                        *class_id == object.class_id() && name == object.logical_name()
+                    })
+                    .map(|(_, _, rights)| rights.clone())
+                    .unwrap_or_default()
+            });
+        let own_rights = AccessRights::new()
+            .with_attribute(1, AttributeAccess::READ_ONLY)
+            .with_attribute(2, AttributeAccess::READ_ONLY)
+            .with_method(1, MethodAccess::ACCESS);
+        let own_element = ObjectListElement::from_object(&association, own_rights);
+        let association = association.with_object(own_element);
+        device.objects.insert(Box::new(association));
+
+        Ok(device)
+    }
+
+    /// Instantiates one object description.
+    ///
+    /// Returns the object's access rights for the association object list.
+    fn add_object(&mut self, object: &JsonValue) -> Result<(u16, ObisCode, AccessRights), String> {
+        let class_id = object
+            .get("class")
+            .and_then(JsonValue::as_i64)
+            .and_then(|class_id| u16::try_from(class_id).ok())
+            .ok_or("class: expected class id")?;
+        let logical_name = obis(object.get("obis").ok_or("obis: missing")?)?;
+        if self.objects.get(class_id, &logical_name).is_some() {
+            return Err(format!("duplicate object {}", logical_name));
+        }
+
+        let value = |default: Data| object.get("value").map(data).unwrap_or(Ok(default));
+        let scaler_unit = || -> Result<ScalerUnit, String> {
+            let scaler = object.get("scaler").map(|scaler| integer(scaler, "scaler"));
+            let unit = object.get("unit").map(|unit| integer::<u8>(unit, "unit"));
+            Ok(ScalerUnit {
+                scaler: scaler.transpose()?.unwrap_or(0),
+                unit: Unit::try_from(unit.transpose()?.unwrap_or(255))
+                    .map_err(|_| String::from("unit: unknown unit"))?,
+            })
+        };
+
+        let attributes = match class_id {
+            1 => {
+                self.objects.insert(Box::new(DataObject::new(logical_name, value(Data::Null)?)));
+                2
+            }
+            3 => {
+                let register = Register::new(
+                    logical_name,
+                    value(Data::DoubleLongUnsigned(0))?,
+                    scaler_unit()?,
+                );
+                self.objects.insert(Box::new(register));
+                3
+            }
+            4 => {
+                let status = object.get("status").map(data).unwrap_or(Ok(Data::Null))?;
+                let register = ExtendedRegister::new(
+                    logical_name,
+                    value(Data::DoubleLongUnsigned(0))?,
+                    scaler_unit()?,
+                    status,
+                    unspecified_date_time(),
+                );
+                self.objects.insert(Box::new(register));
+                5
+            }
+            5 => {
+                let register = DemandRegister {
+                    logical_name,
+                    current_average_value: value(Data::DoubleLongUnsigned(0))?,
+                    last_average_value: value(Data::DoubleLongUnsigned(0))?,
+                    scaler_unit: scaler_unit()?,
+                    status: Data::Null,
+                    capture_time: unspecified_date_time(),
+                    start_time_current: unspecified_date_time(),
+                    period: optional_integer(object, "period", 900)?,
+                    number_of_periods: optional_integer(object, "number_of_periods", 1)?,
+                };
+                self.objects.insert(Box::new(register));
+                9
+            }
+            7 => {
+                let capture_objects = object
+                    .get("capture_objects")
+                    .map(|objects| objects.as_array().ok_or("capture_objects: expected array"))
+                    .transpose()?
+                    .unwrap_or_default()
+                    .iter()
+                    .map(capture_object)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let capture_period = optional_integer(object, "capture_period", 0)?;
+                let entries = optional_integer(object, "entries", 96)?;
+                let profile = match object.get("sort").and_then(JsonValue::as_str) {
+                    None | Some("fifo") => ProfileGeneric::with_fifo(
+                        logical_name,
+                        capture_objects,
+                        capture_period,
+                        entries,
+                    ),
+                    Some("lifo") => ProfileGeneric::with_lifo(
+                        logical_name,
+                        capture_objects,
+                        capture_period,
+                        entries,
+                    ),
+                    Some(_) => return Err("sort: expected \"fifo\" or \"lifo\"".into()),
+                };
+                self.objects.insert(Box::new(profile));
+                if capture_period > 0 {
+                    self.profiles.push(logical_name);
+                }
+                8
+            }
+            8 => {
+                let mut clock = Clock::new(logical_name);
+                clock.time_zone = self.time_zone;
+                self.objects.insert(Box::new(clock));
+                self.clocks.push(logical_name);
+                9
+            }
+            _ => return Err(format!("class {} is not supported", class_id)),
+        };
+
+        if let Some(simulate) = object.get("simulate") {
+            if !matches!(class_id, 1 | 3 | 4) {
+                return Err(
+                    "simulate: only Data, Register and ExtendedRegister values can be simulated"
+                        .into(),
+                );
+            }
+            self.generators.push(Generator {
+                class_id,
+                logical_name,
+                kind: generator_kind(simulate)?,
+                remainder: 0.0,
+            });
+        }
+
+        let rights = match object.get("access") {
+            Some(access) => access_rights(access)?,
+            None => read_only(attributes),
+        };
+        Ok((class_id, logical_name, rights))
+    }
+}
+
+fn invalid(message: &str) -> SimError {
+    SimError::InvalidDescription(message.into())
+}
+
+fn read_only(attributes: i8) -> AccessRights {
+    (1..=attributes).fold(AccessRights::new(), |rights, id| {
+        rights.with_attribute(id, AttributeAccess::READ_ONLY)
+    })
+}
+
+fn unspecified_date_time() -> DateTime {
+    DateTime {
+        date: Date { year: 0xFFFF, month: 0xFF, day_of_month: 0xFF, day_of_week: 0xFF },
+        time: Time {
+            hour: Some(0xFF),
+            minute: Some(0xFF),
+            second: Some(0xFF),
+            hundredth: Some(0xFF),
+        },
+        offset_minutes: None,
+        clock_status: None,
+    }
+}
+
+fn octets(bytes: &[u8]) -> Data {
+    Data::OctetString(bytes.to_vec())
+}
+
+fn integer<T: TryFrom<i64>>(value: &JsonValue, name: &str) -> Result<T, String> {
+    value
+        .as_i64()
+        .and_then(|value| T::try_from(value).ok())
+        .ok_or_else(|| format!("{}: expected integer in range", name))
+}
+
+fn optional_integer<T: TryFrom<i64>>(
+    object: &JsonValue,
+    name: &str,
+    default: T,
+) -> Result<T, String> {
+    object
+        .get(name)
+        .map(|value| integer(value, name))
+        .transpose()
+        .map(|value| value.unwrap_or(default))
+}
+
+fn number(object: &JsonValue, name: &str, default: f64) -> Result<f64, String> {
+    match object.get(name) {
+        Some(value) => value.as_f64().ok_or_else(|| format!("{}: expected number", name)),
+        None => Ok(default),
+    }
+}
+
+/// Parses an OBIS code written as `a.b.c.d.e.f` or `a-b:c.d.e*f`.
+fn obis(value: &JsonValue) -> Result<ObisCode, String> {
+    let text = value.as_str().ok_or("obis: expected string")?;
+    let groups = text
+        .split(['.', '-', ':', '*'])
+        .map(|group| group.parse::<u8>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("obis: invalid code {:?}", text))?;
+    ObisCode::try_from(groups.as_slice()).map_err(|_| format!("obis: invalid code {:?}", text))
+}
+
+fn hex(text: &str) -> Result<Vec<u8>, String> {
+    let invalid = || format!("invalid hex string {:?}", text);
+    // Byte-wise, so multi-byte UTF-8 characters are rejected rather than split
+    let nibbles = text
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .map(|byte| char::from(byte).to_digit(16).ok_or_else(invalid))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !nibbles.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    Ok(nibbles.chunks(2).map(|pair| (pair[0] << 4 | pair[1]) as u8).collect())
+}
+
+/// Parses a typed value `{"type": "...", "value": ...}`.
+fn data(value: &JsonValue) -> Result<Data, String> {
+    let kind = value.get("type").and_then(JsonValue::as_str).ok_or("value: missing type")?;
+    let inner = value.get("value").unwrap_or(&JsonValue::Null);
+    let int = |name| integer::<i64>(inner, name);
+    Ok(match kind {
+        "null" => Data::Null,
+        "boolean" => match inner {
+            JsonValue::Bool(value) => Data::Boolean(*value),
+            _ => return Err("boolean: expected true or false".into()),
+        },
+        "integer" => Data::Integer(integer(inner, kind)?),
+        "unsigned" => Data::Unsigned(integer(inner, kind)?),
+        "long" => Data::Long(integer(inner, kind)?),
+        "long-unsigned" => Data::LongUnsigned(integer(inner, kind)?),
+        "double-long" => Data::DoubleLong(integer(inner, kind)?),
+        "double-long-unsigned" => Data::DoubleLongUnsigned(integer(inner, kind)?),
+        "long64" => Data::Long64(int(kind)?),
+        "long64-unsigned" => Data::Long64Unsigned(integer(inner, kind)?),
+        "enum" => Data::Enum(integer(inner, kind)?),
+        "float32" => Data::Float32(inner.as_f64().ok_or("float32: expected number")? as f32),
+        "float64" => Data::Float64(inner.as_f64().ok_or("float64: expected number")?),
+        "octet-string" => {
+            Data::OctetString(hex(inner.as_str().ok_or("octet-string: expected hex string")?)?)
+        }
+        "visible-string" => {
+            octets(inner.as_str().ok_or("visible-string: expected string")?.as_bytes())
+        }
+        "utf8-string" => {
+            Data::Utf8String(inner.as_str().ok_or("utf8-string: expected string")?.into())
+        }
+        "structure" | "array" => Data::Structure(
+            inner
+                .as_array()
+                .ok_or("structure: expected array")?
+                .iter()
+                .map(data)
+                .collect::<Result<_, _>>()?,
+        ),
+        _ => return Err(format!("value: unsupported type {:?}", kind)),
+    })
+}
+
+fn capture_object(value: &JsonValue) -> Result<CaptureObjectDefinition, String> {
+    Ok(CaptureObjectDefinition {
+        class_id: value
+            .get("class")
+            .map(|class_id| integer(class_id, "capture_objects.class"))
+            .ok_or("capture_objects.class: missing")??,
+        logical_name: obis(value.get("obis").ok_or("capture_objects.obis: missing")?)?,
+        attribute_index: value
+            .get("attribute")
+            .map(|id| integer(id, "capture_objects.attribute"))
+            .unwrap_or(Ok(2))?,
+        data_index: value
+            .get("data_index")
+            .map(|id| integer(id, "capture_objects.data_index"))
+            .unwrap_or(Ok(0))?,
+    })
+}
+
+fn generator_kind(value: &JsonValue) -> Result<GeneratorKind, String> {
+    let jitter = number(value, "jitter", 0.0)?;
+    if !(0.0..=1.0).contains(&jitter) {
+        return Err("simulate.jitter: expected a fraction between 0 and 1".into());
+    }
+    match value.get("kind").and_then(JsonValue::as_str) {
+        Some("counter") => Ok(GeneratorKind::Counter { rate: number(value, "rate", 0.0)?, jitter }),
+        Some("gauge") => Ok(GeneratorKind::Gauge { base: number(value, "base", 0.0)?, jitter }),
+        _ => Err("simulate.kind: expected \"counter\" or \"gauge\"".into()),
+    }
+}
+
+fn parse_authentication(value: &JsonValue) -> Result<(MechanismName, Vec<u8>), SimError> {
+    let mechanism = match value.get("mechanism").and_then(JsonValue::as_str) {
+        Some("none") => MechanismName::LowestLevelSecurity,
+        Some("lls") => MechanismName::LowLevelSecurity,
+        Some("hls") => MechanismName::HighLevelSecurity,
+        _ => {
+            return Err(invalid("authentication.mechanism: expected \"none\", \"lls\" or \"hls\""));
+        }
+    };
+    let secret = match (value.get("secret"), value.get("secret_hex")) {
+        (Some(secret), None) => secret
+            .as_str()
+            .map(|secret| secret.as_bytes().to_vec())
+            .ok_or_else(|| invalid("authentication.secret: expected string"))?,
+        (None, Some(secret)) => secret
+            .as_str()
+            .ok_or_else(|| invalid("authentication.secret_hex: expected string"))
+            .and_then(|secret| hex(secret).map_err(|error| invalid(&error)))?,
+        (None, None) if mechanism == MechanismName::LowestLevelSecurity => Vec::new(),
+        _ => return Err(invalid("authentication: expected either secret or secret_hex")),
+    };
+    Ok((mechanism, secret))
+}
+
+/// Parses `{"system_title": "...", "global_key": "...", "authentication_key": "...",
+/// "invocation_counter": 0, "policy": "..."}`.
+fn parse_security(value: &JsonValue) -> Result<ServerSecurity, String> {
+    fn key<const N: usize>(value: &JsonValue, name: &str) -> Result<[u8; N], String> {
+        let text = value
+            .get(name)
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| format!("security.{}: expected hex string", name))?;
+        hex(text)?.try_into().map_err(|_| format!("security.{}: expected {} bytes", name, N))
+    }
+
+    let policy = match value.get("policy").map(|policy| policy.as_str()) {
+        None | Some(Some("access-rights")) => SecurityPolicy::AccessRights,
+        Some(Some("all-requests")) => SecurityPolicy::AllRequests,
+        _ => return Err("security.policy: expected \"access-rights\" or \"all-requests\"".into()),
+    };
+    let mut security = ServerSecurity::new(key(value, "system_title")?, key(value, "global_key")?)
+        .with_invocation_counter(optional_integer(value, "invocation_counter", 0)?)
+        .with_policy(policy);
+    if value.get("authentication_key").is_some() {
+        security = security.with_authentication_key(key(value, "authentication_key")?);
+    }
+    Ok(security)
+}
+
+/// Parses `{"attributes": {"2": "read-write"}, "methods": {"1": "access"}}`.
+fn access_rights(value: &JsonValue) -> Result<AccessRights, String> {
+    const ATTRIBUTE_MODES: [&str; 7] = [
+        "no-access",
+        "read-only",
+        "write-only",
+        "read-write",
+        "authenticated-read-only",
+        "authenticated-write-only",
+        "authenticated-read-write",
+    ];
+    const METHOD_MODES: [&str; 3] = ["no-access", "access", "authenticated-access"];
+
+    let entries = |name: &str| -> Result<Vec<(i8, usize)>, String> {
+        let modes: &[&str] = if name == "attributes" { &ATTRIBUTE_MODES } else { &METHOD_MODES };
+        let Some(members) = value.get(name) else {
+            return Ok(Vec::new());
+        };
+        members
+            .as_object()
+            .ok_or_else(|| format!("access.{}: expected object", name))?
+            .iter()
+            .map(|(id, mode)| {
+                let id = id
+                    .parse::<i8>()
+                    .map_err(|_| format!("access.{}: invalid id {:?}", name, id))?;
+                let mode = mode
+                    .as_str()
+                    .and_then(|mode| modes.iter().position(|candidate| *candidate == mode))
+                    .ok_or_else(|| {
+                        format!("access.{}.{}: expected one of {:?}", name, id, modes)
+                    })?;
+                Ok((id, mode))
+            })
+            .collect()
+    };
+
+    let mut rights = AccessRights::new();
+    for (id, mode) in entries("attributes")? {
+        rights = rights.with_attribute(
+            id,
+            AttributeAccess::from_access_mode(mode as u8).unwrap_or(AttributeAccess::NO_ACCESS),
+        );
+    }
+    for (id, mode) in entries("methods")? {
+        rights = rights.with_method(
+            id,
+            MethodAccess::from_access_mode(mode as u8).unwrap_or(MethodAccess::NO_ACCESS),
+        );
+    }
+    Ok(rights)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_obis_notations() {
+        let expected = ObisCode::new(1, 0, 1, 8, 0, 255);
+        assert_eq!(obis(&JsonValue::String("1.0.1.8.0.255".into())), Ok(expected));
+        assert_eq!(obis(&JsonValue::String("1-0:1.8.0*255".into())), Ok(expected));
+        assert!(obis(&JsonValue::String("1.0.1.8.0".into())).is_err());
+        assert!(obis(&JsonValue::String("1.0.1.8.0.256".into())).is_err());
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(hex("0a FF\n10"), Ok(vec![0x0A, 0xFF, 0x10]));
+        assert_eq!(hex(""), Ok(vec![]));
+        assert!(hex("0A F").is_err());
+        assert!(hex("0G").is_err());
+        assert!(hex("é0").is_err());
+        assert!(hex("0é").is_err());
+        assert!(hex("+1").is_err());
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let value = |json: &str| data(&JsonValue::parse(json).unwrap());
+        assert_eq!(
+            value(r#"{"type": "long-unsigned", "value": 230}"#),
+            Ok(Data::LongUnsigned(230))
+        );
+        assert_eq!(
+            value(r#"{"type": "octet-string", "value": "0A FF"}"#),
+            Ok(Data::OctetString(vec![0x0A, 0xFF]))
+        );
+        assert_eq!(
+            value(
+                r#"{"type": "structure", "value": [{"type": "integer", "value": -1}, {"type": "null"}]}"#
+            ),
+            Ok(Data::Structure(vec![Data::Integer(-1), Data::Null]))
+        );
+        assert!(value(r#"{"type": "unsigned", "value": 256}"#).is_err());
+        assert_eq!(value(r#"{"type": "boolean", "value": true}"#), Ok(Data::Boolean(true)));
+        assert!(value(r#"{"type": "boolean", "value": 1}"#).is_err());
+        assert!(value(r#"{"type": "bcd", "value": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_access_rights() {
+        let rights = access_rights(
+            &JsonValue::parse(
+                r#"{"attributes": {"2": "read-write"}, "methods": {"1": "authenticated-access"}}"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(rights.attribute_access(2), Some(AttributeAccess::READ_WRITE));
+        assert_eq!(rights.method_access(1), Some(MethodAccess::AUTHENTICATED_ACCESS));
+        assert!(
+            access_rights(&JsonValue::parse(r#"{"attributes": {"2": "rw"}}"#).unwrap()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_security() {
+        let security = |json: &str| parse_security(&JsonValue::parse(json).unwrap());
+        assert_eq!(
+            security(
+                r#"{"system_title": "4D4D4D0000000001", "global_key": "000102030405060708090A0B0C0D0E0F",
+                    "authentication_key": "D0D1D2D3D4D5D6D7D8D9DADBDCDDDEDF",
+                    "invocation_counter": 10, "policy": "all-requests"}"#
+            ),
+            Ok(ServerSecurity::new(
+                [0x4D, 0x4D, 0x4D, 0, 0, 0, 0, 1],
+                [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]
+            )
+            .with_authentication_key(core::array::from_fn(|i| 0xD0 + i as u8))
+            .with_invocation_counter(10)
+            .with_policy(SecurityPolicy::AllRequests))
+        );
+        assert!(
+            security(
+                r#"{"system_title": "4D4D4D", "global_key": "000102030405060708090A0B0C0D0E0F"}"#
+            )
+            .unwrap_err()
+            .contains("system_title")
+        );
+        assert!(
+            security(r#"{"system_title": "4D4D4D0000000001"}"#).unwrap_err().contains("global_key")
+        );
+    }
+}
@@ -0,0 +1,395 @@
+//! Connection handling: APDU framing on byte streams.
+
+use alloc::vec::Vec;
+use core::str::FromStr;
+use std::io::{self, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use super::Simulator;
+use crate::server::ServerSession;
+use crate::transport::hdlc::{
+    HDLC_CONTROL_POLL_FINAL, HDLC_DEFAULT_MAX_INFO_LENGTH, HDLC_LLC_SIZE, HdlcAddress, HdlcError,
+    HdlcSequence, MAX_HDLC_FRAME_SIZE, complete_frame_len, decode_frame, encode_frame,
+    is_information_frame, is_receive_ready, skip_fill_flags,
+};
+
+/// Wrapper protocol version (IEC 62056-47).
+const WRAPPER_VERSION: [u8; 2] = [0x00, 0x01];
+
+/// Wrapper header: version, source wPort, destination wPort, length.
+const WRAPPER_HEADER_SIZE: usize = 8;
+
+/// Largest APDU read in one piece without framing.
+const RAW_BUFFER_SIZE: usize = 0xFFFF;
+
+/// SNRM command (set normal response mode), without the poll bit.
+const HDLC_SNRM: u8 = 0x83;
+
+/// DISC command (disconnect), without the poll bit.
+const HDLC_DISC: u8 = 0x43;
+
+/// UA response (unnumbered acknowledge) with the final bit.
+const HDLC_UA: u8 = 0x73;
+
+/// LLC header of server-to-client information fields.
+const LLC_RESPONSE_HEADER: [u8; 3] = [0xE6, 0xE7, 0x00];
+
+/// Framing of APDUs on a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Framing {
+    /// IEC 62056-47 wrapper header (TCP/UDP profile).
+    #[default]
+    Wrapper,
+    /// HDLC frames (IEC 62056-46): SNRM/UA, segmented I-frames, DISC/UA.
+    Hdlc,
+    /// Bare APDUs, one per read, as sent by the plain TCP transport of this crate.
+    Raw,
+}
+
+impl FromStr for Framing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrapper" => Ok(Framing::Wrapper),
+            "hdlc" => Ok(Framing::Hdlc),
+            "raw" => Ok(Framing::Raw),
+            _ => Err(format!("unknown framing {:?} (expected wrapper, hdlc or raw)", s)),
+        }
+    }
+}
+
+/// Accepts clients on `listener`, serving each connection on its own thread.
+///
+/// All connections share the simulator; each has its own association.
+pub fn serve(
+    listener: TcpListener,
+    simulator: Arc<Mutex<Simulator>>,
+    framing: Framing,
+) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let simulator = Arc::clone(&simulator);
+        thread::spawn(move || serve_connection(&simulator, stream, framing));
+    }
+    Ok(())
+}
+
+/// Serves one client until it closes the connection.
+pub fn serve_connection<S: Read + Write>(
+    simulator: &Mutex<Simulator>,
+    mut stream: S,
+    framing: Framing,
+) -> io::Result<()> {
+    let mut connection = Connection { simulator, session: ServerSession::new() };
+    match framing {
+        Framing::Wrapper => serve_wrapper(&mut stream, &mut connection),
+        Framing::Hdlc => serve_hdlc(&mut stream, &mut connection),
+        Framing::Raw => serve_raw(&mut stream, &mut connection),
+    }
+}
+
+/// One client association on a shared simulator.
+struct Connection<'a> {
+    simulator: &'a Mutex<Simulator>,
+    session: ServerSession,
+}
+
+impl Connection<'_> {
+    /// Answers a request APDU; requests the server cannot answer get an ExceptionResponse.
+    ///
+    /// If a request panicked on another connection, the simulator may have been left half
+    /// updated: the failure is logged and this association starts over.
+    fn respond(&mut self, request: &[u8]) -> Vec<u8> {
+        let mut simulator = self.simulator.lock().unwrap_or_else(|poisoned| {
+            eprintln!("dlms-sim: a request panicked on another connection, resetting association");
+            self.simulator.clear_poison();
+            self.session = ServerSession::new();
+            poisoned.into_inner()
+        });
+        simulator
+            .handle(&mut self.session, request)
+            .unwrap_or_else(|error| error.exception_response())
+    }
+
+    /// Drops the association (link layer disconnect).
+    fn reset(&mut self) {
+        self.session = ServerSession::new();
+    }
+}
+
+fn invalid_data(error: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+/// Fills `buffer`, returning `false` if the peer closed the connection before the first byte.
+fn read_exact_or_eof(stream: &mut impl Read, buffer: &mut [u8]) -> io::Result<bool> {
+    match stream.read(buffer)? {
+        0 => Ok(false),
+        n => stream.read_exact(&mut buffer[n..]).map(|_| true),
+    }
+}
+
+fn serve_raw<S: Read + Write>(stream: &mut S, connection: &mut Connection) -> io::Result<()> {
+    let mut buffer = alloc::vec![0u8; RAW_BUFFER_SIZE];
+    loop {
+        let n = stream.read(&mut buffer)?;
+        if n == 0 {
+            return Ok(());
+        }
+        stream.write_all(&connection.respond(&buffer[..n]))?;
+    }
+}
+
+fn serve_wrapper<S: Read + Write>(stream: &mut S, connection: &mut Connection) -> io::Result<()> {
+    let mut header = [0u8; WRAPPER_HEADER_SIZE];
+    loop {
+        if !read_exact_or_eof(stream, &mut header)? {
+            return Ok(());
+        }
+        if header[..2] != WRAPPER_VERSION {
+            return Err(invalid_data("unsupported wrapper version"));
+        }
+        let mut request = alloc::vec![0u8; u16::from_be_bytes([header[6], header[7]]).into()];
+        stream.read_exact(&mut request)?;
+
+        let response = connection.respond(&request);
+        let length = u16::try_from(response.len()).map_err(invalid_data)?;
+        let mut frame = Vec::with_capacity(WRAPPER_HEADER_SIZE + response.len());
+        frame.extend_from_slice(&WRAPPER_VERSION);
+        frame.extend_from_slice(&header[4..6]); // our wPort is the request's destination
+        frame.extend_from_slice(&header[2..4]);
+        frame.extend_from_slice(&length.to_be_bytes());
+        frame.extend_from_slice(&response);
+        stream.write_all(&frame)?;
+    }
+}
+
+/// Server side of an HDLC link.
+struct HdlcLink {
+    sequence: HdlcSequence,
+    received: Vec<u8>,
+    frame_buffer: [u8; MAX_HDLC_FRAME_SIZE],
+}
+
+impl HdlcLink {
+    /// Reads the next complete frame; `None` when the peer closed the connection.
+    fn read_frame(&mut self, stream: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+        let mut chunk = [0u8; MAX_HDLC_FRAME_SIZE];
+        loop {
+            let fill = skip_fill_flags(&self.received);
+            self.received.drain(..fill);
+            if let Some(len) = complete_frame_len(&self.received).map_err(invalid_data)? {
+                return Ok(Some(self.received.drain(..len).collect()));
+            }
+            let n = stream.read(&mut chunk)?;
+            if n == 0 {
+                return Ok(None);
+            }
+            self.received.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    fn send(
+        &mut self,
+        stream: &mut impl Write,
+        addresses: &(HdlcAddress, HdlcAddress),
+        control: u8,
+        segmented: bool,
+        llc: &[u8],
+        data: &[u8],
+    ) -> io::Result<()> {
+        let (client, server) = addresses;
+        let len =
+            encode_frame(&mut self.frame_buffer, client, server, control, segmented, llc, data)
+                .map_err(invalid_data)?;
+        stream.write_all(&self.frame_buffer[..len])
+    }
+}
+
+/// Returns the (client, server) addresses of a frame.
+fn frame_addresses(frame: &[u8]) -> Result<(HdlcAddress, HdlcAddress), HdlcError> {
+    // Flag and two format bytes precede the destination (server) address
+    let (server, server_len) = HdlcAddress::decode(frame.get(3..).unwrap_or_default())?;
+    let (client, _) = HdlcAddress::decode(frame.get(3 + server_len..).unwrap_or_default())?;
+    Ok((client, server))
+}
+
+fn serve_hdlc<S: Read + Write>(stream: &mut S, connection: &mut Connection) -> io::Result<()> {
+    let mut link = HdlcLink {
+        sequence: HdlcSequence::default(),
+        received: Vec::new(),
+        frame_buffer: [0u8; MAX_HDLC_FRAME_SIZE],
+    };
+    let mut request = Vec::new();
+    let mut first_segment = true;
+
+    while let Some(frame) = link.read_frame(stream)? {
+        let decoded = decode_frame(&frame).map_err(invalid_data)?;
+        let addresses = frame_addresses(&frame).map_err(invalid_data)?;
+        let control = decoded.control;
+
+        match control & !HDLC_CONTROL_POLL_FINAL {
+            HDLC_SNRM => {
+                link.sequence = HdlcSequence::default();
+                request.clear();
+                first_segment = true;
+                link.send(stream, &addresses, HDLC_UA, false, &[], &[])?;
+            }
+            HDLC_DISC => {
+                connection.reset();
+                link.send(stream, &addresses, HDLC_UA, false, &[], &[])?;
+            }
+            _ if is_information_frame(control) => {
+                link.sequence.acknowledge(control);
+                let mut information = decoded.information;
+                if first_segment {
+                    // Only the first segment carries the LLC header
+                    information = information.get(HDLC_LLC_SIZE..).unwrap_or_default();
+                }
+                request.extend_from_slice(information);
+
+                if decoded.segmented {
+                    first_segment = false;
+                    let control = link.sequence.receive_ready_control();
+                    link.send(stream, &addresses, control, false, &[], &[])?;
+                    continue;
+                }
+                first_segment = true;
+
+                let response = connection.respond(&core::mem::take(&mut request));
+                let mut llc: &[u8] = &LLC_RESPONSE_HEADER;
+                let mut remaining = &response[..];
+                loop {
+                    let (segment, rest) = remaining
+                        .split_at(remaining.len().min(HDLC_DEFAULT_MAX_INFO_LENGTH - llc.len()));
+                    let segmented = !rest.is_empty();
+                    let control = link.sequence.next_information_control();
+                    link.send(stream, &addresses, control, segmented, llc, segment)?;
+                    if !segmented {
+                        break;
+                    }
+
+                    // The client requests each further segment with RR
+                    let Some(frame) = link.read_frame(stream)? else {
+                        return Ok(());
+                    };
+                    let control = decode_frame(&frame).map_err(invalid_data)?.control;
+                    if !is_receive_ready(control) {
+                        return Err(invalid_data("expected RR during segmented response"));
+                    }
+                    llc = &[];
+                    remaining = rest;
+                }
+            }
+            // Stray RR frames and unsupported commands are ignored
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::association::{AareApdu, AarqApdu};
+    use crate::server::ServerError;
+    use crate::transport::hdlc::HdlcTransport;
+    use crate::transport::sync::Transport;
+    use std::net::TcpStream;
+
+    fn listen(framing: Framing) -> std::net::SocketAddr {
+        // The serial number is longer than one HDLC frame
+        let description = format!(
+            r#"{{"objects": [{{"class": 1, "obis": "0.0.96.1.0.255",
+                "value": {{"type": "visible-string", "value": "{}"}}}}]}}"#,
+            "1".repeat(300)
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let simulator = Arc::new(Mutex::new(Simulator::from_json(&description).unwrap()));
+        thread::spawn(move || serve(listener, simulator, framing));
+        address
+    }
+
+    #[test]
+    fn test_wrapper() {
+        let mut stream = TcpStream::connect(listen(Framing::Wrapper)).unwrap();
+        let aarq = AarqApdu::new_simple_ln(1024).encode();
+        let mut frame = vec![0x00, 0x01, 0x00, 0x10, 0x00, 0x01];
+        frame.extend_from_slice(&(aarq.len() as u16).to_be_bytes());
+        frame.extend_from_slice(&aarq);
+        stream.write_all(&frame).unwrap();
+
+        let mut header = [0u8; WRAPPER_HEADER_SIZE];
+        stream.read_exact(&mut header).unwrap();
+        assert_eq!(header[..6], [0x00, 0x01, 0x00, 0x01, 0x00, 0x10]);
+        let mut aare = vec![0u8; u16::from_be_bytes([header[6], header[7]]).into()];
+        stream.read_exact(&mut aare).unwrap();
+        assert!(AareApdu::parse(&aare).unwrap().1.is_accepted());
+    }
+
+    #[test]
+    fn test_respond_after_poisoned_simulator() {
+        let simulator = Mutex::new(Simulator::from_json(r#"{"objects": []}"#).unwrap());
+        let mut connection = Connection { simulator: &simulator, session: ServerSession::new() };
+        let aarq = AarqApdu::new_simple_ln(1024).encode();
+        assert!(AareApdu::parse(&connection.respond(&aarq)).unwrap().1.is_accepted());
+
+        thread::scope(|scope| {
+            let panicked = scope.spawn(|| {
+                let _simulator = simulator.lock().unwrap();
+                panic!("request handler failed");
+            });
+            assert!(panicked.join().is_err());
+        });
+        assert!(simulator.is_poisoned());
+
+        // The association is dropped, so the request must associate again
+        let get = [0xC0, 0x01, 0xC1, 0x00, 0x01, 0x00, 0x00, 0x60, 0x01, 0x00, 0xFF, 0x02, 0x00];
+        assert_eq!(connection.respond(&get), ServerError::NotAssociated.exception_response());
+        assert!(!simulator.is_poisoned());
+        assert!(AareApdu::parse(&connection.respond(&aarq)).unwrap().1.is_accepted());
+    }
+
+    #[derive(Debug)]
+    struct Tcp(TcpStream);
+
+    impl Transport for Tcp {
+        type Error = io::Error;
+
+        fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+            self.0.write_all(data)
+        }
+
+        fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+            self.0.read(buffer)
+        }
+    }
+
+    #[test]
+    fn test_hdlc_with_segmented_response() {
+        let stream = TcpStream::connect(listen(Framing::Hdlc)).unwrap();
+        let mut hdlc = HdlcTransport::new(Tcp(stream), 0x10, 0x01);
+
+        let aarq = AarqApdu::new_simple_ln(1024).encode();
+        hdlc.send(&aarq).unwrap();
+        let mut buffer = [0u8; 1024];
+        let len = hdlc.recv(&mut buffer).unwrap();
+        assert!(AareApdu::parse(&buffer[..len]).unwrap().1.is_accepted());
+
+        let request = crate::get::GetRequest::Normal(crate::get::GetRequestNormal {
+            invoke_id: 0xC1,
+            class_id: 1,
+            instance_id: crate::ObisCode::new(0, 0, 96, 1, 0, 255),
+            attribute_id: 2,
+            access_selection: None,
+        });
+        hdlc.send(&request.encode()).unwrap();
+        let len = hdlc.recv(&mut buffer).unwrap();
+        assert!(len > 300);
+        assert_eq!(buffer[..2], [0xC4, 0x01]);
+    }
+}
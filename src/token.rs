@@ -0,0 +1,143 @@
+//! Token status of the Token Gateway (class 115)
+//!
+//! Prepayment meters take credit as tokens, e.g. the 20-digit tokens of the
+//! STS standard, entered through the `enter` method of the Token Gateway.
+//! The gateway reports the outcome of the last token in its `token_status`
+//! attribute.
+//!
+//! These types are shared by the client, which enters tokens and reads the
+//! status back, and the [`TokenGateway`](crate::cosem::token_gateway) object
+//! of the server.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use dlms_cosem::Data;
+//! use dlms_cosem::token::{TokenStatus, TokenStatusCode};
+//!
+//! let data = Data::Structure(vec![Data::Enum(3), Data::BitString(vec![])]);
+//! let status = TokenStatus::from_data(&data).unwrap();
+//! assert_eq!(status.code, TokenStatusCode::ExecutionOk);
+//! assert!(status.code.is_success());
+//! ```
+
+use core::fmt;
+
+use crate::Data;
+use crate::get::DataAccessResult;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// Outcome of the last token, `token_status_code` of attribute 6.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TokenStatusCode {
+    /// The token has the expected format
+    FormatOk = 0,
+    /// The token is authentic
+    AuthenticationOk = 1,
+    /// The token is valid for this meter
+    ValidationOk = 2,
+    /// The token was executed
+    ExecutionOk = 3,
+    /// The token is malformed
+    FormatFailure = 4,
+    /// The token failed authentication
+    AuthenticationFailure = 5,
+    /// The token is not valid for this meter, e.g. already used
+    ValidationFailure = 6,
+    /// The token could not be executed
+    ExecutionFailure = 7,
+    /// The token was received and is not processed yet
+    Received = 8,
+}
+
+impl TokenStatusCode {
+    /// Converts a token_status_code enum value.
+    ///
+    /// Returns Err if value is not in range 0-8.
+    pub fn from_u8(value: u8) -> Result<Self, DataAccessResult> {
+        match value {
+            0 => Ok(Self::FormatOk),
+            1 => Ok(Self::AuthenticationOk),
+            2 => Ok(Self::ValidationOk),
+            3 => Ok(Self::ExecutionOk),
+            4 => Ok(Self::FormatFailure),
+            5 => Ok(Self::AuthenticationFailure),
+            6 => Ok(Self::ValidationFailure),
+            7 => Ok(Self::ExecutionFailure),
+            8 => Ok(Self::Received),
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+
+    /// Returns whether the token went through, i.e. was executed.
+    pub fn is_success(self) -> bool {
+        self == Self::ExecutionOk
+    }
+
+    /// Returns whether the token was rejected.
+    pub fn is_failure(self) -> bool {
+        matches!(
+            self,
+            Self::FormatFailure
+                | Self::AuthenticationFailure
+                | Self::ValidationFailure
+                | Self::ExecutionFailure
+        )
+    }
+}
+
+impl fmt::Display for TokenStatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::FormatOk => "token format ok",
+            Self::AuthenticationOk => "token authentication ok",
+            Self::ValidationOk => "token validation ok",
+            Self::ExecutionOk => "token executed",
+            Self::FormatFailure => "token format failure",
+            Self::AuthenticationFailure => "token authentication failure",
+            Self::ValidationFailure => "token validation failure",
+            Self::ExecutionFailure => "token execution failure",
+            Self::Received => "token received, not processed yet",
+        })
+    }
+}
+
+/// Status of the last token, `{token_status_code, token_status_data}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TokenStatus {
+    /// Outcome
+    pub code: TokenStatusCode,
+    /// Bit string with details, specific to the token standard
+    pub data: Vec<u8>,
+}
+
+impl TokenStatus {
+    /// Creates a status without details.
+    pub fn new(code: TokenStatusCode) -> Self {
+        Self { code, data: Vec::new() }
+    }
+
+    /// Encodes the status as `{token_status_code, token_status_data}`.
+    pub fn to_data(&self) -> Data {
+        Data::Structure(vec![Data::Enum(self.code as u8), Data::BitString(self.data.clone())])
+    }
+
+    /// Parses `{token_status_code, token_status_data}`.
+    pub fn from_data(data: &Data) -> Result<Self, DataAccessResult> {
+        match data {
+            Data::Structure(fields) => match fields.as_slice() {
+                [Data::Enum(code), Data::BitString(data)] => {
+                    Ok(Self { code: TokenStatusCode::from_u8(*code)?, data: data.clone() })
+                }
+                _ => Err(DataAccessResult::TypeUnmatched),
+            },
+            _ => Err(DataAccessResult::TypeUnmatched),
+        }
+    }
+}
@@ -23,6 +23,14 @@
 //! - [`hdlc::HdlcTransport`] - Synchronous HDLC wrapper (feature: `transport-hdlc`)
 //! - [`hdlc::AsyncHdlcTransport`] - Async HDLC wrapper (feature: `transport-hdlc-async`)
 //!
+//! ## In-Memory Transports (Testing)
+//!
+//! - [`memory::MemoryTransport`] - Connected in-memory pair (feature: `transport-memory`)
+//! - [`memory::ScriptedTransport`] - Scripted mock meter (feature: `transport-memory`)
+//! - [`record::RecordingTransport`] - Records a session to JSON lines (feature: `transport-record`)
+//! - [`record::ReplayTransport`] - Replays a recorded session (feature: `transport-record`)
+//! - Async variants with the `async-client` feature
+//!
 //! # Examples
 //!
 //! ## Synchronous TCP Transport
@@ -85,6 +93,12 @@ pub mod tcp;
 #[cfg(any(feature = "transport-hdlc", feature = "transport-hdlc-async"))]
 pub mod hdlc;
 
+#[cfg(feature = "transport-memory")]
+pub mod memory;
+
+#[cfg(feature = "transport-record")]
+pub mod record;
+
 // Re-export commonly used types for convenience
 #[cfg(feature = "client")]
 pub use sync::Transport;
@@ -116,4 +130,4 @@ pub use hdlc::AsyncHdlcTransport;
 
 // Re-export HDLC types and constants (from parent module)
 #[cfg(any(feature = "transport-hdlc", feature = "transport-hdlc-async"))]
-pub use hdlc::{HDLC_FLAG, HdlcError, MAX_HDLC_FRAME_SIZE};
+pub use hdlc::{HDLC_FLAG, HdlcAddress, HdlcAddressSize, HdlcError, MAX_HDLC_FRAME_SIZE};
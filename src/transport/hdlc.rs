@@ -165,13 +165,54 @@ pub(crate) const FCS16_XOR_OUTPUT: u16 = 0xFFFF;
 // HDLC Protocol Constants
 // ============================================================================
 
-/// Frame Format Type 3 identifier (1010yyyy where yyyy = length field size).
+/// Frame Format Type 3 identifier (1010SLLL where S = segmentation, L = length bits 10-8).
 ///
-/// Format 0xA0 indicates a single-byte length field.
+/// Format 0xA0 indicates an unsegmented frame whose length fits in the second format byte.
 pub(crate) const HDLC_FORMAT_TYPE_3: u8 = 0xA0;
 
-/// Control field for I-frame (Information frame, N(S)=0, N(R)=0, P/F=0).
-pub(crate) const HDLC_CONTROL_I_FRAME: u8 = 0x10;
+/// Mask selecting the frame format type nibble of the first format byte.
+pub(crate) const HDLC_FORMAT_TYPE_MASK: u8 = 0xF0;
+
+/// Segmentation bit (S) of the frame format field.
+///
+/// Per IEC 62056-46, a set S bit means the information field continues in the next frame.
+pub(crate) const HDLC_FORMAT_SEGMENTATION_BIT: u8 = 0x08;
+
+/// Mask selecting frame length bits 10-8 from the first format byte.
+pub(crate) const HDLC_FORMAT_LENGTH_HIGH_MASK: u8 = 0x07;
+
+/// Size of the frame format field in bytes (type + segmentation + 11-bit length).
+pub(crate) const HDLC_FORMAT_SIZE: usize = 2;
+
+/// Largest value representable by the 11-bit frame length sub-field.
+pub(crate) const HDLC_MAX_FRAME_LENGTH: usize = 0x07FF;
+
+/// Poll/Final bit of the control field.
+pub(crate) const HDLC_CONTROL_POLL_FINAL: u8 = 0x10;
+
+/// Mask selecting the frame kind bit of the control field (0 = I-frame).
+pub(crate) const HDLC_CONTROL_I_FRAME_MASK: u8 = 0x01;
+
+/// Mask selecting the supervisory frame kind bits of the control field.
+pub(crate) const HDLC_CONTROL_S_FRAME_MASK: u8 = 0x0F;
+
+/// Supervisory RR (Receive Ready) frame identifier (RRR P 0001).
+pub(crate) const HDLC_CONTROL_RR: u8 = 0x01;
+
+/// Bit position of the send sequence number N(S) in an I-frame control field.
+pub(crate) const HDLC_CONTROL_NS_SHIFT: u8 = 1;
+
+/// Bit position of the receive sequence number N(R) in I- and S-frame control fields.
+pub(crate) const HDLC_CONTROL_NR_SHIFT: u8 = 5;
+
+/// Mask for a 3-bit HDLC sequence number (modulo 8 operation).
+pub(crate) const HDLC_SEQUENCE_MASK: u8 = 0x07;
+
+/// Default maximum information field length in bytes.
+///
+/// Per IEC 62056-46, this is the value in effect when no other length has been
+/// negotiated in the SNRM/UA exchange.
+pub const HDLC_DEFAULT_MAX_INFO_LENGTH: usize = 128;
 
 /// Size of FCS (Frame Check Sequence) in bytes.
 pub(crate) const HDLC_FCS_SIZE: usize = 2;
@@ -184,8 +225,9 @@ pub(crate) const HDLC_LLC_SIZE: usize = 3;
 
 /// Minimum valid HDLC frame size in bytes.
 ///
-/// Includes: Flag(1) + Format(1) + Length(1) + Addr(2) + Ctrl(1) + HCS(2) + FCS(2) + Flag(1) = 11 bytes minimum
-pub(crate) const HDLC_MIN_FRAME_SIZE: usize = 11;
+/// Includes: Flag(1) + Format(1) + Length(1) + Addr(2) + Ctrl(1) + FCS(2) + Flag(1) = 9 bytes minimum
+/// (frames without an information field, such as RR, carry no HCS)
+pub(crate) const HDLC_MIN_FRAME_SIZE: usize = 9;
 
 /// Maximum HDLC overhead in bytes (conservative estimate for buffer sizing).
 ///
@@ -195,9 +237,43 @@ pub(crate) const HDLC_MAX_OVERHEAD_BYTES: usize = 20;
 /// Number of flag bytes in a complete frame (opening + closing).
 pub(crate) const HDLC_FLAG_COUNT: usize = 2;
 
+/// Largest information field that fits in a frame built in the transport buffers.
+///
+/// Leaves room for the HDLC overhead within [`MAX_HDLC_FRAME_SIZE`].
+pub const HDLC_MAX_INFO_LENGTH: usize = MAX_HDLC_FRAME_SIZE - HDLC_MAX_OVERHEAD_BYTES;
+
+/// Smallest frame buffer size accepted by the transport wrappers.
+///
+/// Leaves room for the HDLC overhead plus at least one APDU byte after the LLC header.
+pub const HDLC_MIN_FRAME_BUFFER_SIZE: usize = HDLC_MAX_OVERHEAD_BYTES + HDLC_LLC_SIZE + 1;
+
 /// LSB mask for HDLC address extension bit (0 = more bytes, 1 = last byte).
 pub(crate) const HDLC_ADDRESS_LSB_MASK: u8 = 0x01;
 
+/// Number of address bits carried by each address byte (the LSB is the extension bit).
+pub(crate) const HDLC_ADDRESS_BITS_PER_BYTE: u32 = 7;
+
+/// Mask for the 7 address bits of one address byte.
+pub(crate) const HDLC_ADDRESS_BYTE_VALUE_MASK: u16 = 0x7F;
+
+/// Largest upper/lower address value in a 1-byte sub-field (7 bits).
+pub const HDLC_ADDRESS_MAX_1_BYTE: u16 = 0x7F;
+
+/// Largest upper/lower address value in a 2-byte sub-field (14 bits).
+pub const HDLC_ADDRESS_MAX_2_BYTES: u16 = 0x3FFF;
+
+/// All-station (broadcast) address in a 1-byte sub-field.
+pub const HDLC_BROADCAST_ADDRESS_1_BYTE: u16 = HDLC_ADDRESS_MAX_1_BYTE;
+
+/// All-station (broadcast) address in a 2-byte sub-field.
+pub const HDLC_BROADCAST_ADDRESS_2_BYTES: u16 = HDLC_ADDRESS_MAX_2_BYTES;
+
+/// Divisor of the default serial-number-to-physical-address formula.
+pub(crate) const HDLC_SERIAL_NUMBER_MODULUS: u64 = 10_000;
+
+/// Offset of the default serial-number-to-physical-address formula.
+pub(crate) const HDLC_SERIAL_NUMBER_OFFSET: u16 = 1_000;
+
 /// Byte mask (0xFF) for extracting single bytes from larger values.
 pub(crate) const BYTE_MASK: u16 = 0xFF;
 
@@ -230,6 +306,10 @@ pub enum HdlcError {
     BufferTooSmall,
     /// Underlying transport error.
     TransportError,
+    /// Received a frame of a type not expected at this point of the exchange.
+    UnexpectedFrame,
+    /// Address value out of range for its size, or malformed address field.
+    InvalidAddress,
 }
 
 impl core::fmt::Display for HdlcError {
@@ -242,6 +322,8 @@ impl core::fmt::Display for HdlcError {
             Self::FcsError => write!(f, "HDLC FCS verification failed"),
             Self::BufferTooSmall => write!(f, "Output buffer too small"),
             Self::TransportError => write!(f, "Underlying transport error"),
+            Self::UnexpectedFrame => write!(f, "Unexpected HDLC frame type"),
+            Self::InvalidAddress => write!(f, "Invalid HDLC address"),
         }
     }
 }
@@ -295,75 +377,559 @@ pub fn compute_fcs(data: &[u8]) -> u16 {
     fcs ^ FCS16_XOR_OUTPUT
 }
 
-/// Encodes an address field using HDLC address extension mechanism (1-4 bytes, LSB extension).
-///
-/// Per IEC 62056-46, addresses are encoded with the LSB indicating whether more bytes follow:
-/// - LSB = 0: More address bytes follow
-/// - LSB = 1: This is the last address byte
-///
-/// # Address Ranges
-///
-/// - 1 byte: 0x00 - 0x7F
-/// - 2 bytes: 0x80 - 0x3FFF
-/// - 3 bytes: 0x4000 - 0x1FFFFF
-/// - 4 bytes: 0x200000 - 0x0FFFFFFF
+/// Size of a server HDLC address field in bytes.
 ///
-/// # Arguments
-///
-/// * `address` - The address value to encode
-/// * `buffer` - Output buffer (must have at least 4 bytes)
+/// Per IEC 62056-46, the server address consists of an upper HDLC address
+/// (logical device) and an optional lower HDLC address (physical device):
 ///
-/// # Returns
+/// - 1 byte: upper address only (7 bits)
+/// - 2 bytes: 1-byte upper + 1-byte lower address (7 bits each)
+/// - 4 bytes: 2-byte upper + 2-byte lower address (14 bits each)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HdlcAddressSize {
+    /// 1-byte address field (upper address only).
+    One = 1,
+    /// 2-byte address field.
+    Two = 2,
+    /// 4-byte address field.
+    Four = 4,
+}
+
+impl HdlcAddressSize {
+    /// Returns the size in bytes.
+    pub const fn encoded_len(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the largest value of an upper or lower sub-field of this size.
+    pub const fn max_value(self) -> u16 {
+        match self {
+            Self::One | Self::Two => HDLC_ADDRESS_MAX_1_BYTE,
+            Self::Four => HDLC_ADDRESS_MAX_2_BYTES,
+        }
+    }
+
+    /// Returns the broadcast (all-station) value of a sub-field of this size.
+    pub const fn broadcast_value(self) -> u16 {
+        match self {
+            Self::One | Self::Two => HDLC_BROADCAST_ADDRESS_1_BYTE,
+            Self::Four => HDLC_BROADCAST_ADDRESS_2_BYTES,
+        }
+    }
+
+    fn from_len(len: usize) -> Option<Self> {
+        match len {
+            1 => Some(Self::One),
+            2 => Some(Self::Two),
+            4 => Some(Self::Four),
+            _ => None,
+        }
+    }
+}
+
+/// Typed HDLC address with upper (logical) and lower (physical) parts.
 ///
-/// The number of bytes written (1-4)
+/// The client address is always a 1-byte address whose logical part is the
+/// client SAP. Server addresses select a logical device (upper address) in the
+/// physical device (lower address), which is often derived from the meter
+/// serial number (see [`HdlcAddress::from_serial_number`]).
 ///
 /// # Examples
 ///
 /// ```
-/// # use dlms_cosem::transport::hdlc::encode_address;
+/// # use dlms_cosem::transport::hdlc::{HdlcAddress, HdlcAddressSize};
+/// // Management logical device (1) in physical device 0x11, 2-byte addressing
+/// let address = HdlcAddress::new(0x01, 0x11, HdlcAddressSize::Two).unwrap();
 /// let mut buffer = [0u8; 4];
-/// let len = encode_address(0x01, &mut buffer);
-/// assert_eq!(len, 1);
-/// assert_eq!(buffer[0], 0x03); // (0x01 << 1) | 0x01
+/// assert_eq!(address.encode(&mut buffer), 2);
+/// assert_eq!(&buffer[..2], &[0x02, 0x23]);
+///
+/// let (decoded, len) = HdlcAddress::decode(&buffer).unwrap();
+/// assert_eq!(len, 2);
+/// assert_eq!(decoded, address);
 /// ```
-pub fn encode_address(address: u32, buffer: &mut [u8]) -> usize {
-    const ADDR_1BYTE_MAX: u32 = 0x7F;
-    const ADDR_2BYTE_MAX: u32 = 0x3FFF;
-    const ADDR_3BYTE_MAX: u32 = 0x1FFFFF;
-    const ADDR_FE_MASK: u32 = 0xFE;
-    const ADDR_SHIFT_7: u32 = 7;
-    const ADDR_SHIFT_14: u32 = 14;
-    const ADDR_SHIFT_21: u32 = 21;
-
-    if address <= ADDR_1BYTE_MAX {
-        // 1-byte address
-        buffer[0] = ((address << 1) | HDLC_ADDRESS_LSB_MASK as u32) as u8;
-        1
-    } else if address <= ADDR_2BYTE_MAX {
-        // 2-byte address
-        buffer[0] = ((address << 1) & ADDR_FE_MASK) as u8;
-        buffer[1] = (((address >> ADDR_SHIFT_7) << 1) | HDLC_ADDRESS_LSB_MASK as u32) as u8;
-        2
-    } else if address <= ADDR_3BYTE_MAX {
-        // 3-byte address
-        buffer[0] = ((address << 1) & ADDR_FE_MASK) as u8;
-        buffer[1] = (((address >> ADDR_SHIFT_7) << 1) & ADDR_FE_MASK) as u8;
-        buffer[2] = (((address >> ADDR_SHIFT_14) << 1) | HDLC_ADDRESS_LSB_MASK as u32) as u8;
-        3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HdlcAddress {
+    /// Upper HDLC address (logical device, or client SAP).
+    pub logical: u16,
+    /// Lower HDLC address (physical device). Not encoded for 1-byte addresses.
+    pub physical: u16,
+    /// Size of the encoded address field.
+    pub size: HdlcAddressSize,
+}
+
+impl HdlcAddress {
+    /// Creates an address, validating both parts against the field size.
+    ///
+    /// For [`HdlcAddressSize::One`], `physical` must be 0.
+    pub fn new(logical: u16, physical: u16, size: HdlcAddressSize) -> Result<Self, HdlcError> {
+        let max = size.max_value();
+        let physical_max = if size == HdlcAddressSize::One { 0 } else { max };
+        if logical > max || physical > physical_max {
+            return Err(HdlcError::InvalidAddress);
+        }
+        Ok(Self { logical, physical, size })
+    }
+
+    /// Creates a 1-byte client address from a client SAP.
+    pub fn client(sap: u8) -> Result<Self, HdlcError> {
+        Self::new(sap as u16, 0, HdlcAddressSize::One)
+    }
+
+    /// Creates the broadcast (all-station) server address for the given size.
+    pub const fn broadcast(size: HdlcAddressSize) -> Self {
+        let value = size.broadcast_value();
+        let physical = if matches!(size, HdlcAddressSize::One) { 0 } else { value };
+        Self { logical: value, physical, size }
+    }
+
+    /// Returns `true` if the physical (or, for 1-byte addresses, logical) part is broadcast.
+    pub fn is_broadcast(&self) -> bool {
+        let broadcast = self.size.broadcast_value();
+        match self.size {
+            HdlcAddressSize::One => self.logical == broadcast,
+            HdlcAddressSize::Two | HdlcAddressSize::Four => self.physical == broadcast,
+        }
+    }
+
+    /// Computes the lower HDLC address from a meter serial number.
+    ///
+    /// Uses the IEC 62056-46 default formula `SN mod 10000 + 1000`, which
+    /// yields a value in the range 1000-10999 and therefore needs a 2-byte
+    /// lower address.
+    pub fn physical_from_serial_number(serial_number: u64) -> u16 {
+        (serial_number % HDLC_SERIAL_NUMBER_MODULUS) as u16 + HDLC_SERIAL_NUMBER_OFFSET
+    }
+
+    /// Creates a 4-byte server address from a logical device and a meter serial number.
+    ///
+    /// See [`physical_from_serial_number`](Self::physical_from_serial_number).
+    pub fn from_serial_number(logical: u16, serial_number: u64) -> Result<Self, HdlcError> {
+        Self::new(logical, Self::physical_from_serial_number(serial_number), HdlcAddressSize::Four)
+    }
+
+    /// Returns the encoded length in bytes.
+    pub fn encoded_len(&self) -> usize {
+        self.size.encoded_len()
+    }
+
+    /// Encodes the address field into `buffer` (which must hold at least 4 bytes).
+    ///
+    /// Each byte carries 7 address bits, most significant first; the LSB of
+    /// the last byte is set to terminate the field.
+    ///
+    /// Returns the number of bytes written (1, 2 or 4).
+    pub fn encode(&self, buffer: &mut [u8]) -> usize {
+        let len = self.size.encoded_len();
+        let sub_field_len = match self.size {
+            HdlcAddressSize::One | HdlcAddressSize::Two => 1,
+            HdlcAddressSize::Four => 2,
+        };
+
+        write_address_sub_field(&mut buffer[..sub_field_len], self.logical);
+        if self.size != HdlcAddressSize::One {
+            write_address_sub_field(&mut buffer[sub_field_len..len], self.physical);
+        }
+        buffer[len - 1] |= HDLC_ADDRESS_LSB_MASK;
+        len
+    }
+
+    /// Decodes an address field from the start of `buffer`.
+    ///
+    /// Returns the address and the number of bytes consumed.
+    pub fn decode(buffer: &[u8]) -> Result<(Self, usize), HdlcError> {
+        let len = address_field_len(buffer).ok_or(HdlcError::InvalidAddress)?;
+        let size = HdlcAddressSize::from_len(len).ok_or(HdlcError::InvalidAddress)?;
+        let field = &buffer[..len];
+
+        let address = match size {
+            HdlcAddressSize::One => {
+                Self { logical: read_address_sub_field(field), physical: 0, size }
+            }
+            HdlcAddressSize::Two | HdlcAddressSize::Four => {
+                let (upper, lower) = field.split_at(len / 2);
+                Self {
+                    logical: read_address_sub_field(upper),
+                    physical: read_address_sub_field(lower),
+                    size,
+                }
+            }
+        };
+        Ok((address, len))
+    }
+}
+
+impl From<u32> for HdlcAddress {
+    /// Converts a combined address value to a typed address.
+    ///
+    /// Values up to 0x7F become 1-byte addresses. Larger values are split into
+    /// upper and lower parts, using a 2-byte field (upper = bits 13-7,
+    /// lower = bits 6-0) when they fit in 14 bits and a 4-byte field
+    /// (upper = bits 27-14, lower = bits 13-0) otherwise. Bits above 27 are ignored.
+    fn from(value: u32) -> Self {
+        const COMBINED_MAX_2_BYTES: u32 = HDLC_ADDRESS_MAX_2_BYTES as u32;
+        const SUB_FIELD_BITS_4_BYTES: u32 = 2 * HDLC_ADDRESS_BITS_PER_BYTE;
+
+        if value <= HDLC_ADDRESS_MAX_1_BYTE as u32 {
+            Self { logical: value as u16, physical: 0, size: HdlcAddressSize::One }
+        } else if value <= COMBINED_MAX_2_BYTES {
+            Self {
+                logical: (value >> HDLC_ADDRESS_BITS_PER_BYTE) as u16,
+                physical: value as u16 & HDLC_ADDRESS_MAX_1_BYTE,
+                size: HdlcAddressSize::Two,
+            }
+        } else {
+            Self {
+                logical: (value >> SUB_FIELD_BITS_4_BYTES) as u16 & HDLC_ADDRESS_MAX_2_BYTES,
+                physical: value as u16 & HDLC_ADDRESS_MAX_2_BYTES,
+                size: HdlcAddressSize::Four,
+            }
+        }
+    }
+}
+
+/// Writes a 1- or 2-byte address sub-field (7 bits per byte, MSB group first, LSB clear).
+fn write_address_sub_field(buffer: &mut [u8], value: u16) {
+    let last = buffer.len() - 1;
+    for (index, byte) in buffer.iter_mut().enumerate() {
+        let shift = (last - index) as u32 * HDLC_ADDRESS_BITS_PER_BYTE;
+        *byte = (((value >> shift) & HDLC_ADDRESS_BYTE_VALUE_MASK) as u8) << 1;
+    }
+}
+
+/// Reads a 1- or 2-byte address sub-field (7 bits per byte, MSB group first).
+fn read_address_sub_field(buffer: &[u8]) -> u16 {
+    buffer.iter().fold(0, |value, byte| (value << HDLC_ADDRESS_BITS_PER_BYTE) | (byte >> 1) as u16)
+}
+
+/// Returns the length of the address field starting at `buffer[0]`.
+///
+/// The address field ends with the first byte whose LSB is set. Returns `None`
+/// if no such byte occurs within the first 4 bytes of `buffer`.
+fn address_field_len(buffer: &[u8]) -> Option<usize> {
+    const HDLC_MAX_ADDRESS_SIZE: usize = 4;
+
+    buffer
+        .iter()
+        .take(HDLC_MAX_ADDRESS_SIZE)
+        .position(|byte| byte & HDLC_ADDRESS_LSB_MASK != 0)
+        .map(|index| index + 1)
+}
+
+/// Writes a 16-bit check sequence in little-endian byte order.
+fn write_check_sequence(buffer: &mut [u8], value: u16) {
+    buffer[0] = (value & BYTE_MASK) as u8;
+    buffer[1] = ((value >> BITS_PER_BYTE) & BYTE_MASK) as u8;
+}
+
+/// Encodes a complete HDLC frame (including both flags) into `buffer`.
+///
+/// The information field is formed by `llc` followed by `data`, so that the
+/// LLC header only has to be passed for the first segment of an APDU. If both
+/// are empty, the frame carries no information field and therefore no HCS.
+///
+/// The 11-bit frame length is split across the two format bytes, and the
+/// segmentation bit is set when `segmented` is `true`.
+///
+/// Returns the number of bytes written.
+pub(crate) fn encode_frame(
+    buffer: &mut [u8],
+    destination: &HdlcAddress,
+    source: &HdlcAddress,
+    control: u8,
+    segmented: bool,
+    llc: &[u8],
+    data: &[u8],
+) -> Result<usize, HdlcError> {
+    const HDLC_ADDRESS_SCRATCH_SIZE: usize = 4;
+
+    let mut destination_field = [0u8; HDLC_ADDRESS_SCRATCH_SIZE];
+    let destination_len = destination.encode(&mut destination_field);
+    let mut source_field = [0u8; HDLC_ADDRESS_SCRATCH_SIZE];
+    let source_len = source.encode(&mut source_field);
+
+    let information_len = llc.len() + data.len();
+    let header_len = HDLC_FORMAT_SIZE + destination_len + source_len + 1;
+    let frame_length = if information_len == 0 {
+        header_len + HDLC_FCS_SIZE
+    } else {
+        header_len + HDLC_HCS_SIZE + information_len + HDLC_FCS_SIZE
+    };
+
+    if frame_length > HDLC_MAX_FRAME_LENGTH {
+        return Err(HdlcError::FrameTooLarge);
+    }
+    if frame_length + HDLC_FLAG_COUNT > buffer.len() {
+        return Err(HdlcError::BufferTooSmall);
+    }
+
+    let mut pos = 0;
+
+    // Opening flag
+    buffer[pos] = HDLC_FLAG;
+    pos += 1;
+
+    // Frame format field: 1010 S LLL LLLLLLLL
+    let mut format =
+        HDLC_FORMAT_TYPE_3 | ((frame_length >> BITS_PER_BYTE) as u8 & HDLC_FORMAT_LENGTH_HIGH_MASK);
+    if segmented {
+        format |= HDLC_FORMAT_SEGMENTATION_BIT;
+    }
+    buffer[pos] = format;
+    buffer[pos + 1] = (frame_length & BYTE_MASK as usize) as u8;
+    pos += HDLC_FORMAT_SIZE;
+
+    // Destination and source addresses
+    buffer[pos..pos + destination_len].copy_from_slice(&destination_field[..destination_len]);
+    pos += destination_len;
+    buffer[pos..pos + source_len].copy_from_slice(&source_field[..source_len]);
+    pos += source_len;
+
+    // Control field
+    buffer[pos] = control;
+    pos += 1;
+
+    if information_len > 0 {
+        // HCS covers format, addresses and control
+        let hcs = compute_fcs(&buffer[1..pos]);
+        write_check_sequence(&mut buffer[pos..], hcs);
+        pos += HDLC_HCS_SIZE;
+
+        buffer[pos..pos + llc.len()].copy_from_slice(llc);
+        pos += llc.len();
+        buffer[pos..pos + data.len()].copy_from_slice(data);
+        pos += data.len();
+    }
+
+    // FCS covers everything between the flags
+    let fcs = compute_fcs(&buffer[1..pos]);
+    write_check_sequence(&mut buffer[pos..], fcs);
+    pos += HDLC_FCS_SIZE;
+
+    // Closing flag
+    buffer[pos] = HDLC_FLAG;
+    pos += 1;
+
+    Ok(pos)
+}
+
+/// Fields of a received HDLC frame needed by the transport wrappers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DecodedFrame<'a> {
+    /// Control field.
+    pub control: u8,
+    /// Whether the segmentation bit was set.
+    pub segmented: bool,
+    /// Raw information field (including the LLC header, if any).
+    pub information: &'a [u8],
+}
+
+/// Decodes a complete HDLC frame (including both flags).
+///
+/// Verifies the flags, the frame length, the HCS (when an information field is
+/// present) and the FCS.
+pub(crate) fn decode_frame(frame: &[u8]) -> Result<DecodedFrame<'_>, HdlcError> {
+    if frame.len() < HDLC_MIN_FRAME_SIZE {
+        return Err(HdlcError::FrameTooShort);
+    }
+    if frame[0] != HDLC_FLAG || frame[frame.len() - 1] != HDLC_FLAG {
+        return Err(HdlcError::InvalidFlag);
+    }
+
+    let body = &frame[1..frame.len() - 1];
+    let format = body[0];
+    if format & HDLC_FORMAT_TYPE_MASK != HDLC_FORMAT_TYPE_3 {
+        return Err(HdlcError::InvalidFrame);
+    }
+    let length =
+        ((format & HDLC_FORMAT_LENGTH_HIGH_MASK) as usize) << BITS_PER_BYTE | body[1] as usize;
+    if length != body.len() {
+        return Err(HdlcError::InvalidFrame);
+    }
+
+    let fcs_pos = body.len() - HDLC_FCS_SIZE;
+    let expected_fcs = compute_fcs(&body[..fcs_pos]);
+    let actual_fcs = u16::from_le_bytes([body[fcs_pos], body[fcs_pos + 1]]);
+    if expected_fcs != actual_fcs {
+        return Err(HdlcError::FcsError);
+    }
+
+    let mut pos = HDLC_FORMAT_SIZE;
+    pos += address_field_len(&body[pos..fcs_pos]).ok_or(HdlcError::InvalidFrame)?;
+    pos += address_field_len(&body[pos..fcs_pos]).ok_or(HdlcError::InvalidFrame)?;
+    if pos == fcs_pos {
+        return Err(HdlcError::InvalidFrame);
+    }
+    let control = body[pos];
+    pos += 1;
+
+    let information = if pos == fcs_pos {
+        &[][..]
     } else {
-        // 4-byte address
-        buffer[0] = ((address << 1) & ADDR_FE_MASK) as u8;
-        buffer[1] = (((address >> ADDR_SHIFT_7) << 1) & ADDR_FE_MASK) as u8;
-        buffer[2] = (((address >> ADDR_SHIFT_14) << 1) & ADDR_FE_MASK) as u8;
-        buffer[3] = (((address >> ADDR_SHIFT_21) << 1) | HDLC_ADDRESS_LSB_MASK as u32) as u8;
-        4
+        if pos + HDLC_HCS_SIZE > fcs_pos {
+            return Err(HdlcError::InvalidFrame);
+        }
+        let expected_hcs = compute_fcs(&body[..pos]);
+        let actual_hcs = u16::from_le_bytes([body[pos], body[pos + 1]]);
+        if expected_hcs != actual_hcs {
+            return Err(HdlcError::FcsError);
+        }
+        &body[pos + HDLC_HCS_SIZE..fcs_pos]
+    };
+
+    Ok(DecodedFrame { control, segmented: format & HDLC_FORMAT_SEGMENTATION_BIT != 0, information })
+}
+
+/// Returns the total size (including both flags) of the first frame in `buffer`.
+///
+/// Returns `Ok(None)` if more bytes are needed to determine the size or to
+/// complete the frame. Surplus opening flags (inter-frame fill) are not
+/// handled here; see [`skip_fill_flags`].
+pub(crate) fn complete_frame_len(buffer: &[u8]) -> Result<Option<usize>, HdlcError> {
+    if buffer.len() < 1 + HDLC_FORMAT_SIZE {
+        return Ok(None);
     }
+    if buffer[0] != HDLC_FLAG {
+        return Err(HdlcError::InvalidFlag);
+    }
+    if buffer[1] & HDLC_FORMAT_TYPE_MASK != HDLC_FORMAT_TYPE_3 {
+        return Err(HdlcError::InvalidFrame);
+    }
+
+    let length =
+        ((buffer[1] & HDLC_FORMAT_LENGTH_HIGH_MASK) as usize) << BITS_PER_BYTE | buffer[2] as usize;
+    let total = length + HDLC_FLAG_COUNT;
+    if total > MAX_HDLC_FRAME_SIZE {
+        return Err(HdlcError::FrameTooLarge);
+    }
+
+    Ok((buffer.len() >= total).then_some(total))
+}
+
+/// Returns the number of redundant flag bytes at the start of `buffer`.
+///
+/// Consecutive flags between frames are fill; only the last one opens the next frame.
+pub(crate) fn skip_fill_flags(buffer: &[u8]) -> usize {
+    buffer.windows(2).take_while(|pair| pair[0] == HDLC_FLAG && pair[1] == HDLC_FLAG).count()
+}
+
+/// Returns the number of bytes to drop to resynchronise on the next flag after
+/// line noise or a malformed frame header at the start of `buffer`.
+fn resync_len(buffer: &[u8]) -> usize {
+    buffer
+        .iter()
+        .skip(1)
+        .position(|&byte| byte == HDLC_FLAG)
+        .map_or(buffer.len(), |index| index + 1)
+}
+
+/// Locates the first frame in `buffer`, skipping inter-frame fill and
+/// resynchronising on the next flag after line noise or a malformed header.
+///
+/// Returns the number of leading bytes to drop, and the size of the frame that
+/// follows them if it is complete.
+pub(crate) fn find_frame(buffer: &[u8]) -> (usize, Option<usize>) {
+    let mut start = 0;
+    loop {
+        start += skip_fill_flags(&buffer[start..]);
+        match complete_frame_len(&buffer[start..]) {
+            Ok(len) => return (start, len),
+            Err(_) => start += resync_len(&buffer[start..]),
+        }
+    }
+}
+
+/// Decodes a received frame and appends its information field to
+/// `buffer[*written..]`, stripping the LLC header from the `first` segment.
+///
+/// Returns the control field and the segmentation bit.
+pub(crate) fn append_information(
+    frame: &[u8],
+    first: bool,
+    buffer: &mut [u8],
+    written: &mut usize,
+) -> Result<(u8, bool), HdlcError> {
+    let frame = decode_frame(frame)?;
+    let mut information = frame.information;
+    if first {
+        // Only the first segment carries the LLC header
+        if information.len() < HDLC_LLC_SIZE {
+            return Err(HdlcError::InvalidFrame);
+        }
+        information = &information[HDLC_LLC_SIZE..];
+    }
+    let end = *written + information.len();
+    if end > buffer.len() {
+        return Err(HdlcError::BufferTooSmall);
+    }
+    buffer[*written..end].copy_from_slice(information);
+    *written = end;
+    Ok((frame.control, frame.segmented))
+}
+
+/// Send and receive sequence state variables V(S) and V(R).
+///
+/// Both counters are modulo 8 as per ISO/IEC 13239 basic operation mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct HdlcSequence {
+    send: u8,
+    receive: u8,
+}
+
+impl HdlcSequence {
+    /// Returns the control field for the next I-frame and advances V(S).
+    pub fn next_information_control(&mut self) -> u8 {
+        let control = (self.receive << HDLC_CONTROL_NR_SHIFT)
+            | HDLC_CONTROL_POLL_FINAL
+            | (self.send << HDLC_CONTROL_NS_SHIFT);
+        self.send = (self.send + 1) & HDLC_SEQUENCE_MASK;
+        control
+    }
+
+    /// Returns the control field of an RR frame acknowledging all received I-frames.
+    pub fn receive_ready_control(&self) -> u8 {
+        (self.receive << HDLC_CONTROL_NR_SHIFT) | HDLC_CONTROL_POLL_FINAL | HDLC_CONTROL_RR
+    }
+
+    /// Updates V(R) after receiving a frame with the given control field.
+    ///
+    /// Only I-frames carry a send sequence number; other frames leave V(R) unchanged.
+    pub fn acknowledge(&mut self, control: u8) {
+        if is_information_frame(control) {
+            self.receive = ((control >> HDLC_CONTROL_NS_SHIFT) + 1) & HDLC_SEQUENCE_MASK;
+        }
+    }
+}
+
+/// Returns `true` if the control field denotes an I-frame.
+pub(crate) fn is_information_frame(control: u8) -> bool {
+    control & HDLC_CONTROL_I_FRAME_MASK == 0
+}
+
+/// Returns `true` if the control field denotes an RR supervisory frame.
+pub(crate) fn is_receive_ready(control: u8) -> bool {
+    control & HDLC_CONTROL_S_FRAME_MASK == HDLC_CONTROL_RR
+}
+
+/// How the transport wrappers read from their underlying transport.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum RecvMode {
+    /// Wait for data without a deadline.
+    Blocking,
+    /// Fail each read that does not complete within the given duration.
+    #[cfg(feature = "std")]
+    Timeout(std::time::Duration),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const CLIENT: HdlcAddress =
+        HdlcAddress { logical: 0x10, physical: 0, size: HdlcAddressSize::One };
+    const SERVER: HdlcAddress =
+        HdlcAddress { logical: 0x01, physical: 0, size: HdlcAddressSize::One };
+
     #[test]
     fn test_fcs_computation() {
         // Test vector from ISO 13239
@@ -393,78 +959,275 @@ mod tests {
     }
 
     #[test]
-    fn test_encode_address_1_byte() {
+    fn test_combined_address_1_byte() {
         let mut buffer = [0u8; 4];
-        let len = encode_address(0x01, &mut buffer);
-        assert_eq!(len, 1);
+        assert_eq!(HdlcAddress::from(0x01).encode(&mut buffer), 1);
         assert_eq!(buffer[0], 0x03); // (0x01 << 1) | HDLC_ADDRESS_LSB_MASK
+        assert_eq!(HdlcAddress::from(0x7F).encode(&mut buffer), 1);
+        assert_eq!(buffer[0], 0xFF);
     }
 
     #[test]
-    fn test_encode_address_2_bytes() {
+    fn test_combined_address_2_bytes() {
         let mut buffer = [0u8; 4];
-        let len = encode_address(0x100, &mut buffer);
-        assert_eq!(len, 2);
-        assert_eq!(buffer[0] & HDLC_ADDRESS_LSB_MASK, 0); // LSB = 0 (more bytes follow)
-        assert_eq!(buffer[1] & HDLC_ADDRESS_LSB_MASK, 1); // LSB = 1 (last byte)
+        // Upper 0x01, lower 0x11, most significant byte first
+        assert_eq!(HdlcAddress::from(0x91).encode(&mut buffer), 2);
+        assert_eq!(&buffer[..2], &[0x02, 0x23]);
+        assert_eq!(HdlcAddress::from(0x80).encode(&mut buffer), 2);
+        assert_eq!(HdlcAddress::from(0x3FFF).encode(&mut buffer), 2);
+        assert_eq!(&buffer[..2], &[0xFE, 0xFF]);
     }
 
     #[test]
-    fn test_encode_address_3_bytes() {
+    fn test_combined_address_4_bytes() {
         let mut buffer = [0u8; 4];
-        let len = encode_address(0x4000, &mut buffer);
-        assert_eq!(len, 3);
-        assert_eq!(buffer[0] & HDLC_ADDRESS_LSB_MASK, 0); // LSB = 0
-        assert_eq!(buffer[1] & HDLC_ADDRESS_LSB_MASK, 0); // LSB = 0
-        assert_eq!(buffer[2] & HDLC_ADDRESS_LSB_MASK, 1); // LSB = 1 (last byte)
+        assert_eq!(HdlcAddress::from(0x4000).encode(&mut buffer), 4);
+        assert_eq!(&buffer, &[0x00, 0x02, 0x00, 0x01]);
+        let (decoded, len) = HdlcAddress::decode(&buffer).unwrap();
+        assert_eq!((decoded, len), (HdlcAddress::from(0x4000), 4));
+    }
+
+    #[test]
+    fn test_hdlc_constants() {
+        assert_eq!(HDLC_FLAG, 0x7E);
+        assert_eq!(MAX_HDLC_FRAME_SIZE, 2048);
+        assert_eq!(LLC_HEADER, [0xE6, 0xE6, 0x00]);
+        assert_eq!(FCS16_POLYNOMIAL, 0x1021);
+        assert_eq!(FCS16_INIT, 0xFFFF);
+        assert_eq!(FCS16_XOR_OUTPUT, 0xFFFF);
+    }
+
+    #[test]
+    fn test_hdlc_error_display() {
+        assert_eq!(HdlcError::FrameTooLarge.to_string(), "HDLC frame too large");
+        assert_eq!(HdlcError::FcsError.to_string(), "HDLC FCS verification failed");
+        assert_eq!(HdlcError::TransportError.to_string(), "Underlying transport error");
+        assert_eq!(HdlcError::UnexpectedFrame.to_string(), "Unexpected HDLC frame type");
     }
 
     #[test]
-    fn test_encode_address_4_bytes() {
+    fn test_encode_decode_frame_roundtrip() {
+        let mut buffer = [0u8; MAX_HDLC_FRAME_SIZE];
+        let len =
+            encode_frame(&mut buffer, &SERVER, &CLIENT, 0x10, false, &LLC_HEADER, &[0xC0, 0x01])
+                .unwrap();
+
+        assert_eq!(buffer[0], HDLC_FLAG);
+        assert_eq!(buffer[1], HDLC_FORMAT_TYPE_3);
+        assert_eq!(buffer[2] as usize, len - HDLC_FLAG_COUNT);
+        assert_eq!(buffer[len - 1], HDLC_FLAG);
+
+        let frame = decode_frame(&buffer[..len]).unwrap();
+        assert_eq!(frame.control, 0x10);
+        assert!(!frame.segmented);
+        assert_eq!(frame.information, &[0xE6, 0xE6, 0x00, 0xC0, 0x01]);
+    }
+
+    #[test]
+    fn test_encode_frame_segmentation_bit() {
+        let mut buffer = [0u8; MAX_HDLC_FRAME_SIZE];
+        let len = encode_frame(&mut buffer, &SERVER, &CLIENT, 0x10, true, &[], &[0xAA]).unwrap();
+
+        assert_eq!(buffer[1] & HDLC_FORMAT_SEGMENTATION_BIT, HDLC_FORMAT_SEGMENTATION_BIT);
+        assert!(decode_frame(&buffer[..len]).unwrap().segmented);
+    }
+
+    #[test]
+    fn test_encode_frame_11_bit_length() {
+        let mut buffer = [0u8; MAX_HDLC_FRAME_SIZE];
+        let data = [0x55; 1000];
+        let len =
+            encode_frame(&mut buffer, &SERVER, &CLIENT, 0x10, false, &LLC_HEADER, &data).unwrap();
+
+        let length =
+            ((buffer[1] & HDLC_FORMAT_LENGTH_HIGH_MASK) as usize) << 8 | buffer[2] as usize;
+        assert_eq!(length, len - HDLC_FLAG_COUNT);
+        assert!(length > 0xFF);
+        assert_eq!(&decode_frame(&buffer[..len]).unwrap().information[3..], &data[..]);
+    }
+
+    #[test]
+    fn test_encode_frame_too_large() {
+        let mut buffer = [0u8; 4096];
+        let data = [0u8; HDLC_MAX_FRAME_LENGTH];
+        let result = encode_frame(&mut buffer, &SERVER, &CLIENT, 0x10, false, &[], &data);
+        assert_eq!(result, Err(HdlcError::FrameTooLarge));
+    }
+
+    #[test]
+    fn test_encode_frame_without_information() {
+        // RR frame: 7E A0 07 03 21 31 FCS FCS 7E (no HCS)
+        let mut buffer = [0u8; 16];
+        let len = encode_frame(&mut buffer, &SERVER, &CLIENT, 0x31, false, &[], &[]).unwrap();
+        assert_eq!(len, 9);
+        assert_eq!(&buffer[..6], &[0x7E, 0xA0, 0x07, 0x03, 0x21, 0x31]);
+
+        let frame = decode_frame(&buffer[..len]).unwrap();
+        assert!(is_receive_ready(frame.control));
+        assert!(frame.information.is_empty());
+    }
+
+    #[test]
+    fn test_decode_frame_errors() {
+        let mut buffer = [0u8; 64];
+        let len = encode_frame(&mut buffer, &SERVER, &CLIENT, 0x10, false, &LLC_HEADER, &[1, 2, 3])
+            .unwrap();
+
+        let mut corrupted = buffer;
+        corrupted[len - 4] ^= 0xFF;
+        assert_eq!(decode_frame(&corrupted[..len]), Err(HdlcError::FcsError));
+
+        let mut corrupted = buffer;
+        corrupted[0] = 0x00;
+        assert_eq!(decode_frame(&corrupted[..len]), Err(HdlcError::InvalidFlag));
+
+        assert_eq!(decode_frame(&buffer[..len - 1]), Err(HdlcError::InvalidFlag));
+        assert_eq!(decode_frame(&buffer[..4]), Err(HdlcError::FrameTooShort));
+    }
+
+    #[test]
+    fn test_complete_frame_len() {
+        let mut buffer = [0u8; 64];
+        let len =
+            encode_frame(&mut buffer, &SERVER, &CLIENT, 0x10, false, &LLC_HEADER, &[1]).unwrap();
+
+        assert_eq!(complete_frame_len(&buffer[..2]), Ok(None));
+        assert_eq!(complete_frame_len(&buffer[..len - 1]), Ok(None));
+        assert_eq!(complete_frame_len(&buffer[..len]), Ok(Some(len)));
+        assert_eq!(complete_frame_len(&buffer[..len + 5]), Ok(Some(len)));
+        assert_eq!(complete_frame_len(&[0x00, 0xA0, 0x07]), Err(HdlcError::InvalidFlag));
+    }
+
+    #[test]
+    fn test_skip_fill_flags() {
+        assert_eq!(skip_fill_flags(&[0x7E, 0x7E, 0x7E, 0xA0]), 2);
+        assert_eq!(skip_fill_flags(&[0x7E, 0xA0]), 0);
+        assert_eq!(skip_fill_flags(&[]), 0);
+    }
+
+    #[test]
+    fn test_sequence_numbers() {
+        let mut sequence = HdlcSequence::default();
+        assert_eq!(sequence.next_information_control(), 0x10);
+        assert_eq!(sequence.next_information_control(), 0x12);
+
+        // Server I-frame with N(S)=0 is acknowledged with N(R)=1
+        sequence.acknowledge(0x30);
+        assert_eq!(sequence.receive_ready_control(), 0x31);
+        assert_eq!(sequence.next_information_control(), 0x34);
+
+        // Supervisory frames do not change V(R)
+        sequence.acknowledge(0x51);
+        assert_eq!(sequence.receive_ready_control(), 0x31);
+
+        // Sequence numbers wrap modulo 8
+        for _ in 0..5 {
+            sequence.next_information_control();
+        }
+        assert_eq!(sequence.next_information_control(), 0x30);
+    }
+
+    #[test]
+    fn test_hdlc_address_one_byte() {
+        let address = HdlcAddress::new(0x10, 0, HdlcAddressSize::One).unwrap();
         let mut buffer = [0u8; 4];
-        let len = encode_address(0x200000, &mut buffer);
-        assert_eq!(len, 4);
-        assert_eq!(buffer[0] & HDLC_ADDRESS_LSB_MASK, 0); // LSB = 0
-        assert_eq!(buffer[1] & HDLC_ADDRESS_LSB_MASK, 0); // LSB = 0
-        assert_eq!(buffer[2] & HDLC_ADDRESS_LSB_MASK, 0); // LSB = 0
-        assert_eq!(buffer[3] & HDLC_ADDRESS_LSB_MASK, 1); // LSB = 1 (last byte)
+        assert_eq!(address.encode(&mut buffer), 1);
+        assert_eq!(buffer[0], 0x21);
+        assert_eq!(HdlcAddress::decode(&buffer).unwrap(), (address, 1));
     }
 
     #[test]
-    fn test_encode_address_max_1_byte() {
+    fn test_hdlc_address_two_bytes() {
+        let address = HdlcAddress::new(0x01, 0x11, HdlcAddressSize::Two).unwrap();
         let mut buffer = [0u8; 4];
-        let len = encode_address(0x7F, &mut buffer);
-        assert_eq!(len, 1);
-        assert_eq!(buffer[0], 0xFF); // (0x7F << 1) | 0x01
+        assert_eq!(address.encode(&mut buffer), 2);
+        assert_eq!(&buffer[..2], &[0x02, 0x23]);
+        assert_eq!(HdlcAddress::decode(&buffer).unwrap(), (address, 2));
     }
 
     #[test]
-    fn test_encode_address_boundary() {
+    fn test_hdlc_address_four_bytes() {
+        let address = HdlcAddress::new(0x01, 0x3FFE, HdlcAddressSize::Four).unwrap();
         let mut buffer = [0u8; 4];
+        assert_eq!(address.encode(&mut buffer), 4);
+        assert_eq!(buffer, [0x00, 0x02, 0xFE, 0xFD]);
+        assert_eq!(HdlcAddress::decode(&buffer).unwrap(), (address, 4));
+    }
 
-        // Just below 2-byte threshold
-        let len = encode_address(0x7F, &mut buffer);
-        assert_eq!(len, 1);
+    #[test]
+    fn test_hdlc_address_out_of_range() {
+        assert_eq!(HdlcAddress::new(0x80, 0, HdlcAddressSize::One), Err(HdlcError::InvalidAddress));
+        assert_eq!(
+            HdlcAddress::new(0x01, 0x01, HdlcAddressSize::One),
+            Err(HdlcError::InvalidAddress)
+        );
+        assert_eq!(
+            HdlcAddress::new(0x01, 0x80, HdlcAddressSize::Two),
+            Err(HdlcError::InvalidAddress)
+        );
+        assert_eq!(
+            HdlcAddress::new(0x4000, 0x01, HdlcAddressSize::Four),
+            Err(HdlcError::InvalidAddress)
+        );
+    }
 
-        // Just at 2-byte threshold
-        let len = encode_address(0x80, &mut buffer);
-        assert_eq!(len, 2);
+    #[test]
+    fn test_hdlc_address_decode_invalid_length() {
+        // 3-byte address fields are not valid
+        assert_eq!(HdlcAddress::decode(&[0x02, 0x04, 0x07]), Err(HdlcError::InvalidAddress));
+        // Unterminated address field
+        assert_eq!(HdlcAddress::decode(&[0x02, 0x04]), Err(HdlcError::InvalidAddress));
     }
 
     #[test]
-    fn test_hdlc_constants() {
-        assert_eq!(HDLC_FLAG, 0x7E);
-        assert_eq!(MAX_HDLC_FRAME_SIZE, 2048);
-        assert_eq!(LLC_HEADER, [0xE6, 0xE6, 0x00]);
-        assert_eq!(FCS16_POLYNOMIAL, 0x1021);
-        assert_eq!(FCS16_INIT, 0xFFFF);
-        assert_eq!(FCS16_XOR_OUTPUT, 0xFFFF);
+    fn test_hdlc_address_broadcast() {
+        let broadcast = HdlcAddress::broadcast(HdlcAddressSize::One);
+        assert!(broadcast.is_broadcast());
+        let mut buffer = [0u8; 4];
+        assert_eq!(broadcast.encode(&mut buffer), 1);
+        assert_eq!(buffer[0], 0xFF);
+
+        let broadcast = HdlcAddress::broadcast(HdlcAddressSize::Four);
+        assert_eq!(broadcast.physical, HDLC_BROADCAST_ADDRESS_2_BYTES);
+        assert!(broadcast.is_broadcast());
+        assert!(!HdlcAddress::new(0x01, 0x10, HdlcAddressSize::Four).unwrap().is_broadcast());
     }
 
     #[test]
-    fn test_hdlc_error_display() {
-        assert_eq!(HdlcError::FrameTooLarge.to_string(), "HDLC frame too large");
-        assert_eq!(HdlcError::FcsError.to_string(), "HDLC FCS verification failed");
-        assert_eq!(HdlcError::TransportError.to_string(), "Underlying transport error");
+    fn test_hdlc_address_from_serial_number() {
+        assert_eq!(HdlcAddress::physical_from_serial_number(12_345_678), 6678);
+        assert_eq!(HdlcAddress::physical_from_serial_number(0), 1000);
+        assert_eq!(HdlcAddress::physical_from_serial_number(9999), 10999);
+
+        let address = HdlcAddress::from_serial_number(0x01, 12_345_678).unwrap();
+        assert_eq!(address.size, HdlcAddressSize::Four);
+        assert_eq!(address.physical, 6678);
+    }
+
+    #[test]
+    fn test_hdlc_address_from_combined_value() {
+        assert_eq!(
+            HdlcAddress::from(0x10),
+            HdlcAddress::new(0x10, 0, HdlcAddressSize::One).unwrap()
+        );
+        assert_eq!(
+            HdlcAddress::from(0x91),
+            HdlcAddress::new(0x01, 0x11, HdlcAddressSize::Two).unwrap()
+        );
+        assert_eq!(
+            HdlcAddress::from((0x01 << 14) | 6678),
+            HdlcAddress::new(0x01, 6678, HdlcAddressSize::Four).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_encode_frame_with_four_byte_server_address() {
+        let server = HdlcAddress::from_serial_number(0x01, 12_345_678).unwrap();
+        let mut buffer = [0u8; MAX_HDLC_FRAME_SIZE];
+        let len =
+            encode_frame(&mut buffer, &server, &CLIENT, 0x10, false, &LLC_HEADER, &[0xC0]).unwrap();
+        let frame = decode_frame(&buffer[..len]).unwrap();
+        assert_eq!(frame.information, &[0xE6, 0xE6, 0x00, 0xC0]);
+        assert_eq!(HdlcAddress::decode(&buffer[3..]).unwrap(), (server, 4));
     }
 }
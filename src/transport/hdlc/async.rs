@@ -11,10 +11,10 @@ use crate::transport::r#async::AsyncTransport;
 
 // Import shared constants and functions from parent module
 use super::{
-    BITS_PER_BYTE, BYTE_MASK, HDLC_ADDRESS_LSB_MASK, HDLC_CONTROL_I_FRAME, HDLC_FCS_SIZE,
-    HDLC_FLAG, HDLC_FLAG_COUNT, HDLC_FORMAT_TYPE_3, HDLC_HCS_SIZE, HDLC_LLC_SIZE,
-    HDLC_MAX_OVERHEAD_BYTES, HDLC_MIN_FRAME_SIZE, HdlcError, LLC_HEADER, MAX_HDLC_FRAME_SIZE,
-    compute_fcs, encode_address,
+    HDLC_DEFAULT_MAX_INFO_LENGTH, HDLC_LLC_SIZE, HDLC_MAX_OVERHEAD_BYTES,
+    HDLC_MIN_FRAME_BUFFER_SIZE, HdlcAddress, HdlcError, HdlcSequence, LLC_HEADER,
+    MAX_HDLC_FRAME_SIZE, RecvMode, append_information, decode_frame, encode_frame, find_frame,
+    is_receive_ready,
 };
 
 /// Asynchronous HDLC transport wrapper.
@@ -23,9 +23,18 @@ use super::{
 /// It automatically encapsulates outgoing data in HDLC frames and
 /// decapsulates incoming HDLC frames.
 ///
+/// APDUs larger than the maximum information field length are split into
+/// I-frames with the segmentation bit set; the wrapper waits for an RR frame
+/// from the server after each segment. Segmented responses are reassembled,
+/// acknowledging each segment with an RR frame.
+///
 /// # Type Parameters
 ///
 /// * `T` - The underlying transport type (must implement `AsyncTransport`)
+/// * `N` - Size of the frame buffers in bytes (default [`MAX_HDLC_FRAME_SIZE`]).
+///   Must be at least [`HDLC_MIN_FRAME_BUFFER_SIZE`]; smaller values save
+///   memory on embedded targets but limit the maximum information field length
+///   to `N - 20` bytes.
 ///
 /// # Examples
 ///
@@ -43,17 +52,21 @@ use super::{
 /// # }
 /// ```
 #[derive(Debug)]
-pub struct AsyncHdlcTransport<T> {
+pub struct AsyncHdlcTransport<T, const N: usize = MAX_HDLC_FRAME_SIZE> {
     /// The underlying transport.
     inner: T,
-    /// Client address (1-4 bytes, stored as u32).
-    client_address: u32,
-    /// Server address (1-4 bytes, stored as u32).
-    server_address: u32,
+    /// Client address.
+    client_address: HdlcAddress,
+    /// Server address (upper/lower HDLC address).
+    server_address: HdlcAddress,
+    /// Maximum information field length for transmitted frames.
+    max_info_length: usize,
+    /// Send and receive sequence numbers.
+    sequence: HdlcSequence,
     /// Buffer for HDLC frame construction.
-    frame_buffer: [u8; MAX_HDLC_FRAME_SIZE],
+    frame_buffer: [u8; N],
     /// Buffer for received data.
-    recv_buffer: [u8; MAX_HDLC_FRAME_SIZE],
+    recv_buffer: [u8; N],
     /// Number of bytes currently in recv_buffer.
     recv_len: usize,
 }
@@ -61,11 +74,16 @@ pub struct AsyncHdlcTransport<T> {
 impl<T> AsyncHdlcTransport<T> {
     /// Creates a new async HDLC transport wrapper.
     ///
+    /// The maximum information field length defaults to
+    /// [`HDLC_DEFAULT_MAX_INFO_LENGTH`](super::HDLC_DEFAULT_MAX_INFO_LENGTH).
+    ///
     /// # Arguments
     ///
     /// * `inner` - The underlying async transport to wrap
-    /// * `client_address` - HDLC client address (1-4 bytes)
-    /// * `server_address` - HDLC server address (1-4 bytes)
+    /// * `client_address` - HDLC client address
+    /// * `server_address` - Combined HDLC server address, converted with
+    ///   [`HdlcAddress::from`] (use [`with_addresses`](Self::with_addresses)
+    ///   for explicit upper/lower addresses)
     ///
     /// # Examples
     ///
@@ -83,16 +101,109 @@ impl<T> AsyncHdlcTransport<T> {
     /// # }
     /// ```
     pub fn new(inner: T, client_address: u32, server_address: u32) -> Self {
+        Self::with_addresses(inner, client_address.into(), server_address.into())
+    }
+
+    /// Creates a new HDLC transport wrapper with typed addresses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dlms_cosem::transport::hdlc::{HdlcAddress, AsyncHdlcTransport};
+    ///
+    /// # fn example<T>(inner: T) {
+    /// let client = HdlcAddress::client(0x10).unwrap();
+    /// let server = HdlcAddress::from_serial_number(0x01, 12_345_678).unwrap();
+    /// let hdlc = AsyncHdlcTransport::with_addresses(inner, client, server);
+    /// assert_eq!(hdlc.server_address().physical, 6678);
+    /// # }
+    /// ```
+    pub fn with_addresses(
+        inner: T,
+        client_address: HdlcAddress,
+        server_address: HdlcAddress,
+    ) -> Self {
+        Self::with_frame_buffers(inner, client_address, server_address)
+    }
+}
+
+impl<T, const N: usize> AsyncHdlcTransport<T, N> {
+    /// Largest information field that fits in an `N`-byte frame buffer.
+    const MAX_INFO_LENGTH: usize = {
+        assert!(N >= HDLC_MIN_FRAME_BUFFER_SIZE, "HDLC frame buffer too small");
+        N - HDLC_MAX_OVERHEAD_BYTES
+    };
+
+    /// Creates a new HDLC transport wrapper with `N`-byte frame buffers.
+    ///
+    /// The maximum information field length defaults to
+    /// [`HDLC_DEFAULT_MAX_INFO_LENGTH`](super::HDLC_DEFAULT_MAX_INFO_LENGTH),
+    /// limited to what fits in the buffers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dlms_cosem::transport::hdlc::{HdlcAddress, AsyncHdlcTransport};
+    ///
+    /// # fn example<T>(inner: T) {
+    /// let client = HdlcAddress::client(0x10).unwrap();
+    /// let server = HdlcAddress::from(0x01);
+    /// // 256-byte frame buffers, e.g. for an RS-485 link on a small MCU
+    /// let hdlc = AsyncHdlcTransport::<_, 256>::with_frame_buffers(inner, client, server);
+    /// assert_eq!(hdlc.max_info_length(), 128);
+    /// # }
+    /// ```
+    pub fn with_frame_buffers(
+        inner: T,
+        client_address: HdlcAddress,
+        server_address: HdlcAddress,
+    ) -> Self {
         Self {
             inner,
             client_address,
             server_address,
-            frame_buffer: [0u8; MAX_HDLC_FRAME_SIZE],
-            recv_buffer: [0u8; MAX_HDLC_FRAME_SIZE],
+            max_info_length: HDLC_DEFAULT_MAX_INFO_LENGTH.min(Self::MAX_INFO_LENGTH),
+            sequence: HdlcSequence::default(),
+            frame_buffer: [0u8; N],
+            recv_buffer: [0u8; N],
             recv_len: 0,
         }
     }
 
+    /// Sets the maximum information field length for transmitted frames.
+    ///
+    /// Use the value negotiated with the server in the SNRM/UA exchange.
+    /// The value is clamped to the range supported by the frame buffers
+    /// (at least one APDU byte after the LLC header, at most `N - 20` bytes,
+    /// i.e. [`HDLC_MAX_INFO_LENGTH`](super::HDLC_MAX_INFO_LENGTH) for the
+    /// default buffer size).
+    pub fn with_max_info_length(mut self, max_info_length: usize) -> Self {
+        self.set_max_info_length(max_info_length);
+        self
+    }
+
+    /// Sets the maximum information field length for transmitted frames.
+    ///
+    /// See [`with_max_info_length`](Self::with_max_info_length).
+    pub fn set_max_info_length(&mut self, max_info_length: usize) {
+        self.max_info_length = max_info_length.clamp(HDLC_LLC_SIZE + 1, Self::MAX_INFO_LENGTH);
+    }
+
+    /// Returns the maximum information field length for transmitted frames.
+    pub fn max_info_length(&self) -> usize {
+        self.max_info_length
+    }
+
+    /// Returns the client address.
+    pub fn client_address(&self) -> HdlcAddress {
+        self.client_address
+    }
+
+    /// Returns the server address.
+    pub fn server_address(&self) -> HdlcAddress {
+        self.server_address
+    }
+
     /// Returns a reference to the underlying transport.
     pub fn inner(&self) -> &T {
         &self.inner
@@ -108,178 +219,142 @@ impl<T> AsyncHdlcTransport<T> {
         self.inner
     }
 
-    /// Wraps data in an HDLC frame.
+    /// Wraps one segment in an HDLC frame in the frame buffer.
     ///
-    /// Returns the frame as a slice of the internal buffer and its length.
-    fn wrap_hdlc(&mut self, data: &[u8]) -> Result<usize, HdlcError> {
-        if data.len() > MAX_HDLC_FRAME_SIZE - HDLC_MAX_OVERHEAD_BYTES {
-            // Reserve space for HDLC overhead
-            return Err(HdlcError::FrameTooLarge);
-        }
+    /// Returns the frame length.
+    fn wrap_hdlc(
+        &mut self,
+        control: u8,
+        segmented: bool,
+        llc: &[u8],
+        data: &[u8],
+    ) -> Result<usize, HdlcError> {
+        encode_frame(
+            &mut self.frame_buffer,
+            &self.server_address,
+            &self.client_address,
+            control,
+            segmented,
+            llc,
+            data,
+        )
+    }
 
-        let mut pos = 0;
-
-        // Opening flag
-        self.frame_buffer[pos] = HDLC_FLAG;
-        pos += 1;
-
-        // Format field (Frame Format Type 3)
-        // Format: 1010yyyy where yyyy = length field size indicator
-        // For simplicity, we use format 0xA0 (length in 1 byte)
-        self.frame_buffer[pos] = HDLC_FORMAT_TYPE_3;
-        pos += 1;
-
-        // Length field (placeholder, will be filled later)
-        let length_pos = pos;
-        pos += 1;
-
-        // Server address (destination)
-        let server_addr_len = encode_address(self.server_address, &mut self.frame_buffer[pos..]);
-        pos += server_addr_len;
-
-        // Client address (source)
-        let client_addr_len = encode_address(self.client_address, &mut self.frame_buffer[pos..]);
-        pos += client_addr_len;
-
-        // Control field (Information frame, no segmentation)
-        // Control = 0x10 (I-frame, N(S)=0, N(R)=0, P/F=0)
-        self.frame_buffer[pos] = HDLC_CONTROL_I_FRAME;
-        pos += 1;
-
-        // Calculate and insert HCS (Header Check Sequence)
-        // HCS is FCS-16 over Format + Length + Addresses + Control
-        let hcs = compute_fcs(&self.frame_buffer[1..pos]);
-        self.frame_buffer[pos] = (hcs & BYTE_MASK) as u8;
-        self.frame_buffer[pos + 1] = ((hcs >> BITS_PER_BYTE) & BYTE_MASK) as u8;
-        pos += HDLC_HCS_SIZE;
-
-        // LLC header
-        self.frame_buffer[pos..pos + HDLC_LLC_SIZE].copy_from_slice(&LLC_HEADER);
-        pos += HDLC_LLC_SIZE;
-
-        // Information field (APDU data)
-        self.frame_buffer[pos..pos + data.len()].copy_from_slice(data);
-        pos += data.len();
-
-        // Calculate and insert FCS (Frame Check Sequence)
-        // FCS is over everything except opening flag and FCS itself
-        let fcs = compute_fcs(&self.frame_buffer[1..pos]);
-        self.frame_buffer[pos] = (fcs & BYTE_MASK) as u8;
-        self.frame_buffer[pos + 1] = ((fcs >> BITS_PER_BYTE) & BYTE_MASK) as u8;
-        pos += HDLC_FCS_SIZE;
-
-        // Closing flag
-        self.frame_buffer[pos] = HDLC_FLAG;
-        pos += 1;
-
-        // Fill in the length field
-        // Length = frame size from format field to FCS (inclusive)
-        let frame_length = pos - HDLC_FLAG_COUNT; // Exclude both flags
-        self.frame_buffer[length_pos] = frame_length as u8;
-
-        Ok(pos)
+    /// Removes the first `len` bytes from the receive buffer.
+    fn consume(&mut self, len: usize) {
+        self.recv_buffer.copy_within(len..self.recv_len, 0);
+        self.recv_len -= len;
     }
+}
 
-    /// Unwraps an HDLC frame and extracts the information field.
+impl<T: AsyncTransport, const N: usize> AsyncHdlcTransport<T, N> {
+    /// Reads from the underlying transport until a complete frame is buffered.
     ///
-    /// Returns the number of bytes written to the output buffer.
-    fn unwrap_hdlc(&self, frame: &[u8], output: &mut [u8]) -> Result<usize, HdlcError> {
-        if frame.len() < HDLC_MIN_FRAME_SIZE {
-            // Minimum frame: Flag + Format + Length + Addr(2) + Ctrl + HCS(2) + FCS(2) + Flag
-            return Err(HdlcError::FrameTooShort);
-        }
-
-        let mut pos = 0;
-
-        // Check opening flag
-        if frame[pos] != HDLC_FLAG {
-            return Err(HdlcError::InvalidFlag);
-        }
-        pos += 1;
-
-        // Skip format field
-        pos += 1;
-
-        // Read length field
-        let _length = frame[pos] as usize;
-        pos += 1;
-
-        // Skip addresses and control field
-        // For simplicity, we scan for HCS position (after addresses + control)
-        // In production, properly parse address fields
-        while pos < frame.len() - 4 && frame[pos] & HDLC_ADDRESS_LSB_MASK == 0 {
-            pos += 1; // Skip address bytes (LSB = 0 means more bytes follow)
-        }
-        if pos < frame.len() - 4 {
-            pos += 1; // Last address byte
-        }
-
-        // Skip control field
-        pos += 1;
-
-        // Skip HCS (2 bytes)
-        pos += HDLC_HCS_SIZE;
-
-        // Find information field end (before FCS)
-        let fcs_pos = frame.len() - HDLC_FCS_SIZE - 1; // FCS (2 bytes) + closing flag
-        let info_len = fcs_pos - pos;
-
-        if info_len < HDLC_LLC_SIZE {
-            // Must have at least LLC header
-            return Err(HdlcError::InvalidFrame);
-        }
+    /// Returns the length of the frame at the start of the receive buffer.
+    async fn read_frame(&mut self, mode: RecvMode) -> Result<usize, HdlcError> {
+        loop {
+            let (noise, len) = find_frame(&self.recv_buffer[..self.recv_len]);
+            self.consume(noise);
+            if let Some(len) = len {
+                return Ok(len);
+            }
+            if self.recv_len == self.recv_buffer.len() {
+                // Drop the partial frame; its tail is skipped as noise
+                self.recv_len = 0;
+                return Err(HdlcError::FrameTooLarge);
+            }
+
+            let buffer = &mut self.recv_buffer[self.recv_len..];
+            let n = match mode {
+                RecvMode::Blocking => self.inner.recv(buffer).await,
+                #[cfg(feature = "std")]
+                RecvMode::Timeout(timeout) => self.inner.recv_timeout(buffer, timeout).await,
+            }
+            .map_err(|_| HdlcError::TransportError)?;
 
-        // Verify FCS
-        let expected_fcs = compute_fcs(&frame[1..fcs_pos]);
-        let actual_fcs = u16::from_le_bytes([frame[fcs_pos], frame[fcs_pos + 1]]);
-        if expected_fcs != actual_fcs {
-            return Err(HdlcError::FcsError);
+            if n == 0 {
+                return Err(HdlcError::TransportError);
+            }
+            self.recv_len += n;
         }
+    }
 
-        // Check closing flag
-        if frame[frame.len() - 1] != HDLC_FLAG {
-            return Err(HdlcError::InvalidFlag);
+    /// Sends an APDU, splitting it into segments of at most `max_info_length` bytes.
+    async fn send_apdu(&mut self, data: &[u8], mode: RecvMode) -> Result<(), HdlcError> {
+        let mut llc: &[u8] = &LLC_HEADER;
+        let mut remaining = data;
+
+        loop {
+            let (segment, rest) =
+                remaining.split_at(remaining.len().min(self.max_info_length - llc.len()));
+            let segmented = !rest.is_empty();
+
+            let control = self.sequence.next_information_control();
+            let frame_len = self.wrap_hdlc(control, segmented, llc, segment)?;
+            self.inner
+                .send(&self.frame_buffer[..frame_len])
+                .await
+                .map_err(|_| HdlcError::TransportError)?;
+
+            if !segmented {
+                return Ok(());
+            }
+
+            // The server acknowledges each segment with an RR frame
+            let frame_len = self.read_frame(mode).await?;
+            let control = decode_frame(&self.recv_buffer[..frame_len]).map(|frame| frame.control);
+            self.consume(frame_len);
+            if !is_receive_ready(control?) {
+                return Err(HdlcError::UnexpectedFrame);
+            }
+
+            llc = &[];
+            remaining = rest;
         }
+    }
 
-        // Skip LLC header (3 bytes)
-        pos += HDLC_LLC_SIZE;
-        let data_len = info_len - HDLC_LLC_SIZE;
-
-        // Copy information field to output
-        if data_len > output.len() {
-            return Err(HdlcError::BufferTooSmall);
+    /// Receives an APDU, reassembling segmented frames into `buffer`.
+    ///
+    /// Returns the APDU length.
+    async fn recv_apdu(&mut self, buffer: &mut [u8], mode: RecvMode) -> Result<usize, HdlcError> {
+        let mut written = 0;
+        let mut first = true;
+
+        loop {
+            let frame_len = self.read_frame(mode).await?;
+            let result =
+                append_information(&self.recv_buffer[..frame_len], first, buffer, &mut written);
+            // A rejected frame is dropped so the next receive starts afresh
+            self.consume(frame_len);
+            let (control, segmented) = result?;
+            self.sequence.acknowledge(control);
+
+            if !segmented {
+                return Ok(written);
+            }
+
+            // Request the next segment
+            let control = self.sequence.receive_ready_control();
+            let rr_len = self.wrap_hdlc(control, false, &[], &[])?;
+            self.inner
+                .send(&self.frame_buffer[..rr_len])
+                .await
+                .map_err(|_| HdlcError::TransportError)?;
+
+            first = false;
         }
-        output[..data_len].copy_from_slice(&frame[pos..pos + data_len]);
-
-        Ok(data_len)
     }
 }
 
-impl<T: AsyncTransport> AsyncTransport for AsyncHdlcTransport<T> {
+impl<T: AsyncTransport, const N: usize> AsyncTransport for AsyncHdlcTransport<T, N> {
     type Error = HdlcError;
 
     async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
-        let frame_len = self.wrap_hdlc(data)?;
-        self.inner
-            .send(&self.frame_buffer[..frame_len])
-            .await
-            .map_err(|_| HdlcError::TransportError)?;
-        Ok(())
+        self.send_apdu(data, RecvMode::Blocking).await
     }
 
     async fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
-        // Read from underlying transport
-        let n =
-            self.inner.recv(&mut self.recv_buffer).await.map_err(|_| HdlcError::TransportError)?;
-
-        if n == 0 {
-            return Err(HdlcError::TransportError);
-        }
-
-        self.recv_len = n;
-
-        // Unwrap HDLC frame
-        self.unwrap_hdlc(&self.recv_buffer[..n], buffer)
+        self.recv_apdu(buffer, RecvMode::Blocking).await
     }
 
     #[cfg(feature = "std")]
@@ -288,21 +363,7 @@ impl<T: AsyncTransport> AsyncTransport for AsyncHdlcTransport<T> {
         buffer: &mut [u8],
         timeout: std::time::Duration,
     ) -> Result<usize, Self::Error> {
-        // Read from underlying transport with timeout
-        let n = self
-            .inner
-            .recv_timeout(&mut self.recv_buffer, timeout)
-            .await
-            .map_err(|_| HdlcError::TransportError)?;
-
-        if n == 0 {
-            return Err(HdlcError::TransportError);
-        }
-
-        self.recv_len = n;
-
-        // Unwrap HDLC frame
-        self.unwrap_hdlc(&self.recv_buffer[..n], buffer)
+        self.recv_apdu(buffer, RecvMode::Timeout(timeout)).await
     }
 }
 
@@ -310,11 +371,63 @@ impl<T: AsyncTransport> AsyncTransport for AsyncHdlcTransport<T> {
 
 #[cfg(test)]
 mod tests {
+    use super::super::{HDLC_CONTROL_RR, HDLC_FLAG};
     use super::*;
+    use alloc::collections::VecDeque;
+    use alloc::vec::Vec;
+    use futures_executor::block_on;
 
-    // Note: Most tests are in parent module (hdlc.rs)
+    // Note: Frame codec tests are in parent module (hdlc.rs)
     // Only transport-specific tests here
 
+    const CLIENT: u32 = 0x10;
+    const SERVER: u32 = 0x01;
+
+    #[derive(Debug, Default)]
+    struct MockTransport {
+        sent: Vec<Vec<u8>>,
+        incoming: VecDeque<Vec<u8>>,
+    }
+
+    impl AsyncTransport for MockTransport {
+        type Error = ();
+
+        async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+            self.sent.push(data.to_vec());
+            Ok(())
+        }
+
+        async fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+            let chunk = self.incoming.pop_front().ok_or(())?;
+            buffer[..chunk.len()].copy_from_slice(&chunk);
+            Ok(chunk.len())
+        }
+
+        #[cfg(feature = "std")]
+        async fn recv_timeout(
+            &mut self,
+            buffer: &mut [u8],
+            _timeout: std::time::Duration,
+        ) -> Result<usize, Self::Error> {
+            self.recv(buffer).await
+        }
+    }
+
+    fn server_frame(control: u8, segmented: bool, llc: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut buffer = [0u8; MAX_HDLC_FRAME_SIZE];
+        let len = encode_frame(
+            &mut buffer,
+            &CLIENT.into(),
+            &SERVER.into(),
+            control,
+            segmented,
+            llc,
+            data,
+        )
+        .unwrap();
+        buffer[..len].to_vec()
+    }
+
     #[test]
     fn test_hdlc_constants_accessible() {
         // Verify we can access shared constants
@@ -322,5 +435,124 @@ mod tests {
         assert_eq!(MAX_HDLC_FRAME_SIZE, 2048);
     }
 
-    // Note: Full integration tests with mock transports are in tests/hdlc_transport_async.rs
+    #[test]
+    fn test_send_segmented_waits_for_rr() {
+        let mut inner = MockTransport::default();
+        inner.incoming.push_back(server_frame(0x30 | HDLC_CONTROL_RR, false, &[], &[]));
+        let mut hdlc = AsyncHdlcTransport::new(inner, CLIENT, SERVER).with_max_info_length(16);
+
+        let apdu: Vec<u8> = (0..20).collect();
+        block_on(hdlc.send(&apdu)).unwrap();
+
+        let sent = &hdlc.inner().sent;
+        assert_eq!(sent.len(), 2);
+        let first = decode_frame(&sent[0]).unwrap();
+        let second = decode_frame(&sent[1]).unwrap();
+        assert!(first.segmented);
+        assert!(!second.segmented);
+        assert_eq!(first.control, 0x10);
+        assert_eq!(second.control, 0x12);
+        assert_eq!(&first.information[3..], &apdu[..13]);
+        assert_eq!(second.information, &apdu[13..]);
+    }
+
+    #[test]
+    fn test_recv_reassembles_segments_and_acknowledges() {
+        let mut inner = MockTransport::default();
+        inner.incoming.push_back(server_frame(0x10, true, &[0xE6, 0xE7, 0x00], &[1, 2]));
+        inner.incoming.push_back(server_frame(0x12, false, &[], &[3]));
+        let mut hdlc = AsyncHdlcTransport::new(inner, CLIENT, SERVER);
+
+        let mut buffer = [0u8; 16];
+        let len = block_on(hdlc.recv(&mut buffer)).unwrap();
+        assert_eq!(&buffer[..len], &[1, 2, 3]);
+
+        let sent = &hdlc.inner().sent;
+        assert_eq!(sent.len(), 1);
+        let rr = decode_frame(&sent[0]).unwrap();
+        assert!(is_receive_ready(rr.control));
+        assert_eq!(rr.control >> 5, 1);
+    }
+
+    #[test]
+    fn test_recv_recovers_after_corrupted_frame() {
+        let mut inner = MockTransport::default();
+        let mut corrupted = server_frame(0x10, false, &[0xE6, 0xE7, 0x00], &[1, 2, 3]);
+        let fcs = corrupted.len() - 2;
+        corrupted[fcs] ^= 0xFF;
+        let mut noisy = vec![0x00, 0xFF];
+        noisy.extend(server_frame(0x12, false, &[0xE6, 0xE7, 0x00], &[4, 5]));
+        inner.incoming.push_back(corrupted);
+        inner.incoming.push_back(noisy);
+        let mut hdlc = AsyncHdlcTransport::new(inner, CLIENT, SERVER);
+
+        let mut buffer = [0u8; 16];
+        assert_eq!(block_on(hdlc.recv(&mut buffer)), Err(HdlcError::FcsError));
+        let len = block_on(hdlc.recv(&mut buffer)).unwrap();
+        assert_eq!(&buffer[..len], &[4, 5]);
+    }
+
+    #[test]
+    fn test_small_frame_buffers() {
+        let mut inner = MockTransport::default();
+        inner.incoming.push_back(server_frame(0x10, false, &[0xE6, 0xE7, 0x00], &[1, 2, 3]));
+        let mut hdlc =
+            AsyncHdlcTransport::<_, 64>::with_frame_buffers(inner, CLIENT.into(), SERVER.into())
+                .with_max_info_length(usize::MAX);
+        assert_eq!(hdlc.max_info_length(), 64 - HDLC_MAX_OVERHEAD_BYTES);
+
+        let mut buffer = [0u8; 16];
+        let len = block_on(hdlc.recv(&mut buffer)).unwrap();
+        assert_eq!(&buffer[..len], &[1, 2, 3]);
+    }
+
+    /// Single-threaded runtimes (glommio, embassy-net) use `!Send` transports.
+    #[cfg(feature = "rt-single-thread")]
+    #[test]
+    fn test_non_send_transport() {
+        use alloc::rc::Rc;
+        use core::cell::RefCell;
+
+        #[derive(Debug, Default)]
+        struct LocalTransport {
+            incoming: Rc<RefCell<VecDeque<Vec<u8>>>>,
+            sent: Rc<RefCell<Vec<Vec<u8>>>>,
+        }
+
+        impl AsyncTransport for LocalTransport {
+            type Error = ();
+
+            async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+                self.sent.borrow_mut().push(data.to_vec());
+                Ok(())
+            }
+
+            async fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+                let chunk = self.incoming.borrow_mut().pop_front().ok_or(())?;
+                buffer[..chunk.len()].copy_from_slice(&chunk);
+                Ok(chunk.len())
+            }
+
+            #[cfg(feature = "std")]
+            async fn recv_timeout(
+                &mut self,
+                buffer: &mut [u8],
+                _timeout: std::time::Duration,
+            ) -> Result<usize, Self::Error> {
+                self.recv(buffer).await
+            }
+        }
+
+        let inner = LocalTransport::default();
+        let sent = Rc::clone(&inner.sent);
+        inner.incoming.borrow_mut().push_back(server_frame(0x10, false, &[0xE6, 0xE7, 0x00], &[7]));
+        let mut hdlc =
+            AsyncHdlcTransport::<_, 256>::with_frame_buffers(inner, CLIENT.into(), SERVER.into());
+
+        block_on(hdlc.send(&[0xC0, 0x01])).unwrap();
+        let mut buffer = [0u8; 8];
+        assert_eq!(block_on(hdlc.recv(&mut buffer)), Ok(1));
+        assert_eq!(buffer[0], 7);
+        assert_eq!(sent.borrow().len(), 1);
+    }
 }
@@ -0,0 +1,688 @@
+//! In-memory transports for testing DLMS client code without sockets or hardware.
+//!
+//! This module provides transports that exchange bytes through memory:
+//!
+//! - [`MemoryTransport`] / [`AsyncMemoryTransport`] - A connected pair of
+//!   endpoints, like a socket pair. Whatever one side sends, the other receives.
+//! - [`ScriptedTransport`] / [`AsyncScriptedTransport`] - A mock meter that
+//!   follows a [`Script`]: it asserts that the client sends the expected request
+//!   bytes and replays canned responses.
+//!
+//! All transports support latency and fault injection through [`FaultInjector`]
+//! (dropped, truncated and corrupted messages), so error handling paths such as
+//! timeouts and invalid frames can be exercised deterministically.
+//!
+//! # Feature
+//!
+//! Requires the `transport-memory` feature. The async transports additionally
+//! require `async-client` and work with any runtime.
+//!
+//! # Examples
+//!
+//! ## Connected Pair
+//!
+//! ```
+//! # #[cfg(feature = "transport-memory")]
+//! # {
+//! use dlms_cosem::transport::Transport;
+//! use dlms_cosem::transport::memory::MemoryTransport;
+//!
+//! let (mut client, mut meter) = MemoryTransport::pair();
+//! client.send(&[0xC0, 0x01, 0xC1]).unwrap();
+//!
+//! let mut buffer = [0u8; 16];
+//! let len = meter.recv(&mut buffer).unwrap();
+//! assert_eq!(&buffer[..len], &[0xC0, 0x01, 0xC1]);
+//! # }
+//! ```
+//!
+//! ## Scripted Mock Meter
+//!
+//! ```
+//! # #[cfg(feature = "transport-memory")]
+//! # {
+//! use dlms_cosem::transport::Transport;
+//! use dlms_cosem::transport::memory::{Script, ScriptedTransport};
+//!
+//! let script = Script::new().exchange(vec![0xC0, 0x01], vec![0xC4, 0x01]);
+//! let mut meter = ScriptedTransport::new(script);
+//!
+//! meter.send(&[0xC0, 0x01]).unwrap();
+//! let mut buffer = [0u8; 16];
+//! let len = meter.recv(&mut buffer).unwrap();
+//! assert_eq!(&buffer[..len], &[0xC4, 0x01]);
+//! assert!(meter.is_finished());
+//! # }
+//! ```
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::task::Waker;
+#[cfg(feature = "async-client")]
+use core::task::{Context, Poll};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+pub mod sync;
+
+pub use sync::{MemoryTransport, ScriptedTransport};
+
+#[cfg(feature = "async-client")]
+pub mod r#async;
+
+#[cfg(feature = "async-client")]
+pub use r#async::{AsyncMemoryTransport, AsyncScriptedTransport};
+
+/// Errors returned by in-memory transports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryError {
+    /// The peer endpoint has been dropped.
+    Disconnected,
+    /// No data became available in time.
+    ///
+    /// Scripted transports return this immediately when no response is pending,
+    /// e.g. because the response was dropped by fault injection.
+    Timeout,
+    /// The client sent a request that does not match the script.
+    UnexpectedRequest {
+        /// Index of the script step that was expected.
+        step: usize,
+        /// The request bytes expected by the script.
+        expected: Vec<u8>,
+        /// The bytes actually sent.
+        actual: Vec<u8>,
+    },
+    /// The client sent a request after the script ran out of steps.
+    ScriptExhausted,
+}
+
+impl core::fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Disconnected => write!(f, "Memory transport peer disconnected"),
+            Self::Timeout => write!(f, "Memory transport receive timeout"),
+            Self::UnexpectedRequest { step, expected, actual } => write!(
+                f,
+                "Unexpected request at script step {step}: expected {expected:02X?}, got {actual:02X?}"
+            ),
+            Self::ScriptExhausted => write!(f, "Request sent after end of script"),
+        }
+    }
+}
+
+impl std::error::Error for MemoryError {}
+
+/// A fault applied to a single message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The message is silently lost.
+    Drop,
+    /// Only the first `n` bytes of the message are delivered.
+    Truncate(usize),
+    /// The byte at `offset` is XORed with `mask` (ignored if out of range).
+    Corrupt {
+        /// Byte offset within the message.
+        offset: usize,
+        /// Bits to flip.
+        mask: u8,
+    },
+}
+
+impl Fault {
+    /// Applies the fault to `message`, returning `None` if it is dropped.
+    fn apply(self, mut message: Vec<u8>) -> Option<Vec<u8>> {
+        match self {
+            Self::Drop => return None,
+            Self::Truncate(len) => message.truncate(len),
+            Self::Corrupt { offset, mask } => {
+                if let Some(byte) = message.get_mut(offset) {
+                    *byte ^= mask;
+                }
+            }
+        }
+        Some(message)
+    }
+}
+
+/// Latency and fault injection for the messages emitted by a transport.
+///
+/// Faults are scheduled by message index (0-based, counting every message the
+/// transport emits), which keeps tests deterministic.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "transport-memory")]
+/// # {
+/// use std::time::Duration;
+/// use dlms_cosem::transport::memory::{Fault, FaultInjector};
+///
+/// let faults = FaultInjector::new()
+///     .with_latency(Duration::from_millis(5))
+///     .with_fault(0, Fault::Drop)
+///     .with_fault(2, Fault::Corrupt { offset: 3, mask: 0xFF });
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjector {
+    /// Delay applied before each message is delivered.
+    latency: Duration,
+    /// Scheduled faults as (message index, fault).
+    faults: Vec<(usize, Fault)>,
+    /// Number of messages processed so far.
+    messages: usize,
+}
+
+impl FaultInjector {
+    /// Creates an injector without latency or faults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the delay applied before each message is delivered.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Schedules a fault for the message with the given index.
+    ///
+    /// Several faults may be scheduled for the same message; they are applied
+    /// in the order they were added.
+    pub fn with_fault(mut self, message_index: usize, fault: Fault) -> Self {
+        self.add_fault(message_index, fault);
+        self
+    }
+
+    /// Sets the delay applied before each message is delivered.
+    pub fn set_latency(&mut self, latency: Duration) {
+        self.latency = latency;
+    }
+
+    /// Schedules a fault for the message with the given index.
+    pub fn add_fault(&mut self, message_index: usize, fault: Fault) {
+        self.faults.push((message_index, fault));
+    }
+
+    /// Returns the delay applied before each message is delivered.
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+
+    /// Returns the number of messages processed so far.
+    pub fn message_count(&self) -> usize {
+        self.messages
+    }
+
+    /// Applies the faults scheduled for the next message.
+    ///
+    /// Returns `None` if the message is dropped.
+    pub(crate) fn apply(&mut self, data: &[u8]) -> Option<Vec<u8>> {
+        let index = self.messages;
+        self.messages += 1;
+
+        self.faults
+            .iter()
+            .filter(|(message_index, _)| *message_index == index)
+            .try_fold(data.to_vec(), |message, (_, fault)| fault.apply(message))
+    }
+}
+
+/// Mask of the invoke id bits in the invoke-id-and-priority byte.
+const INVOKE_ID_MASK: u8 = 0x0F;
+
+/// GET-Request, SET-Request and ACTION-Request tags.
+const INVOKE_ID_REQUEST_TAGS: [u8; 3] = [0xC0, 0xC1, 0xC3];
+
+/// Ciphered APDU tags (glo-, ded-, general-glo- and general-ded-ciphering).
+const CIPHERED_APDU_TAGS: [u8; 14] =
+    [0xC8, 0xC9, 0xCB, 0xCC, 0xCD, 0xCF, 0xD0, 0xD1, 0xD3, 0xD4, 0xD5, 0xD7, 0xDB, 0xDC];
+
+/// Differences tolerated when comparing a sent request with the expected one.
+///
+/// Requests are compared at the APDU level, so recordings and scripts should
+/// be taken above any framing (e.g. wrap the HDLC transport, not the TCP
+/// transport below it).
+///
+/// The default is strict byte-for-byte comparison.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestTolerance {
+    /// Ignore the invoke id of GET, SET and ACTION requests.
+    pub invoke_id: bool,
+    /// Ignore the invocation counter of ciphered APDUs.
+    ///
+    /// As the ciphertext depends on the invocation counter, only the APDU
+    /// header (tag, system title, length and security control) and the total
+    /// length are compared.
+    pub invocation_counter: bool,
+}
+
+impl RequestTolerance {
+    /// Strict byte-for-byte comparison.
+    pub const STRICT: Self = Self { invoke_id: false, invocation_counter: false };
+
+    /// Tolerates both invoke id and invocation counter differences.
+    pub const LENIENT: Self = Self { invoke_id: true, invocation_counter: true };
+
+    /// Returns `true` if `actual` matches `expected` within this tolerance.
+    pub fn matches(&self, expected: &[u8], actual: &[u8]) -> bool {
+        if expected == actual {
+            return true;
+        }
+        if expected.len() != actual.len() || expected.is_empty() || expected[0] != actual[0] {
+            return false;
+        }
+
+        let tag = expected[0];
+        if self.invoke_id && INVOKE_ID_REQUEST_TAGS.contains(&tag) && expected.len() >= 3 {
+            return expected[1] == actual[1]
+                && expected[2] & !INVOKE_ID_MASK == actual[2] & !INVOKE_ID_MASK
+                && expected[3..] == actual[3..];
+        }
+        if self.invocation_counter && CIPHERED_APDU_TAGS.contains(&tag) {
+            return ciphered_header_len(expected)
+                .is_some_and(|len| actual.len() >= len && expected[..len] == actual[..len]);
+        }
+        false
+    }
+}
+
+/// Returns the length of a ciphered APDU header up to and including the
+/// security control byte (i.e. the offset of the invocation counter).
+fn ciphered_header_len(apdu: &[u8]) -> Option<usize> {
+    // Tag + system title (length-prefixed)
+    let title_len = *apdu.get(1)? as usize;
+    let mut offset = 2 + title_len;
+
+    // A-XDR length of the ciphered content
+    let length = *apdu.get(offset)?;
+    offset += 1;
+    if length & 0x80 != 0 {
+        offset += (length & 0x7F) as usize;
+    }
+
+    // Security control byte
+    apdu.get(offset)?;
+    Some(offset + 1)
+}
+
+/// One step of a [`Script`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ScriptStep {
+    /// The client must send exactly these bytes.
+    Request(Vec<u8>),
+    /// The meter sends these bytes.
+    Response(Vec<u8>),
+}
+
+/// Expected requests and canned responses for a [`ScriptedTransport`].
+///
+/// Responses following a request are queued once that request has been
+/// received. Responses at the start of the script are available immediately
+/// (e.g. unsolicited data notifications).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Script {
+    steps: VecDeque<ScriptStep>,
+}
+
+impl Script {
+    /// Creates an empty script.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a request the client must send.
+    pub fn expect(mut self, request: impl Into<Vec<u8>>) -> Self {
+        self.steps.push_back(ScriptStep::Request(request.into()));
+        self
+    }
+
+    /// Appends a response sent by the meter.
+    pub fn respond(mut self, response: impl Into<Vec<u8>>) -> Self {
+        self.steps.push_back(ScriptStep::Response(response.into()));
+        self
+    }
+
+    /// Appends a request followed by its response.
+    pub fn exchange(self, request: impl Into<Vec<u8>>, response: impl Into<Vec<u8>>) -> Self {
+        self.expect(request).respond(response)
+    }
+
+    /// Returns the number of remaining steps.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns `true` if the script has no remaining steps.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// Script execution state shared by the sync and async scripted transports.
+#[derive(Debug)]
+pub(crate) struct ScriptRunner {
+    script: Script,
+    /// Index of the next script step.
+    step: usize,
+    /// Responses queued for the client.
+    pending: VecDeque<Vec<u8>>,
+    faults: FaultInjector,
+    tolerance: RequestTolerance,
+}
+
+impl ScriptRunner {
+    pub(crate) fn new(script: Script) -> Self {
+        let mut runner = Self {
+            script,
+            step: 0,
+            pending: VecDeque::new(),
+            faults: FaultInjector::default(),
+            tolerance: RequestTolerance::default(),
+        };
+        runner.queue_responses();
+        runner
+    }
+
+    pub(crate) fn faults_mut(&mut self) -> &mut FaultInjector {
+        &mut self.faults
+    }
+
+    pub(crate) fn set_faults(&mut self, faults: FaultInjector) {
+        self.faults = faults;
+    }
+
+    pub(crate) fn set_tolerance(&mut self, tolerance: RequestTolerance) {
+        self.tolerance = tolerance;
+    }
+
+    pub(crate) fn latency(&self) -> Duration {
+        self.faults.latency()
+    }
+
+    /// Returns `true` if all steps ran and all responses were received.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.script.is_empty() && self.pending.is_empty()
+    }
+
+    /// Returns the remaining script steps.
+    pub(crate) fn remaining(&self) -> &Script {
+        &self.script
+    }
+
+    /// Checks a request against the script and queues the following responses.
+    pub(crate) fn on_send(&mut self, data: &[u8]) -> Result<(), MemoryError> {
+        match self.script.steps.pop_front() {
+            Some(ScriptStep::Request(expected)) if self.tolerance.matches(&expected, data) => {}
+            Some(ScriptStep::Request(expected)) => {
+                return Err(MemoryError::UnexpectedRequest {
+                    step: self.step,
+                    expected,
+                    actual: data.to_vec(),
+                });
+            }
+            Some(ScriptStep::Response(_)) => unreachable!("responses are queued eagerly"),
+            None => return Err(MemoryError::ScriptExhausted),
+        }
+        self.step += 1;
+        self.queue_responses();
+        Ok(())
+    }
+
+    /// Copies the next pending response into `buffer`.
+    pub(crate) fn on_recv(&mut self, buffer: &mut [u8]) -> Result<usize, MemoryError> {
+        take_chunk(&mut self.pending, buffer).ok_or(MemoryError::Timeout)
+    }
+
+    fn queue_responses(&mut self) {
+        while let Some(ScriptStep::Response(_)) = self.script.steps.front() {
+            let Some(ScriptStep::Response(response)) = self.script.steps.pop_front() else {
+                unreachable!();
+            };
+            self.step += 1;
+            if let Some(response) = self.faults.apply(&response) {
+                self.pending.push_back(response);
+            }
+        }
+    }
+}
+
+/// Copies the front chunk of `queue` into `buffer`, keeping any remainder queued.
+///
+/// Returns `None` if the queue is empty.
+fn take_chunk(queue: &mut VecDeque<Vec<u8>>, buffer: &mut [u8]) -> Option<usize> {
+    let chunk = queue.front_mut()?;
+    let n = chunk.len().min(buffer.len());
+    buffer[..n].copy_from_slice(&chunk[..n]);
+    if n == chunk.len() {
+        queue.pop_front();
+    } else {
+        chunk.drain(..n);
+    }
+    Some(n)
+}
+
+/// One direction of a connected in-memory pair.
+#[derive(Debug, Default)]
+pub(crate) struct Channel {
+    state: Mutex<ChannelState>,
+    ready: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct ChannelState {
+    queue: VecDeque<Vec<u8>>,
+    closed: bool,
+    /// Waker of a pending async receiver.
+    waker: Option<Waker>,
+}
+
+impl Channel {
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ChannelState> {
+        // A panic while holding the lock cannot leave the queue inconsistent
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Queues a message for the receiving endpoint.
+    pub(crate) fn push(&self, message: Vec<u8>) -> Result<(), MemoryError> {
+        let mut state = self.lock();
+        if state.closed {
+            return Err(MemoryError::Disconnected);
+        }
+        state.queue.push_back(message);
+        self.notify(state);
+        Ok(())
+    }
+
+    /// Marks the channel as closed and wakes the receiver.
+    pub(crate) fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        self.notify(state);
+    }
+
+    fn notify(&self, mut state: MutexGuard<'_, ChannelState>) {
+        let waker = state.waker.take();
+        drop(state);
+        self.ready.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Receives into `buffer`, or returns `None` if no data is available yet.
+    fn try_recv(state: &mut ChannelState, buffer: &mut [u8]) -> Option<Result<usize, MemoryError>> {
+        match take_chunk(&mut state.queue, buffer) {
+            Some(n) => Some(Ok(n)),
+            None if state.closed => Some(Err(MemoryError::Disconnected)),
+            None => None,
+        }
+    }
+
+    /// Blocks until data is available or the optional timeout expires.
+    pub(crate) fn recv_blocking(
+        &self,
+        buffer: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<usize, MemoryError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut state = self.lock();
+        loop {
+            if let Some(result) = Self::try_recv(&mut state, buffer) {
+                return result;
+            }
+            state = match deadline {
+                None => self.ready.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner()),
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Err(MemoryError::Timeout);
+                    }
+                    self.ready
+                        .wait_timeout(state, remaining)
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .0
+                }
+            };
+        }
+    }
+
+    /// Polls for data, registering the waker if none is available.
+    #[cfg(feature = "async-client")]
+    pub(crate) fn poll_recv(
+        &self,
+        cx: &mut Context<'_>,
+        buffer: &mut [u8],
+    ) -> Poll<Result<usize, MemoryError>> {
+        let mut state = self.lock();
+        match Self::try_recv(&mut state, buffer) {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_injection() {
+        let mut faults = FaultInjector::new()
+            .with_fault(0, Fault::Drop)
+            .with_fault(1, Fault::Truncate(2))
+            .with_fault(2, Fault::Corrupt { offset: 1, mask: 0xFF })
+            .with_fault(3, Fault::Corrupt { offset: 10, mask: 0xFF });
+
+        assert_eq!(faults.apply(&[1, 2, 3]), None);
+        assert_eq!(faults.apply(&[1, 2, 3]), Some(vec![1, 2]));
+        assert_eq!(faults.apply(&[1, 2, 3]), Some(vec![1, 0xFD, 3]));
+        assert_eq!(faults.apply(&[1, 2, 3]), Some(vec![1, 2, 3]));
+        assert_eq!(faults.apply(&[1, 2, 3]), Some(vec![1, 2, 3]));
+        assert_eq!(faults.message_count(), 5);
+    }
+
+    #[test]
+    fn test_script_runner() {
+        let script = Script::new()
+            .respond(vec![0x0F])
+            .exchange(vec![0x01], vec![0x81])
+            .respond(vec![0x82])
+            .expect(vec![0x02]);
+        let mut runner = ScriptRunner::new(script);
+        let mut buffer = [0u8; 4];
+
+        // Leading responses are available immediately
+        assert_eq!(runner.on_recv(&mut buffer), Ok(1));
+        assert_eq!(buffer[0], 0x0F);
+        assert_eq!(runner.on_recv(&mut buffer), Err(MemoryError::Timeout));
+
+        runner.on_send(&[0x01]).unwrap();
+        assert_eq!(runner.on_recv(&mut buffer), Ok(1));
+        assert_eq!(buffer[0], 0x81);
+        assert_eq!(runner.on_recv(&mut buffer), Ok(1));
+        assert_eq!(buffer[0], 0x82);
+
+        assert_eq!(
+            runner.on_send(&[0x03]),
+            Err(MemoryError::UnexpectedRequest {
+                step: 4,
+                expected: vec![0x02],
+                actual: vec![0x03]
+            })
+        );
+        assert!(runner.is_finished());
+        assert_eq!(runner.on_send(&[0x02]), Err(MemoryError::ScriptExhausted));
+    }
+
+    #[test]
+    fn test_script_runner_partial_read() {
+        let mut runner = ScriptRunner::new(Script::new().respond(vec![1, 2, 3]));
+        let mut buffer = [0u8; 2];
+        assert_eq!(runner.on_recv(&mut buffer), Ok(2));
+        assert_eq!(buffer, [1, 2]);
+        assert_eq!(runner.on_recv(&mut buffer), Ok(1));
+        assert_eq!(buffer[0], 3);
+    }
+
+    #[test]
+    fn test_request_tolerance_invoke_id() {
+        let expected = [0xC0, 0x01, 0xC1, 0x00, 0x08];
+        let actual = [0xC0, 0x01, 0xC2, 0x00, 0x08];
+        assert!(!RequestTolerance::STRICT.matches(&expected, &actual));
+        assert!(
+            RequestTolerance { invoke_id: true, ..Default::default() }.matches(&expected, &actual)
+        );
+
+        // Priority bits and the rest of the request must still match
+        assert!(!RequestTolerance::LENIENT.matches(&expected, &[0xC0, 0x01, 0x41, 0x00, 0x08]));
+        assert!(!RequestTolerance::LENIENT.matches(&expected, &[0xC0, 0x01, 0xC2, 0x00, 0x09]));
+    }
+
+    #[test]
+    fn test_request_tolerance_invocation_counter() {
+        // glo-get-request: tag, title, length, SC, IC, ciphertext
+        let mut expected = vec![0xC8, 0x08, 1, 2, 3, 4, 5, 6, 7, 8, 0x07, 0x30];
+        expected.extend_from_slice(&[0, 0, 0, 1, 0xAA, 0xBB]);
+        let mut actual = expected.clone();
+        actual[15] = 2;
+        actual[16] = 0xCC;
+
+        assert!(!RequestTolerance::STRICT.matches(&expected, &actual));
+        assert!(
+            RequestTolerance { invocation_counter: true, ..Default::default() }
+                .matches(&expected, &actual)
+        );
+
+        // Different system title is not tolerated
+        actual[2] = 0xFF;
+        assert!(!RequestTolerance::LENIENT.matches(&expected, &actual));
+    }
+
+    #[test]
+    fn test_script_runner_with_tolerance() {
+        let mut runner =
+            ScriptRunner::new(Script::new().exchange(vec![0xC0, 0x01, 0xC1], vec![0xC4]));
+        runner.set_tolerance(RequestTolerance::LENIENT);
+        runner.on_send(&[0xC0, 0x01, 0xC5]).unwrap();
+        assert!(!runner.is_finished());
+    }
+
+    #[test]
+    fn test_memory_error_display() {
+        assert_eq!(MemoryError::Timeout.to_string(), "Memory transport receive timeout");
+        let error =
+            MemoryError::UnexpectedRequest { step: 1, expected: vec![0xC0], actual: vec![0xC1] };
+        assert_eq!(
+            error.to_string(),
+            "Unexpected request at script step 1: expected [C0], got [C1]"
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! Asynchronous in-memory transports.
+//!
+//! These transports are runtime-agnostic: waiting for data uses wakers only, and
+//! timeouts and latency use a helper thread to wake the task, so they work with
+//! any executor (Tokio, Smol, Glommio, `futures::executor`, ...).
+//!
+//! See parent module [`crate::transport::memory`] for an overview.
+
+use core::future::poll_fn;
+use core::task::{Context, Poll, Waker};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::transport::r#async::AsyncTransport;
+
+use super::{Channel, FaultInjector, MemoryError, RequestTolerance, Script, ScriptRunner};
+
+/// Runtime-agnostic timer.
+///
+/// On first poll, a helper thread is spawned that wakes the most recently
+/// registered waker once the deadline has passed.
+#[derive(Debug)]
+struct Delay {
+    deadline: Instant,
+    waker: Option<Arc<Mutex<Waker>>>,
+}
+
+impl Delay {
+    fn new(duration: Duration) -> Self {
+        Self { deadline: Instant::now() + duration, waker: None }
+    }
+
+    fn poll_elapsed(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let now = Instant::now();
+        if now >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        match &self.waker {
+            Some(waker) => {
+                let mut waker = waker.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                waker.clone_from(cx.waker());
+            }
+            None => {
+                let waker = Arc::new(Mutex::new(cx.waker().clone()));
+                let remaining = self.deadline - now;
+                let thread_waker = Arc::clone(&waker);
+                std::thread::spawn(move || {
+                    std::thread::sleep(remaining);
+                    thread_waker
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .wake_by_ref();
+                });
+                self.waker = Some(waker);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// Waits for `duration` without blocking the executor.
+async fn sleep(duration: Duration) {
+    if duration.is_zero() {
+        return;
+    }
+    let mut delay = Delay::new(duration);
+    poll_fn(|cx| delay.poll_elapsed(cx)).await
+}
+
+/// One endpoint of a connected async in-memory pair.
+///
+/// Async counterpart of [`MemoryTransport`](super::MemoryTransport); both
+/// endpoints may live on different tasks or threads.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(all(feature = "transport-memory", feature = "async-client"))]
+/// # {
+/// use dlms_cosem::transport::AsyncTransport;
+/// use dlms_cosem::transport::memory::AsyncMemoryTransport;
+///
+/// # async fn example() {
+/// let (mut client, mut meter) = AsyncMemoryTransport::pair();
+/// client.send(&[0xC0, 0x01, 0xC1]).await.unwrap();
+///
+/// let mut buffer = [0u8; 16];
+/// let len = meter.recv(&mut buffer).await.unwrap();
+/// assert_eq!(&buffer[..len], &[0xC0, 0x01, 0xC1]);
+/// # }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncMemoryTransport {
+    /// Messages sent to this endpoint.
+    rx: Arc<Channel>,
+    /// Messages sent to the peer endpoint.
+    tx: Arc<Channel>,
+    /// Latency and faults applied to sent messages.
+    faults: FaultInjector,
+}
+
+impl AsyncMemoryTransport {
+    /// Creates a connected pair of endpoints.
+    pub fn pair() -> (Self, Self) {
+        let (a_to_b, b_to_a) = (Channel::new(), Channel::new());
+        let a =
+            Self { rx: Arc::clone(&b_to_a), tx: Arc::clone(&a_to_b), faults: FaultInjector::new() };
+        let b = Self { rx: a_to_b, tx: b_to_a, faults: FaultInjector::new() };
+        (a, b)
+    }
+
+    /// Sets the latency and faults applied to messages sent by this endpoint.
+    pub fn with_faults(mut self, faults: FaultInjector) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Returns the latency and faults applied to messages sent by this endpoint.
+    pub fn faults_mut(&mut self) -> &mut FaultInjector {
+        &mut self.faults
+    }
+}
+
+impl Drop for AsyncMemoryTransport {
+    fn drop(&mut self) {
+        self.tx.close();
+        self.rx.close();
+    }
+}
+
+impl AsyncTransport for AsyncMemoryTransport {
+    type Error = MemoryError;
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        sleep(self.faults.latency()).await;
+        match self.faults.apply(data) {
+            Some(message) => self.tx.push(message),
+            None => Ok(()),
+        }
+    }
+
+    async fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        poll_fn(|cx| self.rx.poll_recv(cx, buffer)).await
+    }
+
+    async fn recv_timeout(
+        &mut self,
+        buffer: &mut [u8],
+        timeout: Duration,
+    ) -> Result<usize, Self::Error> {
+        let mut delay = Delay::new(timeout);
+        poll_fn(|cx| match self.rx.poll_recv(cx, buffer) {
+            Poll::Ready(result) => Poll::Ready(result),
+            Poll::Pending => delay.poll_elapsed(cx).map(|()| Err(MemoryError::Timeout)),
+        })
+        .await
+    }
+}
+
+/// Async scripted mock meter.
+///
+/// Async counterpart of [`ScriptedTransport`](super::ScriptedTransport).
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(all(feature = "transport-memory", feature = "async-client"))]
+/// # {
+/// use dlms_cosem::transport::AsyncTransport;
+/// use dlms_cosem::transport::memory::{AsyncScriptedTransport, Script};
+///
+/// # async fn example() {
+/// let script = Script::new().exchange(vec![0xC0, 0x01], vec![0xC4, 0x01]);
+/// let mut meter = AsyncScriptedTransport::new(script);
+///
+/// meter.send(&[0xC0, 0x01]).await.unwrap();
+/// let mut buffer = [0u8; 16];
+/// assert_eq!(meter.recv(&mut buffer).await, Ok(2));
+/// assert!(meter.is_finished());
+/// # }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncScriptedTransport {
+    runner: ScriptRunner,
+}
+
+impl AsyncScriptedTransport {
+    /// Creates a scripted transport.
+    pub fn new(script: Script) -> Self {
+        Self { runner: ScriptRunner::new(script) }
+    }
+
+    /// Sets the latency and faults applied to responses.
+    ///
+    /// Responses at the start of the script have already been queued and are
+    /// not affected.
+    pub fn with_faults(mut self, faults: FaultInjector) -> Self {
+        self.runner.set_faults(faults);
+        self
+    }
+
+    /// Sets the differences tolerated when matching requests.
+    pub fn with_tolerance(mut self, tolerance: RequestTolerance) -> Self {
+        self.runner.set_tolerance(tolerance);
+        self
+    }
+
+    /// Returns the latency and faults applied to responses.
+    pub fn faults_mut(&mut self) -> &mut FaultInjector {
+        self.runner.faults_mut()
+    }
+
+    /// Returns `true` if all requests were sent and all responses received.
+    pub fn is_finished(&self) -> bool {
+        self.runner.is_finished()
+    }
+
+    /// Returns the steps that have not been executed yet.
+    pub fn remaining(&self) -> &Script {
+        self.runner.remaining()
+    }
+}
+
+impl AsyncTransport for AsyncScriptedTransport {
+    type Error = MemoryError;
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.runner.on_send(data)
+    }
+
+    async fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        sleep(self.runner.latency()).await;
+        self.runner.on_recv(buffer)
+    }
+
+    async fn recv_timeout(
+        &mut self,
+        buffer: &mut [u8],
+        _timeout: Duration,
+    ) -> Result<usize, Self::Error> {
+        self.recv(buffer).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Fault;
+    use super::*;
+    use futures_executor::block_on;
+
+    #[test]
+    fn test_pair_roundtrip() {
+        let (mut a, mut b) = AsyncMemoryTransport::pair();
+        block_on(a.send(&[1, 2, 3])).unwrap();
+
+        let mut buffer = [0u8; 8];
+        assert_eq!(block_on(b.recv(&mut buffer)), Ok(3));
+        assert_eq!(&buffer[..3], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_recv_wakes_on_send_from_other_thread() {
+        let (mut a, mut b) = AsyncMemoryTransport::pair();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            block_on(b.send(&[0xAA])).unwrap();
+            b
+        });
+
+        let mut buffer = [0u8; 8];
+        assert_eq!(block_on(a.recv(&mut buffer)), Ok(1));
+        assert_eq!(buffer[0], 0xAA);
+        drop(handle.join().unwrap());
+    }
+
+    #[test]
+    fn test_recv_timeout() {
+        let (mut a, _b) = AsyncMemoryTransport::pair();
+        let mut buffer = [0u8; 8];
+        let start = Instant::now();
+        assert_eq!(
+            block_on(a.recv_timeout(&mut buffer, Duration::from_millis(10))),
+            Err(MemoryError::Timeout)
+        );
+        assert!(start.elapsed() >= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let (a, mut b) = AsyncMemoryTransport::pair();
+        drop(a);
+        let mut buffer = [0u8; 8];
+        assert_eq!(block_on(b.recv(&mut buffer)), Err(MemoryError::Disconnected));
+    }
+
+    #[test]
+    fn test_pair_faults() {
+        let (a, mut b) = AsyncMemoryTransport::pair();
+        let mut a = a.with_faults(
+            FaultInjector::new()
+                .with_latency(Duration::from_millis(1))
+                .with_fault(0, Fault::Truncate(1)),
+        );
+
+        block_on(a.send(&[1, 2])).unwrap();
+        let mut buffer = [0u8; 8];
+        assert_eq!(block_on(b.recv(&mut buffer)), Ok(1));
+    }
+
+    #[test]
+    fn test_scripted_transport() {
+        let script = Script::new().exchange(vec![0x01], vec![0x81]);
+        let mut meter = AsyncScriptedTransport::new(script);
+        let mut buffer = [0u8; 8];
+
+        assert_eq!(
+            block_on(meter.send(&[0x02])),
+            Err(MemoryError::UnexpectedRequest {
+                step: 0,
+                expected: vec![0x01],
+                actual: vec![0x02]
+            })
+        );
+        assert_eq!(block_on(meter.recv(&mut buffer)), Err(MemoryError::Timeout));
+    }
+}
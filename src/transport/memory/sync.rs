@@ -0,0 +1,314 @@
+//! Synchronous in-memory transports.
+//!
+//! See parent module [`crate::transport::memory`] for an overview.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::transport::sync::Transport;
+
+use super::{Channel, FaultInjector, MemoryError, RequestTolerance, Script, ScriptRunner};
+
+/// One endpoint of a connected in-memory pair.
+///
+/// Messages sent on one endpoint are received on the other, in order. A
+/// receive returns at most one message; if the buffer is smaller than the
+/// message, the remainder is returned by the following receives.
+///
+/// Dropping an endpoint disconnects the pair: the peer can still receive the
+/// messages already queued, then gets [`MemoryError::Disconnected`].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "transport-memory")]
+/// # {
+/// use std::thread;
+/// use dlms_cosem::transport::Transport;
+/// use dlms_cosem::transport::memory::MemoryTransport;
+///
+/// let (mut client, mut meter) = MemoryTransport::pair();
+///
+/// // Simple echo meter on another thread
+/// let handle = thread::spawn(move || {
+///     let mut buffer = [0u8; 64];
+///     let len = meter.recv(&mut buffer).unwrap();
+///     meter.send(&buffer[..len]).unwrap();
+/// });
+///
+/// client.send(&[0xC0, 0x01, 0xC1]).unwrap();
+/// let mut buffer = [0u8; 64];
+/// assert_eq!(client.recv(&mut buffer).unwrap(), 3);
+/// handle.join().unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MemoryTransport {
+    /// Messages sent to this endpoint.
+    rx: Arc<Channel>,
+    /// Messages sent to the peer endpoint.
+    tx: Arc<Channel>,
+    /// Latency and faults applied to sent messages.
+    faults: FaultInjector,
+}
+
+impl MemoryTransport {
+    /// Creates a connected pair of endpoints.
+    pub fn pair() -> (Self, Self) {
+        let (a_to_b, b_to_a) = (Channel::new(), Channel::new());
+        let a =
+            Self { rx: Arc::clone(&b_to_a), tx: Arc::clone(&a_to_b), faults: FaultInjector::new() };
+        let b = Self { rx: a_to_b, tx: b_to_a, faults: FaultInjector::new() };
+        (a, b)
+    }
+
+    /// Sets the latency and faults applied to messages sent by this endpoint.
+    pub fn with_faults(mut self, faults: FaultInjector) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Returns the latency and faults applied to messages sent by this endpoint.
+    pub fn faults_mut(&mut self) -> &mut FaultInjector {
+        &mut self.faults
+    }
+}
+
+impl Drop for MemoryTransport {
+    fn drop(&mut self) {
+        self.tx.close();
+        self.rx.close();
+    }
+}
+
+impl Transport for MemoryTransport {
+    type Error = MemoryError;
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        let latency = self.faults.latency();
+        if !latency.is_zero() {
+            std::thread::sleep(latency);
+        }
+        match self.faults.apply(data) {
+            Some(message) => self.tx.push(message),
+            None => Ok(()),
+        }
+    }
+
+    fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        self.rx.recv_blocking(buffer, None)
+    }
+
+    fn recv_timeout(&mut self, buffer: &mut [u8], timeout: Duration) -> Result<usize, Self::Error> {
+        self.rx.recv_blocking(buffer, Some(timeout))
+    }
+}
+
+/// Scripted mock meter.
+///
+/// Follows a [`Script`]: every sent request must match the next expected
+/// request byte for byte, after which the responses that follow it in the
+/// script are queued for receiving. Receiving with no response pending
+/// returns [`MemoryError::Timeout`] instead of blocking.
+///
+/// Latency and faults (see [`with_faults`](Self::with_faults)) apply to the
+/// responses.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "transport-memory")]
+/// # {
+/// use dlms_cosem::transport::Transport;
+/// use dlms_cosem::transport::memory::{Fault, FaultInjector, MemoryError, Script, ScriptedTransport};
+///
+/// let script = Script::new()
+///     .exchange(vec![0xC0, 0x01], vec![0xC4, 0x01])
+///     .exchange(vec![0xC0, 0x01], vec![0xC4, 0x01]);
+/// // The meter does not answer the first request
+/// let mut meter = ScriptedTransport::new(script)
+///     .with_faults(FaultInjector::new().with_fault(0, Fault::Drop));
+///
+/// let mut buffer = [0u8; 16];
+/// meter.send(&[0xC0, 0x01]).unwrap();
+/// assert_eq!(meter.recv(&mut buffer), Err(MemoryError::Timeout));
+/// meter.send(&[0xC0, 0x01]).unwrap();
+/// assert_eq!(meter.recv(&mut buffer), Ok(2));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ScriptedTransport {
+    runner: ScriptRunner,
+}
+
+impl ScriptedTransport {
+    /// Creates a scripted transport.
+    pub fn new(script: Script) -> Self {
+        Self { runner: ScriptRunner::new(script) }
+    }
+
+    /// Sets the latency and faults applied to responses.
+    ///
+    /// Responses at the start of the script have already been queued and are
+    /// not affected.
+    pub fn with_faults(mut self, faults: FaultInjector) -> Self {
+        self.runner.set_faults(faults);
+        self
+    }
+
+    /// Sets the differences tolerated when matching requests.
+    pub fn with_tolerance(mut self, tolerance: RequestTolerance) -> Self {
+        self.runner.set_tolerance(tolerance);
+        self
+    }
+
+    /// Returns the latency and faults applied to responses.
+    pub fn faults_mut(&mut self) -> &mut FaultInjector {
+        self.runner.faults_mut()
+    }
+
+    /// Returns `true` if all requests were sent and all responses received.
+    pub fn is_finished(&self) -> bool {
+        self.runner.is_finished()
+    }
+
+    /// Returns the steps that have not been executed yet.
+    pub fn remaining(&self) -> &Script {
+        self.runner.remaining()
+    }
+}
+
+impl Transport for ScriptedTransport {
+    type Error = MemoryError;
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.runner.on_send(data)
+    }
+
+    fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        let latency = self.runner.latency();
+        if !latency.is_zero() {
+            std::thread::sleep(latency);
+        }
+        self.runner.on_recv(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Fault;
+    use super::*;
+    use std::thread;
+    use std::time::Instant;
+
+    #[test]
+    fn test_pair_roundtrip() {
+        let (mut a, mut b) = MemoryTransport::pair();
+        a.send(&[1, 2, 3]).unwrap();
+        b.send(&[4]).unwrap();
+
+        let mut buffer = [0u8; 8];
+        assert_eq!(b.recv(&mut buffer), Ok(3));
+        assert_eq!(&buffer[..3], &[1, 2, 3]);
+        assert_eq!(a.recv(&mut buffer), Ok(1));
+        assert_eq!(buffer[0], 4);
+    }
+
+    #[test]
+    fn test_pair_partial_reads() {
+        let (mut a, mut b) = MemoryTransport::pair();
+        a.send(&[1, 2, 3]).unwrap();
+
+        let mut buffer = [0u8; 2];
+        assert_eq!(b.recv(&mut buffer), Ok(2));
+        assert_eq!(b.recv(&mut buffer), Ok(1));
+        assert_eq!(buffer[0], 3);
+    }
+
+    #[test]
+    fn test_pair_across_threads() {
+        let (mut a, mut b) = MemoryTransport::pair();
+        let handle = thread::spawn(move || {
+            let mut buffer = [0u8; 8];
+            let len = b.recv(&mut buffer).unwrap();
+            b.send(&buffer[..len]).unwrap();
+        });
+
+        a.send(&[0xAA, 0xBB]).unwrap();
+        let mut buffer = [0u8; 8];
+        assert_eq!(a.recv(&mut buffer), Ok(2));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_recv_timeout() {
+        let (mut a, _b) = MemoryTransport::pair();
+        let mut buffer = [0u8; 8];
+        assert_eq!(
+            a.recv_timeout(&mut buffer, Duration::from_millis(10)),
+            Err(MemoryError::Timeout)
+        );
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let (mut a, b) = MemoryTransport::pair();
+        a.send(&[1]).unwrap();
+        drop(a);
+
+        let mut b = b;
+        let mut buffer = [0u8; 8];
+        assert_eq!(b.recv(&mut buffer), Ok(1));
+        assert_eq!(b.recv(&mut buffer), Err(MemoryError::Disconnected));
+        assert_eq!(b.send(&[1]), Err(MemoryError::Disconnected));
+    }
+
+    #[test]
+    fn test_pair_faults_and_latency() {
+        let (a, mut b) = MemoryTransport::pair();
+        let mut a = a.with_faults(
+            FaultInjector::new()
+                .with_latency(Duration::from_millis(5))
+                .with_fault(0, Fault::Drop)
+                .with_fault(1, Fault::Truncate(1)),
+        );
+
+        let start = Instant::now();
+        a.send(&[1, 2]).unwrap();
+        a.send(&[3, 4]).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(10));
+
+        let mut buffer = [0u8; 8];
+        assert_eq!(b.recv(&mut buffer), Ok(1));
+        assert_eq!(buffer[0], 3);
+    }
+
+    #[test]
+    fn test_scripted_transport() {
+        let script = Script::new().exchange(vec![0x01], vec![0x81, 0x82]).expect(vec![0x02]);
+        let mut meter = ScriptedTransport::new(script);
+        let mut buffer = [0u8; 8];
+
+        assert_eq!(meter.recv(&mut buffer), Err(MemoryError::Timeout));
+        meter.send(&[0x01]).unwrap();
+        assert_eq!(meter.recv(&mut buffer), Ok(2));
+        assert!(!meter.is_finished());
+        assert_eq!(meter.remaining().len(), 1);
+
+        meter.send(&[0x02]).unwrap();
+        assert!(meter.is_finished());
+    }
+
+    #[test]
+    fn test_scripted_transport_corrupts_response() {
+        let script = Script::new().exchange(vec![0x01], vec![0x81, 0x82]);
+        let mut meter = ScriptedTransport::new(script).with_faults(
+            FaultInjector::new().with_fault(0, Fault::Corrupt { offset: 1, mask: 0x01 }),
+        );
+
+        meter.send(&[0x01]).unwrap();
+        let mut buffer = [0u8; 8];
+        assert_eq!(meter.recv(&mut buffer), Ok(2));
+        assert_eq!(&buffer[..2], &[0x81, 0x83]);
+    }
+}
@@ -0,0 +1,393 @@
+//! Record-and-replay transports for capturing real meter sessions.
+//!
+//! - [`RecordingTransport`] / [`AsyncRecordingTransport`] - Wrap a transport and
+//!   log every sent and received chunk with a timestamp.
+//! - [`ReplayTransport`] / [`AsyncReplayTransport`] - Play a [`Recording`] back:
+//!   verify that the client sends the recorded requests (within a
+//!   [`RequestTolerance`]) and return the recorded responses.
+//!
+//! This makes it possible to capture the exchange with a misbehaving field
+//! meter once and turn it into a regression test.
+//!
+//! # File Format
+//!
+//! Recordings are stored as JSON lines, one object per chunk:
+//!
+//! ```text
+//! {"timestamp_us":0,"direction":"send","data":"c001c100080000010000ff0200"}
+//! {"timestamp_us":18342,"direction":"recv","data":"c401c1000600000064"}
+//! ```
+//!
+//! - `timestamp_us` - Microseconds since the start of the recording
+//! - `direction` - `"send"` (client to meter) or `"recv"` (meter to client)
+//! - `data` - Lowercase hex-encoded bytes
+//!
+//! Record above any framing (wrap the HDLC transport rather than the TCP
+//! transport below it) so that the recording contains APDUs; request
+//! tolerances are applied at the APDU level.
+//!
+//! # Feature
+//!
+//! Requires the `transport-record` feature. The async transports additionally
+//! require `async-client`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # #[cfg(all(feature = "transport-record", feature = "transport-tcp"))]
+//! # {
+//! use dlms_cosem::transport::memory::RequestTolerance;
+//! use dlms_cosem::transport::record::{RecordingTransport, ReplayTransport};
+//! use dlms_cosem::transport::tcp::TcpTransport;
+//!
+//! # fn example() -> std::io::Result<()> {
+//! // Capture a session with a real meter
+//! let tcp = TcpTransport::connect("192.168.1.100:4059")?;
+//! let transport = RecordingTransport::create(tcp, "meter-session.jsonl")?;
+//! // ... use with DlmsClient ...
+//!
+//! // Later, in a regression test
+//! let transport = ReplayTransport::load("meter-session.jsonl")?
+//!     .with_tolerance(RequestTolerance::LENIENT);
+//! // ... use with DlmsClient ...
+//! # Ok(())
+//! # }
+//! # }
+//! ```
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use super::memory::Script;
+
+pub mod sync;
+
+pub use sync::{RecordingTransport, ReplayTransport};
+
+#[cfg(feature = "async-client")]
+pub mod r#async;
+
+#[cfg(feature = "async-client")]
+pub use r#async::{AsyncRecordingTransport, AsyncReplayTransport};
+
+pub use super::memory::RequestTolerance;
+
+/// Direction of a recorded chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Sent by the client.
+    Send,
+    /// Received by the client.
+    Recv,
+}
+
+impl Direction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Send => "send",
+            Self::Recv => "recv",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "send" => Some(Self::Send),
+            "recv" => Some(Self::Recv),
+            _ => None,
+        }
+    }
+}
+
+/// One recorded chunk of data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedFrame {
+    /// Microseconds since the start of the recording.
+    pub timestamp_us: u64,
+    /// Direction of the chunk.
+    pub direction: Direction,
+    /// The bytes sent or received.
+    pub data: Vec<u8>,
+}
+
+impl RecordedFrame {
+    /// Formats the frame as one JSON line (without line terminator).
+    pub fn to_json(&self) -> String {
+        let mut line = String::with_capacity(64 + self.data.len() * 2);
+        let _ = write!(
+            line,
+            "{{\"timestamp_us\":{},\"direction\":\"{}\",\"data\":\"",
+            self.timestamp_us,
+            self.direction.as_str()
+        );
+        for byte in &self.data {
+            let _ = write!(line, "{byte:02x}");
+        }
+        line.push_str("\"}");
+        line
+    }
+
+    /// Parses a frame from one JSON line.
+    ///
+    /// Accepts a flat JSON object with the keys `timestamp_us`, `direction`
+    /// and `data` in any order; unknown keys are rejected.
+    pub fn from_json(line: &str) -> io::Result<Self> {
+        let invalid =
+            |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_owned());
+
+        let body = line
+            .trim()
+            .strip_prefix('{')
+            .and_then(|body| body.strip_suffix('}'))
+            .ok_or_else(|| invalid("recording line is not a JSON object"))?;
+
+        let (mut timestamp_us, mut direction, mut data) = (None, None, None);
+        for member in body.split(',') {
+            let (key, value) =
+                member.split_once(':').ok_or_else(|| invalid("malformed JSON member"))?;
+            let value = value.trim();
+            match json_string(key.trim()).ok_or_else(|| invalid("malformed JSON key"))? {
+                "timestamp_us" => {
+                    timestamp_us =
+                        Some(value.parse().map_err(|_| invalid("invalid timestamp_us"))?);
+                }
+                "direction" => {
+                    direction = Some(
+                        json_string(value)
+                            .and_then(Direction::from_str)
+                            .ok_or_else(|| invalid("invalid direction"))?,
+                    );
+                }
+                "data" => {
+                    data = Some(
+                        json_string(value)
+                            .and_then(decode_hex)
+                            .ok_or_else(|| invalid("invalid hex data"))?,
+                    );
+                }
+                _ => return Err(invalid("unknown key in recording line")),
+            }
+        }
+
+        Ok(Self {
+            timestamp_us: timestamp_us.ok_or_else(|| invalid("missing timestamp_us"))?,
+            direction: direction.ok_or_else(|| invalid("missing direction"))?,
+            data: data.ok_or_else(|| invalid("missing data"))?,
+        })
+    }
+}
+
+/// Returns the contents of a JSON string literal without escapes.
+fn json_string(s: &str) -> Option<&str> {
+    let inner = s.strip_prefix('"')?.strip_suffix('"')?;
+    (!inner.contains(['"', '\\'])).then_some(inner)
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
+
+/// A recorded session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    /// Recorded chunks in order.
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl Recording {
+    /// Creates an empty recording.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a recording in JSON lines format. Blank lines are ignored.
+    pub fn read_from<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut frames = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            frames.push(RecordedFrame::from_json(&line)?);
+        }
+        Ok(Self { frames })
+    }
+
+    /// Loads a recording from a JSON lines file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the recording in JSON lines format.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        for frame in &self.frames {
+            write_frame(&mut writer, frame)?;
+        }
+        writer.flush()
+    }
+
+    /// Saves the recording to a JSON lines file.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Converts the recording into a [`Script`]: sent chunks become expected
+    /// requests, received chunks become responses.
+    pub fn to_script(&self) -> Script {
+        self.frames.iter().fold(Script::new(), |script, frame| match frame.direction {
+            Direction::Send => script.expect(frame.data.clone()),
+            Direction::Recv => script.respond(frame.data.clone()),
+        })
+    }
+}
+
+/// Errors returned by recording transports.
+#[derive(Debug)]
+pub enum RecordError<E> {
+    /// The wrapped transport failed.
+    Transport(E),
+    /// Writing the recording failed.
+    Io(io::Error),
+}
+
+impl<E: core::fmt::Debug> core::fmt::Display for RecordError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Transport(e) => write!(f, "Transport error: {e:?}"),
+            Self::Io(e) => write!(f, "Recording I/O error: {e}"),
+        }
+    }
+}
+
+impl<E: core::fmt::Debug> std::error::Error for RecordError<E> {}
+
+/// Writes recorded chunks with timestamps relative to its creation.
+#[derive(Debug)]
+pub(crate) struct Recorder<W> {
+    writer: W,
+    start: Instant,
+}
+
+impl<W: Write> Recorder<W> {
+    pub(crate) fn new(writer: W) -> Self {
+        Self { writer, start: Instant::now() }
+    }
+
+    /// Appends a chunk and flushes, so the recording survives a crash.
+    pub(crate) fn record(&mut self, direction: Direction, data: &[u8]) -> io::Result<()> {
+        let frame = RecordedFrame {
+            timestamp_us: self.start.elapsed().as_micros() as u64,
+            direction,
+            data: data.to_vec(),
+        };
+        write_frame(&mut self.writer, &frame)?;
+        self.writer.flush()
+    }
+}
+
+impl<W> Recorder<W> {
+    pub(crate) fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    pub(crate) fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+fn write_frame<W: Write>(writer: &mut W, frame: &RecordedFrame) -> io::Result<()> {
+    writeln!(writer, "{}", frame.to_json())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Recording {
+        Recording {
+            frames: vec![
+                RecordedFrame {
+                    timestamp_us: 0,
+                    direction: Direction::Send,
+                    data: vec![0xC0, 0x01],
+                },
+                RecordedFrame {
+                    timestamp_us: 1500,
+                    direction: Direction::Recv,
+                    data: vec![0xC4, 0x01, 0xAB],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_recording_format() {
+        let mut output = Vec::new();
+        sample().write_to(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"timestamp_us\":0,\"direction\":\"send\",\"data\":\"c001\"}\n\
+             {\"timestamp_us\":1500,\"direction\":\"recv\",\"data\":\"c401ab\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_recording_roundtrip() {
+        let mut output = Vec::new();
+        sample().write_to(&mut output).unwrap();
+        output.extend_from_slice(b"\n");
+        assert_eq!(Recording::read_from(&output[..]).unwrap(), sample());
+    }
+
+    #[test]
+    fn test_recording_invalid_hex() {
+        let line = b"{\"timestamp_us\":0,\"direction\":\"send\",\"data\":\"c0z1\"}";
+        let error = Recording::read_from(&line[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let line = b"{\"timestamp_us\":0,\"direction\":\"send\",\"data\":\"c00\"}";
+        assert!(Recording::read_from(&line[..]).is_err());
+
+        let line = b"{\"timestamp_us\":0,\"data\":\"c0\"}";
+        assert!(Recording::read_from(&line[..]).is_err());
+    }
+
+    #[test]
+    fn test_recorded_frame_from_json_any_order() {
+        let frame = RecordedFrame::from_json(
+            " { \"data\": \"C0FF\", \"direction\": \"recv\", \"timestamp_us\": 42 } ",
+        )
+        .unwrap();
+        assert_eq!(
+            frame,
+            RecordedFrame { timestamp_us: 42, direction: Direction::Recv, data: vec![0xC0, 0xFF] }
+        );
+    }
+
+    #[test]
+    fn test_recording_to_script() {
+        let script = sample().to_script();
+        assert_eq!(script, Script::new().exchange(vec![0xC0, 0x01], vec![0xC4, 0x01, 0xAB]));
+    }
+
+    #[test]
+    fn test_recorder_timestamps() {
+        let mut recorder = Recorder::new(Vec::new());
+        recorder.record(Direction::Send, &[1]).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        recorder.record(Direction::Recv, &[2]).unwrap();
+
+        let recording = Recording::read_from(&recorder.into_writer()[..]).unwrap();
+        assert_eq!(recording.frames.len(), 2);
+        assert!(recording.frames[1].timestamp_us >= 2000);
+        assert_eq!(recording.frames[1].direction, Direction::Recv);
+    }
+}
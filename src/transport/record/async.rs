@@ -0,0 +1,164 @@
+//! Asynchronous record-and-replay transports.
+//!
+//! See parent module [`crate::transport::record`] for the file format.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::transport::r#async::{AsyncTransport, MaybeSend};
+use crate::transport::memory::{AsyncScriptedTransport, MemoryError, RequestTolerance};
+
+use super::{Direction, RecordError, Recorder, Recording};
+
+/// Async transport wrapper that records every sent and received chunk.
+///
+/// Async counterpart of [`RecordingTransport`](super::RecordingTransport).
+/// Recording writes are synchronous; they are small and flushed per chunk.
+#[derive(Debug)]
+pub struct AsyncRecordingTransport<T, W = BufWriter<File>> {
+    inner: T,
+    recorder: Recorder<W>,
+}
+
+impl<T> AsyncRecordingTransport<T> {
+    /// Wraps `inner`, recording to a newly created file at `path`.
+    pub fn create(inner: T, path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(inner, BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<T, W: Write> AsyncRecordingTransport<T, W> {
+    /// Wraps `inner`, recording to `writer`.
+    pub fn new(inner: T, writer: W) -> Self {
+        Self { inner, recorder: Recorder::new(writer) }
+    }
+}
+
+impl<T, W> AsyncRecordingTransport<T, W> {
+    /// Returns a reference to the wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped transport.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Returns a reference to the recording destination.
+    pub fn writer(&self) -> &W {
+        self.recorder.writer()
+    }
+
+    /// Consumes the wrapper and returns the transport and recording destination.
+    pub fn into_parts(self) -> (T, W) {
+        (self.inner, self.recorder.into_writer())
+    }
+}
+
+impl<T: AsyncTransport, W: Write + core::fmt::Debug + MaybeSend> AsyncTransport
+    for AsyncRecordingTransport<T, W>
+{
+    type Error = RecordError<T::Error>;
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.inner.send(data).await.map_err(RecordError::Transport)?;
+        self.recorder.record(Direction::Send, data).map_err(RecordError::Io)
+    }
+
+    async fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        let n = self.inner.recv(buffer).await.map_err(RecordError::Transport)?;
+        self.recorder.record(Direction::Recv, &buffer[..n]).map_err(RecordError::Io)?;
+        Ok(n)
+    }
+
+    async fn recv_timeout(
+        &mut self,
+        buffer: &mut [u8],
+        timeout: Duration,
+    ) -> Result<usize, Self::Error> {
+        let n = self.inner.recv_timeout(buffer, timeout).await.map_err(RecordError::Transport)?;
+        self.recorder.record(Direction::Recv, &buffer[..n]).map_err(RecordError::Io)?;
+        Ok(n)
+    }
+}
+
+/// Async transport that replays a [`Recording`].
+///
+/// Async counterpart of [`ReplayTransport`](super::ReplayTransport).
+#[derive(Debug)]
+pub struct AsyncReplayTransport {
+    inner: AsyncScriptedTransport,
+}
+
+impl AsyncReplayTransport {
+    /// Creates a replay transport with strict request matching.
+    pub fn new(recording: &Recording) -> Self {
+        Self { inner: AsyncScriptedTransport::new(recording.to_script()) }
+    }
+
+    /// Loads a recording from a JSON lines file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(&Recording::load(path)?))
+    }
+
+    /// Sets the differences tolerated when matching requests.
+    pub fn with_tolerance(mut self, tolerance: RequestTolerance) -> Self {
+        self.inner = self.inner.with_tolerance(tolerance);
+        self
+    }
+
+    /// Returns `true` if all recorded requests were sent and all responses received.
+    pub fn is_finished(&self) -> bool {
+        self.inner.is_finished()
+    }
+}
+
+impl AsyncTransport for AsyncReplayTransport {
+    type Error = MemoryError;
+
+    async fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.inner.send(data).await
+    }
+
+    async fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        self.inner.recv(buffer).await
+    }
+
+    async fn recv_timeout(
+        &mut self,
+        buffer: &mut [u8],
+        timeout: Duration,
+    ) -> Result<usize, Self::Error> {
+        self.inner.recv_timeout(buffer, timeout).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::memory::Script;
+    use futures_executor::block_on;
+
+    #[test]
+    fn test_record_then_replay() {
+        let meter =
+            AsyncScriptedTransport::new(Script::new().exchange(vec![0xC0, 0x01, 0xC1], vec![0xC4]));
+        let mut recording = AsyncRecordingTransport::new(meter, Vec::new());
+
+        let mut buffer = [0u8; 16];
+        block_on(recording.send(&[0xC0, 0x01, 0xC1])).unwrap();
+        assert_eq!(block_on(recording.recv(&mut buffer)).unwrap(), 1);
+
+        let (_, log) = recording.into_parts();
+        let recording = Recording::read_from(&log[..]).unwrap();
+
+        let mut replay =
+            AsyncReplayTransport::new(&recording).with_tolerance(RequestTolerance::LENIENT);
+        block_on(replay.send(&[0xC0, 0x01, 0xC3])).unwrap();
+        assert_eq!(block_on(replay.recv(&mut buffer)), Ok(1));
+        assert!(replay.is_finished());
+    }
+}
@@ -0,0 +1,221 @@
+//! Synchronous record-and-replay transports.
+//!
+//! See parent module [`crate::transport::record`] for the file format.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::transport::memory::{MemoryError, RequestTolerance, ScriptedTransport};
+use crate::transport::sync::Transport;
+
+use super::{Direction, RecordError, Recorder, Recording};
+
+/// Transport wrapper that records every sent and received chunk.
+///
+/// Each chunk is written as one JSON line and flushed immediately, so the
+/// recording is complete even if the session ends abnormally.
+///
+/// # Type Parameters
+///
+/// * `T` - The wrapped transport
+/// * `W` - Destination of the recording (default: buffered file)
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "transport-record")]
+/// # {
+/// use dlms_cosem::transport::Transport;
+/// use dlms_cosem::transport::memory::MemoryTransport;
+/// use dlms_cosem::transport::record::{Recording, RecordingTransport};
+///
+/// let (client, mut meter) = MemoryTransport::pair();
+/// let mut client = RecordingTransport::new(client, Vec::new());
+///
+/// client.send(&[0xC0, 0x01]).unwrap();
+/// meter.send(&[0xC4, 0x01]).unwrap();
+/// let mut buffer = [0u8; 16];
+/// client.recv(&mut buffer).unwrap();
+///
+/// let (_, log) = client.into_parts();
+/// let recording = Recording::read_from(&log[..]).unwrap();
+/// assert_eq!(recording.frames.len(), 2);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RecordingTransport<T, W = BufWriter<File>> {
+    inner: T,
+    recorder: Recorder<W>,
+}
+
+impl<T> RecordingTransport<T> {
+    /// Wraps `inner`, recording to a newly created file at `path`.
+    pub fn create(inner: T, path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(inner, BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<T, W: Write> RecordingTransport<T, W> {
+    /// Wraps `inner`, recording to `writer`.
+    pub fn new(inner: T, writer: W) -> Self {
+        Self { inner, recorder: Recorder::new(writer) }
+    }
+}
+
+impl<T, W> RecordingTransport<T, W> {
+    /// Returns a reference to the wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped transport.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Returns a reference to the recording destination.
+    pub fn writer(&self) -> &W {
+        self.recorder.writer()
+    }
+
+    /// Consumes the wrapper and returns the transport and recording destination.
+    pub fn into_parts(self) -> (T, W) {
+        (self.inner, self.recorder.into_writer())
+    }
+}
+
+impl<T: Transport, W: Write + core::fmt::Debug> Transport for RecordingTransport<T, W> {
+    type Error = RecordError<T::Error>;
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.inner.send(data).map_err(RecordError::Transport)?;
+        self.recorder.record(Direction::Send, data).map_err(RecordError::Io)
+    }
+
+    fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        let n = self.inner.recv(buffer).map_err(RecordError::Transport)?;
+        self.recorder.record(Direction::Recv, &buffer[..n]).map_err(RecordError::Io)?;
+        Ok(n)
+    }
+
+    fn recv_timeout(&mut self, buffer: &mut [u8], timeout: Duration) -> Result<usize, Self::Error> {
+        let n = self.inner.recv_timeout(buffer, timeout).map_err(RecordError::Transport)?;
+        self.recorder.record(Direction::Recv, &buffer[..n]).map_err(RecordError::Io)?;
+        Ok(n)
+    }
+}
+
+/// Transport that replays a [`Recording`].
+///
+/// Sent data must match the recorded requests (within the configured
+/// [`RequestTolerance`]); received data is taken from the recording. Errors
+/// are reported as [`MemoryError`], like [`ScriptedTransport`].
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "transport-record")]
+/// # {
+/// use dlms_cosem::transport::Transport;
+/// use dlms_cosem::transport::record::{Recording, ReplayTransport, RequestTolerance};
+///
+/// let log = b"{\"timestamp_us\":0,\"direction\":\"send\",\"data\":\"c001c1\"}\n\
+///             {\"timestamp_us\":9000,\"direction\":\"recv\",\"data\":\"c401c100\"}\n";
+/// let recording = Recording::read_from(&log[..]).unwrap();
+///
+/// // The client now uses a different invoke id
+/// let mut meter = ReplayTransport::new(&recording).with_tolerance(RequestTolerance::LENIENT);
+/// meter.send(&[0xC0, 0x01, 0xC2]).unwrap();
+/// let mut buffer = [0u8; 16];
+/// assert_eq!(meter.recv(&mut buffer), Ok(4));
+/// assert!(meter.is_finished());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ReplayTransport {
+    inner: ScriptedTransport,
+}
+
+impl ReplayTransport {
+    /// Creates a replay transport with strict request matching.
+    pub fn new(recording: &Recording) -> Self {
+        Self { inner: ScriptedTransport::new(recording.to_script()) }
+    }
+
+    /// Loads a recording from a JSON lines file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(&Recording::load(path)?))
+    }
+
+    /// Sets the differences tolerated when matching requests.
+    pub fn with_tolerance(mut self, tolerance: RequestTolerance) -> Self {
+        self.inner = self.inner.with_tolerance(tolerance);
+        self
+    }
+
+    /// Returns `true` if all recorded requests were sent and all responses received.
+    pub fn is_finished(&self) -> bool {
+        self.inner.is_finished()
+    }
+}
+
+impl Transport for ReplayTransport {
+    type Error = MemoryError;
+
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        self.inner.send(data)
+    }
+
+    fn recv(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        self.inner.recv(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::memory::{MemoryTransport, Script};
+
+    #[test]
+    fn test_record_then_replay() {
+        let meter = ScriptedTransport::new(
+            Script::new().exchange(vec![0xC0, 0x01, 0xC1], vec![0xC4, 0x01, 0xC1, 0x00]),
+        );
+        let mut recording = RecordingTransport::new(meter, Vec::new());
+
+        let mut buffer = [0u8; 16];
+        recording.send(&[0xC0, 0x01, 0xC1]).unwrap();
+        assert_eq!(recording.recv(&mut buffer).unwrap(), 4);
+
+        let (meter, log) = recording.into_parts();
+        assert!(meter.is_finished());
+        let recording = Recording::read_from(&log[..]).unwrap();
+
+        let mut replay = ReplayTransport::new(&recording);
+        assert!(matches!(
+            replay.send(&[0xC0, 0x01, 0xC2]),
+            Err(MemoryError::UnexpectedRequest { step: 0, .. })
+        ));
+
+        let mut replay = ReplayTransport::new(&recording).with_tolerance(RequestTolerance::LENIENT);
+        replay.send(&[0xC0, 0x01, 0xC2]).unwrap();
+        assert_eq!(replay.recv(&mut buffer), Ok(4));
+        assert_eq!(&buffer[..4], &[0xC4, 0x01, 0xC1, 0x00]);
+        assert!(replay.is_finished());
+    }
+
+    #[test]
+    fn test_recording_transport_errors_not_recorded() {
+        let (client, _meter) = MemoryTransport::pair();
+        let mut client = RecordingTransport::new(client, Vec::new());
+
+        let mut buffer = [0u8; 16];
+        assert!(matches!(
+            client.recv_timeout(&mut buffer, Duration::from_millis(1)),
+            Err(RecordError::Transport(MemoryError::Timeout))
+        ));
+        assert!(client.writer().is_empty());
+    }
+}